@@ -0,0 +1,461 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Context, Result, bail};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use topo_core::{Chunk, ChunkKind, FileInfo, FileRole};
+use topo_render::{Redacted, Withheld};
+use topo_score::ImportGraph;
+use topo_treesit::{Chunker, CompositeChunker};
+
+/// Number of recent commits reported for the file.
+const RECENT_COMMITS: usize = 5;
+
+/// A commit touching the location.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitInfo {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub subject: String,
+}
+
+/// Parse a `path:line` location. The path may be absolute under `root`.
+pub fn parse_location(location: &str, root: &Path) -> Result<(String, u32)> {
+    let Some((path, line)) = location.rsplit_once(':') else {
+        bail!(CliError::usage(format!(
+            "expected <path:line>, got {location:?}"
+        )));
+    };
+    let line: u32 = line
+        .parse()
+        .with_context(|| format!("invalid line number in {location:?}"))?;
+    if line == 0 {
+        bail!(CliError::usage("line numbers start at 1"));
+    }
+
+    let path = Path::new(path);
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let relative = relative.strip_prefix("./").unwrap_or(relative);
+    Ok((relative.to_string_lossy().replace('\\', "/"), line))
+}
+
+/// Reject a line past the end of `path`, whose `total` lines are known only
+/// once the file is read.
+pub fn check_line(path: &str, line: u32, total: u32) -> Result<()> {
+    if line > total.max(1) {
+        bail!(CliError::usage(format!(
+            "line {line} is past the end of {path} ({total} lines)"
+        )));
+    }
+    Ok(())
+}
+
+/// The innermost non-import chunk containing `line`, falling back to the
+/// nearest chunk that starts above it (single-line regex chunks).
+pub fn enclosing_chunk(chunks: &[Chunk], line: u32) -> Option<&Chunk> {
+    let candidates = chunks.iter().filter(|c| c.kind != ChunkKind::Import);
+
+    let containing = candidates
+        .clone()
+        .filter(|c| c.start_line <= line && line <= c.end_line)
+        .min_by_key(|c| c.end_line - c.start_line);
+    if containing.is_some() {
+        return containing;
+    }
+
+    candidates
+        .filter(|c| c.start_line <= line)
+        .max_by_key(|c| c.start_line)
+}
+
+/// Test files related to `path`: tests that import it, plus tests whose
+/// file name contains its stem.
+pub fn related_tests(path: &str, files: &[FileInfo], graph: &ImportGraph) -> Vec<String> {
+    let stem = Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let mut tests: Vec<String> = graph
+        .importers_of(path)
+        .into_iter()
+        .filter(|p| FileRole::from_path(Path::new(p)) == FileRole::Test)
+        .map(str::to_string)
+        .collect();
+
+    // Generic stems like "mod" or "index" would match half the test suite
+    if stem.len() > 2 && !matches!(stem.as_str(), "mod" | "lib" | "index" | "main" | "init") {
+        for f in files.iter().filter(|f| f.role == FileRole::Test) {
+            let name = Path::new(&f.path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+            if name.contains(&stem) && !tests.contains(&f.path) && f.path != path {
+                tests.push(f.path.clone());
+            }
+        }
+    }
+
+    tests
+}
+
+/// Recent commits touching `path`, newest first.
+fn recent_commits(root: &Path, path: &str, limit: usize) -> Vec<CommitInfo> {
+    let output = Command::new("git")
+        .args([
+            "log",
+            &format!("-n{limit}"),
+            "--date=short",
+            "--format=%h%x1f%an%x1f%ad%x1f%s",
+            "--",
+            path,
+        ])
+        .current_dir(root)
+        .output();
+
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_commit_line)
+        .collect()
+}
+
+fn parse_commit_line(line: &str) -> Option<CommitInfo> {
+    let mut parts = line.split('\u{1f}');
+    Some(CommitInfo {
+        hash: parts.next()?.to_string(),
+        author: parts.next()?.to_string(),
+        date: parts.next()?.to_string(),
+        subject: parts.next().unwrap_or("").to_string(),
+    })
+}
+
+/// The commit that last changed `line`, from `git blame`.
+fn blame_line(root: &Path, path: &str, line: u32) -> Option<CommitInfo> {
+    let output = Command::new("git")
+        .args([
+            "blame",
+            "--porcelain",
+            "-L",
+            &format!("{line},{line}"),
+            "--",
+            path,
+        ])
+        .current_dir(root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let hash = lines.next()?.split_whitespace().next()?;
+    let mut info = CommitInfo {
+        hash: hash.chars().take(7).collect(),
+        author: String::new(),
+        date: String::new(),
+        subject: String::new(),
+    };
+    for l in lines {
+        if let Some(v) = l.strip_prefix("author ") {
+            info.author = v.to_string();
+        } else if let Some(v) = l.strip_prefix("summary ") {
+            info.subject = v.to_string();
+        }
+    }
+    Some(info)
+}
+
+pub fn run(cli: &Cli, location: &str) -> Result<()> {
+    let root = cli.repo_root()?;
+    let (path, line) = parse_location(location, &root)?;
+    let path = cli.repo_relative(&root, &path);
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let Some(info) = bundle.files.iter().find(|f| f.path == path) else {
+        bail!(CliError::untracked(&path, &root));
+    };
+    if super::query::is_license_excluded(info, &cli.exclude_license) {
+        bail!(
+            CliError::not_found(format!(
+                "{path} is licensed {}, which --exclude-license leaves out",
+                info.license.as_deref().unwrap_or_default()
+            ))
+            .hint("Drop the matching --exclude-license to inspect it.")
+        );
+    }
+
+    let content = fs::read_to_string(root.join(&path))?;
+    check_line(&path, line, content.lines().count() as u32)?;
+    let chunks = CompositeChunker.chunk(&content, info.language);
+    let chunk = enclosing_chunk(&chunks, line);
+    let policy = super::query::content_policy(&root)?;
+    let snippet: Option<Result<Redacted, Withheld>> = chunk.map(|c| {
+        let text = content
+            .lines()
+            .skip(c.start_line.saturating_sub(1) as usize)
+            .take((c.end_line + 1 - c.start_line) as usize)
+            .collect::<Vec<_>>()
+            .join("\n");
+        policy.embed(&path, info.size, &text)
+    });
+    let embedded = snippet.as_ref().and_then(|s| s.as_ref().ok());
+    let withheld = snippet.as_ref().and_then(|s| s.as_ref().err());
+
+    // The graph covers every file, so its cache stays valid; files under an
+    // excluded license are only left out of what is listed
+    let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+    let excluded: HashSet<&str> = bundle
+        .files
+        .iter()
+        .filter(|f| super::query::is_license_excluded(f, &cli.exclude_license))
+        .map(|f| f.path.as_str())
+        .collect();
+    let importers: Vec<&str> = graph
+        .importers_of(&path)
+        .into_iter()
+        .filter(|p| !excluded.contains(p))
+        .collect();
+    let imports: Vec<&String> = graph
+        .imports_of(&path)
+        .iter()
+        .filter(|p| !excluded.contains(p.as_str()))
+        .collect();
+    let tests = related_tests(&path, &bundle.files, &graph)
+        .into_iter()
+        .filter(|p| !excluded.contains(p.as_str()))
+        .collect::<Vec<_>>();
+    let commits = recent_commits(&root, &path, RECENT_COMMITS);
+    let blame = blame_line(&root, &path, line);
+
+    let commit_json = |c: &CommitInfo| {
+        serde_json::json!({
+            "hash": c.hash,
+            "author": c.author,
+            "date": c.date,
+            "subject": c.subject,
+        })
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "line": line,
+                "language": info.language.as_str(),
+                "chunk": chunk.map(|c| serde_json::json!({
+                    "kind": format!("{:?}", c.kind).to_lowercase(),
+                    "name": c.name,
+                    "start_line": c.start_line,
+                    "end_line": c.end_line,
+                    "content": embedded.map(|s| &s.text),
+                    "withheld": withheld.map(ToString::to_string),
+                    "redactions": embedded.map(|s| s.redactions.iter().map(|r| serde_json::json!({
+                        "kind": r.kind,
+                        "line": c.start_line as usize + r.line - 1,
+                    })).collect::<Vec<_>>()),
+                })),
+                "importers": importers,
+                "imports": imports,
+                "tests": tests,
+                "blame": blame.as_ref().map(commit_json),
+                "recent_commits": commits.iter().map(commit_json).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            match chunk {
+                Some(c) => println!(
+                    "{path}:{line} in {} (L{}-{})",
+                    c.name, c.start_line, c.end_line
+                ),
+                None => println!("{path}:{line}"),
+            }
+            for p in &importers {
+                println!("importer {p}");
+            }
+            for t in &tests {
+                println!("test {t}");
+            }
+            for c in &commits {
+                println!("commit {} {}", c.hash, c.subject);
+            }
+        }
+        _ => {
+            println!("Context for {path}:{line}");
+            println!();
+            match (chunk, &snippet) {
+                (Some(c), Some(Err(reason))) => println!(
+                    "Enclosing {:?} `{}` (lines {}-{}): content withheld, {reason}",
+                    c.kind, c.name, c.start_line, c.end_line
+                ),
+                (Some(c), Some(Ok(text))) => {
+                    println!(
+                        "Enclosing {:?} `{}` (lines {}-{}):",
+                        c.kind, c.name, c.start_line, c.end_line
+                    );
+                    for (i, l) in text.text.lines().enumerate() {
+                        let n = c.start_line as usize + i;
+                        let marker = if n == line as usize { ">" } else { " " };
+                        println!("{marker}{n:>5} | {l}");
+                    }
+                    if let Some(summary) = text.summary() {
+                        println!("        ({summary})");
+                    }
+                }
+                _ => println!("No enclosing chunk found."),
+            }
+
+            print_list("Imported by", importers.iter().copied());
+            print_list("Imports", imports.iter().map(|p| p.as_str()));
+            print_list("Related tests", tests.iter().map(String::as_str));
+
+            if let Some(b) = &blame {
+                println!(
+                    "\nLast changed in {} by {}: {}",
+                    b.hash, b.author, b.subject
+                );
+            }
+            if !commits.is_empty() {
+                println!("\nRecent commits:");
+                for c in &commits {
+                    println!("  {} {} {:<16} {}", c.hash, c.date, c.author, c.subject);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_list<'a>(title: &str, items: impl Iterator<Item = &'a str>) {
+    let items: Vec<&str> = items.collect();
+    if items.is_empty() {
+        return;
+    }
+    println!("\n{title} ({}):", items.len());
+    for item in items {
+        println!("  {item}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::Language;
+
+    fn chunk(kind: ChunkKind, name: &str, start: u32, end: u32) -> Chunk {
+        Chunk {
+            kind,
+            name: name.to_string(),
+            start_line: start,
+            end_line: end,
+            content: String::new(),
+        }
+    }
+
+    fn file(path: &str) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size: 100,
+            language: Language::Rust,
+            role: FileRole::from_path(Path::new(path)),
+            sha256: [0u8; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        }
+    }
+
+    #[test]
+    fn parse_location_relative_and_absolute() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            parse_location("src/auth.rs:42", root).unwrap(),
+            ("src/auth.rs".to_string(), 42)
+        );
+        assert_eq!(
+            parse_location("/repo/src/auth.rs:7", root).unwrap(),
+            ("src/auth.rs".to_string(), 7)
+        );
+        assert!(parse_location("src/auth.rs", root).is_err());
+        assert!(parse_location("src/auth.rs:0", root).is_err());
+    }
+
+    #[test]
+    fn check_line_rejects_lines_past_the_end() {
+        assert!(check_line("src/auth.rs", 42, 42).is_ok());
+        assert!(check_line("empty.rs", 1, 0).is_ok());
+        let err = check_line("src/auth.rs", 999_999, 42).unwrap_err();
+        let err = CliError::classify(&err);
+        assert_eq!(err.code.exit_code(), 2);
+        assert!(
+            err.message
+                .contains("past the end of src/auth.rs (42 lines)")
+        );
+    }
+
+    #[test]
+    fn enclosing_chunk_prefers_innermost() {
+        let chunks = vec![
+            chunk(ChunkKind::Import, "std", 1, 1),
+            chunk(ChunkKind::Impl, "Server", 3, 30),
+            chunk(ChunkKind::Function, "handle", 10, 20),
+        ];
+        assert_eq!(enclosing_chunk(&chunks, 15).unwrap().name, "handle");
+        assert_eq!(enclosing_chunk(&chunks, 25).unwrap().name, "Server");
+        assert!(enclosing_chunk(&chunks, 1).is_none());
+    }
+
+    #[test]
+    fn enclosing_chunk_falls_back_to_preceding() {
+        let chunks = vec![
+            chunk(ChunkKind::Function, "a", 1, 1),
+            chunk(ChunkKind::Function, "b", 10, 10),
+        ];
+        assert_eq!(enclosing_chunk(&chunks, 12).unwrap().name, "b");
+    }
+
+    #[test]
+    fn related_tests_by_import_and_name() {
+        let files = vec![
+            file("src/auth.rs"),
+            file("tests/auth_test.rs"),
+            file("tests/login_test.rs"),
+            file("tests/db_test.rs"),
+        ];
+        let graph = ImportGraph::from_imports(&[(
+            "tests/login_test.rs".to_string(),
+            vec!["src/auth.rs".to_string()],
+        )]);
+
+        let tests = related_tests("src/auth.rs", &files, &graph);
+        assert_eq!(tests, vec!["tests/login_test.rs", "tests/auth_test.rs"]);
+    }
+
+    #[test]
+    fn parse_commit_line_fields() {
+        let c = parse_commit_line("abc1234\u{1f}Ada\u{1f}2025-01-02\u{1f}Fix auth").unwrap();
+        assert_eq!(c.hash, "abc1234");
+        assert_eq!(c.author, "Ada");
+        assert_eq!(c.subject, "Fix auth");
+    }
+}
@@ -0,0 +1,333 @@
+use super::query::QueryOptions;
+use crate::error::CliError;
+use crate::preset::Preset;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::io::{BufRead, IsTerminal, Write};
+use std::process::ExitCode;
+use topo_core::{Config, TokenBudget};
+
+/// Context windows, in tokens, by model name prefix. The longest matching
+/// prefix wins, so `gpt-4o` is not mistaken for `gpt-4`.
+const CONTEXT_WINDOWS: &[(&str, u64)] = &[
+    ("claude", 200_000),
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("gemini", 1_048_576),
+    ("llama", 128_000),
+    ("mistral", 128_000),
+    ("codestral", 256_000),
+    ("deepseek", 128_000),
+    ("qwen", 128_000),
+];
+
+/// Share of the context window given to selected files. The rest is left
+/// for instructions, the conversation and the model's own output.
+pub(super) const CONTEXT_SHARE: f64 = 0.25;
+
+/// Repositories up to this many files get a freshly rebuilt deep index.
+pub(super) const SMALL_REPO_FILES: usize = 5_000;
+
+/// Above this many files, building a deep index for one query isn't worth it.
+pub(super) const LARGE_REPO_FILES: usize = 20_000;
+
+/// Budgets of at least this many tokens have room for the long tail.
+const LARGE_BUDGET_TOKENS: u64 = 100_000;
+
+/// A recommended preset and budget for one task and model.
+#[derive(Debug, Clone)]
+struct Plan {
+    preset: Preset,
+    max_tokens: u64,
+    reasons: Vec<String>,
+}
+
+impl Plan {
+    /// A byte budget that never binds before the token budget, so the
+    /// preset's own byte default doesn't cut the selection short.
+    fn max_bytes(&self) -> u64 {
+        self.max_tokens * 4
+    }
+}
+
+/// Context window of `model` in tokens, if it is a known model.
+fn context_window(model: &str) -> Option<u64> {
+    let model = model.to_ascii_lowercase();
+    CONTEXT_WINDOWS
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|&(_, tokens)| tokens)
+}
+
+/// Pick a built-in preset and token budget from the context window and the
+/// repository's size.
+fn plan(context_tokens: u64, files: usize, repo_tokens: u64, has_index: bool) -> Plan {
+    let max_tokens = (context_tokens as f64 * CONTEXT_SHARE) as u64;
+    let mut reasons = vec![format!(
+        "{max_tokens} tokens is {:.0}% of the {context_tokens}-token context, leaving the rest for the conversation",
+        CONTEXT_SHARE * 100.0
+    )];
+
+    let name = if files > LARGE_REPO_FILES && !has_index {
+        reasons.push(format!(
+            "{files} files and no deep index: building one would dominate a single query; run `topo index --deep` to unlock balanced"
+        ));
+        "fast"
+    } else if files > SMALL_REPO_FILES {
+        reasons.push(format!(
+            "{files} files: the cached deep index keeps scoring fast without a rebuild"
+        ));
+        "balanced"
+    } else if max_tokens >= LARGE_BUDGET_TOKENS {
+        reasons.push(format!(
+            "{files} files and a large budget: rebuild the index and admit low-scoring files too"
+        ));
+        "thorough"
+    } else {
+        reasons.push(format!(
+            "{files} files: rebuilding the deep index is cheap, so add structural signals"
+        ));
+        "deep"
+    };
+    if repo_tokens <= max_tokens {
+        reasons.push(format!(
+            "the whole repository (~{repo_tokens} tokens) fits in the budget; ranking only orders it"
+        ));
+    }
+
+    Plan {
+        preset: Preset::builtin(name).expect("planner picks built-in presets"),
+        max_tokens,
+        reasons,
+    }
+}
+
+/// The `topo quick` invocation that applies a plan, indexing as needed.
+fn command(task: &str, plan: &Plan) -> String {
+    format!(
+        "topo quick '{}' --preset {} --max-tokens {} --max-bytes {}",
+        task.replace('\'', r"'\''"),
+        plan.preset,
+        plan.max_tokens,
+        plan.max_bytes()
+    )
+}
+
+/// Ask on stderr whether to go ahead; anything but yes declines.
+fn confirm(prompt: &str) -> Result<bool> {
+    eprint!("{prompt} [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes"))
+}
+
+/// Recommend a preset, token budget and expected file count for `task` on
+/// `model`, and with `run`, execute the query with them.
+pub fn run(
+    cli: &Cli,
+    task: &str,
+    model: &str,
+    context_tokens: Option<u64>,
+    run: bool,
+    yes: bool,
+) -> Result<ExitCode> {
+    let Some(context) = context_tokens.or_else(|| context_window(model)) else {
+        bail!(
+            CliError::usage(format!("unknown model {model:?}"))
+                .hint("Pass its context window with --context-tokens.")
+        );
+    };
+    let root = cli.repo_root()?;
+    let bundle = cli.bundle_builder(&root).build()?;
+    let repo_tokens: u64 = bundle.files.iter().map(|f| f.estimated_tokens()).sum();
+    let has_index = topo_index::index_path(&root).exists();
+    let plan = plan(context, bundle.file_count(), repo_tokens, has_index);
+
+    // Estimate the selection with the index as it stands; the real run may
+    // rebuild it first
+    let config = Config::load(&root)?;
+    let deep_index = if plan.preset.use_structural_signals() && has_index {
+        super::query::load_index_for(&root, task)?
+    } else {
+        None
+    };
+    let scoring = super::query::Scoring::from_config(&config)
+        .include_vendored(cli.include_vendored)
+        .weights(&config, &plan.preset, None)?
+        .git_history(&root, &plan.preset);
+    let ranked: Vec<_> = super::query::score_files(
+        task,
+        &bundle.files,
+        &plan.preset,
+        deep_index.as_ref(),
+        &scoring,
+    )
+    .into_iter()
+    .filter(|f| f.score >= plan.preset.default_min_score())
+    .collect();
+    let selected = TokenBudget {
+        max_bytes: None,
+        max_tokens: Some(plan.max_tokens),
+    }
+    .enforce(&ranked);
+    let expected_tokens: u64 = selected.iter().map(|f| f.tokens).sum();
+    let command = command(task, &plan);
+
+    if run {
+        log::info!(
+            "Plan: preset {}, {} tokens, ~{} files (~{expected_tokens} tokens)",
+            plan.preset,
+            plan.max_tokens,
+            selected.len()
+        );
+        if !yes {
+            if !std::io::stdin().is_terminal() {
+                bail!(CliError::usage(
+                    "--run asks for confirmation; pass --yes when stdin is not a terminal"
+                ));
+            }
+            if !confirm(&format!("Run `{command}`?"))? {
+                log::info!("Not running.");
+                return Ok(ExitCode::SUCCESS);
+            }
+        }
+        let options = QueryOptions {
+            preset: plan.preset.clone(),
+            max_bytes: Some(plan.max_bytes()),
+            max_tokens: Some(plan.max_tokens),
+            min_score: None,
+            top: None,
+            semantic_weight: None,
+            semantic_only: false,
+            embedder: None,
+            rerank: false,
+            fail_on_empty: false,
+            outline: None,
+            no_expand: false,
+            weights: None,
+            since: None,
+            scope: None,
+            owner: None,
+            group_by: None,
+        };
+        return super::quick::run(cli, task, options);
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "task": task,
+                "model": model,
+                "context_tokens": context,
+                "repo": {
+                    "files": bundle.file_count(),
+                    "tokens": repo_tokens,
+                    "deep_index": has_index,
+                },
+                "preset": plan.preset.as_str(),
+                "max_tokens": plan.max_tokens,
+                "min_score": plan.preset.default_min_score(),
+                "expected_files": selected.len(),
+                "expected_tokens": expected_tokens,
+                "reasons": plan.reasons,
+                "command": command,
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            println!(
+                "--preset {} --max-tokens {} (~{} files)",
+                plan.preset,
+                plan.max_tokens,
+                selected.len()
+            );
+        }
+        _ => {
+            println!("Model:   {model} ({context}-token context)");
+            println!(
+                "Repo:    {} files, ~{repo_tokens} tokens, {}",
+                bundle.file_count(),
+                if has_index {
+                    "deep index built"
+                } else {
+                    "no deep index"
+                }
+            );
+            println!("Preset:  {}", plan.preset);
+            println!("Budget:  {} tokens", plan.max_tokens);
+            println!(
+                "Expect:  ~{} files, ~{expected_tokens} tokens",
+                selected.len()
+            );
+            println!();
+            for reason in &plan.reasons {
+                println!("  - {reason}");
+            }
+            println!();
+            println!("Run: {command}");
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_window_prefers_longest_prefix() {
+        assert_eq!(context_window("gpt-4o-mini"), Some(128_000));
+        assert_eq!(context_window("gpt-4-0613"), Some(8_192));
+        assert_eq!(context_window("Claude-Sonnet-4"), Some(200_000));
+        assert_eq!(context_window("my-local-model"), None);
+    }
+
+    #[test]
+    fn plan_follows_repo_size_and_budget() {
+        let small = plan(200_000, 300, 2_000_000, false);
+        assert_eq!(small.preset.as_str(), "deep");
+        assert_eq!(small.max_tokens, 50_000);
+
+        assert_eq!(
+            plan(1_000_000, 300, 2_000_000, false).preset.as_str(),
+            "thorough"
+        );
+        assert_eq!(
+            plan(200_000, 8_000, 9_000_000, true).preset.as_str(),
+            "balanced"
+        );
+        assert_eq!(
+            plan(200_000, 30_000, 9_000_000, false).preset.as_str(),
+            "fast"
+        );
+        assert_eq!(
+            plan(200_000, 30_000, 9_000_000, true).preset.as_str(),
+            "balanced"
+        );
+
+        let tiny = plan(200_000, 10, 4_000, false);
+        assert!(tiny.reasons.iter().any(|r| r.contains("whole repository")));
+    }
+
+    #[test]
+    fn command_quotes_the_task() {
+        let plan = plan(128_000, 10, 1_000, false);
+        assert_eq!(
+            command("don't panic", &plan),
+            r"topo quick 'don'\''t panic' --preset deep --max-tokens 32000 --max-bytes 128000"
+        );
+    }
+}
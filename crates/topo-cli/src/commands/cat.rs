@@ -0,0 +1,189 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Context, Result, bail};
+use std::fs;
+use topo_core::{Chunk, ChunkKind};
+use topo_treesit::{Chunker, CompositeChunker};
+
+/// Chunk names listed in the hint when `--chunk` matches nothing.
+const LISTED_CHUNKS: usize = 10;
+
+/// Parse a `--lines` range: `40-120`, `40-` (to the end) or `40`.
+/// The end is `None` when open.
+pub fn parse_lines(range: &str) -> Result<(u32, Option<u32>)> {
+    let parse = |n: &str| -> Result<u32> {
+        n.trim()
+            .parse()
+            .with_context(|| format!("invalid line number in --lines {range:?}"))
+    };
+    let (start, end) = match range.split_once('-') {
+        Some((start, "")) => (parse(start)?, None),
+        Some((start, end)) => (parse(start)?, Some(parse(end)?)),
+        None => {
+            let line = parse(range)?;
+            (line, Some(line))
+        }
+    };
+    if start == 0 {
+        bail!(CliError::usage("line numbers start at 1"));
+    }
+    if end.is_some_and(|end| end < start) {
+        bail!(CliError::usage(format!(
+            "--lines {range:?} ends before it starts"
+        )));
+    }
+    Ok((start, end))
+}
+
+/// The first non-import chunk called `name`.
+pub fn find_chunk<'a>(chunks: &'a [Chunk], name: &str) -> Option<&'a Chunk> {
+    chunks
+        .iter()
+        .filter(|c| c.kind != ChunkKind::Import)
+        .find(|c| c.name == name)
+}
+
+pub fn run(cli: &Cli, path: &str, lines: Option<&str>, chunk: Option<&str>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = cli.repo_relative(&root, path);
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let Some(info) = bundle.files.iter().find(|f| f.path == path) else {
+        bail!(CliError::untracked(&path, &root));
+    };
+    let content = fs::read_to_string(root.join(&path))?;
+    let total = content.lines().count() as u32;
+
+    // Resolve the slice before reading it, so a bad range never prints
+    let chunks;
+    let (start, end, named) = match (lines, chunk) {
+        (_, Some(name)) => {
+            chunks = CompositeChunker.chunk(&content, info.language);
+            let Some(c) = find_chunk(&chunks, name) else {
+                let mut names: Vec<&str> = chunks
+                    .iter()
+                    .filter(|c| c.kind != ChunkKind::Import)
+                    .map(|c| c.name.as_str())
+                    .collect();
+                names.dedup();
+                names.truncate(LISTED_CHUNKS);
+                bail!(
+                    CliError::not_found(format!("no chunk named {name:?} in {path}")).hint(
+                        if names.is_empty() {
+                            "The file has no functions or types to select.".to_string()
+                        } else {
+                            format!("Chunks include: {}.", names.join(", "))
+                        }
+                    )
+                );
+            };
+            (c.start_line, c.end_line.min(total), Some(c))
+        }
+        (Some(range), None) => {
+            let (start, end) = parse_lines(range)?;
+            if start > total.max(1) {
+                bail!(CliError::usage(format!(
+                    "--lines {range:?} starts past the end of {path} ({total} lines)"
+                )));
+            }
+            (start, end.unwrap_or(total).min(total), None)
+        }
+        (None, None) => (1, total, None),
+    };
+
+    let text = content
+        .lines()
+        .skip(start as usize - 1)
+        .take((end + 1).saturating_sub(start) as usize)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let policy = super::query::content_policy(&root)?;
+    let slice = match policy.embed(&path, info.size, &text) {
+        Ok(slice) => slice,
+        Err(reason) => bail!(
+            CliError::config(format!("content of {path} is withheld: {reason}"))
+                .hint("The [content] section of .topo/config.toml decides what may be printed.")
+        ),
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "language": info.language.as_str(),
+                "start_line": start,
+                "end_line": end,
+                "total_lines": total,
+                "chunk": named.map(|c| serde_json::json!({
+                    "kind": format!("{:?}", c.kind).to_lowercase(),
+                    "name": c.name,
+                })),
+                "content": slice.text,
+                "redactions": slice.redactions.iter().map(|r| serde_json::json!({
+                    "kind": r.kind,
+                    "line": start as usize + r.line - 1,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            println!("{path}:{start}-{end}");
+            if !slice.text.is_empty() {
+                println!("{}", slice.text);
+            }
+        }
+        _ => {
+            match named {
+                Some(c) => println!(
+                    "{path} — {:?} `{}` (lines {start}-{end} of {total})",
+                    c.kind, c.name
+                ),
+                None => println!("{path} (lines {start}-{end} of {total})"),
+            }
+            for (i, l) in slice.text.lines().enumerate() {
+                println!("{:>6} | {l}", start as usize + i);
+            }
+            if let Some(summary) = slice.summary() {
+                println!("       ({summary})");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lines_accepts_ranges() {
+        assert_eq!(parse_lines("40-120").unwrap(), (40, Some(120)));
+        assert_eq!(parse_lines("40-").unwrap(), (40, None));
+        assert_eq!(parse_lines("7").unwrap(), (7, Some(7)));
+        assert!(parse_lines("0-3").is_err());
+        assert!(parse_lines("9-3").is_err());
+        assert!(parse_lines("a-b").is_err());
+    }
+
+    #[test]
+    fn find_chunk_skips_imports() {
+        let chunk = |kind, name: &str, line| Chunk {
+            kind,
+            name: name.to_string(),
+            start_line: line,
+            end_line: line,
+            content: String::new(),
+        };
+        let chunks = vec![
+            chunk(ChunkKind::Import, "login", 1),
+            chunk(ChunkKind::Function, "login", 5),
+        ];
+        assert_eq!(find_chunk(&chunks, "login").unwrap().start_line, 5);
+        assert!(find_chunk(&chunks, "logout").is_none());
+    }
+}
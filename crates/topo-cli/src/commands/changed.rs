@@ -0,0 +1,279 @@
+use crate::error::{CliError, ErrorCode};
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::process::ExitCode;
+use topo_core::{DeepIndex, FileInfo, FileRole, Language};
+use topo_score::ImportGraph;
+
+/// Files that differ between the stored index and the working tree.
+#[derive(Debug, Default, PartialEq)]
+pub struct ChangeSet {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+}
+
+impl ChangeSet {
+    /// Compare the scanned files against the index by content hash. Files
+    /// missing from the index for which `skipped` holds, such as the
+    /// artifacts and binaries the index leaves out, are not reported as
+    /// added.
+    pub fn compute(
+        index: &DeepIndex,
+        files: &[FileInfo],
+        skipped: impl Fn(&FileInfo) -> bool,
+    ) -> Self {
+        let mut changes = Self::default();
+        for f in files {
+            match index.files.get(&f.path) {
+                None if skipped(f) => {}
+                None => changes.added.push(f.path.clone()),
+                Some(entry) if entry.sha256 != f.sha256 => changes.modified.push(f.path.clone()),
+                Some(_) => {}
+            }
+        }
+
+        let current: HashSet<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        changes.deleted = index
+            .files
+            .keys()
+            .filter(|p| !current.contains(p.as_str()))
+            .cloned()
+            .collect();
+
+        changes.added.sort();
+        changes.modified.sort();
+        changes.deleted.sort();
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+
+    fn all(&self) -> impl Iterator<Item = &String> {
+        self.added.iter().chain(&self.modified).chain(&self.deleted)
+    }
+}
+
+/// Unchanged files importing a changed file, mapped to the changed files they
+/// import ("blast radius").
+pub fn impacted(changes: &ChangeSet, graph: &ImportGraph) -> BTreeMap<String, Vec<String>> {
+    let changed: Vec<&String> = changes.all().collect();
+    let mut impacted: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in &changed {
+        for importer in graph.importers_of(path) {
+            if changed.iter().any(|c| c.as_str() == importer) {
+                continue;
+            }
+            impacted
+                .entry(importer.to_string())
+                .or_default()
+                .push(path.to_string());
+        }
+    }
+    impacted
+}
+
+pub fn run(cli: &Cli, exit_code: bool) -> Result<ExitCode> {
+    let root = cli.repo_root()?;
+    let Some(index) = topo_index::load(&root)? else {
+        bail!(CliError::no_index(&root));
+    };
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let changes = ChangeSet::compute(&index, &bundle.files, |f| topo_index::is_skipped(&root, f));
+
+    // Keep deleted paths in the graph so their former importers still resolve
+    let mut graph_files = bundle.files.clone();
+    graph_files.extend(changes.deleted.iter().map(|p| placeholder(p)));
+    let graph = topo_index::build_graph(&root, &graph_files);
+    let impacted = impacted(&changes, &graph);
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "index": topo_index::index_path(&root),
+                "added": changes.added,
+                "modified": changes.modified,
+                "deleted": changes.deleted,
+                "impacted": impacted.iter().map(|(path, via)| serde_json::json!({
+                    "path": path,
+                    "via": via,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for (tag, paths) in [
+                ("A", &changes.added),
+                ("M", &changes.modified),
+                ("D", &changes.deleted),
+            ] {
+                for p in paths {
+                    println!("{tag} {p}");
+                }
+            }
+            for p in impacted.keys() {
+                println!("I {p}");
+            }
+        }
+        _ => {
+            if changes.is_empty() {
+                println!("No changes since the last index.");
+            } else {
+                for (title, paths) in [
+                    ("Added", &changes.added),
+                    ("Modified", &changes.modified),
+                    ("Deleted", &changes.deleted),
+                ] {
+                    if paths.is_empty() {
+                        continue;
+                    }
+                    println!("{title} ({}):", paths.len());
+                    for p in paths {
+                        println!("  {p}");
+                    }
+                }
+                if !impacted.is_empty() {
+                    println!("\nImpacted importers ({}):", impacted.len());
+                    for (path, via) in &impacted {
+                        println!("  {path}  <- {}", via.join(", "));
+                    }
+                }
+                println!("\nRun `topo index --deep` to reindex.");
+            }
+        }
+    }
+
+    if exit_code && !changes.is_empty() {
+        return Ok(ExitCode::from(ErrorCode::StaleIndex.exit_code()));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn placeholder(path: &str) -> FileInfo {
+    FileInfo {
+        path: path.to_string(),
+        size: 0,
+        language: Language::from_path(Path::new(path)),
+        role: FileRole::from_path(Path::new(path)),
+        sha256: [0u8; 32],
+        lines: 0,
+        executable: false,
+        shebang: None,
+        entry_point: false,
+        license: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use topo_core::FileEntry;
+
+    fn entry(hash: u8) -> FileEntry {
+        FileEntry {
+            sha256: [hash; 32],
+            chunks: Vec::new(),
+            term_frequencies: HashMap::new(),
+            positions: HashMap::new(),
+            doc_length: 0,
+            summary: Default::default(),
+            signature: Vec::new(),
+            imports: Vec::new(),
+        }
+    }
+
+    fn info(path: &str, hash: u8) -> FileInfo {
+        FileInfo {
+            sha256: [hash; 32],
+            ..placeholder(path)
+        }
+    }
+
+    #[test]
+    fn change_set_classifies_files() {
+        let index = DeepIndex {
+            version: topo_core::INDEX_VERSION,
+            files: HashMap::from([
+                ("src/a.rs".to_string(), entry(1)),
+                ("src/b.rs".to_string(), entry(2)),
+                ("src/gone.rs".to_string(), entry(3)),
+            ]),
+            avg_doc_length: 1.0,
+            total_docs: 3,
+            doc_frequencies: HashMap::new(),
+            pagerank_scores: HashMap::new(),
+            pagerank_settings: String::new(),
+            stop_words: Vec::new(),
+            errors: Vec::new(),
+        };
+        let files = vec![
+            info("src/a.rs", 1),
+            info("src/b.rs", 9),
+            info("src/new.rs", 4),
+        ];
+
+        let changes = ChangeSet::compute(&index, &files, |_| false);
+        assert_eq!(changes.added, vec!["src/new.rs"]);
+        assert_eq!(changes.modified, vec!["src/b.rs"]);
+        assert_eq!(changes.deleted, vec!["src/gone.rs"]);
+    }
+
+    #[test]
+    fn change_set_ignores_files_the_index_skips() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = DeepIndex {
+            version: topo_core::INDEX_VERSION,
+            files: HashMap::from([("src/app.ts".to_string(), entry(1))]),
+            avg_doc_length: 1.0,
+            total_docs: 1,
+            doc_frequencies: HashMap::new(),
+            pagerank_scores: HashMap::new(),
+            pagerank_settings: String::new(),
+            stop_words: Vec::new(),
+            errors: Vec::new(),
+        };
+        let files = vec![
+            info("src/app.ts", 1),
+            info("dist/app.min.js", 2),
+            info("dist/app.js.map", 3),
+            info("logo.png", 5),
+            info("src/new.ts", 4),
+        ];
+        std::fs::write(
+            dir.path().join("logo.png"),
+            b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR",
+        )
+        .unwrap();
+
+        let changes = ChangeSet::compute(&index, &files, |f| topo_index::is_skipped(dir.path(), f));
+        assert_eq!(changes.added, vec!["src/new.ts"]);
+        assert!(changes.modified.is_empty());
+        assert!(changes.deleted.is_empty());
+    }
+
+    #[test]
+    fn impacted_excludes_changed_importers() {
+        let changes = ChangeSet {
+            modified: vec!["src/auth.rs".to_string(), "src/server.rs".to_string()],
+            ..Default::default()
+        };
+        let graph = ImportGraph::from_imports(&[
+            ("src/server.rs".to_string(), vec!["src/auth.rs".to_string()]),
+            ("src/api.rs".to_string(), vec!["src/auth.rs".to_string()]),
+        ]);
+
+        let impacted = impacted(&changes, &graph);
+        assert_eq!(impacted.len(), 1);
+        assert_eq!(impacted["src/api.rs"], vec!["src/auth.rs"]);
+    }
+}
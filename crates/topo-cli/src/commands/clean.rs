@@ -0,0 +1,83 @@
+use crate::{Cli, OutputFormat};
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Remove derived caches under `.topo/`. Config, manifests, stats and the
+/// last query are kept; everything removed is rebuilt on demand.
+pub fn run(cli: &Cli, embeddings_only: bool) -> Result<()> {
+    let root = cli.repo_root()?;
+    let mut targets: Vec<PathBuf> = vec![topo_index::embeddings_dir(&root)];
+    if !embeddings_only {
+        targets.push(topo_index::index_path(&root));
+        targets.push(topo_index::index_backup_path(&root));
+        targets.push(topo_index::graph_path(&root));
+        targets.push(topo_scanner::scan_cache_path(&root));
+    }
+
+    let mut removed: Vec<(String, u64)> = Vec::new();
+    for path in targets {
+        if !path.exists() {
+            continue;
+        }
+        let bytes = disk_size(&path);
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+        let display = path.strip_prefix(&root).unwrap_or(&path);
+        removed.push((display.to_string_lossy().replace('\\', "/"), bytes));
+    }
+    let freed: u64 = removed.iter().map(|(_, b)| b).sum();
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "removed": removed.iter().map(|(p, b)| serde_json::json!({
+                    "path": p,
+                    "bytes": b,
+                })).collect::<Vec<_>>(),
+                "freed_bytes": freed,
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for (p, _) in &removed {
+                println!("{p}");
+            }
+        }
+        _ => {
+            if cli.is_quiet() {
+                return Ok(());
+            }
+            if removed.is_empty() {
+                println!("Nothing to clean.");
+                return Ok(());
+            }
+            for (p, b) in &removed {
+                println!("Removed {p} ({b} bytes)");
+            }
+            println!("Freed {freed} bytes.");
+        }
+    }
+    Ok(())
+}
+
+/// Total size of a file, or of every file under a directory.
+fn disk_size(path: &Path) -> u64 {
+    if path.is_dir() {
+        fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| disk_size(&entry.path()))
+            .sum()
+    } else {
+        fs::metadata(path).map_or(0, |m| m.len())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::Cli;
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::Write;
+
+/// Print a completion script for `shell`, generated from the CLI definition.
+pub fn run(shell: Shell) -> Result<()> {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut std::io::stdout());
+    Ok(())
+}
+
+/// Print the `topo(1)` man page in roff format.
+pub fn man() -> Result<()> {
+    let mut buf = Vec::new();
+    render_man(&mut buf)?;
+    std::io::stdout().write_all(&buf)?;
+    Ok(())
+}
+
+fn render_man(out: &mut dyn Write) -> Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completions_cover_subcommands() {
+        let mut cmd = Cli::command();
+        let mut buf = Vec::new();
+        clap_complete::generate(Shell::Bash, &mut cmd, "topo", &mut buf);
+        let script = String::from_utf8(buf).unwrap();
+        assert!(script.contains("blame-context"));
+        assert!(script.contains("--max-tokens"));
+    }
+
+    #[test]
+    fn man_page_renders() {
+        let mut buf = Vec::new();
+        render_man(&mut buf).unwrap();
+        let page = String::from_utf8(buf).unwrap();
+        assert!(
+            page.starts_with(".ie"),
+            "unexpected header: {}",
+            &page[..40]
+        );
+        assert!(page.contains("topo"));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use topo_score::{ImportGraph, ImportTrace};
+
+/// Number of files `path` reaches by following import edges, directly or
+/// through other files.
+fn transitive_imports(graph: &ImportGraph, path: &str) -> usize {
+    let mut seen: HashSet<&str> = HashSet::from([path]);
+    let mut queue: VecDeque<&str> = VecDeque::from([path]);
+    while let Some(current) = queue.pop_front() {
+        for import in graph.imports_of(current) {
+            if seen.insert(import) {
+                queue.push_back(import);
+            }
+        }
+    }
+    seen.len() - 1
+}
+
+pub fn run(cli: &Cli, path: &str, explain: bool) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = cli.repo_relative(&root, path);
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let Some(file) = bundle.files.iter().find(|f| f.path == path) else {
+        bail!(CliError::untracked(&path, &root));
+    };
+
+    if explain {
+        let content = fs::read_to_string(root.join(&path))?;
+        let raw: Vec<String> = topo_score::extract_weighted_imports(&content, file.language)
+            .into_iter()
+            .map(|(raw, _)| raw)
+            .collect();
+        let all_paths: Vec<&str> = bundle.files.iter().map(|f| f.path.as_str()).collect();
+        let options = super::query::resolve_options(&root)?.with_manifests(&root, &all_paths);
+        let traces = topo_score::explain_imports(&path, file.language, &raw, &all_paths, &options);
+        return print_traces(cli, &path, &traces);
+    }
+
+    let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+    let imports = graph.imports_of(&path);
+    let importers = graph.importers_of(&path);
+    let fan_out = transitive_imports(&graph, &path);
+    let fan_in = super::impact::dependents(&graph, &path, None).len();
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "imports": imports.iter().map(|to| serde_json::json!({
+                    "path": to,
+                    "weight": graph.edge_weight(&path, to),
+                })).collect::<Vec<_>>(),
+                "imported_by": importers,
+                "fan_out": {"direct": imports.len(), "transitive": fan_out},
+                "fan_in": {"direct": importers.len(), "transitive": fan_in},
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for p in imports {
+                println!("> {p}");
+            }
+            for p in &importers {
+                println!("< {p}");
+            }
+        }
+        _ => {
+            println!("Imports ({} direct, {fan_out} transitive):", imports.len());
+            for p in imports {
+                println!("  {p}");
+            }
+            println!(
+                "\nImported by ({} direct, {fan_in} transitive):",
+                importers.len()
+            );
+            for p in &importers {
+                println!("  {p}");
+            }
+            if imports.is_empty() && importers.is_empty() {
+                println!("\nNo resolved edges. Run `topo deps {path} --explain` to see why.");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_traces(cli: &Cli, path: &str, traces: &[ImportTrace]) -> Result<()> {
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "imports": traces.iter().map(|t| serde_json::json!({
+                    "raw": t.raw,
+                    "resolver": t.resolver,
+                    "rule": t.rule,
+                    "chosen": t.chosen,
+                    "rejected": t.rejected.iter().map(|(p, reason)| serde_json::json!({
+                        "path": p,
+                        "reason": reason,
+                    })).collect::<Vec<_>>(),
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for t in traces {
+                let chosen = if t.chosen.is_empty() {
+                    "-".to_string()
+                } else {
+                    t.chosen.join(",")
+                };
+                println!("{} [{}] {chosen}", t.raw, t.resolver);
+            }
+        }
+        _ => {
+            if traces.is_empty() {
+                println!("{path} has no imports Topo recognizes.");
+                return Ok(());
+            }
+            println!("Import resolution for {path}:");
+            for t in traces {
+                println!("\n  {}  [{}]", t.raw, t.resolver);
+                println!("    looked for: {}", t.rule);
+                if t.chosen.is_empty() {
+                    println!("    chosen:     none (external or unresolved)");
+                }
+                for (i, p) in t.chosen.iter().enumerate() {
+                    let label = if i == 0 { "chosen:" } else { "" };
+                    println!("    {label:<11} {p}");
+                }
+                for (i, (p, reason)) in t.rejected.iter().enumerate() {
+                    let label = if i == 0 { "rejected:" } else { "" };
+                    println!("    {label:<11} {p} ({reason})");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitive_imports_follow_forward_edges_once() {
+        let graph = ImportGraph::from_imports(&[
+            (
+                "src/main.rs".to_string(),
+                vec!["src/api.rs".to_string(), "src/db.rs".to_string()],
+            ),
+            ("src/api.rs".to_string(), vec!["src/db.rs".to_string()]),
+            ("src/db.rs".to_string(), vec!["src/main.rs".to_string()]),
+            ("src/lone.rs".to_string(), vec![]),
+        ]);
+        assert_eq!(transitive_imports(&graph, "src/api.rs"), 2);
+        assert_eq!(transitive_imports(&graph, "src/main.rs"), 2);
+        assert_eq!(transitive_imports(&graph, "src/lone.rs"), 0);
+    }
+}
@@ -1,12 +1,42 @@
 use crate::Cli;
+use crate::preset::Preset;
 use anyhow::Result;
+use topo_core::Config;
+use topo_score::SignalWeights;
+
+/// The repository's config with `[defaults]` and `[weights]` resolved to
+/// the values commands use when no flag overrides them.
+fn effective_config(config: &Config) -> Result<serde_json::Value> {
+    let preset = Preset::resolve(None, config)?;
+    let weights = SignalWeights::from_config(&config.weights);
+    let mut json = serde_json::to_value(config)?;
+    json["defaults"] = serde_json::json!({
+        "preset": preset.as_str(),
+        "max_bytes": config.defaults.max_bytes.unwrap_or(preset.default_max_bytes()),
+        "max_tokens": config.defaults.max_tokens,
+        "min_score": config.defaults.min_score.unwrap_or(preset.default_min_score()),
+    });
+    json["weights"] = serde_json::json!({
+        "bm25f": weights.bm25f,
+        "heuristic": weights.heuristic,
+        "pagerank": weights.pagerank,
+        "git_recency": weights.git_recency,
+        "co_change": weights.co_change,
+        "churn": weights.churn,
+        "rrf_k": weights.rrf_k,
+    });
+    Ok(json)
+}
 
 pub fn run(cli: &Cli) -> Result<()> {
+    let root = cli.repo_root()?;
+    let config = Config::load(&root)?;
+    let config_path = Config::path(&root);
     let description = serde_json::json!({
         "name": "topo",
         "version": env!("CARGO_PKG_VERSION"),
         "replaces": "repo-context",
-        "commands": ["index", "query", "quick", "render", "explain", "inspect", "describe", "mcp", "init", "gain"],
+        "commands": ["index", "watch", "query", "quick", "more", "render", "diff-results", "explain", "budget", "blame-context", "cat", "test-context", "expand", "summarize", "deps", "unresolved", "impact", "changed", "manifest", "inspect", "overview", "clean", "describe", "completions", "man", "mcp", "init", "gain"],
         "formats": ["jsonl", "json", "human", "compact"],
         "languages": [
             "rust", "go", "python", "javascript", "typescript",
@@ -14,20 +44,73 @@ pub fn run(cli: &Cli) -> Result<()> {
             "scala", "haskell", "elixir", "lua", "php", "r"
         ],
         "scoring": ["heuristic", "content", "hybrid"],
-        "presets": ["fast", "balanced", "deep", "thorough"],
+        "presets": Preset::names(&config),
+        "config": {
+            "path": config_path,
+            "found": config_path.exists(),
+            "effective": effective_config(&config)?,
+        },
     });
 
     match cli.effective_format() {
         crate::OutputFormat::Human => {
             println!("topo v{}", env!("CARGO_PKG_VERSION"));
             println!();
-            println!("Commands:  index, query, quick, render, explain, inspect, describe, mcp");
+            println!(
+                "Commands:  index, watch, query, quick, more, render, diff-results, explain, budget, blame-context, cat, test-context, expand, summarize, deps, unresolved, impact, changed, manifest, inspect, overview, clean, describe, completions, man, mcp"
+            );
             println!("Formats:   jsonl, json, human");
             println!(
                 "Languages: rust, go, python, javascript, typescript, java, ruby, c, cpp, shell, swift, kotlin, scala, haskell, elixir, lua, php, r"
             );
             println!("Scoring:   heuristic, content, hybrid");
-            println!("Presets:   fast, balanced, deep, thorough");
+            println!("Presets:   {}", Preset::names(&config).join(", "));
+            let preset = Preset::resolve(None, &config)?;
+            let weights = SignalWeights::from_config(&config.weights);
+            println!();
+            println!(
+                "Config:    {}{}",
+                config_path.display(),
+                if config_path.exists() {
+                    ""
+                } else {
+                    " (not found, using defaults)"
+                }
+            );
+            println!(
+                "Defaults:  preset {preset}, max_bytes {}, max_tokens {}, min_score {}",
+                config
+                    .defaults
+                    .max_bytes
+                    .unwrap_or(preset.default_max_bytes()),
+                config
+                    .defaults
+                    .max_tokens
+                    .map_or("none".to_string(), |t| t.to_string()),
+                config
+                    .defaults
+                    .min_score
+                    .unwrap_or(preset.default_min_score()),
+            );
+            println!(
+                "Weights:   bm25f {}, heuristic {}, pagerank {}, git_recency {}, co_change {}, churn {}, rrf_k {}",
+                weights.bm25f,
+                weights.heuristic,
+                weights.pagerank,
+                weights.git_recency,
+                weights.co_change,
+                weights.churn,
+                weights.rrf_k
+            );
+            if !config.scan.ignore.is_empty() {
+                println!("Ignore:    {}", config.scan.ignore.join(", "));
+            }
+            if !config.scan.skip_dirs.is_empty() {
+                println!("Skip dirs: {}", config.scan.skip_dirs.join(", "));
+            }
+            if !config.scan.keep_dirs.is_empty() {
+                println!("Keep dirs: {}", config.scan.keep_dirs.join(", "));
+            }
         }
         _ => {
             println!("{}", serde_json::to_string_pretty(&description)?);
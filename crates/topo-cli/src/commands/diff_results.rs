@@ -0,0 +1,333 @@
+use crate::error::CliError;
+use crate::last_query;
+use crate::{Cli, OutputFormat};
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// One file in a selection, with its 1-based rank.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selected {
+    pub path: String,
+    pub rank: usize,
+    pub score: f64,
+}
+
+/// A file present in both selections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shifted {
+    pub path: String,
+    pub old_rank: usize,
+    pub new_rank: usize,
+    pub old_score: f64,
+    pub new_score: f64,
+}
+
+impl Shifted {
+    /// Positive when the file moved up in the ranking.
+    pub fn rank_shift(&self) -> i64 {
+        self.old_rank as i64 - self.new_rank as i64
+    }
+
+    pub fn score_delta(&self) -> f64 {
+        self.new_score - self.old_score
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.rank_shift() != 0 || self.score_delta() != 0.0
+    }
+}
+
+/// Differences between two selections.
+#[derive(Debug, Default)]
+pub struct ResultDiff {
+    pub added: Vec<Selected>,
+    pub removed: Vec<Selected>,
+    pub common: Vec<Shifted>,
+}
+
+impl ResultDiff {
+    pub fn compute(old: &[Selected], new: &[Selected]) -> Self {
+        let old_by_path: HashMap<&str, &Selected> =
+            old.iter().map(|s| (s.path.as_str(), s)).collect();
+        let new_by_path: HashMap<&str, &Selected> =
+            new.iter().map(|s| (s.path.as_str(), s)).collect();
+
+        let added = new
+            .iter()
+            .filter(|s| !old_by_path.contains_key(s.path.as_str()))
+            .cloned()
+            .collect();
+        let removed = old
+            .iter()
+            .filter(|s| !new_by_path.contains_key(s.path.as_str()))
+            .cloned()
+            .collect();
+        let common = new
+            .iter()
+            .filter_map(|n| {
+                old_by_path.get(n.path.as_str()).map(|o| Shifted {
+                    path: n.path.clone(),
+                    old_rank: o.rank,
+                    new_rank: n.rank,
+                    old_score: o.score,
+                    new_score: n.score,
+                })
+            })
+            .collect();
+
+        Self {
+            added,
+            removed,
+            common,
+        }
+    }
+
+    /// Whether the two selections contain the same files in the same order.
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.common.iter().all(|s| s.rank_shift() == 0)
+    }
+}
+
+/// Parse a selection from JSONL v0.3 (`Path`/`Score` entries) or from the
+/// JSON output format (`files` array).
+pub fn parse_selection(content: &str) -> Result<Vec<Selected>> {
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Whole-document JSON output
+    if let Ok(v) = serde_json::from_str::<serde_json::Value>(trimmed)
+        && let Some(files) = v.get("files").and_then(|f| f.as_array())
+    {
+        return Ok(files
+            .iter()
+            .filter_map(|f| Some((f.get("path")?.as_str()?, f["score"].as_f64().unwrap_or(0.0))))
+            .enumerate()
+            .map(|(i, (path, score))| Selected {
+                path: path.to_string(),
+                rank: i + 1,
+                score,
+            })
+            .collect());
+    }
+
+    let mut selected = Vec::new();
+    for line in trimmed.lines().filter(|l| !l.trim().is_empty()) {
+        let v: serde_json::Value = serde_json::from_str(line)?;
+        if let Some(path) = v.get("Path").and_then(|p| p.as_str()) {
+            selected.push(Selected {
+                path: path.to_string(),
+                rank: selected.len() + 1,
+                score: v["Score"].as_f64().unwrap_or(0.0),
+            });
+        }
+    }
+    Ok(selected)
+}
+
+fn read_selection(path: &Path) -> Result<Vec<Selected>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse_selection(&content).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Files delivered by the last query (including `topo more` slices), in rank order.
+fn last_selection(root: &Path) -> Result<Vec<Selected>> {
+    let Some(last) = last_query::load(root)? else {
+        bail!(CliError::no_previous_query());
+    };
+    Ok(last
+        .ranked
+        .iter()
+        .filter(|f| last.delivered.contains(&f.path))
+        .enumerate()
+        .map(|(i, f)| Selected {
+            path: f.path.clone(),
+            rank: i + 1,
+            score: f.score,
+        })
+        .collect())
+}
+
+pub fn run(cli: &Cli, a: &Path, b: Option<&Path>, against_last: bool) -> Result<()> {
+    let old = read_selection(a)?;
+    let (new, new_label) = match b {
+        Some(b) if !against_last => (read_selection(b)?, b.display().to_string()),
+        _ => (last_selection(&cli.repo_root()?)?, "last query".to_string()),
+    };
+    let diff = ResultDiff::compute(&old, &new);
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "old": a.display().to_string(),
+                "new": new_label,
+                "unchanged": diff.is_unchanged(),
+                "added": diff.added.iter().map(|s| serde_json::json!({
+                    "path": s.path,
+                    "rank": s.rank,
+                    "score": s.score,
+                })).collect::<Vec<_>>(),
+                "removed": diff.removed.iter().map(|s| serde_json::json!({
+                    "path": s.path,
+                    "rank": s.rank,
+                    "score": s.score,
+                })).collect::<Vec<_>>(),
+                "changed": diff.common.iter().filter(|s| s.is_changed()).map(|s| serde_json::json!({
+                    "path": s.path,
+                    "old_rank": s.old_rank,
+                    "new_rank": s.new_rank,
+                    "rank_shift": s.rank_shift(),
+                    "old_score": s.old_score,
+                    "new_score": s.new_score,
+                    "score_delta": s.score_delta(),
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for s in &diff.added {
+                println!("+ {} (#{})", s.path, s.rank);
+            }
+            for s in &diff.removed {
+                println!("- {} (#{})", s.path, s.rank);
+            }
+            for s in diff.common.iter().filter(|s| s.rank_shift() != 0) {
+                println!("~ {} #{} -> #{}", s.path, s.old_rank, s.new_rank);
+            }
+        }
+        _ => {
+            println!("Comparing {} -> {new_label}", a.display());
+            println!();
+            if diff.is_unchanged() {
+                println!("Selections are identical ({} files).", new.len());
+                return Ok(());
+            }
+
+            println!(
+                "{:<2} {:<50} {:>6} {:>6} {:>6} {:>9}",
+                "", "PATH", "OLD", "NEW", "SHIFT", "DELTA"
+            );
+            println!("{}", "-".repeat(84));
+            for s in &diff.added {
+                println!(
+                    "{:<2} {:<50} {:>6} {:>6} {:>6} {:>+9.4}",
+                    "+",
+                    truncate(&s.path, 50),
+                    "-",
+                    s.rank,
+                    "",
+                    s.score
+                );
+            }
+            for s in &diff.removed {
+                println!(
+                    "{:<2} {:<50} {:>6} {:>6} {:>6} {:>+9.4}",
+                    "-",
+                    truncate(&s.path, 50),
+                    s.rank,
+                    "-",
+                    "",
+                    -s.score
+                );
+            }
+            for s in &diff.common {
+                if !s.is_changed() {
+                    continue;
+                }
+                println!(
+                    "{:<2} {:<50} {:>6} {:>6} {:>+6} {:>+9.4}",
+                    "~",
+                    truncate(&s.path, 50),
+                    s.old_rank,
+                    s.new_rank,
+                    s.rank_shift(),
+                    s.score_delta()
+                );
+            }
+            println!("{}", "-".repeat(84));
+            println!(
+                "{} added, {} removed, {} moved (of {} common)",
+                diff.added.len(),
+                diff.removed.len(),
+                diff.common.iter().filter(|s| s.rank_shift() != 0).count(),
+                diff.common.len()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.len() <= max {
+        s.to_string()
+    } else {
+        format!("...{}", &s[s.len() - max + 3..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sel(path: &str, rank: usize, score: f64) -> Selected {
+        Selected {
+            path: path.to_string(),
+            rank,
+            score,
+        }
+    }
+
+    #[test]
+    fn parse_jsonl_selection() {
+        let content = r#"{"Version":"0.3","Query":"auth","Preset":"balanced"}
+{"Path":"src/a.rs","Score":0.9,"Tokens":10}
+{"Path":"src/b.rs","Score":0.5,"Tokens":10}
+{"TotalFiles":2,"TotalTokens":20,"ScannedFiles":5}
+"#;
+        let parsed = parse_selection(content).unwrap();
+        assert_eq!(
+            parsed,
+            vec![sel("src/a.rs", 1, 0.9), sel("src/b.rs", 2, 0.5)]
+        );
+    }
+
+    #[test]
+    fn parse_json_selection() {
+        let content = r#"{"version":"0.3","files":[{"path":"x.rs","score":0.4}]}"#;
+        let parsed = parse_selection(content).unwrap();
+        assert_eq!(parsed, vec![sel("x.rs", 1, 0.4)]);
+    }
+
+    #[test]
+    fn diff_detects_added_removed_and_shifts() {
+        let old = vec![sel("a", 1, 0.9), sel("b", 2, 0.8), sel("c", 3, 0.7)];
+        let new = vec![sel("b", 1, 0.85), sel("a", 2, 0.8), sel("d", 3, 0.6)];
+        let diff = ResultDiff::compute(&old, &new);
+
+        assert_eq!(diff.added, vec![sel("d", 3, 0.6)]);
+        assert_eq!(diff.removed, vec![sel("c", 3, 0.7)]);
+        assert_eq!(diff.common.len(), 2);
+        let b = diff.common.iter().find(|s| s.path == "b").unwrap();
+        assert_eq!(b.rank_shift(), 1);
+        assert!((b.score_delta() - 0.05).abs() < 1e-9);
+        assert!(!diff.is_unchanged());
+    }
+
+    #[test]
+    fn identical_selections_are_unchanged() {
+        let old = vec![sel("a", 1, 0.9), sel("b", 2, 0.8)];
+        let diff = ResultDiff::compute(&old, &old);
+        assert!(diff.is_unchanged());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Number of commits touching the file scanned for co-changes.
+const CO_CHANGE_COMMITS: usize = 200;
+
+/// Commits touching more files than this are treated as sweeping refactors
+/// and ignored for co-change counting.
+const MAX_COMMIT_FILES: usize = 50;
+
+/// How a neighbor relates to the expanded file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Relation {
+    Test,
+    Importer,
+    Import,
+    CoChange,
+}
+
+impl Relation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Test => "test",
+            Self::Importer => "importer",
+            Self::Import => "import",
+            Self::CoChange => "co-change",
+        }
+    }
+
+    fn weight(&self) -> f64 {
+        match self {
+            Self::Test => 0.9,
+            Self::Importer => 0.8,
+            Self::Import => 0.7,
+            Self::CoChange => 0.6,
+        }
+    }
+}
+
+/// A file in the neighborhood of the expanded file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbor {
+    pub path: String,
+    pub score: f64,
+    pub relations: Vec<Relation>,
+    /// Number of commits that changed both files.
+    pub co_changes: usize,
+}
+
+/// Rank neighbors by combining their relations.
+///
+/// Each file scores by its strongest relation (co-change scaled by how often
+/// the files changed together), plus a small bonus per additional relation.
+pub fn rank_neighbors(
+    relations: &[(String, Relation)],
+    co_changes: &HashMap<String, usize>,
+) -> Vec<Neighbor> {
+    let max_co = co_changes.values().copied().max().unwrap_or(0).max(1) as f64;
+
+    let mut by_path: HashMap<&str, Neighbor> = HashMap::new();
+    let co_change_rels = co_changes.keys().map(|p| (p.as_str(), Relation::CoChange));
+    for (path, rel) in relations
+        .iter()
+        .map(|(p, r)| (p.as_str(), *r))
+        .chain(co_change_rels)
+    {
+        let entry = by_path.entry(path).or_insert_with(|| Neighbor {
+            path: path.to_string(),
+            score: 0.0,
+            relations: Vec::new(),
+            co_changes: co_changes.get(path).copied().unwrap_or(0),
+        });
+        if !entry.relations.contains(&rel) {
+            entry.relations.push(rel);
+        }
+    }
+
+    let mut neighbors: Vec<Neighbor> = by_path
+        .into_values()
+        .map(|mut n| {
+            n.relations.sort();
+            let best = n
+                .relations
+                .iter()
+                .map(|r| match r {
+                    Relation::CoChange => r.weight() * n.co_changes as f64 / max_co,
+                    _ => r.weight(),
+                })
+                .fold(0.0, f64::max);
+            n.score = best + 0.05 * (n.relations.len() - 1) as f64;
+            n
+        })
+        .collect();
+    neighbors.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    neighbors
+}
+
+/// Count how often other files changed in the same commit as `path`.
+fn co_changed_files(root: &Path, path: &str) -> HashMap<String, usize> {
+    let output = Command::new("git")
+        .args([
+            "log",
+            &format!("-n{CO_CHANGE_COMMITS}"),
+            "--full-diff",
+            "--name-only",
+            "--relative",
+            "--format=%x1e",
+            "--",
+            path,
+        ])
+        .current_dir(root)
+        .output();
+
+    let Ok(output) = output else {
+        return HashMap::new();
+    };
+    if !output.status.success() {
+        return HashMap::new();
+    }
+    parse_co_changes(&String::from_utf8_lossy(&output.stdout), path)
+}
+
+/// Parse `git log --name-only --format=%x1e` output into co-change counts.
+fn parse_co_changes(log: &str, path: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for commit in log.split('\u{1e}') {
+        let files: Vec<&str> = commit
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        if files.len() > MAX_COMMIT_FILES {
+            continue;
+        }
+        for f in files.into_iter().filter(|f| *f != path) {
+            *counts.entry(f.to_string()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+pub fn run(cli: &Cli, path: &str, top: usize) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = cli.repo_relative(&root, path);
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    if !bundle.files.iter().any(|f| f.path == path) {
+        bail!(CliError::untracked(&path, &root));
+    }
+
+    let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+    let mut relations: Vec<(String, Relation)> = Vec::new();
+    for t in super::blame_context::related_tests(&path, &bundle.files, &graph) {
+        relations.push((t, Relation::Test));
+    }
+    for p in super::test_context::paired_impls(&path, &bundle.files) {
+        relations.push((p, Relation::Test));
+    }
+    for p in graph.importers_of(&path) {
+        relations.push((p.to_string(), Relation::Importer));
+    }
+    for p in graph.imports_of(&path) {
+        relations.push((p.clone(), Relation::Import));
+    }
+
+    // Only keep co-changed files that still exist in the tree
+    let mut co_changes = co_changed_files(&root, &path);
+    co_changes.retain(|p, _| bundle.files.iter().any(|f| &f.path == p));
+
+    let mut neighbors = rank_neighbors(&relations, &co_changes);
+    neighbors.truncate(top);
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "neighbors": neighbors.iter().map(|n| serde_json::json!({
+                    "path": n.path,
+                    "score": n.score,
+                    "relations": n.relations.iter().map(Relation::as_str).collect::<Vec<_>>(),
+                    "co_changes": n.co_changes,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for n in &neighbors {
+                let rels: Vec<&str> = n.relations.iter().map(Relation::as_str).collect();
+                println!("{} ({}, {:.2})", n.path, rels.join("+"), n.score);
+            }
+        }
+        _ => {
+            if neighbors.is_empty() {
+                println!("No related files found for {path}.");
+                return Ok(());
+            }
+            println!("Neighborhood of {path}");
+            println!();
+            println!("{:<60} {:>6}  RELATIONS", "PATH", "SCORE");
+            println!("{}", "-".repeat(88));
+            for n in &neighbors {
+                let rels: Vec<String> = n
+                    .relations
+                    .iter()
+                    .map(|r| match r {
+                        Relation::CoChange => format!("co-change x{}", n.co_changes),
+                        _ => r.as_str().to_string(),
+                    })
+                    .collect();
+                println!("{:<60} {:>6.2}  {}", n.path, n.score, rels.join(", "));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_co_changes_counts_and_skips_self() {
+        let log = "\u{1e}\n\nsrc/auth.rs\nsrc/session.rs\n\u{1e}\n\nsrc/auth.rs\nsrc/session.rs\ntests/auth.rs\n";
+        let counts = parse_co_changes(log, "src/auth.rs");
+        assert_eq!(counts.get("src/session.rs"), Some(&2));
+        assert_eq!(counts.get("tests/auth.rs"), Some(&1));
+        assert!(!counts.contains_key("src/auth.rs"));
+    }
+
+    #[test]
+    fn rank_neighbors_combines_relations() {
+        let relations = vec![
+            ("tests/auth.rs".to_string(), Relation::Test),
+            ("src/server.rs".to_string(), Relation::Importer),
+            ("src/db.rs".to_string(), Relation::Import),
+        ];
+        let co_changes = HashMap::from([
+            ("src/server.rs".to_string(), 4),
+            ("README.md".to_string(), 1),
+        ]);
+
+        let ranked = rank_neighbors(&relations, &co_changes);
+        assert_eq!(ranked[0].path, "tests/auth.rs");
+        assert_eq!(ranked[1].path, "src/server.rs");
+        assert_eq!(
+            ranked[1].relations,
+            vec![Relation::Importer, Relation::CoChange]
+        );
+        assert_eq!(ranked.last().unwrap().path, "README.md");
+    }
+}
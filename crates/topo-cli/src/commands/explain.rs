@@ -1,30 +1,94 @@
 use crate::Cli;
 use crate::preset::Preset;
 use anyhow::Result;
-use topo_scanner::BundleBuilder;
+use topo_core::{Config, DropReason, ScoredFile, TokenBudget, WeightsConfig};
+use topo_score::{MultiQueryResult, SubQueryWinners};
 
-pub fn run(cli: &Cli, task: &str, top: usize, preset: Preset) -> Result<()> {
+/// How many top-ranked files `query` would return, and why it stops there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cutoff {
+    pub selected: usize,
+    /// `None` when every scored file would be returned.
+    pub reason: Option<DropReason>,
+}
+
+/// Apply the same min-score filter and budget as `query` to a ranking.
+pub fn cutoff(scored: &[ScoredFile], min_score: f64, budget: &TokenBudget) -> Cutoff {
+    let above = scored.iter().take_while(|f| f.score >= min_score).count();
+    let selected = budget.enforce(&scored[..above]).len();
+    let reason = if selected < above {
+        Some(DropReason::Budget)
+    } else if above < scored.len() {
+        Some(DropReason::Threshold)
+    } else {
+        None
+    };
+    Cutoff { selected, reason }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    cli: &Cli,
+    task: &str,
+    top: usize,
+    preset: Preset,
+    max_bytes: Option<u64>,
+    max_tokens: Option<u64>,
+    min_score: Option<f64>,
+    weights: Option<WeightsConfig>,
+    summary: bool,
+) -> Result<()> {
     let root = cli.repo_root()?;
-    let bundle = BundleBuilder::new(&root).build()?;
+    let mut bundle = cli.bundle_builder(&root).build()?;
+    super::query::apply_package_scope(cli, &root, &mut bundle);
+    super::query::apply_license_filter(cli, &mut bundle);
 
     // Load deep index for PageRank when using structural signals
     let deep_index = if preset.use_structural_signals() {
-        topo_index::load(&root)?
+        super::query::load_index_for(&root, task)?
     } else {
         None
     };
 
-    let scored = super::query::score_files(task, &bundle.files, preset, deep_index.as_ref());
+    let config = Config::load(&root)?;
+    let scoring = super::query::Scoring::from_config(&config)
+        .include_vendored(cli.include_vendored)
+        .weights(&config, &preset, weights.as_ref())?
+        .git_history(&root, &preset);
+    let weights = scoring.effective_weights(&preset);
+    let MultiQueryResult {
+        files: scored,
+        sub_queries,
+        expansion,
+    } = super::query::score_files_with_sub_queries(
+        task,
+        &bundle.files,
+        &preset,
+        deep_index.as_ref(),
+        &scoring,
+    );
 
     let display_count = top.min(scored.len());
     let results = &scored[..display_count];
 
+    // Reconcile with what `query` would return under the same settings
+    let effective_min_score = min_score.unwrap_or(preset.default_min_score());
+    let effective_max_bytes = max_bytes.unwrap_or(preset.default_max_bytes());
+    let budget = TokenBudget {
+        max_bytes: Some(effective_max_bytes),
+        max_tokens,
+    };
+    let cut = cutoff(&scored, effective_min_score, &budget);
+
     match cli.effective_format() {
         crate::OutputFormat::Json | crate::OutputFormat::Jsonl => {
-            let output: Vec<serde_json::Value> = results
+            let files: Vec<serde_json::Value> = results
                 .iter()
-                .map(|f| {
+                .enumerate()
+                .map(|(i, f)| {
                     serde_json::json!({
+                        "rank": i + 1,
+                        "selected": i < cut.selected,
                         "path": f.path,
                         "score": f.score,
                         "signals": {
@@ -32,40 +96,142 @@ pub fn run(cli: &Cli, task: &str, top: usize, preset: Preset) -> Result<()> {
                             "heuristic": f.signals.heuristic,
                             "pagerank": f.signals.pagerank,
                             "git_recency": f.signals.git_recency,
+                            "co_change": f.signals.co_change,
+                            "churn": f.signals.churn,
+                            "file_type": f.signals.file_type,
+                            "entry_point": f.signals.entry_point,
+                            "negated": f.signals.negated,
+                            "vendored": f.signals.vendored,
                         },
                         "tokens": f.tokens,
                         "language": f.language.as_str(),
                         "role": f.role.as_str(),
+                        "renamed_from": scoring.renamed_from.get(&f.path),
+                        "sub_query_wins": sub_query_wins(&sub_queries, &f.path),
                     })
                 })
                 .collect();
+            if !summary {
+                println!("{}", serde_json::to_string_pretty(&files)?);
+                return Ok(());
+            }
+            let output = serde_json::json!({
+                "query": task,
+                "preset": preset.as_str(),
+                "min_score": effective_min_score,
+                "max_bytes": effective_max_bytes,
+                "max_tokens": max_tokens,
+                "expansion": expansion,
+                "weights": {
+                    "bm25f": weights.bm25f,
+                    "heuristic": weights.heuristic,
+                    "pagerank": weights.pagerank,
+                    "git_recency": weights.git_recency,
+                    "co_change": weights.co_change,
+                    "churn": weights.churn,
+                    "rrf_k": weights.rrf_k,
+                },
+                "scored_files": scored.len(),
+                "selected_files": cut.selected,
+                "selected_tokens": scored[..cut.selected].iter().map(|f| f.tokens).sum::<u64>(),
+                "cutoff": cut.reason.map(|reason| serde_json::json!({
+                    "after_rank": cut.selected,
+                    "reason": reason.as_str(),
+                })),
+                "files": files,
+            });
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
         _ => {
             println!("Score breakdown for query: \"{task}\"");
+            println!(
+                "Weights: bm25f {:.2}, heuristic {:.2}, pagerank {}, git_recency {}, co_change {}, churn {}, rrf_k {}",
+                weights.bm25f,
+                weights.heuristic,
+                weights.pagerank,
+                weights.git_recency,
+                weights.co_change,
+                weights.churn,
+                weights.rrf_k
+            );
+            if !expansion.is_empty() {
+                println!("Expanded with: {}", expansion.join(", "));
+            }
             println!("Showing top {display_count} of {} files\n", scored.len());
 
             println!(
-                "{:<50} {:>8} {:>8} {:>8} {:>8} {:>8}",
-                "PATH", "TOTAL", "BM25F", "HEUR", "PR", "ROLE"
+                "{:<50} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                "PATH", "TOTAL", "BM25F", "HEUR", "PR", "GIT", "CO", "CHURN", "TYPE", "ROLE"
             );
-            println!("{}", "-".repeat(95));
+            println!("{}", "-".repeat(131));
 
-            for f in results {
+            for (i, f) in results.iter().enumerate() {
+                if i == cut.selected && cut.reason.is_some() {
+                    let reason = cut.reason.map(|r| r.as_str()).unwrap_or_default();
+                    println!("{:-^131}", format!(" query cutoff ({reason}) "));
+                }
                 let pr = f
                     .signals
                     .pagerank
                     .map(|v| format!("{v:.4}"))
                     .unwrap_or_else(|| "-".to_string());
+                let recency = f
+                    .signals
+                    .git_recency
+                    .map(|v| format!("{v:.4}"))
+                    .unwrap_or_else(|| "-".to_string());
+                let co_change = f
+                    .signals
+                    .co_change
+                    .map(|v| format!("{v:.4}"))
+                    .unwrap_or_else(|| "-".to_string());
+                let churn = f
+                    .signals
+                    .churn
+                    .map(|v| format!("{v:.4}"))
+                    .unwrap_or_else(|| "-".to_string());
+                let file_type = f
+                    .signals
+                    .file_type
+                    .map(|v| format!("x{v:.2}"))
+                    .unwrap_or_else(|| "-".to_string());
                 println!(
-                    "{:<50} {:>8.4} {:>8.4} {:>8.4} {:>8} {:>8}",
+                    "{:<50} {:>8.4} {:>8.4} {:>8.4} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
                     truncate(&f.path, 50),
                     f.score,
                     f.signals.bm25f,
                     f.signals.heuristic,
                     pr,
+                    recency,
+                    co_change,
+                    churn,
+                    file_type,
                     f.role.as_str(),
                 );
+                if let Some(previous) = scoring.renamed_from.get(&f.path) {
+                    println!("  renamed from {previous}");
+                }
+            }
+
+            println!(
+                "\nquery would select {} files (min_score {effective_min_score}, max_bytes {effective_max_bytes}{})",
+                cut.selected,
+                max_tokens
+                    .map(|t| format!(", max_tokens {t}"))
+                    .unwrap_or_default(),
+            );
+
+            if !sub_queries.is_empty() {
+                println!("\nSub-queries ({}):", sub_queries.len());
+                for (i, sq) in sub_queries.iter().enumerate() {
+                    let winners = if sq.paths.is_empty() {
+                        "-".to_string()
+                    } else {
+                        sq.paths.join(", ")
+                    };
+                    println!("  {}. \"{}\"", i + 1, sq.query);
+                    println!("     -> {winners}");
+                }
             }
         }
     }
@@ -73,6 +239,15 @@ pub fn run(cli: &Cli, task: &str, top: usize, preset: Preset) -> Result<()> {
     Ok(())
 }
 
+/// Sub-queries for which `path` was among the top-ranked files.
+pub fn sub_query_wins<'a>(sub_queries: &'a [SubQueryWinners], path: &str) -> Vec<&'a str> {
+    sub_queries
+        .iter()
+        .filter(|sq| sq.paths.iter().any(|p| p == path))
+        .map(|sq| sq.query.as_str())
+        .collect()
+}
+
 fn truncate(s: &str, max: usize) -> String {
     if s.len() <= max {
         s.to_string()
@@ -80,3 +255,58 @@ fn truncate(s: &str, max: usize) -> String {
         format!("...{}", &s[s.len() - max + 3..])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::{FileRole, Language, SignalBreakdown};
+
+    fn scored(path: &str, score: f64, tokens: u64) -> ScoredFile {
+        ScoredFile {
+            path: path.to_string(),
+            score,
+            signals: SignalBreakdown::default(),
+            tokens,
+            language: Language::Rust,
+            role: FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn cutoff_reports_budget_then_threshold() {
+        let files = vec![
+            scored("a.rs", 0.9, 100),
+            scored("b.rs", 0.8, 100),
+            scored("c.rs", 0.01, 100),
+        ];
+        let unlimited = TokenBudget {
+            max_bytes: None,
+            max_tokens: None,
+        };
+        assert_eq!(
+            cutoff(&files, 0.1, &unlimited),
+            Cutoff {
+                selected: 2,
+                reason: Some(DropReason::Threshold)
+            }
+        );
+
+        let tight = TokenBudget {
+            max_bytes: None,
+            max_tokens: Some(150),
+        };
+        assert_eq!(
+            cutoff(&files, 0.1, &tight),
+            Cutoff {
+                selected: 1,
+                reason: Some(DropReason::Budget)
+            }
+        );
+        assert_eq!(cutoff(&files, 0.0, &unlimited).reason, None);
+    }
+}
@@ -1,98 +1,155 @@
 use crate::Cli;
+use crate::stats::{self, StatsEvent};
 use anyhow::Result;
-use std::collections::HashSet;
-use std::fs;
-use std::io::BufRead;
+use std::collections::{BTreeMap, HashSet};
 
-/// Stats entry from `.topo/stats.jsonl`.
-#[derive(serde::Deserialize)]
-struct StatsEntry {
-    #[allow(dead_code)]
-    timestamp: String,
-    event: String,
-    #[serde(default)]
-    path: Option<String>,
-    #[serde(default)]
-    files_suggested: Option<usize>,
-    #[serde(default)]
-    tokens_suggested: Option<u64>,
+/// Totals over the stats log.
+#[derive(Debug, Default, PartialEq)]
+struct Gain {
+    sessions: u64,
+    suggestion_events: u64,
+    files_suggested: u64,
+    tokens_suggested: u64,
+    files_opened: usize,
+    /// Latencies of the queries that recorded one, sorted.
+    latencies_ms: Vec<u64>,
+    presets: BTreeMap<String, u64>,
+    /// Tokens suggested and allowed, over queries that recorded a budget.
+    budget_used: u64,
+    budget_available: u64,
+    /// Queries that recorded truncation, and how many were truncated.
+    truncation_known: u64,
+    truncated: u64,
+}
+
+impl Gain {
+    fn from_events(events: &[StatsEvent]) -> Self {
+        let mut gain = Self::default();
+        let mut files_opened: HashSet<&str> = HashSet::new();
+        for event in events {
+            match event.event.as_str() {
+                "session_start" => gain.sessions += 1,
+                "topo_query" => {
+                    gain.suggestion_events += 1;
+                    gain.files_suggested += event.files_suggested.unwrap_or(0) as u64;
+                    gain.tokens_suggested += event.tokens_suggested.unwrap_or(0);
+                    gain.latencies_ms.extend(event.latency_ms);
+                    if let Some(preset) = &event.preset {
+                        *gain.presets.entry(preset.clone()).or_default() += 1;
+                    }
+                    if let Some(budget) = event.budget_tokens {
+                        gain.budget_used += event.tokens_suggested.unwrap_or(0);
+                        gain.budget_available += budget;
+                    }
+                    if let Some(truncated) = event.truncated {
+                        gain.truncation_known += 1;
+                        gain.truncated += u64::from(truncated);
+                    }
+                }
+                "file_read" => {
+                    if let Some(path) = &event.path {
+                        files_opened.insert(path);
+                    }
+                }
+                _ => {}
+            }
+        }
+        gain.files_opened = files_opened.len();
+        gain.latencies_ms.sort_unstable();
+        gain
+    }
+
+    fn avg_latency_ms(&self) -> Option<f64> {
+        (!self.latencies_ms.is_empty())
+            .then(|| self.latencies_ms.iter().sum::<u64>() as f64 / self.latencies_ms.len() as f64)
+    }
+
+    /// Nearest-rank 95th percentile latency.
+    fn p95_latency_ms(&self) -> Option<u64> {
+        let n = self.latencies_ms.len();
+        let rank = (n as f64 * 0.95).ceil() as usize;
+        self.latencies_ms.get(rank.max(1) - 1).copied()
+    }
+
+    /// Share of the available budget that suggestions filled.
+    fn budget_usage(&self) -> Option<f64> {
+        (self.budget_available > 0).then(|| self.budget_used as f64 / self.budget_available as f64)
+    }
 }
 
 pub fn run(cli: &Cli) -> Result<()> {
     let root = cli.repo_root()?;
-    let stats_path = root.join(".topo/stats.jsonl");
-
-    if !stats_path.exists() {
+    let Some(events) = stats::load(&root)? else {
         println!("No topo stats found.");
         println!();
         println!("Stats are collected automatically when Claude Code hooks are installed.");
         println!("Run `topo init` to set up hooks.");
         return Ok(());
-    }
-
-    let file = fs::File::open(&stats_path)?;
-    let reader = std::io::BufReader::new(file);
-
-    let mut sessions = 0u64;
-    let mut total_files_suggested = 0u64;
-    let mut total_tokens_suggested = 0u64;
-    let mut files_opened: HashSet<String> = HashSet::new();
-    let mut suggestion_events = 0u64;
-
-    for line in reader.lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
-        }
-        let entry: StatsEntry = match serde_json::from_str(&line) {
-            Ok(e) => e,
-            Err(_) => continue, // skip malformed lines
-        };
-
-        match entry.event.as_str() {
-            "session_start" => {
-                sessions += 1;
-            }
-            "topo_query" => {
-                suggestion_events += 1;
-                if let Some(n) = entry.files_suggested {
-                    total_files_suggested += n as u64;
-                }
-                if let Some(t) = entry.tokens_suggested {
-                    total_tokens_suggested += t;
-                }
-            }
-            "file_read" => {
-                if let Some(path) = entry.path {
-                    files_opened.insert(path);
-                }
-            }
-            _ => {}
-        }
-    }
+    };
+    let gain = Gain::from_events(&events);
 
     match cli.effective_format() {
         crate::OutputFormat::Json | crate::OutputFormat::Jsonl => {
             let output = serde_json::json!({
-                "sessions": sessions,
-                "suggestion_events": suggestion_events,
-                "files_suggested": total_files_suggested,
-                "files_opened": files_opened.len(),
-                "tokens_suggested": total_tokens_suggested,
+                "sessions": gain.sessions,
+                "suggestion_events": gain.suggestion_events,
+                "files_suggested": gain.files_suggested,
+                "files_opened": gain.files_opened,
+                "tokens_suggested": gain.tokens_suggested,
+                "latency_ms": {
+                    "queries": gain.latencies_ms.len(),
+                    "avg": gain.avg_latency_ms(),
+                    "p95": gain.p95_latency_ms(),
+                },
+                "presets": gain.presets,
+                "budget": {
+                    "tokens_used": gain.budget_used,
+                    "tokens_available": gain.budget_available,
+                    "usage": gain.budget_usage(),
+                },
+                "truncated": {
+                    "queries": gain.truncation_known,
+                    "truncated": gain.truncated,
+                },
             });
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
         _ => {
             println!("Topo context savings:");
-            println!("  Sessions:         {sessions}");
-            println!("  Suggestions:      {suggestion_events}");
-            println!("  Files suggested:  {total_files_suggested}");
-            println!("  Files opened:     {}", files_opened.len());
-            println!("  Tokens suggested: {total_tokens_suggested}");
-            if suggestion_events > 0 {
-                let avg = total_files_suggested as f64 / suggestion_events as f64;
+            println!("  Sessions:         {}", gain.sessions);
+            println!("  Suggestions:      {}", gain.suggestion_events);
+            println!("  Files suggested:  {}", gain.files_suggested);
+            println!("  Files opened:     {}", gain.files_opened);
+            println!("  Tokens suggested: {}", gain.tokens_suggested);
+            if gain.suggestion_events > 0 {
+                let avg = gain.files_suggested as f64 / gain.suggestion_events as f64;
                 println!("  Avg files/query:  {avg:.1}");
             }
+            if let (Some(avg), Some(p95)) = (gain.avg_latency_ms(), gain.p95_latency_ms()) {
+                println!("  Query latency:    {avg:.0} ms avg, {p95} ms p95");
+            }
+            if let Some(usage) = gain.budget_usage() {
+                println!(
+                    "  Budget used:      {:.0}% ({} of {} tokens)",
+                    usage * 100.0,
+                    gain.budget_used,
+                    gain.budget_available
+                );
+            }
+            if gain.truncation_known > 0 {
+                println!(
+                    "  Truncated:        {} of {} queries",
+                    gain.truncated, gain.truncation_known
+                );
+            }
+            if !gain.presets.is_empty() {
+                let presets: Vec<String> = gain
+                    .presets
+                    .iter()
+                    .map(|(name, n)| format!("{name} {n}"))
+                    .collect();
+                println!("  Presets:          {}", presets.join(", "));
+            }
         }
     }
 
@@ -106,7 +163,7 @@ mod tests {
     #[test]
     fn parses_stats_entries() {
         let json = r#"{"timestamp":"2025-01-01T00:00:00Z","event":"topo_query","files_suggested":10,"tokens_suggested":5000}"#;
-        let entry: StatsEntry = serde_json::from_str(json).unwrap();
+        let entry: StatsEvent = serde_json::from_str(json).unwrap();
         assert_eq!(entry.event, "topo_query");
         assert_eq!(entry.files_suggested, Some(10));
         assert_eq!(entry.tokens_suggested, Some(5000));
@@ -116,7 +173,7 @@ mod tests {
     fn parses_file_read_entry() {
         let json =
             r#"{"timestamp":"2025-01-01T00:00:00Z","event":"file_read","path":"src/main.rs"}"#;
-        let entry: StatsEntry = serde_json::from_str(json).unwrap();
+        let entry: StatsEvent = serde_json::from_str(json).unwrap();
         assert_eq!(entry.event, "file_read");
         assert_eq!(entry.path, Some("src/main.rs".to_string()));
     }
@@ -124,7 +181,48 @@ mod tests {
     #[test]
     fn parses_session_start_entry() {
         let json = r#"{"timestamp":"2025-01-01T00:00:00Z","event":"session_start"}"#;
-        let entry: StatsEntry = serde_json::from_str(json).unwrap();
+        let entry: StatsEvent = serde_json::from_str(json).unwrap();
         assert_eq!(entry.event, "session_start");
     }
+
+    #[test]
+    fn totals_mix_v1_and_v2_events() {
+        let lines = [
+            r#"{"timestamp":"2025-01-01T00:00:00Z","event":"session_start"}"#,
+            r#"{"timestamp":"2025-01-01T00:00:01Z","event":"topo_query","files_suggested":4,"tokens_suggested":1000}"#,
+            r#"{"v":2,"timestamp":"2025-01-01T00:00:02Z","event":"topo_query","files_suggested":6,"tokens_suggested":3000,"latency_ms":200,"preset":"fast","budget_tokens":12500,"truncated":true}"#,
+            r#"{"v":2,"timestamp":"2025-01-01T00:00:03Z","event":"topo_query","files_suggested":2,"tokens_suggested":2000,"latency_ms":100,"preset":"fast","budget_tokens":12500,"truncated":false}"#,
+            r#"{"timestamp":"2025-01-01T00:00:04Z","event":"file_read","path":"src/main.rs"}"#,
+            r#"{"timestamp":"2025-01-01T00:00:05Z","event":"file_read","path":"src/main.rs"}"#,
+        ];
+        let events: Vec<StatsEvent> = lines
+            .iter()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let gain = Gain::from_events(&events);
+
+        assert_eq!(gain.sessions, 1);
+        assert_eq!(gain.suggestion_events, 3);
+        assert_eq!(gain.files_suggested, 12);
+        assert_eq!(gain.tokens_suggested, 6000);
+        assert_eq!(gain.files_opened, 1);
+        assert_eq!(gain.latencies_ms, [100, 200]);
+        assert_eq!(gain.avg_latency_ms(), Some(150.0));
+        assert_eq!(gain.p95_latency_ms(), Some(200));
+        assert_eq!(gain.presets.get("fast"), Some(&2));
+        assert_eq!(gain.budget_usage(), Some(0.2));
+        assert_eq!((gain.truncated, gain.truncation_known), (1, 2));
+    }
+
+    #[test]
+    fn v1_only_logs_have_no_v2_totals() {
+        let gain = Gain::from_events(&[StatsEvent {
+            event: "topo_query".to_string(),
+            files_suggested: Some(3),
+            ..Default::default()
+        }]);
+        assert_eq!(gain.avg_latency_ms(), None);
+        assert_eq!(gain.p95_latency_ms(), None);
+        assert_eq!(gain.budget_usage(), None);
+    }
 }
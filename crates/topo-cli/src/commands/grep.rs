@@ -0,0 +1,284 @@
+use crate::error::CliError;
+use crate::preset::Preset;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use rayon::prelude::*;
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::fs;
+use topo_core::Config;
+
+/// Files listed when `--top` isn't given.
+const DEFAULT_TOP: usize = 20;
+
+/// How `topo grep` matches lines and how much it prints around them.
+#[derive(Debug, Clone, Default)]
+pub struct GrepOptions {
+    /// Match the pattern literally rather than as a regex.
+    pub fixed_strings: bool,
+    pub ignore_case: bool,
+    /// Lines of context before each match.
+    pub before: usize,
+    /// Lines of context after each match.
+    pub after: usize,
+    pub top: Option<usize>,
+}
+
+/// A line printed for a file: a match or context around one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrepLine {
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+    pub is_match: bool,
+}
+
+/// The matches in one file, with the file's relevance to the pattern.
+struct FileMatches {
+    path: String,
+    score: f64,
+    matches: usize,
+    lines: Vec<GrepLine>,
+}
+
+pub fn run(cli: &Cli, pattern: &str, options: GrepOptions) -> Result<()> {
+    let regex = matcher(pattern, &options)?;
+    let root = cli.repo_root()?;
+    let mut bundle = cli.bundle_builder(&root).build()?;
+    super::query::apply_package_scope(cli, &root, &mut bundle);
+    super::query::apply_license_filter(cli, &mut bundle);
+    let policy = super::query::content_policy(&root)?;
+
+    let mut files: Vec<FileMatches> = bundle
+        .files
+        .par_iter()
+        .filter(|f| policy.check(&f.path, f.size).is_ok())
+        .filter_map(|f| {
+            // Binary and unreadable files never match
+            let content = fs::read_to_string(root.join(&f.path)).ok()?;
+            let mut lines = search(&content, &regex, options.before, options.after);
+            if lines.is_empty() {
+                return None;
+            }
+            for line in &mut lines {
+                if let Ok(redacted) = policy.embed(&f.path, f.size, &line.text) {
+                    line.text = redacted.text;
+                }
+            }
+            Some(FileMatches {
+                path: f.path.clone(),
+                score: 0.0,
+                matches: lines.iter().filter(|l| l.is_match).count(),
+                lines,
+            })
+        })
+        .collect();
+
+    // Rank by the scorer's relevance to the pattern's words, scored against
+    // the whole bundle so IDF and PageRank see the rest of the repository
+    let task = terms(pattern, options.fixed_strings);
+    if !files.is_empty() && !task.is_empty() {
+        let config = Config::load(&root)?;
+        let preset = Preset::resolve(None, &config)?;
+        let deep_index = if preset.use_structural_signals() {
+            super::query::load_index_for(&root, &task)?
+        } else {
+            None
+        };
+        let scoring = super::query::Scoring::from_config(&config)
+            .include_vendored(cli.include_vendored)
+            .weights(&config, &preset, None)?
+            .git_history(&root, &preset);
+        let scores: HashMap<String, f64> =
+            super::query::score_files(&task, &bundle.files, &preset, deep_index.as_ref(), &scoring)
+                .into_iter()
+                .map(|f| (f.path, f.score))
+                .collect();
+        for file in &mut files {
+            file.score = scores.get(&file.path).copied().unwrap_or(0.0);
+        }
+    }
+    files.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.matches.cmp(&a.matches))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    let total_files = files.len();
+    let total_matches: usize = files.iter().map(|f| f.matches).sum();
+    files.truncate(options.top.unwrap_or(DEFAULT_TOP));
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "pattern": pattern,
+                "total_files": total_files,
+                "total_matches": total_matches,
+                "files": files.iter().map(|f| serde_json::json!({
+                    "path": f.path,
+                    "score": f.score,
+                    "matches": f.matches,
+                    "lines": f.lines.iter().map(|l| serde_json::json!({
+                        "line": l.line,
+                        "text": l.text,
+                        "match": l.is_match,
+                    })).collect::<Vec<_>>(),
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            // grep's own layout: `:` after a match's line number, `-` after
+            // context, `--` between groups when there is context
+            let grouped = options.before > 0 || options.after > 0;
+            let mut first = true;
+            for f in &files {
+                for (i, l) in f.lines.iter().enumerate() {
+                    if grouped && !first && (i == 0 || l.line != f.lines[i - 1].line + 1) {
+                        println!("--");
+                    }
+                    first = false;
+                    let sep = if l.is_match { ':' } else { '-' };
+                    println!("{}{sep}{}{sep}{}", f.path, l.line, l.text);
+                }
+            }
+        }
+        _ => {
+            if files.is_empty() {
+                println!("No lines match {pattern:?}.");
+                return Ok(());
+            }
+            println!(
+                "{total_matches} {} for {pattern:?} in {total_files} {}, most relevant first",
+                plural(total_matches, "match", "matches"),
+                plural(total_files, "file", "files"),
+            );
+            for f in &files {
+                println!();
+                println!(
+                    "{}  ({} {}, score {:.3})",
+                    f.path,
+                    f.matches,
+                    plural(f.matches, "match", "matches"),
+                    f.score
+                );
+                for (i, l) in f.lines.iter().enumerate() {
+                    if i > 0 && l.line != f.lines[i - 1].line + 1 {
+                        println!("{:>6}", "...");
+                    }
+                    let sep = if l.is_match { ':' } else { ' ' };
+                    println!("{:>6}{sep} {}", l.line, l.text);
+                }
+            }
+            if files.len() < total_files {
+                println!();
+                println!("... {} more files (raise --top)", total_files - files.len());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn plural<'a>(n: usize, one: &'a str, many: &'a str) -> &'a str {
+    if n == 1 { one } else { many }
+}
+
+/// The regex for `pattern`, escaped first under `--fixed-strings`.
+fn matcher(pattern: &str, options: &GrepOptions) -> Result<Regex> {
+    let source = if options.fixed_strings {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+    match RegexBuilder::new(&source)
+        .case_insensitive(options.ignore_case)
+        .build()
+    {
+        Ok(regex) => Ok(regex),
+        Err(e) => bail!(
+            CliError::usage(format!("invalid pattern {pattern:?}: {e}"))
+                .hint("Use -F to match the pattern literally.")
+        ),
+    }
+}
+
+/// The words of `pattern` scored as a task. Regex escapes such as `\s`
+/// and `\w` are dropped so their letters don't count as words.
+pub fn terms(pattern: &str, fixed_strings: bool) -> String {
+    let mut text = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && !fixed_strings {
+            chars.next();
+            text.push(' ');
+        } else if c.is_alphanumeric() || c == '_' {
+            text.push(c);
+        } else {
+            text.push(' ');
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The lines of `content` matching `regex`, with `before` and `after`
+/// lines of context around each; overlapping context is printed once.
+pub fn search(content: &str, regex: &Regex, before: usize, after: usize) -> Vec<GrepLine> {
+    let lines: Vec<&str> = content.lines().collect();
+    let matched: Vec<bool> = lines.iter().map(|l| regex.is_match(l)).collect();
+    let mut shown = vec![false; lines.len()];
+    for i in (0..lines.len()).filter(|&i| matched[i]) {
+        let end = (i + after).min(lines.len() - 1);
+        shown[i.saturating_sub(before)..=end].fill(true);
+    }
+    lines
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| shown[i])
+        .map(|(i, text)| GrepLine {
+            line: i + 1,
+            text: text.to_string(),
+            is_match: matched[i],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_overlapping_context() {
+        let content = "a\nfoo one\nb\nc\nfoo two\nd\ne\nf\ng\nfoo three\n";
+        let regex = Regex::new("foo").unwrap();
+        let lines: Vec<(usize, bool)> = search(content, &regex, 1, 1)
+            .iter()
+            .map(|l| (l.line, l.is_match))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (1, false),
+                (2, true),
+                (3, false),
+                (4, false),
+                (5, true),
+                (6, false),
+                (9, false),
+                (10, true),
+            ]
+        );
+        assert!(search(content, &Regex::new("bar").unwrap(), 2, 2).is_empty());
+    }
+
+    #[test]
+    fn pattern_terms_skip_regex_escapes() {
+        assert_eq!(terms(r"fn\s+parse_\w+", false), "fn parse_");
+        assert_eq!(terms("refresh_token(", true), "refresh_token");
+        assert_eq!(terms(r"a\b", true), "a b");
+    }
+}
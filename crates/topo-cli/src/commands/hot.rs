@@ -0,0 +1,190 @@
+use crate::{Cli, OutputFormat};
+use anyhow::Result;
+use std::collections::HashMap;
+use topo_core::Config;
+use topo_score::{Churn, GraphMetrics};
+
+/// Files listed when `--top` isn't given.
+const DEFAULT_TOP: usize = 20;
+
+/// A file that both changes often and sits at the center of the import
+/// graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hotspot {
+    pub path: String,
+    /// Commits touching the file within the churn window.
+    pub commits: u32,
+    /// Commit count normalized to [0.0, 1.0].
+    pub churn: f64,
+    /// Normalized PageRank.
+    pub centrality: f64,
+    /// Fan-in, fan-out and depth from the entry points.
+    pub metrics: GraphMetrics,
+    /// `churn × centrality`.
+    pub score: f64,
+}
+
+/// Files committed within the window, hottest first; ties go to the more
+/// often changed file, then by path.
+pub fn rank_hotspots<'a>(
+    paths: impl Iterator<Item = &'a str>,
+    churn: &Churn,
+    pagerank: &HashMap<String, f64>,
+    metrics: &HashMap<String, GraphMetrics>,
+) -> Vec<Hotspot> {
+    let scores = churn.scores();
+    let mut hotspots: Vec<Hotspot> = paths
+        .filter_map(|path| {
+            let churn_score = scores.get(path).copied()?;
+            let centrality = pagerank.get(path).copied().unwrap_or(0.0);
+            Some(Hotspot {
+                path: path.to_string(),
+                commits: churn.commits(path),
+                churn: churn_score,
+                centrality,
+                metrics: metrics.get(path).copied().unwrap_or_default(),
+                score: churn_score * centrality,
+            })
+        })
+        .collect();
+    hotspots.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.commits.cmp(&a.commits))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    hotspots
+}
+
+pub fn run(cli: &Cli, days: Option<u32>, top: Option<usize>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let config = Config::load(&root)?;
+    let window = days.unwrap_or_else(|| Churn::window(&config.churn));
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let churn = Churn::load(&root, window)?;
+    if churn.is_empty() {
+        log::warn!("no commits in the last {window} days; is this a git repository?");
+    }
+    let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+    let pagerank = graph.normalized_pagerank_with(&super::query::pagerank_params(&root)?);
+    let metrics = graph.metrics(
+        bundle
+            .files
+            .iter()
+            .filter(|f| f.entry_point)
+            .map(|f| f.path.as_str()),
+    );
+    let mut hotspots = rank_hotspots(
+        bundle.files.iter().map(|f| f.path.as_str()),
+        &churn,
+        &pagerank,
+        &metrics,
+    );
+    let total = hotspots.len();
+    hotspots.truncate(top.unwrap_or(DEFAULT_TOP));
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "window_days": window,
+                "changed_files": total,
+                "hotspots": hotspots.iter().map(|h| serde_json::json!({
+                    "path": h.path,
+                    "commits": h.commits,
+                    "churn": h.churn,
+                    "centrality": h.centrality,
+                    "fan_in": h.metrics.fan_in,
+                    "fan_out": h.metrics.fan_out,
+                    "depth": h.metrics.depth,
+                    "score": h.score,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for h in &hotspots {
+                println!("{} ({} commits, {:.2})", h.path, h.commits, h.score);
+            }
+        }
+        _ => {
+            if hotspots.is_empty() {
+                println!("No files changed in the last {window} days.");
+                return Ok(());
+            }
+            println!("Hotspots over the last {window} days: {total} changed files");
+            println!();
+            println!(
+                "{:<50} {:>7} {:>7} {:>10} {:>6} {:>7} {:>5} {:>7}",
+                "PATH", "COMMITS", "CHURN", "CENTRALITY", "FAN-IN", "FAN-OUT", "DEPTH", "HOT"
+            );
+            println!("{}", "-".repeat(106));
+            for h in &hotspots {
+                let depth = h.metrics.depth.map_or("-".to_string(), |d| d.to_string());
+                println!(
+                    "{:<50} {:>7} {:>7.3} {:>10.3} {:>6} {:>7} {:>5} {:>7.3}",
+                    h.path,
+                    h.commits,
+                    h.churn,
+                    h.centrality,
+                    h.metrics.fan_in,
+                    h.metrics.fan_out,
+                    depth,
+                    h.score
+                );
+            }
+            if hotspots.len() < total {
+                println!("... {} more (raise --top)", total - hotspots.len());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotspots_need_both_churn_and_centrality() {
+        let churn = Churn::from_counts(HashMap::from([
+            ("src/core.rs".to_string(), 4),
+            ("src/leaf.rs".to_string(), 12),
+            ("CHANGELOG.md".to_string(), 30),
+            ("src/gone.rs".to_string(), 2),
+        ]));
+        let pagerank = HashMap::from([
+            ("src/core.rs".to_string(), 1.0),
+            ("src/leaf.rs".to_string(), 0.2),
+            ("src/stable.rs".to_string(), 0.9),
+        ]);
+        let paths = [
+            "CHANGELOG.md",
+            "src/core.rs",
+            "src/leaf.rs",
+            "src/stable.rs",
+        ];
+        let metrics = HashMap::from([(
+            "src/core.rs".to_string(),
+            GraphMetrics {
+                fan_in: 3,
+                fan_out: 1,
+                depth: Some(1),
+            },
+        )]);
+        let ranked = rank_hotspots(paths.into_iter(), &churn, &pagerank, &metrics);
+        let order: Vec<&str> = ranked.iter().map(|h| h.path.as_str()).collect();
+        // Unchanged and unscanned files are left out; a busy file nothing
+        // imports trails
+        assert_eq!(order, ["src/core.rs", "src/leaf.rs", "CHANGELOG.md"]);
+        assert_eq!(ranked[0].commits, 4);
+        assert_eq!(ranked[0].metrics.fan_in, 3);
+        assert_eq!(ranked[1].metrics, GraphMetrics::default());
+        assert_eq!(ranked[2].score, 0.0);
+    }
+}
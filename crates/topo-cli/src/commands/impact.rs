@@ -0,0 +1,172 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::collections::{HashMap, HashSet, VecDeque};
+use topo_score::ImportGraph;
+
+/// A file that transitively imports the target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependent {
+    pub path: String,
+    /// Number of reverse import edges from the target (1 = direct importer).
+    pub depth: usize,
+    /// The file this one imports on its shortest path to the target.
+    pub via: String,
+    pub pagerank: f64,
+}
+
+/// Walk reverse import edges from `path` breadth-first.
+///
+/// Each dependent is reported once, at its shortest distance. `max_depth`
+/// limits how many edges are followed; `None` walks the full closure.
+pub fn dependents(graph: &ImportGraph, path: &str, max_depth: Option<usize>) -> Vec<Dependent> {
+    let mut seen: HashSet<&str> = HashSet::from([path]);
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(path, 0)]);
+    let mut found = Vec::new();
+
+    while let Some((current, depth)) = queue.pop_front() {
+        if max_depth.is_some_and(|max| depth >= max) {
+            continue;
+        }
+        for importer in graph.importers_of(current) {
+            if seen.insert(importer) {
+                found.push(Dependent {
+                    path: importer.to_string(),
+                    depth: depth + 1,
+                    via: current.to_string(),
+                    pagerank: 0.0,
+                });
+                queue.push_back((importer, depth + 1));
+            }
+        }
+    }
+    found
+}
+
+/// Attach PageRank and order dependents by it, nearest first on ties.
+pub fn rank_dependents(
+    mut found: Vec<Dependent>,
+    pagerank: &HashMap<String, f64>,
+) -> Vec<Dependent> {
+    for d in &mut found {
+        d.pagerank = pagerank.get(&d.path).copied().unwrap_or(0.0);
+    }
+    found.sort_by(|a, b| {
+        b.pagerank
+            .partial_cmp(&a.pagerank)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.depth.cmp(&b.depth))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    found
+}
+
+pub fn run(cli: &Cli, path: &str, depth: Option<usize>, top: Option<usize>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = cli.repo_relative(&root, path);
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    if !bundle.files.iter().any(|f| f.path == path) {
+        bail!(CliError::untracked(&path, &root));
+    }
+
+    let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+    let found = dependents(&graph, &path, depth);
+    let total = found.len();
+    let params = super::query::pagerank_params(&root)?;
+    let mut ranked = rank_dependents(found, &graph.normalized_pagerank_with(&params));
+    if let Some(top) = top {
+        ranked.truncate(top);
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "max_depth": depth,
+                "total_dependents": total,
+                "dependents": ranked.iter().map(|d| serde_json::json!({
+                    "path": d.path,
+                    "depth": d.depth,
+                    "via": d.via,
+                    "pagerank": d.pagerank,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for d in &ranked {
+                println!("{} (depth {}, {:.2})", d.path, d.depth, d.pagerank);
+            }
+        }
+        _ => {
+            if ranked.is_empty() {
+                println!("Nothing imports {path}.");
+                return Ok(());
+            }
+            println!("Impact of changing {path}: {total} dependent files");
+            println!();
+            println!("{:<50} {:>5} {:>8}  VIA", "PATH", "DEPTH", "PAGERANK");
+            println!("{}", "-".repeat(88));
+            for d in &ranked {
+                println!(
+                    "{:<50} {:>5} {:>8.3}  {}",
+                    d.path, d.depth, d.pagerank, d.via
+                );
+            }
+            if ranked.len() < total {
+                println!("... {} more (raise --top)", total - ranked.len());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> ImportGraph {
+        ImportGraph::from_imports(&[
+            (
+                "src/session.rs".to_string(),
+                vec!["src/auth.rs".to_string()],
+            ),
+            ("src/api.rs".to_string(), vec!["src/session.rs".to_string()]),
+            ("src/main.rs".to_string(), vec!["src/api.rs".to_string()]),
+            ("src/db.rs".to_string(), vec![]),
+        ])
+    }
+
+    #[test]
+    fn dependents_follow_reverse_edges() {
+        let found = dependents(&graph(), "src/auth.rs", None);
+        let paths: Vec<(&str, usize)> = found.iter().map(|d| (d.path.as_str(), d.depth)).collect();
+        assert_eq!(
+            paths,
+            vec![("src/session.rs", 1), ("src/api.rs", 2), ("src/main.rs", 3)]
+        );
+        assert_eq!(found[1].via, "src/session.rs");
+    }
+
+    #[test]
+    fn dependents_respect_depth_limit() {
+        let found = dependents(&graph(), "src/auth.rs", Some(2));
+        assert_eq!(found.len(), 2);
+        assert!(dependents(&graph(), "src/auth.rs", Some(0)).is_empty());
+    }
+
+    #[test]
+    fn rank_orders_by_pagerank_then_depth() {
+        let found = dependents(&graph(), "src/auth.rs", None);
+        let pagerank = HashMap::from([("src/main.rs".to_string(), 0.9)]);
+        let ranked = rank_dependents(found, &pagerank);
+        assert_eq!(ranked[0].path, "src/main.rs");
+        assert_eq!(ranked[1].path, "src/session.rs");
+    }
+}
@@ -1,75 +1,282 @@
 use crate::Cli;
 use anyhow::Result;
-use topo_index::IndexBuilder;
-use topo_scanner::BundleBuilder;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use topo_core::{Bundle, DeepIndex, IndexError};
+use topo_index::{BuildStats, IndexBackend, IndexBuilder};
+use topo_scanner::ScanStats;
 
-pub fn run(cli: &Cli, deep: bool, force: bool) -> Result<()> {
+pub fn run(cli: &Cli, deep: bool, force: bool, stats: bool) -> Result<()> {
     let root = cli.repo_root()?;
 
-    if !cli.is_quiet() {
-        eprintln!(
-            "Indexing {} (mode: {})...",
-            root.display(),
-            if deep { "deep" } else { "shallow" }
-        );
-    }
+    log::info!(
+        "Indexing {} (mode: {})...",
+        root.display(),
+        if deep { "deep" } else { "shallow" }
+    );
 
     // Scan the repository
-    let bundle = BundleBuilder::new(&root).build()?;
+    let (bundle, scan) = cli.bundle_builder(&root).build_with_stats()?;
+
+    log::info!(
+        "Scanned {} files (fingerprint: {})",
+        bundle.file_count(),
+        &bundle.fingerprint[..12]
+    );
+
+    let report = stats || cli.is_verbose();
+    if deep {
+        build_deep(
+            &root,
+            &bundle,
+            Some(&scan),
+            force,
+            report,
+            cli.index_backend(),
+            cli.include_artifacts(),
+        )?;
+    }
+
+    if report && !deep {
+        log::info!("{}", format_scan_stats(&scan).trim_end());
+    }
+
+    log::info!("Done.");
+
+    Ok(())
+}
+
+/// Build (or incrementally update) the deep index for an already-scanned
+/// bundle, saving it when anything changed. With `report`, logs a
+/// breakdown of where the build spent its time, including the `scan` that
+/// produced the bundle when known. Without a `backend` the
+/// index is saved the way it already is. Generated artifacts are left out
+/// unless `include_artifacts`.
+pub fn build_deep(
+    root: &Path,
+    bundle: &Bundle,
+    scan: Option<&ScanStats>,
+    force: bool,
+    report: bool,
+    backend: Option<IndexBackend>,
+    include_artifacts: bool,
+) -> Result<DeepIndex> {
+    let builder = IndexBuilder::new(root)
+        .include_artifacts(include_artifacts)
+        .stop_words(super::query::stop_words(root)?)
+        .resolve_options(super::query::resolve_options(root)?)
+        .pagerank(super::query::pagerank_params(root)?);
 
-    if !cli.is_quiet() {
-        eprintln!(
-            "Scanned {} files (fingerprint: {})",
-            bundle.file_count(),
-            &bundle.fingerprint[..12]
+    // Load existing index (unless force rebuild or built with other stop words)
+    let existing = if force {
+        None
+    } else {
+        topo_index::load(root)?.filter(|idx| builder.is_compatible(idx))
+    };
+
+    // Build index, skipping unchanged files when existing index is available
+    let (index, stats) = builder.build_with_stats(&bundle.files, existing.as_ref())?;
+    let reindexed = stats.files_indexed;
+
+    let current = topo_index::backend(root);
+    let backend = backend.or(current).unwrap_or_default();
+    let is_incremental = existing.is_some();
+    // Files can also drop out (deleted, or artifacts now left out)
+    let nothing_changed = is_incremental
+        && reindexed == 0
+        && existing.as_ref().map(|e| e.files.len()) == Some(index.files.len())
+        && current == Some(backend);
+
+    if is_incremental {
+        log::info!(
+            "Incremental update: {} files indexed ({} changed)",
+            index.total_docs,
+            reindexed
+        );
+    } else {
+        log::info!("Full index build: {} files indexed", index.total_docs);
+    }
+    if stats.artifacts_skipped > 0 {
+        log::info!(
+            "Left out {} minified bundles, source maps and lockfiles; use --include-artifacts to index them",
+            stats.artifacts_skipped
+        );
+    }
+    if !index.errors.is_empty() {
+        log::warn!(
+            "{} files could not be indexed ({}); run `topo inspect --errors` for details",
+            index.errors.len(),
+            error_counts(&index.errors)
         );
     }
 
-    if deep {
-        // Load existing index (unless force rebuild)
-        let existing = if force {
-            None
-        } else {
-            topo_index::load(&root)?
-        };
+    let mut save_time = None;
+    if nothing_changed {
+        log::info!(
+            "Index unchanged at {}",
+            topo_index::index_path(root).display()
+        );
+    } else {
+        let save_started = Instant::now();
+        topo_index::save_with(&index, root, backend)?;
+        save_time = Some(save_started.elapsed());
 
-        // Build index, skipping unchanged files when existing index is available
-        let builder = IndexBuilder::new(&root);
-        let (index, reindexed) = builder.build(&bundle.files, existing.as_ref())?;
-
-        let is_incremental = existing.is_some();
-        let nothing_changed = is_incremental && reindexed == 0;
-
-        if !cli.is_quiet() {
-            if is_incremental {
-                eprintln!(
-                    "Incremental update: {} files indexed ({} changed)",
-                    index.total_docs, reindexed
-                );
-            } else {
-                eprintln!("Full index build: {} files indexed", index.total_docs);
-            }
+        log::info!("Index saved to {}", topo_index::index_path(root).display());
+    }
+
+    if report {
+        log::info!("{}", format_build_stats(scan, &stats, save_time).trim_end());
+    }
+
+    Ok(index)
+}
+
+/// Error counts by kind, e.g. `2 unreadable, 1 not-utf8`.
+fn error_counts(errors: &[IndexError]) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for error in errors {
+        let kind = error.kind.as_str();
+        match counts.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((kind, 1)),
         }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+        .iter()
+        .map(|(kind, n)| format!("{n} {kind}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Human-readable timing breakdown for a scan on its own.
+fn format_scan_stats(scan: &ScanStats) -> String {
+    let mut out = String::from("\nScan timings:\n");
+    push_scan_stages(&mut out, scan);
+    out
+}
 
-        if nothing_changed {
-            if !cli.is_quiet() {
-                eprintln!(
-                    "Index unchanged at {}",
-                    topo_index::index_path(&root).display()
-                );
-            }
-        } else {
-            topo_index::save(&index, &root)?;
-
-            if !cli.is_quiet() {
-                eprintln!("Index saved to {}", topo_index::index_path(&root).display());
-            }
+fn push_scan_stages(out: &mut String, scan: &ScanStats) {
+    out.push_str(&format!(
+        "  {:<14} {:>10}\n  {:<14} {:>10}  ({} files hashed, {} reused)\n",
+        "walk",
+        format_duration(scan.walk()),
+        "hash",
+        format_duration(scan.hash),
+        scan.files_hashed,
+        scan.files_reused
+    ));
+}
+
+/// Human-readable timing breakdown for an index build, preceded by the
+/// stages of the `scan` that fed it.
+fn format_build_stats(
+    scan: Option<&ScanStats>,
+    stats: &BuildStats,
+    save_time: Option<Duration>,
+) -> String {
+    let mut out = String::from("\nIndex build timings (per-file stages summed across threads):\n");
+    if let Some(scan) = scan {
+        push_scan_stages(&mut out, scan);
+    }
+    let mut line = |label: &str, d: Duration| {
+        out.push_str(&format!("  {label:<14} {:>10}\n", format_duration(d)));
+    };
+    line("read", stats.read);
+    line("tokenize", stats.tokenize);
+    line("chunk", stats.chunk());
+    line("imports", stats.imports);
+    line("graph", stats.graph);
+    if let Some(d) = save_time {
+        line("serialize", d);
+    }
+    line("build (wall)", stats.wall);
+    out.push_str(&format!(
+        "\nPageRank: {}, {:.1}% of import edges changed\n",
+        match (stats.pagerank_iterations, stats.pagerank_warm) {
+            (0, _) => "previous scores kept".to_string(),
+            (n, true) => format!("{n} iterations from the previous scores"),
+            (n, false) => format!("{n} iterations"),
+        },
+        stats.edge_churn * 100.0
+    ));
+
+    let chunked = stats.chunk_ranked();
+    if !chunked.is_empty() {
+        out.push_str("\nChunking by language:\n");
+        for (lang, d) in chunked.iter().filter(|(_, d)| !d.is_zero()) {
+            out.push_str(&format!(
+                "  {:<14} {:>10}\n",
+                lang.as_str(),
+                format_duration(*d)
+            ));
         }
     }
 
-    if !cli.is_quiet() {
-        eprintln!("Done.");
+    if !stats.slowest.is_empty() {
+        out.push_str(&format!(
+            "\nSlowest files ({} indexed, {} reused):\n",
+            stats.files_indexed, stats.files_reused
+        ));
+        for f in &stats.slowest {
+            out.push_str(&format!(
+                "  {:>10}  {}\n",
+                format_duration(f.elapsed),
+                f.path
+            ));
+        }
     }
+    out
+}
 
-    Ok(())
+fn format_duration(d: Duration) -> String {
+    if d.as_secs() >= 1 {
+        format!("{:.2}s", d.as_secs_f64())
+    } else {
+        format!("{:.1}ms", d.as_secs_f64() * 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_stats_report_lists_stages() {
+        let stats = BuildStats {
+            read: Duration::from_millis(12),
+            wall: Duration::from_millis(1500),
+            ..Default::default()
+        };
+        let scan = ScanStats {
+            files_hashed: 4,
+            files_reused: 6,
+            hash: Duration::from_millis(7),
+            wall: Duration::from_millis(10),
+        };
+        let text = format_build_stats(Some(&scan), &stats, Some(Duration::from_millis(3)));
+        assert!(text.contains("walk"));
+        assert!(text.contains("3.0ms"));
+        assert!(text.contains("7.0ms  (4 files hashed, 6 reused)"));
+        assert!(text.contains("read"));
+        assert!(text.contains("12.0ms"));
+        assert!(text.contains("serialize"));
+        assert!(text.contains("1.50s"));
+    }
+
+    #[test]
+    fn error_counts_group_by_kind() {
+        use topo_core::IndexErrorKind;
+        let error = |path: &str, kind| IndexError {
+            path: path.to_string(),
+            kind,
+            detail: String::new(),
+        };
+        let errors = vec![
+            error("a", IndexErrorKind::NotUtf8),
+            error("b", IndexErrorKind::Unreadable),
+            error("c", IndexErrorKind::NotUtf8),
+        ];
+        assert_eq!(error_counts(&errors), "2 not-utf8, 1 unreadable");
+    }
 }
@@ -1,7 +1,11 @@
-use crate::Cli;
+use crate::mcp_clients::{self, Registration};
+use crate::{Cli, HookShell, McpClient};
 use anyhow::Result;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use topo_core::{FileInfo, FileRole, Language};
+use topo_scanner::BundleBuilder;
 
 const AGENTS_MD: &str = include_str!("../../templates/AGENTS.md");
 const CURSOR_TOPO_MD: &str = include_str!("../../templates/cursor-topo.md");
@@ -10,8 +14,279 @@ const CLAUDE_MD_SECTION: &str = include_str!("../../templates/claude-md-section.
 const TOPO_CONTEXT_SH: &str = include_str!("../../templates/topo-context.sh");
 const TOPO_HINT_SH: &str = include_str!("../../templates/topo-hint.sh");
 const TOPO_TRACK_SH: &str = include_str!("../../templates/topo-track.sh");
+const TOPO_CONTEXT_PS1: &str = include_str!("../../templates/topo-context.ps1");
+const TOPO_HINT_PS1: &str = include_str!("../../templates/topo-hint.ps1");
+const TOPO_TRACK_PS1: &str = include_str!("../../templates/topo-track.ps1");
+
+/// Hook scripts by name, as bash and as PowerShell.
+const HOOK_SCRIPTS: [(&str, &str, &str); 3] = [
+    ("topo-context", TOPO_CONTEXT_SH, TOPO_CONTEXT_PS1),
+    ("topo-hint", TOPO_HINT_SH, TOPO_HINT_PS1),
+    ("topo-track", TOPO_TRACK_SH, TOPO_TRACK_PS1),
+];
+
+impl HookShell {
+    /// The shell Claude Code hooks can count on here: Windows setups often
+    /// have neither bash nor jq.
+    pub fn native() -> Self {
+        if cfg!(windows) {
+            Self::Powershell
+        } else {
+            Self::Bash
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Bash => "sh",
+            Self::Powershell => "ps1",
+        }
+    }
+
+    /// The settings.json command that runs hook script `name`.
+    fn command(self, name: &str) -> String {
+        match self {
+            Self::Bash => format!("\"$CLAUDE_PROJECT_DIR\"/.claude/hooks/{name}.sh"),
+            // -ExecutionPolicy Bypass: generated scripts are unsigned
+            Self::Powershell => format!(
+                "powershell -NoProfile -ExecutionPolicy Bypass -File \"$CLAUDE_PROJECT_DIR/.claude/hooks/{name}.ps1\""
+            ),
+        }
+    }
+}
+
+/// Languages named in the repository summary.
+const PROBE_LANGUAGES: usize = 3;
+/// Modules named in the repository summary.
+const PROBE_MODULES: usize = 3;
+/// Repositories up to this many tokens fit in most context windows whole.
+const SMALL_REPO_TOKENS: u64 = 50_000;
+/// Context window the budget guidance is worked out for.
+const EXAMPLE_CONTEXT_TOKENS: u64 = 200_000;
+/// Example task when the repository has no modules to name.
+const GENERIC_TASK: &str = "refactor authentication middleware";
+
+/// Manifests and lockfiles, and the package manager each one implies.
+const PACKAGE_MANAGERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Cargo"),
+    ("package.json", "npm"),
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "Yarn"),
+    ("bun.lockb", "Bun"),
+    ("go.mod", "Go modules"),
+    ("pyproject.toml", "pyproject"),
+    ("requirements.txt", "pip"),
+    ("Pipfile", "Pipenv"),
+    ("Gemfile", "Bundler"),
+    ("composer.json", "Composer"),
+    ("mix.exs", "Mix"),
+    ("Package.swift", "SwiftPM"),
+    ("pom.xml", "Maven"),
+    ("build.gradle", "Gradle"),
+    ("build.gradle.kts", "Gradle"),
+];
+
+/// What `topo init` learns about the repository to tailor AGENTS.md and
+/// the Cursor rules.
+#[derive(Debug, Default)]
+struct RepoProbe {
+    files: usize,
+    tokens: u64,
+    /// Code languages by file count, largest first.
+    languages: Vec<(Language, usize)>,
+    package_managers: Vec<&'static str>,
+    /// Largest source directories.
+    modules: Vec<String>,
+}
+
+impl RepoProbe {
+    fn scan(root: &Path) -> Result<Self> {
+        let bundle = BundleBuilder::new(root).build()?;
+        Ok(Self::from_files(&bundle.files))
+    }
+
+    fn from_files(files: &[FileInfo]) -> Self {
+        let mut languages: HashMap<Language, usize> = HashMap::new();
+        for f in files
+            .iter()
+            .filter(|f| f.language.is_programming_language())
+        {
+            *languages.entry(f.language).or_default() += 1;
+        }
+        let mut languages: Vec<(Language, usize)> = languages.into_iter().collect();
+        languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+        languages.truncate(PROBE_LANGUAGES);
+
+        let mut package_managers: Vec<&'static str> = Vec::new();
+        for f in files {
+            let name = f.path.rsplit('/').next().unwrap_or(&f.path);
+            if let Some(&(_, manager)) = PACKAGE_MANAGERS.iter().find(|(m, _)| *m == name)
+                && !package_managers.contains(&manager)
+            {
+                package_managers.push(manager);
+            }
+        }
+        // A pnpm, Yarn or Bun lockfile says which tool reads package.json
+        if package_managers
+            .iter()
+            .any(|m| ["pnpm", "Yarn", "Bun"].contains(m))
+        {
+            package_managers.retain(|m| *m != "npm");
+        }
+        package_managers.sort_unstable();
+
+        let code: Vec<FileInfo> = files
+            .iter()
+            .filter(|f| f.role == FileRole::Implementation && f.language.is_programming_language())
+            .cloned()
+            .collect();
+        let modules = super::overview::directories(&code)
+            .into_iter()
+            .take(PROBE_MODULES)
+            .map(|(path, _)| path)
+            .collect();
+
+        Self {
+            files: files.len(),
+            tokens: files.iter().map(|f| f.estimated_tokens()).sum(),
+            languages,
+            package_managers,
+            modules,
+        }
+    }
+
+    /// Preset to start with, and why; the same thresholds as `topo budget`.
+    fn preset(&self) -> (&'static str, String) {
+        let files = self.files;
+        if files > super::budget::LARGE_REPO_FILES {
+            (
+                "fast",
+                format!(
+                    "with {files} files, a deep index takes a while; run `topo index --deep` once and `balanced` becomes cheap"
+                ),
+            )
+        } else if files > super::budget::SMALL_REPO_FILES {
+            (
+                "balanced",
+                format!("with {files} files, the cached deep index keeps queries fast"),
+            )
+        } else {
+            (
+                "deep",
+                format!(
+                    "with {files} files, rebuilding the deep index is cheap enough to add structural signals"
+                ),
+            )
+        }
+    }
+
+    fn budget_guidance(&self) -> String {
+        let tokens = approx_tokens(self.tokens);
+        if self.tokens <= SMALL_REPO_TOKENS {
+            format!(
+                "The whole repository is about {tokens} tokens, so it fits in most context windows and ranking only decides what to read first."
+            )
+        } else {
+            let share = (EXAMPLE_CONTEXT_TOKENS as f64 * super::budget::CONTEXT_SHARE) as u64;
+            format!(
+                "The whole repository is about {tokens} tokens, so cap each selection with `--max-tokens` (`--max-tokens {share}` leaves most of a {}-token context free), or let `topo budget \"task\" --model <name>` choose.",
+                approx_tokens(EXAMPLE_CONTEXT_TOKENS)
+            )
+        }
+    }
 
-enum WriteResult {
+    /// A task naming a real module, for the CLI examples.
+    fn example_task(&self) -> String {
+        match self.modules.first() {
+            Some(module) => {
+                let name = module.rsplit('/').next().unwrap_or(module);
+                format!("how {name} handles errors")
+            }
+            None => GENERIC_TASK.to_string(),
+        }
+    }
+
+    /// Size and main languages in a few words, e.g. `120 files, mostly rust`.
+    fn repo_line(&self) -> String {
+        match self.languages.first() {
+            Some((language, _)) => format!("{}, mostly {}", files(self.files), language.as_str()),
+            None => files(self.files),
+        }
+    }
+
+    fn repo_summary(&self) -> String {
+        if self.files == 0 {
+            return "No files were found when `topo init` ran; run `topo init --force` once there is code to describe.".to_string();
+        }
+        let mut summary = format!(
+            "{}, about {} tokens.",
+            files(self.files),
+            approx_tokens(self.tokens)
+        );
+        if !self.languages.is_empty() {
+            let languages: Vec<String> = self
+                .languages
+                .iter()
+                .map(|(l, n)| format!("{} ({})", l.as_str(), files(*n)))
+                .collect();
+            summary.push_str(&format!(" Languages: {}.", languages.join(", ")));
+        }
+        if !self.package_managers.is_empty() {
+            summary.push_str(&format!(
+                " Package managers: {}.",
+                self.package_managers.join(", ")
+            ));
+        }
+        if !self.modules.is_empty() {
+            let modules: Vec<String> = self.modules.iter().map(|m| format!("`{m}`")).collect();
+            summary.push_str(&format!(" Largest modules: {}.", modules.join(", ")));
+        }
+        summary
+    }
+
+    /// Values for the `{{name}}` placeholders of the templates.
+    fn vars(&self) -> Vec<(&'static str, String)> {
+        let (preset, reason) = self.preset();
+        vec![
+            ("repo_summary", self.repo_summary()),
+            ("repo_line", self.repo_line()),
+            ("preset", preset.to_string()),
+            ("preset_reason", reason),
+            ("budget_guidance", self.budget_guidance()),
+            ("example_task", self.example_task()),
+        ]
+    }
+}
+
+/// `n files`, or `1 file`.
+fn files(n: usize) -> String {
+    if n == 1 {
+        "1 file".to_string()
+    } else {
+        format!("{n} files")
+    }
+}
+
+/// `n` tokens rounded for prose: `800`, `12k`, `1.4M`.
+fn approx_tokens(n: u64) -> String {
+    if n >= 1_000_000 {
+        format!("{:.1}M", n as f64 / 1_000_000.0)
+    } else if n >= 1_000 {
+        format!("{}k", (n as f64 / 1_000.0).round() as u64)
+    } else {
+        n.to_string()
+    }
+}
+
+/// Fill the `{{name}}` placeholders of `template` from `vars`.
+fn render(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{{{name}}}}}"), value)
+        })
+}
+
+pub(super) enum WriteResult {
     Created,
     Skipped,
 }
@@ -31,20 +306,33 @@ const TOPO_START: &str = "<!-- topo:start -->";
 const TOPO_END: &str = "<!-- topo:end -->";
 
 fn inject_claude_md(path: &Path, section: &str, force: bool) -> Result<WriteResult> {
+    inject_section(path, section, (TOPO_START, TOPO_END), force)
+}
+
+/// Put `section`, which starts and ends with `markers`, into the file at
+/// `path`: replacing the marked section if there is one (only with `force`),
+/// else appending it. Content outside the markers is never touched.
+pub(super) fn inject_section(
+    path: &Path,
+    section: &str,
+    markers: (&str, &str),
+    force: bool,
+) -> Result<WriteResult> {
+    let (start_marker, end_marker) = markers;
     let content = if path.exists() {
         fs::read_to_string(path)?
     } else {
         String::new()
     };
 
-    if let Some(start) = content.find(TOPO_START) {
+    if let Some(start) = content.find(start_marker) {
         if !force {
             return Ok(WriteResult::Skipped);
         }
         // Replace existing section (inclusive of markers)
         let end = content[start..]
-            .find(TOPO_END)
-            .map(|i| start + i + TOPO_END.len())
+            .find(end_marker)
+            .map(|i| start + i + end_marker.len())
             .unwrap_or(content.len());
         let mut new_content = String::with_capacity(content.len());
         new_content.push_str(&content[..start]);
@@ -97,7 +385,7 @@ fn write_hook(path: &Path, content: &str, force: bool) -> Result<WriteResult> {
 
 /// Patch `.claude/settings.json` to register topo hooks.
 /// Merges hook entries into existing settings without destroying user config.
-fn patch_claude_settings(root: &Path, force: bool) -> Result<WriteResult> {
+fn patch_claude_settings(root: &Path, shell: HookShell, force: bool) -> Result<WriteResult> {
     let settings_path = root.join(".claude/settings.json");
     let mut settings: serde_json::Value = if settings_path.exists() {
         let content = fs::read_to_string(&settings_path)?;
@@ -119,7 +407,7 @@ fn patch_claude_settings(root: &Path, force: bool) -> Result<WriteResult> {
         "UserPromptSubmit": [{
             "hooks": [{
                 "type": "command",
-                "command": "\"$CLAUDE_PROJECT_DIR\"/.claude/hooks/topo-context.sh",
+                "command": shell.command("topo-context"),
                 "timeout": 15
             }]
         }],
@@ -127,7 +415,7 @@ fn patch_claude_settings(root: &Path, force: bool) -> Result<WriteResult> {
             "matcher": "Glob|Grep",
             "hooks": [{
                 "type": "command",
-                "command": "\"$CLAUDE_PROJECT_DIR\"/.claude/hooks/topo-hint.sh",
+                "command": shell.command("topo-hint"),
                 "timeout": 10
             }]
         }],
@@ -135,7 +423,7 @@ fn patch_claude_settings(root: &Path, force: bool) -> Result<WriteResult> {
             "matcher": "Read",
             "hooks": [{
                 "type": "command",
-                "command": "\"$CLAUDE_PROJECT_DIR\"/.claude/hooks/topo-track.sh",
+                "command": shell.command("topo-track"),
                 "timeout": 5
             }]
         }]
@@ -193,16 +481,23 @@ fn check_topo_on_path() {
 
     println!();
     println!("Optional: for tools without shell access, topo also runs as an MCP server.");
-    println!("See https://github.com/demwunz/topo#mcp for setup instructions.");
+    println!("Register it with `topo init --mcp` (Claude Desktop, Cursor and VS Code).");
 }
 
-pub fn run(cli: &Cli, force: bool, hooks: bool) -> Result<()> {
+pub fn run(cli: &Cli, force: bool, hooks: bool, shell: HookShell, mcp: &[McpClient]) -> Result<()> {
     let root = cli.repo_root()?;
     let quiet = cli.is_quiet();
 
+    // Tailor the instructions to the repository; generic text if the scan fails
+    let probe = RepoProbe::scan(&root).unwrap_or_else(|e| {
+        log::warn!("could not scan the repository, writing generic instructions: {e}");
+        RepoProbe::default()
+    });
+    let vars = probe.vars();
+
     // AGENTS.md at repo root
     let agents_path = root.join("AGENTS.md");
-    match write_template(&agents_path, AGENTS_MD, force)? {
+    match write_template(&agents_path, &render(AGENTS_MD, &vars), force)? {
         WriteResult::Created => {
             if !quiet {
                 println!("  Created AGENTS.md");
@@ -217,7 +512,7 @@ pub fn run(cli: &Cli, force: bool, hooks: bool) -> Result<()> {
 
     // .cursor/rules/topo.md
     let cursor_path = root.join(".cursor/rules/topo.md");
-    match write_template(&cursor_path, CURSOR_TOPO_MD, force)? {
+    match write_template(&cursor_path, &render(CURSOR_TOPO_MD, &vars), force)? {
         WriteResult::Created => {
             if !quiet {
                 println!("  Created .cursor/rules/topo.md");
@@ -279,55 +574,29 @@ pub fn run(cli: &Cli, force: bool, hooks: bool) -> Result<()> {
         }
 
         let hooks_dir = root.join(".claude/hooks");
-        let context_path = hooks_dir.join("topo-context.sh");
-        match write_hook(&context_path, TOPO_CONTEXT_SH, force)? {
-            WriteResult::Created => {
-                if !quiet {
-                    println!("  Created .claude/hooks/topo-context.sh");
+        for (name, bash, powershell) in HOOK_SCRIPTS {
+            let script = match shell {
+                HookShell::Bash => bash,
+                HookShell::Powershell => powershell,
+            };
+            let file = format!("{name}.{}", shell.extension());
+            match write_hook(&hooks_dir.join(&file), script, force)? {
+                WriteResult::Created => {
+                    if !quiet {
+                        println!("  Created .claude/hooks/{file}");
+                    }
                 }
-            }
-            WriteResult::Skipped => {
-                if !quiet {
-                    println!(
-                        "  Skipped .claude/hooks/topo-context.sh (already exists, use --force to overwrite)"
-                    );
-                }
-            }
-        }
-
-        let hint_path = hooks_dir.join("topo-hint.sh");
-        match write_hook(&hint_path, TOPO_HINT_SH, force)? {
-            WriteResult::Created => {
-                if !quiet {
-                    println!("  Created .claude/hooks/topo-hint.sh");
-                }
-            }
-            WriteResult::Skipped => {
-                if !quiet {
-                    println!(
-                        "  Skipped .claude/hooks/topo-hint.sh (already exists, use --force to overwrite)"
-                    );
+                WriteResult::Skipped => {
+                    if !quiet {
+                        println!(
+                            "  Skipped .claude/hooks/{file} (already exists, use --force to overwrite)"
+                        );
+                    }
                 }
             }
         }
 
-        let track_path = hooks_dir.join("topo-track.sh");
-        match write_hook(&track_path, TOPO_TRACK_SH, force)? {
-            WriteResult::Created => {
-                if !quiet {
-                    println!("  Created .claude/hooks/topo-track.sh");
-                }
-            }
-            WriteResult::Skipped => {
-                if !quiet {
-                    println!(
-                        "  Skipped .claude/hooks/topo-track.sh (already exists, use --force to overwrite)"
-                    );
-                }
-            }
-        }
-
-        match patch_claude_settings(&root, force)? {
+        match patch_claude_settings(&root, shell, force)? {
             WriteResult::Created => {
                 if !quiet {
                     println!("  Patched .claude/settings.json (hook registration)");
@@ -343,6 +612,15 @@ pub fn run(cli: &Cli, force: bool, hooks: bool) -> Result<()> {
         }
     }
 
+    // MCP client configs (--mcp)
+    if !mcp.is_empty() {
+        if !quiet {
+            println!();
+            println!("MCP clients:");
+        }
+        register_mcp_clients(&root, mcp, force, quiet)?;
+    }
+
     if !quiet {
         println!();
         check_topo_on_path();
@@ -351,6 +629,42 @@ pub fn run(cli: &Cli, force: bool, hooks: bool) -> Result<()> {
     Ok(())
 }
 
+/// Point each of `clients` at `topo mcp` for `root`.
+fn register_mcp_clients(
+    root: &Path,
+    clients: &[McpClient],
+    force: bool,
+    quiet: bool,
+) -> Result<()> {
+    // Claude Desktop is not started in the repository, so the root must be absolute
+    let root = std::path::absolute(root)?;
+    for &client in clients {
+        let path = client.config_path(&root)?;
+        let shown = path
+            .strip_prefix(&root)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        let result = mcp_clients::register(client, &path, &root, force)?;
+        if quiet {
+            continue;
+        }
+        let label = client.label();
+        match result {
+            Registration::Added => println!("  Registered with {label} in {shown}"),
+            Registration::Updated => println!("  Updated the {label} entry in {shown}"),
+            Registration::Unchanged => println!("  {label} already registered in {shown}"),
+            Registration::Skipped => println!(
+                "  Skipped {label} ({shown} has another topo server, use --force to replace it)"
+            ),
+        }
+    }
+    if !quiet && clients.contains(&McpClient::ClaudeDesktop) {
+        println!("  Restart Claude Desktop to load the server.");
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -364,6 +678,67 @@ mod tests {
         assert!(!TOPO_CONTEXT_SH.is_empty());
         assert!(!TOPO_HINT_SH.is_empty());
         assert!(!TOPO_TRACK_SH.is_empty());
+        assert!(!TOPO_CONTEXT_PS1.is_empty());
+        assert!(!TOPO_HINT_PS1.is_empty());
+        assert!(!TOPO_TRACK_PS1.is_empty());
+    }
+
+    fn file(path: &str) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size: 4_000,
+            language: Language::from_path(Path::new(path)),
+            role: FileRole::from_path(Path::new(path)),
+            sha256: [0; 32],
+            lines: 100,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        }
+    }
+
+    #[test]
+    fn probe_reads_languages_managers_and_modules() {
+        let files = vec![
+            file("Cargo.toml"),
+            file("web/package.json"),
+            file("web/yarn.lock"),
+            file("src/auth/login.rs"),
+            file("src/auth/token.rs"),
+            file("src/lib.rs"),
+            file("web/app.ts"),
+            file("README.md"),
+        ];
+        let probe = RepoProbe::from_files(&files);
+        assert_eq!(probe.files, 8);
+        assert_eq!(probe.languages[0], (Language::Rust, 3));
+        assert_eq!(probe.package_managers, ["Cargo", "Yarn"]);
+        assert_eq!(probe.modules, ["src", "web"]);
+        assert_eq!(probe.example_task(), "how src handles errors");
+        assert_eq!(probe.preset().0, "deep");
+    }
+
+    #[test]
+    fn rendered_templates_have_no_placeholders() {
+        let files = vec![file("src/main.rs")];
+        for probe in [RepoProbe::default(), RepoProbe::from_files(&files)] {
+            let vars = probe.vars();
+            for template in [AGENTS_MD, CURSOR_TOPO_MD] {
+                let text = render(template, &vars);
+                assert!(!text.contains("{{"), "{text}");
+            }
+        }
+        let agents = render(AGENTS_MD, &RepoProbe::from_files(&files).vars());
+        assert!(agents.contains("1 file, about 1k tokens. Languages: rust (1 file)."));
+        assert!(agents.contains("topo quick \"how src handles errors\""));
+    }
+
+    #[test]
+    fn approx_tokens_rounds_for_prose() {
+        assert_eq!(approx_tokens(800), "800");
+        assert_eq!(approx_tokens(12_400), "12k");
+        assert_eq!(approx_tokens(1_440_000), "1.4M");
     }
 
     #[test]
@@ -396,7 +771,7 @@ mod tests {
     #[test]
     fn patch_claude_settings_creates_new() {
         let dir = tempdir().unwrap();
-        let result = patch_claude_settings(dir.path(), false).unwrap();
+        let result = patch_claude_settings(dir.path(), HookShell::Bash, false).unwrap();
         assert!(matches!(result, WriteResult::Created));
         let content = fs::read_to_string(dir.path().join(".claude/settings.json")).unwrap();
         let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
@@ -405,6 +780,27 @@ mod tests {
         assert!(settings["hooks"]["PostToolUse"].is_array());
     }
 
+    #[test]
+    fn patch_claude_settings_picks_commands_per_shell() {
+        let dir = tempdir().unwrap();
+        patch_claude_settings(dir.path(), HookShell::Powershell, false).unwrap();
+        let content = fs::read_to_string(dir.path().join(".claude/settings.json")).unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let command = settings["hooks"]["PostToolUse"][0]["hooks"][0]["command"]
+            .as_str()
+            .unwrap();
+        assert!(command.starts_with("powershell "));
+        assert!(command.ends_with("/.claude/hooks/topo-track.ps1\""));
+
+        patch_claude_settings(dir.path(), HookShell::Bash, true).unwrap();
+        let content = fs::read_to_string(dir.path().join(".claude/settings.json")).unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            settings["hooks"]["UserPromptSubmit"][0]["hooks"][0]["command"],
+            "\"$CLAUDE_PROJECT_DIR\"/.claude/hooks/topo-context.sh"
+        );
+    }
+
     #[test]
     fn patch_claude_settings_merges_existing() {
         let dir = tempdir().unwrap();
@@ -415,7 +811,7 @@ mod tests {
             r#"{"allowedTools": ["bash"]}"#,
         )
         .unwrap();
-        let result = patch_claude_settings(dir.path(), false).unwrap();
+        let result = patch_claude_settings(dir.path(), HookShell::Bash, false).unwrap();
         assert!(matches!(result, WriteResult::Created));
         let content = fs::read_to_string(settings_dir.join("settings.json")).unwrap();
         let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
@@ -429,9 +825,9 @@ mod tests {
     fn patch_claude_settings_skips_when_present() {
         let dir = tempdir().unwrap();
         // First patch
-        patch_claude_settings(dir.path(), false).unwrap();
+        patch_claude_settings(dir.path(), HookShell::Bash, false).unwrap();
         // Second patch should skip
-        let result = patch_claude_settings(dir.path(), false).unwrap();
+        let result = patch_claude_settings(dir.path(), HookShell::Bash, false).unwrap();
         assert!(matches!(result, WriteResult::Skipped));
     }
 
@@ -1,21 +1,25 @@
 use crate::Cli;
+use crate::error::CliError;
 use anyhow::Result;
+use topo_core::IndexError;
+use topo_index::CorpusSnapshot;
 
-pub fn run(cli: &Cli) -> Result<()> {
+pub fn run(cli: &Cli, errors: bool) -> Result<()> {
     let root = cli.repo_root()?;
     let index_path = topo_index::index_path(&root);
 
     if !index_path.exists() {
-        anyhow::bail!(
-            "No index found at {}. Run `topo index --deep` first.",
-            index_path.display()
-        );
+        anyhow::bail!(CliError::no_index(&root));
     }
 
     let metadata = std::fs::metadata(&index_path)?;
     let file_size = metadata.len();
 
     let index = topo_index::load(&root)?.ok_or_else(|| anyhow::anyhow!("Failed to load index"))?;
+    if errors {
+        print_errors(&index.errors);
+        return Ok(());
+    }
 
     // Collect language stats
     let mut lang_counts: std::collections::HashMap<String, usize> =
@@ -38,7 +42,11 @@ pub fn run(cli: &Cli) -> Result<()> {
     }
 
     println!("Index: {}", index_path.display());
-    println!("Format: rkyv binary");
+    let format = match topo_index::backend(&root).unwrap_or_default() {
+        topo_index::IndexBackend::Bin => "rkyv binary",
+        topo_index::IndexBackend::Sqlite => "SQLite",
+    };
+    println!("Format: {format}");
     println!(
         "Size: {:.1} MB ({} bytes)",
         file_size as f64 / 1_048_576.0,
@@ -50,11 +58,18 @@ pub fn run(cli: &Cli) -> Result<()> {
     println!("Unique terms: {}", index.doc_frequencies.len());
     println!("Terms (file-level): {}", total_terms);
     println!("Avg doc length: {:.1}", index.avg_doc_length);
+    if !index.errors.is_empty() {
+        println!(
+            "Not indexed: {} files (see `topo inspect --errors`)",
+            index.errors.len()
+        );
+    }
+    print_drift(&topo_index::corpus_history(&root)?);
     println!();
 
     // Top extensions by file count
     let mut sorted_langs: Vec<_> = lang_counts.into_iter().collect();
-    sorted_langs.sort_by(|a, b| b.1.cmp(&a.1));
+    sorted_langs.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     println!("Files by extension:");
     for (ext, count) in sorted_langs.iter().take(15) {
@@ -65,5 +80,122 @@ pub fn run(cli: &Cli) -> Result<()> {
         println!("  (other)       {rest:>6}");
     }
 
+    // Scan-time metadata from the working tree
+    let bundle = cli.bundle_builder(&root).build()?;
+    let total_lines: u64 = bundle.files.iter().map(|f| f.lines).sum();
+    let executables = bundle.files.iter().filter(|f| f.executable).count();
+    let mut interpreters: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    for f in &bundle.files {
+        if let Some(interp) = f.shebang.as_deref() {
+            *interpreters.entry(interp).or_default() += 1;
+        }
+    }
+
+    println!();
+    println!(
+        "Working tree: {} files, {} lines",
+        bundle.file_count(),
+        total_lines
+    );
+    println!("Executable files: {executables}");
+    if !interpreters.is_empty() {
+        let mut sorted: Vec<_> = interpreters.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        println!("Scripts by interpreter:");
+        for (interp, count) in sorted {
+            println!("  {interp:<13} {count:>6}");
+        }
+    }
+
+    let mut licenses: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    for f in &bundle.files {
+        if let Some(license) = f.license.as_deref() {
+            *licenses.entry(license).or_default() += 1;
+        }
+    }
+    if !licenses.is_empty() {
+        let mut sorted: Vec<_> = licenses.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        println!("Files by license:");
+        for (license, count) in sorted {
+            println!("  {license:<24} {count:>6}");
+        }
+    }
+
+    let entry_points: Vec<&str> = bundle
+        .files
+        .iter()
+        .filter(|f| f.entry_point)
+        .map(|f| f.path.as_str())
+        .collect();
+    if !entry_points.is_empty() {
+        println!("Entry points ({}):", entry_points.len());
+        for path in entry_points.iter().take(20) {
+            println!("  {path}");
+        }
+        if entry_points.len() > 20 {
+            println!("  ... {} more", entry_points.len() - 20);
+        }
+    }
+
+    // Declared third-party packages by the number of files importing them
+    let unresolved = topo_index::unresolved_imports(&root, &bundle.files);
+    let usage = topo_score::dependency_usage(&unresolved);
+    if !usage.is_empty() {
+        println!("Third-party dependencies by importing files:");
+        for (name, files) in usage.iter().take(15) {
+            println!("  {name:<30} {files:>6}");
+        }
+        if usage.len() > 15 {
+            println!("  ... {} more", usage.len() - 15);
+        }
+    }
+
     Ok(())
 }
+
+/// The files the last build left out, with the reason for each.
+fn print_errors(errors: &[IndexError]) {
+    if errors.is_empty() {
+        println!("No indexing errors in the last build.");
+        return;
+    }
+    println!("Files not indexed ({}):", errors.len());
+    for error in errors {
+        println!(
+            "  {:<11} {}: {}",
+            error.kind.as_str(),
+            error.path,
+            error.detail
+        );
+    }
+}
+
+/// Flag statistics that moved sharply between the last two index builds.
+fn print_drift(history: &[CorpusSnapshot]) {
+    let [.., previous, latest] = history else {
+        return;
+    };
+    let drifts = latest.drift_from(previous);
+    if drifts.is_empty() {
+        return;
+    }
+    println!();
+    println!(
+        "Drift since the build of {}:",
+        crate::stats::utc_timestamp(previous.built_at)
+    );
+    for drift in &drifts {
+        let change = drift.change();
+        let change = if change.is_finite() {
+            format!("{:+.0}%", change * 100.0)
+        } else {
+            "new".to_string()
+        };
+        println!(
+            "  {:<22} {:>10.1} -> {:<10.1} ({change})",
+            drift.metric, drift.before, drift.after
+        );
+    }
+    println!("  Sharp swings usually mean an ignore rule or the scanner changed, not the code.");
+}
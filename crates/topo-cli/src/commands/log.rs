@@ -0,0 +1,121 @@
+use crate::error::CliError;
+use crate::preset::Preset;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use topo_core::Config;
+use topo_score::{CommitLog, DEFAULT_LOG_COMMITS, Tokenizer};
+
+/// Commits listed when `--top` isn't given.
+const DEFAULT_TOP: usize = 10;
+
+pub fn run(cli: &Cli, task: &str, days: Option<u32>, top: Option<usize>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let stop_words = super::query::stop_words(&root)?;
+    let (terms, _) = Tokenizer::tokenize_query_with(task, &stop_words);
+    if terms.is_empty() {
+        bail!(
+            CliError::usage("the task has no searchable words")
+                .hint("Describe the feature, e.g. `topo log \"token refresh\"`.")
+        );
+    }
+
+    let history = CommitLog::load(&root, DEFAULT_LOG_COMMITS, days)?;
+    if history.is_empty() {
+        log::warn!("no commits to search; is this a git repository?");
+    }
+
+    // Touched files count by the scorer's relevance to the task
+    let mut bundle = cli.bundle_builder(&root).build()?;
+    super::query::apply_package_scope(cli, &root, &mut bundle);
+    super::query::apply_license_filter(cli, &mut bundle);
+    let config = Config::load(&root)?;
+    let preset = Preset::resolve(None, &config)?;
+    let deep_index = if preset.use_structural_signals() {
+        super::query::load_index_for(&root, task)?
+    } else {
+        None
+    };
+    let scoring = super::query::Scoring::from_config(&config)
+        .include_vendored(cli.include_vendored)
+        .weights(&config, &preset, None)?
+        .git_history(&root, &preset);
+    let file_scores: HashMap<String, f64> =
+        super::query::score_files(task, &bundle.files, &preset, deep_index.as_ref(), &scoring)
+            .into_iter()
+            .map(|f| (f.path, f.score))
+            .collect();
+
+    let mut commits = history.rank(task, &stop_words, &file_scores);
+    let total = commits.len();
+    commits.truncate(top.unwrap_or(DEFAULT_TOP));
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "task": task,
+                "searched_commits": history.len(),
+                "matching_commits": total,
+                "commits": commits.iter().map(|m| serde_json::json!({
+                    "hash": m.commit.hash,
+                    "date": m.commit.date,
+                    "author": m.commit.author,
+                    "subject": m.commit.subject,
+                    "score": m.score,
+                    "files": m.top_files,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for m in &commits {
+                println!(
+                    "{} {} ({})",
+                    short(&m.commit.hash),
+                    m.commit.subject,
+                    m.top_files.join(", ")
+                );
+            }
+        }
+        _ => {
+            if commits.is_empty() {
+                println!(
+                    "No commits match \"{task}\" among the last {} searched.",
+                    history.len()
+                );
+                return Ok(());
+            }
+            println!(
+                "Commits matching \"{task}\": {total} of the last {} searched",
+                history.len()
+            );
+            println!();
+            for m in &commits {
+                println!(
+                    "{}  {}  {}  ({:.2})",
+                    short(&m.commit.hash),
+                    m.commit.date,
+                    m.commit.subject,
+                    m.score
+                );
+                if !m.top_files.is_empty() {
+                    println!("         {}", m.top_files.join(", "));
+                }
+            }
+            if commits.len() < total {
+                println!("... {} more (raise --top)", total - commits.len());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The abbreviated hash shown in listings.
+fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(7)]
+}
@@ -0,0 +1,383 @@
+use crate::error::CliError;
+use crate::last_query;
+use crate::{Cli, OutputFormat};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use topo_core::{FileInfo, ScoredFile, SignalBreakdown};
+use topo_render::{CompactWriter, JsonlWriter};
+
+/// Directory under `.topo/` holding saved manifests.
+const MANIFEST_DIR: &str = "manifests";
+
+/// A named, persisted file selection with content hashes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub task: String,
+    pub preset: String,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub sha256: String,
+    pub score: f64,
+}
+
+/// State of a manifest file relative to the working tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Unchanged,
+    Modified,
+    Deleted,
+}
+
+impl FileStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unchanged => "unchanged",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+impl Manifest {
+    /// Compare each entry against freshly scanned files.
+    pub fn status(&self, current: &[FileInfo]) -> Vec<(&ManifestEntry, FileStatus)> {
+        let hashes: HashMap<&str, String> = current
+            .iter()
+            .map(|f| (f.path.as_str(), hex_encode(&f.sha256)))
+            .collect();
+        self.files
+            .iter()
+            .map(|entry| {
+                let status = match hashes.get(entry.path.as_str()) {
+                    None => FileStatus::Deleted,
+                    Some(h) if *h == entry.sha256 => FileStatus::Unchanged,
+                    Some(_) => FileStatus::Modified,
+                };
+                (entry, status)
+            })
+            .collect()
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn manifest_path(root: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || name.contains(['/', '\\'])
+        || name.contains(char::is_whitespace)
+    {
+        bail!(CliError::usage(format!(
+            "invalid manifest name {name:?}: it must be non-empty, not start with '.', \
+             and contain no slashes or whitespace"
+        )));
+    }
+    Ok(root
+        .join(".topo")
+        .join(MANIFEST_DIR)
+        .join(format!("{name}.json")))
+}
+
+/// Save the last query's delivered files (or a selection file) as a manifest.
+pub fn save(cli: &Cli, name: &str, from: Option<&Path>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = manifest_path(&root, name)?;
+
+    let (task, preset, selected): (String, String, Vec<(String, f64)>) = match from {
+        Some(file) => {
+            let content =
+                fs::read_to_string(file).with_context(|| format!("reading {}", file.display()))?;
+            let header: Option<serde_json::Value> = content
+                .lines()
+                .next()
+                .and_then(|l| serde_json::from_str(l).ok());
+            let field = |key: &str| {
+                header
+                    .as_ref()
+                    .and_then(|h| h.get(key))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let selection = super::diff_results::parse_selection(&content)?;
+            (
+                field("Query"),
+                field("Preset"),
+                selection.into_iter().map(|s| (s.path, s.score)).collect(),
+            )
+        }
+        None => {
+            let Some(last) = last_query::load(&root)? else {
+                bail!(CliError::no_previous_query());
+            };
+            let selected = last
+                .ranked
+                .iter()
+                .filter(|f| last.delivered.contains(&f.path))
+                .map(|f| (f.path.clone(), f.score))
+                .collect();
+            (last.task, last.preset, selected)
+        }
+    };
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let hashes: HashMap<&str, &[u8; 32]> = bundle
+        .files
+        .iter()
+        .map(|f| (f.path.as_str(), &f.sha256))
+        .collect();
+
+    let files: Vec<ManifestEntry> = selected
+        .into_iter()
+        .filter_map(|(path, score)| {
+            let sha = hashes.get(path.as_str())?;
+            Some(ManifestEntry {
+                sha256: hex_encode(*sha),
+                path,
+                score,
+            })
+        })
+        .collect();
+
+    let manifest = Manifest {
+        name: name.to_string(),
+        task,
+        preset,
+        saved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        files,
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&manifest)?)?;
+
+    log::info!(
+        "Saved manifest '{name}' ({} files) to {}",
+        manifest.files.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Load a manifest, report which files changed, and emit the selection.
+pub fn load(cli: &Cli, name: &str) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = manifest_path(&root, name)?;
+    if !path.exists() {
+        bail!(
+            CliError::not_found(format!("manifest '{name}' not found"))
+                .hint(format!("Run `topo manifest save {name}` first."))
+        );
+    }
+    let manifest: Manifest = serde_json::from_str(&fs::read_to_string(&path)?)
+        .with_context(|| format!("parsing {}", path.display()))?;
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let status = manifest.status(&bundle.files);
+    let changed = status
+        .iter()
+        .filter(|(_, s)| *s != FileStatus::Unchanged)
+        .count();
+
+    // Rebuild the selection from current file metadata
+    let by_path: HashMap<&str, &FileInfo> =
+        bundle.files.iter().map(|f| (f.path.as_str(), f)).collect();
+    let files: Vec<ScoredFile> = manifest
+        .files
+        .iter()
+        .filter_map(|e| {
+            let info = by_path.get(e.path.as_str())?;
+            Some(ScoredFile {
+                path: e.path.clone(),
+                score: e.score,
+                signals: SignalBreakdown::default(),
+                tokens: info.estimated_tokens(),
+                language: info.language,
+                role: info.role,
+                root: None,
+                cluster: None,
+                pair: None,
+                chunks: Vec::new(),
+                outline: Vec::new(),
+            })
+        })
+        .collect();
+
+    match cli.effective_format() {
+        OutputFormat::Jsonl | OutputFormat::Auto => {
+            if changed > 0 {
+                log::info!("Manifest '{name}': {changed} file(s) changed since save");
+            }
+            let output = JsonlWriter::new(&manifest.task, &manifest.preset)
+                .render(&files, bundle.file_count())?;
+            print!("{output}");
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "name": manifest.name,
+                "task": manifest.task,
+                "preset": manifest.preset,
+                "saved_at": manifest.saved_at,
+                "changed": changed,
+                "files": status.iter().map(|(e, s)| serde_json::json!({
+                    "path": e.path,
+                    "score": e.score,
+                    "status": s.as_str(),
+                })).collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Compact => {
+            if changed > 0 {
+                for (e, s) in status.iter().filter(|(_, s)| *s != FileStatus::Unchanged) {
+                    println!("{} ({})", e.path, s.as_str());
+                }
+            }
+            print!("{}", CompactWriter::new().render(&files));
+        }
+        OutputFormat::Human | OutputFormat::Markdown | OutputFormat::Xml => {
+            println!(
+                "Manifest '{}' — {} files — task: \"{}\"",
+                manifest.name,
+                manifest.files.len(),
+                manifest.task
+            );
+            println!();
+            for (e, s) in &status {
+                println!("  {:<10} {}", s.as_str(), e.path);
+            }
+            println!();
+            println!("{changed} of {} files changed since save", status.len());
+        }
+    }
+
+    Ok(())
+}
+
+/// List saved manifests.
+pub fn list(cli: &Cli) -> Result<()> {
+    let root = cli.repo_root()?;
+    let dir = root.join(".topo").join(MANIFEST_DIR);
+
+    let mut manifests: Vec<Manifest> = Vec::new();
+    if dir.exists() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json")
+                && let Ok(data) = fs::read_to_string(&path)
+                && let Ok(m) = serde_json::from_str::<Manifest>(&data)
+            {
+                manifests.push(m);
+            }
+        }
+    }
+    manifests.sort_by(|a, b| a.name.cmp(&b.name));
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output: Vec<serde_json::Value> = manifests
+                .iter()
+                .map(|m| {
+                    serde_json::json!({
+                        "name": m.name,
+                        "task": m.task,
+                        "files": m.files.len(),
+                        "saved_at": m.saved_at,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            if manifests.is_empty() {
+                println!("No saved manifests.");
+            }
+            for m in &manifests {
+                println!("{:<24} {:>4} files  \"{}\"", m.name, m.files.len(), m.task);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::{FileRole, Language};
+
+    fn info(path: &str, hash_byte: u8) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size: 100,
+            language: Language::Rust,
+            role: FileRole::Implementation,
+            sha256: [hash_byte; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        }
+    }
+
+    fn entry(path: &str, hash_byte: u8) -> ManifestEntry {
+        ManifestEntry {
+            path: path.to_string(),
+            sha256: hex_encode(&[hash_byte; 32]),
+            score: 0.5,
+        }
+    }
+
+    #[test]
+    fn status_detects_modified_and_deleted() {
+        let manifest = Manifest {
+            name: "m".to_string(),
+            task: "auth".to_string(),
+            preset: "balanced".to_string(),
+            saved_at: 0,
+            files: vec![entry("a.rs", 1), entry("b.rs", 2), entry("c.rs", 3)],
+        };
+        let current = vec![info("a.rs", 1), info("b.rs", 9)];
+
+        let status: Vec<_> = manifest
+            .status(&current)
+            .into_iter()
+            .map(|(e, s)| (e.path.as_str(), s))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                ("a.rs", FileStatus::Unchanged),
+                ("b.rs", FileStatus::Modified),
+                ("c.rs", FileStatus::Deleted),
+            ]
+        );
+    }
+
+    #[test]
+    fn manifest_names_are_validated() {
+        let root = Path::new("/repo");
+        assert!(manifest_path(root, "auth-refactor_2").is_ok());
+        assert!(manifest_path(root, "").is_err());
+        assert!(manifest_path(root, "../escape").is_err());
+        assert!(manifest_path(root, ".hidden").is_err());
+    }
+}
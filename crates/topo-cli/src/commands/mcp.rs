@@ -1,6 +1,6 @@
 use crate::Cli;
 use crate::preset::Preset;
-use anyhow::Result;
+use anyhow::{Result, bail};
 use rmcp::{
     ErrorData as McpError, ServerHandler, ServiceExt,
     handler::server::{tool::ToolRouter, wrapper::Parameters},
@@ -11,20 +11,37 @@ use rmcp::{
     transport::stdio,
 };
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use topo_core::{Bundle, Config, DeepIndex, DiskFiles, FileProvider, Overlay};
 
 // ---------------------------------------------------------------------------
 // Parameter structs
 // ---------------------------------------------------------------------------
 
+const BUFFERS_DESCRIPTION: &str = "Unsaved editor buffers to rank by instead of the files on disk: each a path (repo-relative or absolute) and its current content. Paths not on disk yet are included as new files.";
+
+/// An editor buffer whose content differs from the file on disk.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+struct Buffer {
+    /// Path of the file, repo-relative or absolute
+    #[schemars(description = "Path of the file, repo-relative or absolute")]
+    path: String,
+
+    /// Current content of the buffer
+    #[schemars(description = "Current content of the buffer")]
+    content: String,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 struct QueryParams {
     /// The task or query describing what you're looking for
     #[schemars(description = "The task or query describing what you're looking for")]
     task: String,
 
-    /// Scoring preset: fast, balanced, deep, thorough
-    #[schemars(description = "Scoring preset: fast, balanced, deep, thorough (default: balanced)")]
+    /// Scoring preset: fast, balanced, deep, thorough, or a custom preset
+    #[schemars(
+        description = "Scoring preset: fast, balanced, deep, thorough, or a [presets] name from .topo/config.toml (default: balanced, or [defaults] preset in .topo/config.toml)"
+    )]
     preset: Option<String>,
 
     /// Maximum bytes for token budget
@@ -42,6 +59,11 @@ struct QueryParams {
     /// Return only the top N files
     #[schemars(description = "Return only the top N files")]
     top: Option<usize>,
+
+    /// Unsaved editor buffers layered over the working tree
+    #[serde(default)]
+    #[schemars(description = BUFFERS_DESCRIPTION)]
+    buffers: Vec<Buffer>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -54,9 +76,16 @@ struct ExplainParams {
     #[schemars(description = "Return top N files (default: 10)")]
     top: Option<usize>,
 
-    /// Scoring preset: fast, balanced, deep, thorough
-    #[schemars(description = "Scoring preset: fast, balanced, deep, thorough (default: balanced)")]
+    /// Scoring preset: fast, balanced, deep, thorough, or a custom preset
+    #[schemars(
+        description = "Scoring preset: fast, balanced, deep, thorough, or a [presets] name from .topo/config.toml (default: balanced, or [defaults] preset in .topo/config.toml)"
+    )]
     preset: Option<String>,
+
+    /// Unsaved editor buffers layered over the working tree
+    #[serde(default)]
+    #[schemars(description = BUFFERS_DESCRIPTION)]
+    buffers: Vec<Buffer>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -77,15 +106,17 @@ struct IndexParams {
 #[derive(Debug, Clone)]
 pub struct TopoServer {
     root: PathBuf,
+    /// `--exclude-license` ids; files licensed under them are never scored.
+    exclude_license: Vec<String>,
     tool_router: ToolRouter<TopoServer>,
 }
 
-fn parse_preset(s: Option<&str>) -> Preset {
+/// The preset a tool call asked for; unknown names fall back to balanced
+/// rather than failing the call.
+fn parse_preset(s: Option<&str>, config: &Config) -> Preset {
     match s {
-        Some("fast") => Preset::Fast,
-        Some("deep") => Preset::Deep,
-        Some("thorough") => Preset::Thorough,
-        _ => Preset::Balanced,
+        Some(name) => Preset::named(name, config).unwrap_or_else(Preset::balanced),
+        None => Preset::resolve(None, config).unwrap_or_else(|_| Preset::balanced()),
     }
 }
 
@@ -94,41 +125,127 @@ fn parse_preset(s: Option<&str>) -> Preset {
 // ---------------------------------------------------------------------------
 
 impl TopoServer {
+    /// The working tree with `buffers` over it.
+    fn overlay(&self, buffers: Vec<Buffer>) -> Result<Overlay> {
+        let mut overlay = Overlay::new(DiskFiles::new(&self.root));
+        for buffer in buffers {
+            let path = self.buffer_path(&buffer.path)?;
+            overlay.insert(path, buffer.content);
+        }
+        Ok(overlay)
+    }
+
+    /// The repo-relative path of a buffer; paths outside the repository
+    /// are an error.
+    fn buffer_path(&self, path: &str) -> Result<String> {
+        let path = Path::new(path);
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let mut parts = Vec::new();
+        for component in relative.components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy()),
+                Component::CurDir => {}
+                _ => bail!("buffer {} is outside the repository", path.display()),
+            }
+        }
+        if parts.is_empty() {
+            bail!("buffer path is empty");
+        }
+        Ok(parts.join("/"))
+    }
+
+    /// The deep index for scoring `task`. With buffers, the full index is
+    /// loaded and their entries rebuilt in memory from `overlay`; the saved
+    /// index is left as it is.
+    fn index_for(
+        &self,
+        task: &str,
+        bundle: &Bundle,
+        overlay: &Overlay,
+    ) -> Result<Option<DeepIndex>> {
+        if overlay.overlaid().is_empty() {
+            return super::query::load_index_for(&self.root, task);
+        }
+        let Some(existing) = topo_index::load(&self.root)? else {
+            return Ok(None);
+        };
+        let builder = topo_index::IndexBuilder::new(&self.root)
+            .stop_words(super::query::stop_words(&self.root)?)
+            .resolve_options(super::query::resolve_options(&self.root)?)
+            .pagerank(super::query::pagerank_params(&self.root)?)
+            .provider(overlay);
+        if !builder.is_compatible(&existing) {
+            return Ok(Some(existing));
+        }
+        let (index, _) = builder.build(&bundle.files, Some(&existing))?;
+        Ok(Some(index))
+    }
+
     fn do_query(&self, params: QueryParams) -> Result<serde_json::Value> {
-        let preset = parse_preset(params.preset.as_deref());
+        let config = Config::load(&self.root)?;
+        let defaults = &config.defaults;
+        let preset = parse_preset(params.preset.as_deref(), &config);
+        let min_score = params.min_score.or(defaults.min_score);
+        let max_bytes = params.max_bytes.or(defaults.max_bytes);
+        let max_tokens = params.max_tokens.or(defaults.max_tokens);
 
         // Auto-index if preset requires it
         if preset.needs_deep_index() {
             self.do_index_inner(true, preset.force_rebuild())?;
         }
 
-        let bundle = topo_scanner::BundleBuilder::new(&self.root).build()?;
+        let overlay = self.overlay(params.buffers)?;
+        let mut bundle = topo_scanner::BundleBuilder::new(&self.root)
+            .provider(&overlay)
+            .build()?;
+        super::query::exclude_licenses(&mut bundle, &self.exclude_license);
 
         let deep_index = if preset.use_structural_signals() {
-            topo_index::load(&self.root)?
+            self.index_for(&params.task, &bundle, &overlay)?
         } else {
             None
         };
 
-        let scored =
-            super::query::score_files(&params.task, &bundle.files, preset, deep_index.as_ref());
-
-        let effective_min_score = params.min_score.unwrap_or(preset.default_min_score());
-        let mut filtered: Vec<topo_core::ScoredFile> = scored
+        let scoring = super::query::scoring(&self.root, &preset)?.git_history(&self.root, &preset);
+        let scored = super::query::score_files(
+            &params.task,
+            &bundle.files,
+            &preset,
+            deep_index.as_ref(),
+            &scoring,
+        );
+
+        let effective_min_score = min_score.unwrap_or(preset.default_min_score());
+        let (ranked, below): (Vec<topo_core::ScoredFile>, Vec<topo_core::ScoredFile>) = scored
             .into_iter()
-            .filter(|f| f.score >= effective_min_score)
-            .collect();
+            .partition(|f| f.score >= effective_min_score);
 
+        let mut filtered = ranked.clone();
         if let Some(n) = params.top {
             filtered.truncate(n);
         }
 
-        let effective_max_bytes = params.max_bytes.unwrap_or(preset.default_max_bytes());
+        let effective_max_bytes = max_bytes.unwrap_or(preset.default_max_bytes());
         let budget = topo_core::TokenBudget {
             max_bytes: Some(effective_max_bytes),
-            max_tokens: params.max_tokens,
+            max_tokens,
         };
-        let budgeted = budget.enforce(&filtered);
+        let mut budgeted = budget.enforce(&filtered);
+        let truncation = topo_core::Truncation::from_cuts(
+            &filtered[budgeted.len()..],
+            &ranked[filtered.len()..],
+            &below,
+        );
+        if preset.chunks() {
+            super::query::attach_chunks(
+                &self.root,
+                &overlay,
+                &params.task,
+                deep_index.as_ref(),
+                &scoring,
+                &mut budgeted,
+            )?;
+        }
 
         let result = serde_json::json!({
             "query": params.task,
@@ -139,28 +256,45 @@ impl TopoServer {
                 "tokens": f.tokens,
                 "language": f.language.as_str(),
                 "role": f.role.as_str(),
+                "chunks": super::query::chunks_json(f),
             })).collect::<Vec<_>>(),
             "total_selected": budgeted.len(),
             "total_scanned": bundle.file_count(),
+            "truncated": super::query::truncation_json(&truncation),
         });
 
         Ok(result)
     }
 
     fn do_explain(&self, params: ExplainParams) -> Result<serde_json::Value> {
-        let preset = parse_preset(params.preset.as_deref());
+        let config = Config::load(&self.root)?;
+        let preset = parse_preset(params.preset.as_deref(), &config);
         let top = params.top.unwrap_or(10);
 
-        let bundle = topo_scanner::BundleBuilder::new(&self.root).build()?;
+        let overlay = self.overlay(params.buffers)?;
+        let mut bundle = topo_scanner::BundleBuilder::new(&self.root)
+            .provider(&overlay)
+            .build()?;
+        super::query::exclude_licenses(&mut bundle, &self.exclude_license);
 
         let deep_index = if preset.use_structural_signals() {
-            topo_index::load(&self.root)?
+            self.index_for(&params.task, &bundle, &overlay)?
         } else {
             None
         };
 
-        let scored =
-            super::query::score_files(&params.task, &bundle.files, preset, deep_index.as_ref());
+        let scoring = super::query::scoring(&self.root, &preset)?.git_history(&self.root, &preset);
+        let topo_score::MultiQueryResult {
+            files: scored,
+            sub_queries,
+            ..
+        } = super::query::score_files_with_sub_queries(
+            &params.task,
+            &bundle.files,
+            &preset,
+            deep_index.as_ref(),
+            &scoring,
+        );
 
         let display_count = top.min(scored.len());
         let results = &scored[..display_count];
@@ -176,10 +310,15 @@ impl TopoServer {
                         "heuristic": f.signals.heuristic,
                         "pagerank": f.signals.pagerank,
                         "git_recency": f.signals.git_recency,
+                        "co_change": f.signals.co_change,
+                        "churn": f.signals.churn,
+                        "file_type": f.signals.file_type,
+                        "entry_point": f.signals.entry_point,
                     },
                     "tokens": f.tokens,
                     "language": f.language.as_str(),
                     "role": f.role.as_str(),
+                    "sub_query_wins": super::explain::sub_query_wins(&sub_queries, &f.path),
                 })
             })
             .collect();
@@ -198,13 +337,16 @@ impl TopoServer {
         let file_count = bundle.file_count();
 
         if deep {
+            let builder = topo_index::IndexBuilder::new(&self.root)
+                .stop_words(super::query::stop_words(&self.root)?)
+                .resolve_options(super::query::resolve_options(&self.root)?)
+                .pagerank(super::query::pagerank_params(&self.root)?);
             let existing = if force {
                 None
             } else {
-                topo_index::load(&self.root)?
+                topo_index::load(&self.root)?.filter(|idx| builder.is_compatible(idx))
             };
 
-            let builder = topo_index::IndexBuilder::new(&self.root);
             let (index, reindexed) = builder.build(&bundle.files, existing.as_ref())?;
             let is_incremental = existing.is_some();
             let nothing_changed = is_incremental && reindexed == 0;
@@ -239,10 +381,17 @@ impl TopoServer {
     pub fn new(root: PathBuf) -> Self {
         Self {
             root,
+            exclude_license: Vec::new(),
             tool_router: Self::tool_router(),
         }
     }
 
+    /// Leave files licensed under any of `ids` out of every tool's results.
+    pub fn exclude_license(mut self, ids: Vec<String>) -> Self {
+        self.exclude_license = ids;
+        self
+    }
+
     #[tool(
         name = "topo_query",
         description = "Find the most relevant files for a task. Use this as your first step for file discovery instead of grep/find/glob. Auto-indexes if needed. Returns scored file paths with token counts."
@@ -345,7 +494,7 @@ pub fn run(cli: &Cli) -> Result<()> {
         .build()?;
 
     rt.block_on(async {
-        let server = TopoServer::new(root);
+        let server = TopoServer::new(root).exclude_license(cli.exclude_license.clone());
         let service = server.serve(stdio()).await?;
         service.waiting().await?;
         Ok(())
@@ -362,16 +511,17 @@ mod tests {
 
     #[test]
     fn parse_preset_defaults_to_balanced() {
-        assert!(matches!(parse_preset(None), Preset::Balanced));
-        assert!(matches!(parse_preset(Some("unknown")), Preset::Balanced));
+        let config = Config::default();
+        assert_eq!(parse_preset(None, &config), Preset::balanced());
+        assert_eq!(parse_preset(Some("unknown"), &config), Preset::balanced());
     }
 
     #[test]
-    fn parse_preset_recognizes_all_variants() {
-        assert!(matches!(parse_preset(Some("fast")), Preset::Fast));
-        assert!(matches!(parse_preset(Some("balanced")), Preset::Balanced));
-        assert!(matches!(parse_preset(Some("deep")), Preset::Deep));
-        assert!(matches!(parse_preset(Some("thorough")), Preset::Thorough));
+    fn parse_preset_recognizes_all_presets() {
+        let config = Config::parse("[presets.review]\nbase = \"deep\"\n").unwrap();
+        for name in ["fast", "balanced", "deep", "thorough", "review"] {
+            assert_eq!(parse_preset(Some(name), &config).as_str(), name);
+        }
     }
 
     #[test]
@@ -387,6 +537,7 @@ mod tests {
             max_tokens: None,
             min_score: None,
             top: None,
+            buffers: Vec::new(),
         };
 
         let result = server.do_query(params).unwrap();
@@ -394,6 +545,54 @@ mod tests {
         assert!(result.get("total_scanned").unwrap().as_u64().unwrap() > 0);
     }
 
+    #[test]
+    fn excluded_licenses_stay_out_of_tool_results() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("billing.rs"), "fn charge() {}\n").unwrap();
+        std::fs::write(
+            dir.path().join("billing_gpl.rs"),
+            "// SPDX-License-Identifier: GPL-3.0-only\nfn charge_gpl() {}\n",
+        )
+        .unwrap();
+
+        let server =
+            TopoServer::new(dir.path().to_path_buf()).exclude_license(vec!["GPL-3.0".to_string()]);
+        let query = server
+            .do_query(QueryParams {
+                task: "billing charge".to_string(),
+                preset: Some("fast".to_string()),
+                max_bytes: None,
+                max_tokens: None,
+                min_score: Some(0.0),
+                top: None,
+                buffers: Vec::new(),
+            })
+            .unwrap();
+        let paths: Vec<&str> = query["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(paths, ["billing.rs"]);
+
+        let explain = server
+            .do_explain(ExplainParams {
+                task: "billing charge".to_string(),
+                top: None,
+                preset: Some("fast".to_string()),
+                buffers: Vec::new(),
+            })
+            .unwrap();
+        assert!(
+            explain
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|f| f["path"] != "billing_gpl.rs")
+        );
+    }
+
     #[test]
     fn do_explain_returns_array() {
         let dir = tempfile::tempdir().unwrap();
@@ -404,12 +603,59 @@ mod tests {
             task: "main function".to_string(),
             top: Some(5),
             preset: Some("fast".to_string()),
+            buffers: Vec::new(),
         };
 
         let result = server.do_explain(params).unwrap();
         assert!(result.is_array());
     }
 
+    #[test]
+    fn do_query_ranks_unsaved_buffers() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("billing.rs"), "fn charge() {}\n").unwrap();
+        std::fs::write(dir.path().join("notes.rs"), "fn todo() {}\n").unwrap();
+
+        let server = TopoServer::new(dir.path().to_path_buf());
+        let query = |buffers| {
+            let result = server
+                .do_query(QueryParams {
+                    task: "\"refund invoice\"".to_string(),
+                    preset: Some("deep".to_string()),
+                    max_bytes: None,
+                    max_tokens: None,
+                    min_score: None,
+                    top: None,
+                    buffers,
+                })
+                .unwrap();
+            result["files"][0]["path"].as_str().map(str::to_string)
+        };
+        let unsaved = Buffer {
+            path: dir.path().join("notes.rs").display().to_string(),
+            content: "fn refund_invoice(invoice: Invoice) { refund(invoice) }\n".to_string(),
+        };
+        assert_eq!(query(Vec::new()).as_deref(), Some("billing.rs"));
+        assert_eq!(query(vec![unsaved]).as_deref(), Some("notes.rs"));
+
+        // The saved index keeps the content on disk
+        let index = topo_index::load(dir.path()).unwrap().unwrap();
+        assert!(
+            !index.files["notes.rs"]
+                .term_frequencies
+                .contains_key("refund")
+        );
+    }
+
+    #[test]
+    fn buffer_paths_stay_in_the_repository() {
+        let server = TopoServer::new(PathBuf::from("/repo"));
+        assert_eq!(server.buffer_path("/repo/src/a.rs").unwrap(), "src/a.rs");
+        assert_eq!(server.buffer_path("./src/a.rs").unwrap(), "src/a.rs");
+        assert!(server.buffer_path("../other/a.rs").is_err());
+        assert!(server.buffer_path("/elsewhere/a.rs").is_err());
+    }
+
     #[test]
     fn do_index_returns_status() {
         let dir = tempfile::tempdir().unwrap();
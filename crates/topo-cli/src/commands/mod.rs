@@ -1,10 +1,37 @@
+pub mod blame_context;
+pub mod budget;
+pub mod cat;
+pub mod changed;
+pub mod clean;
+pub mod completions;
+pub mod deps;
 pub mod describe;
+pub mod diff_results;
+pub mod expand;
 pub mod explain;
 pub mod gain;
+pub mod grep;
+pub mod hot;
+pub mod impact;
 pub mod index;
 pub mod init;
 pub mod inspect;
+pub mod log;
+pub mod manifest;
 pub mod mcp;
+pub mod more;
+pub mod outline;
+pub mod overview;
+pub mod owners;
+pub mod pack;
 pub mod query;
 pub mod quick;
+pub mod related;
 pub mod render;
+pub mod similar;
+pub mod summarize;
+pub mod symbols;
+pub mod test_context;
+pub mod tree;
+pub mod unresolved;
+pub mod watch;
@@ -0,0 +1,64 @@
+use crate::Cli;
+use crate::error::CliError;
+use crate::last_query;
+use anyhow::{Result, bail};
+use topo_core::{TokenBudget, Truncation};
+
+/// Return the next budget slice of the last query's ranking, skipping files
+/// that were already delivered.
+pub fn run(
+    cli: &Cli,
+    max_bytes: Option<u64>,
+    max_tokens: Option<u64>,
+    top: Option<usize>,
+) -> Result<()> {
+    let root = cli.repo_root()?;
+
+    let Some(mut last) = last_query::load(&root)? else {
+        bail!(CliError::no_previous_query());
+    };
+
+    let all_remaining = last.remaining();
+    let mut remaining = all_remaining.clone();
+    if let Some(n) = top {
+        remaining.truncate(n);
+    }
+
+    let effective_max_bytes = max_bytes.unwrap_or(last.max_bytes);
+    let budget = TokenBudget {
+        max_bytes: Some(effective_max_bytes),
+        max_tokens: max_tokens.or(last.max_tokens),
+    };
+    let budgeted = budget.enforce(&remaining);
+    let truncation = Truncation::from_cuts(
+        &remaining[budgeted.len()..],
+        &all_remaining[remaining.len()..],
+        &[],
+    );
+
+    if budgeted.is_empty() {
+        log::info!(
+            "No more files for \"{}\" ({} already delivered).",
+            last.task,
+            last.delivered.len()
+        );
+    }
+
+    last.mark_delivered(&budgeted);
+    last_query::save(&root, &last)?;
+
+    super::query::output_results(
+        cli,
+        &root,
+        &last.task,
+        &last.preset,
+        last.scope.as_deref(),
+        &budgeted,
+        last.scanned_count,
+        &budget,
+        last.min_score,
+        &truncation,
+    )?;
+
+    Ok(())
+}
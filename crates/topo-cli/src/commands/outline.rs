@@ -0,0 +1,81 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::fs;
+use topo_treesit::OutlineNode;
+
+pub fn run(cli: &Cli, path: &str) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = cli.repo_relative(&root, path);
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let Some(info) = bundle.files.iter().find(|f| f.path == path) else {
+        bail!(CliError::untracked(&path, &root));
+    };
+    let content = fs::read_to_string(root.join(&path))?;
+    let total = content.lines().count();
+    let outline = topo_treesit::outline(&content, info.language);
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "language": info.language.as_str(),
+                "total_lines": total,
+                "outline": outline.iter().map(to_json).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            walk(&outline, 0, &mut |node, depth| {
+                println!(
+                    "{path}:{} {}{}",
+                    node.chunk.start_line,
+                    "  ".repeat(depth),
+                    node.chunk.name
+                );
+            });
+        }
+        _ => {
+            if outline.is_empty() {
+                println!("No imports, types or functions found in {path}.");
+                return Ok(());
+            }
+            println!("Outline of {path} ({total} lines)");
+            println!();
+            walk(&outline, 0, &mut |node, depth| {
+                println!(
+                    "{:>11}  {}{:<8} {}",
+                    format!("{}-{}", node.chunk.start_line, node.chunk.end_line),
+                    "  ".repeat(depth),
+                    node.chunk.kind.as_str(),
+                    node.chunk.name
+                );
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn to_json(node: &OutlineNode) -> serde_json::Value {
+    serde_json::json!({
+        "name": node.chunk.name,
+        "kind": node.chunk.kind.as_str(),
+        "start_line": node.chunk.start_line,
+        "end_line": node.chunk.end_line,
+        "children": node.children.iter().map(to_json).collect::<Vec<_>>(),
+    })
+}
+
+/// Visit `nodes` depth first in source order, with their nesting depth.
+fn walk(nodes: &[OutlineNode], depth: usize, visit: &mut impl FnMut(&OutlineNode, usize)) {
+    for node in nodes {
+        visit(node, depth);
+        walk(&node.children, depth + 1, visit);
+    }
+}
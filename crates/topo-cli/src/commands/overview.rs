@@ -0,0 +1,609 @@
+use super::init::{WriteResult, inject_section};
+use super::summarize::file_summary;
+use crate::{Cli, OutputFormat};
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use topo_core::{FileInfo, FileRole, FileSummary, Language, SummaryKind};
+use topo_score::GraphMetrics;
+
+/// Markers around the generated overview in AGENTS.md and CLAUDE.md.
+const OVERVIEW_START: &str = "<!-- topo:overview:start -->";
+const OVERVIEW_END: &str = "<!-- topo:overview:end -->";
+
+/// Files `--write` refreshes when present; AGENTS.md is created otherwise.
+const TARGETS: &[&str] = &["AGENTS.md", "CLAUDE.md"];
+
+const LANGUAGES: usize = 6;
+const DIRECTORIES: usize = 15;
+const CORE_MODULES: usize = 10;
+const ENTRY_POINTS: usize = 10;
+const KEY_TYPES: usize = 15;
+/// Highest-ranked files searched for key types.
+const KEY_TYPE_FILES: usize = 30;
+/// Key types listed from any one file.
+const KEY_TYPES_PER_FILE: usize = 3;
+
+/// Files whose leading doc comment describes their directory, best first.
+const DIRECTORY_DOCS: &[&str] = &[
+    "lib.rs",
+    "src/lib.rs",
+    "mod.rs",
+    "main.rs",
+    "src/main.rs",
+    "__init__.py",
+    "doc.go",
+    "index.ts",
+    "index.js",
+    "README.md",
+];
+
+/// Keywords that introduce a type declaration in a signature.
+const TYPE_KEYWORDS: &[&str] = &[
+    "struct",
+    "enum",
+    "trait",
+    "class",
+    "interface",
+    "type",
+    "protocol",
+    "object",
+    "union",
+];
+
+/// A deterministic summary of the repository.
+#[derive(Debug, Default)]
+pub struct Overview {
+    pub languages: Vec<(Language, usize)>,
+    pub directories: Vec<Directory>,
+    pub core_modules: Vec<Module>,
+    pub entry_points: Vec<String>,
+    pub key_types: Vec<KeyType>,
+}
+
+#[derive(Debug)]
+pub struct Directory {
+    pub path: String,
+    pub files: usize,
+    pub purpose: String,
+}
+
+#[derive(Debug)]
+pub struct Module {
+    pub path: String,
+    pub pagerank: f64,
+    /// Fan-in, fan-out and depth from the entry points.
+    pub metrics: GraphMetrics,
+    pub description: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct KeyType {
+    pub name: String,
+    pub path: String,
+}
+
+pub fn run(cli: &Cli, write: bool) -> Result<()> {
+    let root = cli.repo_root()?;
+    let bundle = cli.bundle_builder(&root).build()?;
+    let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+    let pagerank = graph.normalized_pagerank_with(&super::query::pagerank_params(&root)?);
+    let metrics = graph.metrics(
+        bundle
+            .files
+            .iter()
+            .filter(|f| f.entry_point)
+            .map(|f| f.path.as_str()),
+    );
+    let index = topo_index::load(&root)?;
+    let overview = build(&bundle.files, &pagerank, &metrics, |file| {
+        file_summary(&root, index.as_ref(), file).map(|(s, _)| s)
+    });
+    let markdown = render_markdown(&overview);
+
+    let mut written: Vec<&str> = Vec::new();
+    if write {
+        let existing: Vec<&str> = TARGETS
+            .iter()
+            .copied()
+            .filter(|name| root.join(name).exists())
+            .collect();
+        let targets = if existing.is_empty() {
+            vec![TARGETS[0]]
+        } else {
+            existing
+        };
+        for name in targets {
+            let result = inject_section(
+                &root.join(name),
+                &markdown,
+                (OVERVIEW_START, OVERVIEW_END),
+                true,
+            )?;
+            if matches!(result, WriteResult::Created) {
+                written.push(name);
+            }
+        }
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "languages": overview.languages.iter().map(|(l, n)| serde_json::json!({
+                    "language": l,
+                    "files": n,
+                })).collect::<Vec<_>>(),
+                "directories": overview.directories.iter().map(|d| serde_json::json!({
+                    "path": d.path,
+                    "files": d.files,
+                    "purpose": d.purpose,
+                })).collect::<Vec<_>>(),
+                "core_modules": overview.core_modules.iter().map(|m| serde_json::json!({
+                    "path": m.path,
+                    "pagerank": m.pagerank,
+                    "fan_in": m.metrics.fan_in,
+                    "fan_out": m.metrics.fan_out,
+                    "depth": m.metrics.depth,
+                    "description": m.description,
+                })).collect::<Vec<_>>(),
+                "entry_points": overview.entry_points,
+                "key_types": overview.key_types.iter().map(|t| serde_json::json!({
+                    "name": t.name,
+                    "path": t.path,
+                })).collect::<Vec<_>>(),
+                "written": written,
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for name in &written {
+                println!("{name}");
+            }
+            if !write {
+                print!("{markdown}");
+            }
+        }
+        _ => {
+            if !write {
+                print!("{markdown}");
+            } else if !cli.is_quiet() {
+                for name in &written {
+                    println!("Updated repository overview in {name}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Assemble the overview from scanned files, normalized PageRank, graph
+/// metrics and a source of file summaries.
+pub fn build(
+    files: &[FileInfo],
+    pagerank: &HashMap<String, f64>,
+    metrics: &HashMap<String, GraphMetrics>,
+    summary_of: impl Fn(&FileInfo) -> Option<FileSummary>,
+) -> Overview {
+    let rank = |path: &str| pagerank.get(path).copied().unwrap_or(0.0);
+    let by_path: HashMap<&str, &FileInfo> = files.iter().map(|f| (f.path.as_str(), f)).collect();
+
+    let mut languages: HashMap<Language, usize> = HashMap::new();
+    for f in files.iter().filter(|f| f.language != Language::Other) {
+        *languages.entry(f.language).or_default() += 1;
+    }
+    let mut languages: Vec<(Language, usize)> = languages.into_iter().collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+    languages.truncate(LANGUAGES);
+
+    // Files ranked by PageRank, most central first
+    let mut ranked: Vec<&FileInfo> = files
+        .iter()
+        .filter(|f| f.role == FileRole::Implementation && rank(&f.path) > 0.0)
+        .collect();
+    ranked.sort_by(|a, b| {
+        rank(&b.path)
+            .total_cmp(&rank(&a.path))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    let core_modules = ranked
+        .iter()
+        .take(CORE_MODULES)
+        .map(|f| Module {
+            path: f.path.clone(),
+            pagerank: rank(&f.path),
+            metrics: metrics.get(&f.path).copied().unwrap_or_default(),
+            description: summary_of(f).as_ref().and_then(leading_doc),
+        })
+        .collect();
+
+    let mut entry_points: Vec<&FileInfo> = files.iter().filter(|f| f.entry_point).collect();
+    entry_points.sort_by(|a, b| {
+        rank(&b.path)
+            .total_cmp(&rank(&a.path))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    let entry_points = entry_points
+        .into_iter()
+        .take(ENTRY_POINTS)
+        .map(|f| f.path.clone())
+        .collect();
+
+    let mut key_types = Vec::new();
+    let mut seen = HashSet::new();
+    for f in ranked.iter().take(KEY_TYPE_FILES) {
+        let Some(summary) = summary_of(f) else {
+            continue;
+        };
+        let declared = summary
+            .lines
+            .iter()
+            .filter(|l| l.kind == SummaryKind::Signature)
+            .filter_map(|l| declared_type(&l.text));
+        for name in declared.take(KEY_TYPES_PER_FILE) {
+            if seen.insert(name.clone()) {
+                key_types.push(KeyType {
+                    name,
+                    path: f.path.clone(),
+                });
+            }
+        }
+        if key_types.len() >= KEY_TYPES {
+            break;
+        }
+    }
+    key_types.truncate(KEY_TYPES);
+
+    let directories = directories(files)
+        .into_iter()
+        .map(|(path, members)| {
+            let purpose = DIRECTORY_DOCS
+                .iter()
+                .filter_map(|name| by_path.get(format!("{path}/{name}").as_str()))
+                .find_map(|f| summary_of(f).as_ref().and_then(leading_doc))
+                .unwrap_or_else(|| fallback_purpose(&members));
+            Directory {
+                path,
+                files: members.len(),
+                purpose,
+            }
+        })
+        .collect();
+
+    Overview {
+        languages,
+        directories,
+        core_modules,
+        entry_points,
+        key_types,
+    }
+}
+
+/// Top-level directories, largest first. A directory holding only
+/// subdirectories (`crates/`, `packages/`) is replaced by its children.
+pub(super) fn directories(files: &[FileInfo]) -> Vec<(String, Vec<&FileInfo>)> {
+    let mut top: BTreeMap<&str, Vec<&FileInfo>> = BTreeMap::new();
+    for f in files {
+        if let Some((dir, _)) = f.path.split_once('/') {
+            top.entry(dir).or_default().push(f);
+        }
+    }
+
+    let mut dirs: Vec<(String, Vec<&FileInfo>)> = Vec::new();
+    for (dir, members) in top {
+        let mut children: BTreeMap<&str, Vec<&FileInfo>> = BTreeMap::new();
+        let mut direct = false;
+        for f in &members {
+            match f.path[dir.len() + 1..].split_once('/') {
+                Some((child, _)) => children.entry(child).or_default().push(f),
+                None => direct = true,
+            }
+        }
+        if !direct && children.len() > 1 {
+            dirs.extend(
+                children
+                    .into_iter()
+                    .map(|(child, m)| (format!("{dir}/{child}"), m)),
+            );
+        } else {
+            dirs.push((dir.to_string(), members));
+        }
+    }
+    dirs.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+    dirs.truncate(DIRECTORIES);
+    dirs
+}
+
+/// A purpose for a directory without a describing doc comment, from the
+/// dominant role and language of its files.
+fn fallback_purpose(files: &[&FileInfo]) -> String {
+    let dominant_role = most_common(files.iter().map(|f| f.role.as_str()));
+    let role = match dominant_role {
+        Some("test") => "Tests",
+        Some("docs") => "Documentation",
+        Some("config") => "Configuration",
+        Some("build") => "Build scripts",
+        Some("generated") => "Generated files",
+        _ => "Source",
+    };
+    let language = most_common(
+        files
+            .iter()
+            .filter(|f| f.language != Language::Other)
+            .map(|f| f.language.as_str()),
+    );
+    match language {
+        Some(lang) if role == "Source" => format!("{lang} source"),
+        Some(lang) => format!("{role} ({lang})"),
+        None => role.to_string(),
+    }
+}
+
+fn most_common<'a>(items: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for item in items {
+        *counts.entry(item).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(item, _)| item)
+}
+
+/// The first sentence of a file's leading doc comment, without comment
+/// markers.
+fn leading_doc(summary: &FileSummary) -> Option<String> {
+    let mut text = String::new();
+    for line in summary
+        .lines
+        .iter()
+        .take_while(|l| l.kind == SummaryKind::Header)
+    {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&doc_text(&line.text));
+        if let Some(end) = text.find(". ") {
+            text.truncate(end + 1);
+            break;
+        }
+        if text.ends_with('.') {
+            break;
+        }
+    }
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Strip comment and docstring markers from a doc line.
+fn doc_text(line: &str) -> String {
+    let text = line
+        .trim()
+        .trim_start_matches("@moduledoc")
+        .trim_start_matches(['/', '!', '#', '*', '-', ' '])
+        .trim_start_matches("\"\"\"")
+        .trim_start_matches("'''");
+    text.trim_end_matches("*/")
+        .trim_end_matches("\"\"\"")
+        .trim_end_matches("'''")
+        .trim()
+        .to_string()
+}
+
+/// The name a signature declares, if it declares a type.
+fn declared_type(signature: &str) -> Option<String> {
+    let mut words = signature.split_whitespace();
+    while let Some(word) = words.next() {
+        if TYPE_KEYWORDS.contains(&word) {
+            let name: String = words
+                .next()?
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            return (!name.is_empty()).then_some(name);
+        }
+    }
+    None
+}
+
+/// The overview as a Markdown section between the overview markers.
+pub fn render_markdown(overview: &Overview) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{OVERVIEW_START}");
+    let _ = writeln!(out, "## Repository Overview\n");
+    let _ = writeln!(
+        out,
+        "_Generated by `topo overview --write` from the index and import graph; rerun it to refresh._\n"
+    );
+
+    if !overview.languages.is_empty() {
+        let languages: Vec<String> = overview
+            .languages
+            .iter()
+            .map(|(l, n)| format!("{} ({n})", l.as_str()))
+            .collect();
+        let _ = writeln!(out, "**Languages:** {}\n", languages.join(", "));
+    }
+
+    if !overview.directories.is_empty() {
+        let _ = writeln!(out, "### Directories\n");
+        for d in &overview.directories {
+            let files = match d.files {
+                1 => "1 file".to_string(),
+                n => format!("{n} files"),
+            };
+            let _ = writeln!(out, "- `{}/` — {} ({files})", d.path, d.purpose);
+        }
+        out.push('\n');
+    }
+
+    if !overview.core_modules.is_empty() {
+        let _ = writeln!(out, "### Core modules\n");
+        for m in &overview.core_modules {
+            let mut structure = match m.metrics.fan_in {
+                1 => "1 importer".to_string(),
+                n => format!("{n} importers"),
+            };
+            let _ = write!(structure, ", imports {}", m.metrics.fan_out);
+            if let Some(depth) = m.metrics.depth {
+                let _ = write!(structure, ", depth {depth}");
+            }
+            match &m.description {
+                Some(d) => {
+                    let _ = writeln!(out, "- `{}` — {d} ({structure})", m.path);
+                }
+                None => {
+                    let _ = writeln!(out, "- `{}` ({structure})", m.path);
+                }
+            }
+        }
+        out.push('\n');
+    }
+
+    if !overview.entry_points.is_empty() {
+        let _ = writeln!(out, "### Entry points\n");
+        for p in &overview.entry_points {
+            let _ = writeln!(out, "- `{p}`");
+        }
+        out.push('\n');
+    }
+
+    if !overview.key_types.is_empty() {
+        let _ = writeln!(out, "### Key types\n");
+        for t in &overview.key_types {
+            let _ = writeln!(out, "- `{}` in `{}`", t.name, t.path);
+        }
+        out.push('\n');
+    }
+
+    let _ = writeln!(out, "{OVERVIEW_END}");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn file(path: &str, entry_point: bool) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size: 100,
+            language: Language::from_path(Path::new(path)),
+            role: FileRole::from_path(Path::new(path)),
+            sha256: [0; 32],
+            lines: 10,
+            executable: false,
+            shebang: None,
+            entry_point,
+            license: None,
+        }
+    }
+
+    fn sources() -> HashMap<&'static str, &'static str> {
+        HashMap::from([
+            (
+                "crates/core/src/lib.rs",
+                "//! Shared types.\n\npub struct Session;\npub enum Role {}\n",
+            ),
+            ("crates/cli/src/main.rs", "fn main() {}\n"),
+            ("docs/guide.md", "# Guide\n"),
+            ("tests/cli.rs", "#[test]\nfn t() {}\n"),
+        ])
+    }
+
+    fn overview() -> Overview {
+        let files: Vec<FileInfo> = sources()
+            .keys()
+            .map(|p| file(p, p.ends_with("main.rs")))
+            .collect();
+        let pagerank = HashMap::from([
+            ("crates/core/src/lib.rs".to_string(), 1.0),
+            ("crates/cli/src/main.rs".to_string(), 0.2),
+        ]);
+        let mut graph = topo_score::ImportGraph::new();
+        graph.add_edge("crates/cli/src/main.rs", "crates/core/src/lib.rs");
+        let metrics = graph.metrics(["crates/cli/src/main.rs"]);
+        let sources = sources();
+        build(&files, &pagerank, &metrics, |f| {
+            Some(topo_treesit::summarize(
+                sources[f.path.as_str()],
+                f.language,
+            ))
+        })
+    }
+
+    #[test]
+    fn builds_modules_types_and_directories() {
+        let o = overview();
+        assert_eq!(o.core_modules[0].path, "crates/core/src/lib.rs");
+        assert_eq!(
+            o.core_modules[0].description.as_deref(),
+            Some("Shared types.")
+        );
+        assert_eq!(o.entry_points, vec!["crates/cli/src/main.rs"]);
+        let types: Vec<&str> = o.key_types.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(types, vec!["Session", "Role"]);
+
+        let dirs: Vec<(&str, &str)> = o
+            .directories
+            .iter()
+            .map(|d| (d.path.as_str(), d.purpose.as_str()))
+            .collect();
+        assert_eq!(
+            dirs,
+            vec![
+                ("crates/cli", "rust source"),
+                ("crates/core", "Shared types."),
+                ("docs", "Documentation (markdown)"),
+                ("tests", "Tests (rust)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_is_wrapped_in_markers() {
+        let md = render_markdown(&overview());
+        assert!(md.starts_with(OVERVIEW_START));
+        assert!(md.trim_end().ends_with(OVERVIEW_END));
+        assert!(md.contains(
+            "- `crates/core/src/lib.rs` — Shared types. (1 importer, imports 0, depth 1)"
+        ));
+        assert!(md.contains("- `Session` in `crates/core/src/lib.rs`"));
+    }
+
+    #[test]
+    fn write_replaces_only_the_overview_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("AGENTS.md");
+        std::fs::write(
+            &path,
+            format!("# Agents\n\n{OVERVIEW_START}\nstale\n{OVERVIEW_END}\n\n## Rules\n"),
+        )
+        .unwrap();
+        let md = render_markdown(&overview());
+        inject_section(&path, &md, (OVERVIEW_START, OVERVIEW_END), true).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# Agents\n\n"));
+        assert!(!content.contains("stale"));
+        assert!(content.contains("Shared types."));
+        assert!(content.ends_with("## Rules\n"));
+    }
+
+    #[test]
+    fn doc_text_strips_markers() {
+        assert_eq!(doc_text("//! Shared types."), "Shared types.");
+        assert_eq!(doc_text("\"\"\"Billing helpers.\"\"\""), "Billing helpers.");
+        assert_eq!(doc_text("/** Fetch things. */"), "Fetch things.");
+        assert_eq!(declared_type("pub struct Foo<T>"), Some("Foo".to_string()));
+        assert_eq!(
+            declared_type("type Handler func()"),
+            Some("Handler".to_string())
+        );
+        assert_eq!(declared_type("pub fn new()"), None);
+    }
+}
@@ -0,0 +1,242 @@
+use super::query::in_scope;
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use topo_core::ScoredFile;
+use topo_scanner::{CodeOwners, owner_matches};
+
+/// Authors listed per file.
+const TOP_AUTHORS: usize = 3;
+
+/// An author of a file and how many of its current lines they last touched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Author {
+    pub name: String,
+    pub email: String,
+    pub lines: usize,
+}
+
+impl Author {
+    /// Whether `who` names this author, by name or email.
+    pub fn is(&self, who: &str) -> bool {
+        owner_matches(&self.name, who) || owner_matches(&self.email, who)
+    }
+}
+
+/// Authors of `path` by the lines `git blame` attributes to them, most
+/// lines first. Empty outside git or for files git doesn't track.
+pub fn blame_summary(root: &Path, path: &str) -> Vec<Author> {
+    let output = Command::new("git")
+        .args(["blame", "--line-porcelain", "--", path])
+        .current_dir(root)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_line_porcelain(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Tally `git blame --line-porcelain` output by author.
+fn parse_line_porcelain(blame: &str) -> Vec<Author> {
+    let mut counts: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut name = "";
+    for line in blame.lines() {
+        if let Some(v) = line.strip_prefix("author ") {
+            name = v;
+        } else if let Some(v) = line.strip_prefix("author-mail ") {
+            let email = v.trim_start_matches('<').trim_end_matches('>');
+            *counts.entry((name, email)).or_default() += 1;
+        }
+    }
+    let mut authors: Vec<Author> = counts
+        .into_iter()
+        .map(|((name, email), lines)| Author {
+            name: name.to_string(),
+            email: email.to_string(),
+            lines,
+        })
+        .collect();
+    authors.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.name.cmp(&b.name)));
+    authors
+}
+
+/// Ownership of one file as reported by `topo owners`.
+struct FileOwners<'a> {
+    path: &'a str,
+    owners: Option<&'a [String]>,
+    authors: Vec<Author>,
+}
+
+/// Whether `who` owns `path`: its CODEOWNERS rule lists them, or, when no
+/// rule covers the file, they are its primary author by blame.
+fn owned_by(root: &Path, codeowners: &CodeOwners, path: &str, who: &str) -> bool {
+    match codeowners.owners_of(path) {
+        Some(owners) => owners.iter().any(|o| owner_matches(o, who)),
+        None => blame_summary(root, path)
+            .first()
+            .is_some_and(|author| author.is(who)),
+    }
+}
+
+/// `--owner`: keep only the files `who` owns.
+pub fn retain_owned(root: &Path, who: &str, files: &mut Vec<ScoredFile>) -> Result<()> {
+    let codeowners = CodeOwners::load(root)?;
+    let owned: Vec<bool> = files
+        .par_iter()
+        .map(|f| owned_by(root, &codeowners, &f.path, who))
+        .collect();
+    let mut owned = owned.into_iter();
+    files.retain(|_| owned.next().unwrap_or(false));
+    log::info!("Owned by {who} ({} files)", files.len());
+    Ok(())
+}
+
+pub fn run(cli: &Cli, path: Option<&str>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let scope = match path {
+        Some(path) => {
+            let relative = cli.repo_relative(&root, path);
+            let relative = relative.trim_end_matches('/');
+            if !root.join(relative).exists() {
+                bail!(
+                    CliError::not_found(format!("{path} does not exist"))
+                        .hint("Pass a file or directory inside the repository.")
+                );
+            }
+            if relative.is_empty() {
+                ".".to_string()
+            } else {
+                relative.to_string()
+            }
+        }
+        None => ".".to_string(),
+    };
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let codeowners = CodeOwners::load(&root)?;
+    let files: Vec<FileOwners> = bundle
+        .files
+        .par_iter()
+        .filter(|f| in_scope(&f.path, &scope))
+        .map(|f| {
+            let mut authors = blame_summary(&root, &f.path);
+            authors.truncate(TOP_AUTHORS);
+            FileOwners {
+                path: &f.path,
+                owners: codeowners.owners_of(&f.path),
+                authors,
+            }
+        })
+        .collect();
+    if files.is_empty() {
+        log::warn!("no scanned files under {scope}");
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let entries: Vec<serde_json::Value> = files
+                .iter()
+                .map(|f| {
+                    serde_json::json!({
+                        "path": f.path,
+                        "owners": f.owners,
+                        "rule": codeowners.pattern_for(f.path),
+                        "authors": f.authors.iter().map(|a| serde_json::json!({
+                            "name": a.name,
+                            "email": a.email,
+                            "lines": a.lines,
+                        })).collect::<Vec<_>>(),
+                    })
+                })
+                .collect();
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else {
+                for entry in &entries {
+                    println!("{}", serde_json::to_string(entry)?);
+                }
+            }
+        }
+        OutputFormat::Compact => {
+            for f in &files {
+                println!(
+                    "{}\t{}\t{}",
+                    f.path,
+                    f.owners.map_or(String::new(), |o| o.join(",")),
+                    f.authors.first().map_or("", |a| a.name.as_str())
+                );
+            }
+        }
+        _ => {
+            for f in &files {
+                println!("{}", f.path);
+                match f.owners {
+                    Some([]) => println!("  owners:  (unowned)"),
+                    Some(owners) => println!("  owners:  {}", owners.join(" ")),
+                    None => {}
+                }
+                if !f.authors.is_empty() {
+                    let listed: Vec<String> = f
+                        .authors
+                        .iter()
+                        .map(|a| format!("{} ({} lines)", a.name, a.lines))
+                        .collect();
+                    println!("  authors: {}", listed.join(", "));
+                }
+            }
+            if codeowners.is_empty() {
+                println!("\nNo CODEOWNERS file; owners come from git blame only.");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_porcelain_tallies_authors() {
+        let blame = "\
+abc123 1 1 2
+author Alice
+author-mail <alice@example.com>
+summary first
+\tfn a() {}
+abc123 2 2
+author Alice
+author-mail <alice@example.com>
+\tfn b() {}
+def456 3 3 1
+author Bob
+author-mail <bob@example.com>
+\tfn c() {}
+";
+        let authors = parse_line_porcelain(blame);
+        assert_eq!(
+            authors,
+            [
+                Author {
+                    name: "Alice".to_string(),
+                    email: "alice@example.com".to_string(),
+                    lines: 2,
+                },
+                Author {
+                    name: "Bob".to_string(),
+                    email: "bob@example.com".to_string(),
+                    lines: 1,
+                },
+            ]
+        );
+        assert!(authors[0].is("alice"));
+        assert!(authors[1].is("bob@example.com"));
+        assert!(!authors[1].is("alice"));
+    }
+}
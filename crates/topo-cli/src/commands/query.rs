@@ -1,43 +1,582 @@
+use crate::error::{CliError, ErrorCode};
+use crate::last_query::{self, LastQuery};
 use crate::preset::Preset;
-use crate::{Cli, OutputFormat};
-use anyhow::Result;
-use topo_core::{DeepIndex, ScoredFile, TokenBudget};
-use topo_render::{CompactWriter, JsonlWriter};
-use topo_scanner::BundleBuilder;
-use topo_score::{HybridScorer, RrfFusion};
-
-pub fn run(
-    cli: &Cli,
-    task: &str,
-    preset: Preset,
-    max_bytes: Option<u64>,
-    max_tokens: Option<u64>,
-    min_score: Option<f64>,
-    top: Option<usize>,
-) -> Result<()> {
+use crate::semantic::{self, Retrieval};
+use crate::stats::{self, StatsEvent};
+use crate::{Cli, GroupBy, OutputFormat};
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Instant;
+use topo_core::{
+    Bundle, ChunkKind, Config, DeepIndex, DiskFiles, FileInfo, FileProvider, OutlineEntry,
+    ScoredFile, TokenBudget, Truncation, WeightsConfig,
+};
+use topo_render::{CompactWriter, ContentPolicy, JsonlWriter, MarkdownWriter, XmlWriter};
+use topo_score::{
+    ChunkScorer, Churn, Clusters, CoChange, FileTypeRule, FilteredQuery, MultiQueryResult,
+    MultiQueryScorer, PageRankParams, PhraseMatcher, QueryExpander, ResolveOptions, SignalWeights,
+    StopWords, TestPairing, Tokenizer, VendoredPolicy,
+};
+use topo_treesit::{Chunker, TreeSitterChunker};
+
+/// Selection settings shared by `query` and `quick`.
+#[derive(Debug, Clone)]
+pub struct QueryOptions {
+    pub preset: Preset,
+    pub max_bytes: Option<u64>,
+    pub max_tokens: Option<u64>,
+    pub min_score: Option<f64>,
+    pub top: Option<usize>,
+    /// `--semantic-weight`; falls back to `[embeddings] semantic_weight`.
+    pub semantic_weight: Option<f64>,
+    /// `--semantic-only`; falls back to `[embeddings] semantic_only`.
+    pub semantic_only: bool,
+    /// `--embedder`; overrides `[embeddings] provider`.
+    pub embedder: Option<String>,
+    /// `--rerank`: re-score the top candidates even if the preset doesn't.
+    pub rerank: bool,
+    /// `--fail-on-empty`: exit non-zero when no file is selected.
+    pub fail_on_empty: bool,
+    /// `--outline`: list up to this many symbols of each selected file.
+    pub outline: Option<usize>,
+    /// `--no-expand`: score the task without expansion terms.
+    pub no_expand: bool,
+    /// `--weights`: overrides `[weights]`.
+    pub weights: Option<WeightsConfig>,
+    /// `--since`: seed co-change with the files changed since this revision.
+    pub since: Option<String>,
+    /// `--scope`: select only files under this path, ranked against the
+    /// whole repository.
+    pub scope: Option<String>,
+    /// `--owner`: select only files this person or team owns.
+    pub owner: Option<String>,
+    /// `--group-by`: present the selection grouped, e.g. by cluster.
+    pub group_by: Option<GroupBy>,
+}
+
+pub fn run(cli: &Cli, task: &str, options: QueryOptions) -> Result<ExitCode> {
     let root = cli.repo_root()?;
+    let started = Instant::now();
+    let filters = parse_task(task)?;
+    let task = filters.text.as_str();
 
     // Scan files
-    let bundle = BundleBuilder::new(&root).build()?;
+    let mut bundle = cli.bundle_builder(&root).build()?;
+    let scanned = bundle.files.clone();
+    apply_package_scope(cli, &root, &mut bundle);
+    apply_license_filter(cli, &mut bundle);
+    apply_query_filters(&filters, &mut bundle);
 
-    // Load deep index for PageRank when using structural signals
-    let deep_index = if preset.use_structural_signals() {
-        topo_index::load(&root)?
+    // Load deep index for PageRank when using structural signals, and for
+    // outlines
+    let deep_index = if options.preset.use_structural_signals()
+        || options.preset.chunk_embeddings()
+        || options.outline.is_some()
+    {
+        load_index_for(&root, task)?
     } else {
         None
     };
 
+    select(
+        cli,
+        &root,
+        &bundle,
+        &scanned,
+        deep_index.as_ref(),
+        task,
+        options,
+        started,
+    )
+}
+
+/// A `--scope` path as a repo-relative prefix without `./` or a trailing
+/// slash. It must exist under `root`.
+fn normalize_scope(root: &Path, scope: &str) -> Result<String> {
+    let trimmed = scope.trim_start_matches("./").trim_end_matches('/');
+    let normalized = if trimmed.is_empty() { "." } else { trimmed };
+    if !root.join(normalized).exists() {
+        bail!(
+            CliError::not_found(format!("--scope {scope} does not exist"))
+                .hint("Pass a file or directory relative to the repository root.")
+        );
+    }
+    Ok(normalized.replace('\\', "/"))
+}
+
+/// Whether `path` is `scope` itself or lies under it.
+pub(crate) fn in_scope(path: &str, scope: &str) -> bool {
+    scope == "."
+        || path
+            .strip_prefix(scope)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Restrict a scanned bundle to the monorepo package containing the current
+/// directory, unless `--whole-repo` was given.
+pub fn apply_package_scope(cli: &Cli, root: &Path, bundle: &mut Bundle) {
+    let Some(package) = cli.package_scope(root) else {
+        return;
+    };
+    let prefix = format!("{package}/");
+    bundle.files.retain(|f| f.path.starts_with(&prefix));
+    log::info!(
+        "Scoped to package {package} ({} files); use --whole-repo to search everything",
+        bundle.file_count()
+    );
+}
+
+/// Drop files whose license names one of the `--exclude-license` ids.
+pub fn apply_license_filter(cli: &Cli, bundle: &mut Bundle) {
+    exclude_licenses(bundle, &cli.exclude_license);
+}
+
+/// Drop files whose license names one of `ids`.
+pub fn exclude_licenses(bundle: &mut Bundle, ids: &[String]) {
+    if ids.is_empty() {
+        return;
+    }
+    let before = bundle.file_count();
+    bundle.files.retain(|f| !is_license_excluded(f, ids));
+    let excluded = before - bundle.file_count();
+    if excluded > 0 {
+        log::info!("Excluded {excluded} files licensed {}", ids.join(", "));
+    }
+}
+
+/// Whether `file`'s license names one of `ids`.
+pub fn is_license_excluded(file: &FileInfo, ids: &[String]) -> bool {
+    file.license.as_deref().is_some_and(|license| {
+        ids.iter()
+            .any(|id| topo_scanner::license_mentions(license, id))
+    })
+}
+
+/// Split the `lang:`, `role:` and `path:` filters off a task. The rest of
+/// the task must still say what to look for.
+pub fn parse_task(task: &str) -> Result<FilteredQuery> {
+    let filters = FilteredQuery::parse(task).map_err(|e| CliError::usage(e.to_string()))?;
+    if filters.has_filters() && filters.text.trim().is_empty() {
+        bail!(
+            CliError::usage("the task has filters but nothing to search for")
+                .hint("Add words to rank by, as in: topo query \"auth middleware lang:rust\".")
+        );
+    }
+    Ok(filters)
+}
+
+/// Keep only the files that pass the task's `lang:`, `role:` and `path:`
+/// filters.
+pub fn apply_query_filters(filters: &FilteredQuery, bundle: &mut Bundle) {
+    if !filters.has_filters() {
+        return;
+    }
+    bundle.files.retain(|f| filters.matches(f));
+    log::info!("Filtered to {} files", bundle.file_count());
+}
+
+/// Stop words for a repository: the built-in list adjusted by the
+/// `[stop_words]` section of `.topo/config.toml`.
+pub fn stop_words(root: &Path) -> Result<StopWords> {
+    let config = Config::load(root)?;
+    Ok(StopWords::from_config(&config.stop_words))
+}
+
+/// Ranking settings from `.topo/config.toml`: stop words and signal weights.
+#[derive(Debug, Clone)]
+pub struct Scoring {
+    pub stop_words: StopWords,
+    pub weights: SignalWeights,
+    /// Expand the task with co-occurring terms from the deep index.
+    pub expand: bool,
+    /// Commit activity per file, fused alongside PageRank; empty unless
+    /// loaded by [`Scoring::git_history`].
+    pub git_recency: HashMap<String, f64>,
+    /// Previous path of each file renamed within the git recency window.
+    pub renamed_from: HashMap<String, String>,
+    /// Commits mined for files that change with the query's top files (or
+    /// with `--since` changes); empty unless loaded by
+    /// [`Scoring::git_history`].
+    pub co_change: CoChange,
+    /// Commits per file over `[churn] window_days`, normalized; empty
+    /// unless loaded by [`Scoring::git_history`].
+    pub churn: HashMap<String, f64>,
+    /// `[churn] window_days`, or the default window.
+    pub churn_window: u32,
+    /// How vendored and generated files are scored.
+    pub vendored: VendoredPolicy,
+    /// Multipliers for lockfiles, snapshots and other artifacts.
+    pub file_type_rules: Vec<FileTypeRule>,
+}
+
+impl Scoring {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            stop_words: StopWords::from_config(&config.stop_words),
+            weights: SignalWeights::from_config(&config.weights),
+            expand: true,
+            git_recency: HashMap::new(),
+            renamed_from: HashMap::new(),
+            co_change: CoChange::default(),
+            churn: HashMap::new(),
+            churn_window: Churn::window(&config.churn),
+            vendored: VendoredPolicy::from_config(&config.vendored),
+            file_type_rules: topo_score::file_type_rules(&config.file_types),
+        }
+    }
+
+    /// `--include-vendored`: score vendored and generated files like any
+    /// other, overriding `[vendored] policy`.
+    pub fn include_vendored(mut self, include: bool) -> Self {
+        if include {
+            self.vendored = VendoredPolicy::Include;
+        }
+        self
+    }
+
+    /// Lay the weights of `preset`, then `--weights`, over the `[weights]`
+    /// of `config`.
+    pub fn weights(
+        mut self,
+        config: &Config,
+        preset: &Preset,
+        overrides: Option<&WeightsConfig>,
+    ) -> Result<Self> {
+        let mut weights = config
+            .weights
+            .overlay(preset.weights())
+            .map_err(|e| CliError::config(format!("preset {preset}: {e}")))?;
+        if let Some(overrides) = overrides {
+            weights = weights
+                .overlay(overrides)
+                .map_err(|e| CliError::usage(format!("invalid --weights: {e}")))?;
+        }
+        self.weights = SignalWeights::from_config(&weights);
+        Ok(self)
+    }
+
+    /// Load commit activity, co-change history and churn for `root` when
+    /// `preset` uses structural signals and their weights are not 0. Without git,
+    /// scoring goes on without them.
+    pub fn git_history(mut self, root: &Path, preset: &Preset) -> Self {
+        if !preset.use_structural_signals() {
+            return self;
+        }
+        if self.weights.git_recency > 0.0 {
+            let recency = topo_score::git_recency(root).unwrap_or_else(|e| {
+                log::warn!("git recency unavailable: {e}");
+                Default::default()
+            });
+            self.git_recency = recency.scores;
+            self.renamed_from = recency.renamed_from;
+        }
+        if self.weights.co_change > 0.0 {
+            self.co_change = CoChange::load(root).unwrap_or_else(|e| {
+                log::warn!("co-change history unavailable: {e}");
+                CoChange::default()
+            });
+        }
+        if self.weights.churn > 0.0 {
+            self.churn = Churn::load(root, self.churn_window)
+                .map(|churn| churn.scores())
+                .unwrap_or_else(|e| {
+                    log::warn!("churn unavailable: {e}");
+                    HashMap::new()
+                });
+        }
+        self
+    }
+
+    /// Seed co-change with the files changed since `rev` (`--since`)
+    /// rather than the query's top files.
+    pub fn since(mut self, root: &Path, rev: Option<&str>) -> Result<Self> {
+        let Some(rev) = rev else {
+            return Ok(self);
+        };
+        if self.co_change.is_empty() {
+            log::warn!("--since only applies to presets with structural signals and git history");
+            return Ok(self);
+        }
+        let changed = topo_score::changed_since(root, rev)
+            .map_err(|e| CliError::usage(format!("invalid --since: {e}")))?;
+        self.co_change = self.co_change.seeds(changed);
+        Ok(self)
+    }
+
+    /// The weights `preset` actually applies: BM25F and heuristic
+    /// normalized, and no fusion weights without structural signals.
+    pub fn effective_weights(&self, preset: &Preset) -> SignalWeights {
+        let (bm25f, heuristic) = self.weights.base();
+        let structural = preset.use_structural_signals();
+        SignalWeights {
+            bm25f,
+            heuristic,
+            pagerank: if structural {
+                self.weights.pagerank
+            } else {
+                0.0
+            },
+            git_recency: if structural {
+                self.weights.git_recency
+            } else {
+                0.0
+            },
+            co_change: if structural {
+                self.weights.co_change
+            } else {
+                0.0
+            },
+            churn: if structural { self.weights.churn } else { 0.0 },
+            rrf_k: self.weights.rrf_k,
+        }
+    }
+}
+
+/// Ranking settings for a repository from `.topo/config.toml`, with the
+/// weights of `preset`.
+pub fn scoring(root: &Path, preset: &Preset) -> Result<Scoring> {
+    let config = Config::load(root)?;
+    Scoring::from_config(&config).weights(&config, preset, None)
+}
+
+/// The deep index, loaded for scoring `task`: a SQLite index reads only the
+/// task's terms and, for query expansion, the files they occur in most.
+pub fn load_index_for(root: &Path, task: &str) -> Result<Option<DeepIndex>> {
+    let terms = Tokenizer::tokenize_with(task, &stop_words(root)?);
+    let mut index = topo_index::load_terms(root, &terms)?;
+    if let Some(index) = index.as_mut() {
+        topo_index::load_cooccurring(root, index, &terms)?;
+    }
+    Ok(index)
+}
+
+/// Import resolution settings for a repository from `.topo/config.toml`.
+pub fn resolve_options(root: &Path) -> Result<ResolveOptions> {
+    let config = Config::load(root)?;
+    Ok(ResolveOptions::from_config(&config))
+}
+
+/// PageRank settings for a repository from `.topo/config.toml`.
+pub fn pagerank_params(root: &Path) -> Result<PageRankParams> {
+    let config = Config::load(root)?;
+    Ok(PageRankParams::from_config(&config.pagerank))
+}
+
+/// Content guardrails for a repository from `.topo/config.toml`.
+pub fn content_policy(root: &Path) -> Result<ContentPolicy> {
+    let config = Config::load(root)?;
+    Ok(ContentPolicy::from_config(&config.content)?)
+}
+
+/// The files a query selected, and what the budget left out.
+#[derive(Debug, Clone)]
+pub struct Selection {
+    /// Ranked files that fit `--top` and the budget, best first.
+    pub files: Vec<ScoredFile>,
+    pub truncation: Truncation,
+    pub budget: TokenBudget,
+    pub min_score: f64,
+    /// The normalized `--scope` path.
+    pub scope: Option<String>,
+    /// Every file scoring at least `min_score`, best first, before `--top`
+    /// and the budget.
+    pub ranked: Vec<ScoredFile>,
+}
+
+/// Score, filter, budget and print an already-scanned bundle. The exit
+/// status is a failure only for an empty selection under `--fail-on-empty`.
+/// `scanned` is every file the scan found, before package, license and
+/// query filters narrowed `bundle`.
+///
+/// Under `TOPO_STATS`, a `topo_query` event timed from `started` is
+/// appended to `.topo/stats.jsonl`.
+#[allow(clippy::too_many_arguments)]
+pub fn select(
+    cli: &Cli,
+    root: &Path,
+    bundle: &Bundle,
+    scanned: &[FileInfo],
+    deep_index: Option<&DeepIndex>,
+    task: &str,
+    options: QueryOptions,
+    started: Instant,
+) -> Result<ExitCode> {
+    let preset = options.preset.clone();
+    let fail_on_empty = options.fail_on_empty;
+    let Selection {
+        files: budgeted,
+        truncation,
+        budget,
+        min_score,
+        scope,
+        ranked,
+    } = select_files(cli, root, bundle, scanned, deep_index, task, options)?;
+    if truncation.budget_dropped > 0 {
+        log::warn!(
+            "budget exhausted, {} ranked files (~{} tokens) dropped; raise --max-bytes/--max-tokens or run `topo more`",
+            truncation.budget_dropped,
+            truncation.budget_dropped_tokens
+        );
+    }
+
+    output_results(
+        cli,
+        root,
+        task,
+        preset.as_str(),
+        scope.as_deref(),
+        &budgeted,
+        bundle.file_count(),
+        &budget,
+        min_score,
+        &truncation,
+    )?;
+
+    if stats::enabled() {
+        let max_bytes = budget.max_bytes.unwrap_or(preset.default_max_bytes());
+        let mut event = StatsEvent::now("topo_query");
+        event.files_suggested = Some(budgeted.len());
+        event.tokens_suggested = Some(budgeted.iter().map(|f| f.tokens).sum());
+        event.latency_ms = Some(started.elapsed().as_millis() as u64);
+        event.preset = Some(preset.as_str().to_string());
+        event.budget_tokens = Some(
+            budget
+                .max_tokens
+                .map_or(max_bytes / 4, |t| t.min(max_bytes / 4)),
+        );
+        event.truncated = Some(truncation.budget_dropped > 0);
+        if let Err(e) = stats::append(root, &event) {
+            log::warn!("could not record query stats: {e}");
+        }
+    }
+
+    if fail_on_empty && budgeted.is_empty() {
+        let code = if ranked.is_empty() {
+            ErrorCode::NoResults
+        } else {
+            ErrorCode::EmptyBudget
+        };
+        log::info!("No files selected ({})", code.as_str());
+        return Ok(ExitCode::from(code.exit_code()));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Score, filter and budget an already-scanned bundle without printing,
+/// saving the full ranking for `topo more`.
+///
+/// `deep_index` feeds PageRank only when the preset uses structural
+/// signals, and chunk embeddings only when the preset asks for them;
+/// outlines are read from it regardless.
+pub fn select_files(
+    cli: &Cli,
+    root: &Path,
+    bundle: &Bundle,
+    scanned: &[FileInfo],
+    deep_index: Option<&DeepIndex>,
+    task: &str,
+    options: QueryOptions,
+) -> Result<Selection> {
+    let QueryOptions {
+        preset,
+        max_bytes,
+        max_tokens,
+        min_score,
+        top,
+        semantic_weight,
+        semantic_only,
+        embedder,
+        rerank,
+        fail_on_empty: _,
+        outline,
+        no_expand,
+        weights,
+        since,
+        scope,
+        owner,
+        group_by,
+    } = options;
+    if outline.is_some() && deep_index.is_none() {
+        bail!(CliError::no_index(root));
+    }
+    let scope = scope.map(|s| normalize_scope(root, &s)).transpose()?;
+
     // Score files
-    let scored = score_files(task, &bundle.files, preset, deep_index.as_ref());
+    let config = Config::load(root)?;
+    let scoring = Scoring {
+        expand: !no_expand,
+        ..Scoring::from_config(&config)
+    }
+    .include_vendored(cli.include_vendored)
+    .weights(&config, &preset, weights.as_ref())?
+    .git_history(root, &preset)
+    .since(root, since.as_deref())?;
+    let embeddings = semantic::with_embedder(&config.embeddings, embedder.as_deref());
+    let retrieval = Retrieval::resolve(semantic_weight, semantic_only, &embeddings)?;
+    let scoring_started = Instant::now();
+    let structural_index = deep_index.filter(|_| preset.use_structural_signals());
+    let mut scored = score_files(task, &bundle.files, &preset, structural_index, &scoring);
+    if let Some(scope) = &scope {
+        // Scored against the whole bundle so IDF, PageRank and co-change
+        // still see the rest of the repository
+        scored.retain(|f| in_scope(&f.path, scope));
+        log::info!("Scoped to {scope} ({} files)", scored.len());
+    }
+    let policy = ContentPolicy::from_config(&config.content)?;
+    let chunks = match deep_index {
+        Some(index) if preset.chunk_embeddings() && retrieval != Retrieval::Lexical => {
+            let paths: Vec<&str> = scanned.iter().map(|f| f.path.as_str()).collect();
+            Some(topo_index::chunks_for(root, index, &paths)?)
+        }
+        _ => None,
+    };
+    semantic::apply(
+        root,
+        &bundle.files,
+        scanned,
+        chunks.as_ref(),
+        task,
+        retrieval,
+        &embeddings,
+        &policy,
+        &mut scored,
+    )?;
+    if rerank || preset.rerank() {
+        crate::rerank::apply(root, task, &config.rerank, &policy, rerank, &mut scored)?;
+    }
 
     // Apply score filter
     let effective_min_score = min_score.unwrap_or(preset.default_min_score());
-    let mut filtered: Vec<ScoredFile> = scored
+    log::debug!(
+        "Scored {} files in {:.1?} (preset {preset}, {}, weights bm25f {} / heuristic {} / pagerank {} / git recency {} / co-change {} / churn {}, rrf k {}, min score {effective_min_score})",
+        scored.len(),
+        scoring_started.elapsed(),
+        if structural_index.is_some() {
+            "deep index"
+        } else {
+            "shallow"
+        },
+        scoring.weights.bm25f,
+        scoring.weights.heuristic,
+        scoring.weights.pagerank,
+        scoring.weights.git_recency,
+        scoring.weights.co_change,
+        scoring.weights.churn,
+        scoring.weights.rrf_k
+    );
+    let (mut ranked, below): (Vec<ScoredFile>, Vec<ScoredFile>) = scored
         .into_iter()
-        .filter(|f| f.score >= effective_min_score)
-        .collect();
+        .partition(|f| f.score >= effective_min_score);
+    if let Some(owner) = &owner {
+        // After the score filter, so only files that could be selected are
+        // blamed
+        super::owners::retain_owned(root, owner, &mut ranked)?;
+    }
 
     // Apply top-N filter
+    let mut filtered = ranked.clone();
     if let Some(n) = top {
         filtered.truncate(n);
     }
@@ -48,73 +587,240 @@ pub fn run(
         max_bytes: Some(effective_max_bytes),
         max_tokens,
     };
-    let budgeted = budget.enforce(&filtered);
+    let mut budgeted = budget.enforce(&filtered);
+    let truncation = Truncation::from_cuts(
+        &filtered[budgeted.len()..],
+        &ranked[filtered.len()..],
+        &below,
+    );
+    log::debug!(
+        "{} ranked, {} below min score; budget of {effective_max_bytes} bytes{} kept {}",
+        ranked.len(),
+        below.len(),
+        max_tokens.map_or(String::new(), |t| format!(" / {t} tokens")),
+        budgeted.len()
+    );
+    // Markdown embeds the chunks' source, so it needs them under any preset
+    if preset.chunks() || matches!(cli.effective_format(), OutputFormat::Markdown) {
+        attach_chunks(
+            root,
+            &DiskFiles::new(root),
+            task,
+            deep_index,
+            &scoring,
+            &mut budgeted,
+        )?;
+    }
+    if let (Some(limit), Some(index)) = (outline, deep_index) {
+        attach_outlines(root, index, limit, &mut budgeted)?;
+    }
+    let pairing = TestPairing::new(task);
+    if pairing.is_active() || group_by.is_some() {
+        let graph = topo_index::load_or_build_graph(root, &bundle.files);
+        pairing.pair(&mut budgeted, &bundle.files, &graph);
+        if group_by == Some(GroupBy::Cluster) {
+            Clusters::of(&graph).group(&mut budgeted);
+        }
+    }
 
-    // Output
-    output_results(
-        cli,
-        task,
-        preset,
-        &budgeted,
-        bundle.file_count(),
-        effective_max_bytes,
-        effective_min_score,
-    )?;
+    // Persist the full ranking so `topo more` can page beyond this budget
+    let mut last = LastQuery {
+        task: task.to_string(),
+        preset: preset.as_str().to_string(),
+        scope: scope.clone(),
+        max_bytes: effective_max_bytes,
+        max_tokens,
+        min_score: effective_min_score,
+        scanned_count: bundle.file_count(),
+        ranked,
+        delivered: Vec::new(),
+    };
+    last.mark_delivered(&budgeted);
+    if let Err(e) = last_query::save(root, &last) {
+        log::warn!("could not save last query: {e}");
+    }
+
+    Ok(Selection {
+        files: budgeted,
+        truncation,
+        budget,
+        min_score: effective_min_score,
+        scope,
+        ranked: last.ranked,
+    })
+}
 
+/// Point each selected file at the chunks that best match `task`, so a
+/// reader can open just those lines. Content comes from `provider`; files
+/// tree-sitter can't parse fall back to the chunks in `index`, if given.
+pub fn attach_chunks(
+    root: &Path,
+    provider: &dyn FileProvider,
+    task: &str,
+    index: Option<&DeepIndex>,
+    scoring: &Scoring,
+    files: &mut [ScoredFile],
+) -> Result<()> {
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    let indexed = match index {
+        Some(index) => topo_index::chunks_for(root, index, &paths)?,
+        None => Default::default(),
+    };
+    let scorer = ChunkScorer::new(task).stop_words(scoring.stop_words.clone());
+    for file in files.iter_mut() {
+        // The index holds declaration lines only; re-chunk the few selected
+        // files with tree-sitter for full ranges and bodies to score
+        let parsed = provider
+            .read_to_string(&file.path)
+            .map(|content| {
+                let language = topo_treesit::chunk_language(&content, file.language);
+                TreeSitterChunker.chunk(&content, language)
+            })
+            .unwrap_or_default();
+        if !parsed.is_empty() {
+            file.chunks = scorer.score(&parsed);
+        } else if let Some(chunks) = indexed.get(&file.path) {
+            file.chunks = scorer.score(chunks);
+        }
+    }
     Ok(())
 }
 
+/// List the first `limit` functions and types of each selected file, in
+/// source order, from the deep index.
+pub fn attach_outlines(
+    root: &Path,
+    index: &DeepIndex,
+    limit: usize,
+    files: &mut [ScoredFile],
+) -> Result<()> {
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    let mut chunks = topo_index::chunks_for(root, index, &paths)?;
+    for file in files.iter_mut() {
+        let Some(mut file_chunks) = chunks.remove(&file.path) else {
+            continue;
+        };
+        file_chunks.sort_by_key(|c| c.start_line);
+        file.outline = file_chunks
+            .into_iter()
+            .filter(|c| c.kind != ChunkKind::Import)
+            .take(limit)
+            .map(|c| OutlineEntry {
+                name: c.name,
+                kind: c.kind,
+                line: c.start_line,
+            })
+            .collect();
+    }
+    Ok(())
+}
+
+/// The `chunks` array of a JSON result; empty unless chunks were matched.
+pub fn chunks_json(file: &ScoredFile) -> serde_json::Value {
+    serde_json::json!(
+        file.chunks
+            .iter()
+            .map(|c| serde_json::json!({
+                "name": c.name,
+                "kind": c.kind.as_str(),
+                "start_line": c.start_line,
+                "end_line": c.end_line,
+            }))
+            .collect::<Vec<_>>()
+    )
+}
+
+/// The `outline` array of a JSON result; empty without `--outline`.
+pub fn outline_json(file: &ScoredFile) -> serde_json::Value {
+    serde_json::json!(
+        file.outline
+            .iter()
+            .map(|o| serde_json::json!({
+                "name": o.name,
+                "kind": o.kind.as_str(),
+                "line": o.line,
+            }))
+            .collect::<Vec<_>>()
+    )
+}
+
 pub fn score_files(
     task: &str,
     files: &[topo_core::FileInfo],
-    _preset: Preset,
+    preset: &Preset,
     deep_index: Option<&DeepIndex>,
+    scoring: &Scoring,
 ) -> Vec<ScoredFile> {
-    let scorer = HybridScorer::new(task);
-    let mut scored = scorer.score(files);
+    score_files_with_sub_queries(task, files, preset, deep_index, scoring).files
+}
 
-    // Apply PageRank via RRF fusion when available
-    if let Some(index) = deep_index
-        && !index.pagerank_scores.is_empty()
-    {
-        // Populate SignalBreakdown.pagerank for each scored file
-        for file in &mut scored {
-            file.signals.pagerank = index.pagerank_scores.get(&file.path).copied();
+/// Score files, also returning the top files for each sub-query when a long
+/// task was decomposed and the terms the task was expanded with.
+pub fn score_files_with_sub_queries(
+    task: &str,
+    files: &[topo_core::FileInfo],
+    _preset: &Preset,
+    deep_index: Option<&DeepIndex>,
+    scoring: &Scoring,
+) -> MultiQueryResult {
+    let weights = scoring.weights;
+    let expansion = match deep_index {
+        Some(index) if scoring.expand => {
+            let (terms, _) = Tokenizer::tokenize_query_with(task, &scoring.stop_words);
+            QueryExpander::new(index).expand(&terms)
         }
+        _ => Vec::new(),
+    };
+    if !expansion.is_empty() {
+        log::debug!("Expanded query with {}", expansion.join(", "));
+    }
+    let mut result = MultiQueryScorer::new(task)
+        .stop_words(scoring.stop_words.clone())
+        .weights(weights)
+        .vendored(scoring.vendored)
+        .file_type_rules(scoring.file_type_rules.clone())
+        .expansion(expansion)
+        .score(files);
 
-        // Build PageRank-sorted ranking (owned strings to avoid borrow conflict)
-        let mut pr_ranked: Vec<(String, f64)> = scored
-            .iter()
-            .filter_map(|f| f.signals.pagerank.map(|pr| (f.path.clone(), pr)))
-            .collect();
-        pr_ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-
-        let pr_ranking: Vec<&str> = pr_ranked.iter().map(|(p, _)| p.as_str()).collect();
-
-        // Fuse base ranking with PageRank ranking via RRF
-        if !pr_ranking.is_empty() {
-            let fusion = RrfFusion::new();
-            fusion.fuse_scored(&mut scored, &[pr_ranking]);
-        }
+    if let Some(index) = deep_index {
+        // Promote files whose content holds a quoted phrase
+        PhraseMatcher::new(task, &scoring.stop_words).boost(&mut result.files, &index.files);
     }
+    // Apply PageRank and the git signals via RRF fusion when available
+    let no_pagerank = HashMap::new();
+    let pagerank = deep_index.map_or(&no_pagerank, |index| &index.pagerank_scores);
+    topo_score::fuse_structural(
+        &mut result.files,
+        pagerank,
+        &scoring.git_recency,
+        &scoring.co_change,
+        &scoring.churn,
+        &weights,
+    );
 
-    scored
+    result
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn output_results(
     cli: &Cli,
+    root: &Path,
     task: &str,
-    preset: Preset,
+    preset: &str,
+    scope: Option<&str>,
     files: &[ScoredFile],
     scanned_count: usize,
-    max_bytes: u64,
+    budget: &TokenBudget,
     min_score: f64,
+    truncation: &Truncation,
 ) -> Result<()> {
     match cli.effective_format() {
         OutputFormat::Jsonl | OutputFormat::Auto => {
-            let output = JsonlWriter::new(task, preset.as_str())
-                .max_bytes(Some(max_bytes))
+            let output = JsonlWriter::new(task, preset)
+                .scope(scope)
+                .max_bytes(budget.max_bytes)
                 .min_score(min_score)
+                .truncated(Some(truncation.clone()))
                 .render(files, scanned_count)?;
             print!("{output}");
         }
@@ -122,22 +828,54 @@ pub fn output_results(
             let json_output = serde_json::json!({
                 "version": "0.3",
                 "query": task,
-                "preset": preset.as_str(),
+                "preset": preset,
+                "scope": scope,
                 "files": files.iter().map(|f| serde_json::json!({
                     "path": f.path,
                     "score": f.score,
                     "tokens": f.tokens,
                     "language": f.language.as_str(),
                     "role": f.role.as_str(),
+                    "cluster": f.cluster,
+                    "pair": f.pair,
+                    "chunks": chunks_json(f),
+                    "outline": outline_json(f),
                 })).collect::<Vec<_>>(),
                 "total_files": files.len(),
                 "scanned_files": scanned_count,
+                "truncated": truncation_json(truncation),
             });
             println!("{}", serde_json::to_string_pretty(&json_output)?);
         }
+        OutputFormat::Markdown => {
+            let policy = content_policy(root)?;
+            let output = MarkdownWriter::new(task, preset)
+                .scope(scope)
+                .policy(&policy)
+                .truncated(Some(truncation.clone()))
+                .render(files, scanned_count, &DiskFiles::new(root))?;
+            print!("{output}");
+        }
+        OutputFormat::Xml => {
+            let policy = content_policy(root)?;
+            let output = XmlWriter::new(task, preset)
+                .budget(budget.clone())
+                .policy(&policy)
+                .render(files, &DiskFiles::new(root))?;
+            print!("{output}");
+        }
         OutputFormat::Compact => {
+            if let Some(scope) = scope {
+                println!("# scope: {scope}");
+            }
             let output = CompactWriter::new().render(files);
             print!("{output}");
+            if truncation.is_truncated() {
+                println!(
+                    "# truncated: {} over budget, {} over top",
+                    truncation.budget_dropped, truncation.top_dropped
+                );
+            }
         }
         OutputFormat::Human => {
             if !files.is_empty() {
@@ -146,7 +884,12 @@ pub fn output_results(
                     "PATH", "SCORE", "TOKENS", "LANG"
                 );
                 println!("{}", "-".repeat(88));
+                let mut cluster = None;
                 for f in files {
+                    if f.cluster.is_some() && f.cluster != cluster {
+                        cluster = f.cluster.clone();
+                        println!("[{}]", cluster.as_deref().unwrap_or_default());
+                    }
                     println!(
                         "{:<60} {:>8.4} {:>8} {:>8}",
                         truncate_path(&f.path, 60),
@@ -154,21 +897,87 @@ pub fn output_results(
                         f.tokens,
                         f.language.as_str(),
                     );
+                    for c in &f.chunks {
+                        println!(
+                            "    {} {} (lines {}-{})",
+                            c.kind.as_str(),
+                            c.name,
+                            c.start_line,
+                            c.end_line
+                        );
+                    }
+                    if !f.outline.is_empty() {
+                        let symbols: Vec<String> = f
+                            .outline
+                            .iter()
+                            .map(|o| format!("{}:{}", o.name, o.line))
+                            .collect();
+                        println!("    outline: {}", symbols.join(", "));
+                    }
+                    if let Some(pair) = &f.pair {
+                        println!("    pair: {pair}");
+                    }
                 }
                 println!("{}", "-".repeat(88));
             }
             println!(
-                "{} files selected (of {} scanned) for query: \"{}\"",
+                "{} files selected (of {} scanned) for query: \"{}\"{}",
                 files.len(),
                 scanned_count,
-                task
+                task,
+                scope.map_or(String::new(), |s| format!(" in {s}"))
             );
+            if truncation.budget_dropped > 0 {
+                println!(
+                    "{} more files (~{} tokens) did not fit the budget; next: {}",
+                    truncation.budget_dropped,
+                    truncation.budget_dropped_tokens,
+                    truncation
+                        .dropped
+                        .iter()
+                        .take(3)
+                        .map(|d| d.path.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            if truncation.top_dropped > 0 {
+                println!("{} more files cut by --top", truncation.top_dropped);
+            }
+            if let Some(best) = truncation.best_below_threshold
+                && files.is_empty()
+            {
+                println!(
+                    "{} files scored below --min-score {min_score} (best {best:.4})",
+                    truncation.below_threshold
+                );
+            }
         }
     }
 
     Ok(())
 }
 
+/// JSON form of the truncation diagnostics, `null` when nothing was cut.
+pub fn truncation_json(truncation: &Truncation) -> serde_json::Value {
+    if !truncation.is_truncated() {
+        return serde_json::Value::Null;
+    }
+    serde_json::json!({
+        "budget_dropped": truncation.budget_dropped,
+        "budget_dropped_tokens": truncation.budget_dropped_tokens,
+        "top_dropped": truncation.top_dropped,
+        "below_min_score": truncation.below_threshold,
+        "best_below_min_score": truncation.best_below_threshold,
+        "files": truncation.dropped.iter().map(|d| serde_json::json!({
+            "path": d.path,
+            "score": d.score,
+            "tokens": d.tokens,
+            "reason": d.reason.as_str(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
 fn truncate_path(path: &str, max_len: usize) -> String {
     if path.len() <= max_len {
         path.to_string()
@@ -1,30 +1,130 @@
-use crate::Cli;
+use super::query::QueryOptions;
 use crate::preset::Preset;
+use crate::{Cli, OutputFormat};
 use anyhow::Result;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
-/// One-shot command: index + query in a single invocation.
-pub fn run(
-    cli: &Cli,
-    task: &str,
-    preset: Preset,
-    max_bytes: Option<u64>,
-    max_tokens: Option<u64>,
-    min_score: Option<f64>,
-    top: Option<usize>,
-) -> Result<()> {
-    // Step 1: Index (if needed)
-    if preset.needs_deep_index() {
-        if !cli.is_quiet() {
-            eprintln!("Building index (preset: {preset})...");
-        }
-        super::index::run(cli, true, preset.force_rebuild())?;
-    } else if !cli.is_quiet() {
-        eprintln!("Scanning (preset: {preset}, shallow mode)...");
-        // Shallow scan happens inside query
+/// One-shot command: scan, index and query in a single pass.
+///
+/// The bundle is scanned once and the freshly built index is handed straight
+/// to scoring instead of being re-read from disk.
+pub fn run(cli: &Cli, task: &str, options: QueryOptions) -> Result<ExitCode> {
+    let root = cli.repo_root()?;
+    let preset = options.preset.clone();
+    let start = Instant::now();
+    let filters = super::query::parse_task(task)?;
+    let task = filters.text.as_str();
+
+    // Only greet interactive users; machine formats stay untouched
+    let first_run = !root.join(".topo").exists()
+        && !cli.is_quiet()
+        && std::io::stdout().is_terminal()
+        && matches!(cli.effective_format(), OutputFormat::Human);
+
+    // A shallow preset still gets an index when its signals or --outline
+    // read one, rather than relying on an index from an earlier run
+    let needs_index = preset.needs_deep_index()
+        || preset.use_structural_signals()
+        || preset.chunk_embeddings()
+        || options.outline.is_some();
+
+    // Step 1: Scan
+    let mode = if needs_index {
+        "deep index"
+    } else {
+        "shallow mode"
+    };
+    log::info!("Scanning (preset: {preset}, {mode})...");
+    let (bundle, scan) = cli.bundle_builder(&root).build_with_stats()?;
+
+    // Step 2: Index (if needed), reusing the scan
+    let deep_index = if needs_index {
+        Some(super::index::build_deep(
+            &root,
+            &bundle,
+            Some(&scan),
+            preset.force_rebuild(),
+            cli.is_verbose(),
+            cli.index_backend(),
+            cli.include_artifacts(),
+        )?)
+    } else {
+        None
+    };
+
+    // The index covers the whole repository; selection stays in the package
+    let scanned = bundle.files.clone();
+    let mut bundle = bundle;
+    super::query::apply_package_scope(cli, &root, &mut bundle);
+    super::query::apply_license_filter(cli, &mut bundle);
+    super::query::apply_query_filters(&filters, &mut bundle);
+
+    // Step 3: Score, budget and print
+    let status = super::query::select(
+        cli,
+        &root,
+        &bundle,
+        &scanned,
+        deep_index.as_ref(),
+        task,
+        options,
+        start,
+    )?;
+
+    if first_run {
+        eprintln!();
+        eprint!(
+            "{}",
+            first_run_guidance(&root, &preset, bundle.file_count(), start.elapsed())
+        );
     }
 
-    // Step 2: Query
-    super::query::run(cli, task, preset, max_bytes, max_tokens, min_score, top)?;
+    Ok(status)
+}
+
+/// One-time summary shown the first time topo runs in a repository.
+fn first_run_guidance(root: &Path, preset: &Preset, scanned: usize, elapsed: Duration) -> String {
+    let index_path = topo_index::index_path(root);
+    let index_line = if index_path.exists() {
+        format!("Deep index: {}", index_path.display())
+    } else {
+        format!("Deep index: not built ({preset} preset scans shallowly); run `topo index --deep`")
+    };
+
+    format!(
+        "First run in {root}: scanned {scanned} files in {secs:.1}s.\n\
+         {index_line}\n\
+         State lives in {state} (add it to .gitignore).\n\
+         \n\
+         Next steps:\n  \
+         topo query \"<task>\" --preset deep   # structural signals (PageRank, AST chunks)\n  \
+         topo explain \"<task>\"               # see why files were selected\n  \
+         topo more                           # page beyond the budget\n  \
+         topo init                           # install editor/agent hooks\n",
+        root = root.display(),
+        secs = elapsed.as_secs_f64(),
+        state = root.join(".topo").display(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(())
+    #[test]
+    fn guidance_mentions_missing_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = first_run_guidance(
+            dir.path(),
+            &Preset::builtin("fast").unwrap(),
+            42,
+            Duration::from_millis(1500),
+        );
+        assert!(text.contains("scanned 42 files in 1.5s"));
+        assert!(text.contains("topo index --deep"));
+        assert!(text.contains("Next steps:"));
+    }
 }
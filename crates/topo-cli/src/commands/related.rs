@@ -0,0 +1,106 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use topo_score::{CoChange, RelatednessScorer};
+
+/// Files listed when `--top` isn't given.
+const DEFAULT_TOP: usize = 20;
+
+pub fn run(cli: &Cli, path: &str, top: Option<usize>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = cli.repo_relative(&root, path);
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    if !bundle.files.iter().any(|f| f.path == path) {
+        bail!(CliError::untracked(&path, &root));
+    }
+
+    let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+    let index = topo_index::load(&root)?;
+    if index.is_none() {
+        log::info!("No deep index; shared symbols skipped (run `topo index --deep`)");
+    }
+    let co_change = CoChange::load(&root).unwrap_or_else(|e| {
+        log::warn!("co-change history unavailable: {e}");
+        CoChange::default()
+    });
+
+    let sources: HashMap<String, String> = match &index {
+        Some(_) => bundle
+            .files
+            .iter()
+            .filter_map(|f| {
+                let content = fs::read_to_string(root.join(&f.path)).ok()?;
+                Some((f.path.clone(), content))
+            })
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    let mut scorer = RelatednessScorer::new(&graph).co_change(&co_change);
+    if let Some(index) = &index {
+        scorer = scorer.symbols(index, &sources);
+    }
+    let mut related = scorer.score(&path, bundle.files.iter().map(|f| f.path.as_str()));
+    let total = related.len();
+    related.truncate(top.unwrap_or(DEFAULT_TOP));
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "total_related": total,
+                "related": related.iter().map(|r| serde_json::json!({
+                    "path": r.path,
+                    "score": r.score,
+                    "distance": r.distance,
+                    "shared_symbols": r.shared_symbols,
+                    "co_change": r.co_change,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for r in &related {
+                println!("{} ({:.2})", r.path, r.score);
+            }
+        }
+        _ => {
+            if related.is_empty() {
+                println!("Nothing related to {path}.");
+                return Ok(());
+            }
+            println!("Files related to {path}: {total}");
+            println!();
+            println!(
+                "{:<50} {:>6} {:>5} {:>7} {:>9}",
+                "PATH", "SCORE", "HOPS", "SYMBOLS", "CO-CHANGE"
+            );
+            println!("{}", "-".repeat(81));
+            for r in &related {
+                println!(
+                    "{:<50} {:>6.3} {:>5} {:>7} {:>9.3}",
+                    r.path,
+                    r.score,
+                    r.distance.map_or("-".to_string(), |d| d.to_string()),
+                    r.shared_symbols.len(),
+                    r.co_change
+                );
+                if !r.shared_symbols.is_empty() {
+                    println!("  shares: {}", r.shared_symbols.join(", "));
+                }
+            }
+            if related.len() < total {
+                println!("... {} more (raise --top)", total - related.len());
+            }
+        }
+    }
+
+    Ok(())
+}
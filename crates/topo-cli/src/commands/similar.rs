@@ -0,0 +1,95 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use topo_score::SimilarityScorer;
+
+/// Files listed when `--top` isn't given.
+const DEFAULT_TOP: usize = 10;
+
+/// Similarity below which files aren't listed, when `--min-similarity`
+/// isn't given.
+const DEFAULT_MIN_SIMILARITY: f64 = 0.3;
+
+pub fn run(cli: &Cli, path: &str, top: Option<usize>, min_similarity: Option<f64>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = cli.repo_relative(&root, path);
+
+    let Some(index) = topo_index::load(&root)? else {
+        bail!(CliError::no_index(&root));
+    };
+    if !index.files.contains_key(&path) {
+        if root.join(&path).is_file() {
+            bail!(
+                CliError::not_found(format!("{path} is not in the deep index"))
+                    .hint("Run `topo index --deep` to index it.")
+            );
+        }
+        bail!(CliError::untracked(&path, &root));
+    }
+
+    let mut similar = SimilarityScorer::new(&index)
+        .score(&path, min_similarity.unwrap_or(DEFAULT_MIN_SIMILARITY));
+    let total = similar.len();
+    similar.truncate(top.unwrap_or(DEFAULT_TOP));
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "path": path,
+                "total_similar": total,
+                "similar": similar.iter().map(|s| serde_json::json!({
+                    "path": s.path,
+                    "similarity": s.similarity,
+                    "overlap": s.overlap,
+                    "near_duplicate": s.is_near_duplicate(),
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for s in &similar {
+                let duplicate = if s.is_near_duplicate() {
+                    " duplicate"
+                } else {
+                    ""
+                };
+                println!(
+                    "{} ({:.2}, overlap {:.2}{duplicate})",
+                    s.path, s.similarity, s.overlap
+                );
+            }
+        }
+        _ => {
+            if similar.is_empty() {
+                println!("No files similar to {path}.");
+                return Ok(());
+            }
+            println!("Files similar to {path}: {total}");
+            println!();
+            println!("{:<50} {:>10} {:>7}", "PATH", "SIMILARITY", "OVERLAP");
+            println!("{}", "-".repeat(69));
+            for s in &similar {
+                println!(
+                    "{:<50} {:>10.3} {:>7.3}{}",
+                    s.path,
+                    s.similarity,
+                    s.overlap,
+                    if s.is_near_duplicate() {
+                        "  near-duplicate"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            if similar.len() < total {
+                println!("... {} more (raise --top)", total - similar.len());
+            }
+        }
+    }
+
+    Ok(())
+}
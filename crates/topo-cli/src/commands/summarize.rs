@@ -0,0 +1,121 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::fs;
+use std::path::Path;
+use topo_core::{DeepIndex, FileInfo, FileSummary};
+
+/// A file's summary and where it came from.
+struct Summarized<'a> {
+    file: &'a FileInfo,
+    summary: FileSummary,
+    /// Taken from the deep index rather than extracted now.
+    cached: bool,
+}
+
+/// The summary of `file`, from the deep index when its hash still matches,
+/// else extracted now. The flag tells which. `None` if it can't be read.
+pub fn file_summary(
+    root: &Path,
+    index: Option<&DeepIndex>,
+    file: &FileInfo,
+) -> Option<(FileSummary, bool)> {
+    let indexed = index
+        .and_then(|idx| idx.files.get(&file.path))
+        .filter(|entry| entry.sha256 == file.sha256);
+    if let Some(entry) = indexed {
+        return Some((entry.summary.clone(), true));
+    }
+    let content = fs::read_to_string(root.join(&file.path)).ok()?;
+    Some((topo_treesit::summarize(&content, file.language), false))
+}
+
+/// Summarize a file, or every file under a directory.
+pub fn run(cli: &Cli, path: &str) -> Result<()> {
+    let root = cli.repo_root()?;
+    let path = cli.repo_relative(&root, path);
+    let path = path.trim_end_matches('/');
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let selected: Vec<&FileInfo> = bundle
+        .files
+        .iter()
+        .filter(|f| {
+            path.is_empty()
+                || path == "."
+                || f.path == path
+                || f.path
+                    .strip_prefix(path)
+                    .is_some_and(|r| r.starts_with('/'))
+        })
+        .collect();
+    if selected.is_empty() {
+        bail!(
+            CliError::not_found(format!(
+                "{path} is not a tracked file or directory under {}",
+                root.display()
+            ))
+            .hint("Paths are relative to the current directory; ignored and binary files are not tracked.")
+        );
+    }
+
+    let index = topo_index::load(&root)?;
+    let summaries: Vec<Summarized> = selected
+        .into_iter()
+        .filter_map(|file| {
+            let (summary, cached) = file_summary(&root, index.as_ref(), file)?;
+            Some(Summarized {
+                file,
+                summary,
+                cached,
+            })
+        })
+        .collect();
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "files": summaries.iter().map(|s| serde_json::json!({
+                    "path": s.file.path,
+                    "language": s.file.language,
+                    "bytes": s.file.size,
+                    "summary_bytes": s.summary.render().len(),
+                    "cached": s.cached,
+                    "lines": s.summary.lines,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for s in summaries.iter().filter(|s| !s.summary.is_empty()) {
+                println!("# {}", s.file.path);
+                print!("{}", s.summary.render());
+            }
+        }
+        _ => {
+            for (i, s) in summaries.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                let text = s.summary.render();
+                println!(
+                    "{} ({} bytes, summary {} bytes)",
+                    s.file.path,
+                    s.file.size,
+                    text.len()
+                );
+                if s.summary.is_empty() {
+                    println!("  (no doc comments, exported signatures or constants)");
+                }
+                for line in text.lines() {
+                    println!("  {line}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
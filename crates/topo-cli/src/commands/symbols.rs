@@ -0,0 +1,71 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat, SymbolKind};
+use anyhow::{Result, bail};
+use topo_score::SymbolSearch;
+
+/// Symbols listed when `--top` isn't given.
+const DEFAULT_TOP: usize = 20;
+
+pub fn run(cli: &Cli, name: &str, kind: Option<SymbolKind>, top: Option<usize>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let Some(index) = topo_index::load(&root)? else {
+        bail!(CliError::no_index(&root));
+    };
+
+    let mut search = SymbolSearch::new(&index);
+    if let Some(kind) = kind {
+        search = search.kind(kind.into());
+    }
+    let mut symbols = search.search(name);
+    let total = symbols.len();
+    symbols.truncate(top.unwrap_or(DEFAULT_TOP));
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "query": name,
+                "total_symbols": total,
+                "symbols": symbols.iter().map(|s| serde_json::json!({
+                    "path": s.path,
+                    "line": s.line,
+                    "name": s.name,
+                    "kind": s.kind.as_str(),
+                    "score": s.score,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for s in &symbols {
+                println!("{}:{} {}", s.path, s.line, s.name);
+            }
+        }
+        _ => {
+            if symbols.is_empty() {
+                println!("No symbols match {name:?}.");
+                return Ok(());
+            }
+            println!("Symbols matching {name:?}: {total}");
+            println!();
+            println!("{:<50} {:<8} NAME", "LOCATION", "KIND");
+            println!("{}", "-".repeat(80));
+            for s in &symbols {
+                println!(
+                    "{:<50} {:<8} {}",
+                    format!("{}:{}", s.path, s.line),
+                    s.kind.as_str(),
+                    s.name
+                );
+            }
+            if symbols.len() < total {
+                println!("... {} more (raise --top)", total - symbols.len());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,365 @@
+use crate::Cli;
+use crate::error::CliError;
+use crate::preset::Preset;
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use topo_core::{
+    Chunk, ChunkKind, FileInfo, FileRole, ScoredFile, SignalBreakdown, TokenBudget, Truncation,
+};
+use topo_score::impl_stem;
+use topo_treesit::{Chunker, CompositeChunker};
+
+/// Scores assigned to each tier of the context pack.
+const SCORE_TEST: f64 = 1.0;
+const SCORE_DIRECT_IMPORT: f64 = 0.8;
+const SCORE_PAIRED_IMPL: f64 = 0.7;
+const SCORE_SECOND_HOP: f64 = 0.4;
+
+/// A test case reference, from the command line or a JUnit report.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TestRef {
+    pub name: String,
+    pub classname: Option<String>,
+    pub file: Option<String>,
+}
+
+/// Extract failing (or erroring) test cases from a JUnit XML report.
+pub fn parse_junit_failures(xml: &str) -> Vec<TestRef> {
+    let mut failures = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find("<testcase") {
+        rest = &rest[start..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..tag_end];
+        let self_closing = tag.ends_with('/');
+
+        let body = if self_closing {
+            ""
+        } else {
+            let close = rest.find("</testcase>").unwrap_or(rest.len());
+            &rest[tag_end..close]
+        };
+
+        if body.contains("<failure") || body.contains("<error") {
+            failures.push(TestRef {
+                name: xml_attr(tag, "name").unwrap_or_default(),
+                classname: xml_attr(tag, "classname"),
+                file: xml_attr(tag, "file"),
+            });
+        }
+        rest = &rest[tag_end..];
+    }
+
+    failures
+}
+
+fn xml_attr(tag: &str, name: &str) -> Option<String> {
+    let needle = format!(" {name}=");
+    let start = tag.find(&needle)? + needle.len();
+    let quote = tag[start..].chars().next()?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+    let value_start = start + 1;
+    let len = tag[value_start..].find(quote)?;
+    Some(
+        tag[value_start..value_start + len]
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+/// The bare function identifier of a test name.
+///
+/// `tests::auth::test_login` → `test_login`, `TestAuth.test_login` →
+/// `test_login`, `TestLogin/expired_token` → `TestLogin`,
+/// `test_parse[case-1]` → `test_parse`.
+pub fn test_identifier(name: &str) -> &str {
+    let name = name.trim();
+    let name = name.split('[').next().unwrap_or(name);
+    let name = name.split('/').next().unwrap_or(name);
+    let name = name.rsplit("::").next().unwrap_or(name);
+    let name = name.rsplit('.').next().unwrap_or(name);
+    name.trim_end_matches("()")
+}
+
+/// Implementation files named like the test file (`auth_test.go` → `auth.go`).
+pub fn paired_impls(test_path: &str, files: &[FileInfo]) -> Vec<String> {
+    if FileRole::from_path(Path::new(test_path)) != FileRole::Test {
+        return Vec::new();
+    }
+    let Some(stem) = impl_stem(test_path) else {
+        return Vec::new();
+    };
+    let test_stem = Path::new(test_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if stem == test_stem {
+        return Vec::new();
+    }
+
+    files
+        .iter()
+        .filter(|f| f.role == FileRole::Implementation && f.path != test_path)
+        .filter(|f| {
+            Path::new(&f.path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.to_lowercase() == stem)
+        })
+        .map(|f| f.path.clone())
+        .collect()
+}
+
+/// Find the file and function chunk defining a test.
+///
+/// Prefers the file named by the test reference, then test-role files, then
+/// any source file containing the identifier (e.g. Rust inline tests).
+fn locate_test(root: &Path, files: &[FileInfo], test: &TestRef) -> Option<(String, Chunk)> {
+    let ident = test_identifier(&test.name);
+    if ident.is_empty() {
+        return None;
+    }
+
+    let mut candidates: Vec<&FileInfo> = files
+        .iter()
+        .filter(|f| f.language.is_programming_language())
+        .collect();
+    let hint = test.file.as_deref().map(|f| f.trim_start_matches("./"));
+    candidates.sort_by_key(|f| {
+        let hinted = hint.is_some_and(|h| f.path == h || f.path.ends_with(h));
+        (!hinted, f.role != FileRole::Test)
+    });
+
+    candidates.into_iter().find_map(|f| {
+        let content = fs::read_to_string(root.join(&f.path)).ok()?;
+        if !content.contains(ident) {
+            return None;
+        }
+        CompositeChunker
+            .chunk(&content, f.language)
+            .into_iter()
+            .find(|c| c.kind == ChunkKind::Function && c.name == ident)
+            .map(|c| (f.path.clone(), c))
+    })
+}
+
+pub fn run(
+    cli: &Cli,
+    target: &str,
+    preset: Preset,
+    max_bytes: Option<u64>,
+    max_tokens: Option<u64>,
+) -> Result<()> {
+    let root = cli.repo_root()?;
+
+    // A path to a JUnit report, or a bare test name
+    let report = [Path::new(target).to_path_buf(), root.join(target)]
+        .into_iter()
+        .find(|p| p.is_file() && p.extension().is_some_and(|e| e == "xml"));
+    let tests: Vec<TestRef> = match report {
+        Some(path) => {
+            let failures = parse_junit_failures(&fs::read_to_string(&path)?);
+            if failures.is_empty() {
+                bail!(CliError::not_found(format!(
+                    "no failing test cases found in {}",
+                    path.display()
+                )));
+            }
+            failures
+        }
+        None => vec![TestRef {
+            name: target.to_string(),
+            ..Default::default()
+        }],
+    };
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+    let by_path: HashMap<&str, &FileInfo> =
+        bundle.files.iter().map(|f| (f.path.as_str(), f)).collect();
+
+    // Assemble the pack: path → best tier score
+    let mut pack: Vec<(String, f64)> = Vec::new();
+    let mut add = |path: &str, score: f64| match pack.iter_mut().find(|(p, _)| p == path) {
+        Some(entry) => entry.1 = entry.1.max(score),
+        None => pack.push((path.to_string(), score)),
+    };
+
+    let mut located = 0;
+    for test in &tests {
+        let Some((path, chunk)) = locate_test(&root, &bundle.files, test) else {
+            log::warn!("could not locate test \"{}\"", test.name);
+            continue;
+        };
+        located += 1;
+        log::info!(
+            "Test \"{}\" → {path}:{}-{}",
+            test.name,
+            chunk.start_line,
+            chunk.end_line
+        );
+
+        add(&path, SCORE_TEST);
+        let mut impls: Vec<String> = graph.imports_of(&path).to_vec();
+        for p in &impls {
+            add(p, SCORE_DIRECT_IMPORT);
+        }
+        for p in paired_impls(&path, &bundle.files) {
+            add(&p, SCORE_PAIRED_IMPL);
+            impls.push(p);
+        }
+        for p in &impls {
+            for dep in graph.imports_of(p) {
+                add(dep, SCORE_SECOND_HOP);
+            }
+        }
+    }
+
+    if located == 0 {
+        bail!(CliError::not_found(format!(
+            "no matching tests found for \"{target}\""
+        )));
+    }
+
+    let mut files: Vec<ScoredFile> = pack
+        .into_iter()
+        .filter_map(|(path, score)| {
+            let info = by_path.get(path.as_str())?;
+            Some(ScoredFile {
+                path,
+                score,
+                signals: SignalBreakdown::default(),
+                tokens: info.estimated_tokens(),
+                language: info.language,
+                role: info.role,
+                root: None,
+                cluster: None,
+                pair: None,
+                chunks: Vec::new(),
+                outline: Vec::new(),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let effective_max_bytes = max_bytes.unwrap_or(preset.default_max_bytes());
+    let budget = TokenBudget {
+        max_bytes: Some(effective_max_bytes),
+        max_tokens,
+    };
+    let budgeted = budget.enforce(&files);
+    let truncation = Truncation::from_cuts(&files[budgeted.len()..], &[], &[]);
+
+    super::query::output_results(
+        cli,
+        &root,
+        &format!("test-context: {target}"),
+        preset.as_str(),
+        None,
+        &budgeted,
+        bundle.file_count(),
+        &budget,
+        0.0,
+        &truncation,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::Language;
+
+    fn file(path: &str) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size: 100,
+            language: Language::from_path(Path::new(path)),
+            role: FileRole::from_path(Path::new(path)),
+            sha256: [0u8; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        }
+    }
+
+    #[test]
+    fn test_identifier_variants() {
+        assert_eq!(test_identifier("tests::auth::test_login"), "test_login");
+        assert_eq!(test_identifier("TestAuth.test_login"), "test_login");
+        assert_eq!(test_identifier("TestLogin/expired_token"), "TestLogin");
+        assert_eq!(test_identifier("test_parse[case-1]"), "test_parse");
+        assert_eq!(test_identifier("plain"), "plain");
+    }
+
+    #[test]
+    fn junit_failures_only() {
+        let xml = r#"<?xml version="1.0"?>
+<testsuite name="suite">
+  <testcase name="test_ok" classname="tests.test_auth"/>
+  <testcase name="test_login" classname="tests.test_auth" file="tests/test_auth.py">
+    <failure message="assert False">trace</failure>
+  </testcase>
+  <testcase name="test_boom" classname="tests.test_db"><error message="x"/></testcase>
+  <testcase name="test_fine" classname="tests.test_db"></testcase>
+</testsuite>"#;
+        let failures = parse_junit_failures(xml);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].name, "test_login");
+        assert_eq!(failures[0].file.as_deref(), Some("tests/test_auth.py"));
+        assert_eq!(failures[1].name, "test_boom");
+        assert_eq!(failures[1].classname.as_deref(), Some("tests.test_db"));
+    }
+
+    #[test]
+    fn paired_impls_strip_test_affixes() {
+        let files = vec![
+            file("pkg/auth/auth.go"),
+            file("pkg/auth/auth_test.go"),
+            file("src/db.py"),
+            file("tests/test_db.py"),
+        ];
+        assert_eq!(
+            paired_impls("pkg/auth/auth_test.go", &files),
+            vec!["pkg/auth/auth.go"]
+        );
+        assert_eq!(paired_impls("tests/test_db.py", &files), vec!["src/db.py"]);
+        assert!(paired_impls("pkg/auth/auth.go", &files).is_empty());
+    }
+
+    #[test]
+    fn locate_test_finds_function_chunk() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("tests")).unwrap();
+        fs::write(
+            dir.path().join("tests/test_auth.py"),
+            "def helper():\n    pass\n\ndef test_login():\n    assert helper() is None\n",
+        )
+        .unwrap();
+        let files = vec![file("tests/test_auth.py")];
+
+        let test = TestRef {
+            name: "tests.test_auth.test_login".to_string(),
+            ..Default::default()
+        };
+        let (path, chunk) = locate_test(dir.path(), &files, &test).unwrap();
+        assert_eq!(path, "tests/test_auth.py");
+        assert_eq!(chunk.start_line, 4);
+    }
+}
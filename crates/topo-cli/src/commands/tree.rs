@@ -0,0 +1,376 @@
+use crate::error::CliError;
+use crate::{Cli, OutputFormat};
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, HashMap};
+use topo_core::FileInfo;
+
+/// Directory levels shown below the root when `--depth` isn't given.
+const DEFAULT_DEPTH: usize = 2;
+
+/// A directory with the totals of every file beneath it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirNode {
+    /// Repo-relative path, `.` for the repository root.
+    pub path: String,
+    pub files: usize,
+    pub tokens: u64,
+    /// Share of the repository's PageRank, in [0.0, 1.0].
+    pub pagerank: f64,
+    /// Share of the repository's recent git activity, in [0.0, 1.0].
+    pub heat: f64,
+    /// Subdirectories, heaviest by tokens first.
+    pub children: Vec<DirNode>,
+}
+
+/// Per-file weights summed into each directory.
+#[derive(Debug, Default)]
+pub struct Weights<'a> {
+    pub pagerank: Option<&'a HashMap<String, f64>>,
+    pub recency: Option<&'a HashMap<String, f64>>,
+}
+
+/// Totals being summed for one directory.
+#[derive(Default)]
+struct Totals {
+    files: usize,
+    tokens: u64,
+    pagerank: f64,
+    heat: f64,
+    children: BTreeMap<String, Totals>,
+}
+
+impl Totals {
+    fn add(&mut self, tokens: u64, pagerank: f64, heat: f64) {
+        self.files += 1;
+        self.tokens += tokens;
+        self.pagerank += pagerank;
+        self.heat += heat;
+    }
+
+    /// The node for `path`, with PageRank and heat as shares of the
+    /// repository totals.
+    fn into_node(self, path: String, pagerank_total: f64, heat_total: f64) -> DirNode {
+        let share = |value: f64, total: f64| if total > 0.0 { value / total } else { 0.0 };
+        let mut children: Vec<DirNode> = self
+            .children
+            .into_iter()
+            .map(|(name, totals)| {
+                let child = if path == "." {
+                    name
+                } else {
+                    format!("{path}/{name}")
+                };
+                totals.into_node(child, pagerank_total, heat_total)
+            })
+            .collect();
+        children.sort_by(|a, b| b.tokens.cmp(&a.tokens).then_with(|| a.path.cmp(&b.path)));
+        DirNode {
+            path,
+            files: self.files,
+            tokens: self.tokens,
+            pagerank: share(self.pagerank, pagerank_total),
+            heat: share(self.heat, heat_total),
+            children,
+        }
+    }
+}
+
+/// The directory tree under `base` (`.` for the whole repository) with
+/// the totals of the files in each subtree. Shares are of the whole
+/// repository, so a subtree's root shows its weight in the repo.
+pub fn build(files: &[FileInfo], base: &str, weights: &Weights) -> DirNode {
+    let weight = |map: Option<&HashMap<String, f64>>, path: &str| {
+        map.and_then(|m| m.get(path)).copied().unwrap_or(0.0)
+    };
+    let pagerank_total: f64 = files
+        .iter()
+        .map(|f| weight(weights.pagerank, &f.path))
+        .sum();
+    let heat_total: f64 = files.iter().map(|f| weight(weights.recency, &f.path)).sum();
+
+    let mut root = Totals::default();
+    for f in files {
+        let relative = if base == "." {
+            f.path.as_str()
+        } else {
+            match f.path.strip_prefix(base).and_then(|r| r.strip_prefix('/')) {
+                Some(relative) => relative,
+                None => continue,
+            }
+        };
+        let (tokens, pagerank, heat) = (
+            f.estimated_tokens(),
+            weight(weights.pagerank, &f.path),
+            weight(weights.recency, &f.path),
+        );
+        root.add(tokens, pagerank, heat);
+        let mut dir = &mut root;
+        if let Some((dirs, _)) = relative.rsplit_once('/') {
+            for name in dirs.split('/') {
+                dir = dir.children.entry(name.to_string()).or_default();
+                dir.add(tokens, pagerank, heat);
+            }
+        }
+    }
+    root.into_node(base.to_string(), pagerank_total, heat_total)
+}
+
+pub fn run(cli: &Cli, path: Option<&str>, weights: bool, depth: Option<usize>) -> Result<()> {
+    let root = cli.repo_root()?;
+    let base = match path {
+        Some(path) => {
+            let relative = cli.repo_relative(&root, path);
+            let relative = relative.trim_end_matches('/');
+            if !root.join(relative).is_dir() {
+                bail!(
+                    CliError::not_found(format!("{path} is not a directory"))
+                        .hint("Pass a directory inside the repository.")
+                );
+            }
+            if relative.is_empty() {
+                ".".to_string()
+            } else {
+                relative.to_string()
+            }
+        }
+        None => ".".to_string(),
+    };
+    let depth = depth.unwrap_or(DEFAULT_DEPTH);
+
+    let bundle = cli.bundle_builder(&root).build()?;
+    let (pagerank, recency) = if weights {
+        let graph = topo_index::load_or_build_graph(&root, &bundle.files);
+        let pagerank = graph.pagerank_with(&super::query::pagerank_params(&root)?);
+        let recency = topo_score::git_recency_scores(&root).unwrap_or_else(|e| {
+            log::warn!("git recency unavailable: {e}");
+            HashMap::new()
+        });
+        (pagerank, recency)
+    } else {
+        Default::default()
+    };
+    let tree = build(
+        &bundle.files,
+        &base,
+        &Weights {
+            pagerank: weights.then_some(&pagerank),
+            recency: weights.then_some(&recency),
+        },
+    );
+    if tree.files == 0 {
+        bail!(
+            CliError::not_found(format!("no tracked files under {base}"))
+                .hint("Ignored and binary files are not tracked.")
+        );
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = to_json(&tree, weights, depth);
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            walk(&tree, depth, &mut |node| {
+                if weights {
+                    println!(
+                        "{}/ {} files, {} tokens, pagerank {:.1}%, heat {:.1}%",
+                        node.path,
+                        node.files,
+                        node.tokens,
+                        node.pagerank * 100.0,
+                        node.heat * 100.0
+                    );
+                } else {
+                    println!(
+                        "{}/ {} files, {} tokens",
+                        node.path, node.files, node.tokens
+                    );
+                }
+            });
+        }
+        _ => {
+            let mut rows: Vec<(String, &DirNode)> = Vec::new();
+            tree_rows(&tree, "", "", depth, &mut rows);
+            let width = rows
+                .iter()
+                .map(|(l, _)| l.chars().count())
+                .max()
+                .unwrap_or(0);
+            if weights {
+                println!(
+                    "{:<width$}  {:>6} {:>9} {:>8} {:>6}",
+                    "", "FILES", "TOKENS", "PAGERANK", "HEAT"
+                );
+            } else {
+                println!("{:<width$}  {:>6} {:>9}", "", "FILES", "TOKENS");
+            }
+            for (label, node) in rows {
+                let pad = width - label.chars().count();
+                print!("{label}{:pad$}  {:>6} {:>9}", "", node.files, node.tokens);
+                if weights {
+                    print!(
+                        " {:>7.1}% {:>5.1}%",
+                        node.pagerank * 100.0,
+                        node.heat * 100.0
+                    );
+                }
+                println!();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn to_json(node: &DirNode, weights: bool, depth: usize) -> serde_json::Value {
+    let mut value = serde_json::json!({
+        "path": node.path,
+        "files": node.files,
+        "tokens": node.tokens,
+    });
+    if weights {
+        value["pagerank"] = node.pagerank.into();
+        value["heat"] = node.heat.into();
+    }
+    if depth > 0 {
+        value["children"] = node
+            .children
+            .iter()
+            .map(|c| to_json(c, weights, depth - 1))
+            .collect();
+    }
+    value
+}
+
+/// Visit `node` and its subdirectories down to `depth` levels, depth first.
+fn walk(node: &DirNode, depth: usize, visit: &mut impl FnMut(&DirNode)) {
+    visit(node);
+    if depth > 0 {
+        for child in &node.children {
+            walk(child, depth - 1, visit);
+        }
+    }
+}
+
+/// Rows of the tree drawing: each directory's label after its branch.
+fn tree_rows<'a>(
+    node: &'a DirNode,
+    branch: &str,
+    indent: &str,
+    depth: usize,
+    rows: &mut Vec<(String, &'a DirNode)>,
+) {
+    let name = node.path.rsplit('/').next().unwrap_or(&node.path);
+    let label = if branch.is_empty() {
+        format!("{}/", node.path)
+    } else {
+        format!("{indent}{branch}{name}/")
+    };
+    rows.push((label, node));
+    if depth == 0 {
+        return;
+    }
+    let child_indent = match branch {
+        "" => String::new(),
+        "├── " => format!("{indent}│   "),
+        _ => format!("{indent}    "),
+    };
+    for (i, child) in node.children.iter().enumerate() {
+        let branch = if i + 1 == node.children.len() {
+            "└── "
+        } else {
+            "├── "
+        };
+        tree_rows(child, branch, &child_indent, depth - 1, rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use topo_core::{FileRole, Language};
+
+    fn file(path: &str, size: u64) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size,
+            language: Language::from_path(Path::new(path)),
+            role: FileRole::from_path(Path::new(path)),
+            sha256: [0; 32],
+            lines: 10,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        }
+    }
+
+    #[test]
+    fn subtrees_sum_their_files() {
+        let files = vec![
+            file("README.md", 400),
+            file("src/main.rs", 800),
+            file("src/auth/session.rs", 4000),
+            file("src/auth/token.rs", 2000),
+            file("docs/guide.md", 1200),
+        ];
+        let pagerank = HashMap::from([
+            ("src/auth/session.rs".to_string(), 0.6),
+            ("src/main.rs".to_string(), 0.2),
+        ]);
+        let recency = HashMap::from([("docs/guide.md".to_string(), 1.0)]);
+        let weights = Weights {
+            pagerank: Some(&pagerank),
+            recency: Some(&recency),
+        };
+
+        let tree = build(&files, ".", &weights);
+        assert_eq!(
+            (tree.path.as_str(), tree.files, tree.tokens),
+            (".", 5, 2100)
+        );
+        let children: Vec<(&str, usize, u64)> = tree
+            .children
+            .iter()
+            .map(|c| (c.path.as_str(), c.files, c.tokens))
+            .collect();
+        assert_eq!(children, [("src", 3, 1700), ("docs", 1, 300)]);
+        let src = &tree.children[0];
+        assert_eq!(src.pagerank, 1.0);
+        assert_eq!(src.heat, 0.0);
+        assert_eq!(src.children[0].path, "src/auth");
+        assert!((src.children[0].pagerank - 0.75).abs() < 1e-9);
+        assert_eq!(tree.children[1].heat, 1.0);
+
+        // Below a subdirectory, shares stay those of the whole repository
+        let auth = build(&files, "src/auth", &weights);
+        assert_eq!((auth.files, auth.tokens), (2, 1500));
+        assert!((auth.pagerank - 0.75).abs() < 1e-9);
+        assert!(auth.children.is_empty());
+    }
+
+    #[test]
+    fn rows_draw_branches_to_the_depth() {
+        let files = vec![
+            file("a/x/one.rs", 40),
+            file("a/y/two.rs", 40),
+            file("b/three.rs", 4),
+        ];
+        let tree = build(&files, ".", &Weights::default());
+        let mut rows = Vec::new();
+        tree_rows(&tree, "", "", 2, &mut rows);
+        let labels: Vec<&str> = rows.iter().map(|(l, _)| l.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["./", "├── a/", "│   ├── x/", "│   └── y/", "└── b/"]
+        );
+        rows.clear();
+        tree_rows(&tree, "", "", 1, &mut rows);
+        assert_eq!(rows.len(), 3);
+    }
+}
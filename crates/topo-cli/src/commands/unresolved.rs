@@ -0,0 +1,92 @@
+use crate::{Cli, OutputFormat};
+use anyhow::Result;
+use topo_score::ImportOrigin;
+
+pub fn run(cli: &Cli, top: usize) -> Result<()> {
+    let root = cli.repo_root()?;
+    let bundle = cli.bundle_builder(&root).build()?;
+    let unresolved = topo_index::unresolved_imports(&root, &bundle.files);
+    let total = unresolved.len();
+    let shown = &unresolved[..total.min(top)];
+    let count = |origin: &str| {
+        unresolved
+            .iter()
+            .filter(|u| u.origin.as_str() == origin)
+            .count()
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let output = serde_json::json!({
+                "total_specifiers": total,
+                "by_origin": {
+                    "dependency": count("dependency"),
+                    "internal": count("internal"),
+                    "undeclared": count("undeclared"),
+                },
+                "unresolved": shown.iter().map(|u| serde_json::json!({
+                    "specifier": u.specifier,
+                    "language": u.language.as_str(),
+                    "origin": u.origin.as_str(),
+                    "package": match &u.origin {
+                        ImportOrigin::Dependency(name) => Some(name),
+                        _ => None,
+                    },
+                    "count": u.files.len(),
+                    "files": u.files,
+                })).collect::<Vec<_>>(),
+            });
+            if matches!(cli.effective_format(), OutputFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for u in shown {
+                println!(
+                    "{} {} {} {}",
+                    u.files.len(),
+                    u.origin.as_str(),
+                    u.language.as_str(),
+                    u.specifier
+                );
+            }
+        }
+        _ => {
+            if unresolved.is_empty() {
+                println!("Every import resolves to a repository file.");
+                return Ok(());
+            }
+            println!(
+                "Unresolved imports: {total} specifiers ({} declared dependencies, {} likely internal, {} undeclared)",
+                count("dependency"),
+                count("internal"),
+                count("undeclared")
+            );
+            println!();
+            println!(
+                "{:<40} {:<12} {:<11} {:>5}  EXAMPLE",
+                "SPECIFIER", "LANGUAGE", "ORIGIN", "FILES"
+            );
+            println!("{}", "-".repeat(100));
+            for u in shown {
+                println!(
+                    "{:<40} {:<12} {:<11} {:>5}  {}",
+                    u.specifier,
+                    u.language.as_str(),
+                    u.origin.as_str(),
+                    u.files.len(),
+                    u.files[0]
+                );
+            }
+            if shown.len() < total {
+                println!("... {} more (raise --top)", total - shown.len());
+            }
+            println!(
+                "\nRun `topo deps <file> --explain` on an example to see why an import missed."
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use crate::Cli;
+use anyhow::Result;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use topo_core::Bundle;
+use topo_scanner::BundleBuilder;
+
+/// Longest a batch is held back while changes keep arriving, so a busy
+/// build directory can't postpone reindexing forever.
+const MAX_BATCH_WAIT: Duration = Duration::from_secs(2);
+
+/// Keep the deep index up to date until interrupted.
+///
+/// Changes are batched until the tree has been quiet for `debounce`; only
+/// the changed files are re-hashed and re-indexed.
+pub fn run(cli: &Cli, debounce: Duration) -> Result<()> {
+    let root = cli.repo_root()?;
+    let builder = cli.bundle_builder(&root);
+    let mut bundle = builder.build()?;
+    super::index::build_deep(
+        &root,
+        &bundle,
+        None,
+        false,
+        false,
+        cli.index_backend(),
+        cli.include_artifacts(),
+    )?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
+    // Events carry absolute paths even when the root was given relatively
+    let watched = root.canonicalize()?;
+    log::info!(
+        "Watching {} for changes (Ctrl-C to stop)...",
+        root.display()
+    );
+
+    while let Ok(first) = rx.recv() {
+        let mut batch = Batch::default();
+        batch.add(&watched, first);
+        let started = Instant::now();
+        while started.elapsed() < MAX_BATCH_WAIT {
+            match rx.recv_timeout(debounce) {
+                Ok(event) => batch.add(&watched, event),
+                Err(_) => break,
+            }
+        }
+
+        match refresh(cli, &root, &builder, &mut bundle, &batch) {
+            Ok(true) => log::info!(
+                "Reindexed {} changed paths in {:.0?}",
+                batch.paths.len(),
+                started.elapsed()
+            ),
+            Ok(false) => log::debug!("{} paths changed, no tracked files", batch.paths.len()),
+            Err(e) => log::warn!("reindex failed: {e:#}"),
+        }
+    }
+    Ok(())
+}
+
+/// Apply a batch of changes to the bundle and, if any file changed, update
+/// the deep index. Returns whether the index was touched.
+fn refresh(
+    cli: &Cli,
+    root: &Path,
+    builder: &BundleBuilder,
+    bundle: &mut Bundle,
+    batch: &Batch,
+) -> Result<bool> {
+    if batch.rescan {
+        *bundle = builder.build()?;
+    } else {
+        let changed: Vec<String> = batch.paths.iter().cloned().collect();
+        if !builder.update(bundle, &changed)? {
+            return Ok(false);
+        }
+    }
+    super::index::build_deep(
+        root,
+        bundle,
+        None,
+        false,
+        cli.is_verbose(),
+        cli.index_backend(),
+        cli.include_artifacts(),
+    )?;
+    Ok(true)
+}
+
+/// Repo-relative paths touched by a run of filesystem events.
+#[derive(Debug, Default)]
+struct Batch {
+    paths: BTreeSet<String>,
+    /// The watcher lost events (e.g. a queue overflow); only a full scan is safe.
+    rescan: bool,
+}
+
+impl Batch {
+    fn add(&mut self, root: &Path, event: notify::Result<Event>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(_) => {
+                self.rescan = true;
+                return;
+            }
+        };
+        if event.need_rescan() {
+            self.rescan = true;
+        }
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in &event.paths {
+            let Ok(rel) = path.strip_prefix(root) else {
+                continue;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            // The index itself lives under .topo; reacting to it would loop
+            if rel.is_empty() || rel == ".topo" || rel.starts_with(".topo/") {
+                continue;
+            }
+            self.paths.insert(rel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, Flag, ModifyKind};
+
+    fn event(kind: EventKind, paths: &[&str]) -> notify::Result<Event> {
+        let mut event = Event::new(kind);
+        for p in paths {
+            event = event.add_path(p.into());
+        }
+        Ok(event)
+    }
+
+    #[test]
+    fn batch_collects_relative_paths() {
+        let root = Path::new("/repo");
+        let mut batch = Batch::default();
+        batch.add(
+            root,
+            event(
+                EventKind::Modify(ModifyKind::Any),
+                &["/repo/src/main.rs", "/repo/src/lib.rs"],
+            ),
+        );
+        batch.add(
+            root,
+            event(EventKind::Create(CreateKind::File), &["/repo/src/main.rs"]),
+        );
+        batch.add(
+            root,
+            event(EventKind::Access(AccessKind::Any), &["/repo/README.md"]),
+        );
+        batch.add(
+            root,
+            event(
+                EventKind::Modify(ModifyKind::Any),
+                &["/repo/.topo/index.bin", "/elsewhere/x.rs"],
+            ),
+        );
+        assert_eq!(
+            batch.paths.iter().map(String::as_str).collect::<Vec<_>>(),
+            ["src/lib.rs", "src/main.rs"]
+        );
+        assert!(!batch.rescan);
+    }
+
+    #[test]
+    fn batch_rescans_after_lost_events() {
+        let mut batch = Batch::default();
+        batch.add(
+            Path::new("/repo"),
+            Ok(Event::new(EventKind::Other).set_flag(Flag::Rescan)),
+        );
+        assert!(batch.rescan);
+
+        let mut batch = Batch::default();
+        batch.add(Path::new("/repo"), Err(notify::Error::generic("overflow")));
+        assert!(batch.rescan);
+    }
+}
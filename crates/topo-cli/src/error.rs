@@ -0,0 +1,332 @@
+//! How failures reach the user. JSON formats get an error envelope on
+//! stdout with a stable code; other formats keep the message on stderr.
+//! Either way the exit code tells the categories apart.
+
+use crate::{Cli, OutputFormat};
+use std::fmt;
+use std::io::IsTerminal;
+use std::process::ExitCode;
+use topo_core::TopoError;
+
+/// Stable failure categories for machine consumers. The last four also
+/// cover runs that succeed but that a script should be able to branch on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Invalid arguments or flag combinations.
+    Usage,
+    /// A file, index, manifest or previous query that does not exist.
+    NotFound,
+    /// `.topo/config.toml` is invalid, or a setting a command needs is missing.
+    Config,
+    /// Reading or writing a file failed.
+    Io,
+    /// Nothing scored above the threshold (with `--fail-on-empty`).
+    NoResults,
+    /// The index is out of date (`changed --exit-code`).
+    StaleIndex,
+    /// Files ranked, but the budget or `--top` left none (with `--fail-on-empty`).
+    EmptyBudget,
+    /// `--root` or `TOPO_ROOT` is not a directory.
+    BadRoot,
+    /// Anything else.
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Usage => "usage",
+            Self::NotFound => "not_found",
+            Self::Config => "config",
+            Self::Io => "io",
+            Self::NoResults => "no_results",
+            Self::StaleIndex => "stale_index",
+            Self::EmptyBudget => "empty_budget",
+            Self::BadRoot => "bad_root",
+            Self::Internal => "internal",
+        }
+    }
+
+    /// Process exit status. 1 stays the generic failure and 2 matches the
+    /// usage errors clap reports itself.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Self::Internal => 1,
+            Self::Usage => 2,
+            Self::NotFound => 3,
+            Self::Config => 4,
+            Self::Io => 5,
+            Self::NoResults => 6,
+            Self::StaleIndex => 7,
+            Self::EmptyBudget => 8,
+            Self::BadRoot => 9,
+        }
+    }
+}
+
+/// An error with a known category and, optionally, what to do about it.
+#[derive(Debug)]
+pub struct CliError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl CliError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Usage, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn config(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Config, message)
+    }
+
+    pub fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// `path` is not among the scanned files.
+    pub fn untracked(path: &str, root: &std::path::Path) -> Self {
+        Self::not_found(format!("{path} is not a tracked file under {}", root.display()))
+            .hint("Paths are relative to the current directory; ignored and binary files are not tracked.")
+    }
+
+    /// An explicit repository root that does not exist.
+    pub fn bad_root(root: &std::path::Path) -> Self {
+        Self::new(
+            ErrorCode::BadRoot,
+            format!("repository root {} is not a directory", root.display()),
+        )
+        .hint("Pass an existing directory to --root or TOPO_ROOT.")
+    }
+
+    /// A command that reads the deep index ran before one was built.
+    pub fn no_index(root: &std::path::Path) -> Self {
+        Self::not_found(format!(
+            "no index found at {}",
+            topo_index::index_path(root).display()
+        ))
+        .hint("Run `topo index --deep` first.")
+    }
+
+    /// A command that pages or saves the last query ran before any query.
+    pub fn no_previous_query() -> Self {
+        Self::not_found("no previous query found").hint("Run `topo query <task>` first.")
+    }
+
+    /// Categorize any error from a command.
+    pub fn classify(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<CliError>() {
+                return Self {
+                    code: e.code,
+                    message: e.message.clone(),
+                    hint: e.hint.clone(),
+                };
+            }
+            if let Some(e) = cause.downcast_ref::<TopoError>() {
+                let code = match e {
+                    TopoError::Config(_) | TopoError::Parse(_) => ErrorCode::Config,
+                    TopoError::Io(_) => ErrorCode::Io,
+                    _ => ErrorCode::Internal,
+                };
+                return Self::new(code, format!("{err:#}"));
+            }
+            if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+                let code = if e.kind() == std::io::ErrorKind::NotFound {
+                    ErrorCode::NotFound
+                } else {
+                    ErrorCode::Io
+                };
+                return Self::new(code, format!("{err:#}"));
+            }
+        }
+        Self::new(ErrorCode::Internal, format!("{err:#}"))
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "code": self.code.as_str(),
+                "message": self.message,
+                "hint": self.hint,
+            }
+        })
+    }
+
+    /// Print the envelope on stdout, pretty for `json` and on one line for
+    /// `jsonl`, and return the matching exit status.
+    fn emit_json(&self, format: &OutputFormat) -> ExitCode {
+        let json = self.to_json();
+        let text = if matches!(format, OutputFormat::Json) {
+            serde_json::to_string_pretty(&json)
+        } else {
+            serde_json::to_string(&json)
+        };
+        println!("{}", text.unwrap_or_default());
+        ExitCode::from(self.code.exit_code())
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Report a failed command in the format its output would have used.
+pub fn report(cli: &Cli, err: &anyhow::Error) -> ExitCode {
+    let report = CliError::classify(err);
+    let format = cli.effective_format();
+    if matches!(format, OutputFormat::Json | OutputFormat::Jsonl) {
+        return report.emit_json(&format);
+    }
+    // People get anyhow's full report: cause chain, and backtrace if enabled
+    eprintln!("Error: {err:?}");
+    if let Some(hint) = &report.hint {
+        eprintln!("Hint: {hint}");
+    }
+    ExitCode::from(report.code.exit_code())
+}
+
+/// Report a command line clap rejected. Help and version requests print
+/// as usual; JSON formats get a `usage` envelope.
+pub fn report_parse(err: clap::Error, args: &[String]) -> ExitCode {
+    use clap::error::ErrorKind;
+
+    let informational = matches!(
+        err.kind(),
+        ErrorKind::DisplayHelp
+            | ErrorKind::DisplayVersion
+            | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+    );
+    let format = match requested_format(args) {
+        Some("json") => OutputFormat::Json,
+        Some("jsonl") => OutputFormat::Jsonl,
+        None if std::env::var_os("HOOK_EVENT_NAME").is_none()
+            && !std::io::stdout().is_terminal() =>
+        {
+            OutputFormat::Jsonl
+        }
+        _ => OutputFormat::Human,
+    };
+    if informational || !matches!(format, OutputFormat::Json | OutputFormat::Jsonl) {
+        err.exit();
+    }
+
+    let rendered = err.render().to_string();
+    let message = rendered
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches("error: ")
+        .to_string();
+    CliError::usage(message)
+        .hint("Run `topo --help` for usage.")
+        .emit_json(&format)
+}
+
+/// The `--format` value on a raw command line, if any.
+fn requested_format(args: &[String]) -> Option<&str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--format" {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix("--format=")
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_keeps_cli_errors() {
+        let err: anyhow::Error = CliError::no_previous_query().into();
+        let report = CliError::classify(&err.context("paging results"));
+        assert_eq!(report.code, ErrorCode::NotFound);
+        assert_eq!(report.message, "no previous query found");
+        assert_eq!(
+            report.hint.as_deref(),
+            Some("Run `topo query <task>` first.")
+        );
+    }
+
+    #[test]
+    fn classify_maps_library_errors() {
+        let config: anyhow::Error = TopoError::Config("bad key".into()).into();
+        assert_eq!(CliError::classify(&config).code, ErrorCode::Config);
+
+        let missing: anyhow::Error =
+            std::io::Error::new(std::io::ErrorKind::NotFound, "gone").into();
+        assert_eq!(CliError::classify(&missing).code, ErrorCode::NotFound);
+
+        let denied: anyhow::Error =
+            std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied").into();
+        assert_eq!(CliError::classify(&denied).code, ErrorCode::Io);
+
+        let other = anyhow::anyhow!("boom").context("while scoring");
+        let report = CliError::classify(&other);
+        assert_eq!(report.code, ErrorCode::Internal);
+        assert_eq!(report.message, "while scoring: boom");
+    }
+
+    #[test]
+    fn envelope_shape() {
+        let json = CliError::usage("bad flag").to_json();
+        assert_eq!(json["error"]["code"], "usage");
+        assert_eq!(json["error"]["message"], "bad flag");
+        assert!(json["error"]["hint"].is_null());
+        assert_eq!(ErrorCode::Usage.exit_code(), 2);
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let codes = [
+            ErrorCode::Usage,
+            ErrorCode::NotFound,
+            ErrorCode::Config,
+            ErrorCode::Io,
+            ErrorCode::NoResults,
+            ErrorCode::StaleIndex,
+            ErrorCode::EmptyBudget,
+            ErrorCode::BadRoot,
+            ErrorCode::Internal,
+        ];
+        let mut exits: Vec<u8> = codes.iter().map(|c| c.exit_code()).collect();
+        exits.sort_unstable();
+        exits.dedup();
+        assert_eq!(exits.len(), codes.len());
+        assert!(!exits.contains(&0));
+    }
+
+    #[test]
+    fn requested_format_reads_both_spellings() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            requested_format(&args(&["topo", "--format", "json", "query"])),
+            Some("json")
+        );
+        assert_eq!(
+            requested_format(&args(&["topo", "query", "--format=jsonl"])),
+            Some("jsonl")
+        );
+        assert_eq!(requested_format(&args(&["topo", "query"])), None);
+    }
+}
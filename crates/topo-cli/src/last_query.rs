@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use topo_core::ScoredFile;
+
+/// File name of the persisted last query inside `.topo/`.
+const LAST_QUERY_FILE: &str = "last_query.json";
+
+/// The most recent query: its parameters, the full ranked list (after the
+/// score filter, before budgeting), and the files delivered so far.
+///
+/// Written by `topo query` and advanced by `topo more`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastQuery {
+    pub task: String,
+    pub preset: String,
+    /// `--scope` of the query, if any.
+    #[serde(default)]
+    pub scope: Option<String>,
+    pub max_bytes: u64,
+    pub max_tokens: Option<u64>,
+    pub min_score: f64,
+    pub scanned_count: usize,
+    pub ranked: Vec<ScoredFile>,
+    pub delivered: Vec<String>,
+}
+
+impl LastQuery {
+    /// Ranked files that have not been delivered yet, in rank order.
+    pub fn remaining(&self) -> Vec<ScoredFile> {
+        self.ranked
+            .iter()
+            .filter(|f| !self.delivered.contains(&f.path))
+            .cloned()
+            .collect()
+    }
+
+    /// Record files as delivered.
+    pub fn mark_delivered(&mut self, files: &[ScoredFile]) {
+        for f in files {
+            if !self.delivered.contains(&f.path) {
+                self.delivered.push(f.path.clone());
+            }
+        }
+    }
+}
+
+/// Path to the persisted last query.
+pub fn path(root: &Path) -> PathBuf {
+    root.join(".topo").join(LAST_QUERY_FILE)
+}
+
+/// Persist the last query to `.topo/last_query.json`.
+pub fn save(root: &Path, last: &LastQuery) -> Result<()> {
+    let path = path(root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, serde_json::to_string(last)?)
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
+}
+
+/// Load the last query. Returns `None` if no query has been run yet.
+pub fn load(root: &Path) -> Result<Option<LastQuery>> {
+    let path = path(root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&path)?;
+    let last =
+        serde_json::from_str(&data).with_context(|| format!("parsing {}", path.display()))?;
+    Ok(Some(last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::{FileRole, Language, SignalBreakdown};
+
+    fn scored(path: &str) -> ScoredFile {
+        ScoredFile {
+            path: path.to_string(),
+            score: 1.0,
+            signals: SignalBreakdown::default(),
+            tokens: 100,
+            language: Language::Rust,
+            role: FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
+        }
+    }
+
+    fn sample() -> LastQuery {
+        LastQuery {
+            task: "auth".to_string(),
+            preset: "balanced".to_string(),
+            scope: None,
+            max_bytes: 1000,
+            max_tokens: None,
+            min_score: 0.01,
+            scanned_count: 3,
+            ranked: vec![scored("a.rs"), scored("b.rs"), scored("c.rs")],
+            delivered: vec!["a.rs".to_string()],
+        }
+    }
+
+    #[test]
+    fn remaining_excludes_delivered() {
+        let last = sample();
+        let remaining: Vec<_> = last.remaining().into_iter().map(|f| f.path).collect();
+        assert_eq!(remaining, vec!["b.rs", "c.rs"]);
+    }
+
+    #[test]
+    fn mark_delivered_is_idempotent() {
+        let mut last = sample();
+        last.mark_delivered(&[scored("a.rs"), scored("b.rs")]);
+        assert_eq!(last.delivered, vec!["a.rs", "b.rs"]);
+    }
+
+    #[test]
+    fn save_load_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(load(dir.path()).unwrap().is_none());
+
+        save(dir.path(), &sample()).unwrap();
+        let loaded = load(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.task, "auth");
+        assert_eq!(loaded.ranked.len(), 3);
+        assert_eq!(loaded.delivered, vec!["a.rs"]);
+    }
+}
@@ -0,0 +1,125 @@
+//! Leveled diagnostics on stderr. Progress notes print at the default
+//! level; `-v` adds debug detail (scan counts, resolver stats, fusion
+//! parameters, timings), `-vv` traces topo's own crates, and `-vvv` traces
+//! dependencies too. `--quiet` silences all of it; stdout only ever carries
+//! the payload.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::io::Write;
+
+/// Install the stderr logger for this run.
+pub fn init(verbose: u8, quiet: bool) {
+    let logger = StderrLogger::new(verbose, quiet);
+    log::set_max_level(logger.max_level());
+    // Only fails if a logger is already installed, which keeps that one
+    let _ = log::set_logger(Box::leak(Box::new(logger)));
+}
+
+struct StderrLogger {
+    verbose: u8,
+    quiet: bool,
+}
+
+impl StderrLogger {
+    fn new(verbose: u8, quiet: bool) -> Self {
+        Self { verbose, quiet }
+    }
+
+    /// The most detailed level any target is allowed.
+    fn max_level(&self) -> LevelFilter {
+        match (self.quiet, self.verbose) {
+            (true, _) => LevelFilter::Off,
+            (false, 0) => LevelFilter::Info,
+            (false, 1) => LevelFilter::Debug,
+            (false, _) => LevelFilter::Trace,
+        }
+    }
+
+    /// The level allowed for `target`. Dependencies only get past warnings
+    /// at `-vvv`, so their chatter doesn't bury topo's own trace output.
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let max = self.max_level();
+        if self.verbose >= 3 || target.starts_with("topo") {
+            max
+        } else {
+            max.min(LevelFilter::Warn)
+        }
+    }
+
+    fn format(&self, record: &Record) -> String {
+        match record.level() {
+            Level::Error => format!("Error: {}", record.args()),
+            Level::Warn => format!("Warning: {}", record.args()),
+            Level::Info => record.args().to_string(),
+            level if self.verbose >= 3 => format!(
+                "[{} {}] {}",
+                level.as_str().to_lowercase(),
+                record.target(),
+                record.args()
+            ),
+            level => format!("[{}] {}", level.as_str().to_lowercase(), record.args()),
+        }
+    }
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let _ = writeln!(std::io::stderr().lock(), "{}", self.format(record));
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity_maps_to_levels() {
+        assert_eq!(StderrLogger::new(0, false).max_level(), LevelFilter::Info);
+        assert_eq!(StderrLogger::new(1, false).max_level(), LevelFilter::Debug);
+        assert_eq!(StderrLogger::new(2, false).max_level(), LevelFilter::Trace);
+        assert_eq!(StderrLogger::new(3, true).max_level(), LevelFilter::Off);
+    }
+
+    #[test]
+    fn dependencies_stay_at_warnings_until_vvv() {
+        let logger = StderrLogger::new(2, false);
+        assert_eq!(logger.level_for("topo_score::resolve"), LevelFilter::Trace);
+        assert_eq!(logger.level_for("ignore::walk"), LevelFilter::Warn);
+        let logger = StderrLogger::new(3, false);
+        assert_eq!(logger.level_for("ignore::walk"), LevelFilter::Trace);
+        let logger = StderrLogger::new(0, true);
+        assert_eq!(logger.level_for("ignore::walk"), LevelFilter::Off);
+    }
+
+    #[test]
+    fn records_are_prefixed_by_level() {
+        let render = |logger: &StderrLogger, level| {
+            logger.format(
+                &Record::builder()
+                    .level(level)
+                    .target("topo_scanner::bundle")
+                    .args(format_args!("Scanned 3 files"))
+                    .build(),
+            )
+        };
+        let logger = StderrLogger::new(1, false);
+        assert_eq!(render(&logger, Level::Info), "Scanned 3 files");
+        assert_eq!(render(&logger, Level::Warn), "Warning: Scanned 3 files");
+        assert_eq!(render(&logger, Level::Debug), "[debug] Scanned 3 files");
+        let logger = StderrLogger::new(3, false);
+        assert_eq!(
+            render(&logger, Level::Trace),
+            "[trace topo_scanner::bundle] Scanned 3 files"
+        );
+    }
+}
@@ -1,20 +1,28 @@
 mod commands;
+mod error;
+mod last_query;
+mod logging;
+mod mcp_clients;
 mod preset;
+mod rerank;
+mod semantic;
+mod stats;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand, ValueEnum};
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 /// Topo — fast codebase indexer and file selector for LLMs.
 #[derive(Parser, Debug)]
 #[command(name = "topo", version, about)]
 pub struct Cli {
-    /// Increase log verbosity
+    /// Increase log verbosity (-v debug detail, -vv trace, -vvv trace dependencies)
     #[arg(short, long, action = clap::ArgAction::Count, global = true)]
     verbose: u8,
 
-    /// Suppress non-essential output
+    /// Suppress all diagnostics; only the payload is printed
     #[arg(short, long, global = true)]
     quiet: bool,
 
@@ -30,6 +38,30 @@ pub struct Cli {
     #[arg(long, global = true)]
     root: Option<PathBuf>,
 
+    /// Search the whole repository instead of the package containing the current directory
+    #[arg(long, global = true)]
+    whole_repo: bool,
+
+    /// Never select files under this license (SPDX id, e.g. GPL-3.0; repeatable)
+    #[arg(long, value_name = "SPDX", global = true)]
+    exclude_license: Vec<String>,
+
+    /// Score vendored and generated files like any other (overrides `[vendored] policy`)
+    #[arg(long, global = true)]
+    include_vendored: bool,
+
+    /// Scan directories with this name even though they are skipped by default (e.g. venv; repeatable)
+    #[arg(long, value_name = "NAME", global = true)]
+    keep_dir: Vec<String>,
+
+    /// Store the deep index as `bin` (default) or `sqlite`; an existing index keeps its store
+    #[arg(long, value_enum, global = true)]
+    index_backend: Option<IndexBackend>,
+
+    /// Deep-index minified bundles, source maps and large lockfiles too
+    #[arg(long, global = true)]
+    include_artifacts: bool,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -41,6 +73,74 @@ pub enum OutputFormat {
     Jsonl,
     Human,
     Compact,
+    Markdown,
+    Xml,
+}
+
+/// How `query` and `quick` group the selected files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// Under the import-graph cluster (module) each file belongs to
+    Cluster,
+}
+
+/// How the deep index is stored under `.topo`.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum IndexBackend {
+    /// One rkyv file, loaded whole
+    Bin,
+    /// A SQLite database, loaded one query's terms at a time
+    Sqlite,
+}
+
+/// Shell the generated Claude Code hooks are written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HookShell {
+    /// Bash scripts; need bash and jq
+    Bash,
+    /// PowerShell scripts, for Windows setups without bash
+    Powershell,
+}
+
+/// MCP clients `topo init --mcp` can register the server with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum McpClient {
+    /// Claude Desktop's claude_desktop_config.json (per user)
+    ClaudeDesktop,
+    /// .cursor/mcp.json in the repository
+    Cursor,
+    /// .vscode/mcp.json in the repository
+    Vscode,
+}
+
+impl From<IndexBackend> for topo_index::IndexBackend {
+    fn from(backend: IndexBackend) -> Self {
+        match backend {
+            IndexBackend::Bin => Self::Bin,
+            IndexBackend::Sqlite => Self::Sqlite,
+        }
+    }
+}
+
+/// Kinds of definition `topo symbols` can be limited to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SymbolKind {
+    /// Functions and methods
+    Function,
+    /// Structs, classes, enums, interfaces and traits
+    Type,
+    /// Impl blocks
+    Impl,
+}
+
+impl From<SymbolKind> for topo_core::ChunkKind {
+    fn from(kind: SymbolKind) -> Self {
+        match kind {
+            SymbolKind::Function => Self::Function,
+            SymbolKind::Type => Self::Type,
+            SymbolKind::Impl => Self::Impl,
+        }
+    }
 }
 
 #[derive(Debug, Subcommand)]
@@ -54,6 +154,17 @@ pub enum Command {
         /// Rebuild index from scratch (ignore cache)
         #[arg(long)]
         force: bool,
+
+        /// Print where the build spent its time (also enabled by -v)
+        #[arg(long)]
+        stats: bool,
+    },
+
+    /// Watch the repository and keep the deep index up to date
+    Watch {
+        /// Milliseconds without changes before a batch is reindexed
+        #[arg(long, default_value = "300")]
+        debounce: u64,
     },
 
     /// Score and select files for a query
@@ -61,9 +172,9 @@ pub enum Command {
         /// The task or query to search for
         task: String,
 
-        /// Preset: fast, balanced, deep, thorough
-        #[arg(long, value_enum, default_value = "balanced")]
-        preset: preset::Preset,
+        /// Preset: fast, balanced, deep, thorough, or a `[presets]` name (default: balanced, or `[defaults] preset`)
+        #[arg(long)]
+        preset: Option<String>,
 
         /// Maximum bytes for token budget
         #[arg(long)]
@@ -80,6 +191,54 @@ pub enum Command {
         /// Return top N files
         #[arg(long)]
         top: Option<usize>,
+
+        /// Weight of the embedding ranking when fused with the lexical one (0 = lexical only)
+        #[arg(long, value_name = "W")]
+        semantic_weight: Option<f64>,
+
+        /// Rank by embedding similarity alone
+        #[arg(long, conflicts_with = "semantic_weight")]
+        semantic_only: bool,
+
+        /// Embedding provider, overriding `[embeddings] provider`: openai, voyage, cohere or local
+        #[arg(long, value_name = "PROVIDER", value_parser = clap::builder::PossibleValuesParser::new(topo_core::EMBEDDING_PROVIDERS))]
+        embedder: Option<String>,
+
+        /// Re-score the top candidates with the `[rerank]` cross-encoder (on with the thorough preset)
+        #[arg(long)]
+        rerank: bool,
+
+        /// Exit with status 6 when nothing scores, or 8 when the budget or --top leaves nothing
+        #[arg(long)]
+        fail_on_empty: bool,
+
+        /// List up to N functions and types of each selected file with their lines (needs the deep index)
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+        outline: Option<usize>,
+
+        /// Don't expand the task with terms that co-occur with it in the deep index
+        #[arg(long)]
+        no_expand: bool,
+
+        /// Override `[weights]`, e.g. bm25f=2,heuristic=0.5 (keys: bm25f, heuristic, pagerank, git_recency, co_change, churn, rrf_k)
+        #[arg(long, value_name = "KEY=W,...", value_parser = topo_core::WeightsConfig::parse)]
+        weights: Option<topo_core::WeightsConfig>,
+
+        /// Boost files that historically change together with the files changed since this revision (structural presets)
+        #[arg(long, value_name = "REV")]
+        since: Option<String>,
+
+        /// Select only files under this path, still ranked with statistics from the whole repository
+        #[arg(long, value_name = "PATH")]
+        scope: Option<String>,
+
+        /// Select only files this person or team owns, by CODEOWNERS or else by git blame
+        #[arg(long, value_name = "WHO")]
+        owner: Option<String>,
+
+        /// Present the selection grouped by module cluster of the import graph
+        #[arg(long, value_enum, value_name = "GROUP")]
+        group_by: Option<GroupBy>,
     },
 
     /// One-shot: index + query in a single command
@@ -87,9 +246,9 @@ pub enum Command {
         /// The task or query to search for
         task: String,
 
-        /// Preset: fast, balanced, deep, thorough
-        #[arg(long, value_enum, default_value = "balanced")]
-        preset: preset::Preset,
+        /// Preset: fast, balanced, deep, thorough, or a `[presets]` name (default: balanced, or `[defaults] preset`)
+        #[arg(long)]
+        preset: Option<String>,
 
         /// Maximum bytes for token budget
         #[arg(long)]
@@ -106,6 +265,103 @@ pub enum Command {
         /// Return top N files
         #[arg(long)]
         top: Option<usize>,
+
+        /// Weight of the embedding ranking when fused with the lexical one (0 = lexical only)
+        #[arg(long, value_name = "W")]
+        semantic_weight: Option<f64>,
+
+        /// Rank by embedding similarity alone
+        #[arg(long, conflicts_with = "semantic_weight")]
+        semantic_only: bool,
+
+        /// Embedding provider, overriding `[embeddings] provider`: openai, voyage, cohere or local
+        #[arg(long, value_name = "PROVIDER", value_parser = clap::builder::PossibleValuesParser::new(topo_core::EMBEDDING_PROVIDERS))]
+        embedder: Option<String>,
+
+        /// Re-score the top candidates with the `[rerank]` cross-encoder (on with the thorough preset)
+        #[arg(long)]
+        rerank: bool,
+
+        /// Exit with status 6 when nothing scores, or 8 when the budget or --top leaves nothing
+        #[arg(long)]
+        fail_on_empty: bool,
+
+        /// List up to N functions and types of each selected file with their lines (needs the deep index)
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+        outline: Option<usize>,
+
+        /// Don't expand the task with terms that co-occur with it in the deep index
+        #[arg(long)]
+        no_expand: bool,
+
+        /// Override `[weights]`, e.g. bm25f=2,heuristic=0.5 (keys: bm25f, heuristic, pagerank, git_recency, co_change, churn, rrf_k)
+        #[arg(long, value_name = "KEY=W,...", value_parser = topo_core::WeightsConfig::parse)]
+        weights: Option<topo_core::WeightsConfig>,
+
+        /// Boost files that historically change together with the files changed since this revision (structural presets)
+        #[arg(long, value_name = "REV")]
+        since: Option<String>,
+
+        /// Select only files under this path, still ranked with statistics from the whole repository
+        #[arg(long, value_name = "PATH")]
+        scope: Option<String>,
+
+        /// Select only files this person or team owns, by CODEOWNERS or else by git blame
+        #[arg(long, value_name = "WHO")]
+        owner: Option<String>,
+
+        /// Present the selection grouped by module cluster of the import graph
+        #[arg(long, value_enum, value_name = "GROUP")]
+        group_by: Option<GroupBy>,
+    },
+
+    /// Return the next slice of the last query's ranking
+    More {
+        /// Maximum bytes for token budget (default: same as the last query)
+        #[arg(long)]
+        max_bytes: Option<u64>,
+
+        /// Maximum tokens for token budget (default: same as the last query)
+        #[arg(long)]
+        max_tokens: Option<u64>,
+
+        /// Return at most N files
+        #[arg(long)]
+        top: Option<usize>,
+    },
+
+    /// Write the contents of a query's files, within the token budget
+    Pack {
+        /// The task or query to search for
+        task: String,
+
+        /// Preset: fast, balanced, deep, thorough, or a `[presets]` name (default: balanced, or `[defaults] preset`)
+        #[arg(long)]
+        preset: Option<String>,
+
+        /// Maximum bytes for token budget
+        #[arg(long)]
+        max_bytes: Option<u64>,
+
+        /// Maximum tokens for token budget
+        #[arg(long)]
+        max_tokens: Option<u64>,
+
+        /// Minimum score threshold
+        #[arg(long)]
+        min_score: Option<f64>,
+
+        /// Pack at most N files
+        #[arg(long)]
+        top: Option<usize>,
+
+        /// Select only files under this path, still ranked with statistics from the whole repository
+        #[arg(long, value_name = "PATH")]
+        scope: Option<String>,
+
+        /// Write the pack to FILE and print a summary instead
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
     },
 
     /// Convert JSONL selection to formatted output
@@ -118,6 +374,23 @@ pub enum Command {
         max_tokens: Option<u64>,
     },
 
+    /// Compare two selections: added/removed files, rank shifts, score deltas
+    DiffResults {
+        /// Baseline selection (JSONL or JSON output of `topo query`)
+        a: PathBuf,
+
+        /// Selection to compare against the baseline
+        #[arg(
+            required_unless_present = "against_last",
+            conflicts_with = "against_last"
+        )]
+        b: Option<PathBuf>,
+
+        /// Compare against the files delivered by the last query
+        #[arg(long)]
+        against_last: bool,
+    },
+
     /// Show per-file score breakdown
     Explain {
         /// The task or query to explain scoring for
@@ -127,17 +400,308 @@ pub enum Command {
         #[arg(long, default_value = "10")]
         top: usize,
 
-        /// Scoring preset
-        #[arg(long, value_enum, default_value = "balanced")]
-        preset: preset::Preset,
+        /// Scoring preset (default: balanced, or `[defaults] preset`)
+        #[arg(long)]
+        preset: Option<String>,
+
+        /// Maximum bytes for token budget (as passed to `query`)
+        #[arg(long)]
+        max_bytes: Option<u64>,
+
+        /// Maximum tokens for token budget (as passed to `query`)
+        #[arg(long)]
+        max_tokens: Option<u64>,
+
+        /// Minimum score threshold (as passed to `query`)
+        #[arg(long)]
+        min_score: Option<f64>,
+
+        /// Override `[weights]` (as passed to `query`)
+        #[arg(long, value_name = "KEY=W,...", value_parser = topo_core::WeightsConfig::parse)]
+        weights: Option<topo_core::WeightsConfig>,
+
+        /// In JSON, wrap the files in an object with the weights and query cutoff
+        #[arg(long)]
+        summary: bool,
+    },
+
+    /// Recommend a preset and token budget for a task on a given model
+    Budget {
+        /// The task or query to plan for
+        task: String,
+
+        /// Target model, e.g. claude-sonnet-4 or gpt-4o (sets the context window)
+        #[arg(long)]
+        model: String,
+
+        /// Context window in tokens, for models topo doesn't know
+        #[arg(long, value_name = "N")]
+        context_tokens: Option<u64>,
+
+        /// Run the query with the recommended settings, after confirmation
+        #[arg(long)]
+        run: bool,
+
+        /// Skip the confirmation for --run
+        #[arg(long, short = 'y', requires = "run")]
+        yes: bool,
+    },
+
+    /// Context around a code location: enclosing chunk, importers, tests, commits
+    BlameContext {
+        /// Location as <path:line>, e.g. from a stack trace
+        location: String,
+    },
+
+    /// Show who owns each file: CODEOWNERS owners and primary authors by git blame
+    Owners {
+        /// File or directory to report on (default: the whole repository)
+        path: Option<String>,
+    },
+
+    /// Print a file, a line range or a named chunk with line numbers, redacted
+    Cat {
+        /// File to print
+        path: String,
+
+        /// Line range: 40-120, 40- (to the end) or 40
+        #[arg(long, value_name = "RANGE")]
+        lines: Option<String>,
+
+        /// Print the function or type with this name instead of a line range
+        #[arg(long, conflicts_with = "lines")]
+        chunk: Option<String>,
+    },
+
+    /// Print a file's outline: its imports, types and functions, with methods nested
+    Outline {
+        /// File to outline
+        path: String,
+    },
+
+    /// Search file contents, grouping matches by file, most relevant file first
+    Grep {
+        /// Regex to look for, or a literal string with -F
+        pattern: String,
+
+        /// Match the pattern literally rather than as a regex
+        #[arg(short = 'F', long)]
+        fixed_strings: bool,
+
+        /// Ignore case when matching
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Lines of context after each match
+        #[arg(short = 'A', long, value_name = "N", default_value_t = 0)]
+        after_context: usize,
+
+        /// Lines of context before each match
+        #[arg(short = 'B', long, value_name = "N", default_value_t = 0)]
+        before_context: usize,
+
+        /// Lines of context before and after each match
+        #[arg(short = 'C', long, value_name = "N")]
+        context: Option<usize>,
+
+        /// Maximum number of files to show (default: 20)
+        #[arg(long)]
+        top: Option<usize>,
+    },
+
+    /// Context pack for a failing test: the test, its imports, and the code under test
+    TestContext {
+        /// Test name (e.g. `tests::auth::test_login`) or path to a JUnit XML report
+        target: String,
+
+        /// Preset used for the default budget (default: balanced, or `[defaults] preset`)
+        #[arg(long)]
+        preset: Option<String>,
+
+        /// Maximum bytes for token budget
+        #[arg(long)]
+        max_bytes: Option<u64>,
+
+        /// Maximum tokens for token budget
+        #[arg(long)]
+        max_tokens: Option<u64>,
+    },
+
+    /// Files to look at alongside one file: tests, importers, imports, co-changes
+    Expand {
+        /// Path of the file being edited
+        path: String,
+
+        /// Maximum number of related files to show
+        #[arg(long, default_value = "20")]
+        top: usize,
+    },
+
+    /// Print an extractive summary of a file or of every file in a directory:
+    /// doc comments, exported signatures and top-level constants
+    Summarize {
+        /// File or directory to summarize
+        path: String,
+    },
+
+    /// Show a file's resolved imports and importers
+    Deps {
+        /// Path of the file to inspect
+        path: String,
+
+        /// Show how each raw import was resolved: resolver, candidates and why
+        /// others were rejected
+        #[arg(long)]
+        explain: bool,
+    },
+
+    /// Report imports that resolve to no repository file, grouped by specifier
+    Unresolved {
+        /// Maximum number of specifiers to show
+        #[arg(long, default_value = "50")]
+        top: usize,
+    },
+
+    /// List files that transitively import a file ("what could break?")
+    Impact {
+        /// Path of the file being changed
+        path: String,
+
+        /// Maximum number of import hops to follow (default: unlimited)
+        #[arg(long)]
+        depth: Option<usize>,
+
+        /// Maximum number of dependents to show
+        #[arg(long)]
+        top: Option<usize>,
+    },
+
+    /// List files related to a file by imports, shared symbols and co-change
+    Related {
+        /// Path of the file to find relatives of
+        path: String,
+
+        /// Maximum number of files to show (default: 20)
+        #[arg(long)]
+        top: Option<usize>,
+    },
+
+    /// List files similar to a file: near-duplicates and parallel implementations
+    Similar {
+        /// Path of the file to compare against
+        path: String,
+
+        /// Maximum number of files to show (default: 10)
+        #[arg(long)]
+        top: Option<usize>,
+
+        /// Hide files less similar than this, 0 to 1 (default: 0.3)
+        #[arg(long, value_name = "X")]
+        min_similarity: Option<f64>,
+    },
+
+    /// Search the deep index for functions, types and impls by name
+    Symbols {
+        /// Name to look for; camelCase, snake_case and separate words all match
+        name: String,
+
+        /// Only show definitions of this kind
+        #[arg(long, value_enum)]
+        kind: Option<SymbolKind>,
+
+        /// Maximum number of symbols to show (default: 20)
+        #[arg(long)]
+        top: Option<usize>,
+    },
+
+    /// List hotspots: files that change most often and sit most central in the import graph
+    Hot {
+        /// Count commits from the last N days (default: `[churn] window_days`, else 365)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        days: Option<u32>,
+
+        /// Maximum number of files to show (default: 20)
+        #[arg(long)]
+        top: Option<usize>,
+    },
+
+    /// List files changed since the last index, plus the files importing them
+    Changed {
+        /// Exit with status 7 when anything changed (for CI)
+        #[arg(long)]
+        exit_code: bool,
+    },
+
+    /// Save or restore a named file selection
+    Manifest {
+        #[command(subcommand)]
+        action: ManifestAction,
     },
 
     /// Inspect the index (file count, size, stats)
-    Inspect,
+    Inspect {
+        /// List the files the last build could not index, and why
+        #[arg(long)]
+        errors: bool,
+    },
+
+    /// Summarize the repository: directories, core modules, entry points and
+    /// key types, from the index and import graph
+    Overview {
+        /// Write the overview between markers in AGENTS.md and CLAUDE.md
+        #[arg(long)]
+        write: bool,
+    },
+
+    /// Find the commits most relevant to a task by their messages and the
+    /// files they touched: the prior art to read before editing
+    Log {
+        /// Task description to match commits against
+        task: String,
+
+        /// Only search commits from the last N days (default: the last 1000 commits)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        days: Option<u32>,
+
+        /// Maximum number of commits to show (default: 10)
+        #[arg(long)]
+        top: Option<usize>,
+    },
+
+    /// Print the directory tree with the files and tokens in each subtree
+    Tree {
+        /// Directory to start from (default: the repository root)
+        path: Option<String>,
+
+        /// Also show each subtree's share of PageRank and recent git activity
+        #[arg(long)]
+        weights: bool,
+
+        /// Directory levels to show below the start (default: 2)
+        #[arg(long, value_name = "N")]
+        depth: Option<usize>,
+    },
+
+    /// Remove cached index, import graph and embeddings under .topo/
+    Clean {
+        /// Only remove the embedding cache
+        #[arg(long)]
+        embeddings: bool,
+    },
 
     /// Print machine-readable tool capabilities
     Describe,
 
+    /// Print a shell completion script
+    Completions {
+        /// Target shell
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Print the man page (roff)
+    Man,
+
     /// Start MCP (Model Context Protocol) server on stdio
     Mcp,
 
@@ -150,21 +714,64 @@ pub enum Command {
         /// Install Claude Code hooks for automatic context injection (default: true)
         #[arg(long, default_value = "true", action = clap::ArgAction::Set)]
         hooks: bool,
+
+        /// Shell to write the hooks for (default: powershell on Windows, bash elsewhere)
+        #[arg(long, value_enum)]
+        hook_shell: Option<HookShell>,
+
+        /// Register `topo mcp` with MCP clients (all of them if none are named)
+        #[arg(long, value_enum, value_delimiter = ',', num_args = 0..)]
+        mcp: Option<Vec<McpClient>>,
     },
 
     /// Show context savings from topo hook usage
     Gain,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum ManifestAction {
+    /// Save the last query's selection (with content hashes) under a name
+    Save {
+        /// Manifest name
+        name: String,
+
+        /// Save a selection file (JSONL or JSON) instead of the last query
+        #[arg(long)]
+        from: Option<PathBuf>,
+    },
+
+    /// Restore a saved selection and report files changed since it was saved
+    Load {
+        /// Manifest name
+        name: String,
+    },
+
+    /// List saved manifests
+    List,
+}
+
 impl Cli {
-    /// Resolve the repository root path.
+    /// `.topo/config.toml` of the repository root.
+    pub fn config(&self) -> Result<topo_core::Config> {
+        Ok(topo_core::Config::load(&self.repo_root()?)?)
+    }
+
+    /// Resolve the repository root path. An explicit root must be a directory.
     pub fn repo_root(&self) -> Result<PathBuf> {
-        if let Some(ref root) = self.root {
-            Ok(root.clone())
-        } else if let Ok(root) = std::env::var("TOPO_ROOT") {
-            Ok(PathBuf::from(root))
+        let explicit = self.root.clone().or_else(|| {
+            std::env::var_os("TOPO_ROOT")
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        });
+        if let Some(root) = explicit {
+            if !root.is_dir() {
+                anyhow::bail!(error::CliError::bad_root(&root));
+            }
+            Ok(root)
         } else {
-            Ok(std::env::current_dir()?)
+            // Walk up to the enclosing repository so topo works from subdirectories
+            let cwd = std::env::current_dir()?;
+            Ok(topo_core::discover_root(&cwd).unwrap_or(cwd))
         }
     }
 
@@ -195,62 +802,410 @@ impl Cli {
     pub fn is_quiet(&self) -> bool {
         self.quiet
     }
+
+    /// The `--index-backend` to save the deep index with, if given.
+    pub fn index_backend(&self) -> Option<topo_index::IndexBackend> {
+        self.index_backend.map(Into::into)
+    }
+
+    /// Whether `--include-artifacts` asks for generated artifacts in the deep index.
+    pub fn include_artifacts(&self) -> bool {
+        self.include_artifacts
+    }
+
+    /// A bundle builder for `root` that also scans the `--keep-dir` directories.
+    pub fn bundle_builder<'a>(&self, root: &'a Path) -> topo_scanner::BundleBuilder<'a> {
+        topo_scanner::BundleBuilder::new(root).keep_dirs(&self.keep_dir)
+    }
+
+    /// The package directory (relative to `root`) that queries default to,
+    /// when running inside one package of a monorepo.
+    pub fn package_scope(&self, root: &std::path::Path) -> Option<String> {
+        if self.whole_repo {
+            return None;
+        }
+        let cwd = std::env::current_dir().ok()?;
+        topo_core::nearest_package(root, &cwd).map(|p| p.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// Turn a user-supplied path into a path relative to `root`.
+    ///
+    /// Relative paths are taken from the current directory, which may be a
+    /// subdirectory of the repository root.
+    pub fn repo_relative(&self, root: &std::path::Path, path: &str) -> String {
+        let path = std::path::Path::new(path);
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()
+                .map(|cwd| cwd.join(path))
+                .unwrap_or_else(|_| path.to_path_buf())
+        };
+        let relative = absolute.strip_prefix(root).unwrap_or(path);
+        let relative = relative.strip_prefix("./").unwrap_or(relative);
+        relative.to_string_lossy().replace('\\', "/")
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.verbose > 0 && !self.quiet
+    }
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let cli = match Cli::try_parse_from(&args) {
+        Ok(cli) => cli,
+        Err(err) => return error::report_parse(err, &args),
+    };
+    logging::init(cli.verbose, cli.quiet);
+    match run(&cli) {
+        Ok(status) => status,
+        Err(err) => error::report(&cli, &err),
+    }
+}
 
+fn run(cli: &Cli) -> Result<ExitCode> {
     match cli.command {
-        Some(Command::Index { deep, force }) => {
-            commands::index::run(&cli, deep, force)?;
+        Some(Command::Index { deep, force, stats }) => {
+            commands::index::run(cli, deep, force, stats)?;
+        }
+        Some(Command::Watch { debounce }) => {
+            commands::watch::run(cli, std::time::Duration::from_millis(debounce))?;
         }
         Some(Command::Query {
             ref task,
-            preset,
+            ref preset,
             max_bytes,
             max_tokens,
             min_score,
             top,
+            semantic_weight,
+            semantic_only,
+            ref embedder,
+            rerank,
+            fail_on_empty,
+            outline,
+            no_expand,
+            ref weights,
+            ref since,
+            ref scope,
+            ref owner,
+            group_by,
         }) => {
-            commands::query::run(&cli, task, preset, max_bytes, max_tokens, min_score, top)?;
+            let config = cli.config()?;
+            let defaults = &config.defaults;
+            let options = commands::query::QueryOptions {
+                preset: preset::Preset::resolve(preset.as_deref(), &config)?,
+                max_bytes: max_bytes.or(defaults.max_bytes),
+                max_tokens: max_tokens.or(defaults.max_tokens),
+                min_score: min_score.or(defaults.min_score),
+                top,
+                semantic_weight,
+                semantic_only,
+                embedder: embedder.clone(),
+                rerank,
+                fail_on_empty,
+                outline,
+                no_expand,
+                weights: weights.clone(),
+                since: since.clone(),
+                scope: scope.clone(),
+                owner: owner.clone(),
+                group_by,
+            };
+            return commands::query::run(cli, task, options);
         }
         Some(Command::Quick {
             ref task,
-            preset,
+            ref preset,
+            max_bytes,
+            max_tokens,
+            min_score,
+            top,
+            semantic_weight,
+            semantic_only,
+            ref embedder,
+            rerank,
+            fail_on_empty,
+            outline,
+            no_expand,
+            ref weights,
+            ref since,
+            ref scope,
+            ref owner,
+            group_by,
+        }) => {
+            let config = cli.config()?;
+            let defaults = &config.defaults;
+            let options = commands::query::QueryOptions {
+                preset: preset::Preset::resolve(preset.as_deref(), &config)?,
+                max_bytes: max_bytes.or(defaults.max_bytes),
+                max_tokens: max_tokens.or(defaults.max_tokens),
+                min_score: min_score.or(defaults.min_score),
+                top,
+                semantic_weight,
+                semantic_only,
+                embedder: embedder.clone(),
+                rerank,
+                fail_on_empty,
+                outline,
+                no_expand,
+                weights: weights.clone(),
+                since: since.clone(),
+                scope: scope.clone(),
+                owner: owner.clone(),
+                group_by,
+            };
+            return commands::quick::run(cli, task, options);
+        }
+        Some(Command::Pack {
+            ref task,
+            ref preset,
             max_bytes,
             max_tokens,
             min_score,
             top,
+            ref scope,
+            ref output,
         }) => {
-            commands::quick::run(&cli, task, preset, max_bytes, max_tokens, min_score, top)?;
+            let config = cli.config()?;
+            let defaults = &config.defaults;
+            let options = commands::query::QueryOptions {
+                preset: preset::Preset::resolve(preset.as_deref(), &config)?,
+                max_bytes: max_bytes.or(defaults.max_bytes),
+                max_tokens: max_tokens.or(defaults.max_tokens),
+                min_score: min_score.or(defaults.min_score),
+                top,
+                semantic_weight: None,
+                semantic_only: false,
+                embedder: None,
+                rerank: false,
+                fail_on_empty: false,
+                outline: None,
+                no_expand: false,
+                weights: None,
+                since: None,
+                scope: scope.clone(),
+                owner: None,
+                group_by: None,
+            };
+            commands::pack::run(cli, task, options, output.as_deref())?;
+        }
+        Some(Command::More {
+            max_bytes,
+            max_tokens,
+            top,
+        }) => {
+            commands::more::run(cli, max_bytes, max_tokens, top)?;
         }
         Some(Command::Render {
             ref file,
             max_tokens,
         }) => {
-            commands::render::run(&cli, file, max_tokens)?;
+            commands::render::run(cli, file, max_tokens)?;
+        }
+        Some(Command::DiffResults {
+            ref a,
+            ref b,
+            against_last,
+        }) => {
+            commands::diff_results::run(cli, a, b.as_deref(), against_last)?;
         }
         Some(Command::Explain {
             ref task,
             top,
-            preset,
+            ref preset,
+            max_bytes,
+            max_tokens,
+            min_score,
+            ref weights,
+            summary,
         }) => {
-            commands::explain::run(&cli, task, top, preset)?;
+            let config = cli.config()?;
+            let defaults = &config.defaults;
+            commands::explain::run(
+                cli,
+                task,
+                top,
+                preset::Preset::resolve(preset.as_deref(), &config)?,
+                max_bytes.or(defaults.max_bytes),
+                max_tokens.or(defaults.max_tokens),
+                min_score.or(defaults.min_score),
+                weights.clone(),
+                summary,
+            )?;
         }
-        Some(Command::Inspect) => {
-            commands::inspect::run(&cli)?;
+        Some(Command::Budget {
+            ref task,
+            ref model,
+            context_tokens,
+            run,
+            yes,
+        }) => {
+            return commands::budget::run(cli, task, model, context_tokens, run, yes);
+        }
+        Some(Command::BlameContext { ref location }) => {
+            commands::blame_context::run(cli, location)?;
+        }
+        Some(Command::Owners { ref path }) => {
+            commands::owners::run(cli, path.as_deref())?;
+        }
+        Some(Command::Cat {
+            ref path,
+            ref lines,
+            ref chunk,
+        }) => {
+            commands::cat::run(cli, path, lines.as_deref(), chunk.as_deref())?;
+        }
+        Some(Command::Outline { ref path }) => {
+            commands::outline::run(cli, path)?;
+        }
+        Some(Command::Grep {
+            ref pattern,
+            fixed_strings,
+            ignore_case,
+            after_context,
+            before_context,
+            context,
+            top,
+        }) => {
+            commands::grep::run(
+                cli,
+                pattern,
+                commands::grep::GrepOptions {
+                    fixed_strings,
+                    ignore_case,
+                    before: context.unwrap_or(before_context).max(before_context),
+                    after: context.unwrap_or(after_context).max(after_context),
+                    top,
+                },
+            )?;
+        }
+        Some(Command::TestContext {
+            ref target,
+            ref preset,
+            max_bytes,
+            max_tokens,
+        }) => {
+            let config = cli.config()?;
+            let defaults = &config.defaults;
+            commands::test_context::run(
+                cli,
+                target,
+                preset::Preset::resolve(preset.as_deref(), &config)?,
+                max_bytes.or(defaults.max_bytes),
+                max_tokens.or(defaults.max_tokens),
+            )?;
+        }
+        Some(Command::Expand { ref path, top }) => {
+            commands::expand::run(cli, path, top)?;
+        }
+        Some(Command::Summarize { ref path }) => {
+            commands::summarize::run(cli, path)?;
+        }
+        Some(Command::Deps { ref path, explain }) => {
+            commands::deps::run(cli, path, explain)?;
+        }
+        Some(Command::Unresolved { top }) => {
+            commands::unresolved::run(cli, top)?;
+        }
+        Some(Command::Impact {
+            ref path,
+            depth,
+            top,
+        }) => {
+            commands::impact::run(cli, path, depth, top)?;
+        }
+        Some(Command::Related { ref path, top }) => {
+            commands::related::run(cli, path, top)?;
+        }
+        Some(Command::Similar {
+            ref path,
+            top,
+            min_similarity,
+        }) => {
+            commands::similar::run(cli, path, top, min_similarity)?;
+        }
+        Some(Command::Symbols {
+            ref name,
+            kind,
+            top,
+        }) => {
+            commands::symbols::run(cli, name, kind, top)?;
+        }
+        Some(Command::Hot { days, top }) => {
+            commands::hot::run(cli, days, top)?;
+        }
+        Some(Command::Changed { exit_code }) => {
+            return commands::changed::run(cli, exit_code);
+        }
+        Some(Command::Manifest { ref action }) => match action {
+            ManifestAction::Save { name, from } => {
+                commands::manifest::save(cli, name, from.as_deref())?;
+            }
+            ManifestAction::Load { name } => {
+                commands::manifest::load(cli, name)?;
+            }
+            ManifestAction::List => {
+                commands::manifest::list(cli)?;
+            }
+        },
+        Some(Command::Inspect { errors }) => {
+            commands::inspect::run(cli, errors)?;
+        }
+        Some(Command::Overview { write }) => {
+            commands::overview::run(cli, write)?;
+        }
+        Some(Command::Log {
+            ref task,
+            days,
+            top,
+        }) => {
+            commands::log::run(cli, task, days, top)?;
+        }
+        Some(Command::Tree {
+            ref path,
+            weights,
+            depth,
+        }) => {
+            commands::tree::run(cli, path.as_deref(), weights, depth)?;
+        }
+        Some(Command::Clean { embeddings }) => {
+            commands::clean::run(cli, embeddings)?;
         }
         Some(Command::Describe) => {
-            commands::describe::run(&cli)?;
+            commands::describe::run(cli)?;
+        }
+        Some(Command::Completions { shell }) => {
+            commands::completions::run(shell)?;
+        }
+        Some(Command::Man) => {
+            commands::completions::man()?;
         }
         Some(Command::Mcp) => {
-            commands::mcp::run(&cli)?;
+            commands::mcp::run(cli)?;
         }
-        Some(Command::Init { force, hooks }) => {
-            commands::init::run(&cli, force, hooks)?;
+        Some(Command::Init {
+            force,
+            hooks,
+            hook_shell,
+            ref mcp,
+        }) => {
+            let mcp = match mcp.as_deref() {
+                Some([]) => &McpClient::ALL[..],
+                Some(clients) => clients,
+                None => &[],
+            };
+            commands::init::run(
+                cli,
+                force,
+                hooks,
+                hook_shell.unwrap_or_else(HookShell::native),
+                mcp,
+            )?;
         }
         Some(Command::Gain) => {
-            commands::gain::run(&cli)?;
+            commands::gain::run(cli)?;
         }
         None => {
             // No subcommand: print version info
@@ -261,7 +1216,7 @@ fn main() -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 #[cfg(test)]
@@ -286,6 +1241,15 @@ mod tests {
         assert!(cli.quiet);
     }
 
+    #[test]
+    fn cli_parses_index_backend() {
+        let cli =
+            Cli::try_parse_from(["topo", "index", "--deep", "--index-backend", "sqlite"]).unwrap();
+        assert_eq!(cli.index_backend(), Some(topo_index::IndexBackend::Sqlite));
+        let cli = Cli::try_parse_from(["topo", "index"]).unwrap();
+        assert_eq!(cli.index_backend(), None);
+    }
+
     #[test]
     fn cli_parses_index() {
         let cli = Cli::try_parse_from(["topo", "index"]).unwrap();
@@ -293,7 +1257,8 @@ mod tests {
             cli.command,
             Some(Command::Index {
                 deep: false,
-                force: false
+                force: false,
+                stats: false
             })
         ));
     }
@@ -305,11 +1270,23 @@ mod tests {
             cli.command,
             Some(Command::Index {
                 deep: true,
-                force: false
+                force: false,
+                stats: false
             })
         ));
     }
 
+    #[test]
+    fn cli_parses_watch() {
+        let cli = Cli::try_parse_from(["topo", "watch"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Watch { debounce: 300 })
+        ));
+        let cli = Cli::try_parse_from(["topo", "watch", "--debounce", "50"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Watch { debounce: 50 })));
+    }
+
     #[test]
     fn cli_parses_query() {
         let cli = Cli::try_parse_from(["topo", "query", "auth middleware"]).unwrap();
@@ -326,27 +1303,176 @@ mod tests {
         let cli = Cli::try_parse_from(["topo", "quick", "auth", "--preset", "fast"]).unwrap();
         match cli.command {
             Some(Command::Quick {
-                ref task, preset, ..
+                ref task,
+                ref preset,
+                ..
             }) => {
                 assert_eq!(task, "auth");
-                assert!(matches!(preset, preset::Preset::Fast));
+                assert_eq!(preset.as_deref(), Some("fast"));
             }
             _ => panic!("expected Quick"),
         }
     }
 
+    #[test]
+    fn cli_parses_semantic_flags() {
+        let cli =
+            Cli::try_parse_from(["topo", "query", "auth", "--semantic-weight", "0.5"]).unwrap();
+        match cli.command {
+            Some(Command::Query {
+                semantic_weight,
+                semantic_only,
+                ..
+            }) => {
+                assert_eq!(semantic_weight, Some(0.5));
+                assert!(!semantic_only);
+            }
+            _ => panic!("expected Query"),
+        }
+        let cli = Cli::try_parse_from(["topo", "quick", "auth", "--semantic-only"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Quick {
+                semantic_only: true,
+                ..
+            })
+        ));
+        assert!(
+            Cli::try_parse_from([
+                "topo",
+                "query",
+                "auth",
+                "--semantic-only",
+                "--semantic-weight",
+                "1"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn cli_parses_embedder() {
+        let cli = Cli::try_parse_from(["topo", "quick", "auth", "--embedder", "openai"]).unwrap();
+        match cli.command {
+            Some(Command::Quick { embedder, .. }) => {
+                assert_eq!(embedder.as_deref(), Some("openai"));
+            }
+            _ => panic!("expected Quick"),
+        }
+        assert!(Cli::try_parse_from(["topo", "query", "auth", "--embedder", "bert"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_rerank() {
+        let cli = Cli::try_parse_from(["topo", "query", "auth", "--rerank"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Query { rerank: true, .. })
+        ));
+    }
+
+    #[test]
+    fn cli_parses_fail_on_empty() {
+        let cli = Cli::try_parse_from(["topo", "quick", "auth", "--fail-on-empty"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Quick {
+                fail_on_empty: true,
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from(["topo", "query", "auth"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Query {
+                fail_on_empty: false,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn cli_parses_outline() {
+        let outline = |args: &[&str]| match Cli::try_parse_from(args).unwrap().command {
+            Some(Command::Query { outline, .. } | Command::Quick { outline, .. }) => outline,
+            _ => panic!("expected Query or Quick"),
+        };
+        assert_eq!(outline(&["topo", "query", "auth"]), None);
+        assert_eq!(outline(&["topo", "query", "auth", "--outline"]), Some(10));
+        assert_eq!(
+            outline(&["topo", "quick", "--outline", "3", "auth"]),
+            Some(3)
+        );
+    }
+
     #[test]
     fn cli_parses_explain() {
         let cli = Cli::try_parse_from(["topo", "explain", "auth", "--top", "5"]).unwrap();
         match cli.command {
-            Some(Command::Explain { ref task, top, .. }) => {
+            Some(Command::Explain {
+                ref task,
+                top,
+                summary,
+                ..
+            }) => {
                 assert_eq!(task, "auth");
                 assert_eq!(top, 5);
+                assert!(!summary);
             }
             _ => panic!("expected Explain"),
         }
     }
 
+    #[test]
+    fn cli_parses_weights() {
+        let weights = |args: &[&str]| match Cli::try_parse_from(args).unwrap().command {
+            Some(
+                Command::Query { weights, .. }
+                | Command::Quick { weights, .. }
+                | Command::Explain { weights, .. },
+            ) => weights,
+            _ => panic!("expected Query, Quick or Explain"),
+        };
+        assert_eq!(weights(&["topo", "query", "auth"]), None);
+        let parsed = weights(&[
+            "topo",
+            "explain",
+            "auth",
+            "--weights",
+            "bm25f=2,heuristic=0.5",
+        ])
+        .unwrap();
+        assert_eq!(parsed.bm25f, Some(2.0));
+        assert_eq!(parsed.heuristic, Some(0.5));
+        assert_eq!(parsed.pagerank, None);
+        assert!(Cli::try_parse_from(["topo", "quick", "auth", "--weights", "bm25=1"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_budget() {
+        let cli = Cli::try_parse_from([
+            "topo", "budget", "auth", "--model", "gpt-4o", "--run", "--yes",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Budget {
+                ref task,
+                ref model,
+                context_tokens,
+                run,
+                yes,
+            }) => {
+                assert_eq!(task, "auth");
+                assert_eq!(model, "gpt-4o");
+                assert_eq!(context_tokens, None);
+                assert!(run && yes);
+            }
+            _ => panic!("expected Budget"),
+        }
+        assert!(Cli::try_parse_from(["topo", "budget", "auth"]).is_err());
+        assert!(Cli::try_parse_from(["topo", "budget", "auth", "--model", "x", "--yes"]).is_err());
+    }
+
     #[test]
     fn cli_parses_describe() {
         let cli = Cli::try_parse_from(["topo", "describe"]).unwrap();
@@ -369,9 +1495,16 @@ mod tests {
     fn cli_parses_init_default_hooks() {
         let cli = Cli::try_parse_from(["topo", "init"]).unwrap();
         match cli.command {
-            Some(Command::Init { force, hooks }) => {
+            Some(Command::Init {
+                force,
+                hooks,
+                hook_shell,
+                mcp,
+            }) => {
                 assert!(!force);
                 assert!(hooks); // hooks default to true
+                assert_eq!(hook_shell, None);
+                assert_eq!(mcp, None);
             }
             _ => panic!("expected Init"),
         }
@@ -388,12 +1521,425 @@ mod tests {
         }
     }
 
+    #[test]
+    fn cli_parses_init_hook_shell() {
+        let cli = Cli::try_parse_from(["topo", "init", "--hook-shell", "powershell"]).unwrap();
+        match cli.command {
+            Some(Command::Init { hook_shell, .. }) => {
+                assert_eq!(hook_shell, Some(HookShell::Powershell));
+            }
+            _ => panic!("expected Init"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_init_mcp() {
+        let cli = Cli::try_parse_from(["topo", "init", "--mcp"]).unwrap();
+        match cli.command {
+            Some(Command::Init { mcp, .. }) => assert_eq!(mcp, Some(vec![])),
+            _ => panic!("expected Init"),
+        }
+        let cli = Cli::try_parse_from(["topo", "init", "--mcp", "cursor,vscode"]).unwrap();
+        match cli.command {
+            Some(Command::Init { mcp, .. }) => {
+                assert_eq!(mcp, Some(vec![McpClient::Cursor, McpClient::Vscode]));
+            }
+            _ => panic!("expected Init"),
+        }
+        assert!(Cli::try_parse_from(["topo", "init", "--mcp", "emacs"]).is_err());
+    }
+
     #[test]
     fn cli_parses_gain() {
         let cli = Cli::try_parse_from(["topo", "gain"]).unwrap();
         assert!(matches!(cli.command, Some(Command::Gain)));
     }
 
+    #[test]
+    fn cli_parses_pack() {
+        let cli = Cli::try_parse_from([
+            "topo",
+            "pack",
+            "auth flow",
+            "--max-tokens",
+            "8000",
+            "-o",
+            "context.txt",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Pack {
+                ref task,
+                max_tokens,
+                ref output,
+                ..
+            }) => {
+                assert_eq!(task, "auth flow");
+                assert_eq!(max_tokens, Some(8000));
+                assert_eq!(output.as_deref(), Some(Path::new("context.txt")));
+            }
+            _ => panic!("expected Pack"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_more() {
+        let cli = Cli::try_parse_from(["topo", "more", "--max-tokens", "2000"]).unwrap();
+        match cli.command {
+            Some(Command::More {
+                max_bytes,
+                max_tokens,
+                top,
+            }) => {
+                assert_eq!(max_bytes, None);
+                assert_eq!(max_tokens, Some(2000));
+                assert_eq!(top, None);
+            }
+            _ => panic!("expected More"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_diff_results() {
+        let cli = Cli::try_parse_from(["topo", "diff-results", "a.jsonl", "b.jsonl"]).unwrap();
+        match cli.command {
+            Some(Command::DiffResults {
+                ref b,
+                against_last,
+                ..
+            }) => {
+                assert_eq!(b.as_deref(), Some(std::path::Path::new("b.jsonl")));
+                assert!(!against_last);
+            }
+            _ => panic!("expected DiffResults"),
+        }
+    }
+
+    #[test]
+    fn cli_diff_results_requires_second_selection() {
+        assert!(Cli::try_parse_from(["topo", "diff-results", "a.jsonl"]).is_err());
+        assert!(Cli::try_parse_from(["topo", "diff-results", "a.jsonl", "--against-last"]).is_ok());
+    }
+
+    #[test]
+    fn cli_parses_manifest_save() {
+        let cli = Cli::try_parse_from(["topo", "manifest", "save", "auth-work"]).unwrap();
+        match cli.command {
+            Some(Command::Manifest {
+                action: ManifestAction::Save { ref name, ref from },
+            }) => {
+                assert_eq!(name, "auth-work");
+                assert!(from.is_none());
+            }
+            _ => panic!("expected Manifest Save"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_manifest_load() {
+        let cli = Cli::try_parse_from(["topo", "manifest", "load", "auth-work"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Manifest {
+                action: ManifestAction::Load { .. }
+            })
+        ));
+    }
+
+    #[test]
+    fn cli_parses_blame_context() {
+        let cli = Cli::try_parse_from(["topo", "blame-context", "src/auth.rs:42"]).unwrap();
+        match cli.command {
+            Some(Command::BlameContext { ref location }) => {
+                assert_eq!(location, "src/auth.rs:42");
+            }
+            _ => panic!("expected BlameContext"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_owners_and_owner_filter() {
+        let cli = Cli::try_parse_from(["topo", "owners", "src"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Owners { path: Some(ref p) }) if p == "src"
+        ));
+        let cli = Cli::try_parse_from(["topo", "query", "auth", "--owner", "@alice"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Query { owner: Some(ref o), .. }) if o == "@alice"
+        ));
+    }
+
+    #[test]
+    fn cli_parses_cat() {
+        let cli = Cli::try_parse_from(["topo", "cat", "src/auth.rs", "--lines", "40-120"]).unwrap();
+        match cli.command {
+            Some(Command::Cat {
+                ref path,
+                ref lines,
+                ref chunk,
+            }) => {
+                assert_eq!(path, "src/auth.rs");
+                assert_eq!(lines.as_deref(), Some("40-120"));
+                assert!(chunk.is_none());
+            }
+            _ => panic!("expected Cat"),
+        }
+        assert!(
+            Cli::try_parse_from(["topo", "cat", "a.rs", "--lines", "1-2", "--chunk", "login"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn cli_parses_outline_command() {
+        let cli = Cli::try_parse_from(["topo", "outline", "src/auth.rs"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Outline { ref path }) if path == "src/auth.rs"
+        ));
+    }
+
+    #[test]
+    fn cli_parses_test_context() {
+        let cli =
+            Cli::try_parse_from(["topo", "test-context", "junit.xml", "--max-tokens", "8000"])
+                .unwrap();
+        match cli.command {
+            Some(Command::TestContext {
+                ref target,
+                max_tokens,
+                ..
+            }) => {
+                assert_eq!(target, "junit.xml");
+                assert_eq!(max_tokens, Some(8000));
+            }
+            _ => panic!("expected TestContext"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_expand() {
+        let cli = Cli::try_parse_from(["topo", "expand", "src/auth.rs", "--top", "5"]).unwrap();
+        match cli.command {
+            Some(Command::Expand { ref path, top }) => {
+                assert_eq!(path, "src/auth.rs");
+                assert_eq!(top, 5);
+            }
+            _ => panic!("expected Expand"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_impact() {
+        let cli = Cli::try_parse_from(["topo", "impact", "src/auth.rs", "--depth", "2"]).unwrap();
+        match cli.command {
+            Some(Command::Impact {
+                ref path,
+                depth,
+                top,
+            }) => {
+                assert_eq!(path, "src/auth.rs");
+                assert_eq!(depth, Some(2));
+                assert_eq!(top, None);
+            }
+            _ => panic!("expected Impact"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_related() {
+        let cli = Cli::try_parse_from(["topo", "related", "src/auth.rs", "--top", "5"]).unwrap();
+        match cli.command {
+            Some(Command::Related { ref path, top }) => {
+                assert_eq!(path, "src/auth.rs");
+                assert_eq!(top, Some(5));
+            }
+            _ => panic!("expected Related"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_similar() {
+        let cli =
+            Cli::try_parse_from(["topo", "similar", "src/retry.rs", "--min-similarity", "0.5"])
+                .unwrap();
+        match cli.command {
+            Some(Command::Similar {
+                ref path,
+                top,
+                min_similarity,
+            }) => {
+                assert_eq!(path, "src/retry.rs");
+                assert_eq!(top, None);
+                assert_eq!(min_similarity, Some(0.5));
+            }
+            _ => panic!("expected Similar"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_grep() {
+        let cli =
+            Cli::try_parse_from(["topo", "grep", "-F", "-i", "-B", "2", "-C", "1", "fn("]).unwrap();
+        match cli.command {
+            Some(Command::Grep {
+                ref pattern,
+                fixed_strings,
+                ignore_case,
+                after_context,
+                before_context,
+                context,
+                top,
+            }) => {
+                assert_eq!(pattern, "fn(");
+                assert!(fixed_strings && ignore_case);
+                assert_eq!((before_context, after_context, context), (2, 0, Some(1)));
+                assert_eq!(top, None);
+            }
+            _ => panic!("expected Grep"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_symbols() {
+        let cli =
+            Cli::try_parse_from(["topo", "symbols", "refreshToken", "--kind", "function"]).unwrap();
+        match cli.command {
+            Some(Command::Symbols {
+                ref name,
+                kind,
+                top,
+            }) => {
+                assert_eq!(name, "refreshToken");
+                assert_eq!(kind, Some(SymbolKind::Function));
+                assert_eq!(top, None);
+            }
+            _ => panic!("expected Symbols"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_exclude_license() {
+        let cli = Cli::try_parse_from([
+            "topo",
+            "quick",
+            "auth",
+            "--exclude-license",
+            "GPL-3.0",
+            "--exclude-license",
+            "AGPL-3.0",
+        ])
+        .unwrap();
+        assert_eq!(cli.exclude_license, vec!["GPL-3.0", "AGPL-3.0"]);
+        assert!(!cli.include_vendored);
+        assert!(cli.keep_dir.is_empty());
+    }
+
+    #[test]
+    fn cli_parses_deps() {
+        let cli = Cli::try_parse_from(["topo", "deps", "src/main.rs", "--explain"]).unwrap();
+        match cli.command {
+            Some(Command::Deps { ref path, explain }) => {
+                assert_eq!(path, "src/main.rs");
+                assert!(explain);
+            }
+            _ => panic!("expected Deps"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_summarize() {
+        let cli = Cli::try_parse_from(["topo", "summarize", "src/lib.rs"]).unwrap();
+        match cli.command {
+            Some(Command::Summarize { ref path }) => assert_eq!(path, "src/lib.rs"),
+            _ => panic!("expected Summarize"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_unresolved() {
+        let cli = Cli::try_parse_from(["topo", "unresolved", "--top", "10"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Unresolved { top: 10 })));
+    }
+
+    #[test]
+    fn cli_parses_overview() {
+        let cli = Cli::try_parse_from(["topo", "overview", "--write"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Overview { write: true })
+        ));
+    }
+
+    #[test]
+    fn cli_parses_log() {
+        let cli =
+            Cli::try_parse_from(["topo", "log", "token refresh", "--days", "90", "--top", "5"])
+                .unwrap();
+        match cli.command {
+            Some(Command::Log {
+                ref task,
+                days,
+                top,
+            }) => {
+                assert_eq!(task, "token refresh");
+                assert_eq!(days, Some(90));
+                assert_eq!(top, Some(5));
+            }
+            _ => panic!("expected Log"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_tree() {
+        let cli =
+            Cli::try_parse_from(["topo", "tree", "src", "--weights", "--depth", "3"]).unwrap();
+        match cli.command {
+            Some(Command::Tree {
+                ref path,
+                weights,
+                depth,
+            }) => {
+                assert_eq!(path.as_deref(), Some("src"));
+                assert!(weights);
+                assert_eq!(depth, Some(3));
+            }
+            _ => panic!("expected Tree"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_clean() {
+        let cli = Cli::try_parse_from(["topo", "clean", "--embeddings"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Clean { embeddings: true })
+        ));
+    }
+
+    #[test]
+    fn cli_parses_completions() {
+        let cli = Cli::try_parse_from(["topo", "completions", "zsh"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Completions {
+                shell: clap_complete::Shell::Zsh
+            })
+        ));
+        assert!(Cli::try_parse_from(["topo", "completions", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_changed() {
+        let cli = Cli::try_parse_from(["topo", "changed", "--exit-code"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Changed { exit_code: true })
+        ));
+    }
+
     #[test]
     fn cli_parses_format_compact() {
         let cli = Cli::try_parse_from(["topo", "--format", "compact"]).unwrap();
@@ -0,0 +1,205 @@
+//! MCP client configs written by `topo init --mcp`: each one gets a `topo`
+//! server entry that runs `topo --root <repo> mcp`.
+
+use crate::McpClient;
+use crate::error::CliError;
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the server entry in every client config.
+const SERVER_NAME: &str = "topo";
+
+/// What registering with one client did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Registration {
+    Added,
+    Updated,
+    /// The entry was already there, as written.
+    Unchanged,
+    /// A different `topo` entry was there and `--force` was not given.
+    Skipped,
+}
+
+impl McpClient {
+    pub const ALL: [Self; 3] = [Self::ClaudeDesktop, Self::Cursor, Self::Vscode];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ClaudeDesktop => "Claude Desktop",
+            Self::Cursor => "Cursor",
+            Self::Vscode => "VS Code",
+        }
+    }
+
+    /// Where the client reads its servers. Claude Desktop has one config per
+    /// user; Cursor and VS Code have one per project.
+    pub fn config_path(self, root: &Path) -> Result<PathBuf> {
+        Ok(match self {
+            Self::ClaudeDesktop => user_config_dir()?
+                .join("Claude")
+                .join("claude_desktop_config.json"),
+            Self::Cursor => root.join(".cursor").join("mcp.json"),
+            Self::Vscode => root.join(".vscode").join("mcp.json"),
+        })
+    }
+
+    /// Key of the server map: VS Code calls it `servers`.
+    fn servers_key(self) -> &'static str {
+        match self {
+            Self::Vscode => "servers",
+            Self::ClaudeDesktop | Self::Cursor => "mcpServers",
+        }
+    }
+
+    /// The server entry that runs `topo mcp` on `root`.
+    fn entry(self, root: &Path) -> Value {
+        let args = json!(["--root", root.to_string_lossy(), "mcp"]);
+        match self {
+            Self::Vscode => json!({ "type": "stdio", "command": "topo", "args": args }),
+            Self::ClaudeDesktop | Self::Cursor => json!({ "command": "topo", "args": args }),
+        }
+    }
+}
+
+/// The per-user application config directory: `~/Library/Application
+/// Support` on macOS, `%APPDATA%` on Windows and `$XDG_CONFIG_HOME` (or
+/// `~/.config`) elsewhere.
+fn user_config_dir() -> Result<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+    let dir = if cfg!(windows) {
+        var("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+    match dir {
+        Some(dir) => Ok(dir),
+        None => bail!(
+            CliError::config("cannot find the user config directory for Claude Desktop")
+                .hint("Set HOME (APPDATA on Windows), or register with --mcp cursor,vscode only.")
+        ),
+    }
+}
+
+/// Add a `topo` server for `root` to the client config at `path`, keeping
+/// every other server and setting. A different `topo` entry is replaced
+/// only with `force`.
+pub fn register(client: McpClient, path: &Path, root: &Path, force: bool) -> Result<Registration> {
+    let mut config: Value = if path.exists() {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        if content.trim().is_empty() {
+            json!({})
+        } else {
+            // Never rewrite a config we cannot read: it is the user's
+            serde_json::from_str(&content).map_err(|e| {
+                CliError::config(format!("{} is not valid JSON: {e}", path.display()))
+                    .hint("Fix or remove the file, then run `topo init --mcp` again.")
+            })?
+        }
+    } else {
+        json!({})
+    };
+    let Some(object) = config.as_object_mut() else {
+        bail!(CliError::config(format!(
+            "{} does not hold a JSON object",
+            path.display()
+        )));
+    };
+
+    let servers = object
+        .entry(client.servers_key())
+        .or_insert_with(|| json!({}));
+    let Some(servers) = servers.as_object_mut() else {
+        bail!(CliError::config(format!(
+            "\"{}\" in {} is not an object",
+            client.servers_key(),
+            path.display()
+        )));
+    };
+
+    let entry = client.entry(root);
+    let result = match servers.get(SERVER_NAME) {
+        Some(existing) if *existing == entry => return Ok(Registration::Unchanged),
+        Some(_) if !force => return Ok(Registration::Skipped),
+        Some(_) => Registration::Updated,
+        None => Registration::Added,
+    };
+    servers.insert(SERVER_NAME.to_string(), entry);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&config)? + "\n")
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn register_creates_and_merges_configs() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".cursor/mcp.json");
+        let root = Path::new("/work/repo");
+
+        assert_eq!(
+            register(McpClient::Cursor, &path, root, false).unwrap(),
+            Registration::Added
+        );
+        assert_eq!(
+            register(McpClient::Cursor, &path, root, false).unwrap(),
+            Registration::Unchanged
+        );
+
+        fs::write(
+            &path,
+            r#"{"mcpServers":{"other":{"command":"x"}},"theme":"dark"}"#,
+        )
+        .unwrap();
+        register(McpClient::Cursor, &path, root, false).unwrap();
+        let config: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config["theme"], "dark");
+        assert_eq!(config["mcpServers"]["other"]["command"], "x");
+        assert_eq!(
+            config["mcpServers"]["topo"]["args"],
+            json!(["--root", "/work/repo", "mcp"])
+        );
+    }
+
+    #[test]
+    fn register_replaces_other_entries_only_with_force() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mcp.json");
+        fs::write(&path, r#"{"servers":{"topo":{"command":"old"}}}"#).unwrap();
+        let root = Path::new("/work/repo");
+
+        assert_eq!(
+            register(McpClient::Vscode, &path, root, false).unwrap(),
+            Registration::Skipped
+        );
+        assert_eq!(
+            register(McpClient::Vscode, &path, root, true).unwrap(),
+            Registration::Updated
+        );
+        let config: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config["servers"]["topo"]["type"], "stdio");
+    }
+
+    #[test]
+    fn register_leaves_invalid_json_alone() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("claude_desktop_config.json");
+        fs::write(&path, "{ not json").unwrap();
+        assert!(register(McpClient::ClaudeDesktop, &path, Path::new("/r"), true).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+    }
+}
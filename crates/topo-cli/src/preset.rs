@@ -1,61 +1,192 @@
-use clap::ValueEnum;
-
-/// Scoring presets that configure index depth and signal selection.
-#[derive(Debug, Clone, Copy, ValueEnum)]
-pub enum Preset {
-    /// Shallow index, heuristic-only scoring (fastest)
-    Fast,
-    /// Deep index (cached), hybrid BM25F + heuristic scoring
-    Balanced,
-    /// Deep index (fresh rebuild), hybrid + structural signals
+use crate::error::CliError;
+use anyhow::{Result, bail};
+use topo_core::{Config, PRESETS, PresetConfig, WeightsConfig};
+
+/// How much indexing a preset asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Depth {
+    /// Shallow scan only, no deep index.
+    Shallow,
+    /// Deep index, reused when up to date.
     Deep,
-    /// Deep index + reranking, all signals including embeddings
-    Thorough,
+    /// Deep index, rebuilt from scratch.
+    Fresh,
+}
+
+impl Depth {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "shallow" => Some(Self::Shallow),
+            "deep" => Some(Self::Deep),
+            "fresh" => Some(Self::Fresh),
+            _ => None,
+        }
+    }
+}
+
+/// A scoring preset: index depth, signal selection, and default budget.
+///
+/// The built-ins are `fast` (shallow index, heuristic-only scoring),
+/// `balanced` (cached deep index, hybrid BM25F + heuristic), `deep` (fresh
+/// deep index, hybrid + structural signals) and `thorough` (`deep` with a
+/// larger budget, per-chunk embeddings and cross-encoder reranking).
+/// `[presets.<name>]` in `.topo/config.toml` adds more or redefines these,
+/// and `[presets.<name>.weights]` tunes their signal weights and RRF `k`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preset {
+    name: String,
+    depth: Depth,
+    structural: bool,
+    chunks: bool,
+    chunk_embeddings: bool,
+    rerank: bool,
+    max_bytes: u64,
+    min_score: f64,
+    /// Laid over `[weights]`; empty for the built-ins.
+    weights: WeightsConfig,
 }
 
 impl Preset {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Self::Fast => "fast",
-            Self::Balanced => "balanced",
-            Self::Deep => "deep",
-            Self::Thorough => "thorough",
+    /// The built-in preset called `name`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        // (depth, structural, chunk embeddings, rerank, max bytes, min score)
+        let (depth, structural, chunk_embeddings, rerank, max_bytes, min_score) = match name {
+            "fast" => (Depth::Shallow, false, false, false, 50_000, 0.05),
+            "balanced" => (Depth::Deep, false, false, false, 100_000, 0.01),
+            "deep" => (Depth::Fresh, true, false, false, 200_000, 0.005),
+            "thorough" => (Depth::Fresh, true, true, true, 500_000, 0.001),
+            _ => return None,
+        };
+        Some(Self {
+            name: name.to_string(),
+            depth,
+            structural,
+            chunks: structural,
+            chunk_embeddings,
+            rerank,
+            max_bytes,
+            min_score,
+            weights: WeightsConfig::default(),
+        })
+    }
+
+    /// The default preset when neither the flag nor the config names one.
+    pub fn balanced() -> Self {
+        Self::builtin("balanced").expect("balanced is built in")
+    }
+
+    /// The preset called `name`: a `[presets]` entry in `config`, else a
+    /// built-in.
+    pub fn named(name: &str, config: &Config) -> Option<Self> {
+        match config.presets.get(name) {
+            Some(custom) => Some(Self::custom(name, custom)),
+            None => Self::builtin(name),
+        }
+    }
+
+    /// A `[presets]` entry, layered over its base.
+    fn custom(name: &str, custom: &PresetConfig) -> Self {
+        let base = custom
+            .base
+            .as_deref()
+            .or(PRESETS.contains(&name).then_some(name))
+            .and_then(Self::builtin)
+            .unwrap_or_else(Self::balanced);
+        Self {
+            name: name.to_string(),
+            depth: custom
+                .depth
+                .as_deref()
+                .and_then(Depth::parse)
+                .unwrap_or(base.depth),
+            structural: custom.structural.unwrap_or(base.structural),
+            chunks: custom.chunks.unwrap_or(base.chunks),
+            chunk_embeddings: custom.chunk_embeddings.unwrap_or(base.chunk_embeddings),
+            rerank: custom.rerank.unwrap_or(base.rerank),
+            max_bytes: custom.max_bytes.unwrap_or(base.max_bytes),
+            min_score: custom.min_score.unwrap_or(base.min_score),
+            weights: custom.weights.clone(),
         }
     }
 
+    /// The `--preset` flag if given, else `[defaults] preset` from
+    /// `.topo/config.toml`, else balanced.
+    pub fn resolve(flag: Option<&str>, config: &Config) -> Result<Self> {
+        let Some(name) = flag.or(config.defaults.preset.as_deref()) else {
+            return Ok(Self::balanced());
+        };
+        match Self::named(name, config) {
+            Some(preset) => Ok(preset),
+            None => bail!(CliError::usage(format!(
+                "unknown preset {name:?}; expected one of {}",
+                Self::names(config).join(", ")
+            ))),
+        }
+    }
+
+    /// Every preset name available with `config`, built-ins first.
+    pub fn names(config: &Config) -> Vec<&str> {
+        let mut names: Vec<&str> = PRESETS.to_vec();
+        names.extend(
+            config
+                .presets
+                .keys()
+                .map(String::as_str)
+                .filter(|name| !PRESETS.contains(name)),
+        );
+        names
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
     /// Whether this preset needs a deep index.
     pub fn needs_deep_index(&self) -> bool {
-        matches!(self, Self::Balanced | Self::Deep | Self::Thorough)
+        self.depth != Depth::Shallow
     }
 
     /// Whether this preset should force-rebuild the index.
     pub fn force_rebuild(&self) -> bool {
-        matches!(self, Self::Deep | Self::Thorough)
+        self.depth == Depth::Fresh
     }
 
     /// Whether to include structural signals (PageRank, git recency).
     pub fn use_structural_signals(&self) -> bool {
-        matches!(self, Self::Deep | Self::Thorough)
+        self.structural
+    }
+
+    /// Whether to deep-parse the selected files and report the chunks that
+    /// best match the query.
+    pub fn chunks(&self) -> bool {
+        self.chunks
+    }
+
+    /// Whether the semantic ranking embeds each indexed chunk and scores a
+    /// file by its best chunk, rather than embedding the head of the file.
+    pub fn chunk_embeddings(&self) -> bool {
+        self.chunk_embeddings
+    }
+
+    /// Whether the top of the fused ranking is re-scored by the `[rerank]`
+    /// cross-encoder, when one is configured.
+    pub fn rerank(&self) -> bool {
+        self.rerank
     }
 
     /// Default max bytes budget for this preset.
     pub fn default_max_bytes(&self) -> u64 {
-        match self {
-            Self::Fast => 50_000,
-            Self::Balanced => 100_000,
-            Self::Deep => 200_000,
-            Self::Thorough => 500_000,
-        }
+        self.max_bytes
     }
 
     /// Default minimum score threshold.
     pub fn default_min_score(&self) -> f64 {
-        match self {
-            Self::Fast => 0.05,
-            Self::Balanced => 0.01,
-            Self::Deep => 0.005,
-            Self::Thorough => 0.001,
-        }
+        self.min_score
+    }
+
+    /// Signal weights this preset sets over `[weights]`.
+    pub fn weights(&self) -> &WeightsConfig {
+        &self.weights
     }
 }
 
@@ -69,42 +200,137 @@ impl std::fmt::Display for Preset {
 mod tests {
     use super::*;
 
+    fn builtin(name: &str) -> Preset {
+        Preset::builtin(name).unwrap()
+    }
+
     #[test]
     fn preset_as_str() {
-        assert_eq!(Preset::Fast.as_str(), "fast");
-        assert_eq!(Preset::Balanced.as_str(), "balanced");
-        assert_eq!(Preset::Deep.as_str(), "deep");
-        assert_eq!(Preset::Thorough.as_str(), "thorough");
+        for name in PRESETS {
+            assert_eq!(builtin(name).as_str(), *name);
+        }
+        assert!(Preset::builtin("fastest").is_none());
+    }
+
+    #[test]
+    fn preset_resolve_prefers_flag_then_config() {
+        let config = Config::parse("[defaults]\npreset = \"deep\"\n").unwrap();
+        assert_eq!(
+            Preset::resolve(Some("fast"), &config).unwrap(),
+            builtin("fast")
+        );
+        assert_eq!(Preset::resolve(None, &config).unwrap(), builtin("deep"));
+        assert_eq!(
+            Preset::resolve(None, &Config::default()).unwrap(),
+            builtin("balanced")
+        );
+        let err = Preset::resolve(Some("fastest"), &config).unwrap_err();
+        assert!(err.to_string().contains("fast, balanced, deep, thorough"));
     }
 
     #[test]
     fn preset_needs_deep_index() {
-        assert!(!Preset::Fast.needs_deep_index());
-        assert!(Preset::Balanced.needs_deep_index());
-        assert!(Preset::Deep.needs_deep_index());
-        assert!(Preset::Thorough.needs_deep_index());
+        assert!(!builtin("fast").needs_deep_index());
+        assert!(builtin("balanced").needs_deep_index());
+        assert!(builtin("deep").needs_deep_index());
+        assert!(builtin("thorough").needs_deep_index());
     }
 
     #[test]
     fn preset_force_rebuild() {
-        assert!(!Preset::Fast.force_rebuild());
-        assert!(!Preset::Balanced.force_rebuild());
-        assert!(Preset::Deep.force_rebuild());
-        assert!(Preset::Thorough.force_rebuild());
+        assert!(!builtin("fast").force_rebuild());
+        assert!(!builtin("balanced").force_rebuild());
+        assert!(builtin("deep").force_rebuild());
+        assert!(builtin("thorough").force_rebuild());
     }
 
     #[test]
     fn preset_structural_signals() {
-        assert!(!Preset::Fast.use_structural_signals());
-        assert!(!Preset::Balanced.use_structural_signals());
-        assert!(Preset::Deep.use_structural_signals());
-        assert!(Preset::Thorough.use_structural_signals());
+        assert!(!builtin("fast").use_structural_signals());
+        assert!(!builtin("balanced").use_structural_signals());
+        assert!(builtin("deep").use_structural_signals());
+        assert!(builtin("thorough").use_structural_signals());
+    }
+
+    #[test]
+    fn preset_chunk_embeddings() {
+        assert!(!builtin("fast").chunk_embeddings());
+        assert!(!builtin("balanced").chunk_embeddings());
+        assert!(!builtin("deep").chunk_embeddings());
+        assert!(builtin("thorough").chunk_embeddings());
+    }
+
+    #[test]
+    fn preset_rerank() {
+        assert!(!builtin("deep").rerank());
+        assert!(builtin("thorough").rerank());
+        let config = Config::parse("[presets.review]\nbase = \"deep\"\nrerank = true\n").unwrap();
+        assert!(Preset::resolve(Some("review"), &config).unwrap().rerank());
     }
 
     #[test]
     fn preset_budgets_increase() {
-        assert!(Preset::Fast.default_max_bytes() < Preset::Balanced.default_max_bytes());
-        assert!(Preset::Balanced.default_max_bytes() < Preset::Deep.default_max_bytes());
-        assert!(Preset::Deep.default_max_bytes() < Preset::Thorough.default_max_bytes());
+        assert!(builtin("fast").default_max_bytes() < builtin("balanced").default_max_bytes());
+        assert!(builtin("balanced").default_max_bytes() < builtin("deep").default_max_bytes());
+        assert!(builtin("deep").default_max_bytes() < builtin("thorough").default_max_bytes());
+    }
+
+    #[test]
+    fn custom_presets_layer_over_their_base() {
+        let config = Config::parse(
+            "[presets.review]\nbase = \"deep\"\ndepth = \"deep\"\nchunks = false\nmax_bytes = 300000\n\n\
+             [presets.fast]\nmin_score = 0.1\n\n[presets.scratch]\nstructural = true\n",
+        )
+        .unwrap();
+
+        let review = Preset::resolve(Some("review"), &config).unwrap();
+        assert_eq!(review.as_str(), "review");
+        assert!(review.needs_deep_index());
+        assert!(!review.force_rebuild());
+        assert!(review.use_structural_signals());
+        assert!(!review.chunks());
+        assert!(!review.rerank());
+        assert_eq!(review.default_max_bytes(), 300_000);
+        assert_eq!(
+            review.default_min_score(),
+            builtin("deep").default_min_score()
+        );
+
+        // Redefining a built-in starts from that built-in
+        let fast = Preset::resolve(Some("fast"), &config).unwrap();
+        assert!(!fast.needs_deep_index());
+        assert_eq!(fast.default_min_score(), 0.1);
+
+        // Without a base, custom presets start from balanced
+        let scratch = Preset::resolve(Some("scratch"), &config).unwrap();
+        assert!(!scratch.force_rebuild());
+        assert!(scratch.use_structural_signals());
+        assert_eq!(scratch.default_max_bytes(), 100_000);
+
+        assert_eq!(
+            Preset::names(&config),
+            ["fast", "balanced", "deep", "thorough", "review", "scratch"]
+        );
+    }
+
+    #[test]
+    fn custom_presets_tune_weights() {
+        let config = Config::parse(
+            "[weights]\npagerank = 0.5\ngit_recency = 1.0\n\n             [presets.review]\nbase = \"deep\"\n\n[presets.review.weights]\npagerank = 2.0\nrrf_k = 30\n",
+        )
+        .unwrap();
+        let review = Preset::resolve(Some("review"), &config).unwrap();
+        assert_eq!(review.weights().pagerank, Some(2.0));
+        assert!(builtin("deep").weights().rrf_k.is_none());
+
+        // Preset weights sit between [weights] and --weights
+        let overrides = WeightsConfig::parse("rrf_k=10").unwrap();
+        let weights = crate::commands::query::Scoring::from_config(&config)
+            .weights(&config, &review, Some(&overrides))
+            .unwrap()
+            .weights;
+        assert_eq!(weights.pagerank, 2.0);
+        assert_eq!(weights.git_recency, 1.0);
+        assert_eq!(weights.rrf_k, 10.0);
     }
 }
@@ -0,0 +1,106 @@
+//! Cross-encoder reranking for `query` and `quick`: the top of the fused
+//! ranking is re-scored by the `[rerank]` model before the budget is applied.
+
+use crate::error::CliError;
+use anyhow::{Result, bail};
+use std::fs;
+use std::path::Path;
+use topo_core::{RerankConfig, ScoredFile};
+use topo_render::ContentPolicy;
+use topo_score::Tokenizer;
+
+/// Leading bytes of a file the reranker reads, after its path.
+/// Cross-encoders see about 512 tokens, so more would be cut anyway.
+const RERANK_BYTES: usize = 2 * 1024;
+
+/// Re-order the top of `scored` by the `[rerank]` model's relevance to
+/// `task`. `requested` is `--rerank`: without a provider it is an error,
+/// while a preset that turns reranking on quietly goes without. Files
+/// `policy` withholds are not sent and keep their place.
+pub fn apply(
+    root: &Path,
+    task: &str,
+    config: &RerankConfig,
+    policy: &ContentPolicy,
+    requested: bool,
+    scored: &mut [ScoredFile],
+) -> Result<()> {
+    let Some(reranker) = topo_score::reranker_from_config(config, root)? else {
+        if requested {
+            bail!(
+                CliError::config("--rerank needs a rerank provider")
+                    .hint("Set `provider` under [rerank] in .topo/config.toml.")
+            );
+        }
+        return Ok(());
+    };
+    let candidates = config
+        .candidates
+        .unwrap_or(topo_score::DEFAULT_CANDIDATES)
+        .min(scored.len());
+    if candidates == 0 {
+        return Ok(());
+    }
+    log::info!(
+        "Reranking the top {candidates} files with {}...",
+        reranker.model()
+    );
+    let (task, _) = Tokenizer::split_negated(task);
+    topo_score::rerank(reranker.as_ref(), &task, scored, candidates, |f| {
+        document(root, &f.path, policy)
+    })
+}
+
+/// What the reranker reads for a file: its path, then its first
+/// [`RERANK_BYTES`] bytes, redacted. `None` when `policy` withholds the file.
+fn document(root: &Path, path: &str, policy: &ContentPolicy) -> Option<String> {
+    let raw = fs::read_to_string(root.join(path)).unwrap_or_default();
+    let content = policy.embed(path, raw.len() as u64, &raw).ok()?.text;
+    let mut end = content.len().min(RERANK_BYTES);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(format!("{path}\n{}", &content[..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requested_without_provider_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let none = RerankConfig::default();
+        let policy = ContentPolicy::default();
+        assert!(apply(dir.path(), "auth", &none, &policy, false, &mut []).is_ok());
+        let err = apply(dir.path(), "auth", &none, &policy, true, &mut []).unwrap_err();
+        assert!(err.to_string().contains("rerank provider"));
+    }
+
+    #[test]
+    fn document_is_path_and_head() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), "é".repeat(RERANK_BYTES)).unwrap();
+        let doc = document(dir.path(), "a.rs", &ContentPolicy::default()).unwrap();
+        assert!(doc.starts_with("a.rs\né"));
+        assert!(doc.len() <= "a.rs\n".len() + RERANK_BYTES);
+    }
+
+    #[test]
+    fn document_follows_the_content_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = format!("AKIA{}", "ABCDEFGHIJKLMNOP");
+        fs::write(dir.path().join("a.rs"), format!("let key = \"{key}\";\n")).unwrap();
+        fs::write(dir.path().join("b.env"), "TOKEN=1\n").unwrap();
+        let policy = ContentPolicy::from_config(&topo_core::ContentConfig {
+            deny: vec!["*.env".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let doc = document(dir.path(), "a.rs", &policy).unwrap();
+        assert!(!doc.contains(&key));
+        assert!(doc.contains("[REDACTED:aws-access-key]"));
+        assert_eq!(document(dir.path(), "b.env", &policy), None);
+    }
+}
@@ -0,0 +1,617 @@
+//! Embedding-based retrieval for `query` and `quick`: how the semantic
+//! ranking is combined with the lexical one, and how it is computed.
+
+use crate::error::CliError;
+use anyhow::{Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use topo_core::{Chunk, ChunkKind, EmbeddingsConfig, FileInfo, ScoredFile};
+use topo_index::{ContentKey, EmbeddingCache};
+use topo_render::ContentPolicy;
+use topo_score::{Embedder, InputKind, RrfFusion, Tokenizer};
+
+/// Semantic weight when neither the flag nor the config sets one.
+const DEFAULT_SEMANTIC_WEIGHT: f64 = 1.0;
+/// Leading bytes of a file that are embedded, after its path.
+const EMBED_BYTES: usize = 8 * 1024;
+/// Most chunks embedded per file; the rest of a long file goes unembedded.
+const MAX_CHUNKS_PER_FILE: usize = 32;
+/// Nearest neighbors taken from the HNSW index per query.
+const SEMANTIC_CANDIDATES: usize = 500;
+
+/// How the lexical and semantic rankings are combined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retrieval {
+    /// BM25F, heuristics and structural signals only.
+    Lexical,
+    /// The semantic ranking fused into the lexical one with RRF at this weight.
+    Hybrid { semantic_weight: f64 },
+    /// Embedding similarity alone.
+    Semantic,
+}
+
+impl Retrieval {
+    /// Resolve `--semantic-weight`/`--semantic-only` against the
+    /// `[embeddings]` config. Without a provider, retrieval is lexical, and
+    /// asking for semantic retrieval explicitly is an error.
+    pub fn resolve(
+        semantic_weight: Option<f64>,
+        semantic_only: bool,
+        config: &EmbeddingsConfig,
+    ) -> Result<Self> {
+        if let Some(w) = semantic_weight
+            && (w.is_nan() || w < 0.0)
+        {
+            bail!(CliError::usage(format!(
+                "--semantic-weight must not be negative, got {w}"
+            )));
+        }
+        if config.provider.is_none() {
+            if semantic_only || semantic_weight.is_some_and(|w| w > 0.0) {
+                bail!(
+                    CliError::config("semantic retrieval needs an embedding provider")
+                        .hint("Set `provider` under [embeddings] in .topo/config.toml.")
+                );
+            }
+            return Ok(Self::Lexical);
+        }
+        if semantic_only || (semantic_weight.is_none() && config.semantic_only) {
+            return Ok(Self::Semantic);
+        }
+        let weight = semantic_weight
+            .or(config.semantic_weight)
+            .unwrap_or(DEFAULT_SEMANTIC_WEIGHT);
+        if weight == 0.0 {
+            Ok(Self::Lexical)
+        } else {
+            Ok(Self::Hybrid {
+                semantic_weight: weight,
+            })
+        }
+    }
+}
+
+/// `config` with the provider `--embedder` names. The model, endpoint, key
+/// and vector settings configured for another provider don't carry over, so
+/// switching providers starts from the new one's defaults.
+pub fn with_embedder(config: &EmbeddingsConfig, embedder: Option<&str>) -> EmbeddingsConfig {
+    match embedder {
+        Some(name) if config.provider.as_deref() != Some(name) => EmbeddingsConfig {
+            provider: Some(name.to_string()),
+            model: None,
+            api_key_env: None,
+            endpoint: None,
+            dimensions: None,
+            model_path: None,
+            tokenizer_path: None,
+            ..config.clone()
+        },
+        _ => config.clone(),
+    }
+}
+
+/// Re-rank the lexically `scored` files according to `retrieval`, filling in
+/// `signals.embedding` for files the semantic search returned.
+///
+/// With `chunks`, files are embedded chunk by chunk; see [`semantic_scores`].
+#[allow(clippy::too_many_arguments)]
+pub fn apply(
+    root: &Path,
+    files: &[FileInfo],
+    scanned: &[FileInfo],
+    chunks: Option<&HashMap<String, Vec<Chunk>>>,
+    task: &str,
+    retrieval: Retrieval,
+    config: &EmbeddingsConfig,
+    policy: &ContentPolicy,
+    scored: &mut [ScoredFile],
+) -> Result<()> {
+    if retrieval == Retrieval::Lexical {
+        return Ok(());
+    }
+    let Some(embedder) = topo_score::embedder_from_config(config, root)? else {
+        return Ok(());
+    };
+    let similarities = semantic_scores(
+        root,
+        files,
+        scanned,
+        chunks,
+        task,
+        embedder.as_ref(),
+        policy,
+    )?;
+    let by_path: HashMap<&str, f64> = similarities.iter().map(|(p, s)| (p.as_str(), *s)).collect();
+    for file in scored.iter_mut() {
+        file.signals.embedding = by_path.get(file.path.as_str()).copied();
+    }
+
+    match retrieval {
+        Retrieval::Lexical => {}
+        Retrieval::Hybrid { semantic_weight } => {
+            let ranking: Vec<&str> = similarities.iter().map(|(p, _)| p.as_str()).collect();
+            RrfFusion::new().fuse_scored_weighted(scored, &[(&ranking, semantic_weight)]);
+        }
+        Retrieval::Semantic => {
+            for file in scored.iter_mut() {
+                file.score = file.signals.embedding.unwrap_or(0.0).max(0.0);
+            }
+            scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+        }
+    }
+    Ok(())
+}
+
+/// Cosine similarity between the task and the nearest files, best first.
+///
+/// Each file is embedded as its path plus its first [`EMBED_BYTES`] bytes.
+/// With `chunks` from the deep index, each function, type and other
+/// non-import chunk of a file is embedded instead, up to the next chunk,
+/// and the file scores as its best chunk; files without chunks still
+/// embed their head. Vectors come from the `.topo/embeddings` cache (chunk
+/// vectors from `.topo/embeddings/chunks`), so only new or changed code is
+/// sent to the embedder; candidates come from the HNSW index.
+///
+/// Content goes through `policy` first: files it withholds are not
+/// embedded, and the rest are sent redacted. Only `files` are embedded,
+/// but the cache is pruned against everything `scanned`, so a filtered
+/// query keeps the vectors of the files it left out.
+pub fn semantic_scores(
+    root: &Path,
+    files: &[FileInfo],
+    scanned: &[FileInfo],
+    chunks: Option<&HashMap<String, Vec<Chunk>>>,
+    task: &str,
+    embedder: &dyn Embedder,
+    policy: &ContentPolicy,
+) -> Result<Vec<(String, f64)>> {
+    let texts = embed_inputs(root, files, chunks, policy);
+
+    let mut cache = if chunks.is_some() {
+        EmbeddingCache::open_chunks(root, embedder.model(), embedder.dims())?
+    } else {
+        EmbeddingCache::open(root, embedder.model(), embedder.dims())?
+    };
+    let keys: Vec<ContentKey> = texts
+        .iter()
+        .map(|(_, t)| topo_index::content_key(t))
+        .collect();
+    let missing = keys.iter().filter(|k| cache.get(k).is_none()).count();
+    if missing > 0 {
+        let unit = if chunks.is_some() { "chunks" } else { "files" };
+        log::info!("Embedding {missing} {unit} with {}...", embedder.model());
+    }
+    let inputs: Vec<&str> = texts.iter().map(|(_, t)| t.as_str()).collect();
+    topo_index::embed_cached(&mut cache, embedder, &inputs)?;
+    // Vectors of files the query left out stay cached for the next one
+    let mut live: HashSet<ContentKey> = keys.iter().copied().collect();
+    if scanned.len() > files.len() {
+        let selected: HashSet<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        let rest: Vec<FileInfo> = scanned
+            .iter()
+            .filter(|f| !selected.contains(f.path.as_str()))
+            .cloned()
+            .collect();
+        live.extend(
+            embed_inputs(root, &rest, chunks, policy)
+                .iter()
+                .map(|(_, t)| topo_index::content_key(t)),
+        );
+    }
+    if let Err(e) = cache.save(|k| live.contains(k)) {
+        log::warn!("could not save embedding cache: {e}");
+    }
+
+    let mut paths_by_key: HashMap<ContentKey, Vec<&str>> = HashMap::new();
+    for (key, (path, _)) in keys.iter().zip(&texts) {
+        let paths = paths_by_key.entry(*key).or_default();
+        if !paths.contains(path) {
+            paths.push(path);
+        }
+    }
+
+    let index = topo_index::load_or_build_ann(&cache);
+    // Negated words would pull the query toward what it excludes
+    let (text, _) = Tokenizer::split_negated(task);
+    let Some(query) = embedder.embed(&[text.as_str()], InputKind::Query)?.pop() else {
+        return Ok(Vec::new());
+    };
+    let hits = index.search(&query, SEMANTIC_CANDIDATES, SEMANTIC_CANDIDATES);
+    // Hits come best first, so a file's first hit is its best chunk
+    let mut seen = HashSet::new();
+    Ok(hits
+        .into_iter()
+        .flat_map(|(key, sim)| {
+            paths_by_key
+                .get(&key)
+                .into_iter()
+                .flatten()
+                .map(move |p| (*p, sim as f64))
+        })
+        .filter(|(p, _)| seen.insert(*p))
+        .map(|(p, sim)| (p.to_string(), sim))
+        .collect())
+}
+
+/// What is embedded for each of `files`, paired with its path: the file's
+/// chunks when `chunks` has any, otherwise its head. Files `policy`
+/// withholds are skipped and the rest are redacted.
+fn embed_inputs<'a>(
+    root: &Path,
+    files: &'a [FileInfo],
+    chunks: Option<&HashMap<String, Vec<Chunk>>>,
+    policy: &ContentPolicy,
+) -> Vec<(&'a str, String)> {
+    let mut texts = Vec::new();
+    for f in files {
+        let Ok(raw) = fs::read_to_string(root.join(&f.path)) else {
+            continue;
+        };
+        let Ok(embedded) = policy.embed(&f.path, f.size, &raw) else {
+            continue;
+        };
+        let content = embedded.text;
+        if content.trim().is_empty() {
+            continue;
+        }
+        let windows = chunks
+            .and_then(|c| c.get(&f.path))
+            .map(|c| chunk_texts(&f.path, &content, c))
+            .unwrap_or_default();
+        if windows.is_empty() {
+            texts.push((f.path.as_str(), format!("{}\n{}", f.path, head(&content))));
+        } else {
+            texts.extend(windows.into_iter().map(|t| (f.path.as_str(), t)));
+        }
+    }
+    texts
+}
+
+/// The first [`EMBED_BYTES`] of `text`, cut at a character boundary.
+fn head(text: &str) -> &str {
+    let mut end = text.len().min(EMBED_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Embedding inputs for the non-import chunks of one file: the path and
+/// chunk name, then the lines from the chunk's start up to the next chunk.
+/// Index chunks may carry only a start line, so the window is taken from
+/// `content` rather than the chunk.
+fn chunk_texts(path: &str, content: &str, chunks: &[Chunk]) -> Vec<String> {
+    let mut starts: Vec<&Chunk> = chunks
+        .iter()
+        .filter(|c| c.kind != ChunkKind::Import)
+        .collect();
+    starts.sort_by_key(|c| c.start_line);
+    starts.dedup_by_key(|c| c.start_line);
+    let lines: Vec<&str> = content.lines().collect();
+    starts
+        .iter()
+        .enumerate()
+        .take(MAX_CHUNKS_PER_FILE)
+        .filter_map(|(i, chunk)| {
+            let from = (chunk.start_line as usize).saturating_sub(1);
+            let to = starts
+                .get(i + 1)
+                .map_or(lines.len(), |next| next.start_line as usize - 1)
+                .min(lines.len());
+            let body = lines.get(from..to)?.join("\n");
+            (!body.trim().is_empty()).then(|| format!("{path}:{}\n{}", chunk.name, head(&body)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configured() -> EmbeddingsConfig {
+        EmbeddingsConfig {
+            provider: Some("openai".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lexical_without_provider() {
+        let none = EmbeddingsConfig::default();
+        assert_eq!(
+            Retrieval::resolve(None, false, &none).unwrap(),
+            Retrieval::Lexical
+        );
+        assert_eq!(
+            Retrieval::resolve(Some(0.0), false, &none).unwrap(),
+            Retrieval::Lexical
+        );
+        assert!(Retrieval::resolve(None, true, &none).is_err());
+        assert!(Retrieval::resolve(Some(2.0), false, &none).is_err());
+    }
+
+    #[test]
+    fn flags_override_config() {
+        let mut config = configured();
+        assert_eq!(
+            Retrieval::resolve(None, false, &config).unwrap(),
+            Retrieval::Hybrid {
+                semantic_weight: 1.0
+            }
+        );
+        config.semantic_weight = Some(0.5);
+        config.semantic_only = true;
+        assert_eq!(
+            Retrieval::resolve(None, false, &config).unwrap(),
+            Retrieval::Semantic
+        );
+        assert_eq!(
+            Retrieval::resolve(Some(2.0), false, &config).unwrap(),
+            Retrieval::Hybrid {
+                semantic_weight: 2.0
+            }
+        );
+        assert_eq!(
+            Retrieval::resolve(Some(0.0), false, &config).unwrap(),
+            Retrieval::Lexical
+        );
+        assert!(Retrieval::resolve(Some(-1.0), false, &config).is_err());
+    }
+
+    #[test]
+    fn embedder_flag_switches_provider() {
+        let mut config = configured();
+        config.model = Some("text-embedding-3-large".to_string());
+        config.batch_size = Some(16);
+        assert_eq!(with_embedder(&config, None), config);
+        assert_eq!(with_embedder(&config, Some("openai")), config);
+
+        let voyage = with_embedder(&config, Some("voyage"));
+        assert_eq!(voyage.provider.as_deref(), Some("voyage"));
+        assert_eq!(voyage.model, None);
+        assert_eq!(voyage.batch_size, Some(16));
+
+        let flagged = with_embedder(&EmbeddingsConfig::default(), Some("openai"));
+        assert!(matches!(
+            Retrieval::resolve(None, false, &flagged).unwrap(),
+            Retrieval::Hybrid { .. }
+        ));
+    }
+
+    /// Embeds text by counting the letters `a` and `b`.
+    struct LetterEmbedder;
+
+    impl Embedder for LetterEmbedder {
+        fn model(&self) -> &str {
+            "letters"
+        }
+        fn dims(&self) -> usize {
+            2
+        }
+        fn embed(&self, texts: &[&str], _: InputKind) -> anyhow::Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|t| {
+                    vec![
+                        t.matches('a').count() as f32 + 0.1,
+                        t.matches('b').count() as f32 + 0.1,
+                    ]
+                })
+                .collect())
+        }
+    }
+
+    fn text_files(paths: &[&str]) -> Vec<FileInfo> {
+        paths
+            .iter()
+            .map(|p| FileInfo {
+                path: p.to_string(),
+                size: 10,
+                language: topo_core::Language::Other,
+                role: topo_core::FileRole::Other,
+                sha256: [0; 32],
+                lines: 1,
+                executable: false,
+                shebang: None,
+                entry_point: false,
+                license: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn semantic_scores_rank_by_similarity_and_cache_vectors() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("x.txt"), "aaaaaaaaaa").unwrap();
+        fs::write(dir.path().join("y.txt"), "bbbbbbbbbb").unwrap();
+        let files = text_files(&["x.txt", "y.txt"]);
+
+        let scores = semantic_scores(
+            dir.path(),
+            &files,
+            &files,
+            None,
+            "bbb",
+            &LetterEmbedder,
+            &ContentPolicy::default(),
+        )
+        .unwrap();
+        assert_eq!(scores[0].0, "y.txt");
+        assert!(scores[0].1 > scores[1].1);
+        assert_eq!(
+            EmbeddingCache::open(dir.path(), "letters", 2)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn filtered_query_keeps_vectors_of_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("x.txt"), "aaaaaaaaaa").unwrap();
+        fs::write(dir.path().join("y.txt"), "bbbbbbbbbb").unwrap();
+        let scanned = text_files(&["x.txt", "y.txt"]);
+        let policy = ContentPolicy::default();
+        let cached = || {
+            EmbeddingCache::open(dir.path(), "letters", 2)
+                .unwrap()
+                .len()
+        };
+
+        semantic_scores(
+            dir.path(),
+            &scanned,
+            &scanned,
+            None,
+            "a",
+            &LetterEmbedder,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(cached(), 2);
+        let only_x = &scanned[..1];
+        semantic_scores(
+            dir.path(),
+            only_x,
+            &scanned,
+            None,
+            "a",
+            &LetterEmbedder,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(cached(), 2);
+
+        // A file that is gone from the scan is pruned
+        semantic_scores(
+            dir.path(),
+            only_x,
+            only_x,
+            None,
+            "a",
+            &LetterEmbedder,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(cached(), 1);
+    }
+
+    #[test]
+    fn withheld_files_are_not_embedded() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("x.txt"), "aaaaaaaaaa").unwrap();
+        fs::write(dir.path().join("secret.env"), "bbbbbbbbbb").unwrap();
+        let files = text_files(&["x.txt", "secret.env"]);
+        let policy = ContentPolicy::from_config(&topo_core::ContentConfig {
+            deny: vec!["*.env".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let scores = semantic_scores(
+            dir.path(),
+            &files,
+            &files,
+            None,
+            "bbb",
+            &LetterEmbedder,
+            &policy,
+        )
+        .unwrap();
+        let paths: Vec<&str> = scores.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, ["x.txt"]);
+    }
+
+    #[test]
+    fn chunk_scores_take_each_files_best_chunk() {
+        let dir = tempfile::tempdir().unwrap();
+        // The matching function is buried under a long unrelated one
+        let long = format!(
+            "fn alpha() {{\n{}\n}}\nfn beta() {{ bbb }}\n",
+            "a".repeat(40)
+        );
+        fs::write(dir.path().join("x.rs"), &long).unwrap();
+        fs::write(dir.path().join("y.rs"), "ababababab").unwrap();
+        let files = text_files(&["x.rs", "y.rs"]);
+        let chunk = |name: &str, line: u32| Chunk {
+            kind: ChunkKind::Function,
+            name: name.to_string(),
+            start_line: line,
+            end_line: line,
+            content: String::new(),
+        };
+        let chunks = HashMap::from([(
+            "x.rs".to_string(),
+            vec![chunk("alpha", 1), chunk("beta", 4)],
+        )]);
+
+        let whole = semantic_scores(
+            dir.path(),
+            &files,
+            &files,
+            None,
+            "bbb",
+            &LetterEmbedder,
+            &ContentPolicy::default(),
+        )
+        .unwrap();
+        assert_eq!(whole[0].0, "y.rs");
+
+        let scores = semantic_scores(
+            dir.path(),
+            &files,
+            &files,
+            Some(&chunks),
+            "bbb",
+            &LetterEmbedder,
+            &ContentPolicy::default(),
+        )
+        .unwrap();
+        let paths: Vec<&str> = scores.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, ["x.rs", "y.rs"]);
+        // Two chunk vectors for x.rs, one head vector for y.rs
+        assert_eq!(
+            EmbeddingCache::open_chunks(dir.path(), "letters", 2)
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn chunk_texts_span_to_the_next_chunk() {
+        let content = "use a;\nfn one() {\n    1\n}\n\nfn two() {}\n";
+        let chunks = vec![
+            Chunk {
+                kind: ChunkKind::Import,
+                name: "use".to_string(),
+                start_line: 1,
+                end_line: 1,
+                content: String::new(),
+            },
+            Chunk {
+                kind: ChunkKind::Function,
+                name: "one".to_string(),
+                start_line: 2,
+                end_line: 4,
+                content: String::new(),
+            },
+            Chunk {
+                kind: ChunkKind::Function,
+                name: "two".to_string(),
+                start_line: 6,
+                end_line: 6,
+                content: String::new(),
+            },
+        ];
+        assert_eq!(
+            chunk_texts("lib.rs", content, &chunks),
+            [
+                "lib.rs:one\nfn one() {\n    1\n}\n",
+                "lib.rs:two\nfn two() {}"
+            ]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Usage events in `.topo/stats.jsonl`, appended by the hooks and by
+//! `query`/`quick` under `TOPO_STATS`, and summed up by `topo gain`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File name of the stats log inside `.topo/`.
+const STATS_FILE: &str = "stats.jsonl";
+
+/// Schema version written by this build. Lines without `v` are version 1,
+/// which only knew the file and token counts of a query.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Environment variable that makes `query` and `quick` log a `topo_query`
+/// event. The generated hooks set it; `0` or empty leaves logging off.
+pub const STATS_ENV: &str = "TOPO_STATS";
+
+/// One line of `.topo/stats.jsonl`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsEvent {
+    #[serde(default = "v1")]
+    pub v: u32,
+    pub timestamp: String,
+    /// `session_start`, `topo_query` or `file_read`.
+    pub event: String,
+    /// File opened (`file_read`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files_suggested: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_suggested: Option<u64>,
+    /// Wall time of the query, scan and indexing included (v2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// Tokens the budget allowed: the smaller of the token limit and the
+    /// byte limit in tokens (v2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_tokens: Option<u64>,
+    /// Whether the budget dropped ranked files (v2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<bool>,
+}
+
+fn v1() -> u32 {
+    1
+}
+
+impl StatsEvent {
+    /// A current-schema event of kind `event`, stamped now.
+    pub fn now(event: &str) -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            v: SCHEMA_VERSION,
+            timestamp: utc_timestamp(secs),
+            event: event.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Whether `TOPO_STATS` asks for query events.
+pub fn enabled() -> bool {
+    std::env::var(STATS_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Path to the stats log.
+pub fn path(root: &Path) -> PathBuf {
+    root.join(".topo").join(STATS_FILE)
+}
+
+/// Append `event` to the stats log.
+pub fn append(root: &Path, event: &StatsEvent) -> Result<()> {
+    let path = path(root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Every well-formed event in the stats log, oldest first. Returns `None`
+/// if nothing has been logged yet; malformed lines are skipped.
+pub fn load(root: &Path) -> Result<Option<Vec<StatsEvent>>> {
+    let path = path(root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let reader = std::io::BufReader::new(fs::File::open(&path)?);
+    let mut events = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(event) = serde_json::from_str(&line) {
+            events.push(event);
+        }
+    }
+    Ok(Some(events))
+}
+
+/// `secs` since the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`, the format the
+/// hooks write with `date -u`.
+pub(crate) fn utc_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_match_date_u() {
+        assert_eq!(utc_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(utc_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(utc_timestamp(1_735_689_599), "2024-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn v1_lines_still_parse() {
+        let json = r#"{"timestamp":"2025-01-01T00:00:00Z","event":"topo_query","files_suggested":10,"tokens_suggested":5000}"#;
+        let event: StatsEvent = serde_json::from_str(json).unwrap();
+        assert_eq!(event.v, 1);
+        assert_eq!(event.files_suggested, Some(10));
+        assert_eq!(event.latency_ms, None);
+    }
+
+    #[test]
+    fn append_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load(dir.path()).unwrap().is_none());
+
+        let mut event = StatsEvent::now("topo_query");
+        event.latency_ms = Some(120);
+        event.preset = Some("fast".to_string());
+        event.truncated = Some(true);
+        append(dir.path(), &event).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(path(dir.path()))
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+        append(dir.path(), &StatsEvent::now("session_start")).unwrap();
+
+        let events = load(dir.path()).unwrap().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], event);
+        assert_eq!(events[0].v, SCHEMA_VERSION);
+        assert_eq!(events[1].event, "session_start");
+    }
+}
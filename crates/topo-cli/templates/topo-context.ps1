@@ -0,0 +1,27 @@
+# topo-context.ps1 — UserPromptSubmit hook for Claude Code (Windows)
+# Automatically injects relevant file suggestions when a prompt is submitted.
+# Generated by `topo init`. Safe to customise — re-run `topo init --force` to reset.
+
+$ErrorActionPreference = 'Stop'
+
+$HookInput = [Console]::In.ReadToEnd() | ConvertFrom-Json
+$Prompt = $HookInput.prompt
+
+# Skip short/meta prompts (commands, single words, empty)
+if (-not $Prompt -or $Prompt.Length -lt 15) {
+  exit 0
+}
+
+# Run topo quick with fast preset for low latency; TOPO_STATS logs the query
+# (latency, budget use) to .topo/stats.jsonl for `topo gain`
+$env:TOPO_STATS = '1'
+$Results = try { (& topo quick $Prompt --format compact --preset fast --top 10 2>$null) -join "`n" } catch { '' }
+
+if ($Results) {
+  @{
+    hookSpecificOutput = @{
+      hookEventName = 'UserPromptSubmit'
+      additionalContext = "Topo file suggestions (ranked by relevance):`n$Results"
+    }
+  } | ConvertTo-Json -Compress
+}
@@ -13,8 +13,9 @@ if [ -z "$PROMPT" ] || [ ${#PROMPT} -lt 15 ]; then
   exit 0
 fi
 
-# Run topo quick with fast preset for low latency
-RESULTS=$(topo quick "$PROMPT" --format compact --preset fast --top 10 2>/dev/null) || true
+# Run topo quick with fast preset for low latency; TOPO_STATS logs the query
+# (latency, budget use) to .topo/stats.jsonl for `topo gain`
+RESULTS=$(TOPO_STATS=1 topo quick "$PROMPT" --format compact --preset fast --top 10 2>/dev/null) || true
 
 if [ -n "$RESULTS" ]; then
   jq -n --arg ctx "Topo file suggestions (ranked by relevance):
@@ -0,0 +1,31 @@
+# topo-hint.ps1 — PreToolUse hook for Claude Code (Glob/Grep, Windows)
+# Injects lightweight file hints when Claude searches for files.
+# Generated by `topo init`. Safe to customise — re-run `topo init --force` to reset.
+
+$ErrorActionPreference = 'Stop'
+
+$HookInput = [Console]::In.ReadToEnd() | ConvertFrom-Json
+
+# Extract the search query from the tool input
+$Query = ''
+if ($HookInput.tool_name -in 'Glob', 'Grep') {
+  $Query = $HookInput.tool_input.pattern
+}
+
+# Skip short or empty queries
+if (-not $Query -or $Query.Length -lt 3) {
+  exit 0
+}
+
+# Run topo quick with fast preset, fewer results for hints
+$env:TOPO_STATS = '1'
+$Results = try { (& topo quick $Query --format compact --preset fast --top 5 2>$null) -join "`n" } catch { '' }
+
+if ($Results) {
+  @{
+    hookSpecificOutput = @{
+      hookEventName = 'PreToolUse'
+      additionalContext = "Topo suggests these files may also be relevant:`n$Results"
+    }
+  } | ConvertTo-Json -Compress
+}
@@ -22,7 +22,7 @@ if [ -z "$QUERY" ] || [ ${#QUERY} -lt 3 ]; then
 fi
 
 # Run topo quick with fast preset, fewer results for hints
-RESULTS=$(topo quick "$QUERY" --format compact --preset fast --top 5 2>/dev/null) || true
+RESULTS=$(TOPO_STATS=1 topo quick "$QUERY" --format compact --preset fast --top 5 2>/dev/null) || true
 
 if [ -n "$RESULTS" ]; then
   jq -n --arg ctx "Topo suggests these files may also be relevant:
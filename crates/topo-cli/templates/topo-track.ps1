@@ -0,0 +1,33 @@
+# topo-track.ps1 — PostToolUse hook for Claude Code (Read, Windows)
+# Tracks which files Claude actually opens for `topo gain` analytics.
+# Generated by `topo init`. Safe to customise — re-run `topo init --force` to reset.
+
+$ErrorActionPreference = 'Stop'
+
+$HookInput = [Console]::In.ReadToEnd() | ConvertFrom-Json
+
+# Only track Read tool usage
+if ($HookInput.tool_name -ne 'Read') {
+  exit 0
+}
+
+$FilePath = $HookInput.tool_input.file_path
+
+if (-not $FilePath) {
+  exit 0
+}
+
+# Append to stats log
+$Root = if ($env:TOPO_ROOT) { $env:TOPO_ROOT } else { '.' }
+$StatsDir = Join-Path $Root '.topo'
+New-Item -ItemType Directory -Force -Path $StatsDir | Out-Null
+
+$Entry = [ordered]@{
+  v = 2
+  timestamp = (Get-Date).ToUniversalTime().ToString('yyyy-MM-ddTHH:mm:ssZ')
+  event = 'file_read'
+  path = $FilePath
+} | ConvertTo-Json -Compress
+
+# AppendAllText writes UTF-8 without a BOM, which would break the first line
+[IO.File]::AppendAllText((Join-Path $StatsDir 'stats.jsonl'), "$Entry`n")
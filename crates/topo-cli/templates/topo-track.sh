@@ -26,5 +26,5 @@ mkdir -p "$STATS_DIR"
 jq -n --arg ts "$(date -u +%Y-%m-%dT%H:%M:%SZ)" \
       --arg event "file_read" \
       --arg path "$FILE_PATH" \
-  '{"timestamp":$ts,"event":$event,"path":$path}' \
+  '{"v":2,"timestamp":$ts,"event":$event,"path":$path}' \
   >> "$STATS_DIR/stats.jsonl"
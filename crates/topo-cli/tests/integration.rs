@@ -72,6 +72,11 @@ fn bundle_to_jsonl_roundtrip() {
             tokens: f.estimated_tokens(),
             language: f.language,
             role: f.role,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
         })
         .collect();
 
@@ -150,6 +155,11 @@ fn make_scored(path: &str, score: f64, tokens: u64, lang: Language, role: FileRo
         tokens,
         language: lang,
         role,
+        root: None,
+        cluster: None,
+        pair: None,
+        chunks: Vec::new(),
+        outline: Vec::new(),
     }
 }
 
@@ -274,6 +284,11 @@ fn budget_enforcement_end_to_end() {
             tokens: f.estimated_tokens(),
             language: f.language,
             role: f.role,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
         })
         .collect();
 
@@ -0,0 +1,918 @@
+use crate::TopoError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Config file location relative to the repository root.
+const CONFIG_FILE: &str = ".topo/config.toml";
+
+/// Project-level settings from `.topo/config.toml`.
+///
+/// Every section is optional; a missing file yields the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub defaults: DefaultsConfig,
+    pub scan: ScanConfig,
+    pub weights: WeightsConfig,
+    pub stop_words: StopWordsConfig,
+    pub python: PythonConfig,
+    pub cpp: CppConfig,
+    pub pagerank: PageRankConfig,
+    pub churn: ChurnConfig,
+    pub vendored: VendoredConfig,
+    pub file_types: FileTypesConfig,
+    pub content: ContentConfig,
+    pub embeddings: EmbeddingsConfig,
+    pub rerank: RerankConfig,
+    pub presets: BTreeMap<String, PresetConfig>,
+}
+
+/// Defaults for `query`, `quick`, `explain` and `test-context`. Flags
+/// override them; unset keys fall back to the preset's values.
+///
+/// ```toml
+/// [defaults]
+/// preset = "deep"        # fast, balanced, deep, thorough or a [presets] name
+/// max_bytes = 200000
+/// max_tokens = 50000
+/// min_score = 0.01
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DefaultsConfig {
+    pub preset: Option<String>,
+    pub max_bytes: Option<u64>,
+    pub max_tokens: Option<u64>,
+    pub min_score: Option<f64>,
+}
+
+/// The built-in presets.
+pub const PRESETS: &[&str] = &["fast", "balanced", "deep", "thorough"];
+
+/// A custom preset, selected with `--preset <name>`. It starts from `base`
+/// and overrides the keys that are set. Naming it after a built-in
+/// redefines that built-in.
+///
+/// ```toml
+/// [presets.review]
+/// base = "deep"          # built-in to start from (default: the same-named built-in, else balanced)
+/// depth = "deep"         # shallow, deep (cached index) or fresh (rebuilt index)
+/// structural = true      # fuse PageRank and git recency into the ranking
+/// chunks = true          # deep-parse selected files and report their best chunks
+/// chunk_embeddings = false  # embed each function and type, not just the file head
+/// rerank = false         # re-score the top candidates with the [rerank] model
+/// max_bytes = 300000
+/// min_score = 0.002
+///
+/// [presets.review.weights]  # laid over [weights] while this preset is in use
+/// pagerank = 2.0
+/// rrf_k = 30
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PresetConfig {
+    pub base: Option<String>,
+    pub depth: Option<String>,
+    pub structural: Option<bool>,
+    pub chunks: Option<bool>,
+    pub chunk_embeddings: Option<bool>,
+    pub rerank: Option<bool>,
+    pub max_bytes: Option<u64>,
+    pub min_score: Option<f64>,
+    pub weights: WeightsConfig,
+}
+
+/// Depths accepted by `[presets.<name>] depth`.
+pub const PRESET_DEPTHS: &[&str] = &["shallow", "deep", "fresh"];
+
+/// Files to leave out of every scan, on top of `.gitignore`.
+///
+/// ```toml
+/// [scan]
+/// ignore = ["vendor/", "**/*.pb.go"]   # gitignore-style globs
+/// skip_dirs = ["third_party"]          # directory names skipped at any depth
+/// keep_dirs = ["venv"]                 # default-skipped names to scan anyway
+/// ```
+///
+/// Skipped directories are pruned before ignore files are read, so a
+/// `!venv/` rule in `.gitignore` doesn't bring one back; keeping one only
+/// lifts the built-in skip, and `.gitignore` still applies.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanConfig {
+    pub ignore: Vec<String>,
+    pub skip_dirs: Vec<String>,
+    pub keep_dirs: Vec<String>,
+}
+
+impl ScanConfig {
+    fn validate(&self) -> Result<(), String> {
+        for (key, names) in [
+            ("skip_dirs", &self.skip_dirs),
+            ("keep_dirs", &self.keep_dirs),
+        ] {
+            if let Some(bad) = names
+                .iter()
+                .find(|n| n.is_empty() || n.contains(['/', '\\']))
+            {
+                return Err(format!(
+                    "scan.{key} takes directory names, not paths, got {bad:?}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Signal weights for ranking. Unset keys keep the defaults.
+///
+/// ```toml
+/// [weights]
+/// bm25f = 0.6       # content relevance; normalized against heuristic
+/// heuristic = 0.4   # path and file-type relevance
+/// pagerank = 1.0    # PageRank ranking's weight in fusion; 0 disables it
+/// git_recency = 0.5 # recent-commit ranking's weight in fusion; 0 disables it
+/// co_change = 0.5   # co-change ranking's weight in fusion; 0 disables it
+/// churn = 0.3       # churn ranking's weight in fusion; 0 disables it
+/// rrf_k = 60        # RRF constant; lower lets the top of each ranking dominate
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeightsConfig {
+    pub bm25f: Option<f64>,
+    pub heuristic: Option<f64>,
+    pub pagerank: Option<f64>,
+    pub git_recency: Option<f64>,
+    pub co_change: Option<f64>,
+    pub churn: Option<f64>,
+    pub rrf_k: Option<f64>,
+}
+
+/// Adjustments to the built-in English stop-word list.
+///
+/// ```toml
+/// [stop_words]
+/// add = ["acme", "internal"]   # domain noise to ignore
+/// remove = ["is", "do"]        # words that carry meaning in this codebase
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StopWordsConfig {
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+}
+
+/// Python import resolution settings.
+///
+/// ```toml
+/// [python]
+/// pythonpath = ["src", "services/api"]   # extra roots for absolute imports
+/// ```
+///
+/// Package roots are also detected from `__init__.py` files; these entries
+/// cover layouts that rely on `PYTHONPATH` or namespace packages.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PythonConfig {
+    pub pythonpath: Vec<String>,
+}
+
+/// C and C++ include resolution settings.
+///
+/// ```toml
+/// [cpp]
+/// include_dirs = ["include", "src"]   # searched like `-I` for quoted includes
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CppConfig {
+    pub include_dirs: Vec<String>,
+}
+
+/// PageRank tuning for the import graph. Unset keys keep the defaults.
+///
+/// ```toml
+/// [pagerank]
+/// damping = 0.85          # probability of following an import, in (0, 1)
+/// epsilon = 1e-6          # stop once no score moves more than this
+/// max_iterations = 100
+/// recompute_churn = 0.01  # keep the last scores until more imports change
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PageRankConfig {
+    pub damping: Option<f64>,
+    pub epsilon: Option<f64>,
+    pub max_iterations: Option<usize>,
+    /// Share of import edges, 0 to 1, that must change between index
+    /// builds before PageRank is recomputed.
+    pub recompute_churn: Option<f64>,
+}
+
+/// Churn settings: how far back commits are counted for the churn signal
+/// and `topo hot`.
+///
+/// ```toml
+/// [churn]
+/// window_days = 365   # count commits from the last N days
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChurnConfig {
+    pub window_days: Option<u32>,
+}
+
+/// How scoring treats vendored (`vendor/`, `node_modules/`,
+/// `third_party/`) and generated files. Under every policy but `include`
+/// they stay out of the corpus statistics, so their terms don't skew IDF.
+///
+/// ```toml
+/// [vendored]
+/// policy = "downweight"   # downweight, exclude or include
+/// multiplier = 0.1        # score multiplier under downweight
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VendoredConfig {
+    pub policy: Option<String>,
+    pub multiplier: Option<f64>,
+}
+
+/// Valid `[vendored] policy` values.
+pub const VENDORED_POLICIES: &[&str] = &["downweight", "exclude", "include"];
+
+/// Score multipliers for file types that code-change tasks rarely want.
+/// Patterns match the file name: `*.lock` by suffix, anything else
+/// exactly. A rule replaces the built-in rule with the same pattern, so a
+/// multiplier of 1 lifts it.
+///
+/// ```toml
+/// [file_types]
+/// defaults = true          # keep the built-in lockfile, snapshot and bundle rules
+///
+/// [file_types.rules]
+/// "*.pb.go" = 0.2
+/// "*.map" = 1.0
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileTypesConfig {
+    pub defaults: bool,
+    pub rules: BTreeMap<String, f64>,
+}
+
+impl Default for FileTypesConfig {
+    fn default() -> Self {
+        Self {
+            defaults: true,
+            rules: BTreeMap::new(),
+        }
+    }
+}
+
+/// Guardrails on file content embedded in output. Files that break a rule
+/// are listed by path only.
+///
+/// ```toml
+/// [content]
+/// max_file_bytes = 262144              # larger files are never embedded
+/// deny = ["**/prod.env", "secrets/"]   # gitignore-style globs never embedded
+/// require_redaction = true             # scan embedded content for secrets
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContentConfig {
+    pub max_file_bytes: Option<u64>,
+    pub deny: Vec<String>,
+    pub require_redaction: bool,
+}
+
+impl Default for ContentConfig {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: None,
+            deny: Vec::new(),
+            require_redaction: true,
+        }
+    }
+}
+
+/// Embedding provider: a hosted API or a local ONNX model. Unset keys use
+/// the provider's defaults.
+///
+/// ```toml
+/// [embeddings]
+/// provider = "voyage"              # openai, voyage, cohere or local
+/// model = "voyage-code-3"
+/// api_key_env = "VOYAGE_API_KEY"   # environment variable holding the key
+/// endpoint = "https://..."         # proxy or self-hosted gateway
+/// dimensions = 1024
+/// batch_size = 64                  # texts per request
+/// max_retries = 3                  # on rate limits, 5xx and network errors
+/// semantic_weight = 1.0            # semantic ranking's weight in fusion; 0 = lexical only
+/// semantic_only = false            # rank by embedding similarity alone
+///
+/// [embeddings]
+/// provider = "local"
+/// model_path = "models/all-MiniLM-L6-v2/model.onnx"   # relative to the repository root
+/// tokenizer_path = "models/all-MiniLM-L6-v2/tokenizer.json"   # default: next to the model
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmbeddingsConfig {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub api_key_env: Option<String>,
+    pub endpoint: Option<String>,
+    pub dimensions: Option<usize>,
+    pub batch_size: Option<usize>,
+    pub max_retries: Option<u32>,
+    pub semantic_weight: Option<f64>,
+    pub semantic_only: bool,
+    /// ONNX model file for the `local` provider.
+    pub model_path: Option<String>,
+    /// `tokenizer.json` for the `local` provider.
+    pub tokenizer_path: Option<String>,
+}
+
+/// Providers accepted by `[embeddings] provider`.
+pub const EMBEDDING_PROVIDERS: &[&str] = &["openai", "voyage", "cohere", "local"];
+
+impl EmbeddingsConfig {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(p) = &self.provider
+            && !EMBEDDING_PROVIDERS.contains(&p.as_str())
+        {
+            return Err(format!(
+                "embeddings.provider must be one of {}, got {p:?}",
+                EMBEDDING_PROVIDERS.join(", ")
+            ));
+        }
+        if self.provider.as_deref() == Some("local") && self.model_path.is_none() {
+            return Err("embeddings.model_path is required for the local provider".to_string());
+        }
+        if self.dimensions == Some(0) {
+            return Err("embeddings.dimensions must be at least 1".to_string());
+        }
+        if self.batch_size == Some(0) {
+            return Err("embeddings.batch_size must be at least 1".to_string());
+        }
+        if let Some(w) = self.semantic_weight
+            && (w.is_nan() || w < 0.0)
+        {
+            return Err(format!(
+                "embeddings.semantic_weight must not be negative, got {w}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Reranker that re-scores the top of the fused ranking, for `--rerank` and
+/// presets with `rerank = true`: a hosted cross-encoder, an LLM
+/// judging relevance, or a local ONNX cross-encoder. Unset keys use the
+/// provider's defaults.
+///
+/// ```toml
+/// [rerank]
+/// provider = "cohere"              # cohere, voyage, openai (LLM) or local
+/// model = "rerank-v3.5"
+/// api_key_env = "COHERE_API_KEY"   # environment variable holding the key
+/// endpoint = "https://..."         # proxy or self-hosted gateway
+/// candidates = 50                  # files re-scored
+/// max_retries = 3                  # on rate limits, 5xx and network errors
+///
+/// [rerank]
+/// provider = "local"
+/// model_path = "models/ms-marco-MiniLM-L-6-v2/model.onnx"   # relative to the repository root
+/// tokenizer_path = "models/ms-marco-MiniLM-L-6-v2/tokenizer.json"   # default: next to the model
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RerankConfig {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub api_key_env: Option<String>,
+    pub endpoint: Option<String>,
+    pub candidates: Option<usize>,
+    pub max_retries: Option<u32>,
+    /// ONNX cross-encoder for the `local` provider.
+    pub model_path: Option<String>,
+    /// `tokenizer.json` for the `local` provider.
+    pub tokenizer_path: Option<String>,
+}
+
+/// Providers accepted by `[rerank] provider`.
+pub const RERANK_PROVIDERS: &[&str] = &["cohere", "voyage", "openai", "local"];
+
+impl RerankConfig {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(p) = &self.provider
+            && !RERANK_PROVIDERS.contains(&p.as_str())
+        {
+            return Err(format!(
+                "rerank.provider must be one of {}, got {p:?}",
+                RERANK_PROVIDERS.join(", ")
+            ));
+        }
+        if self.provider.as_deref() == Some("local") && self.model_path.is_none() {
+            return Err("rerank.model_path is required for the local provider".to_string());
+        }
+        if self.candidates == Some(0) {
+            return Err("rerank.candidates must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl DefaultsConfig {
+    fn validate(&self, presets: &BTreeMap<String, PresetConfig>) -> Result<(), String> {
+        if let Some(p) = &self.preset
+            && !PRESETS.contains(&p.as_str())
+            && !presets.contains_key(p)
+        {
+            return Err(format!(
+                "defaults.preset must be one of {} or a [presets] name, got {p:?}",
+                PRESETS.join(", ")
+            ));
+        }
+        if let Some(m) = self.min_score
+            && m.is_nan()
+        {
+            return Err("defaults.min_score must be a number".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl PresetConfig {
+    fn validate(&self, name: &str) -> Result<(), String> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "preset names may only use letters, digits, '-' and '_', got {name:?}"
+            ));
+        }
+        if let Some(b) = &self.base
+            && !PRESETS.contains(&b.as_str())
+        {
+            return Err(format!(
+                "presets.{name}.base must be one of {}, got {b:?}",
+                PRESETS.join(", ")
+            ));
+        }
+        if let Some(d) = &self.depth
+            && !PRESET_DEPTHS.contains(&d.as_str())
+        {
+            return Err(format!(
+                "presets.{name}.depth must be one of {}, got {d:?}",
+                PRESET_DEPTHS.join(", ")
+            ));
+        }
+        if self.max_bytes == Some(0) {
+            return Err(format!("presets.{name}.max_bytes must be at least 1"));
+        }
+        if let Some(m) = self.min_score
+            && m.is_nan()
+        {
+            return Err(format!("presets.{name}.min_score must be a number"));
+        }
+        self.weights.validate(&format!("presets.{name}.weights"))
+    }
+}
+
+impl WeightsConfig {
+    /// Parse `--weights` overrides: comma-separated `key=value` pairs such
+    /// as `bm25f=2,heuristic=0.5`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut weights = Self::default();
+        for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got {pair:?}"))?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("weight {key} must be a number, got {value:?}"))?;
+            let slot = match key.trim() {
+                "bm25f" => &mut weights.bm25f,
+                "heuristic" => &mut weights.heuristic,
+                "pagerank" => &mut weights.pagerank,
+                "git_recency" => &mut weights.git_recency,
+                "co_change" => &mut weights.co_change,
+                "churn" => &mut weights.churn,
+                "rrf_k" => &mut weights.rrf_k,
+                other => {
+                    return Err(format!(
+                        "unknown weight {other:?}; expected bm25f, heuristic, pagerank, git_recency, co_change, churn or rrf_k"
+                    ));
+                }
+            };
+            *slot = Some(value);
+        }
+        Ok(weights)
+    }
+
+    /// These weights with every key set in `overrides` replaced, checked as
+    /// a whole.
+    pub fn overlay(&self, overrides: &Self) -> Result<Self, String> {
+        let weights = Self {
+            bm25f: overrides.bm25f.or(self.bm25f),
+            heuristic: overrides.heuristic.or(self.heuristic),
+            pagerank: overrides.pagerank.or(self.pagerank),
+            git_recency: overrides.git_recency.or(self.git_recency),
+            co_change: overrides.co_change.or(self.co_change),
+            churn: overrides.churn.or(self.churn),
+            rrf_k: overrides.rrf_k.or(self.rrf_k),
+        };
+        weights.validate("weights")?;
+        Ok(weights)
+    }
+
+    /// Check the weights as the config `section` they were read from.
+    fn validate(&self, section: &str) -> Result<(), String> {
+        for (key, weight) in [
+            ("bm25f", self.bm25f),
+            ("heuristic", self.heuristic),
+            ("pagerank", self.pagerank),
+            ("git_recency", self.git_recency),
+            ("co_change", self.co_change),
+            ("churn", self.churn),
+            ("rrf_k", self.rrf_k),
+        ] {
+            if let Some(w) = weight
+                && (w.is_nan() || w < 0.0)
+            {
+                return Err(format!("{section}.{key} must not be negative, got {w}"));
+            }
+        }
+        if self.bm25f == Some(0.0) && self.heuristic == Some(0.0) {
+            return Err(format!(
+                "{section}.bm25f and {section}.heuristic cannot both be 0"
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl PageRankConfig {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(d) = self.damping
+            && (d.is_nan() || d <= 0.0 || d >= 1.0)
+        {
+            return Err(format!("pagerank.damping must be between 0 and 1, got {d}"));
+        }
+        if let Some(e) = self.epsilon
+            && (e.is_nan() || e <= 0.0)
+        {
+            return Err(format!("pagerank.epsilon must be positive, got {e}"));
+        }
+        if self.max_iterations == Some(0) {
+            return Err("pagerank.max_iterations must be at least 1".to_string());
+        }
+        if let Some(c) = self.recompute_churn
+            && !(0.0..=1.0).contains(&c)
+        {
+            return Err(format!(
+                "pagerank.recompute_churn must be between 0 and 1, got {c}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl ChurnConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.window_days == Some(0) {
+            return Err("churn.window_days must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl VendoredConfig {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(p) = &self.policy
+            && !VENDORED_POLICIES.contains(&p.as_str())
+        {
+            return Err(format!(
+                "vendored.policy must be one of {}, got {p:?}",
+                VENDORED_POLICIES.join(", ")
+            ));
+        }
+        if let Some(m) = self.multiplier
+            && !(0.0..=1.0).contains(&m)
+        {
+            return Err(format!(
+                "vendored.multiplier must be between 0 and 1, got {m}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FileTypesConfig {
+    fn validate(&self) -> Result<(), String> {
+        for (pattern, multiplier) in &self.rules {
+            if pattern.is_empty() || pattern == "*" {
+                return Err(format!(
+                    "file_types.rules pattern {pattern:?} must name a file or a *suffix"
+                ));
+            }
+            if !(0.0..=1.0).contains(multiplier) {
+                return Err(format!(
+                    "file_types.rules.{pattern:?} must be between 0 and 1, got {multiplier}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Path of the config file for a repository.
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(CONFIG_FILE)
+    }
+
+    /// Load the config for a repository, falling back to defaults when the
+    /// file does not exist.
+    pub fn load(root: &Path) -> Result<Self, TopoError> {
+        let path = Self::path(root);
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::parse_str(&content)
+                .map_err(|msg| TopoError::Config(format!("{}: {msg}", path.display()))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Parse config file contents.
+    pub fn parse(content: &str) -> Result<Self, TopoError> {
+        Self::parse_str(content).map_err(TopoError::Config)
+    }
+
+    fn parse_str(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| e.message().to_string())?;
+        config.defaults.validate(&config.presets)?;
+        for (name, preset) in &config.presets {
+            preset.validate(name)?;
+        }
+        config.weights.validate("weights")?;
+        config.scan.validate()?;
+        config.pagerank.validate()?;
+        config.churn.validate()?;
+        config.vendored.validate()?;
+        config.file_types.validate()?;
+        config.embeddings.validate()?;
+        config.rerank.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_yields_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Config::load(dir.path()).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parses_stop_words() {
+        let config =
+            Config::parse("[stop_words]\nadd = [\"acme\"]\nremove = [\"is\", \"do\"]\n").unwrap();
+        assert_eq!(config.stop_words.add, vec!["acme"]);
+        assert_eq!(config.stop_words.remove, vec!["is", "do"]);
+    }
+
+    #[test]
+    fn parses_pythonpath() {
+        let config = Config::parse("[python]\npythonpath = [\"src\"]\n").unwrap();
+        assert_eq!(config.python.pythonpath, vec!["src"]);
+    }
+
+    #[test]
+    fn parses_include_dirs() {
+        let config = Config::parse("[cpp]\ninclude_dirs = [\"include\", \"src\"]\n").unwrap();
+        assert_eq!(config.cpp.include_dirs, vec!["include", "src"]);
+    }
+
+    #[test]
+    fn parses_pagerank() {
+        let config = Config::parse("[pagerank]\ndamping = 0.7\nmax_iterations = 50\n").unwrap();
+        assert_eq!(config.pagerank.damping, Some(0.7));
+        assert_eq!(config.pagerank.epsilon, None);
+        assert_eq!(config.pagerank.max_iterations, Some(50));
+    }
+
+    #[test]
+    fn rejects_out_of_range_pagerank() {
+        for bad in [
+            "damping = 1.0",
+            "damping = 0",
+            "epsilon = -1.0",
+            "max_iterations = 0",
+            "recompute_churn = 1.5",
+        ] {
+            let err = Config::parse(&format!("[pagerank]\n{bad}\n")).unwrap_err();
+            assert!(matches!(err, TopoError::Config(_)), "{bad}");
+        }
+    }
+
+    #[test]
+    fn parses_scan_dirs() {
+        let config = Config::parse(
+            "[scan]\nskip_dirs = [\"third_party\"]\nkeep_dirs = [\"venv\", \".env\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.scan.skip_dirs, vec!["third_party"]);
+        assert_eq!(config.scan.keep_dirs, vec!["venv", ".env"]);
+        assert!(Config::parse("[scan]\nkeep_dirs = [\"src/venv\"]\n").is_err());
+        assert!(Config::parse("[scan]\nskip_dirs = [\"\"]\n").is_err());
+    }
+
+    #[test]
+    fn parses_churn() {
+        let config = Config::parse("[churn]\nwindow_days = 180\n").unwrap();
+        assert_eq!(config.churn.window_days, Some(180));
+        assert!(Config::parse("[churn]\nwindow_days = 0\n").is_err());
+    }
+
+    #[test]
+    fn parses_file_types() {
+        let config = Config::parse(
+            "[file_types]\ndefaults = false\n\n[file_types.rules]\n\"*.pb.go\" = 0.2\n",
+        )
+        .unwrap();
+        assert!(!config.file_types.defaults);
+        assert_eq!(config.file_types.rules.get("*.pb.go"), Some(&0.2));
+        assert!(Config::default().file_types.defaults);
+        assert!(Config::parse("[file_types.rules]\n\"*.lock\" = 1.5\n").is_err());
+        assert!(Config::parse("[file_types.rules]\n\"*\" = 0.5\n").is_err());
+    }
+
+    #[test]
+    fn parses_vendored() {
+        let config = Config::parse("[vendored]\npolicy = \"exclude\"\nmultiplier = 0.2\n").unwrap();
+        assert_eq!(config.vendored.policy.as_deref(), Some("exclude"));
+        assert_eq!(config.vendored.multiplier, Some(0.2));
+        assert!(Config::parse("[vendored]\npolicy = \"hide\"\n").is_err());
+        assert!(Config::parse("[vendored]\nmultiplier = 2.0\n").is_err());
+    }
+
+    #[test]
+    fn parses_content_policy() {
+        assert!(Config::default().content.require_redaction);
+        let config = Config::parse(
+            "[content]\nmax_file_bytes = 1024\ndeny = [\"**/prod.env\"]\nrequire_redaction = false\n",
+        )
+        .unwrap();
+        assert_eq!(config.content.max_file_bytes, Some(1024));
+        assert_eq!(config.content.deny, vec!["**/prod.env"]);
+        assert!(!config.content.require_redaction);
+    }
+
+    #[test]
+    fn parses_embeddings() {
+        let config = Config::parse(
+            "[embeddings]\nprovider = \"cohere\"\nbatch_size = 32\nmax_retries = 5\n",
+        )
+        .unwrap();
+        assert_eq!(config.embeddings.provider.as_deref(), Some("cohere"));
+        assert_eq!(config.embeddings.batch_size, Some(32));
+        assert_eq!(config.embeddings.max_retries, Some(5));
+        assert_eq!(config.embeddings.model, None);
+
+        let local = Config::parse(
+            "[embeddings]\nprovider = \"local\"\nmodel_path = \"models/minilm.onnx\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            local.embeddings.model_path.as_deref(),
+            Some("models/minilm.onnx")
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_embedding_provider() {
+        for bad in [
+            "provider = \"acme\"",
+            "provider = \"local\"",
+            "dimensions = 0",
+            "batch_size = 0",
+            "semantic_weight = -1.0",
+        ] {
+            let err = Config::parse(&format!("[embeddings]\n{bad}\n")).unwrap_err();
+            assert!(matches!(err, TopoError::Config(_)), "{bad}");
+        }
+    }
+
+    #[test]
+    fn parses_rerank() {
+        let config = Config::parse("[rerank]\nprovider = \"voyage\"\ncandidates = 20\n").unwrap();
+        assert_eq!(config.rerank.provider.as_deref(), Some("voyage"));
+        assert_eq!(config.rerank.candidates, Some(20));
+        for bad in [
+            "provider = \"acme\"",
+            "provider = \"local\"",
+            "candidates = 0",
+        ] {
+            let err = Config::parse(&format!("[rerank]\n{bad}\n")).unwrap_err();
+            assert!(matches!(err, TopoError::Config(_)), "{bad}");
+        }
+    }
+
+    #[test]
+    fn parses_defaults_scan_and_weights() {
+        let config = Config::parse(
+            "[defaults]\npreset = \"deep\"\nmax_tokens = 4000\n\n[scan]\nignore = [\"vendor/\"]\n\n[weights]\npagerank = 0.5\n",
+        )
+        .unwrap();
+        assert_eq!(config.defaults.preset.as_deref(), Some("deep"));
+        assert_eq!(config.defaults.max_tokens, Some(4000));
+        assert_eq!(config.defaults.max_bytes, None);
+        assert_eq!(config.scan.ignore, vec!["vendor/"]);
+        assert_eq!(config.weights.pagerank, Some(0.5));
+        assert_eq!(config.weights.bm25f, None);
+    }
+
+    #[test]
+    fn rejects_bad_defaults_and_weights() {
+        for bad in [
+            "[defaults]\npreset = \"fastest\"",
+            "[weights]\nbm25f = -0.1",
+            "[weights]\nbm25f = 0.0\nheuristic = 0.0",
+        ] {
+            let err = Config::parse(bad).unwrap_err();
+            assert!(matches!(err, TopoError::Config(_)), "{bad}");
+        }
+    }
+
+    #[test]
+    fn weight_overrides_layer_over_config() {
+        let overrides = WeightsConfig::parse("bm25f=2, git_recency=0").unwrap();
+        assert_eq!(overrides.bm25f, Some(2.0));
+        assert_eq!(overrides.git_recency, Some(0.0));
+        assert_eq!(overrides.heuristic, None);
+
+        let config = WeightsConfig {
+            heuristic: Some(0.5),
+            bm25f: Some(1.0),
+            ..Default::default()
+        };
+        let weights = config.overlay(&overrides).unwrap();
+        assert_eq!(weights.bm25f, Some(2.0));
+        assert_eq!(weights.heuristic, Some(0.5));
+
+        for bad in ["bm25f", "bm25f=high", "recency=1", "pagerank=-1"] {
+            let parsed = WeightsConfig::parse(bad).and_then(|w| config.overlay(&w));
+            assert!(parsed.is_err(), "{bad}");
+        }
+        let zero = WeightsConfig::parse("bm25f=0,heuristic=0").unwrap();
+        assert!(WeightsConfig::default().overlay(&zero).is_err());
+    }
+
+    #[test]
+    fn parses_custom_presets() {
+        let config = Config::parse(
+            "[defaults]\npreset = \"review\"\n\n[presets.review]\nbase = \"deep\"\nchunks = false\nmax_bytes = 300000\n",
+        )
+        .unwrap();
+        let review = &config.presets["review"];
+        assert_eq!(review.base.as_deref(), Some("deep"));
+        assert_eq!(review.chunks, Some(false));
+        assert_eq!(review.max_bytes, Some(300000));
+        assert_eq!(review.depth, None);
+
+        let weighted =
+            Config::parse("[presets.review.weights]\npagerank = 2.0\nrrf_k = 30\n").unwrap();
+        let weights = &weighted.presets["review"].weights;
+        assert_eq!(weights.pagerank, Some(2.0));
+        assert_eq!(weights.rrf_k, Some(30.0));
+
+        for bad in [
+            "[presets.review]\nbase = \"review\"",
+            "[presets.review.weights]\nrrf_k = -1",
+            "[presets.review]\ndepth = \"medium\"",
+            "[presets.\"a b\"]\nrerank = true",
+            "[presets.review]\nsignals = true",
+        ] {
+            let err = Config::parse(bad).unwrap_err();
+            assert!(matches!(err, TopoError::Config(_)), "{bad}");
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let err = Config::parse("[stop_words]\nadded = [\"acme\"]\n").unwrap_err();
+        assert!(matches!(err, TopoError::Config(_)));
+    }
+}
@@ -1,12 +1,25 @@
 //! Topo core domain types, traits, and errors.
 
+mod config;
 mod error;
+mod provider;
+mod repo;
 mod types;
 
+pub use config::{
+    ChurnConfig, Config, ContentConfig, CppConfig, DefaultsConfig, EMBEDDING_PROVIDERS,
+    EmbeddingsConfig, FileTypesConfig, PRESET_DEPTHS, PRESETS, PageRankConfig, PresetConfig,
+    PythonConfig, RERANK_PROVIDERS, RerankConfig, ScanConfig, StopWordsConfig, VENDORED_POLICIES,
+    VendoredConfig, WeightsConfig,
+};
 pub use error::TopoError;
+pub use provider::{DiskFiles, FileProvider, Overlay};
+pub use repo::{discover_root, nearest_package};
 pub use types::{
-    Bundle, Chunk, ChunkKind, DeepIndex, FileEntry, FileInfo, FileRole, Language, ScoredFile,
-    SignalBreakdown, TermFreqs, TokenBudget,
+    Bundle, Chunk, ChunkKind, ChunkMatch, DeepIndex, DropReason, DroppedFile, FileEntry, FileInfo,
+    FileRole, FileSummary, INDEX_VERSION, IndexError, IndexErrorKind, Language, OutlineEntry,
+    ScoredFile, SignalBreakdown, SummaryKind, SummaryLine, TermFreqs, TermPositions, TokenBudget,
+    Truncation,
 };
 
 #[cfg(test)]
@@ -102,6 +115,37 @@ mod tests {
         assert_eq!(FileRole::Other.as_str(), "other");
     }
 
+    // --- from_name ---
+
+    #[test]
+    fn language_from_name_accepts_names_and_extensions() {
+        assert_eq!(Language::from_name("rust"), Some(Language::Rust));
+        assert_eq!(
+            Language::from_name("TypeScript"),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(Language::from_name("py"), Some(Language::Python));
+        assert_eq!(Language::from_name("c++"), Some(Language::Cpp));
+        assert_eq!(Language::from_name("cobol"), None);
+    }
+
+    #[test]
+    fn file_role_from_name_round_trips() {
+        for role in [
+            FileRole::Implementation,
+            FileRole::Test,
+            FileRole::Config,
+            FileRole::Documentation,
+            FileRole::Generated,
+            FileRole::Build,
+            FileRole::Other,
+        ] {
+            assert_eq!(FileRole::from_name(role.as_str()), Some(role));
+        }
+        assert_eq!(FileRole::from_name("tests"), Some(FileRole::Test));
+        assert_eq!(FileRole::from_name("vendor"), None);
+    }
+
     // --- FileRole::Display ---
 
     #[test]
@@ -319,6 +363,11 @@ mod tests {
             language: Language::Rust,
             role: FileRole::Implementation,
             sha256: [0u8; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
         };
         assert_eq!(info.estimated_tokens(), 100);
     }
@@ -350,6 +399,11 @@ mod tests {
                     language: Language::Rust,
                     role: FileRole::Implementation,
                     sha256: [0u8; 32],
+                    lines: 0,
+                    executable: false,
+                    shebang: None,
+                    entry_point: false,
+                    license: None,
                 },
                 FileInfo {
                     path: "b.rs".to_string(),
@@ -357,6 +411,11 @@ mod tests {
                     language: Language::Rust,
                     role: FileRole::Implementation,
                     sha256: [0u8; 32],
+                    lines: 0,
+                    executable: false,
+                    shebang: None,
+                    entry_point: false,
+                    license: None,
                 },
             ],
             scanned_at: std::time::SystemTime::now(),
@@ -377,6 +436,11 @@ mod tests {
             tokens: 100,
             language: Language::Rust,
             role: FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
         };
         let b = ScoredFile {
             path: "b.rs".to_string(),
@@ -385,6 +449,11 @@ mod tests {
             tokens: 200,
             language: Language::Rust,
             role: FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
         };
         assert!(a.score > b.score);
     }
@@ -422,6 +491,11 @@ mod tests {
             tokens,
             language: Language::Rust,
             role: FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
         }
     }
 
@@ -487,4 +561,45 @@ mod tests {
         };
         assert!(budget.enforce(&[]).is_empty());
     }
+
+    // --- Truncation ---
+
+    #[test]
+    fn truncation_counts_each_stage() {
+        let budget_cut = vec![make_scored("b.rs", 200, 0.8), make_scored("c.rs", 300, 0.7)];
+        let top_cut = vec![make_scored("d.rs", 50, 0.6)];
+        let below = vec![
+            make_scored("e.rs", 10, 0.004),
+            make_scored("f.rs", 10, 0.002),
+        ];
+
+        let t = Truncation::from_cuts(&budget_cut, &top_cut, &below);
+        assert!(t.is_truncated());
+        assert_eq!(t.budget_dropped, 2);
+        assert_eq!(t.budget_dropped_tokens, 500);
+        assert_eq!(t.top_dropped, 1);
+        assert_eq!(t.below_threshold, 2);
+        assert_eq!(t.best_below_threshold, Some(0.004));
+        let reasons: Vec<DropReason> = t.dropped.iter().map(|d| d.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![DropReason::Budget, DropReason::Budget, DropReason::Top]
+        );
+    }
+
+    #[test]
+    fn truncation_caps_listing() {
+        let cut: Vec<ScoredFile> = (0..50)
+            .map(|i| make_scored(&format!("{i}.rs"), 10, 0.5))
+            .collect();
+        let t = Truncation::from_cuts(&cut, &[], &[]);
+        assert_eq!(t.dropped.len(), Truncation::MAX_LISTED);
+        assert_eq!(t.budget_dropped, 50);
+    }
+
+    #[test]
+    fn truncation_threshold_only_is_not_truncated() {
+        let below = vec![make_scored("e.rs", 10, 0.004)];
+        assert!(!Truncation::from_cuts(&[], &[], &below).is_truncated());
+    }
 }
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Where file content comes from when scanning, indexing and packing.
+///
+/// The working tree by default ([`DiskFiles`]); embedders such as language
+/// servers and editors layer unsaved buffers over it with [`Overlay`], so
+/// results reflect what the user sees rather than what was last saved.
+pub trait FileProvider: Send + Sync {
+    /// The content of the repo-relative `path`.
+    fn read(&self, path: &str) -> io::Result<Vec<u8>>;
+
+    /// Repo-relative paths whose content doesn't come from disk, sorted.
+    /// They are scanned even if missing from the working tree.
+    fn overlaid(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The content of `path` as UTF-8 text.
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Files as they are on disk under a repository root.
+#[derive(Debug, Clone)]
+pub struct DiskFiles {
+    root: PathBuf,
+}
+
+impl DiskFiles {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl FileProvider for DiskFiles {
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.root.join(path))
+    }
+}
+
+/// In-memory content layered over another provider: overlaid paths read
+/// from memory, everything else from the base.
+#[derive(Debug, Clone)]
+pub struct Overlay<P = DiskFiles> {
+    base: P,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl<P: FileProvider> Overlay<P> {
+    pub fn new(base: P) -> Self {
+        Self {
+            base,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Use `content` for the repo-relative `path` instead of the base's.
+    pub fn file(mut self, path: impl Into<String>, content: impl Into<Vec<u8>>) -> Self {
+        self.insert(path, content);
+        self
+    }
+
+    /// Replace the content of `path`, as when a buffer is edited.
+    pub fn insert(&mut self, path: impl Into<String>, content: impl Into<Vec<u8>>) {
+        let path = path.into().replace('\\', "/");
+        self.files.insert(path, content.into());
+    }
+
+    /// Fall back to the base's content for `path`, as when a buffer is
+    /// saved or closed.
+    pub fn remove(&mut self, path: &str) {
+        self.files.remove(path);
+    }
+}
+
+impl<P: FileProvider> FileProvider for Overlay<P> {
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        match self.files.get(path) {
+            Some(content) => Ok(content.clone()),
+            None => self.base.read(path),
+        }
+    }
+
+    fn overlaid(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.files.keys().cloned().collect();
+        for path in self.base.overlaid() {
+            if !self.files.contains_key(&path) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_takes_precedence_over_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), "fn saved() {}").unwrap();
+        fs::write(dir.path().join("b.rs"), "fn b() {}").unwrap();
+
+        let mut overlay = Overlay::new(DiskFiles::new(dir.path()))
+            .file("a.rs", "fn unsaved() {}")
+            .file("new.rs", "fn draft() {}");
+        assert_eq!(overlay.read_to_string("a.rs").unwrap(), "fn unsaved() {}");
+        assert_eq!(overlay.read_to_string("b.rs").unwrap(), "fn b() {}");
+        assert_eq!(overlay.read_to_string("new.rs").unwrap(), "fn draft() {}");
+        assert_eq!(overlay.overlaid(), ["a.rs", "new.rs"]);
+
+        overlay.remove("a.rs");
+        assert_eq!(overlay.read_to_string("a.rs").unwrap(), "fn saved() {}");
+        assert!(overlay.read("missing.rs").is_err());
+    }
+}
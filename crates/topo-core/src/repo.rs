@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+
+/// Find the working-tree root for `start`.
+///
+/// Honors `GIT_WORK_TREE`, then walks up to the nearest directory containing
+/// `.git` (a directory, or a file as in worktrees and submodules), then falls
+/// back to the parent of a `GIT_DIR` named `.git`. Returns `None` outside a
+/// repository or for bare repositories.
+pub fn discover_root(start: &Path) -> Option<PathBuf> {
+    discover_root_with(
+        start,
+        std::env::var_os("GIT_DIR").as_deref().map(Path::new),
+        std::env::var_os("GIT_WORK_TREE").as_deref().map(Path::new),
+    )
+}
+
+fn discover_root_with(
+    start: &Path,
+    git_dir_env: Option<&Path>,
+    work_tree_env: Option<&Path>,
+) -> Option<PathBuf> {
+    if let Some(work_tree) = work_tree_env {
+        return Some(start.join(work_tree));
+    }
+
+    if let Some(root) = start.ancestors().find(|dir| dir.join(".git").exists()) {
+        return Some(root.to_path_buf());
+    }
+
+    let git_dir = start.join(git_dir_env?);
+    match git_dir.file_name() {
+        Some(name) if name == ".git" => git_dir.parent().map(Path::to_path_buf),
+        _ => None,
+    }
+}
+
+/// Files marking a package (crate, module, project) boundary.
+const PACKAGE_MARKERS: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "pyproject.toml",
+    "setup.py",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "Gemfile",
+    "composer.json",
+    "mix.exs",
+    "Package.swift",
+];
+
+/// The nearest package directory containing `start`, relative to `root`.
+///
+/// Only directories strictly below `root` count: a package at the root itself
+/// means the repository is a single package and there is nothing to scope.
+pub fn nearest_package(root: &Path, start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .take_while(|dir| *dir != root && dir.starts_with(root))
+        .find(|dir| PACKAGE_MARKERS.iter().any(|m| dir.join(m).is_file()))
+        .and_then(|dir| dir.strip_prefix(root).ok())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn discover_walks_up_to_dot_git() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("src/auth")).unwrap();
+
+        let found = discover_root_with(&dir.path().join("src/auth"), None, None);
+        assert_eq!(found.as_deref(), Some(dir.path()));
+    }
+
+    #[test]
+    fn discover_accepts_worktree_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let wt = dir.path().join("wt");
+        fs::create_dir_all(wt.join("src")).unwrap();
+        fs::write(wt.join(".git"), "gitdir: ../main/.git/worktrees/wt\n").unwrap();
+
+        assert_eq!(
+            discover_root_with(&wt.join("src"), None, None).as_deref(),
+            Some(wt.as_path())
+        );
+    }
+
+    #[test]
+    fn nearest_package_below_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("Cargo.toml"), "[workspace]").unwrap();
+        fs::create_dir_all(root.join("crates/api/src/handlers")).unwrap();
+        fs::write(root.join("crates/api/Cargo.toml"), "[package]").unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+
+        assert_eq!(
+            nearest_package(root, &root.join("crates/api/src/handlers")),
+            Some(PathBuf::from("crates/api"))
+        );
+        assert_eq!(nearest_package(root, &root.join("docs")), None);
+        assert_eq!(nearest_package(root, root), None);
+    }
+
+    #[test]
+    fn discover_uses_env_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let work_tree = dir.path().join("checkout");
+        assert_eq!(
+            discover_root_with(dir.path(), None, Some(&work_tree)),
+            Some(work_tree.clone())
+        );
+        assert_eq!(
+            discover_root_with(dir.path(), Some(&work_tree.join(".git")), None),
+            Some(work_tree)
+        );
+        assert_eq!(
+            discover_root_with(dir.path(), Some(Path::new("repo.git")), None),
+            None
+        );
+    }
+}
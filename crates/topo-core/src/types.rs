@@ -11,6 +11,23 @@ pub struct FileInfo {
     pub language: Language,
     pub role: FileRole,
     pub sha256: [u8; 32],
+    /// Number of lines (a final line without a trailing newline counts).
+    #[serde(default)]
+    pub lines: u64,
+    /// Whether any execute permission bit is set (always false off Unix).
+    #[serde(default)]
+    pub executable: bool,
+    /// Interpreter named by a `#!` line, e.g. `python3` or `bash`.
+    #[serde(default)]
+    pub shebang: Option<String>,
+    /// Whether the file starts a program: `main.rs`, `bin/`, `cmd/`, a
+    /// server bootstrap, a Dockerfile `CMD` target, ...
+    #[serde(default)]
+    pub entry_point: bool,
+    /// SPDX license expression from the file's own `SPDX-License-Identifier`
+    /// header, else from the nearest `LICENSE` file above it.
+    #[serde(default)]
+    pub license: Option<String>,
 }
 
 impl FileInfo {
@@ -120,6 +137,44 @@ impl Language {
         }
     }
 
+    /// Parse a language name as written by [`Language::as_str`], or one of
+    /// its file extensions (`rs`, `ts`, `py`). Case-insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        let language = match name.as_str() {
+            "rust" => Self::Rust,
+            "go" | "golang" => Self::Go,
+            "python" => Self::Python,
+            "javascript" => Self::JavaScript,
+            "typescript" => Self::TypeScript,
+            "java" => Self::Java,
+            "ruby" => Self::Ruby,
+            "c" => Self::C,
+            "cpp" | "c++" => Self::Cpp,
+            "shell" => Self::Shell,
+            "markdown" => Self::Markdown,
+            "yaml" => Self::Yaml,
+            "toml" => Self::Toml,
+            "json" => Self::Json,
+            "html" => Self::Html,
+            "css" => Self::Css,
+            "swift" => Self::Swift,
+            "kotlin" => Self::Kotlin,
+            "scala" => Self::Scala,
+            "haskell" => Self::Haskell,
+            "elixir" => Self::Elixir,
+            "lua" => Self::Lua,
+            "php" => Self::Php,
+            "r" => Self::R,
+            "other" => Self::Other,
+            ext => match Self::from_extension(ext) {
+                Self::Other => return None,
+                language => language,
+            },
+        };
+        Some(language)
+    }
+
     /// Returns true if this language is a programming language
     /// (as opposed to markup/config/data format).
     pub fn is_programming_language(&self) -> bool {
@@ -179,6 +234,21 @@ impl FileRole {
         }
     }
 
+    /// Parse a role name as written by [`FileRole::as_str`], or its long
+    /// form (`implementation`, `tests`, `documentation`). Case-insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "impl" | "implementation" | "src" | "source" => Some(Self::Implementation),
+            "test" | "tests" => Some(Self::Test),
+            "config" => Some(Self::Config),
+            "docs" | "doc" | "documentation" => Some(Self::Documentation),
+            "generated" => Some(Self::Generated),
+            "build" => Some(Self::Build),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+
     /// Classify a file's role based on its path.
     ///
     /// Priority order: Generated > Test > Documentation > Build > Config > Implementation > Other
@@ -372,6 +442,45 @@ pub struct ScoredFile {
     pub tokens: u64,
     pub language: Language,
     pub role: FileRole,
+    /// Label of the repository root the file belongs to, when several roots
+    /// were ranked together.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// Label of the import-graph cluster the file belongs to, when results
+    /// were grouped by cluster.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<String>,
+    /// The test of a selected implementation file, or the implementation
+    /// of a selected test, when the query targets implementation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pair: Option<String>,
+    /// The chunks that best match the query, best first. Only filled in
+    /// from a deep index, and only for selected files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<ChunkMatch>,
+    /// The file's leading symbols in source order, when an outline was
+    /// asked for.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outline: Vec<OutlineEntry>,
+}
+
+/// A chunk of a selected file that matches the query, so a reader can go
+/// straight to those lines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkMatch {
+    pub name: String,
+    pub kind: ChunkKind,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub score: f64,
+}
+
+/// One symbol of a file outline: where a function or type is declared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutlineEntry {
+    pub name: String,
+    pub kind: ChunkKind,
+    pub line: u32,
 }
 
 /// Per-signal score breakdown for explainability.
@@ -381,9 +490,33 @@ pub struct SignalBreakdown {
     pub heuristic: f64,
     pub pagerank: Option<f64>,
     pub git_recency: Option<f64>,
+    /// How often the file changed together with the query's seed files.
+    #[serde(default)]
+    pub co_change: Option<f64>,
+    /// Commits touching the file within the churn window, normalized.
+    #[serde(default)]
+    pub churn: Option<f64>,
     pub embedding: Option<f64>,
+    /// Multiplier from a file-type rule (e.g. lockfiles), when one applied.
+    #[serde(default)]
+    pub file_type: Option<f64>,
+    /// Boost for an entry point on an architecture-style query, when applied.
+    #[serde(default)]
+    pub entry_point: Option<f64>,
+    /// Relevance from the cross-encoder reranker, for files it re-scored.
+    #[serde(default)]
+    pub rerank: Option<f64>,
+    /// Penalty for a file named by a negated query term (`-vendor`), when applied.
+    #[serde(default)]
+    pub negated: Option<f64>,
+    /// Multiplier for a vendored or generated file, when downweighted.
+    #[serde(default)]
+    pub vendored: Option<f64>,
 }
 
+/// Version written by this build. Indexes older than this are rebuilt.
+pub const INDEX_VERSION: u32 = 7;
+
 /// The deep index containing pre-computed term frequencies and chunks.
 #[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
 pub struct DeepIndex {
@@ -394,6 +527,66 @@ pub struct DeepIndex {
     pub doc_frequencies: std::collections::HashMap<String, u32>,
     /// Normalized PageRank scores per file path (0.0–1.0).
     pub pagerank_scores: std::collections::HashMap<String, f64>,
+    /// The `[pagerank]` settings the scores were computed with. Scores
+    /// from other settings are recomputed rather than kept.
+    pub pagerank_settings: String,
+    /// Stop words dropped when the index was built, sorted. Entries built with
+    /// a different list cannot be reused.
+    pub stop_words: Vec<String>,
+    /// Files the last build could not index, sorted by path.
+    pub errors: Vec<IndexError>,
+}
+
+/// A file left out of the deep index because it could not be indexed.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+pub struct IndexError {
+    pub path: String,
+    pub kind: IndexErrorKind,
+    /// The underlying error or limit, for humans.
+    pub detail: String,
+}
+
+/// Why a file could not be indexed.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum IndexErrorKind {
+    /// Reading the file failed (permissions, removed mid-build, ...).
+    Unreadable,
+    /// Text that is not valid UTF-8. Binary files are skipped silently.
+    NotUtf8,
+    /// Larger than the deep index accepts.
+    TooLarge,
+}
+
+impl IndexErrorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unreadable => "unreadable",
+            Self::NotUtf8 => "not-utf8",
+            Self::TooLarge => "too-large",
+        }
+    }
 }
 
 /// Per-file entry in the deep index.
@@ -402,7 +595,18 @@ pub struct FileEntry {
     pub sha256: [u8; 32],
     pub chunks: Vec<Chunk>,
     pub term_frequencies: std::collections::HashMap<String, TermFreqs>,
+    /// Where each term occurs, for matching quoted phrases (version 4).
+    pub positions: std::collections::HashMap<String, TermPositions>,
     pub doc_length: u32,
+    /// Extractive summary, built with the entry and reused while the hash matches.
+    pub summary: FileSummary,
+    /// MinHash signature of the content's token shingles, for finding
+    /// near-duplicate files (version 5). Empty for files without tokens.
+    pub signature: Vec<u32>,
+    /// Files this one imports, as of the build that last computed PageRank
+    /// (version 6), so the next build can tell how much the import graph
+    /// changed.
+    pub imports: Vec<String>,
 }
 
 /// A code chunk extracted by tree-sitter or regex fallback.
@@ -439,6 +643,98 @@ pub enum ChunkKind {
     Other,
 }
 
+impl ChunkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Function => "function",
+            Self::Type => "type",
+            Self::Impl => "impl",
+            Self::Import => "import",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Deterministic extractive summary of a file: doc comments, exported
+/// signatures and top-level constants, copied from the source in order.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+pub struct FileSummary {
+    pub lines: Vec<SummaryLine>,
+}
+
+impl FileSummary {
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The summary as text, one source line per line, with `...` wherever
+    /// lines were skipped between two kept ones.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut previous: Option<u32> = None;
+        for line in &self.lines {
+            if previous.is_some_and(|p| line.line > p + 1) {
+                out.push_str("...\n");
+            }
+            out.push_str(&line.text);
+            out.push('\n');
+            previous = Some(line.line);
+        }
+        out
+    }
+}
+
+/// One line kept in a [`FileSummary`].
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+pub struct SummaryLine {
+    pub kind: SummaryKind,
+    /// 1-based line number of the first source line this was taken from.
+    pub line: u32,
+    pub text: String,
+}
+
+/// What a [`SummaryLine`] was extracted from.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryKind {
+    /// Documentation of the declaration that follows.
+    Doc,
+    Signature,
+    Constant,
+    /// The file's leading doc comment.
+    Header,
+}
+
 /// Term frequency counts across different fields.
 #[derive(Debug, Clone, Default, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
 pub struct TermFreqs {
@@ -447,6 +743,28 @@ pub struct TermFreqs {
     pub body: u32,
 }
 
+/// Token positions of one term in each field, ascending. Symbol positions
+/// leave a gap between names, so terms of two symbols are never adjacent.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+pub struct TermPositions {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filename: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub body: Vec<u32>,
+}
+
 /// Token budget configuration for query results.
 #[derive(Debug, Clone)]
 pub struct TokenBudget {
@@ -490,3 +808,90 @@ impl TokenBudget {
         result
     }
 }
+
+/// Why a ranked file was left out of a selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DropReason {
+    /// Cut because the token/byte budget was exhausted.
+    Budget,
+    /// Cut by the `top` limit.
+    Top,
+    /// Scored below `min_score`.
+    Threshold,
+}
+
+impl DropReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Budget => "budget",
+            Self::Top => "top",
+            Self::Threshold => "threshold",
+        }
+    }
+}
+
+/// A ranked file that did not make it into the selection.
+#[derive(Debug, Clone)]
+pub struct DroppedFile {
+    pub path: String,
+    pub score: f64,
+    pub tokens: u64,
+    pub reason: DropReason,
+}
+
+/// Diagnostics for files cut from a selection, so callers can decide
+/// whether to re-query with a bigger budget or a lower threshold.
+#[derive(Debug, Clone, Default)]
+pub struct Truncation {
+    /// Highest-ranked dropped files (budget cuts first), capped at [`Truncation::MAX_LISTED`].
+    pub dropped: Vec<DroppedFile>,
+    pub budget_dropped: usize,
+    pub budget_dropped_tokens: u64,
+    pub top_dropped: usize,
+    pub below_threshold: usize,
+    /// Best score among files below `min_score`.
+    pub best_below_threshold: Option<f64>,
+}
+
+impl Truncation {
+    /// Maximum number of dropped files listed individually.
+    pub const MAX_LISTED: usize = 20;
+
+    /// Build diagnostics from the files cut at each stage.
+    ///
+    /// `budget_cut` and `top_cut` are in rank order; `below_threshold` only
+    /// contributes counts since it usually holds most of the repository.
+    pub fn from_cuts(
+        budget_cut: &[ScoredFile],
+        top_cut: &[ScoredFile],
+        below_threshold: &[ScoredFile],
+    ) -> Self {
+        let dropped = budget_cut
+            .iter()
+            .map(|f| (f, DropReason::Budget))
+            .chain(top_cut.iter().map(|f| (f, DropReason::Top)))
+            .take(Self::MAX_LISTED)
+            .map(|(f, reason)| DroppedFile {
+                path: f.path.clone(),
+                score: f.score,
+                tokens: f.tokens,
+                reason,
+            })
+            .collect();
+
+        Self {
+            dropped,
+            budget_dropped: budget_cut.len(),
+            budget_dropped_tokens: budget_cut.iter().map(|f| f.tokens).sum(),
+            top_dropped: top_cut.len(),
+            below_threshold: below_threshold.len(),
+            best_below_threshold: below_threshold.iter().map(|f| f.score).reduce(f64::max),
+        }
+    }
+
+    /// Whether ranked files above the threshold were cut (by budget or `top`).
+    pub fn is_truncated(&self) -> bool {
+        self.budget_dropped > 0 || self.top_dropped > 0
+    }
+}
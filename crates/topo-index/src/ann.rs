@@ -0,0 +1,400 @@
+use crate::embeddings::{ContentKey, EmbeddingCache};
+use sha2::{Digest, Sha256};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+use std::fs;
+
+/// HNSW graph location inside the embeddings directory.
+const HNSW_FILE: &str = "hnsw.bin";
+/// Bumped whenever the stored graph layout changes.
+const HNSW_VERSION: u32 = 1;
+
+/// Neighbors kept per node on upper layers; layer 0 keeps twice as many.
+const DEFAULT_M: usize = 16;
+/// Candidate list size while inserting.
+const DEFAULT_EF_CONSTRUCTION: usize = 100;
+/// Below this many vectors a search scans every vector instead.
+const EXACT_SEARCH_LIMIT: usize = 256;
+
+/// A similarity paired with a node, ordered by similarity.
+#[derive(Clone, Copy, PartialEq)]
+struct Scored(f32, u32);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// Hierarchical navigable small-world graph over cached embeddings, for
+/// approximate cosine nearest-neighbor search.
+///
+/// Vectors are normalized on insert so similarity is a dot product. Layer
+/// assignment uses a fixed-seed generator, so the same vectors inserted in
+/// the same order always build the same graph.
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct HnswIndex {
+    version: u32,
+    fingerprint: String,
+    dims: usize,
+    m: usize,
+    ef_construction: usize,
+    keys: Vec<ContentKey>,
+    vectors: Vec<f32>,
+    /// Per node, per layer the node lives on, its neighbor ids.
+    links: Vec<Vec<Vec<u32>>>,
+    entry: Option<u32>,
+    rng: u64,
+}
+
+impl HnswIndex {
+    pub fn new(dims: usize) -> Self {
+        Self {
+            version: HNSW_VERSION,
+            fingerprint: String::new(),
+            dims,
+            m: DEFAULT_M,
+            ef_construction: DEFAULT_EF_CONSTRUCTION,
+            keys: Vec::new(),
+            vectors: Vec::new(),
+            links: Vec::new(),
+            entry: None,
+            rng: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Neighbors kept per node (default 16). Higher improves recall at the
+    /// cost of memory and build time.
+    pub fn m(mut self, m: usize) -> Self {
+        self.m = m.max(2);
+        self
+    }
+
+    /// Candidate list size while inserting (default 100).
+    pub fn ef_construction(mut self, ef: usize) -> Self {
+        self.ef_construction = ef.max(1);
+        self
+    }
+
+    /// Build an index over every vector in the cache.
+    pub fn from_cache(cache: &EmbeddingCache) -> Self {
+        let mut index = Self::new(cache.dims());
+        for (key, vector) in cache.iter() {
+            index.insert(key, vector);
+        }
+        index.fingerprint = cache_fingerprint(cache);
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Add a vector. Panics if its length is not the index dimension.
+    pub fn insert(&mut self, key: ContentKey, vector: &[f32]) {
+        assert_eq!(vector.len(), self.dims, "vector dimension mismatch");
+        let id = self.keys.len() as u32;
+        self.keys.push(key);
+        self.vectors.extend(normalized(vector));
+        let level = self.random_level();
+        self.links.push(vec![Vec::new(); level + 1]);
+
+        let Some(mut entry) = self.entry else {
+            self.entry = Some(id);
+            return;
+        };
+        let top = self.links[entry as usize].len() - 1;
+        let query = self.vector(id).to_vec();
+
+        for layer in (level + 1..=top).rev() {
+            entry = self.greedy(&query, entry, layer);
+        }
+        let mut entries = vec![entry];
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &entries, self.ef_construction, layer);
+            let max = if layer == 0 { self.m * 2 } else { self.m };
+            let neighbors: Vec<u32> = found.iter().take(self.m).map(|s| s.1).collect();
+            for &n in &neighbors {
+                self.links[n as usize][layer].push(id);
+                if self.links[n as usize][layer].len() > max {
+                    self.prune(n, layer, max);
+                }
+            }
+            self.links[id as usize][layer] = neighbors;
+            entries = found.iter().map(|s| s.1).collect();
+        }
+        if level > top {
+            self.entry = Some(id);
+        }
+    }
+
+    /// The `k` cached keys most similar to `query`, best first, with their
+    /// cosine similarity. `ef` (at least `k`) trades speed for recall.
+    pub fn search(&self, query: &[f32], k: usize, ef: usize) -> Vec<(ContentKey, f32)> {
+        if query.len() != self.dims || k == 0 {
+            return Vec::new();
+        }
+        let query = normalized(query);
+        let found: Vec<Scored> = if self.len() <= EXACT_SEARCH_LIMIT {
+            let mut all: Vec<Scored> = (0..self.len() as u32)
+                .map(|id| Scored(dot(&query, self.vector(id)), id))
+                .collect();
+            all.sort_by(|a, b| b.cmp(a));
+            all
+        } else {
+            let Some(mut entry) = self.entry else {
+                return Vec::new();
+            };
+            let top = self.links[entry as usize].len() - 1;
+            for layer in (1..=top).rev() {
+                entry = self.greedy(&query, entry, layer);
+            }
+            self.search_layer(&query, &[entry], ef.max(k), 0)
+        };
+        found
+            .into_iter()
+            .take(k)
+            .map(|Scored(sim, id)| (self.keys[id as usize], sim))
+            .collect()
+    }
+
+    fn vector(&self, id: u32) -> &[f32] {
+        let start = id as usize * self.dims;
+        &self.vectors[start..start + self.dims]
+    }
+
+    /// Layer for a new node: geometric with ratio 1/m.
+    fn random_level(&mut self) -> usize {
+        // xorshift64*
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let bits = self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11;
+        let uniform = (bits as f64 + 1.0) / (1u64 << 53) as f64;
+        let ml = 1.0 / (self.m as f64).ln();
+        (-uniform.ln() * ml).floor() as usize
+    }
+
+    /// Walk `layer` from `entry` to the node most similar to `query`.
+    fn greedy(&self, query: &[f32], mut entry: u32, layer: usize) -> u32 {
+        let mut best = dot(query, self.vector(entry));
+        loop {
+            let mut moved = false;
+            for &n in &self.links[entry as usize][layer] {
+                let sim = dot(query, self.vector(n));
+                if sim > best {
+                    best = sim;
+                    entry = n;
+                    moved = true;
+                }
+            }
+            if !moved {
+                return entry;
+            }
+        }
+    }
+
+    /// Best-first search of one layer, returning up to `ef` nodes, best first.
+    fn search_layer(&self, query: &[f32], entries: &[u32], ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited: HashSet<u32> = entries.iter().copied().collect();
+        let mut candidates: BinaryHeap<Scored> = BinaryHeap::new();
+        let mut results: BinaryHeap<Reverse<Scored>> = BinaryHeap::new();
+        for &e in entries {
+            let s = Scored(dot(query, self.vector(e)), e);
+            candidates.push(s);
+            results.push(Reverse(s));
+            if results.len() > ef {
+                results.pop();
+            }
+        }
+
+        while let Some(current) = candidates.pop() {
+            let worst = results.peek().map_or(f32::NEG_INFINITY, |r| r.0.0);
+            if current.0 < worst && results.len() >= ef {
+                break;
+            }
+            for &n in &self.links[current.1 as usize][layer] {
+                if !visited.insert(n) {
+                    continue;
+                }
+                let s = Scored(dot(query, self.vector(n)), n);
+                let worst = results.peek().map_or(f32::NEG_INFINITY, |r| r.0.0);
+                if results.len() < ef || s.0 > worst {
+                    candidates.push(s);
+                    results.push(Reverse(s));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        let mut found: Vec<Scored> = results.into_iter().map(|r| r.0).collect();
+        found.sort_by(|a, b| b.cmp(a));
+        found
+    }
+
+    /// Keep only the `max` neighbors of `node` most similar to it.
+    fn prune(&mut self, node: u32, layer: usize, max: usize) {
+        let base = self.vector(node).to_vec();
+        let mut scored: Vec<Scored> = self.links[node as usize][layer]
+            .iter()
+            .map(|&n| Scored(dot(&base, self.vector(n)), n))
+            .collect();
+        scored.sort_by(|a, b| b.cmp(a));
+        scored.truncate(max);
+        self.links[node as usize][layer] = scored.into_iter().map(|s| s.1).collect();
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalized(v: &[f32]) -> Vec<f32> {
+    let norm = dot(v, v).sqrt();
+    if norm == 0.0 {
+        return v.to_vec();
+    }
+    v.iter().map(|x| x / norm).collect()
+}
+
+/// Hash of the model, dimension and keys a cache holds; the HNSW graph is
+/// rebuilt whenever it changes.
+fn cache_fingerprint(cache: &EmbeddingCache) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(cache.model().as_bytes());
+    hasher.update(cache.dims().to_le_bytes());
+    for (key, _) in cache.iter() {
+        hasher.update(key);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// The HNSW graph over `cache`, loaded from `.topo/embeddings/hnsw.bin` when
+/// it was built from the same vectors, otherwise built and saved.
+///
+/// Saving is best-effort: a read-only checkout still gets a fresh graph.
+pub fn load_or_build_ann(cache: &EmbeddingCache) -> HnswIndex {
+    let fingerprint = cache_fingerprint(cache);
+    let path = cache.dir().join(HNSW_FILE);
+    if let Ok(bytes) = fs::read(&path)
+        && let Ok(index) = rkyv::from_bytes::<HnswIndex, rkyv::rancor::Error>(&bytes)
+        && index.version == HNSW_VERSION
+        && index.fingerprint == fingerprint
+    {
+        return index;
+    }
+    let index = HnswIndex::from_cache(cache);
+    if let Ok(bytes) = rkyv::to_bytes::<rkyv::rancor::Error>(&index) {
+        let _ = fs::create_dir_all(cache.dir());
+        let _ = fs::write(&path, &bytes);
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::content_key;
+
+    /// Deterministic pseudo-random vectors.
+    fn vectors(n: usize, dims: usize) -> Vec<Vec<f32>> {
+        let mut state = 42u64;
+        (0..n)
+            .map(|_| {
+                (0..dims)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn key(i: usize) -> ContentKey {
+        content_key(&i.to_string())
+    }
+
+    #[test]
+    fn finds_exact_match_first() {
+        let data = vectors(50, 8);
+        let mut index = HnswIndex::new(8);
+        for (i, v) in data.iter().enumerate() {
+            index.insert(key(i), v);
+        }
+        let hits = index.search(&data[17], 3, 10);
+        assert_eq!(hits[0].0, key(17));
+        assert!((hits[0].1 - 1.0).abs() < 1e-5);
+        assert!(hits[0].1 >= hits[1].1 && hits[1].1 >= hits[2].1);
+    }
+
+    #[test]
+    fn approximate_search_recalls_true_neighbors() {
+        let data = vectors(1000, 16);
+        let mut index = HnswIndex::new(16);
+        for (i, v) in data.iter().enumerate() {
+            index.insert(key(i), v);
+        }
+
+        let mut recalled = 0;
+        let queries = vectors(1020, 16).split_off(1000);
+        for q in &queries {
+            let qn = normalized(q);
+            let mut exact: Vec<(usize, f32)> = data
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i, dot(&qn, &normalized(v))))
+                .collect();
+            exact.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let truth: HashSet<ContentKey> = exact[..10].iter().map(|(i, _)| key(*i)).collect();
+            recalled += index
+                .search(q, 10, 64)
+                .iter()
+                .filter(|(k, _)| truth.contains(k))
+                .count();
+        }
+        // 20 queries × 10 neighbors
+        assert!(recalled >= 180, "recall {recalled}/200");
+    }
+
+    #[test]
+    fn loads_saved_graph_until_cache_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = EmbeddingCache::open(dir.path(), "m", 4).unwrap();
+        for (i, v) in vectors(10, 4).into_iter().enumerate() {
+            cache.insert(key(i), v).unwrap();
+        }
+        let built = load_or_build_ann(&cache);
+        assert_eq!(built.len(), 10);
+        assert!(cache.dir().join(HNSW_FILE).exists());
+        assert_eq!(load_or_build_ann(&cache).fingerprint, built.fingerprint);
+
+        cache.insert(key(10), vec![1.0, 0.0, 0.0, 0.0]).unwrap();
+        let rebuilt = load_or_build_ann(&cache);
+        assert_eq!(rebuilt.len(), 11);
+        assert_ne!(rebuilt.fingerprint, built.fingerprint);
+    }
+
+    #[test]
+    fn wrong_dimension_query_finds_nothing() {
+        let mut index = HnswIndex::new(2);
+        index.insert(key(0), &[1.0, 0.0]);
+        assert!(index.search(&[1.0, 0.0, 0.0], 1, 1).is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+//! Generated artifacts left out of the deep index: minified bundles, source
+//! maps and large lockfiles. Their vocabulary is noise that skews document
+//! frequencies for every other file; the shallow scan still lists them.
+
+use topo_core::{FileInfo, Language};
+
+/// Lockfiles at least this large are left out; small ones are harmless.
+const LOCKFILE_MIN_BYTES: u64 = 32 * 1024;
+/// Average line length above which a script or stylesheet counts as minified.
+const MINIFIED_AVG_LINE_BYTES: usize = 300;
+/// Files shorter than this are never called minified.
+const MINIFIED_MIN_BYTES: usize = 2 * 1024;
+
+/// Lockfiles without a `.lock` suffix.
+const LOCKFILES: &[&str] = &[
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "pnpm-lock.yaml",
+    "go.sum",
+];
+
+/// Why a file is kept out of the deep index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    Minified,
+    SourceMap,
+    Lockfile,
+}
+
+/// Classify a file by name and size alone. Minified files without a
+/// `.min.` name are caught by [`is_minified`] once their content is read.
+pub fn classify_path(path: &str, size: u64) -> Option<Artifact> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    if name.ends_with(".map") {
+        Some(Artifact::SourceMap)
+    } else if name.ends_with(".min.js") || name.ends_with(".min.css") {
+        Some(Artifact::Minified)
+    } else if (name.ends_with(".lock") || LOCKFILES.contains(&name)) && size >= LOCKFILE_MIN_BYTES {
+        Some(Artifact::Lockfile)
+    } else {
+        None
+    }
+}
+
+/// Whether `info` is a script or stylesheet whose content may be minified.
+pub fn may_be_minified(info: &FileInfo) -> bool {
+    matches!(
+        info.language,
+        Language::JavaScript | Language::TypeScript | Language::Css
+    ) && info.size as usize >= MINIFIED_MIN_BYTES
+}
+
+/// Whether `content` looks minified: very long lines on average.
+pub fn is_minified(content: &str) -> bool {
+    if content.len() < MINIFIED_MIN_BYTES {
+        return false;
+    }
+    let lines = content.lines().count().max(1);
+    content.len() / lines > MINIFIED_AVG_LINE_BYTES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_path_finds_artifacts() {
+        assert_eq!(
+            classify_path("dist/app.js.map", 10),
+            Some(Artifact::SourceMap)
+        );
+        assert_eq!(
+            classify_path("vendor/jquery.min.js", 10),
+            Some(Artifact::Minified)
+        );
+        assert_eq!(
+            classify_path("Cargo.lock", 100_000),
+            Some(Artifact::Lockfile)
+        );
+        assert_eq!(
+            classify_path("web/package-lock.json", 100_000),
+            Some(Artifact::Lockfile)
+        );
+        assert_eq!(classify_path("Cargo.lock", 1_000), None);
+        assert_eq!(classify_path("src/lock.rs", 100_000), None);
+    }
+
+    #[test]
+    fn is_minified_needs_long_lines() {
+        let bundle = "var a=1;".repeat(1_000);
+        assert!(is_minified(&bundle));
+        let source = "const a = 1;\n".repeat(1_000);
+        assert!(!is_minified(&source));
+        assert!(!is_minified("var a=1;"));
+    }
+}
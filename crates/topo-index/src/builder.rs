@@ -1,24 +1,101 @@
 use rayon::prelude::*;
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use topo_core::{ChunkKind, DeepIndex, FileEntry, FileInfo, Language, TermFreqs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use topo_core::{
+    ChunkKind, DeepIndex, DiskFiles, FileEntry, FileInfo, FileProvider, INDEX_VERSION, IndexError,
+    IndexErrorKind, Language, TermFreqs, TermPositions,
+};
+use topo_score::{ImportGraph, PageRankParams, ResolveOptions, StopWords, WeightedImports};
 use topo_treesit::{Chunker, RegexChunker};
 
+use crate::artifacts;
+use crate::stats::{BuildStats, FileStageTimes, FileTiming};
+
+/// Files larger than this are left out of the deep index and reported.
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Leading bytes checked for a NUL when telling binary files from text.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
 /// Builds a DeepIndex from a list of scanned files.
 pub struct IndexBuilder<'a> {
     root: &'a Path,
+    stop_words: StopWords,
+    resolve_options: ResolveOptions,
+    pagerank: PageRankParams,
+    include_artifacts: bool,
+    provider: Option<&'a dyn FileProvider>,
 }
 
 impl<'a> IndexBuilder<'a> {
     pub fn new(root: &'a Path) -> Self {
-        Self { root }
+        Self {
+            root,
+            stop_words: StopWords::default(),
+            resolve_options: ResolveOptions::default(),
+            pagerank: PageRankParams::default(),
+            include_artifacts: false,
+            provider: None,
+        }
+    }
+
+    /// Read file content from `provider` instead of disk, so in-memory
+    /// overlays are indexed as they are. Carried-forward entries are still
+    /// matched by SHA-256, which the scanner took from the same provider.
+    pub fn provider(mut self, provider: &'a dyn FileProvider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Also index minified bundles, source maps and large lockfiles, which
+    /// are left out by default.
+    pub fn include_artifacts(mut self, include: bool) -> Self {
+        self.include_artifacts = include;
+        self
+    }
+
+    /// Settings for resolving imports when building the PageRank graph.
+    /// Package manifests found among the indexed files are added on top.
+    pub fn resolve_options(mut self, options: ResolveOptions) -> Self {
+        self.resolve_options = options;
+        self
+    }
+
+    /// Damping and convergence settings for PageRank over the import graph.
+    pub fn pagerank(mut self, params: PageRankParams) -> Self {
+        self.pagerank = params;
+        self
+    }
+
+    /// Replace the stop-word list dropped from indexed terms.
+    ///
+    /// Must match the list used at query time. An existing index built with a
+    /// different list is rebuilt from scratch.
+    pub fn stop_words(mut self, stop_words: StopWords) -> Self {
+        self.stop_words = stop_words;
+        self
+    }
+
+    fn read(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        match self.provider {
+            Some(provider) => provider.read(path),
+            None => DiskFiles::new(self.root).read(path),
+        }
+    }
+
+    /// Whether entries of `existing` can be carried forward by this builder.
+    pub fn is_compatible(&self, existing: &DeepIndex) -> bool {
+        existing.stop_words == self.stop_words.to_vec()
     }
 
     /// Build a deep index from a list of scanned file metadata.
     ///
     /// When `existing` is provided, files whose SHA-256 matches the existing
-    /// entry are carried forward without re-reading or re-indexing.
+    /// entry are carried forward without re-reading or re-indexing. Files
+    /// that cannot be read, are not UTF-8 or are too large are left out and
+    /// listed in [`DeepIndex::errors`]; binary files are skipped silently.
     ///
     /// Returns `(index, reindexed_count)` — the number of files that were
     /// actually re-indexed (0 means nothing changed).
@@ -27,51 +104,170 @@ impl<'a> IndexBuilder<'a> {
         files: &[FileInfo],
         existing: Option<&DeepIndex>,
     ) -> anyhow::Result<(DeepIndex, usize)> {
-        use std::sync::atomic::{AtomicUsize, Ordering};
-        let reindexed = AtomicUsize::new(0);
+        let (index, stats) = self.build_with_stats(files, existing)?;
+        Ok((index, stats.files_indexed))
+    }
 
-        // Process files in parallel, collecting entries and raw imports
-        let results: Vec<(String, FileEntry, Language, Vec<String>)> = files
+    /// Like [`IndexBuilder::build`], also reporting where the time went.
+    pub fn build_with_stats(
+        &self,
+        files: &[FileInfo],
+        existing: Option<&DeepIndex>,
+    ) -> anyhow::Result<(DeepIndex, BuildStats)> {
+        let started = Instant::now();
+        let existing = existing.filter(|idx| self.is_compatible(idx));
+
+        // Process files in parallel, collecting entries, raw imports and timings
+        type FileResult = (
+            String,
+            FileEntry,
+            Language,
+            WeightedImports,
+            FileStageTimes,
+            bool,
+        );
+        let skipped = AtomicUsize::new(0);
+        let skip = || -> Option<FileResult> {
+            skipped.fetch_add(1, Ordering::Relaxed);
+            None
+        };
+        let errors = Mutex::new(Vec::new());
+        let fail = |info: &FileInfo, kind, detail: String| -> Option<FileResult> {
+            errors
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(IndexError {
+                    path: info.path.clone(),
+                    kind,
+                    detail,
+                });
+            None
+        };
+        let results: Vec<FileResult> = files
             .par_iter()
             .filter_map(|info| {
+                let mut times = FileStageTimes::default();
+                let check_minified = !self.include_artifacts && artifacts::may_be_minified(info);
+                if !self.include_artifacts
+                    && artifacts::classify_path(&info.path, info.size).is_some()
+                {
+                    return skip();
+                }
+
                 // Skip unchanged files — carry forward existing entry
                 if let Some(existing) = existing
                     && let Some(old_entry) = existing.files.get(&info.path)
                     && old_entry.sha256 == info.sha256
                 {
                     // Still need to read content for import extraction
-                    let full_path = self.root.join(&info.path);
-                    let imports = if info.language.is_programming_language() {
-                        fs::read_to_string(&full_path)
-                            .map(|c| topo_score::extract_imports(&c, info.language))
-                            .unwrap_or_default()
+                    let imports = if info.language.is_programming_language() || check_minified {
+                        let t = Instant::now();
+                        let content = self
+                            .read(&info.path)
+                            .ok()
+                            .and_then(|bytes| String::from_utf8(bytes).ok());
+                        times.read = t.elapsed();
+                        // The entry may predate the artifact check
+                        if check_minified && content.as_deref().is_some_and(artifacts::is_minified)
+                        {
+                            return skip();
+                        }
+                        let t = Instant::now();
+                        let imports = content
+                            .filter(|_| info.language.is_programming_language())
+                            .map(|c| topo_score::extract_weighted_imports(&c, info.language))
+                            .unwrap_or_default();
+                        times.imports = t.elapsed();
+                        imports
                     } else {
                         Vec::new()
                     };
-                    return Some((info.path.clone(), old_entry.clone(), info.language, imports));
+                    return Some((
+                        info.path.clone(),
+                        old_entry.clone(),
+                        info.language,
+                        imports,
+                        times,
+                        false,
+                    ));
                 }
 
-                let full_path = self.root.join(&info.path);
-                let content = fs::read_to_string(&full_path).ok()?;
-                let entry = build_file_entry(info, &content);
+                if info.size > MAX_FILE_BYTES {
+                    return fail(
+                        info,
+                        IndexErrorKind::TooLarge,
+                        format!("{} bytes, over the {MAX_FILE_BYTES} byte limit", info.size),
+                    );
+                }
+                let t = Instant::now();
+                let bytes = match self.read(&info.path) {
+                    Ok(bytes) => bytes,
+                    Err(e) => return fail(info, IndexErrorKind::Unreadable, e.to_string()),
+                };
+                times.read = t.elapsed();
+                let content = match String::from_utf8(bytes) {
+                    Ok(content) => content,
+                    Err(e) if is_binary(e.as_bytes()) => return None,
+                    Err(e) => {
+                        return fail(info, IndexErrorKind::NotUtf8, e.utf8_error().to_string());
+                    }
+                };
+                if check_minified && artifacts::is_minified(&content) {
+                    return skip();
+                }
+                let entry = build_file_entry(info, &content, &self.stop_words, &mut times);
+                let t = Instant::now();
                 let imports = if info.language.is_programming_language() {
-                    topo_score::extract_imports(&content, info.language)
+                    topo_score::extract_weighted_imports(&content, info.language)
                 } else {
                     Vec::new()
                 };
-                reindexed.fetch_add(1, Ordering::Relaxed);
-                Some((info.path.clone(), entry, info.language, imports))
+                times.imports = t.elapsed();
+                Some((
+                    info.path.clone(),
+                    entry,
+                    info.language,
+                    imports,
+                    times,
+                    true,
+                ))
             })
             .collect();
 
-        let reindexed_count = reindexed.load(Ordering::Relaxed);
+        let mut errors = errors.into_inner().unwrap_or_else(PoisonError::into_inner);
+        errors.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut stats = BuildStats {
+            artifacts_skipped: skipped.into_inner(),
+            ..Default::default()
+        };
+        let mut timings = Vec::with_capacity(results.len());
+        for (path, _, lang, _, times, reindexed) in &results {
+            if *reindexed {
+                stats.files_indexed += 1;
+            } else {
+                stats.files_reused += 1;
+            }
+            stats.read += times.read;
+            stats.tokenize += times.tokenize;
+            stats.imports += times.imports;
+            *stats
+                .chunk_by_language
+                .entry(*lang)
+                .or_insert(Duration::ZERO) += times.chunk;
+            timings.push(FileTiming {
+                path: path.clone(),
+                language: *lang,
+                elapsed: times.total(),
+            });
+        }
+        stats.set_slowest(timings);
 
         // Split into entries and imports
         let mut entries: Vec<(String, FileEntry)> = Vec::with_capacity(results.len());
-        let mut file_imports: Vec<(String, Language, Vec<String>)> =
+        let mut file_imports: Vec<(String, Language, WeightedImports)> =
             Vec::with_capacity(results.len());
 
-        for (path, entry, lang, imports) in results {
+        for (path, entry, lang, imports, _, _) in results {
             if !imports.is_empty() {
                 file_imports.push((path.clone(), lang, imports));
             }
@@ -96,78 +292,220 @@ impl<'a> IndexBuilder<'a> {
         }
 
         // Build import graph and compute PageRank
+        let graph_started = Instant::now();
         let all_paths: Vec<&str> = entries.iter().map(|(p, _)| p.as_str()).collect();
-        let graph = topo_score::build_import_graph(&file_imports, &all_paths);
-        let pagerank_scores = graph.normalized_pagerank();
+        let options = self
+            .resolve_options
+            .clone()
+            .with_manifests(self.root, &all_paths);
+        let graph = topo_score::build_import_graph_with(&file_imports, &all_paths, &options);
+        // Seed PageRank with the previous build's scores, and keep them
+        // outright while few imports changed among the same files and the
+        // settings are the same
+        let (pagerank_scores, kept) = match existing {
+            Some(existing) => {
+                stats.edge_churn = edge_churn(existing, &graph);
+                let same_files = existing.pagerank_scores.len() == all_paths.len()
+                    && all_paths
+                        .iter()
+                        .all(|p| existing.pagerank_scores.contains_key(*p));
+                let same_settings = existing.pagerank_settings == self.pagerank.fingerprint();
+                if same_files && same_settings && !self.pagerank.needs_recompute(stats.edge_churn) {
+                    (existing.pagerank_scores.clone(), true)
+                } else {
+                    let (scores, iterations) =
+                        graph.normalized_pagerank_from(&self.pagerank, &existing.pagerank_scores);
+                    stats.pagerank_iterations = iterations;
+                    stats.pagerank_warm = true;
+                    (scores, false)
+                }
+            }
+            None => {
+                stats.edge_churn = 1.0;
+                let (scores, iterations) =
+                    graph.normalized_pagerank_from(&self.pagerank, &HashMap::new());
+                stats.pagerank_iterations = iterations;
+                (scores, false)
+            }
+        };
+        // Record the edges the scores were computed from, so churn keeps
+        // adding up across builds that keep the scores
+        for (path, entry) in &mut entries {
+            entry.imports = match existing.and_then(|e| e.files.get(path.as_str())) {
+                Some(previous) if kept => previous.imports.clone(),
+                _ => graph.imports_of(path).to_vec(),
+            };
+        }
+        stats.graph = graph_started.elapsed();
 
         let file_map: HashMap<String, FileEntry> = entries.into_iter().collect();
+        stats.wall = started.elapsed();
+        log::debug!(
+            "Indexed {} files, reused {}; import graph {} nodes in {:.1?}, total {:.1?}",
+            stats.files_indexed,
+            stats.files_reused,
+            pagerank_scores.len(),
+            stats.graph,
+            stats.wall
+        );
+        log::trace!(
+            "PageRank: {} ({} iterations), {:.1}% of import edges changed",
+            if kept {
+                "kept previous scores"
+            } else if stats.pagerank_warm {
+                "warm start"
+            } else {
+                "cold start"
+            },
+            stats.pagerank_iterations,
+            stats.edge_churn * 100.0
+        );
 
         Ok((
             DeepIndex {
-                version: 2,
+                version: INDEX_VERSION,
                 files: file_map,
                 avg_doc_length,
                 total_docs,
                 doc_frequencies,
                 pagerank_scores,
+                pagerank_settings: self.pagerank.fingerprint(),
+                stop_words: self.stop_words.to_vec(),
+                errors,
             },
-            reindexed_count,
+            stats,
         ))
     }
 }
 
+/// Share of import edges added or removed since `existing` was built, out
+/// of the larger of the two graphs' edge counts.
+fn edge_churn(existing: &DeepIndex, graph: &ImportGraph) -> f64 {
+    let before: HashSet<(&str, &str)> = existing
+        .files
+        .iter()
+        .flat_map(|(from, e)| e.imports.iter().map(move |to| (from.as_str(), to.as_str())))
+        .collect();
+    let after: HashSet<(&str, &str)> = graph.edges().map(|(from, to, _)| (from, to)).collect();
+    let total = before.len().max(after.len());
+    if total == 0 {
+        return 0.0;
+    }
+    before.symmetric_difference(&after).count() as f64 / total as f64
+}
+
+/// Whether a build leaves `info` out on purpose rather than listing it
+/// among the errors: an artifact, or a binary file. The file is read from
+/// under `root` unless its name and size settle it.
+pub fn is_skipped(root: &Path, info: &FileInfo) -> bool {
+    if artifacts::classify_path(&info.path, info.size).is_some() {
+        return true;
+    }
+    let Ok(bytes) = std::fs::read(root.join(&info.path)) else {
+        return false;
+    };
+    match String::from_utf8(bytes) {
+        Ok(content) => artifacts::may_be_minified(info) && artifacts::is_minified(&content),
+        Err(e) => is_binary(e.as_bytes()),
+    }
+}
+
+/// Whether `bytes` look binary: a NUL among the leading bytes, as git
+/// decides.
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
 /// Build a FileEntry from file metadata and content.
-fn build_file_entry(info: &FileInfo, content: &str) -> FileEntry {
+fn build_file_entry(
+    info: &FileInfo,
+    content: &str,
+    stop_words: &StopWords,
+    times: &mut FileStageTimes,
+) -> FileEntry {
     let mut term_frequencies: HashMap<String, TermFreqs> = HashMap::new();
+    let mut positions: HashMap<String, TermPositions> = HashMap::new();
+    let t = Instant::now();
 
     // Tokenize filename for filename field
-    let filename_tokens = tokenize_path(&info.path);
-    for token in &filename_tokens {
+    let filename_tokens = tokenize_path(&info.path, stop_words);
+    for (i, token) in filename_tokens.iter().enumerate() {
         term_frequencies.entry(token.clone()).or_default().filename += 1;
+        positions
+            .entry(token.clone())
+            .or_default()
+            .filename
+            .push(i as u32);
     }
 
     // Tokenize content for body field
-    let body_tokens = tokenize_content(content);
+    let body_tokens = tokenize_content(content, stop_words);
     let doc_length = body_tokens.len() as u32;
-    for token in &body_tokens {
+    for (i, token) in body_tokens.iter().enumerate() {
         term_frequencies.entry(token.clone()).or_default().body += 1;
+        positions
+            .entry(token.clone())
+            .or_default()
+            .body
+            .push(i as u32);
     }
 
+    times.tokenize += t.elapsed();
+
     // Extract chunks via regex (fast indexing pass)
+    let t = Instant::now();
     let chunks = RegexChunker.chunk(content, info.language);
+    let summary = topo_treesit::summarize(content, info.language);
+    times.chunk = t.elapsed();
+    let t = Instant::now();
 
-    // Tokenize chunk names for symbols field
+    // Tokenize chunk names for symbols field; a gap keeps names apart
+    let mut position = 0u32;
     for chunk in &chunks {
         if matches!(
             chunk.kind,
             ChunkKind::Function | ChunkKind::Type | ChunkKind::Impl
         ) {
-            let symbol_tokens = tokenize_identifier(&chunk.name);
+            let symbol_tokens = tokenize_identifier(&chunk.name, stop_words);
             for token in &symbol_tokens {
                 term_frequencies.entry(token.clone()).or_default().symbols += 1;
+                positions
+                    .entry(token.clone())
+                    .or_default()
+                    .symbols
+                    .push(position);
+                position += 1;
             }
+            position += 1;
         }
     }
 
+    times.tokenize += t.elapsed();
+
     FileEntry {
         sha256: info.sha256,
         chunks,
         term_frequencies,
+        positions,
         doc_length,
+        summary,
+        signature: topo_score::minhash_signature(content),
+        imports: Vec::new(),
     }
 }
 
 /// Tokenize a file path into search terms.
-fn tokenize_path(path: &str) -> Vec<String> {
+fn tokenize_path(path: &str, stop_words: &StopWords) -> Vec<String> {
     path.split(['/', '\\', '.', '-', '_'])
         .flat_map(split_camel_case)
         .filter(|t| t.len() >= 2)
         .map(|t| t.to_lowercase())
+        .filter(|t| !stop_words.contains(t))
         .collect()
 }
 
 /// Tokenize file content into search terms (whitespace-split, lowercased, min length 2).
-fn tokenize_content(content: &str) -> Vec<String> {
+fn tokenize_content(content: &str, stop_words: &StopWords) -> Vec<String> {
     content
         .split(|c: char| !c.is_alphanumeric() && c != '_')
         .flat_map(|word| {
@@ -177,15 +515,17 @@ fn tokenize_content(content: &str) -> Vec<String> {
         })
         .filter(|t| t.len() >= 2)
         .map(|t| t.to_lowercase())
+        .filter(|t| !stop_words.contains(t))
         .collect()
 }
 
 /// Tokenize a single identifier (function/type name).
-fn tokenize_identifier(name: &str) -> Vec<String> {
+fn tokenize_identifier(name: &str, stop_words: &StopWords) -> Vec<String> {
     name.split('_')
         .flat_map(split_camel_case)
         .filter(|t| t.len() >= 2)
         .map(|t| t.to_lowercase())
+        .filter(|t| !stop_words.contains(t))
         .collect()
 }
 
@@ -242,9 +582,140 @@ mod tests {
             language: Language::from_path(Path::new(path)),
             role: topo_core::FileRole::from_path(Path::new(path)),
             sha256: hash,
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
         }
     }
 
+    #[test]
+    fn build_reads_through_the_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("auth.rs"), "fn saved_login() {}\n").unwrap();
+        let unsaved = "fn unsaved_login() {}\n";
+        let overlay =
+            topo_core::Overlay::new(topo_core::DiskFiles::new(dir.path())).file("auth.rs", unsaved);
+
+        let files = vec![make_file_info("auth.rs", unsaved)];
+        let index = IndexBuilder::new(dir.path())
+            .provider(&overlay)
+            .build(&files, None)
+            .unwrap()
+            .0;
+        let entry = &index.files["auth.rs"];
+        assert!(entry.term_frequencies.contains_key("unsaved"));
+        assert!(!entry.term_frequencies.contains_key("saved"));
+    }
+
+    #[test]
+    fn pagerank_is_kept_or_warm_started_by_edge_churn() {
+        let dir = tempfile::tempdir().unwrap();
+        let build = |files: &[(&str, &str)], existing: Option<&DeepIndex>| {
+            let infos: Vec<FileInfo> = files
+                .iter()
+                .map(|(path, content)| {
+                    fs::write(dir.path().join(path), content).unwrap();
+                    make_file_info(path, content)
+                })
+                .collect();
+            IndexBuilder::new(dir.path())
+                .build_with_stats(&infos, existing)
+                .unwrap()
+        };
+
+        let (first, stats) = build(
+            &[
+                ("a.py", "import b\n"),
+                ("b.py", "x = 1\n"),
+                ("c.py", "import b\n"),
+            ],
+            None,
+        );
+        assert_eq!(first.files["a.py"].imports, ["b.py"]);
+        assert!(stats.pagerank_iterations > 0 && !stats.pagerank_warm);
+
+        // Same imports: the scores carry over without iterating
+        let (second, stats) = build(
+            &[
+                ("a.py", "import b\n# edited\n"),
+                ("b.py", "x = 1\n"),
+                ("c.py", "import b\n"),
+            ],
+            Some(&first),
+        );
+        assert_eq!(stats.edge_churn, 0.0);
+        assert_eq!(stats.pagerank_iterations, 0);
+        assert_eq!(second.pagerank_scores, first.pagerank_scores);
+
+        // A changed import: recomputed from the previous scores
+        let (third, stats) = build(
+            &[
+                ("a.py", "import b\n# edited\n"),
+                ("b.py", "x = 1\n"),
+                ("c.py", "import a\n"),
+            ],
+            Some(&second),
+        );
+        assert_eq!(stats.edge_churn, 1.0);
+        assert!(stats.pagerank_warm && stats.pagerank_iterations > 0);
+        assert!(third.pagerank_scores["a.py"] > first.pagerank_scores["a.py"]);
+        assert_eq!(third.files["c.py"].imports, ["a.py"]);
+    }
+
+    #[test]
+    fn is_skipped_matches_what_builds_leave_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = "var a=1;".repeat(1_000);
+        let source = "const a = 1;\n".repeat(1_000);
+        fs::write(dir.path().join("bundle.js"), &bundle).unwrap();
+        fs::write(dir.path().join("app.js"), &source).unwrap();
+        fs::write(
+            dir.path().join("logo.png"),
+            b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR",
+        )
+        .unwrap();
+
+        assert!(is_skipped(
+            dir.path(),
+            &make_file_info("bundle.js", &bundle)
+        ));
+        assert!(!is_skipped(dir.path(), &make_file_info("app.js", &source)));
+        assert!(is_skipped(dir.path(), &make_file_info("app.js.map", "{}")));
+        assert!(is_skipped(dir.path(), &make_file_info("logo.png", "")));
+        assert!(!is_skipped(dir.path(), &make_file_info("gone.rs", "")));
+    }
+
+    #[test]
+    fn pagerank_is_recomputed_when_its_settings_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let infos: Vec<FileInfo> = [("a.py", "import b\n"), ("b.py", "x = 1\n")]
+            .iter()
+            .map(|(path, content)| {
+                fs::write(dir.path().join(path), content).unwrap();
+                make_file_info(path, content)
+            })
+            .collect();
+        let (first, _) = IndexBuilder::new(dir.path())
+            .build_with_stats(&infos, None)
+            .unwrap();
+        assert_eq!(
+            first.pagerank_settings,
+            PageRankParams::default().fingerprint()
+        );
+
+        let params = PageRankParams::default().damping(0.5);
+        let (second, stats) = IndexBuilder::new(dir.path())
+            .pagerank(params)
+            .build_with_stats(&infos, Some(&first))
+            .unwrap();
+        assert_eq!(stats.edge_churn, 0.0);
+        assert!(stats.pagerank_iterations > 0);
+        assert_eq!(second.pagerank_settings, params.fingerprint());
+        assert_ne!(second.pagerank_scores, first.pagerank_scores);
+    }
+
     #[test]
     fn build_index_from_files() {
         let dir = tempfile::tempdir().unwrap();
@@ -458,7 +929,7 @@ type Config struct {
 
     #[test]
     fn tokenize_path_splits_correctly() {
-        let tokens = tokenize_path("src/auth/middleware.rs");
+        let tokens = tokenize_path("src/auth/middleware.rs", &StopWords::default());
         assert!(tokens.contains(&"src".to_string()));
         assert!(tokens.contains(&"auth".to_string()));
         assert!(tokens.contains(&"middleware".to_string()));
@@ -466,19 +937,119 @@ type Config struct {
 
     #[test]
     fn tokenize_path_windows_separators() {
-        let tokens = tokenize_path(r"src\auth\middleware.rs");
+        let tokens = tokenize_path(r"src\auth\middleware.rs", &StopWords::default());
         assert!(tokens.contains(&"src".to_string()));
         assert!(tokens.contains(&"auth".to_string()));
         assert!(tokens.contains(&"middleware".to_string()));
         // Should produce same tokens as Unix path
-        assert_eq!(tokens, tokenize_path("src/auth/middleware.rs"));
+        assert_eq!(
+            tokens,
+            tokenize_path("src/auth/middleware.rs", &StopWords::default())
+        );
     }
 
     #[test]
     fn tokenize_content_handles_code() {
-        let tokens = tokenize_content("fn authenticate(token: &str) -> bool {}");
+        let tokens = tokenize_content(
+            "fn authenticate(token: &str) -> bool {}",
+            &StopWords::default(),
+        );
         assert!(tokens.contains(&"authenticate".to_string()));
         assert!(tokens.contains(&"token".to_string()));
         assert!(tokens.contains(&"bool".to_string()));
     }
+
+    #[test]
+    fn index_applies_stop_words() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = "fn is_ready() -> bool { acme_check() }\n";
+        fs::write(dir.path().join("ready.rs"), content).unwrap();
+        let files = vec![make_file_info("ready.rs", content)];
+
+        let (default_index, _) = IndexBuilder::new(dir.path()).build(&files, None).unwrap();
+        let entry = &default_index.files["ready.rs"];
+        assert!(!entry.term_frequencies.contains_key("is"));
+        assert!(entry.term_frequencies.contains_key("acme"));
+
+        let stop_words = StopWords::default()
+            .with_words(&["acme"])
+            .without_words(&["is"]);
+        let (custom, reindexed) = IndexBuilder::new(dir.path())
+            .stop_words(stop_words)
+            .build(&files, Some(&default_index))
+            .unwrap();
+        // A different stop list invalidates the existing entries
+        assert_eq!(reindexed, 1);
+        let entry = &custom.files["ready.rs"];
+        assert!(entry.term_frequencies.contains_key("is"));
+        assert!(!entry.term_frequencies.contains_key("acme"));
+        assert!(custom.stop_words.contains(&"acme".to_string()));
+    }
+
+    #[test]
+    fn index_leaves_out_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = "fn main() {}\n";
+        let bundle = "var a=1;".repeat(1_000);
+        fs::write(dir.path().join("main.rs"), source).unwrap();
+        fs::write(dir.path().join("app.js"), &bundle).unwrap();
+        fs::write(dir.path().join("app.js.map"), "{}").unwrap();
+        let files = vec![
+            make_file_info("main.rs", source),
+            make_file_info("app.js", &bundle),
+            make_file_info("app.js.map", "{}"),
+        ];
+
+        let (index, stats) = IndexBuilder::new(dir.path())
+            .build_with_stats(&files, None)
+            .unwrap();
+        assert_eq!(index.files.keys().collect::<Vec<_>>(), vec!["main.rs"]);
+        assert_eq!(stats.artifacts_skipped, 2);
+
+        let (all, _) = IndexBuilder::new(dir.path())
+            .include_artifacts(true)
+            .build(&files, None)
+            .unwrap();
+        assert_eq!(all.total_docs, 3);
+
+        // Entries indexed before are dropped once artifacts are left out
+        let (index, stats) = IndexBuilder::new(dir.path())
+            .build_with_stats(&files, Some(&all))
+            .unwrap();
+        assert_eq!(index.total_docs, 1);
+        assert_eq!(stats.artifacts_skipped, 2);
+    }
+
+    #[test]
+    fn index_reports_files_it_cannot_index() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.path().join("latin1.txt"), b"caf\xe9\n").unwrap();
+        fs::write(dir.path().join("logo.png"), b"\x89PNG\x00\x00\xff").unwrap();
+        let mut huge = make_file_info("huge.sql", "");
+        huge.size = MAX_FILE_BYTES + 1;
+        let files = vec![
+            make_file_info("main.rs", "fn main() {}\n"),
+            make_file_info("latin1.txt", ""),
+            make_file_info("logo.png", ""),
+            make_file_info("gone.rs", ""),
+            huge,
+        ];
+
+        let index = IndexBuilder::new(dir.path()).build(&files, None).unwrap().0;
+        assert_eq!(index.total_docs, 1);
+        let errors: Vec<(&str, IndexErrorKind)> = index
+            .errors
+            .iter()
+            .map(|e| (e.path.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                ("gone.rs", IndexErrorKind::Unreadable),
+                ("huge.sql", IndexErrorKind::TooLarge),
+                ("latin1.txt", IndexErrorKind::NotUtf8),
+            ]
+        );
+    }
 }
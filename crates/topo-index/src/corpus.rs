@@ -0,0 +1,216 @@
+//! Corpus statistics recorded on every index save, so `topo inspect` can
+//! flag builds whose shape changed sharply from the one before. Such drift
+//! usually means an ignore rule or the scanner changed, not the code.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use topo_core::{DeepIndex, Language};
+
+/// History file inside `.topo/`.
+const HISTORY_FILE: &str = "corpus.jsonl";
+/// Snapshots kept in the history; older ones are dropped.
+const MAX_SNAPSHOTS: usize = 20;
+/// Relative change in file count, vocabulary or average document length
+/// that counts as drift.
+const DRIFT_RATIO: f64 = 0.25;
+/// A language must gain or lose at least this many files to be flagged.
+const MIN_LANGUAGE_CHANGE: usize = 10;
+/// Relative change in a language's file count that counts as drift.
+const LANGUAGE_DRIFT_RATIO: f64 = 0.5;
+
+/// Summary statistics of one index build.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorpusSnapshot {
+    /// Seconds since the Unix epoch.
+    pub built_at: u64,
+    pub files: u32,
+    pub avg_doc_length: f64,
+    /// Distinct terms across the corpus.
+    pub vocabulary: usize,
+    /// Indexed files per language.
+    pub languages: BTreeMap<String, usize>,
+}
+
+/// One statistic that moved more than expected between two builds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    /// `files`, `vocabulary`, `avg_doc_length` or `language:<name>`.
+    pub metric: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+impl Drift {
+    /// Relative change, e.g. `-0.4` for a 40% drop; infinite from zero.
+    pub fn change(&self) -> f64 {
+        if self.before == 0.0 {
+            f64::INFINITY
+        } else {
+            (self.after - self.before) / self.before
+        }
+    }
+}
+
+impl CorpusSnapshot {
+    /// The statistics of `index`, stamped `built_at`.
+    pub fn of(index: &DeepIndex, built_at: u64) -> Self {
+        let mut languages = BTreeMap::new();
+        for path in index.files.keys() {
+            let language = Language::from_path(Path::new(path));
+            *languages.entry(language.as_str().to_string()).or_default() += 1;
+        }
+        Self {
+            built_at,
+            files: index.total_docs,
+            avg_doc_length: index.avg_doc_length,
+            vocabulary: index.doc_frequencies.len(),
+            languages,
+        }
+    }
+
+    /// Statistics that moved sharply since `previous`.
+    pub fn drift_from(&self, previous: &Self) -> Vec<Drift> {
+        let mut drifts = Vec::new();
+        let mut check = |metric: &str, before: f64, after: f64| {
+            let drift = Drift {
+                metric: metric.to_string(),
+                before,
+                after,
+            };
+            if drift.change().abs() >= DRIFT_RATIO {
+                drifts.push(drift);
+            }
+        };
+        check("files", previous.files as f64, self.files as f64);
+        check(
+            "vocabulary",
+            previous.vocabulary as f64,
+            self.vocabulary as f64,
+        );
+        check(
+            "avg_doc_length",
+            previous.avg_doc_length,
+            self.avg_doc_length,
+        );
+
+        let names: std::collections::BTreeSet<&String> = previous
+            .languages
+            .keys()
+            .chain(self.languages.keys())
+            .collect();
+        for name in names {
+            let before = previous.languages.get(name).copied().unwrap_or(0);
+            let after = self.languages.get(name).copied().unwrap_or(0);
+            let drift = Drift {
+                metric: format!("language:{name}"),
+                before: before as f64,
+                after: after as f64,
+            };
+            if before.abs_diff(after) >= MIN_LANGUAGE_CHANGE
+                && drift.change().abs() >= LANGUAGE_DRIFT_RATIO
+            {
+                drifts.push(drift);
+            }
+        }
+        drifts
+    }
+}
+
+/// Path to the snapshot history.
+pub fn corpus_history_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(".topo").join(HISTORY_FILE)
+}
+
+/// Every recorded snapshot, oldest first. Malformed lines are skipped.
+pub fn corpus_history(repo_root: &Path) -> anyhow::Result<Vec<CorpusSnapshot>> {
+    let path = corpus_history_path(repo_root);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Append the statistics of `index`, keeping the last [`MAX_SNAPSHOTS`].
+pub fn record_snapshot(index: &DeepIndex, repo_root: &Path) -> anyhow::Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut history = corpus_history(repo_root)?;
+    history.push(CorpusSnapshot::of(index, now));
+    let keep = history.len().saturating_sub(MAX_SNAPSHOTS);
+
+    let path = corpus_history_path(repo_root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut out = Vec::new();
+    for snapshot in &history[keep..] {
+        writeln!(out, "{}", serde_json::to_string(snapshot)?)?;
+    }
+    fs::write(path, out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(files: u32, vocabulary: usize, languages: &[(&str, usize)]) -> CorpusSnapshot {
+        CorpusSnapshot {
+            built_at: 0,
+            files,
+            avg_doc_length: 100.0,
+            vocabulary,
+            languages: languages
+                .iter()
+                .map(|(name, n)| (name.to_string(), *n))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn small_changes_are_not_drift() {
+        let before = snapshot(1000, 5000, &[("rust", 900), ("markdown", 100)]);
+        let after = snapshot(1050, 5200, &[("rust", 940), ("markdown", 105)]);
+        assert!(after.drift_from(&before).is_empty());
+    }
+
+    #[test]
+    fn sharp_changes_are_drift() {
+        let before = snapshot(1000, 5000, &[("rust", 900), ("markdown", 100)]);
+        let after = snapshot(
+            3000,
+            5100,
+            &[("rust", 900), ("markdown", 100), ("javascript", 2000)],
+        );
+        let drifts = after.drift_from(&before);
+        let metrics: Vec<&str> = drifts.iter().map(|d| d.metric.as_str()).collect();
+        assert_eq!(metrics, vec!["files", "language:javascript"]);
+        assert_eq!(drifts[0].change(), 2.0);
+        assert!(drifts[1].change().is_infinite());
+    }
+
+    #[test]
+    fn history_keeps_the_latest_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(corpus_history(dir.path()).unwrap().is_empty());
+        let index = crate::IndexBuilder::new(dir.path())
+            .build(&[], None)
+            .unwrap()
+            .0;
+        for _ in 0..MAX_SNAPSHOTS + 2 {
+            record_snapshot(&index, dir.path()).unwrap();
+        }
+        let history = corpus_history(dir.path()).unwrap();
+        assert_eq!(history.len(), MAX_SNAPSHOTS);
+        assert_eq!(history[0].files, 0);
+    }
+}
@@ -0,0 +1,414 @@
+use crate::store::embeddings_dir;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use topo_score::{Embedder, InputKind};
+
+const META_FILE: &str = "meta.json";
+const KEYS_FILE: &str = "keys.bin";
+const VECTORS_FILE: &str = "vectors.f32";
+/// Subdirectory of the per-chunk cache.
+const CHUNKS_DIR: &str = "chunks";
+/// Bumped whenever the on-disk layout changes.
+const EMBEDDINGS_VERSION: u32 = 1;
+
+/// Size of a content-hash key in `keys.bin`.
+const KEY_BYTES: usize = 32;
+
+/// SHA-256 of the embedded text; unchanged code keeps its key across runs.
+pub type ContentKey = [u8; KEY_BYTES];
+
+/// Describes the vectors in the cache; a different model or dimension
+/// discards everything.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Meta {
+    version: u32,
+    model: String,
+    dims: usize,
+    count: usize,
+}
+
+/// Row storage: the memory-mapped `vectors.f32`, or decoded rows when the
+/// mapping cannot be viewed as `f32` in place.
+enum Rows {
+    Mapped(Mmap),
+    Owned(Vec<f32>),
+}
+
+impl Rows {
+    fn as_slice(&self) -> &[f32] {
+        match self {
+            Self::Mapped(map) => {
+                // SAFETY: `open` only keeps a mapping whose bytes align to f32
+                // with no remainder, on a little-endian target.
+                let (_, rows, _) = unsafe { map.align_to::<f32>() };
+                rows
+            }
+            Self::Owned(rows) => rows,
+        }
+    }
+}
+
+/// Vectors keyed by content hash, stored under `.topo/embeddings/`.
+///
+/// The layout is flat so it can be memory-mapped without parsing:
+/// `keys.bin` holds sorted 32-byte keys, `vectors.f32` the rows for those
+/// keys in the same order as little-endian `f32`, and `meta.json` the model
+/// name and dimension. Lookups binary-search the keys and slice the mapping.
+pub struct EmbeddingCache {
+    dir: PathBuf,
+    model: String,
+    dims: usize,
+    keys: Vec<ContentKey>,
+    rows: Rows,
+    added: BTreeMap<ContentKey, Vec<f32>>,
+}
+
+impl EmbeddingCache {
+    /// Open the cache for `model`, starting empty when none exists or the
+    /// stored one was written by another model, dimension or layout.
+    pub fn open(root: &Path, model: &str, dims: usize) -> anyhow::Result<Self> {
+        Self::open_in(embeddings_dir(root), model, dims)
+    }
+
+    /// Open the per-chunk cache, kept apart from the per-file one so each
+    /// prunes only its own stale vectors.
+    pub fn open_chunks(root: &Path, model: &str, dims: usize) -> anyhow::Result<Self> {
+        Self::open_in(embeddings_dir(root).join(CHUNKS_DIR), model, dims)
+    }
+
+    fn open_in(dir: PathBuf, model: &str, dims: usize) -> anyhow::Result<Self> {
+        let mut cache = Self {
+            dir,
+            model: model.to_string(),
+            dims,
+            keys: Vec::new(),
+            rows: Rows::Owned(Vec::new()),
+            added: BTreeMap::new(),
+        };
+
+        let Ok(meta) = fs::read_to_string(cache.dir.join(META_FILE)) else {
+            return Ok(cache);
+        };
+        let Ok(meta) = serde_json::from_str::<Meta>(&meta) else {
+            return Ok(cache);
+        };
+        if meta.version != EMBEDDINGS_VERSION
+            || meta.model != model
+            || meta.dims != dims
+            || meta.count == 0
+        {
+            return Ok(cache);
+        }
+
+        let key_bytes = fs::read(cache.dir.join(KEYS_FILE))?;
+        let file = File::open(cache.dir.join(VECTORS_FILE))?;
+        // SAFETY: the cache files are only replaced by rename, never
+        // truncated in place, so the mapping stays valid while open.
+        let map = unsafe { Mmap::map(&file)? };
+        if key_bytes.len() != meta.count * KEY_BYTES || map.len() != meta.count * dims * 4 {
+            // Interrupted write or foreign files: rebuild
+            return Ok(cache);
+        }
+
+        cache.keys = key_bytes
+            .chunks_exact(KEY_BYTES)
+            .map(|k| k.try_into().expect("chunks_exact yields KEY_BYTES"))
+            .collect();
+        // SAFETY: only used to check alignment; the view is rebuilt on access
+        let (prefix, _, suffix) = unsafe { map.align_to::<f32>() };
+        cache.rows = if cfg!(target_endian = "little") && prefix.is_empty() && suffix.is_empty() {
+            Rows::Mapped(map)
+        } else {
+            Rows::Owned(
+                map.chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+            )
+        };
+        Ok(cache)
+    }
+
+    /// Directory holding the cache files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Model the cached vectors were produced by.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Length of every vector.
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Cached vector for `key`.
+    pub fn get(&self, key: &ContentKey) -> Option<&[f32]> {
+        if let Some(v) = self.added.get(key) {
+            return Some(v);
+        }
+        let i = self.keys.binary_search(key).ok()?;
+        Some(&self.rows.as_slice()[i * self.dims..(i + 1) * self.dims])
+    }
+
+    /// Add or replace the vector for `key`. Kept in memory until [`save`].
+    ///
+    /// [`save`]: EmbeddingCache::save
+    pub fn insert(&mut self, key: ContentKey, vector: Vec<f32>) -> anyhow::Result<()> {
+        if vector.len() != self.dims {
+            anyhow::bail!(
+                "embedding has {} dimensions, cache for {} expects {}",
+                vector.len(),
+                self.model,
+                self.dims
+            );
+        }
+        self.added.insert(key, vector);
+        Ok(())
+    }
+
+    /// Number of cached vectors, saved or not.
+    pub fn len(&self) -> usize {
+        self.keys
+            .iter()
+            .filter(|k| !self.added.contains_key(*k))
+            .count()
+            + self.added.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every cached vector, saved or not, in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (ContentKey, &[f32])> {
+        self.merged().into_iter()
+    }
+
+    fn merged(&self) -> BTreeMap<ContentKey, &[f32]> {
+        let mut merged: BTreeMap<ContentKey, &[f32]> = BTreeMap::new();
+        let rows = self.rows.as_slice();
+        for (i, key) in self.keys.iter().enumerate() {
+            merged.insert(*key, &rows[i * self.dims..(i + 1) * self.dims]);
+        }
+        for (key, vector) in &self.added {
+            merged.insert(*key, vector);
+        }
+        merged
+    }
+
+    /// Write the cache, dropping vectors whose key `keep` rejects so content
+    /// that no longer exists does not accumulate.
+    ///
+    /// Files are written beside the old ones and renamed into place, so a
+    /// reader never sees a half-written cache.
+    pub fn save(&self, keep: impl Fn(&ContentKey) -> bool) -> anyhow::Result<()> {
+        let mut merged = self.merged();
+        merged.retain(|k, _| keep(k));
+
+        let mut key_bytes = Vec::with_capacity(merged.len() * KEY_BYTES);
+        let mut vector_bytes = Vec::with_capacity(merged.len() * self.dims * 4);
+        for (key, vector) in &merged {
+            key_bytes.extend_from_slice(key);
+            for x in *vector {
+                vector_bytes.extend_from_slice(&x.to_le_bytes());
+            }
+        }
+        let meta = Meta {
+            version: EMBEDDINGS_VERSION,
+            model: self.model.clone(),
+            dims: self.dims,
+            count: merged.len(),
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        // Meta is removed first and written last: a crash in between leaves
+        // no meta, which `open` treats as an empty cache
+        let _ = fs::remove_file(self.dir.join(META_FILE));
+        write_replace(&self.dir.join(KEYS_FILE), &key_bytes)?;
+        write_replace(&self.dir.join(VECTORS_FILE), &vector_bytes)?;
+        write_replace(
+            &self.dir.join(META_FILE),
+            serde_json::to_string_pretty(&meta)?.as_bytes(),
+        )?;
+        Ok(())
+    }
+}
+
+fn write_replace(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Cache key for a piece of embedded text.
+pub fn content_key(text: &str) -> ContentKey {
+    Sha256::digest(text.as_bytes()).into()
+}
+
+/// Vectors for `texts`, asking `embedder` only for content not already in
+/// `cache`. New vectors are added to the cache; call [`EmbeddingCache::save`]
+/// to keep them.
+pub fn embed_cached(
+    cache: &mut EmbeddingCache,
+    embedder: &dyn Embedder,
+    texts: &[&str],
+) -> anyhow::Result<Vec<Vec<f32>>> {
+    if embedder.model() != cache.model() || embedder.dims() != cache.dims() {
+        anyhow::bail!(
+            "embedder {} ({} dims) does not match cache for {} ({} dims)",
+            embedder.model(),
+            embedder.dims(),
+            cache.model(),
+            cache.dims()
+        );
+    }
+    let keys: Vec<ContentKey> = texts.iter().map(|t| content_key(t)).collect();
+    let mut missing: Vec<(ContentKey, &str)> = Vec::new();
+    for (key, text) in keys.iter().zip(texts) {
+        if cache.get(key).is_none() && !missing.iter().any(|(k, _)| k == key) {
+            missing.push((*key, text));
+        }
+    }
+    if !missing.is_empty() {
+        let inputs: Vec<&str> = missing.iter().map(|(_, t)| *t).collect();
+        let vectors = embedder.embed(&inputs, InputKind::Document)?;
+        for ((key, _), vector) in missing.into_iter().zip(vectors) {
+            cache.insert(key, vector)?;
+        }
+    }
+    Ok(keys
+        .iter()
+        .map(|k| cache.get(k).map(<[f32]>::to_vec).unwrap_or_default())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vectors_survive_save_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = content_key("fn a() {}");
+        let b = content_key("fn b() {}");
+
+        let mut cache = EmbeddingCache::open(dir.path(), "test-model", 3).unwrap();
+        assert!(cache.is_empty());
+        cache.insert(a, vec![1.0, 2.0, 3.0]).unwrap();
+        cache.insert(b, vec![4.0, 5.0, 6.0]).unwrap();
+        cache.save(|_| true).unwrap();
+
+        let cache = EmbeddingCache::open(dir.path(), "test-model", 3).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&a), Some(&[1.0, 2.0, 3.0][..]));
+        assert_eq!(cache.get(&b), Some(&[4.0, 5.0, 6.0][..]));
+        assert_eq!(cache.get(&content_key("fn c() {}")), None);
+    }
+
+    #[test]
+    fn chunk_cache_is_separate() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = content_key("fn a() {}");
+        let mut chunks = EmbeddingCache::open_chunks(dir.path(), "m", 2).unwrap();
+        chunks.insert(key, vec![1.0, 0.0]).unwrap();
+        chunks.save(|_| true).unwrap();
+
+        // Pruning the per-file cache leaves chunk vectors alone
+        EmbeddingCache::open(dir.path(), "m", 2)
+            .unwrap()
+            .save(|_| false)
+            .unwrap();
+        let chunks = EmbeddingCache::open_chunks(dir.path(), "m", 2).unwrap();
+        assert_eq!(chunks.get(&key), Some(&[1.0, 0.0][..]));
+        assert!(chunks.dir().ends_with("embeddings/chunks"));
+        assert!(EmbeddingCache::open(dir.path(), "m", 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn save_drops_keys_no_longer_present() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = content_key("old");
+        let b = content_key("new");
+
+        let mut cache = EmbeddingCache::open(dir.path(), "m", 2).unwrap();
+        cache.insert(a, vec![1.0, 1.0]).unwrap();
+        cache.save(|_| true).unwrap();
+
+        let mut cache = EmbeddingCache::open(dir.path(), "m", 2).unwrap();
+        cache.insert(b, vec![2.0, 2.0]).unwrap();
+        cache.save(|k| *k == b).unwrap();
+
+        let cache = EmbeddingCache::open(dir.path(), "m", 2).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&a), None);
+        assert_eq!(cache.get(&b), Some(&[2.0, 2.0][..]));
+    }
+
+    #[test]
+    fn other_model_or_dims_starts_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = EmbeddingCache::open(dir.path(), "m", 2).unwrap();
+        cache.insert(content_key("x"), vec![1.0, 1.0]).unwrap();
+        cache.save(|_| true).unwrap();
+
+        assert!(
+            EmbeddingCache::open(dir.path(), "other", 2)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(EmbeddingCache::open(dir.path(), "m", 4).unwrap().is_empty());
+        assert_eq!(EmbeddingCache::open(dir.path(), "m", 2).unwrap().len(), 1);
+    }
+
+    /// Embeds each text as `[len, 1]` and counts the texts it was asked for.
+    struct CountingEmbedder(std::cell::Cell<usize>);
+
+    impl Embedder for CountingEmbedder {
+        fn model(&self) -> &str {
+            "counting"
+        }
+        fn dims(&self) -> usize {
+            2
+        }
+        fn embed(&self, texts: &[&str], _: InputKind) -> anyhow::Result<Vec<Vec<f32>>> {
+            self.0.set(self.0.get() + texts.len());
+            Ok(texts.iter().map(|t| vec![t.len() as f32, 1.0]).collect())
+        }
+    }
+
+    #[test]
+    fn embed_cached_only_embeds_new_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let embedder = CountingEmbedder(std::cell::Cell::new(0));
+
+        let mut cache = EmbeddingCache::open(dir.path(), "counting", 2).unwrap();
+        let vectors = embed_cached(&mut cache, &embedder, &["ab", "abc", "ab"]).unwrap();
+        assert_eq!(
+            vectors,
+            vec![vec![2.0, 1.0], vec![3.0, 1.0], vec![2.0, 1.0]]
+        );
+        assert_eq!(embedder.0.get(), 2);
+        cache.save(|_| true).unwrap();
+
+        let mut cache = EmbeddingCache::open(dir.path(), "counting", 2).unwrap();
+        embed_cached(&mut cache, &embedder, &["abc", "abcd"]).unwrap();
+        assert_eq!(embedder.0.get(), 3);
+
+        let mut other = EmbeddingCache::open(dir.path(), "other", 2).unwrap();
+        assert!(embed_cached(&mut other, &embedder, &["a"]).is_err());
+    }
+
+    #[test]
+    fn insert_rejects_wrong_dimension() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = EmbeddingCache::open(dir.path(), "m", 2).unwrap();
+        assert!(cache.insert(content_key("x"), vec![1.0]).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use topo_core::{Config, FileInfo, Language};
+use topo_score::{ImportGraph, ResolveOptions, UnresolvedImport, WeightedImports};
+
+/// Graph cache location relative to the repository root.
+const GRAPH_FILE: &str = ".topo/graph.json";
+/// Bumped whenever import extraction or resolution changes what a graph holds.
+const GRAPH_VERSION: u32 = 1;
+
+/// A persisted import graph and the inputs it was built from.
+#[derive(Serialize, Deserialize)]
+struct StoredGraph {
+    version: u32,
+    fingerprint: String,
+    graph: ImportGraph,
+}
+
+/// Build the resolved import graph for a set of scanned files.
+///
+/// Reads each programming-language file, extracts its imports, and resolves
+/// them against the file list. Unreadable files are skipped, and resolution
+/// settings come from `.topo/config.toml` (when it parses) and package
+/// manifests such as `composer.json`.
+pub fn build_graph(root: &Path, files: &[FileInfo]) -> ImportGraph {
+    let file_imports = read_imports(root, files);
+    let all_paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    let options = resolve_options(root, &all_paths);
+    topo_score::build_import_graph_with(&file_imports, &all_paths, &options)
+}
+
+/// Imports among `files` that resolve to no repo file, resolved with the same
+/// settings as [`build_graph`].
+pub fn unresolved_imports(root: &Path, files: &[FileInfo]) -> Vec<UnresolvedImport> {
+    let file_imports = read_imports(root, files);
+    let all_paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    let options = resolve_options(root, &all_paths);
+    topo_score::unresolved_imports(&file_imports, &all_paths, &options)
+}
+
+fn read_imports(root: &Path, files: &[FileInfo]) -> Vec<(String, Language, WeightedImports)> {
+    files
+        .par_iter()
+        .filter(|info| info.language.is_programming_language())
+        .filter_map(|info| {
+            let content = fs::read_to_string(root.join(&info.path)).ok()?;
+            let imports = topo_score::extract_weighted_imports(&content, info.language);
+            if imports.is_empty() {
+                return None;
+            }
+            Some((info.path.clone(), info.language, imports))
+        })
+        .collect()
+}
+
+fn resolve_options(root: &Path, all_paths: &[&str]) -> ResolveOptions {
+    Config::load(root)
+        .map(|config| ResolveOptions::from_config(&config))
+        .unwrap_or_default()
+        .with_manifests(root, all_paths)
+}
+
+/// The import graph for `files`, loaded from `.topo/graph.json` when it was
+/// built from the same file contents and config, otherwise built and saved.
+///
+/// Saving is best-effort: a read-only checkout still gets a fresh graph.
+pub fn load_or_build_graph(root: &Path, files: &[FileInfo]) -> ImportGraph {
+    let fingerprint = graph_fingerprint(root, files);
+    if let Ok(Some(graph)) = load_graph(root, &fingerprint) {
+        return graph;
+    }
+    let graph = build_graph(root, files);
+    let _ = save_graph(&graph, root, &fingerprint);
+    graph
+}
+
+/// Get the path to the graph cache file.
+pub fn graph_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(GRAPH_FILE)
+}
+
+/// Save an import graph built from inputs matching `fingerprint`.
+pub fn save_graph(graph: &ImportGraph, repo_root: &Path, fingerprint: &str) -> anyhow::Result<()> {
+    let path = graph_path(repo_root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let stored = StoredGraph {
+        version: GRAPH_VERSION,
+        fingerprint: fingerprint.to_string(),
+        graph: graph.clone(),
+    };
+    fs::write(path, serde_json::to_vec(&stored)?)?;
+    Ok(())
+}
+
+/// Load the saved import graph. Returns None if there is none, or it was
+/// built by another version or from inputs other than `fingerprint`.
+pub fn load_graph(repo_root: &Path, fingerprint: &str) -> anyhow::Result<Option<ImportGraph>> {
+    let path = graph_path(repo_root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = fs::read(&path)?;
+    match serde_json::from_slice::<StoredGraph>(&bytes) {
+        Ok(stored) if stored.version == GRAPH_VERSION && stored.fingerprint == fingerprint => {
+            Ok(Some(stored.graph))
+        }
+        // Stale or unreadable — rebuild
+        _ => Ok(None),
+    }
+}
+
+/// Hash of everything a graph depends on: each file's path and content hash,
+/// and the config file that sets resolution options.
+pub fn graph_fingerprint(root: &Path, files: &[FileInfo]) -> String {
+    let mut sorted: Vec<&FileInfo> = files.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut hasher = Sha256::new();
+    for f in sorted {
+        hasher.update(f.path.as_bytes());
+        hasher.update([0]);
+        hasher.update(f.sha256);
+    }
+    if let Ok(config) = fs::read(Config::path(root)) {
+        hasher.update(&config);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
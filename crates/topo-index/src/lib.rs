@@ -1,10 +1,35 @@
 //! Deep index with serialization and incremental updates.
 
+#[cfg(feature = "embeddings")]
+mod ann;
+mod artifacts;
 mod builder;
+mod corpus;
+#[cfg(feature = "embeddings")]
+mod embeddings;
+mod graph;
+mod multi_root;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stats;
 mod store;
 
-pub use builder::IndexBuilder;
-pub use store::{index_path, load, merge_incremental, save};
+#[cfg(feature = "embeddings")]
+pub use ann::{HnswIndex, load_or_build_ann};
+pub use builder::{IndexBuilder, is_skipped};
+pub use corpus::{CorpusSnapshot, Drift, corpus_history, corpus_history_path, record_snapshot};
+#[cfg(feature = "embeddings")]
+pub use embeddings::{ContentKey, EmbeddingCache, content_key, embed_cached};
+pub use graph::{
+    build_graph, graph_fingerprint, graph_path, load_graph, load_or_build_graph, save_graph,
+    unresolved_imports,
+};
+pub use multi_root::{MultiRootQuery, MultiRootResult, RootRanking};
+pub use stats::{BuildStats, FileTiming};
+pub use store::{
+    IndexBackend, backend, chunks_for, embeddings_dir, index_backup_path, index_path, load,
+    load_cooccurring, load_terms, merge_incremental, save, save_with,
+};
 
 #[cfg(test)]
 mod tests {
@@ -25,6 +50,11 @@ mod tests {
             language: Language::from_path(Path::new(path)),
             role: topo_core::FileRole::from_path(Path::new(path)),
             sha256: hash,
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
         }
     }
 
@@ -113,4 +143,50 @@ mod tests {
         assert_eq!(merged.files["a.rs"].sha256, index_v2.files["a.rs"].sha256);
         assert_ne!(merged.files["a.rs"].sha256, index_v1.files["a.rs"].sha256);
     }
+
+    #[test]
+    fn build_graph_resolves_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        let main = "use crate::auth;\n\nfn main() {\n    auth::check();\n}\n";
+        let auth = "pub fn check() -> bool {\n    true\n}\n";
+        fs::write(dir.path().join("src/main.rs"), main).unwrap();
+        fs::write(dir.path().join("src/auth.rs"), auth).unwrap();
+
+        let files = vec![
+            make_file_info("src/main.rs", main),
+            make_file_info("src/auth.rs", auth),
+        ];
+        let graph = build_graph(dir.path(), &files);
+
+        assert_eq!(graph.imports_of("src/main.rs"), ["src/auth.rs"]);
+        assert_eq!(graph.importers_of("src/auth.rs"), vec!["src/main.rs"]);
+    }
+
+    #[test]
+    fn graph_cache_roundtrip_and_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = "mod auth;\n";
+        let auth = "pub fn check() {}\n";
+        fs::write(dir.path().join("main.rs"), main).unwrap();
+        fs::write(dir.path().join("auth.rs"), auth).unwrap();
+        let files = vec![
+            make_file_info("main.rs", main),
+            make_file_info("auth.rs", auth),
+        ];
+
+        let graph = load_or_build_graph(dir.path(), &files);
+        assert!(graph_path(dir.path()).exists());
+
+        let fingerprint = graph_fingerprint(dir.path(), &files);
+        assert_eq!(load_graph(dir.path(), &fingerprint).unwrap(), Some(graph));
+
+        // Any content change invalidates the saved graph
+        let changed = vec![
+            make_file_info("main.rs", "fn main() {}\n"),
+            make_file_info("auth.rs", auth),
+        ];
+        let fingerprint = graph_fingerprint(dir.path(), &changed);
+        assert_eq!(load_graph(dir.path(), &fingerprint).unwrap(), None);
+    }
 }
@@ -0,0 +1,306 @@
+use anyhow::{Context, anyhow, bail};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::thread;
+use topo_core::{Config, ScoredFile};
+use topo_scanner::BundleBuilder;
+use topo_score::{
+    MultiQueryScorer, PageRankParams, ResolveOptions, RrfFusion, SignalWeights, StopWords,
+};
+
+use crate::IndexBuilder;
+
+/// Scores one task against several repository roots at once, one thread per
+/// root, each with the settings from its own `.topo/config.toml`.
+///
+/// Every returned file carries the label of its root in
+/// [`ScoredFile::root`]. The merged ranking interleaves the per-root
+/// rankings by Reciprocal Rank Fusion, since raw scores from separately
+/// indexed corpora are not comparable.
+pub struct MultiRootQuery {
+    task: String,
+    roots: Vec<(String, PathBuf)>,
+    deep: bool,
+    min_score: f64,
+}
+
+/// One root's share of a [`MultiRootQuery`].
+#[derive(Debug, Clone)]
+pub struct RootRanking {
+    pub label: String,
+    pub root: PathBuf,
+    /// Files scanned under this root.
+    pub scanned: usize,
+    /// Files at or above the minimum score, best first.
+    pub files: Vec<ScoredFile>,
+}
+
+/// Per-root and merged rankings from a [`MultiRootQuery`].
+#[derive(Debug, Clone)]
+pub struct MultiRootResult {
+    /// In the order the roots were added.
+    pub roots: Vec<RootRanking>,
+    /// Every root's files in one ranking, scored by RRF.
+    pub merged: Vec<ScoredFile>,
+}
+
+impl MultiRootQuery {
+    pub fn new(task: &str) -> Self {
+        Self {
+            task: task.to_string(),
+            roots: Vec::new(),
+            deep: false,
+            min_score: 0.0,
+        }
+    }
+
+    /// Add a root, labelled with its directory name.
+    pub fn root(self, root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let label = root
+            .canonicalize()
+            .ok()
+            .as_deref()
+            .unwrap_or(&root)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| root.display().to_string());
+        self.labeled_root(&label, root)
+    }
+
+    /// Add a root under an explicit label.
+    pub fn labeled_root(mut self, label: &str, root: impl Into<PathBuf>) -> Self {
+        self.roots.push((label.to_string(), root.into()));
+        self
+    }
+
+    /// Build or update each root's deep index (saving it) and fuse PageRank
+    /// into its ranking. Off by default: lexical and heuristic signals only.
+    pub fn deep(mut self, deep: bool) -> Self {
+        self.deep = deep;
+        self
+    }
+
+    /// Drop files scoring below this threshold. Files scoring 0 are always
+    /// dropped.
+    pub fn min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Scan, optionally index, and score every root in parallel.
+    pub fn run(&self) -> anyhow::Result<MultiRootResult> {
+        let mut labels = HashSet::new();
+        for (label, root) in &self.roots {
+            if !labels.insert(label.as_str()) {
+                bail!("two roots are labelled {label}; give them distinct labels");
+            }
+            if !root.is_dir() {
+                bail!("root {label} ({}) is not a directory", root.display());
+            }
+        }
+
+        let roots: Vec<RootRanking> = thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .roots
+                .iter()
+                .map(|(label, root)| {
+                    let handle = scope.spawn(move || {
+                        self.rank_root(label, root)
+                            .with_context(|| format!("scoring root {label}"))
+                    });
+                    (label, handle)
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|(label, h)| {
+                    h.join()
+                        .map_err(|_| anyhow!("scoring root {label} panicked"))?
+                })
+                .collect::<anyhow::Result<_>>()
+        })?;
+
+        let merged = merge(&roots, RrfFusion::new().k());
+        Ok(MultiRootResult { roots, merged })
+    }
+
+    fn rank_root(&self, label: &str, root: &Path) -> anyhow::Result<RootRanking> {
+        let config = Config::load(root)?;
+        let stop_words = StopWords::from_config(&config.stop_words);
+        let weights = SignalWeights::from_config(&config.weights);
+        let bundle = BundleBuilder::new(root).build()?;
+
+        let index = if self.deep {
+            let builder = IndexBuilder::new(root)
+                .stop_words(stop_words.clone())
+                .resolve_options(ResolveOptions::from_config(&config))
+                .pagerank(PageRankParams::from_config(&config.pagerank));
+            let existing = crate::load(root)?.filter(|idx| builder.is_compatible(idx));
+            let (index, reindexed) = builder.build(&bundle.files, existing.as_ref())?;
+            if existing.is_none() || reindexed > 0 {
+                crate::save(&index, root)?;
+            }
+            Some(index)
+        } else {
+            None
+        };
+
+        let mut files = MultiQueryScorer::new(&self.task)
+            .stop_words(stop_words)
+            .weights(weights)
+            .vendored(topo_score::VendoredPolicy::from_config(&config.vendored))
+            .file_type_rules(topo_score::file_type_rules(&config.file_types))
+            .score(&bundle.files)
+            .files;
+        if let Some(index) = &index {
+            let recency = if weights.git_recency > 0.0 {
+                topo_score::git_recency_scores(root)?
+            } else {
+                HashMap::new()
+            };
+            let co_change = if weights.co_change > 0.0 {
+                topo_score::CoChange::load(root)?
+            } else {
+                topo_score::CoChange::default()
+            };
+            let churn = if weights.churn > 0.0 {
+                topo_score::Churn::load(root, topo_score::Churn::window(&config.churn))?.scores()
+            } else {
+                HashMap::new()
+            };
+            topo_score::fuse_structural(
+                &mut files,
+                &index.pagerank_scores,
+                &recency,
+                &co_change,
+                &churn,
+                &weights,
+            );
+        }
+        files.retain(|f| f.score > 0.0 && f.score >= self.min_score);
+        for file in &mut files {
+            file.root = Some(label.to_string());
+        }
+        log::debug!(
+            "Root {label}: {} of {} files ranked",
+            files.len(),
+            bundle.file_count()
+        );
+
+        Ok(RootRanking {
+            label: label.to_string(),
+            root: root.to_path_buf(),
+            scanned: bundle.file_count(),
+            files,
+        })
+    }
+}
+
+/// Merge per-root rankings by RRF. A file appears in exactly one ranking,
+/// so this interleaves them by rank, earlier roots first on ties.
+fn merge(roots: &[RootRanking], k: f64) -> Vec<ScoredFile> {
+    let mut merged: Vec<(usize, usize, ScoredFile)> = roots
+        .iter()
+        .enumerate()
+        .flat_map(|(r, ranking)| {
+            ranking.files.iter().enumerate().map(move |(rank, file)| {
+                let mut file = file.clone();
+                file.score = 1.0 / (k + rank as f64 + 1.0);
+                (rank, r, file)
+            })
+        })
+        .collect();
+    merged.sort_by_key(|&(rank, r, _)| (rank, r));
+    merged.into_iter().map(|(_, _, file)| file).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn repo(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn ranks_each_root_and_labels_files() {
+        let api = repo(&[
+            (
+                "src/auth.rs",
+                "pub fn authenticate(token: &str) -> bool { true }\n",
+            ),
+            ("src/db.rs", "pub fn connect() {}\n"),
+        ]);
+        let web = repo(&[("src/login.ts", "export function authenticate() {}\n")]);
+
+        let result = MultiRootQuery::new("authenticate")
+            .labeled_root("api", api.path())
+            .labeled_root("web", web.path())
+            .run()
+            .unwrap();
+
+        assert_eq!(result.roots.len(), 2);
+        assert_eq!(result.roots[0].label, "api");
+        assert_eq!(result.roots[0].scanned, 2);
+        assert_eq!(result.roots[0].files[0].path, "src/auth.rs");
+        assert_eq!(result.roots[1].files[0].path, "src/login.ts");
+        assert!(
+            result
+                .roots
+                .iter()
+                .all(|r| r.files.iter().all(|f| f.root.as_deref() == Some(&r.label)))
+        );
+
+        // Top file of each root first, in root order
+        let top: Vec<(&str, &str)> = result.merged[..2]
+            .iter()
+            .map(|f| (f.root.as_deref().unwrap(), f.path.as_str()))
+            .collect();
+        assert_eq!(top, [("api", "src/auth.rs"), ("web", "src/login.ts")]);
+        assert!(result.merged[0].score > result.merged[2].score);
+    }
+
+    #[test]
+    fn deep_mode_saves_each_index() {
+        let a = repo(&[
+            ("src/main.rs", "mod auth;\nfn main() { auth::login(); }\n"),
+            ("src/auth.rs", "pub fn login() {}\n"),
+        ]);
+        let b = repo(&[("lib.py", "def login():\n    pass\n")]);
+
+        let result = MultiRootQuery::new("login")
+            .root(a.path())
+            .root(b.path())
+            .deep(true)
+            .run()
+            .unwrap();
+
+        assert!(crate::index_path(a.path()).exists());
+        assert!(crate::index_path(b.path()).exists());
+        assert!(
+            result.roots[0]
+                .files
+                .iter()
+                .any(|f| f.signals.pagerank.is_some())
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_labels() {
+        let a = repo(&[("a.rs", "fn a() {}\n")]);
+        let err = MultiRootQuery::new("a")
+            .labeled_root("x", a.path())
+            .labeled_root("x", a.path())
+            .run()
+            .unwrap_err();
+        assert!(err.to_string().contains("labelled x"));
+    }
+}
@@ -0,0 +1,334 @@
+//! SQLite index store. Terms live in their own table keyed by term, so a
+//! query can load just the rows for its own terms instead of the whole
+//! index.
+
+use rusqlite::{Connection, OptionalExtension, Params, params};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use topo_core::{
+    Chunk, DeepIndex, FileEntry, FileSummary, INDEX_VERSION, IndexError, TermFreqs, TermPositions,
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE files (
+        path TEXT PRIMARY KEY,
+        sha256 BLOB NOT NULL,
+        doc_length INTEGER NOT NULL,
+        pagerank REAL,
+        chunks TEXT NOT NULL,
+        summary TEXT NOT NULL,
+        signature BLOB NOT NULL,
+        imports TEXT NOT NULL
+    );
+    CREATE TABLE terms (
+        term TEXT NOT NULL,
+        path TEXT NOT NULL,
+        filename INTEGER NOT NULL,
+        symbols INTEGER NOT NULL,
+        body INTEGER NOT NULL,
+        positions TEXT NOT NULL,
+        PRIMARY KEY (term, path)
+    ) WITHOUT ROWID;
+    CREATE INDEX terms_by_path ON terms (path);
+    CREATE TABLE doc_frequencies (term TEXT PRIMARY KEY, df INTEGER NOT NULL) WITHOUT ROWID;
+";
+
+/// Write `index` to a fresh database at `path`, replacing any previous one
+/// only once the new one is complete and keeping it as the backup.
+pub(crate) fn save(index: &DeepIndex, path: &Path) -> anyhow::Result<()> {
+    let tmp = path.with_extension("db.tmp");
+    let _ = fs::remove_file(&tmp);
+    let mut conn = Connection::open(&tmp)?;
+    conn.execute_batch(SCHEMA)?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut meta = tx.prepare("INSERT INTO meta (key, value) VALUES (?1, ?2)")?;
+        meta.execute(params!["version", index.version.to_string()])?;
+        meta.execute(params!["avg_doc_length", index.avg_doc_length.to_string()])?;
+        meta.execute(params!["total_docs", index.total_docs.to_string()])?;
+        meta.execute(params!["pagerank_settings", index.pagerank_settings])?;
+        meta.execute(params![
+            "stop_words",
+            serde_json::to_string(&index.stop_words)?
+        ])?;
+        meta.execute(params!["errors", serde_json::to_string(&index.errors)?])?;
+
+        let mut file = tx.prepare(
+            "INSERT INTO files (path, sha256, doc_length, pagerank, chunks, summary, signature, imports)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        let mut term = tx.prepare(
+            "INSERT INTO terms (term, path, filename, symbols, body, positions)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for (path, entry) in &index.files {
+            file.execute(params![
+                path,
+                &entry.sha256[..],
+                entry.doc_length,
+                index.pagerank_scores.get(path),
+                serde_json::to_string(&entry.chunks)?,
+                serde_json::to_string(&entry.summary)?,
+                entry
+                    .signature
+                    .iter()
+                    .flat_map(|h| h.to_le_bytes())
+                    .collect::<Vec<u8>>(),
+                serde_json::to_string(&entry.imports)?,
+            ])?;
+            for (t, tf) in &entry.term_frequencies {
+                let positions = entry.positions.get(t).cloned().unwrap_or_default();
+                term.execute(params![
+                    t,
+                    path,
+                    tf.filename,
+                    tf.symbols,
+                    tf.body,
+                    serde_json::to_string(&positions)?,
+                ])?;
+            }
+        }
+
+        let mut df = tx.prepare("INSERT INTO doc_frequencies (term, df) VALUES (?1, ?2)")?;
+        for (t, n) in &index.doc_frequencies {
+            df.execute(params![t, n])?;
+        }
+    }
+    tx.commit()?;
+    drop(conn);
+
+    crate::store::replace_keeping_backup(&tmp, path)?;
+    Ok(())
+}
+
+const TERMS: &str = "SELECT term, path, filename, symbols, body, positions FROM terms";
+const DOC_FREQUENCIES: &str = "SELECT term, df FROM doc_frequencies";
+
+/// Load the index at `path`. With `terms`, only those terms' frequencies
+/// are read and file entries come without chunks, summaries, signatures or
+/// imports.
+///
+/// Returns `None` for a database from an older index version, which lacks
+/// term positions.
+pub(crate) fn load(db: &Path, terms: Option<&[String]>) -> anyhow::Result<Option<DeepIndex>> {
+    let conn = Connection::open(db)?;
+    let meta = |key: &str| -> anyhow::Result<Option<String>> {
+        Ok(conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |r| r.get(0))
+            .optional()?)
+    };
+    let version: u32 = match meta("version")?.map(|v| v.parse()) {
+        Some(Ok(v)) if v >= INDEX_VERSION => v,
+        _ => return Ok(None),
+    };
+    let avg_doc_length: f64 = meta("avg_doc_length")?.unwrap_or_default().parse()?;
+    let total_docs: u32 = meta("total_docs")?.unwrap_or_default().parse()?;
+    let pagerank_settings = meta("pagerank_settings")?.unwrap_or_default();
+    let stop_words: Vec<String> = serde_json::from_str(&meta("stop_words")?.unwrap_or_default())?;
+    let errors: Vec<IndexError> =
+        serde_json::from_str(&meta("errors")?.unwrap_or_else(|| "[]".to_string()))?;
+
+    let mut files = HashMap::new();
+    let mut pagerank_scores = HashMap::new();
+    let mut stmt = conn.prepare(if terms.is_some() {
+        "SELECT path, sha256, doc_length, pagerank, NULL, NULL, NULL, NULL FROM files"
+    } else {
+        "SELECT path, sha256, doc_length, pagerank, chunks, summary, signature, imports FROM files"
+    })?;
+    let mut rows = stmt.query(())?;
+    while let Some(row) = rows.next()? {
+        let path: String = row.get(0)?;
+        let sha256: [u8; 32] = row
+            .get::<_, Vec<u8>>(1)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("corrupt sha256 for {path} in {}", db.display()))?;
+        if let Some(pr) = row.get::<_, Option<f64>>(3)? {
+            pagerank_scores.insert(path.clone(), pr);
+        }
+        let chunks: Vec<Chunk> = match row.get::<_, Option<String>>(4)? {
+            Some(json) => serde_json::from_str(&json)?,
+            None => Vec::new(),
+        };
+        let summary: FileSummary = match row.get::<_, Option<String>>(5)? {
+            Some(json) => serde_json::from_str(&json)?,
+            None => FileSummary::default(),
+        };
+        let signature: Vec<u32> = row
+            .get::<_, Option<Vec<u8>>>(6)?
+            .unwrap_or_default()
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let imports: Vec<String> = match row.get::<_, Option<String>>(7)? {
+            Some(json) => serde_json::from_str(&json)?,
+            None => Vec::new(),
+        };
+        files.insert(
+            path,
+            FileEntry {
+                sha256,
+                chunks,
+                term_frequencies: HashMap::new(),
+                positions: HashMap::new(),
+                doc_length: row.get(2)?,
+                summary,
+                signature,
+                imports,
+            },
+        );
+    }
+
+    let mut doc_frequencies = HashMap::new();
+    match terms {
+        Some(terms) => {
+            for term in terms {
+                read_terms(
+                    &conn,
+                    &format!("{TERMS} WHERE term = ?1"),
+                    [term],
+                    &mut files,
+                )?;
+                read_doc_frequencies(
+                    &conn,
+                    &format!("{DOC_FREQUENCIES} WHERE term = ?1"),
+                    [term],
+                    &mut doc_frequencies,
+                )?;
+            }
+        }
+        None => {
+            read_terms(&conn, TERMS, (), &mut files)?;
+            read_doc_frequencies(&conn, DOC_FREQUENCIES, (), &mut doc_frequencies)?;
+        }
+    }
+
+    Ok(Some(DeepIndex {
+        version,
+        files,
+        avg_doc_length,
+        total_docs,
+        doc_frequencies,
+        pagerank_scores,
+        pagerank_settings,
+        stop_words,
+        errors,
+    }))
+}
+
+/// Add every term of the files where each of `terms` is most frequent, up
+/// to [`topo_score::EXPANSION_DOCS_PER_TERM`] files per term, and those
+/// terms' document frequencies to a partially loaded `index`.
+pub(crate) fn load_cooccurring(
+    db: &Path,
+    index: &mut DeepIndex,
+    terms: &[String],
+) -> anyhow::Result<()> {
+    let conn = Connection::open(db)?;
+    let mut neighbors = Vec::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT path FROM terms WHERE term = ?1
+             ORDER BY filename + symbols + body DESC, path LIMIT ?2",
+        )?;
+        for term in terms {
+            let paths = stmt
+                .query_map(params![term, topo_score::EXPANSION_DOCS_PER_TERM], |r| {
+                    r.get::<_, String>(0)
+                })?;
+            for path in paths {
+                neighbors.push(path?);
+            }
+        }
+    }
+    neighbors.sort();
+    neighbors.dedup();
+    for path in &neighbors {
+        read_terms(
+            &conn,
+            &format!("{TERMS} WHERE path = ?1"),
+            [path],
+            &mut index.files,
+        )?;
+    }
+
+    let mut vocabulary: Vec<String> = neighbors
+        .iter()
+        .filter_map(|path| index.files.get(path))
+        .flat_map(|entry| entry.term_frequencies.keys().cloned())
+        .filter(|term| !index.doc_frequencies.contains_key(term))
+        .collect();
+    vocabulary.sort();
+    vocabulary.dedup();
+    for term in vocabulary {
+        read_doc_frequencies(
+            &conn,
+            &format!("{DOC_FREQUENCIES} WHERE term = ?1"),
+            [term],
+            &mut index.doc_frequencies,
+        )?;
+    }
+    Ok(())
+}
+
+/// The chunks of each of `paths` found in the database at `db`.
+pub(crate) fn load_chunks(
+    db: &Path,
+    paths: &[&str],
+) -> anyhow::Result<HashMap<String, Vec<Chunk>>> {
+    let conn = Connection::open(db)?;
+    let mut stmt = conn.prepare("SELECT chunks FROM files WHERE path = ?1")?;
+    let mut chunks = HashMap::new();
+    for path in paths {
+        let json: Option<String> = stmt.query_row([path], |r| r.get(0)).optional()?;
+        if let Some(json) = json {
+            chunks.insert(path.to_string(), serde_json::from_str(&json)?);
+        }
+    }
+    Ok(chunks)
+}
+
+/// Add the term frequency and position rows `sql` selects to the matching
+/// file entries.
+fn read_terms(
+    conn: &Connection,
+    sql: &str,
+    params: impl Params,
+    files: &mut HashMap<String, FileEntry>,
+) -> anyhow::Result<()> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let mut rows = stmt.query(params)?;
+    while let Some(row) = rows.next()? {
+        let path: String = row.get(1)?;
+        if let Some(entry) = files.get_mut(&path) {
+            let term: String = row.get(0)?;
+            let positions: TermPositions = serde_json::from_str(&row.get::<_, String>(5)?)?;
+            entry.positions.insert(term.clone(), positions);
+            entry.term_frequencies.insert(
+                term,
+                TermFreqs {
+                    filename: row.get(2)?,
+                    symbols: row.get(3)?,
+                    body: row.get(4)?,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+fn read_doc_frequencies(
+    conn: &Connection,
+    sql: &str,
+    params: impl Params,
+    doc_frequencies: &mut HashMap<String, u32>,
+) -> anyhow::Result<()> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let mut rows = stmt.query(params)?;
+    while let Some(row) = rows.next()? {
+        doc_frequencies.insert(row.get(0)?, row.get(1)?);
+    }
+    Ok(())
+}
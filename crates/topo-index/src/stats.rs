@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use topo_core::Language;
+
+/// Number of slowest files kept in [`BuildStats::slowest`].
+pub const SLOWEST_FILES: usize = 10;
+
+/// Time spent on one file during an index build.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileTiming {
+    pub path: String,
+    pub language: Language,
+    pub elapsed: Duration,
+}
+
+/// Where an index build spent its time.
+///
+/// Per-file stages run in parallel, so their durations are summed across
+/// threads (CPU time) and can exceed the wall-clock total.
+#[derive(Debug, Clone, Default)]
+pub struct BuildStats {
+    pub files_indexed: usize,
+    pub files_reused: usize,
+    /// Minified bundles, source maps and large lockfiles left out.
+    pub artifacts_skipped: usize,
+    pub read: Duration,
+    pub tokenize: Duration,
+    pub chunk_by_language: HashMap<Language, Duration>,
+    pub imports: Duration,
+    pub graph: Duration,
+    pub wall: Duration,
+    /// Share of import edges added or removed since the previous build; 1
+    /// without one.
+    pub edge_churn: f64,
+    /// Power iterations PageRank ran; 0 when the previous scores were kept.
+    pub pagerank_iterations: usize,
+    /// Whether PageRank started from the previous build's scores.
+    pub pagerank_warm: bool,
+    /// Slowest files, most expensive first.
+    pub slowest: Vec<FileTiming>,
+}
+
+impl BuildStats {
+    /// Total chunking time across languages.
+    pub fn chunk(&self) -> Duration {
+        self.chunk_by_language.values().sum()
+    }
+
+    /// Chunking time per language, most expensive first.
+    pub fn chunk_ranked(&self) -> Vec<(Language, Duration)> {
+        let mut ranked: Vec<(Language, Duration)> = self
+            .chunk_by_language
+            .iter()
+            .map(|(l, d)| (*l, *d))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.as_str().cmp(b.0.as_str())));
+        ranked
+    }
+
+    /// Keep the [`SLOWEST_FILES`] most expensive of `timings`.
+    pub(crate) fn set_slowest(&mut self, mut timings: Vec<FileTiming>) {
+        timings.sort_by(|a, b| b.elapsed.cmp(&a.elapsed).then(a.path.cmp(&b.path)));
+        timings.truncate(SLOWEST_FILES);
+        self.slowest = timings;
+    }
+}
+
+/// Per-file stage timings collected inside the parallel build.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FileStageTimes {
+    pub read: Duration,
+    pub tokenize: Duration,
+    pub chunk: Duration,
+    pub imports: Duration,
+}
+
+impl FileStageTimes {
+    pub fn total(&self) -> Duration {
+        self.read + self.tokenize + self.chunk + self.imports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(path: &str, ms: u64) -> FileTiming {
+        FileTiming {
+            path: path.to_string(),
+            language: Language::Rust,
+            elapsed: Duration::from_millis(ms),
+        }
+    }
+
+    #[test]
+    fn slowest_keeps_most_expensive() {
+        let mut stats = BuildStats::default();
+        let timings = (0..15).map(|i| timing(&format!("f{i}.rs"), i)).collect();
+        stats.set_slowest(timings);
+        assert_eq!(stats.slowest.len(), SLOWEST_FILES);
+        assert_eq!(stats.slowest[0].path, "f14.rs");
+    }
+
+    #[test]
+    fn chunk_ranked_orders_by_time() {
+        let mut stats = BuildStats::default();
+        stats
+            .chunk_by_language
+            .insert(Language::Rust, Duration::from_millis(5));
+        stats
+            .chunk_by_language
+            .insert(Language::Python, Duration::from_millis(9));
+        assert_eq!(stats.chunk_ranked()[0].0, Language::Python);
+        assert_eq!(stats.chunk(), Duration::from_millis(14));
+    }
+}
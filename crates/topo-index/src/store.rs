@@ -1,49 +1,263 @@
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs;
-use std::path::Path;
-use topo_core::DeepIndex;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use topo_core::{Chunk, DeepIndex, INDEX_VERSION};
 
 /// Default index file location relative to repo root.
 const INDEX_DIR: &str = ".topo";
 const INDEX_FILE: &str = "index.bin";
+const SQLITE_FILE: &str = "index.db";
+const EMBEDDINGS_DIR: &str = "embeddings";
+
+/// How the deep index is stored under `.topo`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexBackend {
+    /// One rkyv file, read whole on every load.
+    #[default]
+    Bin,
+    /// A SQLite database; queries load only their own terms.
+    Sqlite,
+}
+
+impl IndexBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bin => "bin",
+            Self::Sqlite => "sqlite",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Bin => INDEX_FILE,
+            Self::Sqlite => SQLITE_FILE,
+        }
+    }
+}
 
-/// Save a DeepIndex to disk using rkyv binary serialization.
+/// The backend of the index already saved under `repo_root`, if any. When
+/// both files exist the more recently written one wins. A backup left
+/// without its index counts, so [`load`] can restore it.
+pub fn backend(repo_root: &Path) -> Option<IndexBackend> {
+    let dir = repo_root.join(INDEX_DIR);
+    let modified = |backend: IndexBackend| {
+        let path = dir.join(backend.file_name());
+        fs::metadata(&path)
+            .or_else(|_| fs::metadata(backup_path(&path)))
+            .and_then(|m| m.modified())
+            .ok()
+    };
+    match (modified(IndexBackend::Bin), modified(IndexBackend::Sqlite)) {
+        (Some(bin), Some(db)) if db > bin => Some(IndexBackend::Sqlite),
+        (Some(_), _) => Some(IndexBackend::Bin),
+        (None, Some(_)) => Some(IndexBackend::Sqlite),
+        (None, None) => None,
+    }
+}
+
+/// Save a DeepIndex to disk in the backend it is already stored in, or as
+/// rkyv binary for a first save.
 pub fn save(index: &DeepIndex, repo_root: &Path) -> anyhow::Result<()> {
+    save_with(index, repo_root, backend(repo_root).unwrap_or_default())
+}
+
+/// Save a DeepIndex to disk with `backend`, removing an index kept by the
+/// other backend so the two can't disagree.
+pub fn save_with(index: &DeepIndex, repo_root: &Path, backend: IndexBackend) -> anyhow::Result<()> {
     let dir = repo_root.join(INDEX_DIR);
     fs::create_dir_all(&dir)?;
 
-    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(index)
-        .map_err(|e| anyhow::anyhow!("rkyv serialize: {e}"))?;
-    fs::write(dir.join(INDEX_FILE), &bytes)?;
+    match backend {
+        IndexBackend::Bin => {
+            let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(index)
+                .map_err(|e| anyhow::anyhow!("rkyv serialize: {e}"))?;
+            let path = dir.join(INDEX_FILE);
+            let tmp = path.with_extension("bin.tmp");
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(&bytes)?;
+            file.sync_all()?;
+            drop(file);
+            replace_keeping_backup(&tmp, &path)?;
+        }
+        #[cfg(feature = "sqlite")]
+        IndexBackend::Sqlite => crate::sqlite::save(index, &dir.join(SQLITE_FILE))?,
+        #[cfg(not(feature = "sqlite"))]
+        IndexBackend::Sqlite => anyhow::bail!("topo was built without SQLite support"),
+    }
 
-    // Remove legacy JSON index if present
-    let legacy = dir.join("index.json");
-    if legacy.exists() {
-        let _ = fs::remove_file(legacy);
+    // Remove the other backend's index and the legacy JSON index if present
+    let stale = match backend {
+        IndexBackend::Bin => SQLITE_FILE,
+        IndexBackend::Sqlite => INDEX_FILE,
+    };
+    let stale = dir.join(stale);
+    for old in [backup_path(&stale), stale, dir.join("index.json")] {
+        if old.exists() {
+            let _ = fs::remove_file(old);
+        }
+    }
+
+    // The history only feeds drift warnings; never fail a save over it
+    if let Err(e) = crate::corpus::record_snapshot(index, repo_root) {
+        log::warn!("could not record corpus statistics: {e}");
     }
 
     Ok(())
 }
 
-/// Load a DeepIndex from disk. Returns None if the index file doesn't exist.
+/// Load a DeepIndex from disk. Returns None if no index file exists.
 pub fn load(repo_root: &Path) -> anyhow::Result<Option<DeepIndex>> {
-    let path = repo_root.join(INDEX_DIR).join(INDEX_FILE);
-    if !path.exists() {
+    load_from(repo_root, None)
+}
+
+/// Load just what scoring `terms` needs. A SQLite index reads only those
+/// terms' frequencies and leaves out chunks and summaries; a binary index
+/// is loaded whole.
+pub fn load_terms(repo_root: &Path, terms: &[String]) -> anyhow::Result<Option<DeepIndex>> {
+    load_from(repo_root, Some(terms))
+}
+
+fn load_from(repo_root: &Path, terms: Option<&[String]>) -> anyhow::Result<Option<DeepIndex>> {
+    let Some(backend) = backend(repo_root) else {
         return Ok(None);
+    };
+    #[cfg(not(feature = "sqlite"))]
+    if backend == IndexBackend::Sqlite {
+        let _ = terms;
+        anyhow::bail!(
+            "topo was built without SQLite support; rebuild with `topo index --deep --force`"
+        )
     }
-
-    let bytes = fs::read(&path)?;
-    let index = match rkyv::from_bytes::<DeepIndex, rkyv::rancor::Error>(&bytes) {
-        Ok(idx) if idx.version >= 2 => idx,
-        // Old version or deserialization failure — force rebuild
-        _ => return Ok(None),
+    let path = repo_root.join(INDEX_DIR).join(backend.file_name());
+    let read = |path: &Path| -> anyhow::Result<Option<DeepIndex>> {
+        match backend {
+            IndexBackend::Bin => {
+                let bytes = fs::read(path)?;
+                let idx = rkyv::from_bytes::<DeepIndex, rkyv::rancor::Error>(&bytes)?;
+                // Old version — force rebuild
+                Ok(Some(idx).filter(|idx| idx.version >= INDEX_VERSION))
+            }
+            #[cfg(feature = "sqlite")]
+            IndexBackend::Sqlite => crate::sqlite::load(path, terms),
+            #[cfg(not(feature = "sqlite"))]
+            IndexBackend::Sqlite => unreachable!("checked above"),
+        }
     };
-    Ok(Some(index))
+    match read(&path) {
+        Ok(index) => Ok(index),
+        Err(e) => recover(&path, &e, read),
+    }
 }
 
-/// Get the path to the index file.
-pub fn index_path(repo_root: &Path) -> std::path::PathBuf {
-    repo_root.join(INDEX_DIR).join(INDEX_FILE)
+/// Restore the backup of the missing or unreadable index at `path` when
+/// the backup loads, so every later read sees it too. Without a usable
+/// backup the index is treated as missing and rebuilt.
+fn recover(
+    path: &Path,
+    error: &anyhow::Error,
+    read: impl Fn(&Path) -> anyhow::Result<Option<DeepIndex>>,
+) -> anyhow::Result<Option<DeepIndex>> {
+    let backup = backup_path(path);
+    if let Ok(Some(index)) = read(&backup) {
+        log::warn!(
+            "index at {} is unreadable ({error}); restored the previous build from {}",
+            path.display(),
+            backup.display()
+        );
+        fs::rename(&backup, path)?;
+        return Ok(Some(index));
+    }
+    log::warn!(
+        "index at {} is unreadable ({error}) and has no usable backup; it will be rebuilt",
+        path.display()
+    );
+    Ok(None)
+}
+
+/// Where the index file at `path` keeps the build before it.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Move the finished file `tmp` to `path`, keeping the file it replaces as
+/// the backup. The backup is a hard link (or a copy where links aren't
+/// supported) and `tmp` is renamed over `path` atomically, so `path` holds
+/// a complete index at every point of an interrupted build.
+pub(crate) fn replace_keeping_backup(tmp: &Path, path: &Path) -> std::io::Result<()> {
+    if path.exists() {
+        let backup = backup_path(path);
+        if backup.exists() {
+            fs::remove_file(&backup)?;
+        }
+        if fs::hard_link(path, &backup).is_err() {
+            fs::copy(path, &backup)?;
+        }
+    }
+    fs::rename(tmp, path)
+}
+
+/// The chunks of each of `paths`, taken from `index` unless the index is
+/// stored in SQLite, whose query-time loads leave chunks out; those are
+/// read from the database.
+pub fn chunks_for(
+    repo_root: &Path,
+    index: &DeepIndex,
+    paths: &[&str],
+) -> anyhow::Result<HashMap<String, Vec<Chunk>>> {
+    #[cfg(feature = "sqlite")]
+    if backend(repo_root) == Some(IndexBackend::Sqlite) {
+        let db = repo_root.join(INDEX_DIR).join(SQLITE_FILE);
+        return crate::sqlite::load_chunks(&db, paths);
+    }
+    #[cfg(not(feature = "sqlite"))]
+    let _ = repo_root;
+    Ok(paths
+        .iter()
+        .filter_map(|p| {
+            let entry = index.files.get(*p)?;
+            Some((p.to_string(), entry.chunks.clone()))
+        })
+        .collect())
+}
+
+/// Complete a [`load_terms`] index for query expansion: a SQLite index
+/// also reads every term of the files where `terms` are most frequent, and
+/// those terms' document frequencies. A binary index is already whole.
+pub fn load_cooccurring(
+    repo_root: &Path,
+    index: &mut DeepIndex,
+    terms: &[String],
+) -> anyhow::Result<()> {
+    #[cfg(feature = "sqlite")]
+    if backend(repo_root) == Some(IndexBackend::Sqlite) {
+        let db = repo_root.join(INDEX_DIR).join(SQLITE_FILE);
+        return crate::sqlite::load_cooccurring(&db, index, terms);
+    }
+    #[cfg(not(feature = "sqlite"))]
+    let _ = (repo_root, index, terms);
+    Ok(())
+}
+
+/// Get the path to the index file: the one already saved, else where a
+/// binary index would go.
+pub fn index_path(repo_root: &Path) -> PathBuf {
+    let backend = backend(repo_root).unwrap_or_default();
+    repo_root.join(INDEX_DIR).join(backend.file_name())
+}
+
+/// Get the path to the backup of the index file: the previous build, kept
+/// to recover from a corrupt index.
+pub fn index_backup_path(repo_root: &Path) -> PathBuf {
+    backup_path(&index_path(repo_root))
+}
+
+/// Get the path to the embedding cache directory.
+pub fn embeddings_dir(repo_root: &Path) -> PathBuf {
+    repo_root.join(INDEX_DIR).join(EMBEDDINGS_DIR)
 }
 
 /// Perform an incremental update: merge new index data with an existing index.
@@ -91,6 +305,9 @@ pub fn merge_incremental(existing: &DeepIndex, fresh: &DeepIndex) -> DeepIndex {
         doc_frequencies,
         // PageRank is recomputed globally, always take from fresh index
         pagerank_scores: fresh.pagerank_scores.clone(),
+        pagerank_settings: fresh.pagerank_settings.clone(),
+        stop_words: fresh.stop_words.clone(),
+        errors: fresh.errors.clone(),
     }
 }
 
@@ -112,6 +329,11 @@ mod tests {
             language: Language::from_path(Path::new(path)),
             role: topo_core::FileRole::from_path(Path::new(path)),
             sha256: hash,
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
         }
     }
 
@@ -128,7 +350,7 @@ mod tests {
         save(&index, dir.path()).unwrap();
         let loaded = load(dir.path()).unwrap().unwrap();
 
-        assert_eq!(loaded.version, 2);
+        assert_eq!(loaded.version, INDEX_VERSION);
         assert_eq!(loaded.total_docs, index.total_docs);
         assert!(loaded.files.contains_key("main.rs"));
         assert_eq!(
@@ -148,12 +370,15 @@ mod tests {
     fn save_creates_topo_dir() {
         let dir = tempfile::tempdir().unwrap();
         let index = DeepIndex {
-            version: 2,
+            version: 3,
             files: HashMap::new(),
             avg_doc_length: 0.0,
             total_docs: 0,
             doc_frequencies: HashMap::new(),
             pagerank_scores: HashMap::new(),
+            pagerank_settings: String::new(),
+            stop_words: Vec::new(),
+            errors: Vec::new(),
         };
 
         save(&index, dir.path()).unwrap();
@@ -236,16 +461,174 @@ mod tests {
         fs::write(topo_dir.join("index.json"), b"{}").unwrap();
 
         let index = DeepIndex {
-            version: 2,
+            version: 3,
             files: HashMap::new(),
             avg_doc_length: 0.0,
             total_docs: 0,
             doc_frequencies: HashMap::new(),
             pagerank_scores: HashMap::new(),
+            pagerank_settings: String::new(),
+            stop_words: Vec::new(),
+            errors: Vec::new(),
         };
 
         save(&index, dir.path()).unwrap();
         assert!(!topo_dir.join("index.json").exists());
         assert!(topo_dir.join("index.bin").exists());
     }
+
+    fn two_file_index(dir: &Path) -> DeepIndex {
+        let auth = "pub fn authenticate(token: &str) -> bool {\n    !token.is_empty()\n}\n";
+        let main = "mod auth;\nfn main() { auth::authenticate(\"x\"); }\n";
+        fs::write(dir.join("auth.rs"), auth).unwrap();
+        fs::write(dir.join("main.rs"), main).unwrap();
+        let files = vec![
+            make_file_info("auth.rs", auth),
+            make_file_info("main.rs", main),
+        ];
+        IndexBuilder::new(dir).build(&files, None).unwrap().0
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_roundtrip_matches_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = two_file_index(dir.path());
+
+        save_with(&index, dir.path(), IndexBackend::Sqlite).unwrap();
+        assert_eq!(backend(dir.path()), Some(IndexBackend::Sqlite));
+        assert_eq!(index_path(dir.path()), dir.path().join(".topo/index.db"));
+        let loaded = load(dir.path()).unwrap().unwrap();
+
+        assert_eq!(loaded.version, index.version);
+        assert_eq!(loaded.total_docs, index.total_docs);
+        assert_eq!(loaded.avg_doc_length, index.avg_doc_length);
+        assert_eq!(loaded.doc_frequencies, index.doc_frequencies);
+        assert_eq!(loaded.pagerank_scores, index.pagerank_scores);
+        assert_eq!(loaded.stop_words, index.stop_words);
+        for (path, entry) in &index.files {
+            let got = &loaded.files[path];
+            assert_eq!(got.sha256, entry.sha256);
+            assert_eq!(got.doc_length, entry.doc_length);
+            assert_eq!(got.chunks.len(), entry.chunks.len());
+            assert_eq!(got.summary, entry.summary);
+            assert_eq!(got.term_frequencies.len(), entry.term_frequencies.len());
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_partial_load_reads_only_query_terms() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = two_file_index(dir.path());
+        save_with(&index, dir.path(), IndexBackend::Sqlite).unwrap();
+
+        let loaded = load_terms(dir.path(), &["authenticate".to_string()])
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.files.len(), 2);
+        assert_eq!(loaded.pagerank_scores, index.pagerank_scores);
+        assert_eq!(
+            loaded.doc_frequencies.keys().collect::<Vec<_>>(),
+            ["authenticate"]
+        );
+        let auth = &loaded.files["auth.rs"];
+        assert!(auth.chunks.is_empty());
+        let chunks = chunks_for(dir.path(), &loaded, &["auth.rs", "gone.rs"]).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks["auth.rs"].len(), index.files["auth.rs"].chunks.len());
+        assert_eq!(
+            auth.term_frequencies.keys().collect::<Vec<_>>(),
+            ["authenticate"]
+        );
+        assert_eq!(
+            auth.term_frequencies["authenticate"].body,
+            index.files["auth.rs"].term_frequencies["authenticate"].body
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_cooccurring_load_reads_neighbor_terms() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = two_file_index(dir.path());
+        save_with(&index, dir.path(), IndexBackend::Sqlite).unwrap();
+
+        let terms = ["token".to_string()];
+        let mut loaded = load_terms(dir.path(), &terms).unwrap().unwrap();
+        load_cooccurring(dir.path(), &mut loaded, &terms).unwrap();
+        let auth = &loaded.files["auth.rs"];
+        assert_eq!(
+            auth.term_frequencies.len(),
+            index.files["auth.rs"].term_frequencies.len()
+        );
+        assert!(loaded.files["main.rs"].term_frequencies.is_empty());
+        assert_eq!(
+            loaded.doc_frequencies["authenticate"],
+            index.doc_frequencies["authenticate"]
+        );
+    }
+
+    #[test]
+    fn corrupt_index_is_restored_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = two_file_index(dir.path());
+        let path = dir.path().join(".topo/index.bin");
+
+        save(&index, dir.path()).unwrap();
+        assert!(!index_backup_path(dir.path()).exists());
+        save(&index, dir.path()).unwrap();
+        assert!(index_backup_path(dir.path()).exists());
+
+        // An interrupted write elsewhere left a truncated file behind
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        let loaded = load(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.total_docs, index.total_docs);
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+        assert!(!index_backup_path(dir.path()).exists());
+
+        // Without a backup the index is rebuilt
+        fs::write(&path, b"garbage").unwrap();
+        assert!(load(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_index_is_restored_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = two_file_index(dir.path());
+        let path = dir.path().join(".topo/index.bin");
+
+        save(&index, dir.path()).unwrap();
+        let first = fs::read(&path).unwrap();
+        save(&index, dir.path()).unwrap();
+        assert!(path.exists());
+        assert_eq!(fs::read(index_backup_path(dir.path())).unwrap(), first);
+
+        // A crash or cleanup left only the backup behind
+        fs::remove_file(&path).unwrap();
+        assert_eq!(backend(dir.path()), Some(IndexBackend::Bin));
+        let loaded = load(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.total_docs, index.total_docs);
+        assert!(path.exists());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn switching_backend_removes_other_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = two_file_index(dir.path());
+        let topo_dir = dir.path().join(".topo");
+
+        save(&index, dir.path()).unwrap();
+        assert!(topo_dir.join("index.bin").exists());
+
+        save_with(&index, dir.path(), IndexBackend::Sqlite).unwrap();
+        assert!(!topo_dir.join("index.bin").exists());
+
+        // A plain save keeps the backend already in use
+        save(&index, dir.path()).unwrap();
+        assert!(topo_dir.join("index.db").exists());
+        assert!(!topo_dir.join("index.bin").exists());
+    }
 }
@@ -3,8 +3,15 @@ use topo_core::ScoredFile;
 
 /// Writes scored files in compact single-line format for hook injection.
 ///
-/// Output format: `path (role, Ntok, score)`
-/// Example: `src/auth.rs (impl, 2494tok, 7.01)`
+/// Output format: `path (role, Ntok, score)`, then `name:start-end` for
+/// each matching chunk.
+/// Example: `src/auth.rs (impl, 2494tok, 7.01) validate_token:40-62`
+///
+/// A file paired with its test or implementation ends with
+/// `[pair: <path>]`.
+///
+/// Files grouped by cluster are preceded by a `# cluster: <label>` line
+/// wherever the cluster changes.
 pub struct CompactWriter;
 
 impl CompactWriter {
@@ -21,8 +28,17 @@ impl CompactWriter {
 
     /// Write compact output to a writer.
     pub fn write_to(&self, writer: &mut dyn Write, files: &[ScoredFile]) -> std::io::Result<()> {
+        let mut cluster = None;
         for file in files {
-            writeln!(
+            if file.cluster.is_some() && file.cluster != cluster {
+                cluster = file.cluster.clone();
+                writeln!(
+                    writer,
+                    "# cluster: {}",
+                    cluster.as_deref().unwrap_or_default()
+                )?;
+            }
+            write!(
                 writer,
                 "{} ({}, {}tok, {:.2})",
                 file.path,
@@ -30,6 +46,17 @@ impl CompactWriter {
                 file.tokens,
                 file.score,
             )?;
+            for chunk in &file.chunks {
+                write!(
+                    writer,
+                    " {}:{}-{}",
+                    chunk.name, chunk.start_line, chunk.end_line
+                )?;
+            }
+            if let Some(pair) = &file.pair {
+                write!(writer, " [pair: {pair}]")?;
+            }
+            writeln!(writer)?;
         }
         Ok(())
     }
@@ -55,6 +82,11 @@ mod tests {
                 tokens: 2494,
                 language: Language::Rust,
                 role: FileRole::Implementation,
+                root: None,
+                cluster: None,
+                pair: None,
+                chunks: Vec::new(),
+                outline: Vec::new(),
             },
             ScoredFile {
                 path: "src/commands/init.rs".to_string(),
@@ -63,6 +95,11 @@ mod tests {
                 tokens: 2635,
                 language: Language::Rust,
                 role: FileRole::Implementation,
+                root: None,
+                cluster: None,
+                pair: None,
+                chunks: Vec::new(),
+                outline: Vec::new(),
             },
             ScoredFile {
                 path: "README.md".to_string(),
@@ -71,6 +108,11 @@ mod tests {
                 tokens: 128,
                 language: Language::Markdown,
                 role: FileRole::Documentation,
+                root: None,
+                cluster: None,
+                pair: None,
+                chunks: Vec::new(),
+                outline: Vec::new(),
             },
         ]
     }
@@ -91,6 +133,17 @@ mod tests {
         assert_eq!(first_line, "src/auth.rs (impl, 2494tok, 7.01)");
     }
 
+    #[test]
+    fn compact_output_ends_with_pair() {
+        let mut files = sample_files();
+        files[0].pair = Some("tests/auth_test.rs".to_string());
+        let output = CompactWriter::new().render(&files);
+        assert_eq!(
+            output.lines().next().unwrap(),
+            "src/auth.rs (impl, 2494tok, 7.01) [pair: tests/auth_test.rs]"
+        );
+    }
+
     #[test]
     fn compact_output_includes_role() {
         let writer = CompactWriter::new();
@@ -99,6 +152,17 @@ mod tests {
         assert!(output.contains("(impl,"));
     }
 
+    #[test]
+    fn compact_output_heads_each_cluster() {
+        let mut files = sample_files();
+        files[0].cluster = Some("src".to_string());
+        files[1].cluster = Some("src".to_string());
+        files[2].cluster = Some(".".to_string());
+        let output = CompactWriter::new().render(&files);
+        let headers: Vec<&str> = output.lines().filter(|l| l.starts_with('#')).collect();
+        assert_eq!(headers, ["# cluster: src", "# cluster: ."]);
+    }
+
     #[test]
     fn compact_empty_files() {
         let writer = CompactWriter::new();
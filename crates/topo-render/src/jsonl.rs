@@ -1,13 +1,15 @@
 use serde::Serialize;
 use std::io::Write;
-use topo_core::ScoredFile;
+use topo_core::{ScoredFile, Truncation};
 
 /// Writes scored files in JSONL v0.3 format.
 pub struct JsonlWriter {
     query: String,
     preset: String,
+    scope: Option<String>,
     max_bytes: Option<u64>,
     min_score: f64,
+    truncated: Option<Truncation>,
 }
 
 #[derive(Serialize)]
@@ -16,6 +18,8 @@ struct Header {
     version: String,
     query: String,
     preset: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
     budget: Budget,
     min_score: f64,
 }
@@ -35,6 +39,58 @@ struct FileEntry {
     tokens: u64,
     language: String,
     role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cluster: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pair: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    chunks: Vec<ChunkEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    outline: Vec<OutlineSymbol>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ChunkEntry {
+    name: String,
+    kind: &'static str,
+    start_line: u32,
+    end_line: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct OutlineSymbol {
+    name: String,
+    kind: &'static str,
+    line: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct TruncatedLine {
+    truncated: TruncatedBlock,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct TruncatedBlock {
+    budget_dropped: usize,
+    budget_dropped_tokens: u64,
+    top_dropped: usize,
+    below_min_score: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    best_below_min_score: Option<f64>,
+    files: Vec<DroppedEntry>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct DroppedEntry {
+    path: String,
+    score: f64,
+    tokens: u64,
+    reason: &'static str,
 }
 
 #[derive(Serialize)]
@@ -50,11 +106,19 @@ impl JsonlWriter {
         Self {
             query: query.to_string(),
             preset: preset.to_string(),
+            scope: None,
             max_bytes: None,
             min_score: 0.0,
+            truncated: None,
         }
     }
 
+    /// Record in the header the path the ranking was scoped to.
+    pub fn scope(mut self, scope: Option<&str>) -> Self {
+        self.scope = scope.map(str::to_string);
+        self
+    }
+
     pub fn max_bytes(mut self, max_bytes: Option<u64>) -> Self {
         self.max_bytes = max_bytes;
         self
@@ -65,6 +129,12 @@ impl JsonlWriter {
         self
     }
 
+    /// Emit a `Truncated` line before the footer when files were cut.
+    pub fn truncated(mut self, truncated: Option<Truncation>) -> Self {
+        self.truncated = truncated.filter(Truncation::is_truncated);
+        self
+    }
+
     /// Render scored files as JSONL v0.3 string.
     pub fn render(&self, files: &[ScoredFile], scanned_count: usize) -> anyhow::Result<String> {
         let mut buf = Vec::new();
@@ -84,6 +154,7 @@ impl JsonlWriter {
             version: "0.3".to_string(),
             query: self.query.clone(),
             preset: self.preset.clone(),
+            scope: self.scope.clone(),
             budget: Budget {
                 max_bytes: self.max_bytes,
             },
@@ -101,12 +172,58 @@ impl JsonlWriter {
                 tokens: file.tokens,
                 language: file.language.as_str().to_string(),
                 role: file.role.as_str().to_string(),
+                cluster: file.cluster.clone(),
+                pair: file.pair.clone(),
+                chunks: file
+                    .chunks
+                    .iter()
+                    .map(|c| ChunkEntry {
+                        name: c.name.clone(),
+                        kind: c.kind.as_str(),
+                        start_line: c.start_line,
+                        end_line: c.end_line,
+                    })
+                    .collect(),
+                outline: file
+                    .outline
+                    .iter()
+                    .map(|o| OutlineSymbol {
+                        name: o.name.clone(),
+                        kind: o.kind.as_str(),
+                        line: o.line,
+                    })
+                    .collect(),
             };
             serde_json::to_writer(&mut *writer, &entry)?;
             writeln!(writer)?;
             total_tokens += file.tokens;
         }
 
+        // Truncation diagnostics
+        if let Some(t) = &self.truncated {
+            let line = TruncatedLine {
+                truncated: TruncatedBlock {
+                    budget_dropped: t.budget_dropped,
+                    budget_dropped_tokens: t.budget_dropped_tokens,
+                    top_dropped: t.top_dropped,
+                    below_min_score: t.below_threshold,
+                    best_below_min_score: t.best_below_threshold,
+                    files: t
+                        .dropped
+                        .iter()
+                        .map(|d| DroppedEntry {
+                            path: d.path.clone(),
+                            score: d.score,
+                            tokens: d.tokens,
+                            reason: d.reason.as_str(),
+                        })
+                        .collect(),
+                },
+            };
+            serde_json::to_writer(&mut *writer, &line)?;
+            writeln!(writer)?;
+        }
+
         // Footer
         let footer = Footer {
             total_files: files.len(),
@@ -1,15 +1,26 @@
-//! JSONL v0.3, JSON, compact, and human-readable output rendering.
+//! JSONL v0.3, JSON, compact, Markdown, XML and human-readable output rendering.
 
 mod compact;
 mod jsonl;
+mod markdown;
+mod policy;
+mod redact;
+mod xml;
 
 pub use compact::CompactWriter;
 pub use jsonl::JsonlWriter;
+pub use markdown::MarkdownWriter;
+pub use policy::{ContentPolicy, Withheld};
+pub use redact::{Redacted, Redaction, redact};
+pub use xml::XmlWriter;
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use topo_core::{FileRole, Language, ScoredFile, SignalBreakdown};
+    use topo_core::{
+        ChunkKind, ChunkMatch, FileRole, Language, OutlineEntry, ScoredFile, SignalBreakdown,
+        Truncation,
+    };
 
     fn sample_files() -> Vec<ScoredFile> {
         vec![
@@ -24,6 +35,11 @@ mod tests {
                 tokens: 1200,
                 language: Language::Rust,
                 role: FileRole::Implementation,
+                root: None,
+                cluster: None,
+                pair: None,
+                chunks: Vec::new(),
+                outline: Vec::new(),
             },
             ScoredFile {
                 path: "src/auth/handler.rs".to_string(),
@@ -36,6 +52,11 @@ mod tests {
                 tokens: 800,
                 language: Language::Rust,
                 role: FileRole::Implementation,
+                root: None,
+                cluster: None,
+                pair: None,
+                chunks: Vec::new(),
+                outline: Vec::new(),
             },
         ]
     }
@@ -75,6 +96,15 @@ mod tests {
         let first_line = output.lines().next().unwrap();
         let header: serde_json::Value = serde_json::from_str(first_line).unwrap();
         assert_eq!(header["Query"], "auth middleware");
+        assert!(header.get("Scope").is_none());
+
+        let output = JsonlWriter::new("auth middleware", "balanced")
+            .scope(Some("src/auth"))
+            .render(&files, 100)
+            .unwrap();
+        let header: serde_json::Value =
+            serde_json::from_str(output.lines().next().unwrap()).unwrap();
+        assert_eq!(header["Scope"], "src/auth");
     }
 
     #[test]
@@ -94,6 +124,60 @@ mod tests {
         assert!(file_entry["Role"].is_string());
     }
 
+    #[test]
+    fn jsonl_file_entries_list_matching_chunks() {
+        let mut files = sample_files();
+        files[0].chunks = vec![ChunkMatch {
+            name: "require_auth".to_string(),
+            kind: ChunkKind::Function,
+            start_line: 12,
+            end_line: 40,
+            score: 2.7,
+        }];
+        let output = JsonlWriter::new("test", "deep")
+            .render(&files, 100)
+            .unwrap();
+
+        let lines: Vec<&str> = output.trim().lines().collect();
+        let first: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(
+            first["Chunks"],
+            serde_json::json!([{
+                "Name": "require_auth",
+                "Kind": "function",
+                "StartLine": 12,
+                "EndLine": 40,
+            }])
+        );
+        let second: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert!(second.get("Chunks").is_none());
+
+        let compact = CompactWriter::new().render(&files[..1]);
+        assert!(compact.trim_end().ends_with(" require_auth:12-40"));
+    }
+
+    #[test]
+    fn jsonl_file_entries_carry_outlines() {
+        let mut files = sample_files();
+        files[1].outline = vec![OutlineEntry {
+            name: "Handler".to_string(),
+            kind: ChunkKind::Type,
+            line: 8,
+        }];
+        let output = JsonlWriter::new("test", "balanced")
+            .render(&files, 100)
+            .unwrap();
+
+        let lines: Vec<&str> = output.trim().lines().collect();
+        let first: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert!(first.get("Outline").is_none());
+        let second: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(
+            second["Outline"],
+            serde_json::json!([{"Name": "Handler", "Kind": "type", "Line": 8}])
+        );
+    }
+
     #[test]
     fn jsonl_footer_has_totals() {
         let files = sample_files();
@@ -150,4 +234,33 @@ mod tests {
         let header: serde_json::Value = serde_json::from_str(first_line).unwrap();
         assert_eq!(header["Preset"], "deep");
     }
+
+    #[test]
+    fn jsonl_truncated_line_before_footer() {
+        let files = sample_files();
+        let truncation = Truncation::from_cuts(&files[1..], &[], &[]);
+        let output = JsonlWriter::new("test", "balanced")
+            .truncated(Some(truncation))
+            .render(&files[..1], 2)
+            .unwrap();
+
+        let lines: Vec<&str> = output.trim().lines().collect();
+        assert_eq!(lines.len(), 4);
+        let block: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(block["Truncated"]["BudgetDropped"], 1);
+        assert_eq!(
+            block["Truncated"]["Files"][0]["Path"],
+            "src/auth/handler.rs"
+        );
+        assert_eq!(block["Truncated"]["Files"][0]["Reason"], "budget");
+    }
+
+    #[test]
+    fn jsonl_no_truncated_line_when_nothing_dropped() {
+        let output = JsonlWriter::new("test", "balanced")
+            .truncated(Some(Truncation::default()))
+            .render(&[], 0)
+            .unwrap();
+        assert!(!output.contains("Truncated"));
+    }
 }
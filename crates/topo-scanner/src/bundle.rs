@@ -1,31 +1,109 @@
+use crate::cache::ScanCache;
 use crate::fingerprint;
-use crate::scanner::Scanner;
+use crate::scanner::{ScanStats, Scanner};
 use std::path::Path;
-use std::time::SystemTime;
-use topo_core::Bundle;
+use std::time::{Instant, SystemTime};
+use topo_core::{Bundle, Config, FileProvider};
 
 /// Orchestrates scan -> hash -> fingerprint -> Bundle.
 pub struct BundleBuilder<'a> {
     root: &'a Path,
+    keep_dirs: Vec<String>,
+    provider: Option<&'a dyn FileProvider>,
 }
 
 impl<'a> BundleBuilder<'a> {
     pub fn new(root: &'a Path) -> Self {
-        Self { root }
+        Self {
+            root,
+            keep_dirs: Vec::new(),
+            provider: None,
+        }
     }
 
-    /// Build a complete Bundle from the repository root.
+    /// Also scan directories with these names that are skipped by default,
+    /// on top of `[scan] keep_dirs`.
+    pub fn keep_dirs(mut self, names: &[String]) -> Self {
+        self.keep_dirs = names.to_vec();
+        self
+    }
+
+    /// Scan in-memory content from `provider` over the files on disk. See
+    /// [`Scanner::provider`].
+    pub fn provider(mut self, provider: &'a dyn FileProvider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// The scanner for this repository, configured by the `[scan]` section
+    /// of `.topo/config.toml`.
+    fn scanner(&self, config: &Config) -> Scanner<'a> {
+        let keep_dirs: Vec<String> = config
+            .scan
+            .keep_dirs
+            .iter()
+            .chain(&self.keep_dirs)
+            .cloned()
+            .collect();
+        let scanner = Scanner::new(self.root)
+            .ignore(&config.scan.ignore)
+            .skip_dirs(&config.scan.skip_dirs)
+            .keep_dirs(&keep_dirs);
+        match self.provider {
+            Some(provider) => scanner.provider(provider),
+            None => scanner,
+        }
+    }
+
+    /// Build a complete Bundle from the repository root, skipping the
+    /// `[scan]` globs and directories from `.topo/config.toml`.
+    ///
+    /// Files unchanged since the last build are not re-read: their content
+    /// metadata comes from `.topo/scan.json`, which is saved best-effort so a
+    /// read-only checkout still scans.
     pub fn build(&self) -> anyhow::Result<Bundle> {
-        let scanner = Scanner::new(self.root);
-        let files = scanner.scan()?;
-        let fp = fingerprint::generate(&files);
+        self.build_with_stats().map(|(bundle, _)| bundle)
+    }
 
-        Ok(Bundle {
+    /// [`build`](Self::build), also reporting where the scan spent its time.
+    pub fn build_with_stats(&self) -> anyhow::Result<(Bundle, ScanStats)> {
+        let started = Instant::now();
+        let config = Config::load(self.root)?;
+        let scanner = self.scanner(&config);
+        let mut cache = ScanCache::load(self.root);
+        let (files, stats) = scanner.scan_with_stats(&mut cache)?;
+        if let Err(e) = cache.save(self.root) {
+            log::debug!("Could not save the scan cache: {e}");
+        }
+        let fp = fingerprint::generate(&files);
+        log::debug!(
+            "Scanned {} files in {:.1?} ({} ignore globs)",
+            files.len(),
+            started.elapsed(),
+            config.scan.ignore.len()
+        );
+
+        let bundle = Bundle {
             fingerprint: fp,
             root: self.root.to_path_buf(),
             files,
             scanned_at: SystemTime::now(),
-        })
+        };
+        Ok((bundle, stats))
+    }
+
+    /// Update a bundle in place after `changed` repo-relative paths were
+    /// modified, created or deleted. See [`Scanner::update`]. Returns whether
+    /// anything changed.
+    pub fn update(&self, bundle: &mut Bundle, changed: &[String]) -> anyhow::Result<bool> {
+        let config = Config::load(self.root)?;
+        let scanner = self.scanner(&config);
+        if !scanner.update(&mut bundle.files, changed)? {
+            return Ok(false);
+        }
+        bundle.fingerprint = fingerprint::generate(&bundle.files);
+        bundle.scanned_at = SystemTime::now();
+        Ok(true)
     }
 }
 
@@ -0,0 +1,140 @@
+//! The shallow layer of `.topo/`: what a scan learned from each file's
+//! content, reused while the file's size and modification time are
+//! unchanged so later scans skip reading and hashing it.
+//!
+//! It is kept apart from the deep index: shallow queries never touch the
+//! deep index, and rebuilding the deep index leaves this cache valid.
+
+use crate::hash::ContentInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Scan cache location relative to the repository root.
+const SCAN_FILE: &str = ".topo/scan.json";
+/// Bumped whenever what a scan reads from file content changes.
+const SCAN_VERSION: u32 = 1;
+/// Files modified this recently are not cached: a coarse file system clock
+/// could hide a second write within the same tick.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+/// Content-derived metadata of one file and the stat it was read at.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedFile {
+    size: u64,
+    /// Modification time in nanoseconds since the Unix epoch.
+    modified: u64,
+    sha256: [u8; 32],
+    lines: u64,
+    shebang: Option<String>,
+    license: Option<String>,
+}
+
+/// Per-file content metadata from earlier scans, keyed by repo-relative path.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    version: u32,
+    files: HashMap<String, CachedFile>,
+    /// Whether the last scan changed anything worth saving.
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl ScanCache {
+    /// The saved cache for `root`, or an empty one if there is none or it
+    /// was written by another version.
+    pub fn load(root: &Path) -> Self {
+        let Ok(bytes) = fs::read(scan_cache_path(root)) else {
+            return Self::default();
+        };
+        match serde_json::from_slice::<Self>(&bytes) {
+            Ok(cache) if cache.version == SCAN_VERSION => cache,
+            // Stale or unreadable — rescan
+            _ => Self::default(),
+        }
+    }
+
+    /// Save the cache if the last scan changed it, through a temporary file
+    /// so an interrupted write leaves the previous cache intact.
+    pub fn save(&mut self, root: &Path) -> anyhow::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let path = scan_cache_path(root);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.version = SCAN_VERSION;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)?;
+        fs::rename(&tmp, &path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Number of files cached.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The cached content of `path`, if the file still has the size and
+    /// modification time it was read at.
+    pub(crate) fn get(&self, path: &str, metadata: &fs::Metadata) -> Option<ContentInfo> {
+        let cached = self.files.get(path)?;
+        if cached.size != metadata.len() || Some(cached.modified) != modified_nanos(metadata) {
+            return None;
+        }
+        Some(ContentInfo {
+            sha256: cached.sha256,
+            lines: cached.lines,
+            shebang: cached.shebang.clone(),
+            license: cached.license.clone(),
+        })
+    }
+
+    /// Replace the cache with the files of a finished scan. Files modified
+    /// within [`SETTLE_TIME`] of `now` are left out and read again next time.
+    pub(crate) fn replace(
+        &mut self,
+        scanned: Vec<(String, fs::Metadata, ContentInfo)>,
+        now: SystemTime,
+    ) {
+        let settled = now
+            .checked_sub(SETTLE_TIME)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos() as u64);
+        let files: HashMap<String, CachedFile> = scanned
+            .into_iter()
+            .filter_map(|(path, metadata, content)| {
+                let modified = modified_nanos(&metadata).filter(|&m| m <= settled)?;
+                let cached = CachedFile {
+                    size: metadata.len(),
+                    modified,
+                    sha256: content.sha256,
+                    lines: content.lines,
+                    shebang: content.shebang,
+                    license: content.license,
+                };
+                Some((path, cached))
+            })
+            .collect();
+        self.dirty |= files != self.files;
+        self.files = files;
+    }
+}
+
+/// Get the path to the scan cache file.
+pub fn scan_cache_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(SCAN_FILE)
+}
+
+fn modified_nanos(metadata: &fs::Metadata) -> Option<u64> {
+    let modified = metadata.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
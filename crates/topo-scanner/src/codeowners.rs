@@ -0,0 +1,159 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs;
+use std::path::Path;
+
+/// Where GitHub and GitLab look for the file, in order of precedence.
+const CODEOWNERS_FILES: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// One `pattern owner...` line.
+#[derive(Debug)]
+struct Rule {
+    pattern: String,
+    matcher: Gitignore,
+    owners: Vec<String>,
+}
+
+/// The rules of a repository's CODEOWNERS file.
+///
+/// Patterns follow gitignore syntax and the last matching rule wins; a
+/// pattern with no owners marks its files as explicitly unowned.
+#[derive(Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// The CODEOWNERS file of `root`, or no rules if it has none.
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        for name in CODEOWNERS_FILES {
+            match fs::read_to_string(root.join(name)) {
+                Ok(text) => return Ok(Self::parse(root, &text)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Parse CODEOWNERS `text`, skipping lines whose pattern is invalid.
+    pub fn parse(root: &Path, text: &str) -> Self {
+        let rules = text
+            .lines()
+            .filter_map(|line| {
+                let line = line.split_once('#').map_or(line, |(rule, _)| rule).trim();
+                let mut fields = line.split_whitespace();
+                let pattern = fields.next()?;
+                let mut builder = GitignoreBuilder::new(root);
+                if let Err(e) = builder.add_line(None, pattern) {
+                    log::warn!("skipping CODEOWNERS pattern {pattern}: {e}");
+                    return None;
+                }
+                Some(Rule {
+                    pattern: pattern.to_string(),
+                    matcher: builder.build().ok()?,
+                    owners: fields.map(str::to_string).collect(),
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The owners of the repo-relative `path`, or `None` if no rule
+    /// matches it.
+    pub fn owners_of(&self, path: &str) -> Option<&[String]> {
+        self.rule_for(path).map(|r| r.owners.as_slice())
+    }
+
+    /// The pattern of the rule deciding the owners of `path`.
+    pub fn pattern_for(&self, path: &str) -> Option<&str> {
+        self.rule_for(path).map(|r| r.pattern.as_str())
+    }
+
+    fn rule_for(&self, path: &str) -> Option<&Rule> {
+        self.rules.iter().rev().find(|rule| {
+            rule.matcher
+                .matched_path_or_any_parents(path, false)
+                .is_ignore()
+        })
+    }
+}
+
+/// Whether `owner`, a CODEOWNERS entry or git author, names `who`: equal
+/// ignoring case and a leading `@`, or the team part of `@org/team`, or
+/// the local part of an email address.
+pub fn owner_matches(owner: &str, who: &str) -> bool {
+    let owner = owner.trim_start_matches('@');
+    let who = who.trim_start_matches('@');
+    owner.eq_ignore_ascii_case(who)
+        || owner
+            .rsplit_once('/')
+            .is_some_and(|(_, team)| team.eq_ignore_ascii_case(who))
+        || (!who.contains('@')
+            && owner
+                .split_once('@')
+                .is_some_and(|(local, _)| local.eq_ignore_ascii_case(who)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEOWNERS: &str = "\
+# Default reviewers
+*                   @core
+*.rs                @rustaceans @alice   # inline comment
+/docs/              @writers
+src/generated/
+crates/**/tests/    qa@example.com
+";
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let owners = CodeOwners::parse(Path::new("/repo"), CODEOWNERS);
+        assert_eq!(owners.owners_of("README.md").unwrap(), ["@core"]);
+        assert_eq!(
+            owners.owners_of("src/main.rs").unwrap(),
+            ["@rustaceans", "@alice"]
+        );
+        assert_eq!(
+            owners.owners_of("docs/guide/intro.md").unwrap(),
+            ["@writers"]
+        );
+        assert_eq!(owners.pattern_for("docs/guide/intro.md"), Some("/docs/"));
+        // Anchored: a nested docs/ directory falls through to earlier rules
+        assert_eq!(owners.owners_of("src/docs/notes.md").unwrap(), ["@core"]);
+        assert!(owners.owners_of("src/generated/api.rs").unwrap().is_empty());
+        assert_eq!(
+            owners.owners_of("crates/x/tests/it.rs").unwrap(),
+            ["qa@example.com"]
+        );
+        assert!(CodeOwners::default().owners_of("README.md").is_none());
+    }
+
+    #[test]
+    fn load_finds_the_github_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(CodeOwners::load(dir.path()).unwrap().is_empty());
+        fs::create_dir_all(dir.path().join(".github")).unwrap();
+        fs::write(dir.path().join(".github/CODEOWNERS"), "* @team\n").unwrap();
+        fs::write(dir.path().join("CODEOWNERS"), "* @other\n").unwrap();
+        let owners = CodeOwners::load(dir.path()).unwrap();
+        assert_eq!(owners.owners_of("a.rs").unwrap(), ["@team"]);
+    }
+
+    #[test]
+    fn owner_matching() {
+        assert!(owner_matches("@alice", "alice"));
+        assert!(owner_matches("@Alice", "@alice"));
+        assert!(owner_matches("@acme/payments", "payments"));
+        assert!(owner_matches("@acme/payments", "@acme/payments"));
+        assert!(owner_matches("alice@example.com", "alice"));
+        assert!(owner_matches("alice@example.com", "alice@example.com"));
+        assert!(!owner_matches("alice@example.com", "alice@other.com"));
+        assert!(!owner_matches("@alicia", "alice"));
+    }
+}
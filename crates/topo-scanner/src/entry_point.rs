@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::Path;
+use topo_core::FileInfo;
+
+/// File names that start a program wherever they live.
+const ENTRY_FILE_NAMES: &[&str] = &[
+    "__main__.py",
+    "main.c",
+    "main.cc",
+    "main.cpp",
+    "main.go",
+    "main.kt",
+    "main.py",
+    "main.rs",
+    "main.swift",
+    "manage.py",
+    "Program.cs",
+    "wsgi.py",
+];
+
+/// JS/TS file stems that start a server when they call `listen`.
+const BOOTSTRAP_STEMS: &[&str] = &["app", "index", "main", "server"];
+
+const BOOTSTRAP_EXTENSIONS: &[&str] = &["cjs", "js", "mjs", "ts"];
+
+/// Calls that bind a server in a JS/TS bootstrap file.
+const BOOTSTRAP_MARKERS: &[&str] = &[".listen(", "createServer("];
+
+/// Whether `rel_path` is an entry point judged by its path, permissions and
+/// (for JS/TS bootstrap candidates) its contents at `abs_path`.
+pub fn is_entry_point(rel_path: &str, abs_path: &Path, executable: bool, shebang: bool) -> bool {
+    let mut parts = rel_path.rsplit('/');
+    let file_name = parts.next().unwrap_or(rel_path);
+    let parent = parts.next();
+
+    if ENTRY_FILE_NAMES.contains(&file_name) {
+        return true;
+    }
+    // Files directly under `bin/` or `cmd/`, including `src/bin/*.rs`
+    if matches!(parent, Some("bin" | "cmd")) {
+        return true;
+    }
+    if executable && shebang {
+        return true;
+    }
+
+    is_bootstrap_candidate(file_name)
+        && fs::read_to_string(abs_path)
+            .is_ok_and(|content| BOOTSTRAP_MARKERS.iter().any(|m| content.contains(m)))
+}
+
+fn is_bootstrap_candidate(file_name: &str) -> bool {
+    file_name.rsplit_once('.').is_some_and(|(stem, ext)| {
+        BOOTSTRAP_STEMS.contains(&stem) && BOOTSTRAP_EXTENSIONS.contains(&ext)
+    })
+}
+
+/// Whether a file is a Dockerfile (`Dockerfile`, `Dockerfile.dev`, `api.dockerfile`).
+pub fn is_dockerfile(rel_path: &str) -> bool {
+    let file_name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+    file_name == "Dockerfile"
+        || file_name.starts_with("Dockerfile.")
+        || file_name.ends_with(".dockerfile")
+}
+
+/// Arguments of `CMD` and `ENTRYPOINT` instructions that look like paths.
+///
+/// Handles both the exec form (`CMD ["node", "dist/server.js"]`) and the
+/// shell form (`CMD python app/main.py`).
+pub fn dockerfile_targets(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        let Some((instruction, args)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        if !instruction.eq_ignore_ascii_case("CMD")
+            && !instruction.eq_ignore_ascii_case("ENTRYPOINT")
+        {
+            continue;
+        }
+        let words = args
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '[' || c == ']')
+            .map(|w| w.trim_matches('"').trim_matches('\''))
+            .filter(|w| !w.is_empty() && !w.starts_with('-'))
+            .filter(|w| w.contains('/') || w.contains('.'));
+        for word in words {
+            let word = word.trim_start_matches("./").trim_start_matches('/');
+            if !word.is_empty() {
+                targets.push(word.to_string());
+            }
+        }
+    }
+    targets
+}
+
+/// Tag files named by a Dockerfile `CMD`/`ENTRYPOINT` as entry points.
+///
+/// Container paths rarely match the repository layout exactly (`/app/server.js`
+/// for `server.js`), so a target matches a file when either path ends with the
+/// other on a directory boundary.
+pub fn tag_dockerfile_targets(root: &Path, files: &mut [FileInfo]) {
+    let targets: Vec<String> = files
+        .iter()
+        .filter(|f| is_dockerfile(&f.path))
+        .filter_map(|f| fs::read_to_string(root.join(&f.path)).ok())
+        .flat_map(|content| dockerfile_targets(&content))
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    for f in files.iter_mut() {
+        if targets.iter().any(|t| path_matches(&f.path, t)) {
+            f.entry_point = true;
+        }
+    }
+}
+
+fn path_matches(path: &str, target: &str) -> bool {
+    let ends_with = |long: &str, short: &str| {
+        long.strip_suffix(short)
+            .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('/'))
+    };
+    ends_with(path, target) || ends_with(target, path)
+}
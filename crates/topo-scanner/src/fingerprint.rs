@@ -34,6 +34,11 @@ mod tests {
             language: Language::Other,
             role: FileRole::Other,
             sha256: [0u8; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
         }
     }
 
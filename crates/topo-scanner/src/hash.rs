@@ -1,11 +1,71 @@
+use crate::license;
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::Path;
 
-/// Compute SHA-256 hash of a file's contents.
-pub fn sha256_file(path: &Path) -> anyhow::Result<[u8; 32]> {
-    let contents = fs::read(path)?;
-    Ok(sha256_bytes(&contents))
+/// Content-derived metadata gathered in the same read as the hash.
+pub struct ContentInfo {
+    pub sha256: [u8; 32],
+    pub lines: u64,
+    pub shebang: Option<String>,
+    pub license: Option<String>,
+}
+
+/// Hash a file and record its line count, shebang interpreter and SPDX
+/// license header.
+pub fn read_content_info(path: &Path) -> anyhow::Result<ContentInfo> {
+    Ok(content_info(&fs::read(path)?))
+}
+
+/// [`read_content_info`] for content already in memory.
+pub fn content_info(contents: &[u8]) -> ContentInfo {
+    ContentInfo {
+        sha256: sha256_bytes(contents),
+        lines: line_count(contents),
+        shebang: shebang_interpreter(contents),
+        license: license::spdx_header(contents),
+    }
+}
+
+/// Count lines; a final line without a trailing newline still counts.
+pub fn line_count(data: &[u8]) -> u64 {
+    let newlines = data.iter().filter(|&&b| b == b'\n').count() as u64;
+    match data.last() {
+        Some(b'\n') | None => newlines,
+        Some(_) => newlines + 1,
+    }
+}
+
+/// The interpreter named by a `#!` first line, without its directory.
+///
+/// `#!/usr/bin/env -S python3 -u` → `python3`, `#!/bin/bash -e` → `bash`.
+pub fn shebang_interpreter(data: &[u8]) -> Option<String> {
+    let rest = data.strip_prefix(b"#!")?;
+    let end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+    let line = std::str::from_utf8(&rest[..end]).ok()?;
+
+    let mut words = line.split_whitespace();
+    let program = words.next()?;
+    let program = if program.rsplit('/').next() == Some("env") {
+        words.find(|w| !w.starts_with('-') && !w.contains('='))?
+    } else {
+        program
+    };
+    let name = program.rsplit('/').next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Whether any execute permission bit is set.
+#[cfg(unix)]
+pub fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+/// Whether any execute permission bit is set.
+#[cfg(not(unix))]
+pub fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
 }
 
 /// Compute SHA-256 hash of a byte slice.
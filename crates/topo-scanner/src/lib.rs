@@ -1,12 +1,19 @@
 //! File walking with gitignore support and content hashing.
 
 mod bundle;
+mod cache;
+mod codeowners;
+pub(crate) mod entry_point;
 pub(crate) mod fingerprint;
 pub(crate) mod hash;
+mod license;
 mod scanner;
 
 pub use bundle::BundleBuilder;
-pub use scanner::Scanner;
+pub use cache::{ScanCache, scan_cache_path};
+pub use codeowners::{CodeOwners, owner_matches};
+pub use license::license_mentions;
+pub use scanner::{DEFAULT_SKIP_DIRS, ScanStats, Scanner};
 
 #[cfg(test)]
 mod tests {
@@ -75,6 +82,176 @@ mod tests {
         assert!(!paths.iter().any(|p| p.ends_with(".tmp")));
     }
 
+    #[test]
+    fn scanner_skips_ignore_globs() {
+        let dir = create_test_dir();
+        let files = Scanner::new(dir.path())
+            .ignore(&["tests/".to_string(), "lib.rs".to_string()])
+            .scan()
+            .unwrap();
+
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert!(paths.contains(&"src/main.rs"));
+        assert!(!paths.contains(&"src/lib.rs"));
+        assert!(!paths.iter().any(|p| p.starts_with("tests/")));
+    }
+
+    #[test]
+    fn scanner_skip_and_keep_dirs() {
+        let dir = create_test_dir();
+        for d in ["venv/app", "third_party", "node_modules/pkg", ".topo"] {
+            fs::create_dir_all(dir.path().join(d)).unwrap();
+            fs::write(dir.path().join(d).join("mod.py"), "x = 1").unwrap();
+        }
+        let paths = |scanner: &Scanner| -> Vec<String> {
+            scanner
+                .scan()
+                .unwrap()
+                .into_iter()
+                .map(|f| f.path)
+                .collect()
+        };
+
+        let defaults = paths(&Scanner::new(dir.path()));
+        assert!(!defaults.iter().any(|p| p.starts_with("venv/")));
+        assert!(defaults.contains(&"third_party/mod.py".to_string()));
+
+        let scanner = Scanner::new(dir.path())
+            .skip_dirs(&["third_party".to_string()])
+            .keep_dirs(&["venv".to_string(), ".topo".to_string()]);
+        let configured = paths(&scanner);
+        assert!(configured.contains(&"venv/app/mod.py".to_string()));
+        assert!(!configured.iter().any(|p| p.starts_with("third_party/")));
+        assert!(!configured.iter().any(|p| p.starts_with("node_modules/")));
+        assert!(!configured.iter().any(|p| p.starts_with(".topo/")));
+        assert!(!scanner.is_ignored("venv/app/mod.py", false).unwrap());
+        assert!(scanner.is_ignored("third_party/mod.py", false).unwrap());
+    }
+
+    #[test]
+    fn scanner_reads_overlays_from_the_provider() {
+        let dir = create_test_dir();
+        let overlay = topo_core::Overlay::new(topo_core::DiskFiles::new(dir.path()))
+            .file("src/main.rs", "fn main() {\n    unsaved();\n}\n")
+            .file("src/draft.rs", "fn draft() {}")
+            .file("target/debug/out.rs", "fn ignored() {}");
+        let files = Scanner::new(dir.path()).provider(&overlay).scan().unwrap();
+
+        let main = files.iter().find(|f| f.path == "src/main.rs").unwrap();
+        assert_eq!(main.lines, 3);
+        assert_eq!(main.size, 29);
+        assert!(files.iter().any(|f| f.path == "src/draft.rs"));
+        assert!(!files.iter().any(|f| f.path.starts_with("target/")));
+    }
+
+    #[test]
+    fn scanner_update_rehashes_only_changed_paths() {
+        let dir = create_test_dir();
+        let scanner = Scanner::new(dir.path());
+        let mut files = scanner.scan().unwrap();
+        let old_main = files
+            .iter()
+            .find(|f| f.path == "src/main.rs")
+            .unwrap()
+            .sha256;
+
+        fs::write(dir.path().join("src/main.rs"), "fn main() { run(); }").unwrap();
+        fs::write(dir.path().join("src/new.rs"), "pub fn new() {}").unwrap();
+        fs::remove_file(dir.path().join("src/lib.rs")).unwrap();
+        fs::write(dir.path().join("build.tmp"), "ignored").unwrap();
+        let changed: Vec<String> = ["src/main.rs", "src/new.rs", "src/lib.rs", "build.tmp"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(scanner.update(&mut files, &changed).unwrap());
+
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert!(paths.contains(&"src/new.rs"));
+        assert!(!paths.contains(&"src/lib.rs"));
+        assert!(!paths.contains(&"build.tmp"));
+        let new_main = files
+            .iter()
+            .find(|f| f.path == "src/main.rs")
+            .unwrap()
+            .sha256;
+        assert_ne!(old_main, new_main);
+        assert!(paths.windows(2).all(|w| w[0] < w[1]));
+
+        // A touched but unmodified file is not a change
+        assert!(
+            !scanner
+                .update(&mut files, &["src/new.rs".to_string()])
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn scan_cache_skips_unchanged_files() {
+        let dir = create_test_dir();
+        let hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        let age = |path: &str| {
+            fs::File::options()
+                .write(true)
+                .open(dir.path().join(path))
+                .unwrap()
+                .set_modified(hour_ago)
+                .unwrap();
+        };
+        for path in ["src/main.rs", "src/lib.rs", "README.md"] {
+            age(path);
+        }
+
+        let scanner = Scanner::new(dir.path());
+        let mut cache = ScanCache::default();
+        let first = scanner.scan_with(&mut cache).unwrap();
+        // Files written moments ago are not trusted yet
+        assert_eq!(cache.len(), 3);
+        cache.save(dir.path()).unwrap();
+        assert!(scan_cache_path(dir.path()).exists());
+
+        // Same size and mtime: the cached hash is reused without reading
+        fs::write(dir.path().join("src/main.rs"), "fn mayn() {}").unwrap();
+        age("src/main.rs");
+        let mut cache = ScanCache::load(dir.path());
+        let hash = |files: &[topo_core::FileInfo]| {
+            files
+                .iter()
+                .find(|f| f.path == "src/main.rs")
+                .unwrap()
+                .sha256
+        };
+        let (second, stats) = scanner.scan_with_stats(&mut cache).unwrap();
+        assert_eq!(hash(&second), hash(&first));
+        assert_eq!(stats.files_reused, 3);
+        assert_eq!(stats.files_hashed, second.len() - 3);
+
+        // A new mtime means the file is read again
+        fs::write(dir.path().join("src/main.rs"), "fn mayn() {}").unwrap();
+        let third = scanner.scan_with(&mut cache).unwrap();
+        assert_ne!(hash(&third), hash(&first));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn scanner_is_ignored_matches_scan() {
+        let dir = create_test_dir();
+        fs::create_dir_all(dir.path().join("src/gen")).unwrap();
+        fs::write(dir.path().join("src/.gitignore"), "gen/\n").unwrap();
+        let scanner = Scanner::new(dir.path()).ignore(&["docs/".to_string()]);
+
+        assert!(scanner.is_ignored("target/debug", false).unwrap());
+        assert!(scanner.is_ignored("a.tmp", false).unwrap());
+        assert!(scanner.is_ignored("src/gen/out.rs", false).unwrap());
+        assert!(scanner.is_ignored("docs/guide.md", false).unwrap());
+        assert!(
+            scanner
+                .is_ignored("node_modules/x/index.js", false)
+                .unwrap()
+        );
+        assert!(!scanner.is_ignored("src/main.rs", false).unwrap());
+        assert!(!scanner.is_ignored(".env", false).unwrap());
+    }
+
     #[test]
     fn scanner_detects_languages() {
         let dir = create_test_dir();
@@ -156,6 +333,179 @@ mod tests {
         assert_ne!(a.sha256, b.sha256);
     }
 
+    #[test]
+    fn scanner_records_lines_and_shebang() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("deploy"),
+            "#!/usr/bin/env bash\nset -e\necho hi",
+        )
+        .unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn a() {}\n\nfn b() {}\n").unwrap();
+
+        let files = Scanner::new(dir.path()).scan().unwrap();
+        let script = files.iter().find(|f| f.path == "deploy").unwrap();
+        assert_eq!(script.lines, 3);
+        assert_eq!(script.shebang.as_deref(), Some("bash"));
+        let lib = files.iter().find(|f| f.path == "lib.rs").unwrap();
+        assert_eq!(lib.lines, 3);
+        assert_eq!(lib.shebang, None);
+    }
+
+    #[test]
+    fn scanner_tags_licenses() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("LICENSE"),
+            "MIT License\n\nPermission is hereby granted, free of charge, ...",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+        fs::write(
+            root.join("src/gpl.rs"),
+            "// SPDX-License-Identifier: GPL-3.0-only\npub fn b() {}\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("third_party/zlib")).unwrap();
+        fs::write(
+            root.join("third_party/zlib/COPYING"),
+            "GNU LESSER GENERAL PUBLIC LICENSE\nVersion 2.1",
+        )
+        .unwrap();
+        fs::write(root.join("third_party/zlib/zlib.c"), "int x;\n").unwrap();
+
+        let files = Scanner::new(root).scan().unwrap();
+        let license = |path: &str| {
+            files
+                .iter()
+                .find(|f| f.path == path)
+                .unwrap()
+                .license
+                .clone()
+        };
+        assert_eq!(license("src/lib.rs").as_deref(), Some("MIT"));
+        assert_eq!(license("src/gpl.rs").as_deref(), Some("GPL-3.0-only"));
+        assert_eq!(
+            license("third_party/zlib/zlib.c").as_deref(),
+            Some("LGPL-2.1")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scanner_records_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(dir.path().join("data.txt"), "x").unwrap();
+
+        let files = Scanner::new(dir.path()).scan().unwrap();
+        assert!(
+            files
+                .iter()
+                .find(|f| f.path == "run.sh")
+                .unwrap()
+                .executable
+        );
+        assert!(
+            !files
+                .iter()
+                .find(|f| f.path == "data.txt")
+                .unwrap()
+                .executable
+        );
+    }
+
+    #[test]
+    fn shebang_interpreter_variants() {
+        assert_eq!(
+            hash::shebang_interpreter(b"#!/usr/bin/env -S python3 -u\n").as_deref(),
+            Some("python3")
+        );
+        assert_eq!(
+            hash::shebang_interpreter(b"#!/bin/bash -e\necho").as_deref(),
+            Some("bash")
+        );
+        assert_eq!(hash::shebang_interpreter(b"# comment\n"), None);
+        assert_eq!(hash::shebang_interpreter(b"#!\n"), None);
+    }
+
+    #[test]
+    fn line_count_edge_cases() {
+        assert_eq!(hash::line_count(b""), 0);
+        assert_eq!(hash::line_count(b"a"), 1);
+        assert_eq!(hash::line_count(b"a\n"), 1);
+        assert_eq!(hash::line_count(b"a\nb"), 2);
+    }
+
+    #[test]
+    fn scanner_tags_entry_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/bin")).unwrap();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::create_dir_all(root.join("worker")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("src/bin/migrate.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn a() {}").unwrap();
+        fs::write(root.join("web/index.ts"), "app.listen(3000);").unwrap();
+        fs::write(root.join("web/util.ts"), "export const a = 1;").unwrap();
+        fs::write(root.join("worker/run.py"), "print()").unwrap();
+        fs::write(
+            root.join("Dockerfile"),
+            "FROM python:3\nCMD [\"python\", \"/app/worker/run.py\"]\n",
+        )
+        .unwrap();
+
+        let files = Scanner::new(root).scan().unwrap();
+        let entries: Vec<&str> = files
+            .iter()
+            .filter(|f| f.entry_point)
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                "src/bin/migrate.rs",
+                "src/main.rs",
+                "web/index.ts",
+                "worker/run.py"
+            ]
+        );
+    }
+
+    #[test]
+    fn dockerfile_targets_both_forms() {
+        assert_eq!(
+            entry_point::dockerfile_targets(
+                "ENTRYPOINT [\"node\", \"./dist/server.js\"]\ncmd gunicorn app.wsgi --bind 0.0.0.0\n"
+            ),
+            vec!["dist/server.js", "app.wsgi", "0.0.0.0"]
+        );
+        assert!(entry_point::is_dockerfile("deploy/api.dockerfile"));
+        assert!(!entry_point::is_dockerfile("docs/Dockerfiles.md"));
+    }
+
+    #[test]
+    fn scanner_skips_worktree_git_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(".git"),
+            "gitdir: ../main/.git/worktrees/wt\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+        let files = Scanner::new(dir.path()).scan().unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["main.rs"]);
+    }
+
     #[test]
     fn scanner_empty_directory() {
         let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use topo_core::FileInfo;
+
+/// How far into a file an `SPDX-License-Identifier` header is looked for.
+const HEADER_BYTES: usize = 4096;
+
+/// File names (case-insensitive) that hold a license for their directory.
+/// `LICENSE-MIT` and `LICENSE-APACHE` count too.
+const LICENSE_FILE_STEMS: &[&str] = &["copying", "licence", "license"];
+
+/// Extensions a license file may carry; `license.rs` is source code.
+const LICENSE_FILE_EXTENSIONS: &[&str] = &["md", "txt", "rst"];
+
+/// Comment leaders allowed before an `SPDX-License-Identifier:` tag.
+const COMMENT_LEADERS: &[&str] = &["<!--", "///", "//!", "//", "/*", "--", "#", "*"];
+
+/// Phrases identifying the full text of common licenses, most specific first.
+const LICENSE_TEXTS: &[(&[&str], &str)] = &[
+    (&["GNU AFFERO GENERAL PUBLIC LICENSE"], "AGPL-3.0"),
+    (
+        &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 3"],
+        "LGPL-3.0",
+    ),
+    (&["GNU LESSER GENERAL PUBLIC LICENSE"], "LGPL-2.1"),
+    (&["GNU GENERAL PUBLIC LICENSE", "Version 3"], "GPL-3.0"),
+    (&["GNU GENERAL PUBLIC LICENSE", "Version 2"], "GPL-2.0"),
+    (&["Mozilla Public License", "2.0"], "MPL-2.0"),
+    (&["Apache License", "Version 2.0"], "Apache-2.0"),
+    (&["Permission is hereby granted, free of charge"], "MIT"),
+    (
+        &["Permission to use, copy, modify, and/or distribute"],
+        "ISC",
+    ),
+    (&["This is free and unencumbered software"], "Unlicense"),
+    (
+        &[
+            "Redistribution and use in source and binary forms",
+            "Neither the name",
+        ],
+        "BSD-3-Clause",
+    ),
+    (
+        &["Redistribution and use in source and binary forms"],
+        "BSD-2-Clause",
+    ),
+];
+
+/// The license expression of an `SPDX-License-Identifier:` line near the
+/// start of a file, without trailing comment closers. The tag only counts at
+/// the start of a line, after comment leaders, so prose and string literals
+/// that mention it are ignored.
+///
+/// `// SPDX-License-Identifier: MIT OR Apache-2.0` → `MIT OR Apache-2.0`.
+pub fn spdx_header(data: &[u8]) -> Option<String> {
+    let head = &data[..data.len().min(HEADER_BYTES)];
+    let text = String::from_utf8_lossy(head);
+    text.lines().find_map(|line| {
+        let rest = strip_comment_leaders(line).strip_prefix("SPDX-License-Identifier:")?;
+        let expression = rest
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim_end_matches("#}")
+            .trim();
+        is_license_expression(expression).then(|| expression.to_string())
+    })
+}
+
+fn strip_comment_leaders(line: &str) -> &str {
+    let mut line = line.trim_start();
+    while let Some(rest) = COMMENT_LEADERS
+        .iter()
+        .find_map(|leader| line.strip_prefix(leader))
+    {
+        line = rest.trim_start();
+    }
+    line
+}
+
+/// Whether `expression` is made of SPDX ids joined by `AND`, `OR` and
+/// `WITH`, with optional parentheses.
+fn is_license_expression(expression: &str) -> bool {
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let terms: Vec<&str> = spaced
+        .split_whitespace()
+        .filter(|t| !matches!(*t, "(" | ")"))
+        .collect();
+    !terms.is_empty()
+        && terms.iter().enumerate().all(|(i, term)| {
+            if i % 2 == 1 {
+                matches!(*term, "AND" | "OR" | "WITH" | "and" | "or" | "with")
+            } else {
+                is_license_id(term)
+            }
+        })
+        && terms.len() % 2 == 1
+}
+
+fn is_license_id(term: &str) -> bool {
+    !term.is_empty()
+        && term
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-'))
+}
+
+/// Whether a file name is a license file: `LICENSE`, `LICENSE.md`,
+/// `COPYING`, `LICENSE-MIT`, `LICENSE-Apache-2.0.txt`, ...
+pub fn is_license_file(file_name: &str) -> bool {
+    let lower = file_name.to_ascii_lowercase();
+    let name = match lower.rsplit_once('.') {
+        Some((name, ext)) if LICENSE_FILE_EXTENSIONS.contains(&ext) => name,
+        _ => lower.as_str(),
+    };
+    LICENSE_FILE_STEMS.iter().any(|stem| {
+        let Some(rest) = name.strip_prefix(stem) else {
+            return false;
+        };
+        if rest.is_empty() {
+            return true;
+        }
+        // A `-<ID>` suffix, whose dots only separate version numbers
+        let Some(id) = rest.strip_prefix('-') else {
+            return false;
+        };
+        is_license_id(id)
+            && id
+                .rsplit_once('.')
+                .is_none_or(|(_, last)| last.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// The SPDX identifier for a license file's text, from its own SPDX header
+/// or by recognizing the wording of a common license.
+pub fn identify_license_text(content: &str) -> Option<String> {
+    if let Some(expression) = spdx_header(content.as_bytes()) {
+        return Some(expression);
+    }
+    LICENSE_TEXTS
+        .iter()
+        .find(|(phrases, _)| phrases.iter().all(|p| content.contains(p)))
+        .map(|(_, id)| id.to_string())
+}
+
+/// Whether a license expression names `id`, ignoring case and the `-only`,
+/// `-or-later` and `+` suffixes: `GPL-3.0` matches `GPL-3.0-or-later` and
+/// `MIT OR GPL-3.0-only`.
+pub fn license_mentions(expression: &str, id: &str) -> bool {
+    let id = id.to_ascii_lowercase();
+    expression
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .map(|term| term.to_ascii_lowercase())
+        .any(|term| {
+            let base = term
+                .trim_end_matches('+')
+                .trim_end_matches("-or-later")
+                .trim_end_matches("-only");
+            term == id || base == id
+        })
+}
+
+/// Tag files without an SPDX header with the license of the nearest license
+/// file in their directory or above. Several license files in one directory
+/// (`LICENSE-MIT`, `LICENSE-APACHE`) combine as a dual license.
+pub fn tag_licenses(root: &Path, files: &mut [FileInfo]) {
+    let mut by_dir: HashMap<String, Vec<String>> = HashMap::new();
+    for f in files.iter() {
+        let (dir, file_name) = f.path.rsplit_once('/').unwrap_or(("", &f.path));
+        if !is_license_file(file_name) {
+            continue;
+        }
+        let Some(id) = fs::read_to_string(root.join(&f.path))
+            .ok()
+            .and_then(|content| identify_license_text(&content))
+        else {
+            continue;
+        };
+        let ids = by_dir.entry(dir.to_string()).or_default();
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    if by_dir.is_empty() {
+        return;
+    }
+    let dir_license: HashMap<String, String> = by_dir
+        .into_iter()
+        .map(|(dir, mut ids)| {
+            ids.sort();
+            (dir, ids.join(" OR "))
+        })
+        .collect();
+
+    for f in files.iter_mut().filter(|f| f.license.is_none()) {
+        let mut dir = f.path.rsplit_once('/').map_or("", |(d, _)| d);
+        loop {
+            if let Some(license) = dir_license.get(dir) {
+                f.license = Some(license.clone());
+                break;
+            }
+            if dir.is_empty() {
+                break;
+            }
+            dir = dir.rsplit_once('/').map_or("", |(d, _)| d);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spdx_header_strips_comment_syntax() {
+        assert_eq!(
+            spdx_header(b"// SPDX-License-Identifier: MIT OR Apache-2.0\nfn main() {}"),
+            Some("MIT OR Apache-2.0".to_string())
+        );
+        assert_eq!(
+            spdx_header(b"/* SPDX-License-Identifier: GPL-3.0-only */\n"),
+            Some("GPL-3.0-only".to_string())
+        );
+        assert_eq!(
+            spdx_header(b"# SPDX-License-Identifier: (MIT OR Apache-2.0)\n"),
+            Some("(MIT OR Apache-2.0)".to_string())
+        );
+        assert_eq!(spdx_header(b"fn main() {}"), None);
+    }
+
+    #[test]
+    fn spdx_header_ignores_mentions_outside_comments() {
+        assert_eq!(
+            spdx_header(b"let tag = \"SPDX-License-Identifier: MIT\";\n"),
+            None
+        );
+        assert_eq!(
+            spdx_header(b"Start each file with an SPDX-License-Identifier: line near the top.\n"),
+            None
+        );
+        assert_eq!(
+            spdx_header(b"/// The `SPDX-License-Identifier:` line near the start\n"),
+            None
+        );
+        assert_eq!(
+            spdx_header(b"// SPDX-License-Identifier: see the LICENSE file\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn identifies_license_texts() {
+        let mit = "MIT License\n\nPermission is hereby granted, free of charge, to any person";
+        assert_eq!(identify_license_text(mit).as_deref(), Some("MIT"));
+        let gpl = "GNU GENERAL PUBLIC LICENSE\nVersion 3, 29 June 2007";
+        assert_eq!(identify_license_text(gpl).as_deref(), Some("GPL-3.0"));
+        assert_eq!(identify_license_text("All rights reserved."), None);
+    }
+
+    #[test]
+    fn license_file_names() {
+        for name in [
+            "LICENSE",
+            "LICENSE.md",
+            "COPYING",
+            "LICENSE-MIT",
+            "licence.txt",
+            "LICENSE-Apache-2.0.txt",
+        ] {
+            assert!(is_license_file(name), "{name}");
+        }
+        for name in ["license.rs", "license.go", "License.ts", "license-check.rs"] {
+            assert!(!is_license_file(name), "{name}");
+        }
+        assert!(!is_license_file("license_check.rs"));
+        assert!(!is_license_file("README.md"));
+    }
+
+    #[test]
+    fn license_mentions_ignores_suffixes() {
+        assert!(license_mentions("GPL-3.0-or-later", "GPL-3.0"));
+        assert!(license_mentions("MIT OR GPL-3.0-only", "gpl-3.0"));
+        assert!(license_mentions(
+            "(GPL-2.0+ WITH Classpath-exception-2.0)",
+            "GPL-2.0"
+        ));
+        assert!(!license_mentions("LGPL-3.0", "GPL-3.0"));
+        assert!(!license_mentions("MIT", "GPL-3.0"));
+    }
+}
@@ -1,50 +1,158 @@
-use crate::hash;
+use crate::cache::ScanCache;
+use crate::hash::ContentInfo;
+use crate::{entry_point, hash, license};
 use ignore::WalkBuilder;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs;
 use std::path::Path;
-use topo_core::{FileInfo, FileRole, Language};
+use std::time::{Duration, Instant, SystemTime};
+use topo_core::{FileInfo, FileProvider, FileRole, Language, TopoError};
+
+/// Directories never scanned: VCS internals and topo's own state.
+const RESERVED_DIRS: &[&str] = &[".git", ".hg", ".svn", ".topo"];
+
+/// Directories skipped by default, regardless of .gitignore, as universally
+/// non-source content. `[scan] keep_dirs` scans them anyway.
+pub const DEFAULT_SKIP_DIRS: &[&str] = &["node_modules", "__pycache__", ".venv", "venv", ".env"];
+
+/// Whether directories named `name` are skipped without consulting ignore
+/// files: reserved, or in `skip_dirs`.
+fn is_skipped_dir(skip_dirs: &[String], name: &str) -> bool {
+    RESERVED_DIRS.contains(&name) || skip_dirs.iter().any(|d| d == name)
+}
+
+/// Where a scan spent its time.
+#[derive(Debug, Clone, Default)]
+pub struct ScanStats {
+    /// Files read and hashed because the scan cache had no match.
+    pub files_hashed: usize,
+    /// Files whose content metadata came from the scan cache.
+    pub files_reused: usize,
+    /// Time spent reading and hashing files.
+    pub hash: Duration,
+    /// Wall-clock time of the whole scan, hashing included.
+    pub wall: Duration,
+}
+
+impl ScanStats {
+    /// Time spent walking the tree and tagging files, outside hashing.
+    pub fn walk(&self) -> Duration {
+        self.wall.saturating_sub(self.hash)
+    }
+}
 
 /// Walks a directory tree, respecting .gitignore rules, and produces `FileInfo` entries.
 pub struct Scanner<'a> {
     root: &'a Path,
+    ignore: Vec<String>,
+    skip_dirs: Vec<String>,
+    provider: Option<&'a dyn FileProvider>,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(root: &'a Path) -> Self {
-        Self { root }
-    }
-
-    /// Directories that are always excluded from scanning, regardless of .gitignore.
-    /// These are either VCS internals or universally non-source content.
-    const ALWAYS_SKIP_DIRS: &'static [&'static str] = &[
-        ".git",
-        "node_modules",
-        ".topo",
-        "__pycache__",
-        ".venv",
-        "venv",
-        ".env",
-        ".svn",
-        ".hg",
-    ];
+        Self {
+            root,
+            ignore: Vec::new(),
+            skip_dirs: DEFAULT_SKIP_DIRS.iter().map(|d| d.to_string()).collect(),
+            provider: None,
+        }
+    }
+
+    /// Also skip paths matching these gitignore-style globs, relative to the root.
+    pub fn ignore(mut self, globs: &[String]) -> Self {
+        self.ignore = globs.to_vec();
+        self
+    }
+
+    /// Also skip directories with these names, at any depth.
+    pub fn skip_dirs(mut self, names: &[String]) -> Self {
+        for name in names {
+            if !self.skip_dirs.contains(name) {
+                self.skip_dirs.push(name.clone());
+            }
+        }
+        self
+    }
+
+    /// Scan directories with these names even though they are skipped by
+    /// default. Reserved directories (`.git`, `.topo`) are always skipped.
+    pub fn keep_dirs(mut self, names: &[String]) -> Self {
+        self.skip_dirs.retain(|d| !names.contains(d));
+        self
+    }
+
+    /// Take the content of the provider's overlaid paths from the provider
+    /// rather than disk, and scan those missing from disk too, unless
+    /// ignored.
+    pub fn provider(mut self, provider: &'a dyn FileProvider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    fn overlaid(&self) -> Vec<String> {
+        self.provider.map(|p| p.overlaid()).unwrap_or_default()
+    }
+
+    fn ignore_matcher(&self) -> Result<Gitignore, TopoError> {
+        let mut builder = GitignoreBuilder::new(self.root);
+        for glob in &self.ignore {
+            builder
+                .add_line(None, glob)
+                .map_err(|e| TopoError::Config(format!("scan.ignore: {e}")))?;
+        }
+        builder
+            .build()
+            .map_err(|e| TopoError::Config(format!("scan.ignore: {e}")))
+    }
 
     /// Scan the directory tree and return metadata for all non-ignored files.
     pub fn scan(&self) -> anyhow::Result<Vec<FileInfo>> {
+        self.scan_with(&mut ScanCache::default())
+    }
+
+    /// Like [`scan`](Self::scan), but files whose size and modification
+    /// time match their entry in `cache` are not read again. Afterwards
+    /// `cache` holds this scan.
+    pub fn scan_with(&self, cache: &mut ScanCache) -> anyhow::Result<Vec<FileInfo>> {
+        self.scan_with_stats(cache).map(|(files, _)| files)
+    }
+
+    /// [`scan_with`](Self::scan_with), also reporting how long hashing took.
+    pub fn scan_with_stats(
+        &self,
+        cache: &mut ScanCache,
+    ) -> anyhow::Result<(Vec<FileInfo>, ScanStats)> {
+        let wall = Instant::now();
+        let started = SystemTime::now();
         let mut files = Vec::new();
+        let mut scanned = Vec::new();
+        let mut stats = ScanStats::default();
+        let ignored = self.ignore_matcher()?;
+        let skip_dirs = self.skip_dirs.clone();
+        let overlaid = self.overlaid();
 
         let walker = WalkBuilder::new(self.root)
             .hidden(false) // don't skip dotfiles by default
             .git_ignore(true)
             .git_global(true)
             .git_exclude(true)
-            .filter_entry(|entry| {
-                // Skip directories that should always be excluded
+            .filter_entry(move |entry| {
+                // Worktrees and submodules have a `.git` file instead of a directory
+                if entry.depth() > 0 && entry.file_name() == ".git" {
+                    return false;
+                }
+
+                // Skipped directories win over ignore files, even a `!dir/` rule
                 if entry.file_type().is_some_and(|ft| ft.is_dir())
                     && let Some(name) = entry.file_name().to_str()
-                    && Self::ALWAYS_SKIP_DIRS.contains(&name)
+                    && is_skipped_dir(&skip_dirs, name)
                 {
                     return false;
                 }
-                true
+
+                let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+                !ignored.matched(entry.path(), is_dir).is_ignore()
             })
             .build();
 
@@ -72,40 +180,240 @@ impl<'a> Scanner<'a> {
                 continue;
             }
 
-            // Always use forward slashes for consistent cross-platform paths
+            let Ok(metadata) = path.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
             let rel_str = rel_path.to_string_lossy().replace('\\', "/");
-
-            // Get file metadata
-            let metadata = match path.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
+            // Overlaid content isn't cached against the file on disk
+            if overlaid.binary_search(&rel_str).is_ok() {
+                continue;
+            }
+            let content = match cache.get(&rel_str, &metadata) {
+                Some(content) => {
+                    stats.files_reused += 1;
+                    content
+                }
+                None => {
+                    let hashing = Instant::now();
+                    let content = hash::read_content_info(path);
+                    stats.hash += hashing.elapsed();
+                    match content {
+                        Ok(content) => {
+                            stats.files_hashed += 1;
+                            content
+                        }
+                        Err(_) => continue,
+                    }
+                }
             };
+            files.push(Self::describe(
+                path,
+                rel_path,
+                metadata.len(),
+                hash::is_executable(&metadata),
+                &content,
+            ));
+            scanned.push((rel_str, metadata, content));
+        }
+        log::debug!(
+            "Reused {} of {} files from the scan cache",
+            stats.files_reused,
+            files.len()
+        );
+        cache.replace(scanned, started);
 
-            // Skip non-regular files
-            if !metadata.is_file() {
+        for rel in &overlaid {
+            if !self.is_ignored(rel, false)?
+                && let Some(info) = self.overlay_info(rel)
+            {
+                files.push(info);
+            }
+        }
+
+        entry_point::tag_dockerfile_targets(self.root, &mut files);
+        license::tag_licenses(self.root, &mut files);
+
+        // Sort by path for deterministic output
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        stats.wall = wall.elapsed();
+        Ok((files, stats))
+    }
+
+    /// Bring an earlier [`scan`](Self::scan) result up to date after the
+    /// given repo-relative paths changed on disk, re-hashing only those.
+    ///
+    /// Modified files are re-read, deleted files (and files under a deleted
+    /// directory) are dropped, and new files are added unless ignored. New
+    /// directories and changes to license files or Dockerfiles, which affect
+    /// other files' metadata, fall back to a full scan. Overlaid paths keep
+    /// the provider's content. Returns whether anything changed.
+    pub fn update(&self, files: &mut Vec<FileInfo>, changed: &[String]) -> anyhow::Result<bool> {
+        let mut dirty = false;
+        let overlaid = self.overlaid();
+        for rel in changed {
+            let rel = rel.trim_matches('/');
+            let abs = self.root.join(rel);
+            let is_dir = abs.is_dir();
+            if rel.is_empty() || self.is_ignored(rel, is_dir)? {
+                continue;
+            }
+            // The provider's content stands until the overlay changes
+            if !is_dir && overlaid.iter().any(|p| p == rel) {
                 continue;
             }
+            let file_name = rel.rsplit('/').next().unwrap_or(rel);
+            if is_dir || license::is_license_file(file_name) || entry_point::is_dockerfile(rel) {
+                *files = self.scan()?;
+                return Ok(true);
+            }
 
-            let size = metadata.len();
-            let language = Language::from_path(rel_path);
-            let role = FileRole::from_path(rel_path);
+            let existing = files.binary_search_by(|f| f.path.as_str().cmp(rel));
+            match (Self::file_info(&abs, Path::new(rel)), existing) {
+                (Some(info), Ok(i)) => {
+                    if info.sha256 != files[i].sha256 || info.executable != files[i].executable {
+                        files[i] = info;
+                        dirty = true;
+                    }
+                }
+                (Some(info), Err(i)) => {
+                    files.insert(i, info);
+                    dirty = true;
+                }
+                (None, _) => {
+                    let prefix = format!("{rel}/");
+                    let before = files.len();
+                    files.retain(|f| f.path != rel && !f.path.starts_with(&prefix));
+                    dirty |= files.len() != before;
+                }
+            }
+        }
 
-            let sha256 = match hash::sha256_file(path) {
-                Ok(h) => h,
-                Err(_) => continue,
+        if dirty {
+            entry_point::tag_dockerfile_targets(self.root, files);
+            license::tag_licenses(self.root, files);
+        }
+        Ok(dirty)
+    }
+
+    /// Whether a scan would skip the repo-relative `path`: a skipped
+    /// directory, an `ignore` glob, or a rule in a `.gitignore` or `.ignore`
+    /// file between the root and the path (or `.git/info/exclude`). Global
+    /// git excludes are not consulted.
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> Result<bool, TopoError> {
+        let components: Vec<&str> = path.split('/').collect();
+        let dirs = if is_dir {
+            &components[..]
+        } else {
+            &components[..components.len() - 1]
+        };
+        if dirs.iter().any(|c| is_skipped_dir(&self.skip_dirs, c)) {
+            return Ok(true);
+        }
+
+        let abs = self.root.join(path);
+        if self
+            .ignore_matcher()?
+            .matched_path_or_any_parents(&abs, is_dir)
+            .is_ignore()
+        {
+            return Ok(true);
+        }
+
+        // Deeper ignore files take precedence, and `.ignore` over `.gitignore`
+        let mut sources = Vec::new();
+        for depth in (0..components.len()).rev() {
+            let dir = self.root.join(components[..depth].join("/"));
+            sources.push((dir.clone(), dir.join(".ignore")));
+            sources.push((dir.clone(), dir.join(".gitignore")));
+        }
+        sources.push((self.root.to_path_buf(), self.root.join(".git/info/exclude")));
+
+        for (dir, file) in sources {
+            if !file.is_file() {
+                continue;
+            }
+            let mut builder = GitignoreBuilder::new(&dir);
+            builder.add(&file);
+            let Ok(matcher) = builder.build() else {
+                continue;
             };
+            let matched = matcher.matched_path_or_any_parents(&abs, is_dir);
+            if matched.is_ignore() {
+                return Ok(true);
+            }
+            if matched.is_whitelist() {
+                return Ok(false);
+            }
+        }
+        Ok(false)
+    }
 
-            files.push(FileInfo {
-                path: rel_str,
-                size,
-                language,
-                role,
-                sha256,
-            });
+    /// Metadata for the file at `path`, or `None` if it is not a readable
+    /// regular file.
+    fn file_info(path: &Path, rel_path: &Path) -> Option<FileInfo> {
+        // Skip non-regular files
+        let metadata = path.metadata().ok()?;
+        if !metadata.is_file() {
+            return None;
         }
+        let content = hash::read_content_info(path).ok()?;
+        Some(Self::describe(
+            path,
+            rel_path,
+            metadata.len(),
+            hash::is_executable(&metadata),
+            &content,
+        ))
+    }
 
-        // Sort by path for deterministic output
-        files.sort_by(|a, b| a.path.cmp(&b.path));
-        Ok(files)
+    /// Metadata for the overlaid repo-relative `rel` from the provider's
+    /// content, or `None` if the provider can't read it. The executable bit
+    /// comes from the file on disk, if there is one.
+    fn overlay_info(&self, rel: &str) -> Option<FileInfo> {
+        let bytes = self.provider?.read(rel).ok()?;
+        let path = self.root.join(rel);
+        let executable = fs::metadata(&path).is_ok_and(|m| hash::is_executable(&m));
+        Some(Self::describe(
+            &path,
+            Path::new(rel),
+            bytes.len() as u64,
+            executable,
+            &hash::content_info(&bytes),
+        ))
+    }
+
+    /// Metadata for a regular file from its size, mode and what was read
+    /// from its content.
+    fn describe(
+        path: &Path,
+        rel_path: &Path,
+        size: u64,
+        executable: bool,
+        content: &ContentInfo,
+    ) -> FileInfo {
+        // Always use forward slashes for consistent cross-platform paths
+        let rel_str = rel_path.to_string_lossy().replace('\\', "/");
+
+        let language = Language::from_path(rel_path);
+        let role = FileRole::from_path(rel_path);
+
+        let entry_point =
+            entry_point::is_entry_point(&rel_str, path, executable, content.shebang.is_some());
+
+        FileInfo {
+            path: rel_str,
+            size,
+            language,
+            role,
+            sha256: content.sha256,
+            lines: content.lines,
+            executable,
+            shebang: content.shebang.clone(),
+            entry_point,
+            license: content.license.clone(),
+        }
     }
 }
@@ -1,6 +1,7 @@
-use crate::tokenizer::Tokenizer;
+use crate::phrase::PhraseMatcher;
+use crate::tokenizer::{StopWords, Tokenizer};
 use std::collections::HashMap;
-use topo_core::TermFreqs;
+use topo_core::{TermFreqs, TermPositions};
 
 /// BM25F field weights.
 const W_FILENAME: f64 = 5.0;
@@ -11,6 +12,10 @@ const W_BODY: f64 = 1.0;
 const K1: f64 = 1.2;
 const B: f64 = 0.75;
 
+/// Weight of a quoted phrase's adjacent occurrences over its terms' loose
+/// ones: an exact phrase match is the stronger signal.
+const PHRASE_BOOST: f64 = 1.5;
+
 /// Precomputed corpus statistics needed for IDF calculation.
 pub struct CorpusStats {
     pub total_docs: usize,
@@ -56,11 +61,16 @@ impl CorpusStats {
     /// for the filename field only. This enables BM25F scoring before the deep index
     /// is built.
     pub fn from_paths(paths: &[&str]) -> Self {
+        Self::from_paths_with(paths, &StopWords::default())
+    }
+
+    /// Like [`CorpusStats::from_paths`], dropping `stop_words` from the paths.
+    pub fn from_paths_with(paths: &[&str], stop_words: &StopWords) -> Self {
         let mut doc_frequencies: HashMap<String, usize> = HashMap::new();
         let mut total_length = 0u64;
 
         for path in paths {
-            let tokens = Tokenizer::tokenize(path);
+            let tokens = Tokenizer::tokenize_with(path, stop_words);
             let unique: std::collections::HashSet<&String> = tokens.iter().collect();
             for token in &unique {
                 *doc_frequencies.entry((*token).clone()).or_default() += 1;
@@ -86,69 +96,168 @@ impl CorpusStats {
 ///
 /// Field weights: filename=5.0, symbols=3.0, body=1.0.
 /// Parameters: k1=1.2, b=0.75.
+///
+/// Negated query words (`-vendor`) never add to the score; use
+/// [`Bm25fScorer::negated`] to find the files they mark.
+///
+/// Quoted phrases (`"token bucket"`) count only where their terms are
+/// adjacent, which needs term positions: [`Bm25fScorer::score_with_positions`]
+/// enforces adjacency and rewards it, while [`Bm25fScorer::score`] falls back
+/// to matching phrase terms loosely.
 pub struct Bm25fScorer {
     query_tokens: Vec<String>,
+    negative_tokens: Vec<String>,
+    phrases: PhraseMatcher,
     stats: CorpusStats,
+    stop_words: StopWords,
 }
 
 impl Bm25fScorer {
     pub fn new(query: &str, stats: CorpusStats) -> Self {
+        Self::with_stop_words(query, stats, StopWords::default())
+    }
+
+    /// Like [`Bm25fScorer::new`], dropping `stop_words` from the query and paths.
+    pub fn with_stop_words(query: &str, stats: CorpusStats, stop_words: StopWords) -> Self {
+        let (query_tokens, negative_tokens) = Tokenizer::tokenize_query_with(query, &stop_words);
+        let (positive, _) = Tokenizer::split_negated(query);
         Self {
-            query_tokens: Tokenizer::tokenize(query),
+            query_tokens,
+            negative_tokens,
+            phrases: PhraseMatcher::new(&positive, &stop_words),
             stats,
+            stop_words,
         }
     }
 
+    /// Whether the query has quoted phrases of two or more terms.
+    pub fn has_phrases(&self) -> bool {
+        !self.phrases.is_empty()
+    }
+
     /// Compute BM25F score for a document given its term frequencies and doc length.
     pub fn score(&self, term_freqs: &HashMap<String, TermFreqs>, doc_length: u32) -> f64 {
         if self.query_tokens.is_empty() || self.stats.total_docs == 0 {
             return 0.0;
         }
+        let length_norm = self.length_norm(doc_length);
+        self.query_tokens
+            .iter()
+            .map(|token| {
+                let tf = term_freqs.get(token).map(weighted).unwrap_or(0.0);
+                self.idf(token) * saturate(tf, length_norm)
+            })
+            .sum()
+    }
 
-        let n = self.stats.total_docs as f64;
-        let avgdl = self.stats.avg_doc_length;
-        let dl = doc_length as f64;
-
-        // Length normalization factor
-        let length_norm = 1.0 - B + B * (dl / avgdl);
-
-        let mut score = 0.0;
-        for token in &self.query_tokens {
-            let df = self.stats.doc_frequencies.get(token).copied().unwrap_or(0) as f64;
-
-            // IDF: log((N - df + 0.5) / (df + 0.5) + 1)
-            let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
-
-            // Weighted term frequency across fields
-            let tf = term_freqs
-                .get(token)
-                .map(|f| {
-                    W_FILENAME * f.filename as f64
-                        + W_SYMBOLS * f.symbols as f64
-                        + W_BODY * f.body as f64
-                })
-                .unwrap_or(0.0);
-
-            // BM25F formula: IDF * tf_weighted / (tf_weighted + k1 * length_norm)
-            if tf > 0.0 {
-                score += idf * tf / (tf + K1 * length_norm);
-            }
+    /// Like [`Bm25fScorer::score`], but a quoted phrase's terms count only
+    /// where they occur next to each other, weighted by [`PHRASE_BOOST`].
+    pub fn score_with_positions(
+        &self,
+        term_freqs: &HashMap<String, TermFreqs>,
+        positions: &HashMap<String, TermPositions>,
+        doc_length: u32,
+    ) -> f64 {
+        if self.phrases.is_empty() {
+            return self.score(term_freqs, doc_length);
+        }
+        if self.query_tokens.is_empty() || self.stats.total_docs == 0 {
+            return 0.0;
         }
+        let length_norm = self.length_norm(doc_length);
+
+        let loose: f64 = self
+            .query_tokens
+            .iter()
+            .filter(|token| !self.phrases.contains_term(token))
+            .map(|token| {
+                let tf = term_freqs.get(token).map(weighted).unwrap_or(0.0);
+                self.idf(token) * saturate(tf, length_norm)
+            })
+            .sum();
+        let phrases: f64 = self
+            .phrases
+            .phrases()
+            .iter()
+            .map(|phrase| {
+                let tf = weighted(&PhraseMatcher::occurrences(phrase, positions));
+                let idf: f64 = phrase.iter().map(|token| self.idf(token)).sum();
+                PHRASE_BOOST * idf * saturate(tf, length_norm)
+            })
+            .sum();
+        loose + phrases
+    }
 
-        score
+    /// IDF: log((N - df + 0.5) / (df + 0.5) + 1)
+    fn idf(&self, token: &str) -> f64 {
+        let n = self.stats.total_docs as f64;
+        let df = self.stats.doc_frequencies.get(token).copied().unwrap_or(0) as f64;
+        ((n - df + 0.5) / (df + 0.5) + 1.0).ln()
+    }
+
+    /// Length normalization factor for a document of `doc_length` terms.
+    fn length_norm(&self, doc_length: u32) -> f64 {
+        1.0 - B + B * (doc_length as f64 / self.stats.avg_doc_length)
     }
 
     /// Score a file using only its path (shallow mode).
     ///
     /// Tokenizes the path and puts all term frequencies into the filename field.
     pub fn score_path(&self, path: &str) -> f64 {
-        let tokens = Tokenizer::tokenize(path);
+        let (term_freqs, positions, doc_length) = self.path_terms(path);
+        self.score_with_positions(&term_freqs, &positions, doc_length)
+    }
+
+    /// Whether a negated query term names the file: it appears in the path
+    /// or among the file's symbols. Body mentions don't count, so `-test`
+    /// leaves files with an inline test module alone.
+    pub fn negated(&self, path: &str, term_freqs: Option<&HashMap<String, TermFreqs>>) -> bool {
+        if self.negative_tokens.is_empty() {
+            return false;
+        }
+        let (path_terms, _, _) = self.path_terms(path);
+        self.negative_tokens.iter().any(|token| {
+            path_terms.contains_key(token)
+                || term_freqs
+                    .and_then(|tf| tf.get(token))
+                    .is_some_and(|f| f.filename > 0 || f.symbols > 0)
+        })
+    }
+
+    fn path_terms(
+        &self,
+        path: &str,
+    ) -> (
+        HashMap<String, TermFreqs>,
+        HashMap<String, TermPositions>,
+        u32,
+    ) {
+        let tokens = Tokenizer::tokenize_with(path, &self.stop_words);
         let mut term_freqs: HashMap<String, TermFreqs> = HashMap::new();
-        for token in &tokens {
+        let mut positions: HashMap<String, TermPositions> = HashMap::new();
+        for (i, token) in tokens.iter().enumerate() {
             term_freqs.entry(token.clone()).or_default().filename += 1;
+            positions
+                .entry(token.clone())
+                .or_default()
+                .filename
+                .push(i as u32);
         }
-        let doc_length = tokens.len() as u32;
-        self.score(&term_freqs, doc_length)
+        (term_freqs, positions, tokens.len() as u32)
+    }
+}
+
+/// Term frequency summed across fields by their weights.
+fn weighted(f: &TermFreqs) -> f64 {
+    W_FILENAME * f.filename as f64 + W_SYMBOLS * f.symbols as f64 + W_BODY * f.body as f64
+}
+
+/// BM25F saturation: tf_weighted / (tf_weighted + k1 * length_norm).
+fn saturate(tf: f64, length_norm: f64) -> f64 {
+    if tf > 0.0 {
+        tf / (tf + K1 * length_norm)
+    } else {
+        0.0
     }
 }
 
@@ -184,6 +293,52 @@ mod tests {
         assert!(score > 0.0);
     }
 
+    #[test]
+    fn bm25f_negated_terms_never_boost() {
+        let stats = CorpusStats::from_paths(&sample_paths());
+        let scorer = Bm25fScorer::new("auth -test", stats);
+        assert_eq!(
+            scorer.score_path("tests/auth_test.rs"),
+            Bm25fScorer::new("auth", CorpusStats::from_paths(&sample_paths()))
+                .score_path("tests/auth_test.rs")
+        );
+        assert!(scorer.negated("tests/auth_test.rs", None));
+        assert!(!scorer.negated("src/auth/handler.rs", None));
+
+        let mut tf = HashMap::new();
+        tf.insert(
+            "test".to_string(),
+            TermFreqs {
+                filename: 0,
+                symbols: 0,
+                body: 4,
+            },
+        );
+        assert!(!scorer.negated("src/auth/handler.rs", Some(&tf)));
+        tf.get_mut("test").unwrap().symbols = 1;
+        assert!(scorer.negated("src/auth/handler.rs", Some(&tf)));
+    }
+
+    #[test]
+    fn bm25f_phrases_require_adjacency() {
+        let paths = [
+            "src/token_bucket.rs",
+            "src/bucket/token.rs",
+            "src/limiter.rs",
+        ];
+        let scorer = Bm25fScorer::new(r#""token bucket""#, CorpusStats::from_paths(&paths));
+        assert!(scorer.has_phrases());
+        assert!(scorer.score_path("src/token_bucket.rs") > 0.0);
+        assert_eq!(scorer.score_path("src/bucket/token.rs"), 0.0);
+
+        // Without positions the phrase terms match loosely
+        let (tf, _, dl) = scorer.path_terms("src/bucket/token.rs");
+        assert!(scorer.score(&tf, dl) > 0.0);
+
+        let loose = Bm25fScorer::new("token bucket", CorpusStats::from_paths(&paths));
+        assert!(scorer.score_path("src/token_bucket.rs") > loose.score_path("src/token_bucket.rs"));
+    }
+
     #[test]
     fn bm25f_no_match_scores_zero() {
         let stats = CorpusStats::from_paths(&sample_paths());
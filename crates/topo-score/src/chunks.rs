@@ -0,0 +1,148 @@
+use crate::tokenizer::{StopWords, Tokenizer};
+use topo_core::{Chunk, ChunkKind, ChunkMatch};
+
+/// Default number of chunks reported per file.
+const DEFAULT_LIMIT: usize = 3;
+
+/// Weight of a query term appearing in a chunk's name, relative to one
+/// occurrence in its body.
+const NAME_WEIGHT: f64 = 2.0;
+
+/// Ranks the chunks of one file against a query: a term in the chunk name
+/// counts most, body occurrences add with diminishing returns. Import
+/// blocks are never reported.
+pub struct ChunkScorer {
+    query: String,
+    stop_words: StopWords,
+    limit: usize,
+}
+
+impl ChunkScorer {
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: query.to_string(),
+            stop_words: StopWords::default(),
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    /// Replace the stop-word list dropped from the query and chunk text.
+    pub fn stop_words(mut self, stop_words: StopWords) -> Self {
+        self.stop_words = stop_words;
+        self
+    }
+
+    /// Report at most this many chunks per file.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// The best matching chunks, best first; ties go to the earlier chunk.
+    pub fn score(&self, chunks: &[Chunk]) -> Vec<ChunkMatch> {
+        let (mut terms, _) = Tokenizer::tokenize_query_with(&self.query, &self.stop_words);
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<ChunkMatch> = chunks
+            .iter()
+            .filter(|c| c.kind != ChunkKind::Import)
+            .filter_map(|chunk| {
+                let score = self.score_chunk(&terms, chunk);
+                (score > 0.0).then(|| ChunkMatch {
+                    name: chunk.name.clone(),
+                    kind: chunk.kind,
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    score,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(a.start_line.cmp(&b.start_line))
+        });
+        matches.truncate(self.limit);
+        matches
+    }
+
+    fn score_chunk(&self, terms: &[String], chunk: &Chunk) -> f64 {
+        let name = Tokenizer::tokenize_with(&chunk.name, &self.stop_words);
+        let body = Tokenizer::tokenize_with(&chunk.content, &self.stop_words);
+        terms
+            .iter()
+            .map(|term| {
+                let in_name = if name.contains(term) {
+                    NAME_WEIGHT
+                } else {
+                    0.0
+                };
+                let tf = body.iter().filter(|t| *t == term).count() as f64;
+                in_name + tf.ln_1p()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(kind: ChunkKind, name: &str, start: u32, end: u32, content: &str) -> Chunk {
+        Chunk {
+            kind,
+            name: name.to_string(),
+            start_line: start,
+            end_line: end,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn name_matches_rank_first() {
+        let chunks = vec![
+            chunk(
+                ChunkKind::Import,
+                "use",
+                1,
+                1,
+                "use crate::auth::validate_token;",
+            ),
+            chunk(
+                ChunkKind::Function,
+                "login",
+                3,
+                8,
+                "fn login() { validate_token(); }",
+            ),
+            chunk(
+                ChunkKind::Function,
+                "validate_token",
+                10,
+                20,
+                "fn validate_token(token: &str) {}",
+            ),
+            chunk(ChunkKind::Type, "Session", 22, 30, "struct Session {}"),
+        ];
+        let matches = ChunkScorer::new("validate token").score(&chunks);
+
+        let names: Vec<&str> = matches.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["validate_token", "login"]);
+        assert_eq!(matches[0].start_line, 10);
+        assert_eq!(matches[0].end_line, 20);
+        assert_eq!(matches[0].kind, ChunkKind::Function);
+    }
+
+    #[test]
+    fn limit_and_empty_query() {
+        let chunks: Vec<Chunk> = (0..5)
+            .map(|i| chunk(ChunkKind::Function, &format!("parse_{i}"), i, i, "parse"))
+            .collect();
+        assert_eq!(ChunkScorer::new("parse").limit(2).score(&chunks).len(), 2);
+        assert!(ChunkScorer::new("the").score(&chunks).is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::git_recency::{git_commit_counts, log_scores};
+use std::collections::HashMap;
+use std::path::Path;
+use topo_core::ChurnConfig;
+
+/// Days of history counted when `[churn] window_days` is unset. Longer
+/// than the git recency window: churn marks code that keeps changing, not
+/// code that changed lately.
+pub const DEFAULT_CHURN_WINDOW_DAYS: u32 = 365;
+
+/// Commits touching each file within a window of days.
+#[derive(Debug, Clone, Default)]
+pub struct Churn {
+    commits: HashMap<String, u32>,
+}
+
+impl Churn {
+    /// The window `config` sets, in days.
+    pub fn window(config: &ChurnConfig) -> u32 {
+        config.window_days.unwrap_or(DEFAULT_CHURN_WINDOW_DAYS)
+    }
+
+    /// Count the commits of the last `window_days` days in `repo_root`,
+    /// following renames; empty outside a git repository.
+    pub fn load(repo_root: &Path, window_days: u32) -> anyhow::Result<Self> {
+        Ok(Self::from_counts(
+            git_commit_counts(repo_root, window_days)?.counts,
+        ))
+    }
+
+    pub fn from_counts(commits: HashMap<String, u32>) -> Self {
+        Self { commits }
+    }
+
+    /// Whether no file was committed within the window.
+    pub fn is_empty(&self) -> bool {
+        self.commits.is_empty()
+    }
+
+    /// Commits touching `path` within the window.
+    pub fn commits(&self, path: &str) -> u32 {
+        self.commits.get(path).copied().unwrap_or(0)
+    }
+
+    /// Commit counts normalized to [0.0, 1.0] on a log scale, 1.0 for the
+    /// most-changed file.
+    pub fn scores(&self) -> HashMap<String, f64> {
+        log_scores(&self.commits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_are_log_normalized() {
+        let churn = Churn::from_counts(HashMap::from([
+            ("src/hot.rs".to_string(), 15),
+            ("src/warm.rs".to_string(), 3),
+            ("src/cold.rs".to_string(), 1),
+        ]));
+        let scores = churn.scores();
+        assert_eq!(scores["src/hot.rs"], 1.0);
+        assert!(scores["src/warm.rs"] > scores["src/cold.rs"]);
+        assert!((scores["src/warm.rs"] - 0.5).abs() < 1e-12);
+        assert_eq!(churn.commits("src/warm.rs"), 3);
+        assert_eq!(churn.commits("src/none.rs"), 0);
+    }
+
+    #[test]
+    fn non_git_directory_has_no_churn() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Churn::load(dir.path(), 30).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+use crate::pagerank::ImportGraph;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use topo_core::ScoredFile;
+
+/// Label propagation rounds; most graphs settle within a handful.
+const MAX_ROUNDS: usize = 20;
+
+/// Module clusters of an import graph: groups of files that import each
+/// other more than they import the rest of the repository.
+///
+/// Found by label propagation over the graph with edge direction dropped
+/// and weights kept. Each cluster is labeled with the directory most of its
+/// files share; files the graph doesn't connect fall back to their own
+/// directory.
+#[derive(Debug, Clone, Default)]
+pub struct Clusters {
+    labels: HashMap<String, String>,
+}
+
+impl Clusters {
+    pub fn of(graph: &ImportGraph) -> Self {
+        let mut neighbors: BTreeMap<&str, HashMap<&str, f64>> = BTreeMap::new();
+        for (from, to, weight) in graph.edges() {
+            if from == to {
+                continue;
+            }
+            *neighbors.entry(from).or_default().entry(to).or_default() += weight;
+            *neighbors.entry(to).or_default().entry(from).or_default() += weight;
+        }
+
+        // Every file starts in its own cluster, then repeatedly joins the
+        // cluster its neighbors weigh most; ties go to the smaller label so
+        // the result doesn't depend on hash order
+        let mut label: HashMap<&str, &str> = neighbors.keys().map(|&n| (n, n)).collect();
+        for _ in 0..MAX_ROUNDS {
+            let mut changed = false;
+            for (&node, adjacent) in &neighbors {
+                let mut tally: HashMap<&str, f64> = HashMap::new();
+                for (&other, &weight) in adjacent {
+                    *tally.entry(label[other]).or_default() += weight;
+                }
+                let best = tally
+                    .into_iter()
+                    .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(a.0)))
+                    .map(|(l, _)| l)
+                    .unwrap_or(node);
+                if best != label[node] {
+                    label.insert(node, best);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut members: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (node, l) in label {
+            members.entry(l).or_default().push(node);
+        }
+        let mut clusters: Vec<Vec<&str>> = members
+            .into_values()
+            .filter(|files| files.len() >= 2)
+            .collect();
+        for files in &mut clusters {
+            files.sort_unstable();
+        }
+        clusters.sort_unstable();
+
+        let mut labels = HashMap::new();
+        let mut taken: HashMap<String, usize> = HashMap::new();
+        for files in clusters {
+            let name = common_directory(&files);
+            let n = taken.entry(name.clone()).or_default();
+            *n += 1;
+            let name = if *n == 1 {
+                name
+            } else {
+                format!("{name} #{n}")
+            };
+            for file in files {
+                labels.insert(file.to_string(), name.clone());
+            }
+        }
+        Self { labels }
+    }
+
+    /// The label of the cluster `path` belongs to.
+    pub fn label(&self, path: &str) -> String {
+        self.labels
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| directory(path).to_string())
+    }
+
+    /// Label each file with its cluster and reorder `files` cluster by
+    /// cluster, clusters in the order of their best-ranked file and files
+    /// in rank order within each.
+    pub fn group(&self, files: &mut [ScoredFile]) {
+        let mut order: HashMap<String, usize> = HashMap::new();
+        for file in files.iter_mut() {
+            let label = self.label(&file.path);
+            let next = order.len();
+            order.entry(label.clone()).or_insert(next);
+            file.cluster = Some(label);
+        }
+        files.sort_by_key(|f| f.cluster.as_ref().map(|l| order[l]));
+    }
+}
+
+/// The directory most of `files` sit in; ties go to the shortest, then
+/// the first alphabetically.
+fn common_directory(files: &[&str]) -> String {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for file in files {
+        *counts.entry(directory(file)).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| {
+            a.1.cmp(&b.1)
+                .then(b.0.len().cmp(&a.0.len()))
+                .then(b.0.cmp(a.0))
+        })
+        .map(|(dir, _)| dir.to_string())
+        .unwrap_or_default()
+}
+
+/// The parent directory of `path`, or `.` at the repository root.
+fn directory(path: &str) -> &str {
+    match Path::new(path).parent().and_then(|p| p.to_str()) {
+        Some("") | None => ".",
+        Some(dir) => dir,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> ImportGraph {
+        ImportGraph::from_imports(&[
+            (
+                "src/auth/login.rs".to_string(),
+                vec!["src/auth/session.rs".to_string()],
+            ),
+            (
+                "src/auth/session.rs".to_string(),
+                vec!["src/auth/token.rs".to_string()],
+            ),
+            (
+                "src/render/page.rs".to_string(),
+                vec!["src/render/layout.rs".to_string()],
+            ),
+            ("src/main.rs".to_string(), Vec::new()),
+        ])
+    }
+
+    #[test]
+    fn connected_files_share_a_cluster() {
+        let clusters = Clusters::of(&graph());
+        assert_eq!(clusters.label("src/auth/login.rs"), "src/auth");
+        assert_eq!(clusters.label("src/auth/token.rs"), "src/auth");
+        assert_eq!(clusters.label("src/render/layout.rs"), "src/render");
+        // Unconnected files fall back to their directory
+        assert_eq!(clusters.label("src/main.rs"), "src");
+        assert_eq!(clusters.label("README.md"), ".");
+    }
+
+    #[test]
+    fn group_orders_clusters_by_their_best_file() {
+        let scored = |path: &str| ScoredFile {
+            path: path.to_string(),
+            score: 1.0,
+            signals: Default::default(),
+            tokens: 0,
+            language: topo_core::Language::Rust,
+            role: topo_core::FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
+        };
+        let mut files = vec![
+            scored("src/render/page.rs"),
+            scored("src/auth/token.rs"),
+            scored("src/render/layout.rs"),
+            scored("src/auth/login.rs"),
+        ];
+        Clusters::of(&graph()).group(&mut files);
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "src/render/page.rs",
+                "src/render/layout.rs",
+                "src/auth/token.rs",
+                "src/auth/login.rs"
+            ]
+        );
+        assert_eq!(files[2].cluster.as_deref(), Some("src/auth"));
+    }
+}
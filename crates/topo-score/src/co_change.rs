@@ -0,0 +1,204 @@
+use anyhow::bail;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+use topo_core::ScoredFile;
+
+/// Recent commits mined for co-changes.
+const CO_CHANGE_COMMITS: usize = 500;
+/// Commits touching more files than this are mass edits (renames,
+/// reformatting) and say nothing about which files belong together.
+const MAX_COMMIT_FILES: usize = 50;
+/// Top-ranked files used as seeds when no changed files are given.
+const SEED_FILES: usize = 5;
+/// ...each scoring at least this share of the top file, so weak matches
+/// on path alone don't seed.
+const SEED_SHARE: f64 = 0.5;
+
+/// The files of each recent commit, for finding files that historically
+/// change together with a set of seed files.
+///
+/// Seeds are the files changed since a revision (`--since`) when given,
+/// else the top of the base ranking.
+#[derive(Debug, Clone, Default)]
+pub struct CoChange {
+    commits: Vec<Vec<String>>,
+    seeds: Vec<String>,
+}
+
+impl CoChange {
+    /// The recent commit history of `repo_root`; empty outside a git
+    /// repository.
+    ///
+    /// `--relative` keeps paths relative to `repo_root` even when it is a
+    /// subdirectory of the repository (or a linked worktree).
+    pub fn load(repo_root: &Path) -> anyhow::Result<Self> {
+        let output = Command::new("git")
+            .args([
+                "log",
+                &format!("-n{CO_CHANGE_COMMITS}"),
+                "--name-only",
+                "--relative",
+                "--format=%x1e",
+            ])
+            .current_dir(repo_root)
+            .output()?;
+
+        if !output.status.success() {
+            // Not a git repo or no commits yet — no history to mine
+            return Ok(Self::default());
+        }
+        Ok(Self::parse(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Parse `git log --name-only --format=%x1e` output.
+    pub fn parse(log: &str) -> Self {
+        let commits = log
+            .split('\u{1e}')
+            .map(|commit| {
+                commit
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|files| files.len() >= 2 && files.len() <= MAX_COMMIT_FILES)
+            .collect();
+        Self {
+            commits,
+            seeds: Vec::new(),
+        }
+    }
+
+    /// Seed with these files instead of the top of the ranking.
+    pub fn seeds(mut self, seeds: Vec<String>) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    /// Whether there is no history to mine.
+    pub fn is_empty(&self) -> bool {
+        self.commits.is_empty()
+    }
+
+    /// Co-change scores for the files of `scored`, which is in ranked
+    /// order: the configured seeds, or up to [`SEED_FILES`] files from the
+    /// top of the ranking, are looked up in [`scores`](Self::scores).
+    pub fn scores_for(&self, scored: &[ScoredFile]) -> HashMap<String, f64> {
+        let seeds: Vec<&str> = if self.seeds.is_empty() {
+            let top = scored.first().map_or(0.0, |f| f.score);
+            scored
+                .iter()
+                .take_while(|f| f.score > 0.0 && f.score >= top * SEED_SHARE)
+                .take(SEED_FILES)
+                .map(|f| f.path.as_str())
+                .collect()
+        } else {
+            self.seeds.iter().map(String::as_str).collect()
+        };
+        self.scores(&seeds)
+    }
+
+    /// How often each file changed in the same commit as any of `seeds`,
+    /// normalized to [0.0, 1.0] on a log scale. Seeds count their own
+    /// commits too, so fusing this ranking doesn't sink them below the
+    /// files they pulled in.
+    pub fn scores(&self, seeds: &[&str]) -> HashMap<String, f64> {
+        let seeds: HashSet<&str> = seeds.iter().copied().collect();
+        let mut counts: HashMap<&str, u32> = HashMap::new();
+        for commit in &self.commits {
+            if !commit.iter().any(|f| seeds.contains(f.as_str())) {
+                continue;
+            }
+            for file in commit {
+                *counts.entry(file).or_default() += 1;
+            }
+        }
+
+        let max_count = counts.values().copied().max().unwrap_or(1) as f64;
+        counts
+            .into_iter()
+            .map(|(path, count)| {
+                let score = (1.0 + count as f64).ln() / (1.0 + max_count).ln();
+                (path.to_string(), score)
+            })
+            .collect()
+    }
+}
+
+/// Files that differ between `rev` and the working tree, relative to
+/// `repo_root`.
+pub fn changed_since(repo_root: &Path, rev: &str) -> anyhow::Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["diff", "--name-only", "--relative", rev, "--"])
+        .current_dir(repo_root)
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "git diff {rev} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\u{1e}\n\nsrc/auth.rs\nsrc/session.rs\n\u{1e}\n\nsrc/auth.rs\nsrc/session.rs\nsrc/token.rs\n\u{1e}\n\nsrc/render.rs\nsrc/layout.rs\n\u{1e}\n\nREADME.md\n";
+
+    #[test]
+    fn files_changed_with_seeds_score() {
+        let history = CoChange::parse(LOG);
+        let scores = history.scores(&["src/auth.rs"]);
+        assert_eq!(scores.len(), 3);
+        assert_eq!(scores["src/auth.rs"], 1.0);
+        assert_eq!(scores["src/session.rs"], 1.0);
+        assert!(scores["src/token.rs"] < 1.0);
+        assert!(!scores.contains_key("src/render.rs"));
+        assert!(history.scores(&["README.md"]).is_empty());
+    }
+
+    #[test]
+    fn seeds_default_to_the_top_of_the_ranking() {
+        let scored = |path: &str, score: f64| ScoredFile {
+            path: path.to_string(),
+            score,
+            signals: Default::default(),
+            tokens: 0,
+            language: topo_core::Language::Rust,
+            role: topo_core::FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
+        };
+        let ranked = [scored("src/render.rs", 2.0), scored("src/auth.rs", 0.5)];
+
+        let history = CoChange::parse(LOG);
+        let scores = history.scores_for(&ranked);
+        let mut paths: Vec<&String> = scores.keys().collect();
+        paths.sort();
+        assert_eq!(paths, ["src/layout.rs", "src/render.rs"]);
+
+        let seeded = history.seeds(vec!["src/token.rs".to_string()]);
+        let scores = seeded.scores_for(&ranked);
+        assert!(scores.contains_key("src/auth.rs"));
+        assert!(!scores.contains_key("src/layout.rs"));
+    }
+
+    #[test]
+    fn mass_edits_are_ignored() {
+        let files: Vec<String> = (0..=MAX_COMMIT_FILES).map(|i| format!("f{i}.rs")).collect();
+        let log = format!("\u{1e}\n\n{}\n", files.join("\n"));
+        assert!(CoChange::parse(&log).is_empty());
+    }
+}
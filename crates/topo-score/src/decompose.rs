@@ -0,0 +1,426 @@
+use crate::expand::EXPANSION_WEIGHT;
+use crate::file_type::FileTypeRule;
+use crate::fusion::RrfFusion;
+use crate::hybrid::{HybridScorer, SignalWeights};
+use crate::tokenizer::{StopWords, Tokenizer};
+use crate::vendored::VendoredPolicy;
+use std::collections::{HashMap, HashSet};
+use topo_core::{FileInfo, ScoredFile};
+
+/// Minimum number of query tokens before a task is decomposed.
+const MIN_TOKENS_TO_SPLIT: usize = 12;
+/// Sentences with fewer tokens than this are merged into their neighbor.
+const MIN_SUBQUERY_TOKENS: usize = 3;
+/// Upper bound on sub-queries, so very long issues don't explode scoring cost.
+const MAX_SUBQUERIES: usize = 6;
+/// Number of top files reported per sub-query.
+const WINNERS_PER_SUBQUERY: usize = 3;
+
+/// Split a long task description into sentence-level sub-queries.
+///
+/// Returns an empty vec when the task is short enough to score as a single
+/// bag of words, or when it doesn't split into at least two useful parts.
+pub fn decompose(task: &str) -> Vec<String> {
+    if Tokenizer::tokenize(task).len() < MIN_TOKENS_TO_SPLIT {
+        return Vec::new();
+    }
+
+    // Merge fragments that are too short to carry a signal of their own
+    let mut parts: Vec<String> = Vec::new();
+    for sentence in split_sentences(task) {
+        let short = Tokenizer::tokenize(&sentence).len() < MIN_SUBQUERY_TOKENS;
+        match parts.last_mut() {
+            Some(last) if short || Tokenizer::tokenize(last).len() < MIN_SUBQUERY_TOKENS => {
+                last.push(' ');
+                last.push_str(&sentence);
+            }
+            _ => parts.push(sentence),
+        }
+    }
+
+    // Drop sub-queries whose token sets duplicate an earlier one
+    let mut seen: Vec<Vec<String>> = Vec::new();
+    parts.retain(|p| {
+        let mut tokens = Tokenizer::tokenize(p);
+        tokens.sort();
+        tokens.dedup();
+        if tokens.is_empty() || seen.contains(&tokens) {
+            return false;
+        }
+        seen.push(tokens);
+        true
+    });
+
+    // Group contiguous sentences when there are too many
+    if parts.len() > MAX_SUBQUERIES {
+        let group_size = parts.len().div_ceil(MAX_SUBQUERIES);
+        parts = parts.chunks(group_size).map(|g| g.join(" ")).collect();
+    }
+
+    if parts.len() < 2 {
+        return Vec::new();
+    }
+    parts
+}
+
+/// Split text on sentence boundaries: `?`, `!`, `;`, newlines, and `.`
+/// followed by whitespace (so `src/main.rs` stays intact).
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let boundary = match c {
+            '?' | '!' | ';' | '\n' => true,
+            '.' => chars.peek().is_none_or(|n| n.is_whitespace()),
+            _ => false,
+        };
+        if boundary {
+            let trimmed = current.trim();
+            if !trimmed.is_empty() {
+                sentences.push(trimmed.to_string());
+            }
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+
+    let trimmed = current.trim();
+    if !trimmed.is_empty() {
+        sentences.push(trimmed.to_string());
+    }
+    sentences
+}
+
+/// The top-ranked files for one sub-query, for explainability.
+#[derive(Debug, Clone)]
+pub struct SubQueryWinners {
+    pub query: String,
+    pub paths: Vec<String>,
+}
+
+/// Scored files plus per-sub-query winners.
+#[derive(Debug, Clone)]
+pub struct MultiQueryResult {
+    pub files: Vec<ScoredFile>,
+    pub sub_queries: Vec<SubQueryWinners>,
+    /// Terms the task was expanded with (empty if not expanded).
+    pub expansion: Vec<String>,
+}
+
+/// Scores a task as a whole and as decomposed sub-queries, fusing the rankings.
+///
+/// Short tasks fall through to plain hybrid scoring. Long tasks are split with
+/// [`decompose`]; each sub-query is scored independently and the rankings are
+/// fused with RRF together with the full-task ranking. Fused scores are
+/// normalized so a file ranked first everywhere scores 1.0.
+///
+/// With [`MultiQueryScorer::expansion`], the full task's score of each file
+/// also gets the expansion terms' score at [`EXPANSION_WEIGHT`].
+pub struct MultiQueryScorer {
+    task: String,
+    sub_queries: Vec<String>,
+    /// The task's `-term` words, applied to every sub-query.
+    negated: String,
+    expansion: Vec<String>,
+    stop_words: StopWords,
+    weights: Option<SignalWeights>,
+    vendored: VendoredPolicy,
+    file_type_rules: Option<Vec<FileTypeRule>>,
+}
+
+impl MultiQueryScorer {
+    pub fn new(task: &str) -> Self {
+        let (text, negated) = Tokenizer::split_negated(task);
+        Self {
+            task: task.to_string(),
+            sub_queries: decompose(&text),
+            negated: negated.iter().map(|w| format!(" -{w}")).collect(),
+            expansion: Vec::new(),
+            stop_words: StopWords::default(),
+            weights: None,
+            vendored: VendoredPolicy::default(),
+            file_type_rules: None,
+        }
+    }
+
+    /// Set the signal weights, as [`HybridScorer::weights`].
+    pub fn weights(mut self, weights: SignalWeights) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Replace the stop-word list used when scoring.
+    pub fn stop_words(mut self, stop_words: StopWords) -> Self {
+        self.stop_words = stop_words;
+        self
+    }
+
+    /// Set how vendored and generated files are scored, as
+    /// [`HybridScorer::vendored`].
+    pub fn vendored(mut self, policy: VendoredPolicy) -> Self {
+        self.vendored = policy;
+        self
+    }
+
+    /// Replace the file-type rules, as [`HybridScorer::file_type_rules`].
+    pub fn file_type_rules(mut self, rules: Vec<FileTypeRule>) -> Self {
+        self.file_type_rules = Some(rules);
+        self
+    }
+
+    /// Blend in the scores of these terms, as from [`crate::QueryExpander`].
+    pub fn expansion(mut self, terms: Vec<String>) -> Self {
+        self.expansion = terms;
+        self
+    }
+
+    /// The sub-queries the task was split into (empty if not decomposed).
+    pub fn sub_queries(&self) -> &[String] {
+        &self.sub_queries
+    }
+
+    fn scorer(&self, query: &str) -> HybridScorer {
+        let mut scorer = HybridScorer::new(query)
+            .stop_words(self.stop_words.clone())
+            .vendored(self.vendored);
+        if let Some(rules) = &self.file_type_rules {
+            scorer = scorer.file_type_rules(rules.clone());
+        }
+        match self.weights {
+            Some(weights) => scorer.weights(weights),
+            None => scorer,
+        }
+    }
+
+    /// Score files, returning them sorted by score (descending).
+    pub fn score(&self, files: &[FileInfo]) -> MultiQueryResult {
+        let mut scored = self.scorer(&self.task).score(files);
+        self.blend_expansion(&mut scored, files);
+        if self.sub_queries.is_empty() || scored.is_empty() {
+            return MultiQueryResult {
+                files: scored,
+                sub_queries: Vec::new(),
+                expansion: self.expansion.clone(),
+            };
+        }
+
+        let sub_rankings: Vec<Vec<ScoredFile>> = self
+            .sub_queries
+            .iter()
+            .map(|q| self.scorer(&format!("{q}{}", self.negated)).score(files))
+            .collect();
+
+        let winners = self
+            .sub_queries
+            .iter()
+            .zip(&sub_rankings)
+            .map(|(query, ranking)| SubQueryWinners {
+                query: query.clone(),
+                paths: ranking
+                    .iter()
+                    .filter(|f| f.score > 0.0)
+                    .take(WINNERS_PER_SUBQUERY)
+                    .map(|f| f.path.clone())
+                    .collect(),
+            })
+            .collect();
+
+        // Only files some query's terms match are fused; RRF would give every
+        // ranked file a score, path priors alone included, so the rest get zero
+        let rankings: Vec<Vec<&str>> = sub_rankings
+            .iter()
+            .map(|r| {
+                r.iter()
+                    .filter(|f| f.signals.bm25f > 0.0)
+                    .map(|f| f.path.as_str())
+                    .collect()
+            })
+            .collect();
+        let matched: HashSet<&str> = rankings.iter().flatten().copied().collect();
+        let mut relevant: Vec<ScoredFile> = Vec::with_capacity(scored.len());
+        let mut unrelated: Vec<ScoredFile> = Vec::new();
+        for mut file in scored {
+            if file.signals.bm25f > 0.0 || matched.contains(file.path.as_str()) {
+                relevant.push(file);
+            } else {
+                file.score = 0.0;
+                unrelated.push(file);
+            }
+        }
+        let fusion = RrfFusion::new();
+        fusion.fuse_scored(&mut relevant, &rankings);
+
+        // Normalize: rank 1 in every list (base + sub-queries) scores 1.0
+        let max_rrf = (rankings.len() + 1) as f64 / (fusion.k() + 1.0);
+        for file in &mut relevant {
+            file.score /= max_rrf;
+        }
+        relevant.extend(unrelated);
+        let scored = relevant;
+
+        MultiQueryResult {
+            files: scored,
+            sub_queries: winners,
+            expansion: self.expansion.clone(),
+        }
+    }
+
+    /// Add the expansion terms' score of each file, at [`EXPANSION_WEIGHT`],
+    /// and re-sort.
+    fn blend_expansion(&self, scored: &mut [ScoredFile], files: &[FileInfo]) {
+        if self.expansion.is_empty() {
+            return;
+        }
+        let query = format!("{}{}", self.expansion.join(" "), self.negated);
+        let expanded: HashMap<String, f64> = self
+            .scorer(&query)
+            .score(files)
+            .into_iter()
+            .map(|f| (f.path, f.score))
+            .collect();
+        for file in scored.iter_mut() {
+            file.score += EXPANSION_WEIGHT * expanded.get(&file.path).copied().unwrap_or(0.0);
+        }
+        scored.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::{FileRole, Language};
+
+    fn file(path: &str) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size: 2000,
+            language: Language::Rust,
+            role: FileRole::Implementation,
+            sha256: [0u8; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        }
+    }
+
+    const LONG_TASK: &str = "Fix the token refresh logic in the auth middleware. \
+        The database connection pool leaks handles under load. \
+        Also update the rendering of compact output for hooks.";
+
+    #[test]
+    fn short_task_not_decomposed() {
+        assert!(decompose("auth middleware").is_empty());
+    }
+
+    #[test]
+    fn long_task_splits_into_sentences() {
+        let parts = decompose(LONG_TASK);
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].contains("auth middleware"));
+        assert!(parts[1].contains("connection pool"));
+    }
+
+    #[test]
+    fn split_keeps_file_paths_intact() {
+        let parts = split_sentences("Look at src/main.rs. Then check lib.rs");
+        assert_eq!(parts, vec!["Look at src/main.rs", "Then check lib.rs"]);
+    }
+
+    #[test]
+    fn short_fragments_are_merged() {
+        let task = "Refactor the scanner walker to support symlinks properly. Ok. \
+            Then make the fingerprint generation skip empty directories entirely";
+        let parts = decompose(task);
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0].ends_with("Ok"));
+    }
+
+    #[test]
+    fn caps_number_of_subqueries() {
+        let task = (0..20)
+            .map(|i| format!("update module{i} handler logic carefully"))
+            .collect::<Vec<_>>()
+            .join(". ");
+        assert!(decompose(&task).len() <= MAX_SUBQUERIES);
+    }
+
+    #[test]
+    fn multi_query_reports_winners() {
+        let files = vec![
+            file("src/auth/middleware.rs"),
+            file("src/db/connection.rs"),
+            file("src/render/compact.rs"),
+            file("src/utils.rs"),
+        ];
+        let scorer = MultiQueryScorer::new(LONG_TASK);
+        let result = scorer.score(&files);
+
+        assert_eq!(result.files.len(), 4);
+        assert_eq!(result.sub_queries.len(), 3);
+        assert_eq!(result.sub_queries[1].paths[0], "src/db/connection.rs");
+        for w in result.files.windows(2) {
+            assert!(w[0].score >= w[1].score);
+        }
+        assert!(result.files[0].score <= 1.0);
+    }
+
+    #[test]
+    fn unrelated_files_are_not_fused() {
+        let files = vec![
+            file("src/auth/middleware.rs"),
+            file("src/db/connection.rs"),
+            file("src/widget/palette.rs"),
+        ];
+        let result = MultiQueryScorer::new(LONG_TASK).score(&files);
+        let unrelated = result
+            .files
+            .iter()
+            .find(|f| f.path == "src/widget/palette.rs")
+            .unwrap();
+        assert_eq!(unrelated.score, 0.0);
+        assert_eq!(result.files.last().unwrap().path, "src/widget/palette.rs");
+        assert!(result.files[0].score > 0.0);
+    }
+
+    #[test]
+    fn multi_query_short_task_matches_hybrid() {
+        let files = vec![file("src/auth.rs"), file("src/db.rs")];
+        let multi = MultiQueryScorer::new("auth").score(&files);
+        let hybrid = HybridScorer::new("auth").score(&files);
+
+        assert!(multi.sub_queries.is_empty());
+        assert_eq!(multi.files[0].path, hybrid[0].path);
+        assert_eq!(multi.files[0].score, hybrid[0].score);
+    }
+
+    #[test]
+    fn expansion_ranks_below_the_task() {
+        let files = vec![
+            file("src/db/pool.rs"),
+            file("src/session/store.rs"),
+            file("src/auth/login.rs"),
+        ];
+        let result = MultiQueryScorer::new("login")
+            .expansion(vec!["session".to_string()])
+            .score(&files);
+        let paths: Vec<&str> = result.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "src/auth/login.rs",
+                "src/session/store.rs",
+                "src/db/pool.rs"
+            ]
+        );
+        assert_eq!(result.expansion, vec!["session"]);
+    }
+}
@@ -0,0 +1,625 @@
+use serde_json::{Value, json};
+use std::path::Path;
+use std::time::Duration;
+use topo_core::EmbeddingsConfig;
+
+/// Longest wait between retries, whatever a `Retry-After` header asks for.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// First retry delay without a `Retry-After` header; doubles per attempt.
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const DEFAULT_BATCH_SIZE: usize = 64;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// What a text is embedded as. Retrieval-tuned models (Voyage, Cohere)
+/// embed queries and documents differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    Document,
+    Query,
+}
+
+/// Turns text into fixed-length vectors.
+pub trait Embedder {
+    /// Identifies the vector space, e.g. `openai:text-embedding-3-small`.
+    /// Vectors from different models must never be compared.
+    fn model(&self) -> &str;
+
+    /// Length of every vector produced.
+    fn dims(&self) -> usize;
+
+    /// One vector per text, in order.
+    fn embed(&self, texts: &[&str], kind: InputKind) -> anyhow::Result<Vec<Vec<f32>>>;
+}
+
+/// A hosted embeddings API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    OpenAi,
+    Voyage,
+    Cohere,
+}
+
+impl Provider {
+    /// Parse a `[embeddings] provider` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "openai" => Some(Self::OpenAi),
+            "voyage" => Some(Self::Voyage),
+            "cohere" => Some(Self::Cohere),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::Voyage => "voyage",
+            Self::Cohere => "cohere",
+        }
+    }
+
+    fn default_endpoint(self) -> &'static str {
+        match self {
+            Self::OpenAi => "https://api.openai.com/v1/embeddings",
+            Self::Voyage => "https://api.voyageai.com/v1/embeddings",
+            Self::Cohere => "https://api.cohere.com/v2/embed",
+        }
+    }
+
+    /// Default model and its vector length.
+    fn default_model(self) -> (&'static str, usize) {
+        match self {
+            Self::OpenAi => ("text-embedding-3-small", 1536),
+            Self::Voyage => ("voyage-code-3", 1024),
+            Self::Cohere => ("embed-english-v3.0", 1024),
+        }
+    }
+
+    fn default_api_key_env(self) -> &'static str {
+        match self {
+            Self::OpenAi => "OPENAI_API_KEY",
+            Self::Voyage => "VOYAGE_API_KEY",
+            Self::Cohere => "COHERE_API_KEY",
+        }
+    }
+
+    /// JSON request body for one batch.
+    fn request_body(
+        self,
+        model: &str,
+        dims: Option<usize>,
+        texts: &[&str],
+        kind: InputKind,
+    ) -> Value {
+        match self {
+            Self::OpenAi => {
+                let mut body = json!({ "model": model, "input": texts });
+                if let Some(d) = dims {
+                    body["dimensions"] = json!(d);
+                }
+                body
+            }
+            Self::Voyage => {
+                let mut body = json!({
+                    "model": model,
+                    "input": texts,
+                    "input_type": match kind {
+                        InputKind::Document => "document",
+                        InputKind::Query => "query",
+                    },
+                });
+                if let Some(d) = dims {
+                    body["output_dimension"] = json!(d);
+                }
+                body
+            }
+            Self::Cohere => json!({
+                "model": model,
+                "texts": texts,
+                "input_type": match kind {
+                    InputKind::Document => "search_document",
+                    InputKind::Query => "search_query",
+                },
+                "embedding_types": ["float"],
+            }),
+        }
+    }
+
+    /// Vectors from a response body, in input order.
+    fn parse_response(self, body: &str) -> anyhow::Result<Vec<Vec<f32>>> {
+        let value: Value = serde_json::from_str(body)?;
+        let to_vector = |v: &Value| -> Option<Vec<f32>> {
+            v.as_array()?
+                .iter()
+                .map(|x| x.as_f64().map(|f| f as f32))
+                .collect()
+        };
+        let vectors = match self {
+            Self::OpenAi | Self::Voyage => {
+                let mut data: Vec<(u64, Vec<f32>)> = value["data"]
+                    .as_array()
+                    .ok_or_else(|| anyhow::anyhow!("response has no `data` array"))?
+                    .iter()
+                    .map(|d| Some((d["index"].as_u64()?, to_vector(&d["embedding"])?)))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| anyhow::anyhow!("malformed `data` entry"))?;
+                data.sort_by_key(|(i, _)| *i);
+                data.into_iter().map(|(_, v)| v).collect()
+            }
+            Self::Cohere => value["embeddings"]["float"]
+                .as_array()
+                .ok_or_else(|| anyhow::anyhow!("response has no `embeddings.float` array"))?
+                .iter()
+                .map(to_vector)
+                .collect::<Option<_>>()
+                .ok_or_else(|| anyhow::anyhow!("malformed embedding"))?,
+        };
+        Ok(vectors)
+    }
+}
+
+/// Result of one HTTP request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Seconds from a `Retry-After` header.
+    pub retry_after: Option<u64>,
+    pub body: String,
+}
+
+/// Sends a JSON POST. Errors are network failures; HTTP error statuses come
+/// back as responses.
+pub trait Transport {
+    fn post_json(&self, url: &str, api_key: &str, body: &str) -> anyhow::Result<HttpResponse>;
+}
+
+/// [`Embedder`] backed by a hosted API: batches inputs, retries rate limits
+/// and server errors with backoff.
+pub struct RemoteEmbedder {
+    provider: Provider,
+    endpoint: String,
+    /// Model name sent to the API.
+    api_model: String,
+    model_id: String,
+    dims: usize,
+    /// Sent to providers that can shorten vectors; `None` keeps the default.
+    requested_dims: Option<usize>,
+    api_key: String,
+    batch_size: usize,
+    max_retries: u32,
+    transport: Box<dyn Transport>,
+    sleep: fn(Duration),
+}
+
+impl RemoteEmbedder {
+    /// Embedder for `provider` with its default model, endpoint and limits.
+    pub fn new(provider: Provider, api_key: &str, transport: Box<dyn Transport>) -> Self {
+        let (model, dims) = provider.default_model();
+        Self {
+            provider,
+            endpoint: provider.default_endpoint().to_string(),
+            api_model: model.to_string(),
+            model_id: format!("{}:{model}", provider.as_str()),
+            dims,
+            requested_dims: None,
+            api_key: api_key.to_string(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_retries: DEFAULT_MAX_RETRIES,
+            transport,
+            sleep: std::thread::sleep,
+        }
+    }
+
+    /// Embedder configured by `[embeddings]`, or `None` when no provider is
+    /// set. The API key is read from the configured environment variable.
+    pub fn from_config(config: &EmbeddingsConfig) -> anyhow::Result<Option<Self>> {
+        let Some(name) = &config.provider else {
+            return Ok(None);
+        };
+        let provider = Provider::from_name(name)
+            .ok_or_else(|| anyhow::anyhow!("unknown embedding provider {name:?}"))?;
+        let key_env = config
+            .api_key_env
+            .as_deref()
+            .unwrap_or(provider.default_api_key_env());
+        let api_key = std::env::var(key_env).map_err(|_| {
+            anyhow::anyhow!("embedding provider {name} needs an API key in ${key_env}")
+        })?;
+
+        let mut embedder = Self::new(provider, &api_key, default_transport()?);
+        if let Some(model) = &config.model {
+            embedder = embedder.model_name(model);
+        }
+        if let Some(endpoint) = &config.endpoint {
+            embedder = embedder.endpoint(endpoint);
+        }
+        if let Some(dims) = config.dimensions {
+            embedder = embedder.dimensions(dims);
+        }
+        if let Some(n) = config.batch_size {
+            embedder = embedder.batch_size(n);
+        }
+        if let Some(n) = config.max_retries {
+            embedder = embedder.max_retries(n);
+        }
+        Ok(Some(embedder))
+    }
+
+    pub fn model_name(mut self, model: &str) -> Self {
+        self.api_model = model.to_string();
+        self.model_id = format!("{}:{model}", self.provider.as_str());
+        self
+    }
+
+    pub fn endpoint(mut self, url: &str) -> Self {
+        self.endpoint = url.to_string();
+        self
+    }
+
+    /// Vector length. Sent to the provider so it shortens its vectors.
+    pub fn dimensions(mut self, dims: usize) -> Self {
+        self.dims = dims;
+        self.requested_dims = Some(dims);
+        self
+    }
+
+    pub fn batch_size(mut self, n: usize) -> Self {
+        self.batch_size = n.max(1);
+        self
+    }
+
+    pub fn max_retries(mut self, n: u32) -> Self {
+        self.max_retries = n;
+        self
+    }
+
+    /// Replace the delay between retries (tests use a no-op).
+    pub fn sleep_with(mut self, sleep: fn(Duration)) -> Self {
+        self.sleep = sleep;
+        self
+    }
+
+    fn embed_batch(&self, texts: &[&str], kind: InputKind) -> anyhow::Result<Vec<Vec<f32>>> {
+        let body = self
+            .provider
+            .request_body(&self.api_model, self.requested_dims, texts, kind)
+            .to_string();
+        let response = post_with_retries(
+            self.transport.as_ref(),
+            &self.endpoint,
+            &self.api_key,
+            &body,
+            self.max_retries,
+            self.sleep,
+            self.provider.as_str(),
+        )?;
+        let vectors = self.provider.parse_response(&response)?;
+        if vectors.len() != texts.len() {
+            anyhow::bail!(
+                "{} returned {} embeddings for {} inputs",
+                self.provider.as_str(),
+                vectors.len(),
+                texts.len()
+            );
+        }
+        if let Some(v) = vectors.iter().find(|v| v.len() != self.dims) {
+            anyhow::bail!(
+                "{} returned {}-dimensional embeddings, expected {}",
+                self.model_id,
+                v.len(),
+                self.dims
+            );
+        }
+        Ok(vectors)
+    }
+}
+
+/// POST `body` to `url` and return the body of the first successful
+/// response, retrying rate limits, server errors and network failures up to
+/// `max_retries` times with backoff. `service` names the API in errors.
+pub(crate) fn post_with_retries(
+    transport: &dyn Transport,
+    url: &str,
+    api_key: &str,
+    body: &str,
+    max_retries: u32,
+    sleep: fn(Duration),
+    service: &str,
+) -> anyhow::Result<String> {
+    let mut attempt = 0;
+    loop {
+        let failure = match transport.post_json(url, api_key, body) {
+            Ok(resp) if (200..300).contains(&resp.status) => return Ok(resp.body),
+            Ok(resp) if resp.status == 429 || resp.status >= 500 => {
+                let error = anyhow::anyhow!(
+                    "{service} returned HTTP {}: {}",
+                    resp.status,
+                    resp.body.trim()
+                );
+                (error, resp.retry_after.map(Duration::from_secs))
+            }
+            Ok(resp) => anyhow::bail!(
+                "{service} returned HTTP {}: {}",
+                resp.status,
+                resp.body.trim()
+            ),
+            Err(e) => (e, None),
+        };
+        let (error, retry_after) = failure;
+        if attempt >= max_retries {
+            return Err(error.context(format!("giving up after {} attempts", attempt + 1)));
+        }
+        let backoff = retry_after.unwrap_or(BASE_BACKOFF * 2u32.pow(attempt));
+        sleep(backoff.min(MAX_BACKOFF));
+        attempt += 1;
+    }
+}
+
+impl Embedder for RemoteEmbedder {
+    fn model(&self) -> &str {
+        &self.model_id
+    }
+
+    fn dims(&self) -> usize {
+        self.dims
+    }
+
+    fn embed(&self, texts: &[&str], kind: InputKind) -> anyhow::Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            vectors.extend(self.embed_batch(batch, kind)?);
+        }
+        Ok(vectors)
+    }
+}
+
+/// The embedder `[embeddings]` configures, or `None` when no provider is
+/// set. `root` anchors the relative paths of a local model.
+pub fn embedder_from_config(
+    config: &EmbeddingsConfig,
+    root: &Path,
+) -> anyhow::Result<Option<Box<dyn Embedder>>> {
+    match config.provider.as_deref() {
+        None => Ok(None),
+        Some("local") => local_embedder(config, root).map(Some),
+        Some(_) => {
+            Ok(RemoteEmbedder::from_config(config)?.map(|e| Box::new(e) as Box<dyn Embedder>))
+        }
+    }
+}
+
+#[cfg(feature = "local-embeddings")]
+fn local_embedder(config: &EmbeddingsConfig, root: &Path) -> anyhow::Result<Box<dyn Embedder>> {
+    Ok(Box::new(crate::onnx::OnnxEmbedder::from_config(
+        config, root,
+    )?))
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+fn local_embedder(_: &EmbeddingsConfig, _: &Path) -> anyhow::Result<Box<dyn Embedder>> {
+    anyhow::bail!("this build of topo has no local embedding support (feature `local-embeddings`)")
+}
+
+#[cfg(feature = "remote-embeddings")]
+pub(crate) fn default_transport() -> anyhow::Result<Box<dyn Transport>> {
+    Ok(Box::new(UreqTransport::new()))
+}
+
+#[cfg(not(feature = "remote-embeddings"))]
+pub(crate) fn default_transport() -> anyhow::Result<Box<dyn Transport>> {
+    anyhow::bail!(
+        "this build of topo has no remote embedding support (feature `remote-embeddings`)"
+    )
+}
+
+/// HTTPS transport using `ureq`.
+#[cfg(feature = "remote-embeddings")]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "remote-embeddings")]
+impl UreqTransport {
+    pub fn new() -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(120)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Self { agent }
+    }
+}
+
+#[cfg(feature = "remote-embeddings")]
+impl Default for UreqTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "remote-embeddings")]
+impl Transport for UreqTransport {
+    fn post_json(&self, url: &str, api_key: &str, body: &str) -> anyhow::Result<HttpResponse> {
+        let mut resp = self
+            .agent
+            .post(url)
+            .header("Authorization", &format!("Bearer {api_key}"))
+            .header("Content-Type", "application/json")
+            .send(body)?;
+        let retry_after = resp
+            .headers()
+            .get("retry-after")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok());
+        Ok(HttpResponse {
+            status: resp.status().as_u16(),
+            retry_after,
+            body: resp.body_mut().read_to_string()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Replays canned responses and records request bodies.
+    struct FakeTransport {
+        responses: RefCell<Vec<anyhow::Result<HttpResponse>>>,
+        requests: Rc<RefCell<Vec<Value>>>,
+    }
+
+    impl Transport for FakeTransport {
+        fn post_json(&self, _url: &str, _key: &str, body: &str) -> anyhow::Result<HttpResponse> {
+            self.requests
+                .borrow_mut()
+                .push(serde_json::from_str(body).unwrap());
+            self.responses.borrow_mut().remove(0)
+        }
+    }
+
+    fn ok(body: Value) -> anyhow::Result<HttpResponse> {
+        Ok(HttpResponse {
+            status: 200,
+            retry_after: None,
+            body: body.to_string(),
+        })
+    }
+
+    fn status(code: u16) -> anyhow::Result<HttpResponse> {
+        Ok(HttpResponse {
+            status: code,
+            retry_after: Some(1),
+            body: "slow down".to_string(),
+        })
+    }
+
+    fn openai_data(vectors: &[[f32; 2]]) -> Value {
+        json!({ "data": vectors.iter().enumerate().map(|(i, v)| json!({
+            "index": i, "embedding": v,
+        })).collect::<Vec<_>>() })
+    }
+
+    fn embedder(
+        provider: Provider,
+        responses: Vec<anyhow::Result<HttpResponse>>,
+    ) -> (RemoteEmbedder, Rc<RefCell<Vec<Value>>>) {
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let transport = FakeTransport {
+            responses: RefCell::new(responses),
+            requests: requests.clone(),
+        };
+        let e = RemoteEmbedder::new(provider, "key", Box::new(transport))
+            .dimensions(2)
+            .sleep_with(|_| {});
+        (e, requests)
+    }
+
+    #[test]
+    fn batches_inputs() {
+        let (e, requests) = embedder(
+            Provider::OpenAi,
+            vec![
+                ok(openai_data(&[[1.0, 0.0], [0.0, 1.0]])),
+                ok(openai_data(&[[0.5, 0.5]])),
+            ],
+        );
+        let e = e.batch_size(2);
+        let vectors = e.embed(&["a", "b", "c"], InputKind::Document).unwrap();
+        assert_eq!(
+            vectors,
+            vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.5, 0.5]]
+        );
+        let requests = requests.borrow();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["input"], json!(["a", "b"]));
+        assert_eq!(requests[0]["dimensions"], 2);
+        assert_eq!(e.model(), "openai:text-embedding-3-small");
+    }
+
+    #[test]
+    fn retries_rate_limits_then_succeeds() {
+        let (e, requests) = embedder(
+            Provider::OpenAi,
+            vec![
+                status(429),
+                Err(anyhow::anyhow!("connection reset")),
+                ok(openai_data(&[[1.0, 0.0]])),
+            ],
+        );
+        assert_eq!(e.embed(&["a"], InputKind::Query).unwrap().len(), 1);
+        assert_eq!(requests.borrow().len(), 3);
+    }
+
+    #[test]
+    fn gives_up_after_max_retries_and_on_client_errors() {
+        let (e, requests) = embedder(Provider::OpenAi, vec![status(503), status(503)]);
+        let err = e
+            .max_retries(1)
+            .embed(&["a"], InputKind::Query)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("HTTP 503"));
+        assert_eq!(requests.borrow().len(), 2);
+
+        let (e, requests) = embedder(Provider::OpenAi, vec![status(401)]);
+        assert!(e.embed(&["a"], InputKind::Query).is_err());
+        assert_eq!(requests.borrow().len(), 1);
+    }
+
+    #[test]
+    fn cohere_and_voyage_send_input_type() {
+        let (e, requests) = embedder(
+            Provider::Cohere,
+            vec![ok(json!({ "embeddings": { "float": [[1.0, 2.0]] } }))],
+        );
+        assert_eq!(
+            e.embed(&["q"], InputKind::Query).unwrap(),
+            vec![vec![1.0, 2.0]]
+        );
+        assert_eq!(requests.borrow()[0]["input_type"], "search_query");
+        assert_eq!(requests.borrow()[0]["texts"], json!(["q"]));
+
+        let (e, requests) = embedder(Provider::Voyage, vec![ok(openai_data(&[[1.0, 2.0]]))]);
+        e.embed(&["doc"], InputKind::Document).unwrap();
+        assert_eq!(requests.borrow()[0]["input_type"], "document");
+    }
+
+    #[test]
+    fn rejects_wrong_dimension() {
+        let (e, _) = embedder(
+            Provider::OpenAi,
+            vec![ok(
+                json!({ "data": [{ "index": 0, "embedding": [1.0, 2.0, 3.0] }] }),
+            )],
+        );
+        assert!(e.embed(&["a"], InputKind::Document).is_err());
+    }
+
+    #[test]
+    fn from_config_without_provider_is_none() {
+        assert!(
+            RemoteEmbedder::from_config(&EmbeddingsConfig::default())
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            embedder_from_config(&EmbeddingsConfig::default(), Path::new("."))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[cfg(not(feature = "local-embeddings"))]
+    #[test]
+    fn local_provider_needs_the_feature() {
+        let config = EmbeddingsConfig {
+            provider: Some("local".to_string()),
+            model_path: Some("model.onnx".to_string()),
+            ..Default::default()
+        };
+        let err = embedder_from_config(&config, Path::new(".")).err().unwrap();
+        assert!(err.to_string().contains("local-embeddings"));
+    }
+}
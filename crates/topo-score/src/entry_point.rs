@@ -0,0 +1,84 @@
+use crate::tokenizer::Tokenizer;
+
+/// Multiplier applied to entry points on architecture-style queries.
+const DEFAULT_BOOST: f64 = 1.5;
+
+/// Query terms that signal the task is about how the program is put together
+/// rather than a specific feature.
+const ARCHITECTURE_TERMS: &[&str] = &[
+    "architecture",
+    "boot",
+    "bootstrap",
+    "entry",
+    "entrypoint",
+    "entrypoints",
+    "flow",
+    "layout",
+    "main",
+    "overview",
+    "startup",
+    "structure",
+    "wiring",
+];
+
+/// Entry-point signal.
+///
+/// Questions like "how does the server start up" or "give me an overview of
+/// the architecture" are best answered from the program's entry points, which
+/// rarely share vocabulary with the query. On such queries, files tagged as
+/// entry points at scan time get their score multiplied up.
+#[derive(Debug, Clone)]
+pub struct EntryPointBoost {
+    boost: f64,
+    active: bool,
+}
+
+impl EntryPointBoost {
+    pub fn new(query: &str) -> Self {
+        let tokens = Tokenizer::tokenize(&Tokenizer::split_negated(query).0);
+        let architecture_intent = tokens
+            .iter()
+            .any(|t| ARCHITECTURE_TERMS.binary_search(&t.as_str()).is_ok());
+        Self {
+            boost: DEFAULT_BOOST,
+            active: architecture_intent,
+        }
+    }
+
+    /// Whether the query was classified as architecture-style.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// The multiplier for a file, or `None` when no boost applies.
+    pub fn multiplier(&self, entry_point: bool) -> Option<f64> {
+        (self.active && entry_point).then_some(self.boost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn architecture_terms_sorted() {
+        let mut sorted = ARCHITECTURE_TERMS.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, ARCHITECTURE_TERMS);
+    }
+
+    #[test]
+    fn architecture_query_boosts_entry_points() {
+        let boost = EntryPointBoost::new("how does the server startup flow work");
+        assert!(boost.is_active());
+        assert_eq!(boost.multiplier(true), Some(DEFAULT_BOOST));
+        assert_eq!(boost.multiplier(false), None);
+    }
+
+    #[test]
+    fn feature_query_leaves_entry_points_alone() {
+        let boost = EntryPointBoost::new("fix token refresh in auth middleware");
+        assert!(!boost.is_active());
+        assert_eq!(boost.multiplier(true), None);
+    }
+}
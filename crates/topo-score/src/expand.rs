@@ -0,0 +1,195 @@
+use std::collections::{HashMap, HashSet};
+use topo_core::{DeepIndex, FileEntry};
+
+/// Files per query term whose vocabulary is mined for expansions: those
+/// where the term is most frequent. A SQLite index loads the same files.
+pub const EXPANSION_DOCS_PER_TERM: usize = 200;
+/// Weight of the expanded query's score added to the task's own score.
+pub const EXPANSION_WEIGHT: f64 = 0.3;
+
+/// Expansion terms added to a query.
+const MAX_EXPANSIONS: usize = 3;
+/// A candidate must share at least this many files with a query term.
+const MIN_SHARED_FILES: usize = 2;
+/// ...and appear in at least this share of the query term's files.
+const MIN_SHARE: f64 = 0.2;
+/// Candidates in more than this share of the corpus are too common to help.
+const MAX_DOCUMENT_SHARE: f64 = 0.2;
+/// Shorter candidates are mostly abbreviations and noise.
+const MIN_TERM_LEN: usize = 3;
+
+/// Suggests terms that co-occur with a query's terms in the deep index, so
+/// `auth` also finds files about `jwt` and `session`.
+///
+/// A candidate scores by the share of a query term's files it appears in,
+/// times its IDF, summed over query terms. Terms common across the corpus
+/// (`self`, `return`) never qualify.
+pub struct QueryExpander<'a> {
+    index: &'a DeepIndex,
+    limit: usize,
+}
+
+impl<'a> QueryExpander<'a> {
+    pub fn new(index: &'a DeepIndex) -> Self {
+        Self {
+            index,
+            limit: MAX_EXPANSIONS,
+        }
+    }
+
+    /// Return at most `limit` expansion terms.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Expansion terms for the query `terms`, best first.
+    pub fn expand(&self, terms: &[String]) -> Vec<String> {
+        let n = self.index.total_docs as f64;
+        if n == 0.0 || self.limit == 0 {
+            return Vec::new();
+        }
+        let query: HashSet<&str> = terms.iter().map(String::as_str).collect();
+
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+        for term in &query {
+            let files = self.files_with(term);
+            if files.len() < MIN_SHARED_FILES {
+                continue;
+            }
+            let mut shared: HashMap<&str, usize> = HashMap::new();
+            for entry in &files {
+                for candidate in entry.term_frequencies.keys() {
+                    if candidate.len() >= MIN_TERM_LEN
+                        && !query.contains(candidate.as_str())
+                        && !candidate.chars().all(|c| c.is_ascii_digit())
+                    {
+                        *shared.entry(candidate).or_default() += 1;
+                    }
+                }
+            }
+            for (candidate, together) in shared {
+                let share = together as f64 / files.len() as f64;
+                let Some(&df) = self.index.doc_frequencies.get(candidate) else {
+                    continue;
+                };
+                if together < MIN_SHARED_FILES
+                    || share < MIN_SHARE
+                    || df as f64 > n * MAX_DOCUMENT_SHARE
+                {
+                    continue;
+                }
+                *scores.entry(candidate).or_default() += share * (n / df as f64).ln();
+            }
+        }
+
+        let mut ranked: Vec<(&str, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(b.0))
+        });
+        ranked
+            .into_iter()
+            .take(self.limit)
+            .map(|(term, _)| term.to_string())
+            .collect()
+    }
+
+    /// The files where `term` is most frequent, up to
+    /// [`EXPANSION_DOCS_PER_TERM`].
+    fn files_with(&self, term: &str) -> Vec<&FileEntry> {
+        let mut files: Vec<(&String, &FileEntry, u32)> = self
+            .index
+            .files
+            .iter()
+            .filter_map(|(path, entry)| {
+                let tf = entry.term_frequencies.get(term)?;
+                Some((path, entry, tf.filename + tf.symbols + tf.body))
+            })
+            .collect();
+        files.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(b.0)));
+        files
+            .into_iter()
+            .take(EXPANSION_DOCS_PER_TERM)
+            .map(|(_, entry, _)| entry)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::TermFreqs;
+
+    fn index(docs: &[&[&str]]) -> DeepIndex {
+        let mut files = HashMap::new();
+        let mut doc_frequencies: HashMap<String, u32> = HashMap::new();
+        for (i, terms) in docs.iter().enumerate() {
+            let term_frequencies: HashMap<String, TermFreqs> = terms
+                .iter()
+                .map(|t| {
+                    let tf = TermFreqs {
+                        filename: 0,
+                        symbols: 0,
+                        body: 1,
+                    };
+                    (t.to_string(), tf)
+                })
+                .collect();
+            for t in term_frequencies.keys() {
+                *doc_frequencies.entry(t.clone()).or_default() += 1;
+            }
+            files.insert(
+                format!("f{i}.rs"),
+                FileEntry {
+                    sha256: [0; 32],
+                    chunks: Vec::new(),
+                    term_frequencies,
+                    positions: HashMap::new(),
+                    doc_length: terms.len() as u32,
+                    summary: Default::default(),
+                    signature: Vec::new(),
+                    imports: Vec::new(),
+                },
+            );
+        }
+        DeepIndex {
+            version: topo_core::INDEX_VERSION,
+            total_docs: files.len() as u32,
+            files,
+            avg_doc_length: 1.0,
+            doc_frequencies,
+            pagerank_scores: HashMap::new(),
+            pagerank_settings: String::new(),
+            stop_words: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn expands_with_co_occurring_terms() {
+        let mut docs: Vec<&[&str]> = vec![
+            &["auth", "jwt", "token", "self"],
+            &["auth", "jwt", "session", "self"],
+            &["auth", "session", "self"],
+            &["render", "self"],
+        ];
+        docs.extend(std::iter::repeat_n(&["parser", "self"][..], 16));
+        let index = index(&docs);
+
+        let expansion = QueryExpander::new(&index).expand(&["auth".to_string()]);
+        assert_eq!(expansion, vec!["jwt", "session"]);
+        assert!(
+            QueryExpander::new(&index)
+                .expand(&["render".to_string()])
+                .is_empty()
+        );
+        assert_eq!(
+            QueryExpander::new(&index)
+                .limit(1)
+                .expand(&["auth".to_string()]),
+            vec!["jwt"]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::tokenizer::Tokenizer;
+use topo_core::FileTypesConfig;
+
+/// Multiplier applied by the default rules.
+const DEFAULT_MULTIPLIER: f64 = 0.1;
+
+/// Query terms that signal the task is about artifacts themselves (lockfiles,
+/// snapshots, bundles), which disables the blanket penalties.
+const ARTIFACT_TERMS: &[&str] = &[
+    "bump",
+    "bundle",
+    "dependencies",
+    "dependency",
+    "deps",
+    "lock",
+    "lockfile",
+    "lockfiles",
+    "minified",
+    "snap",
+    "snapshot",
+    "snapshots",
+    "sourcemap",
+    "upgrade",
+];
+
+/// A blanket rule downranking a file type for code-change tasks.
+///
+/// Patterns match the file name: `*.lock` matches by suffix, anything else
+/// must equal the file name (`package-lock.json`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileTypeRule {
+    pub pattern: String,
+    pub multiplier: f64,
+}
+
+impl FileTypeRule {
+    pub fn new(pattern: &str, multiplier: f64) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            multiplier: multiplier.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        match self.pattern.strip_prefix('*') {
+            Some(suffix) => file_name.ends_with(suffix),
+            None => file_name == self.pattern,
+        }
+    }
+}
+
+/// The built-in rules: lockfiles, snapshots, minified bundles, source maps.
+pub fn default_file_type_rules() -> Vec<FileTypeRule> {
+    [
+        "*.lock",
+        "package-lock.json",
+        "pnpm-lock.yaml",
+        "npm-shrinkwrap.json",
+        "*.snap",
+        "*.min.js",
+        "*.min.css",
+        "*.map",
+    ]
+    .iter()
+    .map(|p| FileTypeRule::new(p, DEFAULT_MULTIPLIER))
+    .collect()
+}
+
+/// The rules `[file_types]` sets: the built-in rules unless turned off,
+/// each replaced by a configured rule with the same pattern, then the
+/// other configured rules.
+pub fn file_type_rules(config: &FileTypesConfig) -> Vec<FileTypeRule> {
+    let defaults = if config.defaults {
+        default_file_type_rules()
+    } else {
+        Vec::new()
+    };
+    defaults
+        .into_iter()
+        .filter(|rule| !config.rules.contains_key(&rule.pattern))
+        .chain(
+            config
+                .rules
+                .iter()
+                .map(|(pattern, &multiplier)| FileTypeRule::new(pattern, multiplier)),
+        )
+        .collect()
+}
+
+/// Filename/intent consistency signal.
+///
+/// Code-change tasks rarely want lockfiles or minified bundles, even when
+/// their names happen to match the query. Unless the query is about such
+/// artifacts, matching files get their score multiplied down.
+#[derive(Debug, Clone)]
+pub struct FileTypePenalty {
+    rules: Vec<FileTypeRule>,
+    active: bool,
+}
+
+impl FileTypePenalty {
+    pub fn new(query: &str) -> Self {
+        let tokens = Tokenizer::tokenize(&Tokenizer::split_negated(query).0);
+        let artifact_intent = tokens
+            .iter()
+            .any(|t| ARTIFACT_TERMS.binary_search(&t.as_str()).is_ok());
+        Self {
+            rules: default_file_type_rules(),
+            active: !artifact_intent,
+        }
+    }
+
+    /// Replace the blanket rules.
+    pub fn with_rules(mut self, rules: Vec<FileTypeRule>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Whether the query was classified as a code-change task.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// The multiplier for `path`, or `None` when no rule applies.
+    pub fn multiplier(&self, path: &str) -> Option<f64> {
+        if !self.active {
+            return None;
+        }
+        self.rules
+            .iter()
+            .filter(|r| r.matches(path))
+            .map(|r| r.multiplier)
+            .reduce(f64::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn artifact_terms_sorted() {
+        let mut sorted = ARTIFACT_TERMS.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, ARTIFACT_TERMS);
+    }
+
+    #[test]
+    fn rule_matching() {
+        assert!(FileTypeRule::new("*.lock", 0.1).matches("Cargo.lock"));
+        assert!(FileTypeRule::new("*.min.js", 0.1).matches("dist/app.min.js"));
+        assert!(!FileTypeRule::new("*.min.js", 0.1).matches("src/min.rs"));
+        assert!(FileTypeRule::new("package-lock.json", 0.1).matches("web/package-lock.json"));
+        assert!(!FileTypeRule::new("package-lock.json", 0.1).matches("package.json"));
+    }
+
+    #[test]
+    fn code_change_query_penalizes_lockfiles() {
+        let penalty = FileTypePenalty::new("refactor auth middleware");
+        assert!(penalty.is_active());
+        assert_eq!(penalty.multiplier("Cargo.lock"), Some(DEFAULT_MULTIPLIER));
+        assert_eq!(
+            penalty.multiplier("__snapshots__/a.test.ts.snap"),
+            Some(0.1)
+        );
+        assert_eq!(penalty.multiplier("src/auth.rs"), None);
+    }
+
+    #[test]
+    fn artifact_query_disables_penalty() {
+        let penalty = FileTypePenalty::new("bump the yarn lock");
+        assert!(!penalty.is_active());
+        assert_eq!(penalty.multiplier("yarn.lock"), None);
+    }
+
+    #[test]
+    fn configured_rules_override_defaults_by_pattern() {
+        let config = FileTypesConfig {
+            defaults: true,
+            rules: [("*.map".to_string(), 1.0), ("*.pb.go".to_string(), 0.2)].into(),
+        };
+        let penalty = FileTypePenalty::new("auth").with_rules(file_type_rules(&config));
+        assert_eq!(penalty.multiplier("api.pb.go"), Some(0.2));
+        assert_eq!(penalty.multiplier("app.js.map"), Some(1.0));
+        assert_eq!(penalty.multiplier("Cargo.lock"), Some(DEFAULT_MULTIPLIER));
+
+        let only_configured = file_type_rules(&FileTypesConfig {
+            defaults: false,
+            ..config
+        });
+        assert_eq!(only_configured.len(), 2);
+    }
+
+    #[test]
+    fn custom_rules_replace_defaults() {
+        let penalty =
+            FileTypePenalty::new("auth").with_rules(vec![FileTypeRule::new("*.svg", 0.5)]);
+        assert_eq!(penalty.multiplier("logo.svg"), Some(0.5));
+        assert_eq!(penalty.multiplier("Cargo.lock"), None);
+    }
+}
@@ -1,8 +1,10 @@
+use crate::co_change::CoChange;
+use crate::hybrid::SignalWeights;
 use std::collections::HashMap;
 use topo_core::ScoredFile;
 
 /// Default RRF constant (standard value from the RRF paper).
-const DEFAULT_K: f64 = 60.0;
+pub(crate) const DEFAULT_K: f64 = 60.0;
 
 /// Reciprocal Rank Fusion: combines multiple ranked lists into a single ranking.
 ///
@@ -23,6 +25,11 @@ impl RrfFusion {
         self
     }
 
+    /// The RRF constant in use.
+    pub fn k(&self) -> f64 {
+        self.k
+    }
+
     /// Combine multiple ranked lists into a single ranking using RRF.
     ///
     /// Each input is a ranked list of `ScoredFile`s (already sorted by their signal score).
@@ -57,24 +64,43 @@ impl RrfFusion {
     ///
     /// Takes ownership of a base scored list and applies RRF from additional signal rankings.
     pub fn fuse_scored(&self, base: &mut [ScoredFile], additional_rankings: &[Vec<&str>]) {
+        let weighted: Vec<(&[&str], f64)> = additional_rankings
+            .iter()
+            .map(|r| (r.as_slice(), 1.0))
+            .collect();
+        self.fuse_scored_weighted(base, &weighted);
+    }
+
+    /// Like [`fuse_scored`](Self::fuse_scored), but each additional ranking
+    /// contributes `weight / (k + rank)` instead of `1 / (k + rank)`. The base
+    /// ranking keeps weight 1; a weight of 0 ignores a ranking.
+    pub fn fuse_scored_weighted(
+        &self,
+        base: &mut [ScoredFile],
+        additional_rankings: &[(&[&str], f64)],
+    ) {
         if additional_rankings.is_empty() {
             return;
         }
-
-        // Build base ranking by current score order
-        let base_ranking: Vec<String> = base.iter().map(|f| f.path.clone()).collect();
-
-        // All rankings including the base
-        let mut all_rankings: Vec<Vec<String>> = vec![base_ranking];
-        for ranking in additional_rankings {
-            all_rankings.push(ranking.iter().map(|s| s.to_string()).collect());
-        }
-
-        // Compute RRF scores
+        log::debug!(
+            "RRF fusion: k = {}, base ranking of {} files plus weights {:?}",
+            self.k,
+            base.len(),
+            additional_rankings
+                .iter()
+                .map(|(_, w)| *w)
+                .collect::<Vec<_>>()
+        );
+
+        // Compute RRF scores, starting from the base ranking by current score order
         let mut rrf_scores: HashMap<String, f64> = HashMap::new();
-        for ranking in &all_rankings {
+        for (rank, file) in base.iter().enumerate() {
+            *rrf_scores.entry(file.path.clone()).or_default() += 1.0 / (self.k + rank as f64 + 1.0);
+        }
+        for (ranking, weight) in additional_rankings {
             for (rank, path) in ranking.iter().enumerate() {
-                *rrf_scores.entry(path.clone()).or_default() += 1.0 / (self.k + rank as f64 + 1.0);
+                *rrf_scores.entry(path.to_string()).or_default() +=
+                    weight / (self.k + rank as f64 + 1.0);
             }
         }
 
@@ -100,6 +126,74 @@ impl Default for RrfFusion {
     }
 }
 
+/// Fill in `signals.pagerank`, `signals.git_recency`, `signals.co_change`
+/// and `signals.churn` from their scores and fuse each ordering into
+/// `scored`, with its weight from `weights` and RRF constant
+/// `weights.rrf_k`. A signal with no scores or a weight of 0 is left out.
+///
+/// `scored` must be in ranked order: co-change is seeded from its top
+/// files unless `co_change` has seeds of its own.
+pub fn fuse_structural(
+    scored: &mut [ScoredFile],
+    pagerank: &HashMap<String, f64>,
+    git_recency: &HashMap<String, f64>,
+    co_change: &CoChange,
+    churn: &HashMap<String, f64>,
+    weights: &SignalWeights,
+) {
+    let use_pagerank = !pagerank.is_empty() && weights.pagerank > 0.0;
+    let use_recency = !git_recency.is_empty() && weights.git_recency > 0.0;
+    let use_churn = !churn.is_empty() && weights.churn > 0.0;
+    let co_change = if !co_change.is_empty() && weights.co_change > 0.0 {
+        co_change.scores_for(scored)
+    } else {
+        HashMap::new()
+    };
+    for file in scored.iter_mut() {
+        if use_pagerank {
+            file.signals.pagerank = pagerank.get(&file.path).copied();
+        }
+        if use_recency {
+            file.signals.git_recency = git_recency.get(&file.path).copied();
+        }
+        file.signals.co_change = co_change.get(&file.path).copied();
+        if use_churn {
+            file.signals.churn = churn.get(&file.path).copied();
+        }
+    }
+
+    // Owned paths, so the rankings don't borrow `scored` while fusing into it
+    let ranking = |signal: fn(&ScoredFile) -> Option<f64>| {
+        let mut ranked: Vec<(String, f64)> = scored
+            .iter()
+            .filter_map(|f| signal(f).map(|s| (f.path.clone(), s)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked.into_iter().map(|(p, _)| p).collect::<Vec<_>>()
+    };
+    let by_pagerank = ranking(|f| f.signals.pagerank);
+    let by_recency = ranking(|f| f.signals.git_recency);
+    let by_co_change = ranking(|f| f.signals.co_change);
+    let by_churn = ranking(|f| f.signals.churn);
+    let by_pagerank: Vec<&str> = by_pagerank.iter().map(String::as_str).collect();
+    let by_recency: Vec<&str> = by_recency.iter().map(String::as_str).collect();
+    let by_co_change: Vec<&str> = by_co_change.iter().map(String::as_str).collect();
+    let by_churn: Vec<&str> = by_churn.iter().map(String::as_str).collect();
+
+    let rankings: Vec<(&[&str], f64)> = [
+        (by_pagerank.as_slice(), weights.pagerank),
+        (by_recency.as_slice(), weights.git_recency),
+        (by_co_change.as_slice(), weights.co_change),
+        (by_churn.as_slice(), weights.churn),
+    ]
+    .into_iter()
+    .filter(|(ranking, _)| !ranking.is_empty())
+    .collect();
+    RrfFusion::new()
+        .with_k(weights.rrf_k)
+        .fuse_scored_weighted(scored, &rankings);
+}
+
 /// Result from RRF fusion.
 #[derive(Debug, Clone)]
 pub struct RrfResult {
@@ -120,6 +214,11 @@ mod tests {
             tokens: 100,
             language: Language::Rust,
             role: FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
         }
     }
 
@@ -239,6 +338,24 @@ mod tests {
         assert_eq!(base[1].score, 2.0);
     }
 
+    #[test]
+    fn rrf_weighted_ranking_can_dominate() {
+        let mut base = vec![
+            make_scored("a.rs", 3.0),
+            make_scored("b.rs", 2.0),
+            make_scored("c.rs", 1.0),
+        ];
+        let other: &[&str] = &["c.rs", "b.rs", "a.rs"];
+
+        let fusion = RrfFusion::new();
+        fusion.fuse_scored_weighted(&mut base, &[(other, 3.0)]);
+        assert_eq!(base[0].path, "c.rs");
+
+        let mut base = vec![make_scored("a.rs", 3.0), make_scored("c.rs", 1.0)];
+        fusion.fuse_scored_weighted(&mut base, &[(other, 0.0)]);
+        assert_eq!(base[0].path, "a.rs");
+    }
+
     #[test]
     fn rrf_file_in_one_ranking_only() {
         let files1 = [make_scored("a.rs", 2.0), make_scored("b.rs", 1.0)];
@@ -253,4 +370,114 @@ mod tests {
         assert_eq!(results.len(), 3);
         assert_eq!(results[0].path, "a.rs");
     }
+
+    #[test]
+    fn fuse_structural_weights_each_signal() {
+        let base = || {
+            vec![
+                make_scored("a.rs", 3.0),
+                make_scored("b.rs", 2.0),
+                make_scored("c.rs", 1.0),
+            ]
+        };
+        let pagerank: HashMap<String, f64> =
+            [("a.rs".to_string(), 0.1), ("c.rs".to_string(), 0.9)].into();
+        let recency: HashMap<String, f64> = [("b.rs".to_string(), 1.0)].into();
+
+        let mut scored = base();
+        let weights = SignalWeights {
+            pagerank: 3.0,
+            git_recency: 0.0,
+            ..Default::default()
+        };
+        fuse_structural(
+            &mut scored,
+            &pagerank,
+            &recency,
+            &CoChange::default(),
+            &HashMap::new(),
+            &weights,
+        );
+        assert_eq!(scored[0].path, "c.rs");
+        assert_eq!(scored[0].signals.pagerank, Some(0.9));
+        assert!(scored.iter().all(|f| f.signals.git_recency.is_none()));
+
+        let mut scored = base();
+        let weights = SignalWeights {
+            pagerank: 0.0,
+            git_recency: 3.0,
+            ..Default::default()
+        };
+        fuse_structural(
+            &mut scored,
+            &pagerank,
+            &recency,
+            &CoChange::default(),
+            &HashMap::new(),
+            &weights,
+        );
+        assert_eq!(scored[0].path, "b.rs");
+        assert!(scored.iter().all(|f| f.signals.pagerank.is_none()));
+
+        let churn: HashMap<String, f64> = [("c.rs".to_string(), 1.0)].into();
+        let mut scored = base();
+        let weights = SignalWeights {
+            pagerank: 0.0,
+            git_recency: 0.0,
+            churn: 3.0,
+            ..Default::default()
+        };
+        fuse_structural(
+            &mut scored,
+            &pagerank,
+            &recency,
+            &CoChange::default(),
+            &churn,
+            &weights,
+        );
+        assert_eq!(scored[0].path, "c.rs");
+        assert_eq!(scored[0].signals.churn, Some(1.0));
+    }
+
+    #[test]
+    fn fuse_structural_uses_rrf_k() {
+        let base = || {
+            vec![
+                make_scored("a.rs", 3.0),
+                make_scored("b.rs", 2.0),
+                make_scored("c.rs", 1.0),
+            ]
+        };
+        let pagerank: HashMap<String, f64> =
+            [("a.rs".to_string(), 0.5), ("c.rs".to_string(), 0.9)].into();
+        let weights = |rrf_k| SignalWeights {
+            pagerank: 1.5,
+            rrf_k,
+            ..Default::default()
+        };
+
+        // With the standard k, the base ranking's lead holds
+        let mut scored = base();
+        fuse_structural(
+            &mut scored,
+            &pagerank,
+            &HashMap::new(),
+            &CoChange::default(),
+            &HashMap::new(),
+            &weights(DEFAULT_K),
+        );
+        assert_eq!(scored[0].path, "a.rs");
+
+        // A small k lets PageRank's top file overtake it
+        let mut scored = base();
+        fuse_structural(
+            &mut scored,
+            &pagerank,
+            &HashMap::new(),
+            &CoChange::default(),
+            &HashMap::new(),
+            &weights(0.0),
+        );
+        assert_eq!(scored[0].path, "c.rs");
+    }
 }
@@ -5,38 +5,68 @@ use std::process::Command;
 /// Number of days to look back for git activity.
 const LOOKBACK_DAYS: u32 = 90;
 
+/// Git recency of the files in a repository.
+#[derive(Debug, Clone, Default)]
+pub struct GitRecency {
+    /// Normalized scores in [0.0, 1.0] where 1.0 = most recently active.
+    pub scores: HashMap<String, f64>,
+    /// The path each file renamed within the window had before its latest
+    /// rename; commits under that path count toward its score.
+    pub renamed_from: HashMap<String, String>,
+}
+
+/// Compute git recency for files in a repository.
+///
+/// Runs `git log` to count commits per file in the last N days, following
+/// renames so a moved file keeps its history.
+pub fn git_recency(repo_root: &Path) -> anyhow::Result<GitRecency> {
+    let commits = git_commit_counts(repo_root, LOOKBACK_DAYS)?;
+    Ok(GitRecency {
+        scores: log_scores(&commits.counts),
+        renamed_from: commits.renamed_from,
+    })
+}
+
 /// Compute git recency scores for files in a repository.
 ///
-/// Runs `git log` to count commits per file in the last N days.
 /// Returns normalized scores in [0.0, 1.0] where 1.0 = most recently active.
 pub fn git_recency_scores(repo_root: &Path) -> anyhow::Result<HashMap<String, f64>> {
-    let commit_counts = git_commit_counts(repo_root, LOOKBACK_DAYS)?;
-
-    if commit_counts.is_empty() {
-        return Ok(HashMap::new());
-    }
-
-    let max_count = commit_counts.values().copied().max().unwrap_or(1) as f64;
+    Ok(git_recency(repo_root)?.scores)
+}
 
-    let scores = commit_counts
-        .into_iter()
-        .map(|(path, count)| {
-            // Log-scale normalization: log(1 + count) / log(1 + max_count)
+/// Normalize commit counts to [0.0, 1.0] on a log scale:
+/// log(1 + count) / log(1 + max_count).
+pub(crate) fn log_scores(counts: &HashMap<String, u32>) -> HashMap<String, f64> {
+    let max_count = counts.values().copied().max().unwrap_or(1) as f64;
+    counts
+        .iter()
+        .map(|(path, &count)| {
             let score = (1.0 + count as f64).ln() / (1.0 + max_count).ln();
-            (path, score)
+            (path.clone(), score)
         })
-        .collect();
+        .collect()
+}
 
-    Ok(scores)
+/// Commits per file, keyed by each file's current path.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CommitCounts {
+    pub counts: HashMap<String, u32>,
+    pub renamed_from: HashMap<String, String>,
 }
 
 /// Count commits per file in the last N days using git log.
-fn git_commit_counts(repo_root: &Path, days: u32) -> anyhow::Result<HashMap<String, u32>> {
+///
+/// `--relative` keeps paths relative to `repo_root` even when it is a
+/// subdirectory of the repository (or a linked worktree). `-M` reports
+/// renames, which [`parse_name_status`] follows back to earlier paths.
+pub(crate) fn git_commit_counts(repo_root: &Path, days: u32) -> anyhow::Result<CommitCounts> {
     let output = Command::new("git")
         .args([
             "log",
             "--format=",
-            "--name-only",
+            "--name-status",
+            "-M",
+            "--relative",
             &format!("--since={days}.days"),
         ])
         .current_dir(repo_root)
@@ -44,20 +74,46 @@ fn git_commit_counts(repo_root: &Path, days: u32) -> anyhow::Result<HashMap<Stri
 
     if !output.status.success() {
         // Not a git repo or git not available — return empty
-        return Ok(HashMap::new());
+        return Ok(CommitCounts::default());
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut counts: HashMap<String, u32> = HashMap::new();
+    Ok(parse_name_status(&String::from_utf8_lossy(&output.stdout)))
+}
 
-    for line in stdout.lines() {
-        let trimmed = line.trim();
-        if !trimmed.is_empty() {
-            *counts.entry(trimmed.to_string()).or_default() += 1;
-        }
+/// Count `git log --name-status` output, newest commit first, per current
+/// path: once a rename `old → new` is seen, older commits to `old` count
+/// toward whatever `new` is called today.
+pub(crate) fn parse_name_status(log: &str) -> CommitCounts {
+    let mut commits = CommitCounts::default();
+    // Earlier path → current path
+    let mut current: HashMap<String, String> = HashMap::new();
+
+    for line in log.lines() {
+        let mut fields = line.trim().split('\t');
+        let (Some(status), Some(path)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let path = match fields.next() {
+            Some(new) if status.starts_with('R') => {
+                let now = current.get(new).cloned().unwrap_or_else(|| new.to_string());
+                commits
+                    .renamed_from
+                    .entry(now.clone())
+                    .or_insert_with(|| path.to_string());
+                current.insert(path.to_string(), now.clone());
+                now
+            }
+            // Copies count toward the new file only
+            Some(new) => new.to_string(),
+            None => current
+                .get(path)
+                .cloned()
+                .unwrap_or_else(|| path.to_string()),
+        };
+        *commits.counts.entry(path).or_default() += 1;
     }
 
-    Ok(counts)
+    commits
 }
 
 /// Score a single file's recency given the full recency map.
@@ -167,6 +223,52 @@ mod tests {
         assert!(active_score > once_score);
     }
 
+    #[test]
+    fn name_status_follows_renames() {
+        let log = "M\tsrc/session.rs\n\nR100\tsrc/auth.rs\tsrc/session.rs\nM\tREADME.md\n\nM\tsrc/auth.rs\n\nR090\tlib/auth.rs\tsrc/auth.rs\n\nM\tlib/auth.rs\nC075\tlib/auth.rs\tlib/copy.rs\n";
+        let commits = parse_name_status(log);
+        assert_eq!(commits.counts["src/session.rs"], 5);
+        assert_eq!(commits.counts["README.md"], 1);
+        assert_eq!(commits.counts["lib/copy.rs"], 1);
+        assert!(!commits.counts.contains_key("src/auth.rs"));
+        assert!(!commits.counts.contains_key("lib/auth.rs"));
+        // The latest earlier name is reported
+        assert_eq!(commits.renamed_from["src/session.rs"], "src/auth.rs");
+        assert_eq!(commits.renamed_from.len(), 1);
+    }
+
+    #[test]
+    fn recency_survives_a_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        init_git_repo(dir.path());
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+        };
+        for i in 0..3 {
+            fs::write(
+                dir.path().join("old.rs"),
+                format!("fn handler() {{}}\nfn v{i}() {{}}\n"),
+            )
+            .unwrap();
+            git(&["add", "old.rs"]);
+            git(&["commit", "-m", &format!("edit {i}")]);
+        }
+        fs::write(dir.path().join("other.rs"), "fn other() {}").unwrap();
+        git(&["add", "other.rs"]);
+        git(&["commit", "-m", "add other"]);
+        git(&["mv", "old.rs", "new.rs"]);
+        git(&["commit", "-m", "rename"]);
+
+        let recency = git_recency(dir.path()).unwrap();
+        assert!(recency.scores["new.rs"] > recency.scores["other.rs"]);
+        assert!(!recency.scores.contains_key("old.rs"));
+        assert_eq!(recency.renamed_from["new.rs"], "old.rs");
+    }
+
     #[test]
     fn file_recency_missing_file() {
         let scores = HashMap::new();
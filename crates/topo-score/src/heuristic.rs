@@ -1,4 +1,4 @@
-use crate::tokenizer::Tokenizer;
+use crate::tokenizer::{StopWords, Tokenizer};
 use topo_core::FileRole;
 
 /// Path-based heuristic scorer.
@@ -11,12 +11,19 @@ use topo_core::FileRole;
 /// - Well-known path bonus (src/, lib/, cmd/ get boost)
 pub struct HeuristicScorer {
     query_tokens: Vec<String>,
+    stop_words: StopWords,
 }
 
 impl HeuristicScorer {
     pub fn new(query: &str) -> Self {
+        Self::with_stop_words(query, StopWords::default())
+    }
+
+    /// Like [`HeuristicScorer::new`], dropping `stop_words` from the query and paths.
+    pub fn with_stop_words(query: &str, stop_words: StopWords) -> Self {
         Self {
-            query_tokens: Tokenizer::tokenize(query),
+            query_tokens: Tokenizer::tokenize_query_with(query, &stop_words).0,
+            stop_words,
         }
     }
 
@@ -48,7 +55,7 @@ impl HeuristicScorer {
             return 0.0;
         }
 
-        let path_tokens = Tokenizer::tokenize(path);
+        let path_tokens = Tokenizer::tokenize_with(path, &self.stop_words);
         let matches = self
             .query_tokens
             .iter()
@@ -1,18 +1,104 @@
 use crate::bm25f::{Bm25fScorer, CorpusStats};
+use crate::entry_point::EntryPointBoost;
+use crate::file_type::{FileTypePenalty, FileTypeRule};
+use crate::fusion::DEFAULT_K;
 use crate::heuristic::HeuristicScorer;
+use crate::tokenizer::StopWords;
+use crate::vendored::VendoredPolicy;
 use std::collections::HashMap;
-use topo_core::{FileInfo, ScoredFile, SignalBreakdown};
+use topo_core::{FileEntry, FileInfo, ScoredFile, SignalBreakdown, WeightsConfig};
 
 /// Default weight for BM25F in hybrid scoring.
 const DEFAULT_BM25F_WEIGHT: f64 = 0.6;
 /// Default weight for heuristic in hybrid scoring.
 const DEFAULT_HEURISTIC_WEIGHT: f64 = 0.4;
+/// Default weight of the PageRank ranking when fused with the base ranking.
+const DEFAULT_PAGERANK_WEIGHT: f64 = 1.0;
+/// Default weight of the git recency ranking. Recent churn is weaker
+/// evidence of relevance than import structure.
+const DEFAULT_GIT_RECENCY_WEIGHT: f64 = 0.5;
+/// Default weight of the co-change ranking.
+const DEFAULT_CO_CHANGE_WEIGHT: f64 = 0.5;
+/// Default weight of the churn ranking: files that keep changing are worth
+/// a look, but less so than ones the query or its seeds point at.
+const DEFAULT_CHURN_WEIGHT: f64 = 0.3;
+/// Score multiplier for files named by a negated query term (`-vendor`).
+/// Strong enough to sink them below any real match without hiding them
+/// from a query that matches nothing else.
+const NEGATED_PENALTY: f64 = 0.1;
+
+/// Ranking signal weights, from the `[weights]` config section or
+/// `--weights`.
+///
+/// `bm25f` and `heuristic` are normalized against each other into the base
+/// score; `pagerank`, `git_recency`, `co_change` and `churn` weight their
+/// rankings when fused with the base ranking, whose reciprocal ranks are damped by `rrf_k`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalWeights {
+    pub bm25f: f64,
+    pub heuristic: f64,
+    /// RRF weight of the PageRank ranking; 0 leaves PageRank out.
+    pub pagerank: f64,
+    /// RRF weight of the git recency ranking; 0 leaves it out.
+    pub git_recency: f64,
+    /// RRF weight of the co-change ranking; 0 leaves it out.
+    pub co_change: f64,
+    /// RRF weight of the churn ranking; 0 leaves it out.
+    pub churn: f64,
+    /// RRF constant `k`; lower values let the top of each ranking dominate.
+    pub rrf_k: f64,
+}
+
+impl Default for SignalWeights {
+    fn default() -> Self {
+        Self {
+            bm25f: DEFAULT_BM25F_WEIGHT,
+            heuristic: DEFAULT_HEURISTIC_WEIGHT,
+            pagerank: DEFAULT_PAGERANK_WEIGHT,
+            git_recency: DEFAULT_GIT_RECENCY_WEIGHT,
+            co_change: DEFAULT_CO_CHANGE_WEIGHT,
+            churn: DEFAULT_CHURN_WEIGHT,
+            rrf_k: DEFAULT_K,
+        }
+    }
+}
+
+impl SignalWeights {
+    /// Weights from config; unset keys keep the defaults.
+    pub fn from_config(config: &WeightsConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            bm25f: config.bm25f.unwrap_or(defaults.bm25f),
+            heuristic: config.heuristic.unwrap_or(defaults.heuristic),
+            pagerank: config.pagerank.unwrap_or(defaults.pagerank),
+            git_recency: config.git_recency.unwrap_or(defaults.git_recency),
+            co_change: config.co_change.unwrap_or(defaults.co_change),
+            churn: config.churn.unwrap_or(defaults.churn),
+            rrf_k: config.rrf_k.unwrap_or(defaults.rrf_k),
+        }
+    }
+
+    /// The BM25F and heuristic weights scaled to sum to 1.0, or the
+    /// defaults if both are 0.
+    pub fn base(&self) -> (f64, f64) {
+        let total = self.bm25f + self.heuristic;
+        if total > 0.0 {
+            (self.bm25f / total, self.heuristic / total)
+        } else {
+            (DEFAULT_BM25F_WEIGHT, DEFAULT_HEURISTIC_WEIGHT)
+        }
+    }
+}
 
 /// Hybrid scorer combining BM25F (content relevance) and heuristic (path-based) signals.
 pub struct HybridScorer {
     bm25f_weight: f64,
     heuristic_weight: f64,
     query: String,
+    file_types: FileTypePenalty,
+    entry_points: EntryPointBoost,
+    stop_words: StopWords,
+    vendored: VendoredPolicy,
 }
 
 impl HybridScorer {
@@ -21,16 +107,35 @@ impl HybridScorer {
             bm25f_weight: DEFAULT_BM25F_WEIGHT,
             heuristic_weight: DEFAULT_HEURISTIC_WEIGHT,
             query: query.to_string(),
+            file_types: FileTypePenalty::new(query),
+            entry_points: EntryPointBoost::new(query),
+            stop_words: StopWords::default(),
+            vendored: VendoredPolicy::default(),
         }
     }
 
-    /// Set custom weights. They will be normalized to sum to 1.0.
-    pub fn weights(mut self, bm25f: f64, heuristic: f64) -> Self {
-        let total = bm25f + heuristic;
-        if total > 0.0 {
-            self.bm25f_weight = bm25f / total;
-            self.heuristic_weight = heuristic / total;
-        }
+    /// Set custom weights. BM25F and heuristic are normalized to sum to
+    /// 1.0; the fusion weights are applied by [`crate::fuse_structural`].
+    pub fn weights(mut self, weights: SignalWeights) -> Self {
+        (self.bm25f_weight, self.heuristic_weight) = weights.base();
+        self
+    }
+
+    /// Replace the blanket file-type rules (lockfiles, snapshots, ...).
+    pub fn file_type_rules(mut self, rules: Vec<FileTypeRule>) -> Self {
+        self.file_types = self.file_types.with_rules(rules);
+        self
+    }
+
+    /// Replace the stop-word list used for the query and file paths.
+    pub fn stop_words(mut self, stop_words: StopWords) -> Self {
+        self.stop_words = stop_words;
+        self
+    }
+
+    /// Set how vendored and generated files are scored.
+    pub fn vendored(mut self, policy: VendoredPolicy) -> Self {
+        self.vendored = policy;
         self
     }
 
@@ -41,19 +146,32 @@ impl HybridScorer {
         }
 
         // Build BM25F corpus stats from file paths (shallow mode)
-        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
-        let stats = CorpusStats::from_paths(&paths);
-        let bm25f = Bm25fScorer::new(&self.query, stats);
-        let heuristic = HeuristicScorer::new(&self.query);
+        let paths: Vec<&str> = files
+            .iter()
+            .filter(|f| self.vendored.in_corpus(&f.path, f.role))
+            .map(|f| f.path.as_str())
+            .collect();
+        let stats = CorpusStats::from_paths_with(&paths, &self.stop_words);
+        let bm25f = Bm25fScorer::with_stop_words(&self.query, stats, self.stop_words.clone());
+        let heuristic = HeuristicScorer::with_stop_words(&self.query, self.stop_words.clone());
 
         let mut scored: Vec<ScoredFile> = files
             .iter()
+            .filter(|f| self.vendored.retains(&f.path, f.role))
             .map(|f| {
                 let bm25f_score = bm25f.score_path(&f.path);
                 let heuristic_score = heuristic.score(&f.path, f.role, f.size);
 
-                let combined =
-                    self.bm25f_weight * bm25f_score + self.heuristic_weight * heuristic_score;
+                let file_type = self.file_types.multiplier(&f.path);
+                let entry_point = self.entry_points.multiplier(f.entry_point);
+                let negated = bm25f.negated(&f.path, None).then_some(NEGATED_PENALTY);
+                let vendored = self.vendored.multiplier(&f.path, f.role);
+                let combined = (self.bm25f_weight * bm25f_score
+                    + self.heuristic_weight * heuristic_score)
+                    * file_type.unwrap_or(1.0)
+                    * entry_point.unwrap_or(1.0)
+                    * negated.unwrap_or(1.0)
+                    * vendored.unwrap_or(1.0);
 
                 ScoredFile {
                     path: f.path.clone(),
@@ -63,11 +181,23 @@ impl HybridScorer {
                         heuristic: heuristic_score,
                         pagerank: None,
                         git_recency: None,
+                        co_change: None,
+                        churn: None,
                         embedding: None,
+                        file_type,
+                        entry_point,
+                        rerank: None,
+                        negated,
+                        vendored,
                     },
                     tokens: f.estimated_tokens(),
                     language: f.language,
                     role: f.role,
+                    root: None,
+                    cluster: None,
+                    pair: None,
+                    chunks: Vec::new(),
+                    outline: Vec::new(),
                 }
             })
             .collect();
@@ -80,32 +210,50 @@ impl HybridScorer {
         scored
     }
 
-    /// Score files with full term frequencies from the deep index.
+    /// Score files with full term frequencies and positions from the deep
+    /// index. `stats` should leave out the files the vendored policy keeps
+    /// out of the corpus.
     pub fn score_with_index(
         &self,
         files: &[FileInfo],
-        term_freqs: &HashMap<String, (HashMap<String, topo_core::TermFreqs>, u32)>,
+        entries: &HashMap<String, FileEntry>,
         stats: CorpusStats,
     ) -> Vec<ScoredFile> {
         if files.is_empty() {
             return Vec::new();
         }
 
-        let bm25f = Bm25fScorer::new(&self.query, stats);
-        let heuristic = HeuristicScorer::new(&self.query);
+        let bm25f = Bm25fScorer::with_stop_words(&self.query, stats, self.stop_words.clone());
+        let heuristic = HeuristicScorer::with_stop_words(&self.query, self.stop_words.clone());
 
         let mut scored: Vec<ScoredFile> = files
             .iter()
+            .filter(|f| self.vendored.retains(&f.path, f.role))
             .map(|f| {
-                let bm25f_score = if let Some((tf, dl)) = term_freqs.get(&f.path) {
-                    bm25f.score(tf, *dl)
+                let indexed = entries.get(&f.path);
+                let bm25f_score = if let Some(entry) = indexed {
+                    bm25f.score_with_positions(
+                        &entry.term_frequencies,
+                        &entry.positions,
+                        entry.doc_length,
+                    )
                 } else {
                     bm25f.score_path(&f.path)
                 };
                 let heuristic_score = heuristic.score(&f.path, f.role, f.size);
 
-                let combined =
-                    self.bm25f_weight * bm25f_score + self.heuristic_weight * heuristic_score;
+                let file_type = self.file_types.multiplier(&f.path);
+                let entry_point = self.entry_points.multiplier(f.entry_point);
+                let negated = bm25f
+                    .negated(&f.path, indexed.map(|entry| &entry.term_frequencies))
+                    .then_some(NEGATED_PENALTY);
+                let vendored = self.vendored.multiplier(&f.path, f.role);
+                let combined = (self.bm25f_weight * bm25f_score
+                    + self.heuristic_weight * heuristic_score)
+                    * file_type.unwrap_or(1.0)
+                    * entry_point.unwrap_or(1.0)
+                    * negated.unwrap_or(1.0)
+                    * vendored.unwrap_or(1.0);
 
                 ScoredFile {
                     path: f.path.clone(),
@@ -115,11 +263,23 @@ impl HybridScorer {
                         heuristic: heuristic_score,
                         pagerank: None,
                         git_recency: None,
+                        co_change: None,
+                        churn: None,
                         embedding: None,
+                        file_type,
+                        entry_point,
+                        rerank: None,
+                        negated,
+                        vendored,
                     },
                     tokens: f.estimated_tokens(),
                     language: f.language,
                     role: f.role,
+                    root: None,
+                    cluster: None,
+                    pair: None,
+                    chunks: Vec::new(),
+                    outline: Vec::new(),
                 }
             })
             .collect();
@@ -146,6 +306,11 @@ mod tests {
                 language: Language::Rust,
                 role: FileRole::Implementation,
                 sha256: [0u8; 32],
+                lines: 0,
+                executable: false,
+                shebang: None,
+                entry_point: false,
+                license: None,
             },
             FileInfo {
                 path: "src/auth/middleware.rs".to_string(),
@@ -153,6 +318,11 @@ mod tests {
                 language: Language::Rust,
                 role: FileRole::Implementation,
                 sha256: [0u8; 32],
+                lines: 0,
+                executable: false,
+                shebang: None,
+                entry_point: false,
+                license: None,
             },
             FileInfo {
                 path: "src/db/connection.rs".to_string(),
@@ -160,6 +330,11 @@ mod tests {
                 language: Language::Rust,
                 role: FileRole::Implementation,
                 sha256: [0u8; 32],
+                lines: 0,
+                executable: false,
+                shebang: None,
+                entry_point: false,
+                license: None,
             },
             FileInfo {
                 path: "tests/auth_test.rs".to_string(),
@@ -167,6 +342,11 @@ mod tests {
                 language: Language::Rust,
                 role: FileRole::Test,
                 sha256: [0u8; 32],
+                lines: 0,
+                executable: false,
+                shebang: None,
+                entry_point: false,
+                license: None,
             },
             FileInfo {
                 path: "README.md".to_string(),
@@ -174,6 +354,11 @@ mod tests {
                 language: Language::Markdown,
                 role: FileRole::Documentation,
                 sha256: [0u8; 32],
+                lines: 0,
+                executable: false,
+                shebang: None,
+                entry_point: false,
+                license: None,
             },
         ]
     }
@@ -217,15 +402,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn signal_weights_fill_unset_keys() {
+        let weights = SignalWeights::from_config(&WeightsConfig {
+            pagerank: Some(0.0),
+            ..Default::default()
+        });
+        assert_eq!(weights.bm25f, DEFAULT_BM25F_WEIGHT);
+        assert_eq!(weights.heuristic, DEFAULT_HEURISTIC_WEIGHT);
+        assert_eq!(weights.pagerank, 0.0);
+        assert_eq!(weights.git_recency, DEFAULT_GIT_RECENCY_WEIGHT);
+        assert_eq!(weights.co_change, DEFAULT_CO_CHANGE_WEIGHT);
+        assert_eq!(weights.rrf_k, DEFAULT_K);
+        assert_eq!(weights.base(), (0.6, 0.4));
+    }
+
     #[test]
     fn hybrid_custom_weights() {
         let files = sample_files();
 
+        let weights = |bm25f, heuristic| SignalWeights {
+            bm25f,
+            heuristic,
+            ..Default::default()
+        };
+
         // All BM25F weight
-        let bm25f_only = HybridScorer::new("auth").weights(1.0, 0.0).score(&files);
+        let bm25f_only = HybridScorer::new("auth")
+            .weights(weights(1.0, 0.0))
+            .score(&files);
 
         // All heuristic weight
-        let heuristic_only = HybridScorer::new("auth").weights(0.0, 1.0).score(&files);
+        let heuristic_only = HybridScorer::new("auth")
+            .weights(weights(0.0, 1.0))
+            .score(&files);
 
         // Scores should differ between the two weighting schemes
         // Both should rank auth files highly, but ordering may differ
@@ -263,4 +473,119 @@ mod tests {
             .unwrap();
         assert_eq!(auth_file.tokens, 2000 / 4); // size / 4 heuristic
     }
+
+    #[test]
+    fn hybrid_vendored_files_policy() {
+        let mut files = sample_files();
+        for i in 0..20 {
+            files.push(FileInfo {
+                path: format!("node_modules/auth-lib/auth{i}.js"),
+                size: 500,
+                language: Language::JavaScript,
+                role: FileRole::Implementation,
+                sha256: [0u8; 32],
+                lines: 0,
+                executable: false,
+                shebang: None,
+                entry_point: false,
+                license: None,
+            });
+        }
+        let score_of = |results: &[ScoredFile], path: &str| {
+            results
+                .iter()
+                .find(|f| f.path == path)
+                .map(|f| f.signals.bm25f)
+        };
+
+        // Kept out of the corpus, vendored files don't make "auth" look common
+        let included = HybridScorer::new("auth")
+            .vendored(VendoredPolicy::Include)
+            .score(&files);
+        let downweighted = HybridScorer::new("auth").score(&files);
+        let handler = "src/auth/handler.rs";
+        assert!(score_of(&downweighted, handler) > score_of(&included, handler));
+
+        let vendored = downweighted
+            .iter()
+            .find(|f| f.path.starts_with("node_modules/"))
+            .unwrap();
+        assert_eq!(vendored.signals.vendored, Some(0.1));
+        assert!(included.iter().all(|f| f.signals.vendored.is_none()));
+
+        let excluded = HybridScorer::new("auth")
+            .vendored(VendoredPolicy::Exclude)
+            .score(&files);
+        assert_eq!(excluded.len(), sample_files().len());
+    }
+
+    #[test]
+    fn hybrid_lockfile_penalized_for_code_changes() {
+        let mut files = sample_files();
+        files.push(FileInfo {
+            path: "auth.lock".to_string(),
+            size: 500,
+            language: Language::Other,
+            role: FileRole::Other,
+            sha256: [0u8; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        });
+
+        let results = HybridScorer::new("auth").score(&files);
+        let lock = results.iter().find(|f| f.path == "auth.lock").unwrap();
+        assert_eq!(lock.signals.file_type, Some(0.1));
+        assert_eq!(results.last().unwrap().path, "auth.lock");
+
+        let results = HybridScorer::new("auth")
+            .file_type_rules(Vec::new())
+            .score(&files);
+        let lock = results.iter().find(|f| f.path == "auth.lock").unwrap();
+        assert!(lock.signals.file_type.is_none());
+    }
+
+    #[test]
+    fn hybrid_negated_terms_sink_files() {
+        let results = HybridScorer::new("auth -test").score(&sample_files());
+        let test = results
+            .iter()
+            .find(|f| f.path == "tests/auth_test.rs")
+            .unwrap();
+        assert_eq!(test.signals.negated, Some(0.1));
+        assert_eq!(results.last().unwrap().path, "tests/auth_test.rs");
+
+        let handler = results
+            .iter()
+            .find(|f| f.path == "src/auth/handler.rs")
+            .unwrap();
+        assert!(handler.signals.negated.is_none());
+    }
+
+    #[test]
+    fn hybrid_entry_point_boosted_for_architecture_queries() {
+        let mut files = sample_files();
+        files.push(FileInfo {
+            path: "src/main.rs".to_string(),
+            size: 1500,
+            language: Language::Rust,
+            role: FileRole::Implementation,
+            sha256: [0u8; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: true,
+            license: None,
+        });
+
+        let results = HybridScorer::new("server startup flow").score(&files);
+        assert_eq!(results[0].path, "src/main.rs");
+        assert_eq!(results[0].signals.entry_point, Some(1.5));
+
+        let results = HybridScorer::new("auth handler").score(&files);
+        let main = results.iter().find(|f| f.path == "src/main.rs").unwrap();
+        assert!(main.signals.entry_point.is_none());
+    }
 }
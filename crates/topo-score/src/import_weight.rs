@@ -0,0 +1,209 @@
+use crate::pagerank::extract_imports;
+use topo_core::Language;
+
+/// Weight of an import that only runs a module for its side effects
+/// (`import './polyfills'`), which binds nothing the file goes on to use.
+const SIDE_EFFECT_WEIGHT: f64 = 0.5;
+
+/// Raw imports of one file with their weights, as `(raw_import, weight)`.
+pub type WeightedImports = Vec<(String, f64)>;
+
+/// How an import statement uses the module it names.
+#[derive(Debug, PartialEq)]
+enum Usage {
+    /// Nothing bound: `import './setup'`, bare `require('x')`.
+    SideEffect,
+    /// A list of named symbols: `import { a, b } from`, `from m import a, b`.
+    Symbols(usize),
+    /// One binding used through its name: `import * as ns`, `import os`.
+    Binding(String),
+}
+
+/// Imports of a file with how heavily it depends on each, as
+/// `(raw_import, weight)`.
+///
+/// A single-symbol import weighs 1.0; more symbols (or more references to a
+/// namespace binding) weigh more, logarithmically, and side-effect imports
+/// weigh [`SIDE_EFFECT_WEIGHT`]. Each raw import appears once, with repeated
+/// imports of it summed. Languages without statement-level detail weigh every
+/// import 1.0.
+pub fn extract_weighted_imports(content: &str, language: Language) -> WeightedImports {
+    let mut weighted = Vec::new();
+    match language {
+        Language::JavaScript | Language::TypeScript | Language::Python | Language::Rust => {
+            for line in content.lines() {
+                for path in extract_imports(line, language) {
+                    let weight = match usage(line.trim(), language) {
+                        Usage::SideEffect => SIDE_EFFECT_WEIGHT,
+                        Usage::Symbols(n) => symbol_weight(n),
+                        Usage::Binding(name) => symbol_weight(references(content, line, &name)),
+                    };
+                    add(&mut weighted, path, weight);
+                }
+            }
+        }
+        _ => {
+            for path in extract_imports(content, language) {
+                add(&mut weighted, path, 1.0);
+            }
+        }
+    }
+    weighted
+}
+
+fn add(weighted: &mut WeightedImports, path: String, weight: f64) {
+    match weighted.iter_mut().find(|(p, _)| *p == path) {
+        Some((_, w)) => *w += weight,
+        None => weighted.push((path, weight)),
+    }
+}
+
+fn symbol_weight(symbols: usize) -> f64 {
+    1.0 + (symbols.max(1) as f64).ln()
+}
+
+fn usage(line: &str, language: Language) -> Usage {
+    match language {
+        Language::Python => python_usage(line),
+        Language::Rust => rust_usage(line),
+        _ => js_usage(line),
+    }
+}
+
+fn js_usage(line: &str) -> Usage {
+    if let Some(rest) = line.strip_prefix("import ") {
+        let Some(from) = rest.find(" from ") else {
+            return Usage::SideEffect;
+        };
+        let clause = rest[..from].trim();
+        let clause = clause.strip_prefix("type ").unwrap_or(clause);
+        return binding_clause(clause);
+    }
+    // const x = require('y') / const { a, b } = require('y') / require('y')
+    match line.find("require(").map(|i| &line[..i]) {
+        Some(before) if before.contains('=') => {
+            let lhs = before.split('=').next().unwrap_or("").trim();
+            let lhs = lhs
+                .trim_start_matches("const ")
+                .trim_start_matches("let ")
+                .trim_start_matches("var ");
+            binding_clause(lhs.trim())
+        }
+        _ => Usage::SideEffect,
+    }
+}
+
+/// `Default, { a, b as c }`, `* as ns`, `Default`, `{ a, b }`.
+fn binding_clause(clause: &str) -> Usage {
+    if let (Some(open), Some(close)) = (clause.find('{'), clause.rfind('}')) {
+        let named = count_items(&clause[open + 1..close.max(open + 1)]);
+        let default = clause[..open].trim().trim_end_matches(',').trim();
+        return Usage::Symbols(named + usize::from(!default.is_empty()));
+    }
+    let name = clause.rsplit(" as ").next().unwrap_or(clause).trim();
+    let name = name.trim_start_matches("* ").trim();
+    if name.is_empty() {
+        Usage::Symbols(1)
+    } else {
+        Usage::Binding(name.to_string())
+    }
+}
+
+fn python_usage(line: &str) -> Usage {
+    if let Some(rest) = line.strip_prefix("from ")
+        && let Some((_, names)) = rest.split_once(" import ")
+    {
+        let names = names.trim().trim_start_matches('(').trim_end_matches(')');
+        return Usage::Symbols(count_items(names));
+    }
+    // import a.b / import a.b as c
+    let rest = line.strip_prefix("import ").unwrap_or(line);
+    let first = rest.split(',').next().unwrap_or(rest).trim();
+    let name = first.rsplit(" as ").next().unwrap_or(first).trim();
+    Usage::Binding(name.to_string())
+}
+
+fn rust_usage(line: &str) -> Usage {
+    if let Some(rest) = line.strip_prefix("mod ") {
+        return Usage::Binding(rest.trim_end_matches(';').trim().to_string());
+    }
+    let path = line.trim_end_matches(';');
+    if let (Some(open), Some(close)) = (path.find('{'), path.rfind('}')) {
+        return Usage::Symbols(count_items(&path[open + 1..close.max(open + 1)]));
+    }
+    // use crate::auth; → referenced as `auth::...`
+    match path.strip_prefix("use crate::") {
+        Some(module) if !module.contains("::") => Usage::Binding(module.trim().to_string()),
+        _ => Usage::Symbols(1),
+    }
+}
+
+/// Non-empty comma-separated items.
+fn count_items(list: &str) -> usize {
+    list.split(',')
+        .filter(|item| !item.trim().is_empty())
+        .count()
+}
+
+/// Whole-word uses of `name` in `content` outside the import `line`.
+fn references(content: &str, line: &str, name: &str) -> usize {
+    let count = |text: &str| {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+        text.match_indices(name)
+            .filter(|&(i, _)| {
+                !text[..i].ends_with(is_word) && !text[i + name.len()..].starts_with(is_word)
+            })
+            .count()
+    };
+    count(content).saturating_sub(count(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weight_of(weighted: &[(String, f64)], path: &str) -> f64 {
+        weighted.iter().find(|(p, _)| p == path).unwrap().1
+    }
+
+    #[test]
+    fn js_symbol_counts_and_side_effects() {
+        let code = r#"
+import { a, b, c, d } from './heavy';
+import light from './light';
+import './polyfills';
+import * as api from './api';
+
+api.get(); api.post(); api.put();
+light();
+"#;
+        let weighted = extract_weighted_imports(code, Language::TypeScript);
+        assert_eq!(weight_of(&weighted, "./light"), 1.0);
+        assert_eq!(weight_of(&weighted, "./polyfills"), SIDE_EFFECT_WEIGHT);
+        assert!(weight_of(&weighted, "./heavy") > weight_of(&weighted, "./light"));
+        assert_eq!(weight_of(&weighted, "./api"), symbol_weight(3));
+    }
+
+    #[test]
+    fn python_from_import_counts_names() {
+        let code = "from app.models import User, Team, Role\nimport os\n";
+        let weighted = extract_weighted_imports(code, Language::Python);
+        assert_eq!(weight_of(&weighted, "app.models"), symbol_weight(3));
+        assert_eq!(weight_of(&weighted, "os"), 1.0);
+    }
+
+    #[test]
+    fn rust_use_lists_and_repeats() {
+        let code = "use crate::auth::{Token, Session};\nuse crate::auth::verify;\n";
+        let weighted = extract_weighted_imports(code, Language::Rust);
+        assert_eq!(weighted.len(), 1);
+        assert_eq!(weight_of(&weighted, "auth"), symbol_weight(2) + 1.0);
+    }
+
+    #[test]
+    fn other_languages_weigh_one() {
+        let code = "import java.util.List;\nimport com.acme.Auth;\n";
+        let weighted = extract_weighted_imports(code, Language::Java);
+        assert!(weighted.iter().all(|(_, w)| *w == 1.0));
+    }
+}
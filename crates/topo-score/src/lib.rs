@@ -1,23 +1,78 @@
 //! BM25F, heuristic, structural, and RRF fusion scoring.
 
 mod bm25f;
+mod chunks;
+mod churn;
+mod cluster;
+mod co_change;
+mod commits;
+mod decompose;
+mod embed;
+mod entry_point;
+mod expand;
+mod file_type;
 mod fusion;
 mod git_recency;
 mod heuristic;
+mod import_weight;
+mod manifest;
+#[cfg(feature = "local-embeddings")]
+mod onnx;
 mod pagerank;
+mod phrase;
+mod query_filter;
+mod related;
+mod rerank;
 mod resolve;
+mod similar;
+mod symbols;
+mod test_pair;
 mod tokenizer;
+mod vendored;
 
 pub mod hybrid;
 
 pub use bm25f::{Bm25fScorer, CorpusStats};
-pub use fusion::{RrfFusion, RrfResult};
-pub use git_recency::{file_recency, git_recency_scores};
+pub use chunks::ChunkScorer;
+pub use churn::{Churn, DEFAULT_CHURN_WINDOW_DAYS};
+pub use cluster::Clusters;
+pub use co_change::{CoChange, changed_since};
+pub use commits::{Commit, CommitLog, CommitMatch, DEFAULT_LOG_COMMITS};
+pub use decompose::{MultiQueryResult, MultiQueryScorer, SubQueryWinners, decompose};
+#[cfg(feature = "remote-embeddings")]
+pub use embed::UreqTransport;
+pub use embed::{
+    Embedder, HttpResponse, InputKind, Provider, RemoteEmbedder, Transport, embedder_from_config,
+};
+pub use entry_point::EntryPointBoost;
+pub use expand::{EXPANSION_DOCS_PER_TERM, QueryExpander};
+pub use file_type::{FileTypePenalty, FileTypeRule, default_file_type_rules, file_type_rules};
+pub use fusion::{RrfFusion, RrfResult, fuse_structural};
+pub use git_recency::{GitRecency, file_recency, git_recency, git_recency_scores};
 pub use heuristic::HeuristicScorer;
-pub use hybrid::HybridScorer;
-pub use pagerank::{ImportGraph, extract_imports};
-pub use resolve::build_import_graph;
-pub use tokenizer::Tokenizer;
+pub use hybrid::{HybridScorer, SignalWeights};
+pub use import_weight::{WeightedImports, extract_weighted_imports};
+#[cfg(feature = "local-embeddings")]
+pub use onnx::{OnnxEmbedder, OnnxReranker};
+pub use pagerank::{GraphMetrics, ImportGraph, PageRankParams, extract_imports};
+pub use phrase::PhraseMatcher;
+pub use query_filter::FilteredQuery;
+pub use related::{Related, RelatednessScorer};
+pub use rerank::{
+    DEFAULT_CANDIDATES, RemoteReranker, RerankProvider, Reranker, rerank, reranker_from_config,
+};
+pub use resolve::{
+    ImportOrigin, ImportTrace, ResolveOptions, UnresolvedImport, build_import_graph,
+    build_import_graph_with, dependency_usage, explain_imports, unresolved_imports,
+};
+pub use similar::{
+    NEAR_DUPLICATE_OVERLAP, SIGNATURE_HASHES, Similar, SimilarityScorer, estimated_jaccard,
+    minhash_signature,
+};
+pub use symbols::{SymbolMatch, SymbolSearch};
+pub use test_pair::{TestPairing, best_impl, best_test, impl_stem};
+pub use tokenizer::{StopWords, Tokenizer};
+pub use vendored::{DEFAULT_VENDORED_MULTIPLIER, VENDORED_DIRS, VendoredPolicy, is_vendored};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,464 @@
+//! Package manifests that tell the resolver where modules live.
+
+use serde_json::Value;
+
+/// Package names declared by a dependency manifest.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeclaredPackages {
+    /// Names the project itself is imported by (package name, Go module path).
+    pub own: Vec<String>,
+    /// Third-party dependencies of any kind: runtime, dev, build, optional.
+    pub dependencies: Vec<String>,
+}
+
+/// PSR-4 namespace prefixes from a `composer.json`, mapped to directories.
+///
+/// Reads both `autoload` and `autoload-dev`. A prefix may map to one
+/// directory or a list; each becomes its own `(prefix, dir)` pair. Prefixes
+/// keep their trailing `\` (`App\`), and directories are made relative to the
+/// repository by joining them onto `composer_dir`, the directory holding the
+/// manifest (`""` at the repository root). Invalid JSON yields no mappings.
+pub fn composer_psr4(content: &str, composer_dir: &str) -> Vec<(String, String)> {
+    let Ok(json) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+
+    let mut mappings = Vec::new();
+    for section in ["autoload", "autoload-dev"] {
+        let Some(psr4) = json
+            .get(section)
+            .and_then(|s| s.get("psr-4"))
+            .and_then(Value::as_object)
+        else {
+            continue;
+        };
+        for (prefix, dirs) in psr4 {
+            let dirs: Vec<&str> = match dirs {
+                Value::String(dir) => vec![dir.as_str()],
+                Value::Array(list) => list.iter().filter_map(Value::as_str).collect(),
+                _ => continue,
+            };
+            for dir in dirs {
+                let dir = dir.trim_start_matches("./").trim_end_matches('/');
+                let joined = match (composer_dir.is_empty(), dir.is_empty() || dir == ".") {
+                    (_, true) => composer_dir.to_string(),
+                    (true, false) => dir.to_string(),
+                    (false, false) => format!("{composer_dir}/{dir}"),
+                };
+                mappings.push((prefix.clone(), joined));
+            }
+        }
+    }
+    mappings
+}
+
+/// Crates from a `Cargo.toml`, with `-` normalized to `_` as in `use` paths.
+///
+/// Covers `[dependencies]`, `[dev-dependencies]`, `[build-dependencies]`,
+/// their `[target.*]` variants and `[workspace.dependencies]`. Invalid TOML
+/// declares nothing.
+pub fn cargo_packages(content: &str) -> DeclaredPackages {
+    let Ok(doc) = content.parse::<toml::Table>() else {
+        return DeclaredPackages::default();
+    };
+    let crate_name = |name: &str| name.replace('-', "_");
+    let mut packages = DeclaredPackages::default();
+    if let Some(name) = doc
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(toml::Value::as_str)
+    {
+        packages.own.push(crate_name(name));
+    }
+
+    let mut tables: Vec<&toml::Table> = vec![&doc];
+    if let Some(workspace) = doc.get("workspace").and_then(toml::Value::as_table) {
+        tables.push(workspace);
+    }
+    if let Some(targets) = doc.get("target").and_then(toml::Value::as_table) {
+        tables.extend(targets.values().filter_map(toml::Value::as_table));
+    }
+    for table in tables {
+        for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+            if let Some(deps) = table.get(section).and_then(toml::Value::as_table) {
+                packages
+                    .dependencies
+                    .extend(deps.keys().map(|k| crate_name(k)));
+            }
+        }
+    }
+    packages
+}
+
+/// Packages from a `package.json`: its `name`, and every key of
+/// `dependencies`, `devDependencies`, `peerDependencies` and
+/// `optionalDependencies`.
+pub fn npm_packages(content: &str) -> DeclaredPackages {
+    let Ok(json) = serde_json::from_str::<Value>(content) else {
+        return DeclaredPackages::default();
+    };
+    let mut packages = DeclaredPackages::default();
+    if let Some(name) = json.get("name").and_then(Value::as_str) {
+        packages.own.push(name.to_string());
+    }
+    for section in [
+        "dependencies",
+        "devDependencies",
+        "peerDependencies",
+        "optionalDependencies",
+    ] {
+        if let Some(deps) = json.get(section).and_then(Value::as_object) {
+            packages.dependencies.extend(deps.keys().cloned());
+        }
+    }
+    packages
+}
+
+/// Module paths from a `go.mod`: the `module` line and every `require`d
+/// module, in single-line or block form.
+pub fn go_mod_packages(content: &str) -> DeclaredPackages {
+    let mut packages = DeclaredPackages::default();
+    let mut in_require = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if in_require {
+            if line == ")" {
+                in_require = false;
+            } else if let Some(module) = line.split_whitespace().next() {
+                packages.dependencies.push(module.to_string());
+            }
+        } else if let Some(module) = line.strip_prefix("module ") {
+            packages
+                .own
+                .push(module.trim().trim_matches('"').to_string());
+        } else if let Some(rest) = line.strip_prefix("require") {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_require = true;
+            } else if let Some(module) = rest.split_whitespace().next() {
+                packages.dependencies.push(module.to_string());
+            }
+        }
+    }
+    packages
+}
+
+/// Distributions from a `pyproject.toml`, normalized like import names
+/// (lowercase, `-` and `.` as `_`).
+///
+/// Reads PEP 621 `[project]` dependencies and optional dependencies, PEP 735
+/// `[dependency-groups]`, and Poetry's dependency tables. Distribution names
+/// that differ from their import name (`PyYAML` for `yaml`) will not match.
+pub fn pyproject_packages(content: &str) -> DeclaredPackages {
+    let Ok(doc) = content.parse::<toml::Table>() else {
+        return DeclaredPackages::default();
+    };
+    let mut packages = DeclaredPackages::default();
+    let project = doc.get("project");
+    let poetry = doc.get("tool").and_then(|t| t.get("poetry"));
+    for table in [project, poetry].into_iter().flatten() {
+        if let Some(name) = table.get("name").and_then(toml::Value::as_str) {
+            packages.own.push(python_name(name));
+        }
+    }
+
+    // PEP 508 requirement strings: `requests>=2`, `uvicorn[standard]`
+    let mut requirements: Vec<&str> = Vec::new();
+    requirements.extend(string_list(project.and_then(|p| p.get("dependencies"))));
+    let groups = [
+        project.and_then(|p| p.get("optional-dependencies")),
+        doc.get("dependency-groups"),
+    ];
+    for group in groups
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_table)
+    {
+        for list in group.values() {
+            requirements.extend(string_list(Some(list)));
+        }
+    }
+    packages
+        .dependencies
+        .extend(requirements.iter().filter_map(|req| {
+            let name = req
+                .split(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+                .next()?;
+            (!name.is_empty()).then(|| python_name(name))
+        }));
+
+    // Poetry: `[tool.poetry.dependencies]`, `[tool.poetry.group.<g>.dependencies]`
+    if let Some(poetry) = poetry {
+        let mut tables = vec![poetry.get("dependencies"), poetry.get("dev-dependencies")];
+        if let Some(groups) = poetry.get("group").and_then(toml::Value::as_table) {
+            tables.extend(groups.values().map(|g| g.get("dependencies")));
+        }
+        for deps in tables
+            .into_iter()
+            .flatten()
+            .filter_map(toml::Value::as_table)
+        {
+            packages.dependencies.extend(
+                deps.keys()
+                    .filter(|k| k.as_str() != "python")
+                    .map(|k| python_name(k)),
+            );
+        }
+    }
+    packages
+}
+
+fn string_list(value: Option<&toml::Value>) -> Vec<&str> {
+    value
+        .and_then(toml::Value::as_array)
+        .map(|list| list.iter().filter_map(toml::Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn python_name(name: &str) -> String {
+    name.to_lowercase().replace(['-', '.'], "_")
+}
+
+/// Target constructors in a `Package.swift` and the directory each defaults to.
+const SWIFT_TARGET_KINDS: &[(&str, &str)] = &[
+    (".executableTarget(", "Sources"),
+    (".macro(", "Sources"),
+    (".plugin(", "Plugins"),
+    (".target(", "Sources"),
+    (".testTarget(", "Tests"),
+];
+
+/// Swift package targets from a `Package.swift`, as `(module, dir)` pairs.
+///
+/// A target lives at its `path:` argument when given, otherwise at the
+/// conventional `Sources/<name>` (`Tests/<name>` for test targets, `Plugins/`
+/// for plugins). Directories are joined onto `package_dir`, the directory
+/// holding the manifest. `.target(name:)` references inside another target's
+/// `dependencies:` are skipped.
+pub fn swift_package_targets(content: &str, package_dir: &str) -> Vec<(String, String)> {
+    let mut targets = Vec::new();
+    let mut rest = content;
+    loop {
+        let next = SWIFT_TARGET_KINDS
+            .iter()
+            .filter_map(|&(kind, default_dir)| {
+                rest.find(kind).map(|at| (at, kind.len(), default_dir))
+            })
+            .min_by_key(|&(at, _, _)| at);
+        let Some((at, kind_len, default_dir)) = next else {
+            break;
+        };
+        let args_start = at + kind_len;
+        let args_len = closing_paren(&rest[args_start..]).unwrap_or(rest.len() - args_start);
+        let args = &rest[args_start..args_start + args_len];
+
+        if let Some(name) = string_argument(args, "name") {
+            let dir = string_argument(args, "path")
+                .map(|p| p.trim_start_matches("./").trim_end_matches('/').to_string())
+                .unwrap_or_else(|| format!("{default_dir}/{name}"));
+            let dir = if package_dir.is_empty() {
+                dir
+            } else {
+                format!("{package_dir}/{dir}")
+            };
+            targets.push((name.to_string(), dir));
+        }
+        rest = &rest[args_start + args_len..];
+    }
+    targets
+}
+
+/// Length of `s` up to the `)` closing an already-opened parenthesis.
+fn closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => {
+                if depth == 0 {
+                    return Some(i);
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The string literal passed as `label:` at the top level of an argument list.
+fn string_argument<'a>(args: &'a str, label: &str) -> Option<&'a str> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    for (i, c) in args.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' | '[' if !in_string => depth += 1,
+            ')' | ']' if !in_string => depth = depth.saturating_sub(1),
+            _ if !in_string
+                && depth == 0
+                && args[i..].starts_with(label)
+                && !args[..i].ends_with(|p: char| p.is_alphanumeric() || p == '_') =>
+            {
+                let after = args[i + label.len()..].trim_start().strip_prefix(':')?;
+                let value = after.trim_start().strip_prefix('"')?;
+                return value.split('"').next();
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composer_psr4_reads_both_sections() {
+        let content = r#"{
+            "autoload": {
+                "psr-4": {
+                    "App\\": "app/",
+                    "Domain\\": ["src/Domain/", "lib/Domain"]
+                }
+            },
+            "autoload-dev": { "psr-4": { "Tests\\": "tests/" } }
+        }"#;
+        let mappings = composer_psr4(content, "");
+        assert!(mappings.contains(&(r"App\".to_string(), "app".to_string())));
+        assert!(mappings.contains(&(r"Domain\".to_string(), "src/Domain".to_string())));
+        assert!(mappings.contains(&(r"Domain\".to_string(), "lib/Domain".to_string())));
+        assert!(mappings.contains(&(r"Tests\".to_string(), "tests".to_string())));
+    }
+
+    #[test]
+    fn composer_psr4_joins_nested_manifest_dir() {
+        let content = r#"{"autoload": {"psr-4": {"Billing\\": "src/", "": "."}}}"#;
+        let mappings = composer_psr4(content, "packages/billing");
+        assert!(mappings.contains(&(r"Billing\".to_string(), "packages/billing/src".to_string())));
+        assert!(mappings.contains(&(String::new(), "packages/billing".to_string())));
+    }
+
+    #[test]
+    fn composer_psr4_ignores_invalid_json() {
+        assert!(composer_psr4("{ not json", "").is_empty());
+    }
+
+    #[test]
+    fn cargo_packages_reads_all_dependency_tables() {
+        let content = r#"
+[package]
+name = "topo-cli"
+
+[dependencies]
+serde-json = "1"
+anyhow = { workspace = true }
+
+[dev-dependencies]
+tempfile = "3"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+"#;
+        let packages = cargo_packages(content);
+        assert_eq!(packages.own, vec!["topo_cli"]);
+        let mut deps = packages.dependencies;
+        deps.sort();
+        assert_eq!(deps, vec!["anyhow", "libc", "serde_json", "tempfile"]);
+    }
+
+    #[test]
+    fn npm_packages_reads_name_and_dependencies() {
+        let content = r#"{
+            "name": "@acme/web",
+            "dependencies": { "react": "^18" },
+            "devDependencies": { "@types/node": "^20" }
+        }"#;
+        let packages = npm_packages(content);
+        assert_eq!(packages.own, vec!["@acme/web"]);
+        assert_eq!(packages.dependencies, vec!["react", "@types/node"]);
+    }
+
+    #[test]
+    fn go_mod_packages_reads_module_and_requires() {
+        let content = "module github.com/acme/api\n\ngo 1.22\n\nrequire github.com/google/uuid v1.6.0\n\nrequire (\n\tgolang.org/x/sync v0.7.0 // indirect\n\tgithub.com/spf13/cobra v1.8.0\n)\n";
+        let packages = go_mod_packages(content);
+        assert_eq!(packages.own, vec!["github.com/acme/api"]);
+        assert_eq!(
+            packages.dependencies,
+            vec![
+                "github.com/google/uuid",
+                "golang.org/x/sync",
+                "github.com/spf13/cobra"
+            ]
+        );
+    }
+
+    #[test]
+    fn pyproject_packages_reads_pep621_and_poetry() {
+        let content = r#"
+[project]
+name = "My-Service"
+dependencies = ["requests>=2.31", "uvicorn[standard]", "ruamel.yaml"]
+
+[project.optional-dependencies]
+test = ["pytest"]
+
+[tool.poetry.dependencies]
+python = "^3.11"
+Django = "^5"
+"#;
+        let packages = pyproject_packages(content);
+        assert_eq!(packages.own, vec!["my_service"]);
+        assert_eq!(
+            packages.dependencies,
+            vec!["requests", "uvicorn", "ruamel_yaml", "pytest", "django"]
+        );
+    }
+
+    #[test]
+    fn swift_package_targets_use_paths_and_conventions() {
+        let content = r#"
+let package = Package(
+    name: "App",
+    targets: [
+        .target(name: "MyFeatureKit", dependencies: [.target(name: "Core")]),
+        .target(
+            name: "Core",
+            path: "Modules/Core/"
+        ),
+        .executableTarget(name: "app", dependencies: ["MyFeatureKit"]),
+        .testTarget(name: "MyFeatureKitTests", dependencies: ["MyFeatureKit"]),
+    ]
+)
+"#;
+        let targets = swift_package_targets(content, "");
+        assert_eq!(
+            targets,
+            vec![
+                (
+                    "MyFeatureKit".to_string(),
+                    "Sources/MyFeatureKit".to_string()
+                ),
+                ("Core".to_string(), "Modules/Core".to_string()),
+                ("app".to_string(), "Sources/app".to_string()),
+                (
+                    "MyFeatureKitTests".to_string(),
+                    "Tests/MyFeatureKitTests".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn swift_package_targets_join_package_dir() {
+        let content = r#".target(name: "Net")"#;
+        assert_eq!(
+            swift_package_targets(content, "Packages/Net"),
+            vec![("Net".to_string(), "Packages/Net/Sources/Net".to_string())]
+        );
+    }
+}
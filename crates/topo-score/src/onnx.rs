@@ -0,0 +1,387 @@
+//! [`Embedder`] running a local sentence-embedding model, such as
+//! all-MiniLM-L6-v2, and [`Reranker`] running a local cross-encoder, such
+//! as ms-marco-MiniLM-L-6-v2, both exported to ONNX. ONNX Runtime is loaded
+//! at run time, so both work offline and without API keys.
+
+use crate::embed::{Embedder, InputKind};
+use crate::rerank::Reranker;
+use anyhow::{Context, anyhow};
+use ort::session::Session;
+use ort::value::Tensor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokenizers::{Encoding, PaddingParams, Tokenizer, TruncationParams};
+use topo_core::{EmbeddingsConfig, RerankConfig};
+
+/// Inputs are cut to this many tokens, the sequence length MiniLM-style
+/// models are trained on.
+const MAX_TOKENS: usize = 256;
+const DEFAULT_BATCH_SIZE: usize = 32;
+/// Query-document pairs scored per run of a cross-encoder.
+const RERANK_BATCH_SIZE: usize = 16;
+
+/// [`Embedder`] backed by an ONNX model and its Hugging Face tokenizer.
+///
+/// Token-level outputs are mean-pooled over the attention mask; models that
+/// already pool (a `[batch, dims]` output) are used as is. Vectors are
+/// L2-normalized either way.
+pub struct OnnxEmbedder {
+    session: Mutex<Session>,
+    tokenizer: Tokenizer,
+    model_id: String,
+    dims: usize,
+    batch_size: usize,
+    /// Whether the graph takes `token_type_ids` (BERT exports do).
+    token_types: bool,
+}
+
+impl OnnxEmbedder {
+    /// Load `model` with the tokenizer at `tokenizer`. `dims` is needed only
+    /// when the model's output width is dynamic.
+    pub fn load(model: &Path, tokenizer: &Path, dims: Option<usize>) -> anyhow::Result<Self> {
+        let tokenizer = load_tokenizer(tokenizer)?;
+        let session = load_session(model)?;
+        let token_types = takes_token_types(&session);
+        let width = session
+            .outputs
+            .first()
+            .and_then(|o| o.output_type.tensor_shape())
+            .and_then(|shape| shape.last().copied())
+            .filter(|&d| d > 0)
+            .map(|d| d as usize);
+        let dims = match (width, dims) {
+            (Some(w), Some(d)) if w != d => anyhow::bail!(
+                "{} produces {w}-dimensional embeddings, but embeddings.dimensions is {d}",
+                model.display()
+            ),
+            (Some(w), _) => w,
+            (None, Some(d)) => d,
+            (None, None) => anyhow::bail!(
+                "{} has a dynamic output width; set embeddings.dimensions",
+                model.display()
+            ),
+        };
+
+        Ok(Self {
+            session: Mutex::new(session),
+            tokenizer,
+            model_id: format!("local:{}", model.display()),
+            dims,
+            batch_size: DEFAULT_BATCH_SIZE,
+            token_types,
+        })
+    }
+
+    /// Embedder configured by `[embeddings]` with `provider = "local"`.
+    /// Relative paths are resolved against `root`; the tokenizer defaults to
+    /// `tokenizer.json` next to the model.
+    pub fn from_config(config: &EmbeddingsConfig, root: &Path) -> anyhow::Result<Self> {
+        let model_path = config
+            .model_path
+            .as_deref()
+            .ok_or_else(|| anyhow!("the local embedding provider needs embeddings.model_path"))?;
+        let model = root.join(model_path);
+        let tokenizer = match &config.tokenizer_path {
+            Some(path) => root.join(path),
+            None => model.with_file_name("tokenizer.json"),
+        };
+        let mut embedder = Self::load(&model, &tokenizer, config.dimensions)?;
+        // Name the vector space after the configured model, not where it
+        // happens to live
+        embedder.model_id = format!("local:{}", config.model.as_deref().unwrap_or(model_path));
+        if let Some(n) = config.batch_size {
+            embedder = embedder.batch_size(n);
+        }
+        Ok(embedder)
+    }
+
+    pub fn batch_size(mut self, n: usize) -> Self {
+        self.batch_size = n.max(1);
+        self
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| anyhow!("tokenizing: {e}"))?;
+        let mask = column(&encodings, Encoding::get_attention_mask);
+        let (out_shape, data) = run(&self.session, &encodings, self.token_types)?;
+        let mut vectors = match *out_shape {
+            [rows, dims] => data
+                .chunks(dims as usize)
+                .take(rows as usize)
+                .map(<[f32]>::to_vec)
+                .collect(),
+            [_, tokens, dims] => mean_pool(&data, tokens as usize, dims as usize, &mask),
+            _ => anyhow::bail!("unexpected ONNX output shape {out_shape:?}"),
+        };
+        for v in &mut vectors {
+            normalize(v);
+        }
+        if let Some(v) = vectors.iter().find(|v| v.len() != self.dims) {
+            anyhow::bail!(
+                "{} returned {}-dimensional embeddings, expected {}",
+                self.model_id,
+                v.len(),
+                self.dims
+            );
+        }
+        Ok(vectors)
+    }
+}
+
+impl Embedder for OnnxEmbedder {
+    fn model(&self) -> &str {
+        &self.model_id
+    }
+
+    fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Symmetric models embed queries and documents alike.
+    fn embed(&self, texts: &[&str], _kind: InputKind) -> anyhow::Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            vectors.extend(self.embed_batch(batch)?);
+        }
+        Ok(vectors)
+    }
+}
+
+/// [`Reranker`] backed by an ONNX cross-encoder, which reads the query and a
+/// document as one input and outputs a relevance logit.
+pub struct OnnxReranker {
+    session: Mutex<Session>,
+    tokenizer: Tokenizer,
+    model_id: String,
+    token_types: bool,
+}
+
+impl OnnxReranker {
+    /// Load the cross-encoder `model` with the tokenizer at `tokenizer`.
+    pub fn load(model: &Path, tokenizer: &Path) -> anyhow::Result<Self> {
+        let tokenizer = load_tokenizer(tokenizer)?;
+        let session = load_session(model)?;
+        let token_types = takes_token_types(&session);
+        Ok(Self {
+            session: Mutex::new(session),
+            tokenizer,
+            model_id: format!("local:{}", model.display()),
+            token_types,
+        })
+    }
+
+    /// Reranker configured by `[rerank]` with `provider = "local"`.
+    /// Relative paths are resolved against `root`; the tokenizer defaults to
+    /// `tokenizer.json` next to the model.
+    pub fn from_config(config: &RerankConfig, root: &Path) -> anyhow::Result<Self> {
+        let model_path = config
+            .model_path
+            .as_deref()
+            .ok_or_else(|| anyhow!("the local rerank provider needs rerank.model_path"))?;
+        let model = root.join(model_path);
+        let tokenizer = match &config.tokenizer_path {
+            Some(path) => root.join(path),
+            None => model.with_file_name("tokenizer.json"),
+        };
+        let mut reranker = Self::load(&model, &tokenizer)?;
+        reranker.model_id = format!("local:{}", config.model.as_deref().unwrap_or(model_path));
+        Ok(reranker)
+    }
+}
+
+impl Reranker for OnnxReranker {
+    fn model(&self) -> &str {
+        &self.model_id
+    }
+
+    fn score(&self, query: &str, documents: &[&str]) -> anyhow::Result<Vec<f64>> {
+        let mut scores = Vec::with_capacity(documents.len());
+        for batch in documents.chunks(RERANK_BATCH_SIZE) {
+            let pairs: Vec<(&str, &str)> = batch.iter().map(|d| (query, *d)).collect();
+            let encodings = self
+                .tokenizer
+                .encode_batch(pairs, true)
+                .map_err(|e| anyhow!("tokenizing: {e}"))?;
+            let (out_shape, data) = run(&self.session, &encodings, self.token_types)?;
+            // One logit per pair, or two classes of which the last is
+            // "relevant"
+            let width = match *out_shape {
+                [_] => 1,
+                [_, width] if width > 0 => width as usize,
+                _ => anyhow::bail!("unexpected ONNX output shape {out_shape:?}"),
+            };
+            scores.extend(
+                data.chunks(width)
+                    .take(batch.len())
+                    .map(|row| f64::from(row[width - 1])),
+            );
+        }
+        Ok(scores)
+    }
+}
+
+fn load_tokenizer(path: &Path) -> anyhow::Result<Tokenizer> {
+    let mut tokenizer = Tokenizer::from_file(path)
+        .map_err(|e| anyhow!("loading tokenizer {}: {e}", path.display()))?;
+    tokenizer
+        .with_truncation(Some(TruncationParams {
+            max_length: MAX_TOKENS,
+            ..Default::default()
+        }))
+        .map_err(|e| anyhow!("configuring tokenizer: {e}"))?;
+    tokenizer.with_padding(Some(PaddingParams::default()));
+    Ok(tokenizer)
+}
+
+fn load_session(model: &Path) -> anyhow::Result<Session> {
+    check_runtime()?;
+    Session::builder()
+        .and_then(|b| b.commit_from_file(model))
+        .with_context(|| format!("loading ONNX model {}", model.display()))
+}
+
+/// Whether the graph takes `token_type_ids` (BERT exports do).
+fn takes_token_types(session: &Session) -> bool {
+    session.inputs.iter().any(|i| i.name == "token_type_ids")
+}
+
+/// One tokenizer field of every encoding, row-major, as model input.
+fn column(encodings: &[Encoding], field: fn(&Encoding) -> &[u32]) -> Vec<i64> {
+    encodings
+        .iter()
+        .flat_map(|e| field(e).iter().map(|&x| i64::from(x)))
+        .collect()
+}
+
+/// Run `session` on a padded batch and copy out its first output.
+fn run(
+    session: &Mutex<Session>,
+    encodings: &[Encoding],
+    token_types: bool,
+) -> anyhow::Result<(Vec<i64>, Vec<f32>)> {
+    let shape = [encodings.len(), encodings.first().map_or(0, Encoding::len)];
+    let mut inputs = ort::inputs![
+        "input_ids" => Tensor::from_array((shape, column(encodings, Encoding::get_ids)))?,
+        "attention_mask" => Tensor::from_array((shape, column(encodings, Encoding::get_attention_mask)))?,
+    ];
+    if token_types {
+        inputs.push((
+            "token_type_ids".into(),
+            Tensor::from_array((shape, column(encodings, Encoding::get_type_ids)))?.into(),
+        ));
+    }
+
+    let mut session = session
+        .lock()
+        .map_err(|_| anyhow!("ONNX session poisoned by an earlier panic"))?;
+    let outputs = session.run(inputs)?;
+    let (out_shape, data) = outputs[0].try_extract_tensor::<f32>()?;
+    Ok((out_shape.to_vec(), data.to_vec()))
+}
+
+/// ONNX Runtime panics when its shared library cannot be loaded; try it
+/// first, the way `ort` will, so that is an error instead.
+fn check_runtime() -> anyhow::Result<()> {
+    let name = match std::env::var("ORT_DYLIB_PATH") {
+        Ok(path) if !path.is_empty() => PathBuf::from(path),
+        _ => PathBuf::from(libloading::library_filename("onnxruntime")),
+    };
+    let beside_exe = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(&name)))
+        .filter(|p| name.is_relative() && p.exists());
+    let path = beside_exe.unwrap_or(name);
+    // SAFETY: ONNX Runtime's initializers have no preconditions; `ort`
+    // loads the same library next
+    unsafe { libloading::Library::new(&path) }.map(drop).map_err(|e| {
+        anyhow!(
+            "could not load ONNX Runtime from {}: {e}; install it or point ORT_DYLIB_PATH at the library",
+            path.display()
+        )
+    })
+}
+
+/// Average the token vectors of each row where `mask` is set.
+/// `hidden` is `[rows, tokens, dims]`, `mask` is `[rows, tokens]`.
+fn mean_pool(hidden: &[f32], tokens: usize, dims: usize, mask: &[i64]) -> Vec<Vec<f32>> {
+    hidden
+        .chunks(tokens * dims)
+        .zip(mask.chunks(tokens))
+        .map(|(row, mask)| {
+            let mut sum = vec![0.0f32; dims];
+            let mut count = 0.0f32;
+            for (token, _) in row.chunks(dims).zip(mask).filter(|(_, m)| **m != 0) {
+                for (s, x) in sum.iter_mut().zip(token) {
+                    *s += x;
+                }
+                count += 1.0;
+            }
+            sum.iter_mut().for_each(|s| *s /= count.max(1.0));
+            sum
+        })
+        .collect()
+}
+
+fn normalize(v: &mut [f32]) {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_pool_skips_padding() {
+        // Two rows of two tokens, 2 dims; the second row's last token is padding
+        let hidden = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 100.0, 100.0];
+        let mask = [1, 1, 1, 0];
+        assert_eq!(
+            mean_pool(&hidden, 2, 2, &mask),
+            [vec![2.0, 3.0], vec![5.0, 6.0]]
+        );
+    }
+
+    #[test]
+    fn normalize_gives_unit_length() {
+        let mut v = vec![3.0, 4.0];
+        normalize(&mut v);
+        assert_eq!(v, [0.6, 0.8]);
+        let mut zero = vec![0.0, 0.0];
+        normalize(&mut zero);
+        assert_eq!(zero, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn from_config_needs_a_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = OnnxEmbedder::from_config(&EmbeddingsConfig::default(), dir.path())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("model_path"));
+
+        let config = EmbeddingsConfig {
+            provider: Some("local".to_string()),
+            model_path: Some("missing/model.onnx".to_string()),
+            ..Default::default()
+        };
+        let err = OnnxEmbedder::from_config(&config, dir.path())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("tokenizer"));
+    }
+
+    #[test]
+    fn reranker_from_config_needs_a_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = OnnxReranker::from_config(&RerankConfig::default(), dir.path())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("rerank.model_path"));
+    }
+}
@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use topo_core::PageRankConfig;
 
 /// Default damping factor for PageRank.
 const DAMPING: f64 = 0.85;
@@ -6,11 +8,100 @@ const DAMPING: f64 = 0.85;
 const EPSILON: f64 = 1e-6;
 /// Maximum iterations to prevent infinite loops.
 const MAX_ITERATIONS: usize = 100;
+/// Default share of import edges that must change between index builds
+/// before PageRank is recomputed.
+const RECOMPUTE_CHURN: f64 = 0.01;
+
+/// Power-iteration settings for [`ImportGraph::pagerank_with`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageRankParams {
+    damping: f64,
+    epsilon: f64,
+    max_iterations: usize,
+    recompute_churn: f64,
+}
+
+impl Default for PageRankParams {
+    fn default() -> Self {
+        Self {
+            damping: DAMPING,
+            epsilon: EPSILON,
+            max_iterations: MAX_ITERATIONS,
+            recompute_churn: RECOMPUTE_CHURN,
+        }
+    }
+}
+
+impl PageRankParams {
+    /// Defaults overridden by a `[pagerank]` config section.
+    pub fn from_config(config: &PageRankConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            damping: config.damping.unwrap_or(defaults.damping),
+            epsilon: config.epsilon.unwrap_or(defaults.epsilon),
+            max_iterations: config.max_iterations.unwrap_or(defaults.max_iterations),
+            recompute_churn: config.recompute_churn.unwrap_or(defaults.recompute_churn),
+        }
+    }
+
+    /// Probability of following an import rather than jumping to a random file.
+    pub fn damping(mut self, damping: f64) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Stop once no score changes by more than this between iterations.
+    pub fn epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Keep the previous build's scores while no more than this share of
+    /// import edges changed; 0 recomputes after any change.
+    pub fn recompute_churn(mut self, churn: f64) -> Self {
+        self.recompute_churn = churn;
+        self
+    }
+
+    /// The settings that shape the scores, as stored in the deep index.
+    /// The churn threshold only decides when to recompute, so it is left out.
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "damping={} epsilon={} max_iterations={}",
+            self.damping, self.epsilon, self.max_iterations
+        )
+    }
+
+    /// Whether an edge churn of `churn` (0 to 1) calls for recomputing.
+    pub fn needs_recompute(&self, churn: f64) -> bool {
+        churn > self.recompute_churn
+    }
+}
+
+/// A file's place in the import graph beyond its PageRank.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GraphMetrics {
+    /// Files that import this one.
+    pub fan_in: usize,
+    /// Files this one imports.
+    pub fan_out: usize,
+    /// Fewest imports between an entry point and this file: 0 for an entry
+    /// point, `None` when no entry point reaches it.
+    pub depth: Option<usize>,
+}
 
 /// Directed graph of file imports for PageRank computation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImportGraph {
     /// Map from file path to list of files it imports.
     edges: HashMap<String, Vec<String>>,
+    /// Weight of each edge, parallel to `edges`.
+    weights: HashMap<String, Vec<f64>>,
     /// All known file paths.
     nodes: Vec<String>,
 }
@@ -19,6 +110,7 @@ impl ImportGraph {
     pub fn new() -> Self {
         Self {
             edges: HashMap::new(),
+            weights: HashMap::new(),
             nodes: Vec::new(),
         }
     }
@@ -27,15 +119,36 @@ impl ImportGraph {
     pub fn add_node(&mut self, path: &str) {
         if !self.edges.contains_key(path) {
             self.edges.insert(path.to_string(), Vec::new());
+            self.weights.insert(path.to_string(), Vec::new());
             self.nodes.push(path.to_string());
         }
     }
 
     /// Add a directed edge: `from` imports `to`.
     pub fn add_edge(&mut self, from: &str, to: &str) {
+        self.add_weighted_edge(from, to, 1.0);
+    }
+
+    /// Add a directed edge with a weight. Adding an existing edge again adds
+    /// to its weight rather than duplicating it.
+    pub fn add_weighted_edge(&mut self, from: &str, to: &str, weight: f64) {
         self.add_node(from);
         self.add_node(to);
-        self.edges.get_mut(from).unwrap().push(to.to_string());
+        let targets = self.edges.get_mut(from).unwrap();
+        let weights = self.weights.get_mut(from).unwrap();
+        match targets.iter().position(|t| t == to) {
+            Some(i) => weights[i] += weight,
+            None => {
+                targets.push(to.to_string());
+                weights.push(weight);
+            }
+        }
+    }
+
+    /// Weight of the edge `from` → `to`, or `None` if there is no such edge.
+    pub fn edge_weight(&self, from: &str, to: &str) -> Option<f64> {
+        let i = self.edges.get(from)?.iter().position(|t| t == to)?;
+        Some(self.weights[from][i])
     }
 
     /// Build the graph from import relationships extracted from source files.
@@ -54,54 +167,118 @@ impl ImportGraph {
         graph
     }
 
-    /// Compute PageRank scores for all nodes in the graph.
+    /// Compute PageRank scores for all nodes in the graph with default settings.
     ///
     /// Returns a map from file path to PageRank score (0.0 - 1.0 range, sums to ~1.0).
     pub fn pagerank(&self) -> HashMap<String, f64> {
+        self.pagerank_with(&PageRankParams::default())
+    }
+
+    /// Compute PageRank scores for all nodes in the graph.
+    ///
+    /// Rank flows along edges in proportion to their weight, so a file passes
+    /// more of its rank to the modules it depends on heavily. Dangling files
+    /// (importing nothing) spread their rank evenly over all files, so the
+    /// scores keep summing to 1 instead of leaking away on leaf-heavy repos.
+    pub fn pagerank_with(&self, params: &PageRankParams) -> HashMap<String, f64> {
+        self.pagerank_from(params, &HashMap::new()).0
+    }
+
+    /// Like [`ImportGraph::pagerank_with`], starting the power iteration
+    /// from `seed` instead of the uniform distribution, and also returning
+    /// the number of iterations run. Seeded with the scores of a slightly
+    /// different graph, it converges to the same result in fewer
+    /// iterations.
+    ///
+    /// `seed` may be on any scale, such as normalized to a maximum of 1.
+    /// Files missing from it start at the uniform share.
+    pub fn pagerank_from(
+        &self,
+        params: &PageRankParams,
+        seed: &HashMap<String, f64>,
+    ) -> (HashMap<String, f64>, usize) {
+        let PageRankParams {
+            damping,
+            epsilon,
+            max_iterations,
+            ..
+        } = *params;
         let n = self.nodes.len();
         if n == 0 {
-            return HashMap::new();
+            return (HashMap::new(), 0);
         }
 
+        // Seeded files keep their share of the seed's mass, scaled to the
+        // share of files they make up
         let initial = 1.0 / n as f64;
+        let seeded = |node: &String| {
+            seed.get(node)
+                .copied()
+                .filter(|s| s.is_finite() && *s > 0.0)
+        };
+        let seeded_count = self
+            .nodes
+            .iter()
+            .filter(|node| seeded(node).is_some())
+            .count();
+        let seed_mass: f64 = self.nodes.iter().filter_map(seeded).sum();
         let mut scores: HashMap<String, f64> = self
             .nodes
             .iter()
-            .map(|node| (node.clone(), initial))
+            .map(|node| {
+                let start = seeded(node)
+                    .map_or(initial, |s| s / seed_mass * seeded_count as f64 / n as f64);
+                (node.clone(), start)
+            })
             .collect();
 
-        // Build reverse edges (who imports each file)
-        let mut incoming: HashMap<&str, Vec<&str>> = HashMap::new();
+        // Build reverse edges (who imports each file, and how heavily)
+        let mut incoming: HashMap<&str, Vec<(&str, f64)>> = HashMap::new();
         for node in &self.nodes {
             incoming.insert(node.as_str(), Vec::new());
         }
         for (from, tos) in &self.edges {
-            for to in tos {
+            for (to, &weight) in tos.iter().zip(&self.weights[from]) {
                 if let Some(inc) = incoming.get_mut(to.as_str()) {
-                    inc.push(from.as_str());
+                    inc.push((from.as_str(), weight));
                 }
             }
         }
 
-        // Outgoing edge counts
-        let out_degree: HashMap<&str, usize> = self
-            .edges
+        // Total outgoing edge weight
+        let out_weight: HashMap<&str, f64> = self
+            .weights
             .iter()
-            .map(|(k, v)| (k.as_str(), v.len()))
+            .map(|(k, v)| (k.as_str(), v.iter().sum()))
             .collect();
 
-        for _ in 0..MAX_ITERATIONS {
+        let dangling: Vec<&str> = self
+            .nodes
+            .iter()
+            .map(String::as_str)
+            .filter(|node| out_weight.get(node).copied().unwrap_or(0.0) == 0.0)
+            .collect();
+
+        let mut iterations = 0;
+        for _ in 0..max_iterations {
+            iterations += 1;
             let mut new_scores: HashMap<String, f64> = HashMap::new();
             let mut max_diff: f64 = 0.0;
 
+            let dangling_mass: f64 = dangling
+                .iter()
+                .map(|node| scores.get(*node).copied().unwrap_or(initial))
+                .sum();
+            let base = (1.0 - damping) / n as f64 + damping * dangling_mass / n as f64;
+
             for node in &self.nodes {
-                let mut rank = (1.0 - DAMPING) / n as f64;
+                let mut rank = base;
 
                 if let Some(inbound) = incoming.get(node.as_str()) {
-                    for &src in inbound {
-                        let src_out = *out_degree.get(src).unwrap_or(&1);
+                    for &(src, weight) in inbound {
+                        let src_out = out_weight.get(src).copied().unwrap_or(weight);
                         let src_score = scores.get(src).copied().unwrap_or(initial);
-                        rank += DAMPING * src_score / src_out as f64;
+                        rank += damping * src_score * weight / src_out;
                     }
                 }
 
@@ -112,27 +289,36 @@ impl ImportGraph {
 
             scores = new_scores;
 
-            if max_diff < EPSILON {
+            if max_diff < epsilon {
                 break;
             }
         }
+        log::debug!(
+            "PageRank over {n} files: {iterations} iterations from a {} start (damping {damping}, epsilon {epsilon})",
+            if seeded_count > 0 { "warm" } else { "cold" }
+        );
 
-        scores
+        (scores, iterations)
     }
 
     /// Compute PageRank and normalize to [0.0, 1.0] range.
     pub fn normalized_pagerank(&self) -> HashMap<String, f64> {
-        let scores = self.pagerank();
-        if scores.is_empty() {
-            return scores;
-        }
+        self.normalized_pagerank_with(&PageRankParams::default())
+    }
 
-        let max = scores.values().cloned().fold(0.0f64, f64::max);
-        if max == 0.0 {
-            return scores;
-        }
+    /// Like [`ImportGraph::normalized_pagerank`], with explicit settings.
+    pub fn normalized_pagerank_with(&self, params: &PageRankParams) -> HashMap<String, f64> {
+        normalize(self.pagerank_with(params))
+    }
 
-        scores.into_iter().map(|(k, v)| (k, v / max)).collect()
+    /// Like [`ImportGraph::pagerank_from`], normalized to [0.0, 1.0].
+    pub fn normalized_pagerank_from(
+        &self,
+        params: &PageRankParams,
+        seed: &HashMap<String, f64>,
+    ) -> (HashMap<String, f64>, usize) {
+        let (scores, iterations) = self.pagerank_from(params, seed);
+        (normalize(scores), iterations)
     }
 
     pub fn node_count(&self) -> usize {
@@ -142,6 +328,83 @@ impl ImportGraph {
     pub fn edge_count(&self) -> usize {
         self.edges.values().map(|v| v.len()).sum()
     }
+
+    /// Every edge as (`from`, `to`, weight), in node insertion order.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str, f64)> {
+        self.nodes.iter().flat_map(|from| {
+            self.edges[from]
+                .iter()
+                .zip(&self.weights[from])
+                .map(move |(to, &w)| (from.as_str(), to.as_str(), w))
+        })
+    }
+
+    /// Files that `path` imports.
+    pub fn imports_of(&self, path: &str) -> &[String] {
+        self.edges.get(path).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Files that import `path`, in node insertion order.
+    pub fn importers_of(&self, path: &str) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|node| self.edges[node.as_str()].iter().any(|to| to == path))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Fan-in, fan-out and depth of every node. Depth follows imports
+    /// breadth first from `entry_points`; those not in the graph are
+    /// ignored.
+    pub fn metrics<'a>(
+        &self,
+        entry_points: impl IntoIterator<Item = &'a str>,
+    ) -> HashMap<String, GraphMetrics> {
+        let mut metrics: HashMap<&str, GraphMetrics> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let fan_out = self.edges[node.as_str()].len();
+                (
+                    node.as_str(),
+                    GraphMetrics {
+                        fan_out,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        for (_, to, _) in self.edges() {
+            if let Some(m) = metrics.get_mut(to) {
+                m.fan_in += 1;
+            }
+        }
+
+        let mut queue = VecDeque::new();
+        for entry in entry_points {
+            if let Some(m) = metrics.get_mut(entry)
+                && m.depth.is_none()
+            {
+                m.depth = Some(0);
+                queue.push_back((entry, 0));
+            }
+        }
+        while let Some((path, depth)) = queue.pop_front() {
+            for to in self.imports_of(path) {
+                if let Some(m) = metrics.get_mut(to.as_str())
+                    && m.depth.is_none()
+                {
+                    m.depth = Some(depth + 1);
+                    queue.push_back((to.as_str(), depth + 1));
+                }
+            }
+        }
+
+        metrics
+            .into_iter()
+            .map(|(path, m)| (path.to_string(), m))
+            .collect()
+    }
 }
 
 impl Default for ImportGraph {
@@ -150,6 +413,15 @@ impl Default for ImportGraph {
     }
 }
 
+/// Scale `scores` so the highest is 1.
+fn normalize(scores: HashMap<String, f64>) -> HashMap<String, f64> {
+    let max = scores.values().cloned().fold(0.0f64, f64::max);
+    if max == 0.0 {
+        return scores;
+    }
+    scores.into_iter().map(|(k, v)| (k, v / max)).collect()
+}
+
 /// Extract import paths from common language patterns.
 ///
 /// Returns a list of imported module/file paths (not yet resolved to actual file paths).
@@ -223,6 +495,15 @@ fn extract_js_imports(content: &str) -> Vec<String> {
     let mut imports = Vec::new();
     for line in content.lines() {
         let trimmed = line.trim();
+        // import "path" (side effects only)
+        if let Some(rest) = trimmed.strip_prefix("import ")
+            && rest.starts_with(['\'', '"'])
+        {
+            let path = rest.trim_end_matches(';').trim_matches(['\'', '"']);
+            if !path.is_empty() {
+                imports.push(path.to_string());
+            }
+        }
         // import ... from "path"
         if trimmed.starts_with("import ")
             && let Some(from_idx) = trimmed.find("from ")
@@ -286,10 +567,17 @@ fn extract_c_includes(content: &str) -> Vec<String> {
     imports
 }
 
+/// Ruby: `require` paths, plus constant references (`Admin::UsersController`)
+/// for autoloaded code that is never required explicitly. Constants are
+/// reported once each, after the requires.
 fn extract_ruby_imports(content: &str) -> Vec<String> {
     let mut imports = Vec::new();
+    let mut constants: Vec<String> = Vec::new();
     for line in content.lines() {
         let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            continue;
+        }
         // require "foo" or require 'foo'
         if let Some(rest) = trimmed
             .strip_prefix("require ")
@@ -299,11 +587,73 @@ fn extract_ruby_imports(content: &str) -> Vec<String> {
             if !path.is_empty() {
                 imports.push(path.to_string());
             }
+            continue;
+        }
+        for constant in ruby_constants(trimmed) {
+            if !constants.contains(&constant) {
+                constants.push(constant);
+            }
         }
     }
+    imports.extend(constants);
     imports
 }
 
+/// Constant paths in a line of Ruby: `Foo`, `Foo::Bar`, `::Foo` (leading `::`
+/// dropped). Text inside string literals is skipped.
+fn ruby_constants(line: &str) -> Vec<String> {
+    let bytes = line.as_bytes();
+    let mut constants = Vec::new();
+    let mut quote: Option<u8> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if let Some(q) = quote {
+            if b == b'\\' {
+                i += 1;
+            } else if b == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        if b == b'"' || b == b'\'' {
+            quote = Some(b);
+            i += 1;
+            continue;
+        }
+        if b == b'#' {
+            break;
+        }
+        // `::Foo` is a top-level reference; `:Foo` is a symbol
+        let leading_scope = i >= 2 && &bytes[i - 2..i] == b"::";
+        let starts_constant = match i.checked_sub(1).map(|j| bytes[j]) {
+            None => true,
+            Some(b':') => leading_scope,
+            Some(p) => !(p.is_ascii_alphanumeric() || matches!(p, b'_' | b'@' | b'$' | b'.')),
+        };
+        if b.is_ascii_uppercase() && starts_constant {
+            let start = i;
+            loop {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                if line[i..].starts_with("::")
+                    && bytes.get(i + 2).is_some_and(u8::is_ascii_uppercase)
+                {
+                    i += 2;
+                } else {
+                    break;
+                }
+            }
+            constants.push(line[start..i].to_string());
+            continue;
+        }
+        i += 1;
+    }
+    constants
+}
+
 fn extract_swift_imports(content: &str) -> Vec<String> {
     // Swift allows `import kind Module.Symbol` where kind is class/struct/enum/protocol/func/var/typealias
     const SWIFT_IMPORT_KINDS: &[&str] = &[
@@ -563,6 +913,76 @@ mod tests {
         assert!((max - min) / max < 0.01);
     }
 
+    #[test]
+    fn pagerank_redistributes_dangling_mass() {
+        // Leaf-heavy star: every file imports one util that imports nothing
+        let mut graph = ImportGraph::new();
+        for i in 0..5 {
+            graph.add_edge(&format!("f{i}.rs"), "util.rs");
+        }
+        let scores = graph.pagerank();
+        let total: f64 = scores.values().sum();
+        assert!((total - 1.0).abs() < 1e-4, "scores sum to {total}");
+        assert!(scores["util.rs"] > scores["f0.rs"]);
+    }
+
+    #[test]
+    fn pagerank_params_from_config() {
+        let config = PageRankConfig {
+            damping: Some(0.5),
+            ..Default::default()
+        };
+        let params = PageRankParams::from_config(&config);
+        assert_eq!(params, PageRankParams::default().damping(0.5));
+
+        let mut graph = ImportGraph::new();
+        graph.add_edge("a.rs", "b.rs");
+        let low = graph.pagerank_with(&params);
+        let high = graph.pagerank();
+        // Less damping means less rank flows along the import
+        assert!(low["b.rs"] < high["b.rs"]);
+    }
+
+    #[test]
+    fn warm_start_converges_to_the_cold_result() {
+        let mut graph = ImportGraph::new();
+        for i in 0..50 {
+            graph.add_edge(&format!("{i}.rs"), &format!("{}.rs", i * 7 % 50));
+            graph.add_edge(&format!("{i}.rs"), &format!("{}.rs", (i + 1) % 10));
+        }
+        let params = PageRankParams::default().epsilon(1e-10);
+        let (before, _) = graph.normalized_pagerank_from(&params, &HashMap::new());
+
+        graph.add_edge("3.rs", "4.rs");
+        let (cold, cold_iterations) = graph.pagerank_from(&params, &HashMap::new());
+        let (warm, warm_iterations) = graph.pagerank_from(&params, &before);
+        assert!(warm_iterations < cold_iterations);
+        for (path, score) in &cold {
+            assert!((score - warm[path]).abs() < 1e-8, "{path}");
+        }
+        assert!(params.needs_recompute(0.02));
+        assert!(!params.needs_recompute(0.01));
+    }
+
+    #[test]
+    fn pagerank_follows_edge_weights() {
+        let mut graph = ImportGraph::new();
+        graph.add_weighted_edge("app.ts", "heavy.ts", 3.0);
+        graph.add_weighted_edge("app.ts", "light.ts", 1.0);
+        let scores = graph.pagerank();
+        assert!(scores["heavy.ts"] > scores["light.ts"]);
+    }
+
+    #[test]
+    fn repeated_edges_accumulate_weight() {
+        let mut graph = ImportGraph::new();
+        graph.add_edge("a.rs", "b.rs");
+        graph.add_weighted_edge("a.rs", "b.rs", 0.5);
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.edge_weight("a.rs", "b.rs"), Some(1.5));
+        assert_eq!(graph.edge_weight("b.rs", "a.rs"), None);
+    }
+
     #[test]
     fn pagerank_from_imports() {
         let imports = vec![
@@ -698,6 +1118,28 @@ require_relative "../helpers/crypto"
         assert!(imports.contains(&"../helpers/crypto".to_string()));
     }
 
+    #[test]
+    fn extract_ruby_constant_references() {
+        let code = r#"
+class Admin::UsersController < ApplicationController
+  def index
+    @users = User.where(role: "Admin::Role") # Billing::Ignored
+    ::Reports::Export.call(@users)
+  end
+end
+"#;
+        let imports = extract_imports(code, topo_core::Language::Ruby);
+        assert_eq!(
+            imports,
+            vec![
+                "Admin::UsersController",
+                "ApplicationController",
+                "User",
+                "Reports::Export",
+            ]
+        );
+    }
+
     #[test]
     fn extract_swift_imports_basic() {
         let code = r#"
@@ -807,6 +1249,24 @@ source "$DIR/config.sh"
         assert!(!imports.iter().any(|i| i.contains("config")));
     }
 
+    #[test]
+    fn graph_neighbors() {
+        let imports = vec![
+            (
+                "a.rs".to_string(),
+                vec!["b.rs".to_string(), "c.rs".to_string()],
+            ),
+            ("d.rs".to_string(), vec!["b.rs".to_string()]),
+        ];
+        let graph = ImportGraph::from_imports(&imports);
+
+        assert_eq!(graph.imports_of("a.rs"), ["b.rs", "c.rs"]);
+        assert!(graph.imports_of("b.rs").is_empty());
+        assert!(graph.imports_of("missing.rs").is_empty());
+        assert_eq!(graph.importers_of("b.rs"), vec!["a.rs", "d.rs"]);
+        assert!(graph.importers_of("a.rs").is_empty());
+    }
+
     #[test]
     fn graph_counts() {
         let mut graph = ImportGraph::new();
@@ -816,4 +1276,33 @@ source "$DIR/config.sh"
         assert_eq!(graph.node_count(), 3);
         assert_eq!(graph.edge_count(), 2);
     }
+
+    #[test]
+    fn metrics_count_fan_and_depth_from_entry_points() {
+        let mut graph = ImportGraph::new();
+        graph.add_edge("main.rs", "app.rs");
+        graph.add_edge("main.rs", "config.rs");
+        graph.add_edge("app.rs", "config.rs");
+        graph.add_edge("app.rs", "db.rs");
+        graph.add_edge("tool.rs", "db.rs");
+        graph.add_node("orphan.rs");
+
+        let metrics = graph.metrics(["main.rs", "missing.rs"]);
+        let m = |path: &str| metrics[path];
+        assert_eq!(
+            m("main.rs"),
+            GraphMetrics {
+                fan_in: 0,
+                fan_out: 2,
+                depth: Some(0),
+            }
+        );
+        // The shortest import chain wins
+        assert_eq!(m("config.rs").fan_in, 2);
+        assert_eq!(m("config.rs").depth, Some(1));
+        assert_eq!(m("db.rs").depth, Some(2));
+        assert_eq!(m("tool.rs").depth, None);
+        assert_eq!(m("orphan.rs"), GraphMetrics::default());
+        assert_eq!(metrics.len(), 6);
+    }
 }
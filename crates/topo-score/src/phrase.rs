@@ -0,0 +1,141 @@
+use crate::tokenizer::{StopWords, Tokenizer};
+use std::collections::HashMap;
+use topo_core::{FileEntry, ScoredFile, TermFreqs, TermPositions};
+
+/// Score multiplier for each quoted phrase a file's indexed content holds.
+const PHRASE_BONUS: f64 = 0.5;
+
+/// The quoted phrases of a query (`"token bucket"`), matched by term
+/// adjacency against indexed positions.
+///
+/// A phrase that tokenizes to a single term is not a phrase; it stays a
+/// plain query term.
+#[derive(Debug, Clone, Default)]
+pub struct PhraseMatcher {
+    phrases: Vec<Vec<String>>,
+}
+
+impl PhraseMatcher {
+    pub fn new(query: &str, stop_words: &StopWords) -> Self {
+        let (_, phrases) = Tokenizer::split_phrases(query);
+        Self {
+            phrases: phrases
+                .iter()
+                .map(|p| Tokenizer::tokenize_with(p, stop_words))
+                .filter(|tokens| tokens.len() >= 2)
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phrases.is_empty()
+    }
+
+    pub fn phrases(&self) -> &[Vec<String>] {
+        &self.phrases
+    }
+
+    /// Whether `token` belongs to one of the phrases.
+    pub fn contains_term(&self, token: &str) -> bool {
+        self.phrases.iter().flatten().any(|t| t == token)
+    }
+
+    /// How often `phrase` occurs in each field, counting only runs where
+    /// its terms sit at consecutive positions.
+    pub fn occurrences(phrase: &[String], positions: &HashMap<String, TermPositions>) -> TermFreqs {
+        let Some(per_term) = phrase
+            .iter()
+            .map(|t| positions.get(t))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return TermFreqs::default();
+        };
+        let count = |field: fn(&TermPositions) -> &Vec<u32>| {
+            field(per_term[0])
+                .iter()
+                .filter(|&&start| {
+                    per_term[1..]
+                        .iter()
+                        .enumerate()
+                        .all(|(k, p)| field(p).binary_search(&(start + k as u32 + 1)).is_ok())
+                })
+                .count() as u32
+        };
+        TermFreqs {
+            filename: count(|p| &p.filename),
+            symbols: count(|p| &p.symbols),
+            body: count(|p| &p.body),
+        }
+    }
+
+    /// How many of the phrases occur somewhere in `positions`.
+    pub fn matches(&self, positions: &HashMap<String, TermPositions>) -> usize {
+        self.phrases
+            .iter()
+            .filter(|phrase| {
+                let tf = Self::occurrences(phrase, positions);
+                tf.filename + tf.symbols + tf.body > 0
+            })
+            .count()
+    }
+
+    /// Raise the score of each file whose indexed content holds a phrase,
+    /// then re-sort. Path-only rankings can't see phrases inside files;
+    /// this lets the deep index promote them.
+    pub fn boost(&self, scored: &mut [ScoredFile], files: &HashMap<String, FileEntry>) {
+        if self.is_empty() {
+            return;
+        }
+        for file in scored.iter_mut() {
+            if let Some(entry) = files.get(&file.path) {
+                let matched = self.matches(&entry.positions);
+                file.score *= 1.0 + PHRASE_BONUS * matched as f64;
+            }
+        }
+        scored.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(body: &[&str]) -> HashMap<String, TermPositions> {
+        let mut positions: HashMap<String, TermPositions> = HashMap::new();
+        for (i, token) in body.iter().enumerate() {
+            positions
+                .entry(token.to_string())
+                .or_default()
+                .body
+                .push(i as u32);
+        }
+        positions
+    }
+
+    #[test]
+    fn phrases_need_two_terms() {
+        let matcher =
+            PhraseMatcher::new(r#"limit "token bucket" "the rate""#, &StopWords::default());
+        assert_eq!(matcher.phrases(), &[vec!["token", "bucket"]]);
+        assert!(matcher.contains_term("bucket"));
+        assert!(!matcher.contains_term("rate"));
+    }
+
+    #[test]
+    fn occurrences_require_adjacency() {
+        let matcher = PhraseMatcher::new(r#""token bucket""#, &StopWords::default());
+        let phrase = &matcher.phrases()[0];
+
+        let adjacent = positions(&["refill", "token", "bucket", "token", "bucket"]);
+        assert_eq!(PhraseMatcher::occurrences(phrase, &adjacent).body, 2);
+        assert_eq!(matcher.matches(&adjacent), 1);
+
+        let apart = positions(&["bucket", "token", "count", "bucket"]);
+        assert_eq!(PhraseMatcher::occurrences(phrase, &apart).body, 0);
+        assert_eq!(matcher.matches(&apart), 0);
+    }
+}
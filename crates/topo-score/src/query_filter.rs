@@ -0,0 +1,155 @@
+use anyhow::{Result, bail};
+use topo_core::{FileInfo, FileRole, Language};
+
+/// A task with its `lang:`, `role:` and `path:` filters split off.
+///
+/// `topo query "auth middleware lang:rust role:impl path:src/"` scores only
+/// Rust implementation files under `src/` against `auth middleware`. Values
+/// may be comma-separated (`lang:rust,go`) and keys may repeat; a file must
+/// match one value of every key that is present.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilteredQuery {
+    /// The task without its filters, for the tokenizer.
+    pub text: String,
+    pub languages: Vec<Language>,
+    pub roles: Vec<FileRole>,
+    /// Repository-relative path prefixes, matched on directory boundaries.
+    pub paths: Vec<String>,
+}
+
+impl FilteredQuery {
+    /// Split `query` into free text and filters. Words with any other
+    /// `key:` prefix (`std::io`, `http://`) stay in the text.
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut words = Vec::new();
+        for word in query.split_whitespace() {
+            let Some((key, values)) = word.split_once(':') else {
+                words.push(word);
+                continue;
+            };
+            if !matches!(key, "lang" | "role" | "path") {
+                words.push(word);
+                continue;
+            }
+            if values.is_empty() {
+                bail!("{key}: needs a value, as in {key}:{}", example(key));
+            }
+            for value in values.split(',').filter(|v| !v.is_empty()) {
+                match key {
+                    "lang" => match Language::from_name(value) {
+                        Some(language) => parsed.languages.push(language),
+                        None => bail!("unknown language {value:?} in lang:{values}"),
+                    },
+                    "role" => match FileRole::from_name(value) {
+                        Some(role) => parsed.roles.push(role),
+                        None => bail!(
+                            "unknown role {value:?} in role:{values} \
+                             (expected impl, test, config, docs, generated, build or other)"
+                        ),
+                    },
+                    _ => {
+                        let path = value.trim_start_matches("./").trim_end_matches('/');
+                        parsed.paths.push(path.to_string());
+                    }
+                }
+            }
+        }
+        parsed.text = words.join(" ");
+        Ok(parsed)
+    }
+
+    /// Whether any filter was given.
+    pub fn has_filters(&self) -> bool {
+        !self.languages.is_empty() || !self.roles.is_empty() || !self.paths.is_empty()
+    }
+
+    /// Whether `file` passes every filter.
+    pub fn matches(&self, file: &FileInfo) -> bool {
+        (self.languages.is_empty() || self.languages.contains(&file.language))
+            && (self.roles.is_empty() || self.roles.contains(&file.role))
+            && (self.paths.is_empty() || self.paths.iter().any(|p| under(&file.path, p)))
+    }
+}
+
+fn example(key: &str) -> &'static str {
+    match key {
+        "lang" => "rust",
+        "role" => "impl",
+        _ => "src/",
+    }
+}
+
+/// Whether `path` is `prefix` or lies under it. An empty prefix (`path:./`)
+/// matches everything.
+fn under(path: &str, prefix: &str) -> bool {
+    prefix.is_empty()
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, language: Language, role: FileRole) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size: 100,
+            language,
+            role,
+            sha256: [0u8; 32],
+            lines: 10,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        }
+    }
+
+    #[test]
+    fn parse_splits_filters_from_text() {
+        let query =
+            FilteredQuery::parse("auth middleware lang:rust role:impl path:./src/").unwrap();
+        assert_eq!(query.text, "auth middleware");
+        assert_eq!(query.languages, vec![Language::Rust]);
+        assert_eq!(query.roles, vec![FileRole::Implementation]);
+        assert_eq!(query.paths, vec!["src".to_string()]);
+        assert!(query.has_filters());
+    }
+
+    #[test]
+    fn parse_keeps_other_colons_in_text() {
+        let query = FilteredQuery::parse("std::io error from http://example.com").unwrap();
+        assert_eq!(query.text, "std::io error from http://example.com");
+        assert!(!query.has_filters());
+    }
+
+    #[test]
+    fn parse_accepts_lists_and_rejects_unknown_values() {
+        let query = FilteredQuery::parse("lang:rust,go lang:py").unwrap();
+        assert_eq!(
+            query.languages,
+            vec![Language::Rust, Language::Go, Language::Python]
+        );
+        assert!(query.text.is_empty());
+        assert!(FilteredQuery::parse("lang:cobol").is_err());
+        assert!(FilteredQuery::parse("role:vendor").is_err());
+        assert!(FilteredQuery::parse("path:").is_err());
+    }
+
+    #[test]
+    fn matches_ors_values_and_ands_keys() {
+        let query = FilteredQuery::parse("x lang:rust,go path:src").unwrap();
+        assert!(query.matches(&file("src/a.rs", Language::Rust, FileRole::Implementation)));
+        assert!(query.matches(&file("src/b.go", Language::Go, FileRole::Test)));
+        assert!(!query.matches(&file(
+            "src/c.py",
+            Language::Python,
+            FileRole::Implementation
+        )));
+        assert!(!query.matches(&file("srcs/a.rs", Language::Rust, FileRole::Implementation)));
+        assert!(!query.matches(&file("lib/a.rs", Language::Rust, FileRole::Implementation)));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::co_change::CoChange;
+use crate::pagerank::ImportGraph;
+use std::collections::{HashMap, HashSet, VecDeque};
+use topo_core::{ChunkKind, DeepIndex};
+
+/// Import hops followed from the target; files further away count as
+/// unconnected.
+const MAX_DISTANCE: usize = 3;
+
+/// Symbols shorter than this (`new`, `id`) match too much to relate files.
+const MIN_SYMBOL_LEN: usize = 4;
+
+/// Symbols defined in more files than this (`new`, `is_empty`, `load`) are
+/// conventions rather than shared vocabulary.
+const MAX_DEFINING_FILES: usize = 2;
+
+/// Share of the score each signal contributes.
+const PROXIMITY_WEIGHT: f64 = 0.5;
+const SYMBOL_WEIGHT: f64 = 0.3;
+const CO_CHANGE_WEIGHT: f64 = 0.2;
+
+/// A file related to the target, with the evidence for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Related {
+    pub path: String,
+    pub score: f64,
+    /// Import hops to the target in either direction, if within reach.
+    pub distance: Option<usize>,
+    /// Symbols one of the two files defines and the other mentions, sorted.
+    pub shared_symbols: Vec<String>,
+    /// How often the file changed together with the target, normalized.
+    pub co_change: f64,
+}
+
+/// Ranks files by how closely they relate to a given file rather than to a
+/// free-text task.
+///
+/// Three kinds of evidence are combined: distance in the import graph
+/// (either direction), symbols defined in one file and mentioned in the
+/// other (definitions from the deep index), and co-change history. Each is normalized
+/// to [0.0, 1.0] and weighted into the score.
+#[derive(Debug, Clone)]
+pub struct RelatednessScorer<'a> {
+    graph: &'a ImportGraph,
+    symbols: Option<(&'a DeepIndex, &'a HashMap<String, String>)>,
+    co_change: Option<&'a CoChange>,
+}
+
+impl<'a> RelatednessScorer<'a> {
+    pub fn new(graph: &'a ImportGraph) -> Self {
+        Self {
+            graph,
+            symbols: None,
+            co_change: None,
+        }
+    }
+
+    /// Find shared symbols: definitions come from the chunks of `index`,
+    /// mentions from `sources`, the content of each file by path.
+    pub fn symbols(mut self, index: &'a DeepIndex, sources: &'a HashMap<String, String>) -> Self {
+        self.symbols = Some((index, sources));
+        self
+    }
+
+    /// Mine `co_change` for files committed with the target.
+    pub fn co_change(mut self, co_change: &'a CoChange) -> Self {
+        self.co_change = Some(co_change);
+        self
+    }
+
+    /// Files of `paths` related to `target`, most related first; files
+    /// with no evidence at all are left out.
+    pub fn score<'p>(&self, target: &str, paths: impl Iterator<Item = &'p str>) -> Vec<Related> {
+        let distances = import_distances(self.graph, target);
+        let co_change = self
+            .co_change
+            .map(|c| c.scores(&[target]))
+            .unwrap_or_default();
+        let symbols = self
+            .symbols
+            .map(|(index, sources)| SymbolIndex::new(index, sources, target));
+        let mut related: Vec<Related> = paths
+            .filter(|&path| path != target)
+            .filter_map(|path| {
+                let distance = distances.get(path).copied();
+                let shared_symbols = symbols
+                    .as_ref()
+                    .map(|s| s.shared_with(path))
+                    .unwrap_or_default();
+                let co_change = co_change.get(path).copied().unwrap_or(0.0);
+                if distance.is_none() && shared_symbols.is_empty() && co_change == 0.0 {
+                    return None;
+                }
+                Some(Related {
+                    path: path.to_string(),
+                    score: 0.0,
+                    distance,
+                    shared_symbols,
+                    co_change,
+                })
+            })
+            .collect();
+
+        let max_shared = related
+            .iter()
+            .map(|r| r.shared_symbols.len())
+            .max()
+            .unwrap_or(0);
+        for r in &mut related {
+            let proximity = r.distance.map_or(0.0, |d| 1.0 / d as f64);
+            let symbols = if max_shared == 0 {
+                0.0
+            } else {
+                (1.0 + r.shared_symbols.len() as f64).ln() / (1.0 + max_shared as f64).ln()
+            };
+            r.score = PROXIMITY_WEIGHT * proximity
+                + SYMBOL_WEIGHT * symbols
+                + CO_CHANGE_WEIGHT * r.co_change;
+        }
+        related.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        related
+    }
+}
+
+/// Shortest import distance from `target` to each file within
+/// [`MAX_DISTANCE`] hops, following edges in both directions.
+fn import_distances<'g>(graph: &'g ImportGraph, target: &'g str) -> HashMap<&'g str, usize> {
+    let mut distances: HashMap<&str, usize> = HashMap::from([(target, 0)]);
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(target, 0)]);
+    while let Some((current, distance)) = queue.pop_front() {
+        if distance >= MAX_DISTANCE {
+            continue;
+        }
+        let neighbors = graph
+            .imports_of(current)
+            .iter()
+            .map(String::as_str)
+            .chain(graph.importers_of(current));
+        for neighbor in neighbors {
+            if !distances.contains_key(neighbor) {
+                distances.insert(neighbor, distance + 1);
+                queue.push_back((neighbor, distance + 1));
+            }
+        }
+    }
+    distances.remove(target);
+    distances
+}
+
+/// The target's side of symbol matching: what it defines and its source.
+struct SymbolIndex<'a> {
+    index: &'a DeepIndex,
+    sources: &'a HashMap<String, String>,
+    /// Files defining each symbol.
+    defining: HashMap<&'a str, usize>,
+    defined: HashSet<&'a str>,
+    source: &'a str,
+}
+
+impl<'a> SymbolIndex<'a> {
+    fn new(index: &'a DeepIndex, sources: &'a HashMap<String, String>, target: &str) -> Self {
+        let mut defining: HashMap<&str, usize> = HashMap::new();
+        for path in index.files.keys() {
+            for name in definitions(index, path).collect::<HashSet<_>>() {
+                *defining.entry(name).or_default() += 1;
+            }
+        }
+        let mut symbols = Self {
+            index,
+            sources,
+            defining,
+            defined: HashSet::new(),
+            source: sources.get(target).map_or("", String::as_str),
+        };
+        symbols.defined = symbols.distinctive(target).collect();
+        symbols
+    }
+
+    /// The symbols `path` defines that few other files do.
+    fn distinctive(&self, path: &str) -> impl Iterator<Item = &'a str> {
+        definitions(self.index, path).filter(|name| {
+            self.defining
+                .get(name)
+                .is_some_and(|&n| n <= MAX_DEFINING_FILES)
+        })
+    }
+
+    /// Symbols the target defines that `path` mentions, and that `path`
+    /// defines and the target mentions.
+    fn shared_with(&self, path: &str) -> Vec<String> {
+        let Some(source) = self.sources.get(path) else {
+            return Vec::new();
+        };
+        let theirs: HashSet<&str> = self.distinctive(path).collect();
+        let mut shared: Vec<String> = self
+            .defined
+            .iter()
+            .filter(|name| !theirs.contains(*name) && mentions(source, name))
+            .chain(
+                theirs
+                    .iter()
+                    .filter(|name| !self.defined.contains(*name) && mentions(self.source, name)),
+            )
+            .map(|name| name.to_string())
+            .collect();
+        shared.sort();
+        shared.dedup();
+        shared
+    }
+}
+
+/// Names of the functions and types `path` defines.
+fn definitions<'a>(index: &'a DeepIndex, path: &str) -> impl Iterator<Item = &'a str> {
+    index
+        .files
+        .get(path)
+        .into_iter()
+        .flat_map(|entry| &entry.chunks)
+        .filter(|c| matches!(c.kind, ChunkKind::Function | ChunkKind::Type))
+        .map(|c| c.name.as_str())
+        .filter(|name| name.len() >= MIN_SYMBOL_LEN)
+}
+
+/// Whether `content` mentions the identifier `name` as a whole word.
+fn mentions(content: &str, name: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    content.match_indices(name).any(|(start, _)| {
+        let before = content[..start].chars().next_back();
+        let after = content[start + name.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::{Chunk, FileEntry, FileSummary};
+
+    fn entry(functions: &[&str]) -> FileEntry {
+        FileEntry {
+            sha256: [0u8; 32],
+            chunks: functions
+                .iter()
+                .map(|name| Chunk {
+                    kind: ChunkKind::Function,
+                    name: name.to_string(),
+                    start_line: 1,
+                    end_line: 1,
+                    content: String::new(),
+                })
+                .collect(),
+            term_frequencies: HashMap::new(),
+            positions: HashMap::new(),
+            doc_length: 0,
+            summary: FileSummary::default(),
+            signature: Vec::new(),
+            imports: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn mentions_whole_identifiers_only() {
+        assert!(mentions("let t = refresh_token(x);", "refresh_token"));
+        assert!(!mentions("fn refresh_tokens() {}", "refresh_token"));
+        assert!(!mentions("do_refresh_token()", "refresh_token"));
+    }
+
+    #[test]
+    fn ranks_by_distance_symbols_and_co_change() {
+        let graph = ImportGraph::from_imports(&[
+            ("src/api.rs".to_string(), vec!["src/auth.rs".to_string()]),
+            ("src/main.rs".to_string(), vec!["src/api.rs".to_string()]),
+            ("src/auth.rs".to_string(), vec!["src/token.rs".to_string()]),
+        ]);
+        let mut index = DeepIndex {
+            version: topo_core::INDEX_VERSION,
+            files: HashMap::new(),
+            avg_doc_length: 0.0,
+            total_docs: 0,
+            doc_frequencies: HashMap::new(),
+            pagerank_scores: HashMap::new(),
+            pagerank_settings: String::new(),
+            stop_words: Vec::new(),
+            errors: Vec::new(),
+        };
+        let mut sources = HashMap::new();
+        for (path, function, source) in [
+            (
+                "src/auth.rs",
+                "verify_session",
+                "fn verify_session() { decode_token() }",
+            ),
+            ("src/token.rs", "decode_token", "fn decode_token() {}"),
+            ("src/admin.rs", "admin", "fn admin() { verify_session() }"),
+        ] {
+            index.files.insert(path.to_string(), entry(&[function]));
+            sources.insert(path.to_string(), source.to_string());
+        }
+        let co_change = CoChange::parse("\u{1e}\nsrc/auth.rs\nsrc/audit.rs\n");
+
+        let related = RelatednessScorer::new(&graph)
+            .symbols(&index, &sources)
+            .co_change(&co_change)
+            .score(
+                "src/auth.rs",
+                [
+                    "src/admin.rs",
+                    "src/api.rs",
+                    "src/audit.rs",
+                    "src/auth.rs",
+                    "src/main.rs",
+                    "src/token.rs",
+                    "src/unrelated.rs",
+                ]
+                .into_iter(),
+            );
+        let order: Vec<&str> = related.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            order,
+            [
+                "src/token.rs",
+                "src/api.rs",
+                "src/admin.rs",
+                "src/main.rs",
+                "src/audit.rs"
+            ]
+        );
+        assert_eq!(related[0].shared_symbols, ["decode_token"]);
+        assert_eq!(related[2].shared_symbols, ["verify_session"]);
+        assert_eq!(related[2].distance, None);
+        assert_eq!(related[3].distance, Some(2));
+        assert!(related[4].co_change > 0.0);
+    }
+}
@@ -0,0 +1,492 @@
+//! Cross-encoder reranking: re-score the top of a fused ranking by reading
+//! the query and each candidate together, which first-stage retrieval
+//! cannot afford to do for every file.
+
+use crate::embed::{Transport, default_transport, post_with_retries};
+use serde_json::{Value, json};
+use std::path::Path;
+use std::time::Duration;
+use topo_core::{RerankConfig, ScoredFile};
+
+/// Files re-scored when `[rerank] candidates` is unset.
+pub const DEFAULT_CANDIDATES: usize = 50;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Instructions for an LLM acting as the reranker.
+const LLM_INSTRUCTIONS: &str = "You rank source files for a coding task. Rate how relevant each \
+numbered file is to the task, from 0 (unrelated) to 10 (must read). Reply with a JSON object \
+{\"scores\": [...]} holding one number per file, in the order given.";
+
+/// Scores how relevant documents are to a query.
+pub trait Reranker {
+    /// Identifies the model, e.g. `cohere:rerank-v3.5`.
+    fn model(&self) -> &str;
+
+    /// One relevance score per document, in order; higher is more relevant.
+    /// Scores are only comparable within one call.
+    fn score(&self, query: &str, documents: &[&str]) -> anyhow::Result<Vec<f64>>;
+}
+
+/// A hosted reranking service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RerankProvider {
+    Cohere,
+    Voyage,
+    /// A chat model asked to grade each candidate.
+    OpenAi,
+}
+
+impl RerankProvider {
+    /// Parse a `[rerank] provider` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cohere" => Some(Self::Cohere),
+            "voyage" => Some(Self::Voyage),
+            "openai" => Some(Self::OpenAi),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cohere => "cohere",
+            Self::Voyage => "voyage",
+            Self::OpenAi => "openai",
+        }
+    }
+
+    fn default_endpoint(self) -> &'static str {
+        match self {
+            Self::Cohere => "https://api.cohere.com/v2/rerank",
+            Self::Voyage => "https://api.voyageai.com/v1/rerank",
+            Self::OpenAi => "https://api.openai.com/v1/chat/completions",
+        }
+    }
+
+    fn default_model(self) -> &'static str {
+        match self {
+            Self::Cohere => "rerank-v3.5",
+            Self::Voyage => "rerank-2",
+            Self::OpenAi => "gpt-4o-mini",
+        }
+    }
+
+    fn default_api_key_env(self) -> &'static str {
+        match self {
+            Self::Cohere => "COHERE_API_KEY",
+            Self::Voyage => "VOYAGE_API_KEY",
+            Self::OpenAi => "OPENAI_API_KEY",
+        }
+    }
+
+    /// JSON request body for one query and its candidates.
+    fn request_body(self, model: &str, query: &str, documents: &[&str]) -> Value {
+        match self {
+            Self::Cohere | Self::Voyage => json!({
+                "model": model,
+                "query": query,
+                "documents": documents,
+            }),
+            Self::OpenAi => {
+                let mut prompt = format!("Task: {query}\n");
+                for (i, doc) in documents.iter().enumerate() {
+                    prompt.push_str(&format!("\n[{i}]\n{doc}\n"));
+                }
+                json!({
+                    "model": model,
+                    "temperature": 0,
+                    "response_format": { "type": "json_object" },
+                    "messages": [
+                        { "role": "system", "content": LLM_INSTRUCTIONS },
+                        { "role": "user", "content": prompt },
+                    ],
+                })
+            }
+        }
+    }
+
+    /// Scores from a response body, in document order.
+    fn parse_response(self, body: &str, documents: usize) -> anyhow::Result<Vec<f64>> {
+        let value: Value = serde_json::from_str(body)?;
+        let scores = match self {
+            Self::Cohere | Self::Voyage => {
+                let results = match self {
+                    Self::Cohere => &value["results"],
+                    _ => &value["data"],
+                };
+                let mut scores = vec![None; documents];
+                for r in results
+                    .as_array()
+                    .ok_or_else(|| anyhow::anyhow!("response has no results array"))?
+                {
+                    let (Some(i), Some(score)) =
+                        (r["index"].as_u64(), r["relevance_score"].as_f64())
+                    else {
+                        anyhow::bail!("malformed rerank result");
+                    };
+                    if let Some(slot) = scores.get_mut(i as usize) {
+                        *slot = Some(score);
+                    }
+                }
+                scores.into_iter().collect::<Option<Vec<f64>>>()
+            }
+            Self::OpenAi => {
+                let content = value["choices"][0]["message"]["content"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("response has no message content"))?;
+                let graded: Value = serde_json::from_str(content)?;
+                graded["scores"]
+                    .as_array()
+                    .and_then(|s| s.iter().map(Value::as_f64).collect::<Option<Vec<f64>>>())
+                    .filter(|s| s.len() == documents)
+            }
+        };
+        scores.ok_or_else(|| {
+            anyhow::anyhow!("{} did not score all {documents} candidates", self.as_str())
+        })
+    }
+}
+
+/// [`Reranker`] backed by a hosted API, retrying rate limits and server
+/// errors with backoff.
+pub struct RemoteReranker {
+    provider: RerankProvider,
+    endpoint: String,
+    api_model: String,
+    model_id: String,
+    api_key: String,
+    max_retries: u32,
+    transport: Box<dyn Transport>,
+    sleep: fn(Duration),
+}
+
+impl RemoteReranker {
+    /// Reranker for `provider` with its default model and endpoint.
+    pub fn new(provider: RerankProvider, api_key: &str, transport: Box<dyn Transport>) -> Self {
+        let model = provider.default_model();
+        Self {
+            provider,
+            endpoint: provider.default_endpoint().to_string(),
+            api_model: model.to_string(),
+            model_id: format!("{}:{model}", provider.as_str()),
+            api_key: api_key.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            transport,
+            sleep: std::thread::sleep,
+        }
+    }
+
+    /// Reranker configured by `[rerank]` with a hosted provider. The API key
+    /// is read from the configured environment variable.
+    pub fn from_config(provider: RerankProvider, config: &RerankConfig) -> anyhow::Result<Self> {
+        let key_env = config
+            .api_key_env
+            .as_deref()
+            .unwrap_or(provider.default_api_key_env());
+        let api_key = std::env::var(key_env).map_err(|_| {
+            anyhow::anyhow!(
+                "rerank provider {} needs an API key in ${key_env}",
+                provider.as_str()
+            )
+        })?;
+
+        let mut reranker = Self::new(provider, &api_key, default_transport()?);
+        if let Some(model) = &config.model {
+            reranker = reranker.model_name(model);
+        }
+        if let Some(endpoint) = &config.endpoint {
+            reranker = reranker.endpoint(endpoint);
+        }
+        if let Some(n) = config.max_retries {
+            reranker = reranker.max_retries(n);
+        }
+        Ok(reranker)
+    }
+
+    pub fn model_name(mut self, model: &str) -> Self {
+        self.api_model = model.to_string();
+        self.model_id = format!("{}:{model}", self.provider.as_str());
+        self
+    }
+
+    pub fn endpoint(mut self, url: &str) -> Self {
+        self.endpoint = url.to_string();
+        self
+    }
+
+    pub fn max_retries(mut self, n: u32) -> Self {
+        self.max_retries = n;
+        self
+    }
+
+    /// Replace the delay between retries (tests use a no-op).
+    pub fn sleep_with(mut self, sleep: fn(Duration)) -> Self {
+        self.sleep = sleep;
+        self
+    }
+}
+
+impl Reranker for RemoteReranker {
+    fn model(&self) -> &str {
+        &self.model_id
+    }
+
+    fn score(&self, query: &str, documents: &[&str]) -> anyhow::Result<Vec<f64>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+        let body = self
+            .provider
+            .request_body(&self.api_model, query, documents)
+            .to_string();
+        let response = post_with_retries(
+            self.transport.as_ref(),
+            &self.endpoint,
+            &self.api_key,
+            &body,
+            self.max_retries,
+            self.sleep,
+            self.provider.as_str(),
+        )?;
+        self.provider.parse_response(&response, documents.len())
+    }
+}
+
+/// The reranker `[rerank]` configures, or `None` when no provider is set.
+/// `root` anchors the relative paths of a local model.
+pub fn reranker_from_config(
+    config: &RerankConfig,
+    root: &Path,
+) -> anyhow::Result<Option<Box<dyn Reranker>>> {
+    let Some(name) = config.provider.as_deref() else {
+        return Ok(None);
+    };
+    if name == "local" {
+        return local_reranker(config, root).map(Some);
+    }
+    let provider = RerankProvider::from_name(name)
+        .ok_or_else(|| anyhow::anyhow!("unknown rerank provider {name:?}"))?;
+    Ok(Some(Box::new(RemoteReranker::from_config(
+        provider, config,
+    )?)))
+}
+
+#[cfg(feature = "local-embeddings")]
+fn local_reranker(config: &RerankConfig, root: &Path) -> anyhow::Result<Box<dyn Reranker>> {
+    Ok(Box::new(crate::onnx::OnnxReranker::from_config(
+        config, root,
+    )?))
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+fn local_reranker(_: &RerankConfig, _: &Path) -> anyhow::Result<Box<dyn Reranker>> {
+    anyhow::bail!("this build of topo has no local model support (feature `local-embeddings`)")
+}
+
+/// Re-order the first `candidates` of `scored`, which is sorted best first,
+/// by `reranker`'s relevance, and record it in `signals.rerank`.
+/// `document` gives the text the reranker reads for a file; candidates it
+/// returns `None` for are not sent and keep their place.
+///
+/// The candidates keep the scores they had, handed out again in the new
+/// order, so score thresholds and the files below the cut are unaffected.
+pub fn rerank(
+    reranker: &dyn Reranker,
+    query: &str,
+    scored: &mut [ScoredFile],
+    candidates: usize,
+    document: impl Fn(&ScoredFile) -> Option<String>,
+) -> anyhow::Result<()> {
+    let n = candidates.min(scored.len());
+    let top = &mut scored[..n];
+    let (slots, documents): (Vec<usize>, Vec<String>) = top
+        .iter()
+        .enumerate()
+        .filter_map(|(i, f)| Some((i, document(f)?)))
+        .unzip();
+    if documents.is_empty() {
+        return Ok(());
+    }
+    let inputs: Vec<&str> = documents.iter().map(String::as_str).collect();
+    let scores = reranker.score(query, &inputs)?;
+    if scores.len() != documents.len() {
+        anyhow::bail!(
+            "{} returned {} scores for {} candidates",
+            reranker.model(),
+            scores.len(),
+            documents.len()
+        );
+    }
+
+    let mut reranked: Vec<ScoredFile> = slots.iter().map(|&i| top[i].clone()).collect();
+    for (file, r) in reranked.iter_mut().zip(scores) {
+        file.signals.rerank = Some(r);
+    }
+    // Stable, so ties keep their fused order
+    let relevance = |f: &ScoredFile| f.signals.rerank.unwrap_or(f64::MIN);
+    reranked.sort_by(|a, b| relevance(b).total_cmp(&relevance(a)));
+    for (&i, file) in slots.iter().zip(reranked) {
+        top[i] = ScoredFile {
+            score: top[i].score,
+            ..file
+        };
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embed::HttpResponse;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use topo_core::{FileRole, Language, SignalBreakdown};
+
+    struct FakeTransport {
+        response: String,
+        requests: Rc<RefCell<Vec<Value>>>,
+    }
+
+    impl Transport for FakeTransport {
+        fn post_json(&self, _url: &str, _key: &str, body: &str) -> anyhow::Result<HttpResponse> {
+            self.requests
+                .borrow_mut()
+                .push(serde_json::from_str(body).unwrap());
+            Ok(HttpResponse {
+                status: 200,
+                retry_after: None,
+                body: self.response.clone(),
+            })
+        }
+    }
+
+    fn reranker(
+        provider: RerankProvider,
+        response: Value,
+    ) -> (RemoteReranker, Rc<RefCell<Vec<Value>>>) {
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let transport = FakeTransport {
+            response: response.to_string(),
+            requests: requests.clone(),
+        };
+        let r = RemoteReranker::new(provider, "key", Box::new(transport)).sleep_with(|_| {});
+        (r, requests)
+    }
+
+    #[test]
+    fn hosted_rerankers_map_results_back_to_documents() {
+        let (r, requests) = reranker(
+            RerankProvider::Cohere,
+            json!({ "results": [
+                { "index": 1, "relevance_score": 0.9 },
+                { "index": 0, "relevance_score": 0.2 },
+            ] }),
+        );
+        assert_eq!(r.score("auth", &["a", "b"]).unwrap(), [0.2, 0.9]);
+        assert_eq!(requests.borrow()[0]["documents"], json!(["a", "b"]));
+        assert_eq!(r.model(), "cohere:rerank-v3.5");
+
+        let (r, _) = reranker(
+            RerankProvider::Voyage,
+            json!({ "data": [{ "index": 0, "relevance_score": 0.5 }] }),
+        );
+        assert!(r.score("auth", &["a", "b"]).is_err());
+    }
+
+    #[test]
+    fn llm_reranker_reads_scores_from_the_reply() {
+        let (r, requests) = reranker(
+            RerankProvider::OpenAi,
+            json!({ "choices": [{ "message": { "content": "{\"scores\": [3, 8]}" } }] }),
+        );
+        assert_eq!(r.score("auth", &["a", "b"]).unwrap(), [3.0, 8.0]);
+        let prompt = requests.borrow()[0]["messages"][1]["content"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(prompt.starts_with("Task: auth\n"));
+        assert!(prompt.contains("[1]\nb"));
+    }
+
+    /// Scores a document by how often it contains the query.
+    struct CountingReranker;
+
+    impl Reranker for CountingReranker {
+        fn model(&self) -> &str {
+            "counting"
+        }
+        fn score(&self, query: &str, documents: &[&str]) -> anyhow::Result<Vec<f64>> {
+            Ok(documents
+                .iter()
+                .map(|d| d.matches(query).count() as f64)
+                .collect())
+        }
+    }
+
+    fn scored(path: &str, score: f64) -> ScoredFile {
+        ScoredFile {
+            path: path.to_string(),
+            score,
+            signals: SignalBreakdown::default(),
+            tokens: 10,
+            language: Language::Rust,
+            role: FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rerank_reorders_candidates_and_keeps_scores() {
+        let mut files = vec![
+            scored("x", 0.9),
+            scored("auth auth", 0.5),
+            scored("auth", 0.4),
+            scored("auth auth auth", 0.1),
+        ];
+        rerank(&CountingReranker, "auth", &mut files, 3, |f| {
+            Some(f.path.clone())
+        })
+        .unwrap();
+        let order: Vec<(&str, f64)> = files.iter().map(|f| (f.path.as_str(), f.score)).collect();
+        assert_eq!(
+            order,
+            [
+                ("auth auth", 0.9),
+                ("auth", 0.5),
+                ("x", 0.4),
+                ("auth auth auth", 0.1)
+            ]
+        );
+        assert_eq!(files[0].signals.rerank, Some(2.0));
+        assert_eq!(files[3].signals.rerank, None);
+    }
+
+    #[test]
+    fn rerank_leaves_files_without_a_document_in_place() {
+        let mut files = vec![
+            scored("x", 0.9),
+            scored("auth auth", 0.5),
+            scored("withheld", 0.4),
+            scored("auth auth auth", 0.3),
+        ];
+        rerank(&CountingReranker, "auth", &mut files, 4, |f| {
+            (f.path != "withheld").then(|| f.path.clone())
+        })
+        .unwrap();
+        let order: Vec<(&str, f64)> = files.iter().map(|f| (f.path.as_str(), f.score)).collect();
+        assert_eq!(
+            order,
+            [
+                ("auth auth auth", 0.9),
+                ("auth auth", 0.5),
+                ("withheld", 0.4),
+                ("x", 0.3)
+            ]
+        );
+        assert_eq!(files[2].signals.rerank, None);
+    }
+}
@@ -1,11 +1,11 @@
+use crate::import_weight::WeightedImports;
+use crate::manifest::{self, DeclaredPackages};
 use crate::pagerank::ImportGraph;
-use std::collections::HashMap;
+use crate::vendored::is_vendored;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
 use std::path::Path;
-use topo_core::Language;
-
-/// Directories whose contents should be excluded from the import graph.
-/// These are vendored/generated paths — external dependencies checked into the repo.
-const VENDORED_DIRS: &[&str] = &["vendor", "node_modules", "third_party"];
+use topo_core::{Config, Language};
 
 /// Indexes for resolving import paths to repo files.
 ///
@@ -15,13 +15,156 @@ const VENDORED_DIRS: &[&str] = &["vendor", "node_modules", "third_party"];
 ///
 /// Most languages use the stem index. Go uses the dir index because Go imports
 /// reference packages (directories), not individual files.
+///
+/// Python additionally resolves absolute imports against `python_roots`, the
+/// directories that act as `sys.path` entries, and C/C++ quoted includes
+/// against `include_roots`, both checking exact paths in `paths`. PHP
+/// namespaces map to directories through the `psr4` prefixes, longest first,
+/// Ruby constants through the Rails autoload directories in `ruby_roots`, and
+/// Elixir modules through the `lib/` directories of mix projects in `mix_roots`,
+/// and Swift modules to every file of their package target in `swift_modules`.
 pub struct RepoIndex {
     pub stem: HashMap<String, Vec<String>>,
     pub dir: HashMap<String, Vec<String>>,
+    pub paths: HashSet<String>,
+    pub python_roots: Vec<String>,
+    pub include_roots: Vec<String>,
+    pub psr4: Vec<(String, String)>,
+    pub ruby_roots: Vec<String>,
+    pub mix_roots: Vec<String>,
+    pub swift_modules: HashMap<String, Vec<String>>,
+}
+
+/// Repository-specific settings for import resolution.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolveOptions {
+    python_paths: Vec<String>,
+    include_dirs: Vec<String>,
+    psr4: Vec<(String, String)>,
+    swift_targets: Vec<(String, String)>,
+    packages: Vec<(Ecosystem, DeclaredPackages)>,
+}
+
+/// Package ecosystems whose manifests declare dependencies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ecosystem {
+    Cargo,
+    Npm,
+    Go,
+    Python,
+}
+
+impl Ecosystem {
+    fn of(language: Language) -> Option<Self> {
+        match language {
+            Language::Rust => Some(Self::Cargo),
+            Language::JavaScript | Language::TypeScript => Some(Self::Npm),
+            Language::Go => Some(Self::Go),
+            Language::Python => Some(Self::Python),
+            _ => None,
+        }
+    }
+}
+
+impl ResolveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options from the `.topo/config.toml` sections that affect resolution.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new()
+            .python_paths(&config.python.pythonpath)
+            .include_dirs(&config.cpp.include_dirs)
+    }
+
+    /// Extra roots (relative to the repository) for absolute Python imports.
+    pub fn python_paths<S: AsRef<str>>(mut self, paths: &[S]) -> Self {
+        self.python_paths = paths.iter().map(|p| normalize_dir(p.as_ref())).collect();
+        self
+    }
+
+    /// Directories (relative to the repository) searched for C/C++ quoted
+    /// includes, like a compiler's `-I` flags.
+    pub fn include_dirs<S: AsRef<str>>(mut self, dirs: &[S]) -> Self {
+        self.include_dirs = dirs.iter().map(|d| normalize_dir(d.as_ref())).collect();
+        self
+    }
+
+    /// Map a PHP namespace prefix (`App\`) to a directory, as a
+    /// `composer.json` `autoload.psr-4` entry does.
+    pub fn psr4(mut self, prefix: &str, dir: &str) -> Self {
+        self.psr4.push((prefix.to_string(), normalize_dir(dir)));
+        self
+    }
+
+    /// Map a Swift module to the directory of the package target defining it.
+    pub fn swift_target(mut self, module: &str, dir: &str) -> Self {
+        self.swift_targets
+            .push((module.to_string(), normalize_dir(dir)));
+        self
+    }
+
+    /// Add mappings declared by package manifests among `paths`, read from
+    /// `root`: PSR-4 prefixes from every non-vendored `composer.json`, targets
+    /// from every `Package.swift`, and the declared packages of every
+    /// `Cargo.toml`, `package.json`, `go.mod` and `pyproject.toml` (used to
+    /// classify [`unresolved_imports`]). Unreadable manifests are skipped.
+    pub fn with_manifests(mut self, root: &Path, paths: &[&str]) -> Self {
+        for &path in paths {
+            if is_vendored(path) {
+                continue;
+            }
+            let (dir, file_name) = path.rsplit_once('/').unwrap_or(("", path));
+            if !matches!(
+                file_name,
+                "composer.json"
+                    | "Package.swift"
+                    | "Cargo.toml"
+                    | "package.json"
+                    | "go.mod"
+                    | "pyproject.toml"
+            ) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(root.join(path)) else {
+                continue;
+            };
+            match file_name {
+                "composer.json" => self.psr4.extend(manifest::composer_psr4(&content, dir)),
+                "Package.swift" => self
+                    .swift_targets
+                    .extend(manifest::swift_package_targets(&content, dir)),
+                "Cargo.toml" => self
+                    .packages
+                    .push((Ecosystem::Cargo, manifest::cargo_packages(&content))),
+                "package.json" => self
+                    .packages
+                    .push((Ecosystem::Npm, manifest::npm_packages(&content))),
+                "go.mod" => self
+                    .packages
+                    .push((Ecosystem::Go, manifest::go_mod_packages(&content))),
+                _ => self
+                    .packages
+                    .push((Ecosystem::Python, manifest::pyproject_packages(&content))),
+            }
+        }
+        self
+    }
 }
 
-/// Build stem and directory indexes from file paths.
-pub fn build_file_index(paths: &[&str]) -> RepoIndex {
+/// Strip `./` and trailing slashes; `.` becomes the repository root (`""`).
+fn normalize_dir(dir: &str) -> String {
+    let dir = dir.trim().trim_start_matches("./").trim_end_matches('/');
+    if dir == "." {
+        String::new()
+    } else {
+        dir.to_string()
+    }
+}
+
+/// Build stem, directory and Python root indexes from file paths.
+pub fn build_file_index(paths: &[&str], options: &ResolveOptions) -> RepoIndex {
     let mut stem_index: HashMap<String, Vec<String>> = HashMap::new();
     let mut dir_index: HashMap<String, Vec<String>> = HashMap::new();
 
@@ -67,7 +210,122 @@ pub fn build_file_index(paths: &[&str]) -> RepoIndex {
     RepoIndex {
         stem: stem_index,
         dir: dir_index,
+        paths: paths.iter().map(|p| p.to_string()).collect(),
+        python_roots: python_roots(paths, &options.python_paths),
+        include_roots: include_roots(paths, &options.include_dirs),
+        psr4: {
+            let mut psr4 = options.psr4.clone();
+            psr4.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.cmp(b)));
+            psr4
+        },
+        ruby_roots: ruby_roots(paths),
+        mix_roots: mix_roots(paths),
+        swift_modules: swift_modules(paths, &options.swift_targets),
+    }
+}
+
+/// The Swift source files of each package target, keyed by module name.
+fn swift_modules(paths: &[&str], targets: &[(String, String)]) -> HashMap<String, Vec<String>> {
+    let mut modules: HashMap<String, Vec<String>> = HashMap::new();
+    for (module, dir) in targets {
+        let prefix = format!("{dir}/");
+        let files = paths
+            .iter()
+            .filter(|p| p.ends_with(".swift") && p.starts_with(&prefix))
+            .map(|p| p.to_string());
+        modules.entry(module.clone()).or_default().extend(files);
+    }
+    modules
+}
+
+/// Source directories of every mix project (a directory with `mix.exs`),
+/// including umbrella apps: `lib/` and `test/support/`.
+fn mix_roots(paths: &[&str]) -> Vec<String> {
+    let mut roots = Vec::new();
+    for path in paths {
+        let project = match path.rsplit_once('/') {
+            Some((dir, "mix.exs")) => format!("{dir}/"),
+            None if *path == "mix.exs" => String::new(),
+            _ => continue,
+        };
+        roots.push(format!("{project}lib"));
+        roots.push(format!("{project}test/support"));
+    }
+    roots.sort();
+    roots
+}
+
+/// Subdirectories of `app/` that Rails does not autoload.
+const RAILS_NON_AUTOLOAD_DIRS: &[&str] = &["assets", "javascript", "views"];
+
+/// Rails autoload roots: every `app/<dir>` (and its `concerns/`), including
+/// those of engines nested deeper in the repository.
+fn ruby_roots(paths: &[&str]) -> Vec<String> {
+    let mut roots: BTreeSet<String> = BTreeSet::new();
+    for path in paths.iter().filter(|p| p.ends_with(".rb")) {
+        let parts: Vec<&str> = path.split('/').collect();
+        let Some(app) = parts.iter().position(|&c| c == "app") else {
+            continue;
+        };
+        // app/<dir>/<file> at minimum
+        if parts.len() < app + 3 || RAILS_NON_AUTOLOAD_DIRS.contains(&parts[app + 1]) {
+            continue;
+        }
+        roots.insert(parts[..app + 2].join("/"));
+        if parts[app + 2] == "concerns" && parts.len() > app + 3 {
+            roots.insert(parts[..app + 3].join("/"));
+        }
+    }
+    roots.into_iter().collect()
+}
+
+/// Directories searched for C/C++ quoted includes after the including file's
+/// own directory: configured ones first, then every directory named `include`.
+fn include_roots(paths: &[&str], configured: &[String]) -> Vec<String> {
+    let detected: BTreeSet<&str> = paths
+        .iter()
+        .filter_map(|p| {
+            let (start, _) = p
+                .match_indices("include/")
+                .find(|&(i, _)| i == 0 || p[..i].ends_with('/'))?;
+            Some(&p[..start + "include".len()])
+        })
+        .collect();
+
+    let mut roots: Vec<String> = Vec::new();
+    for root in configured.iter().map(String::as_str).chain(detected) {
+        if !roots.iter().any(|r| r == root) {
+            roots.push(root.to_string());
+        }
+    }
+    roots
+}
+
+/// Directories that act as `sys.path` entries: configured paths first, then
+/// the parent of every top-level package (a directory with `__init__.py`
+/// whose parent has none).
+///
+/// The repository root is always tried last and is not included here.
+fn python_roots(paths: &[&str], configured: &[String]) -> Vec<String> {
+    let packages: HashSet<&str> = paths
+        .iter()
+        .filter_map(|p| p.strip_suffix("__init__.py"))
+        .map(|dir| dir.trim_end_matches('/'))
+        .collect();
+
+    let detected: BTreeSet<&str> = packages
+        .iter()
+        .map(|&dir| dir.rsplit_once('/').map_or("", |(parent, _)| parent))
+        .filter(|parent| !packages.contains(parent))
+        .collect();
+
+    let mut roots: Vec<String> = Vec::new();
+    for root in configured.iter().map(String::as_str).chain(detected) {
+        if !root.is_empty() && !roots.iter().any(|r| r == root) {
+            roots.push(root.to_string());
+        }
     }
+    roots
 }
 
 /// Resolve a single raw import to candidate repo file paths.
@@ -82,18 +340,19 @@ pub fn resolve_import(
     let candidates = match language {
         Language::Rust => resolve_rust(raw_import, &file_index.stem),
         Language::JavaScript | Language::TypeScript => {
-            resolve_js(raw_import, importing_file, &file_index.stem)
+            resolve_js(raw_import, importing_file, file_index)
         }
-        Language::Python => resolve_python(raw_import, importing_file, &file_index.stem),
+        Language::Python => resolve_python(raw_import, importing_file, file_index),
         Language::Go => resolve_go(raw_import, file_index),
         Language::Java | Language::Kotlin => resolve_java(raw_import, &file_index.stem),
-        Language::C | Language::Cpp => {
-            resolve_c_include(raw_import, importing_file, &file_index.stem)
+        Language::C | Language::Cpp => resolve_c_include(raw_import, importing_file, file_index),
+        Language::Ruby if raw_import.starts_with(|c: char| c.is_ascii_uppercase()) => {
+            resolve_ruby_constant(raw_import, file_index)
         }
         Language::Ruby => resolve_ruby(raw_import, importing_file, &file_index.stem),
-        Language::Swift => resolve_swift(raw_import, &file_index.stem),
-        Language::Elixir => resolve_elixir(raw_import, &file_index.stem),
-        Language::Php => resolve_php(raw_import, importing_file, &file_index.stem),
+        Language::Swift => resolve_swift(raw_import, file_index),
+        Language::Elixir => resolve_elixir(raw_import, file_index),
+        Language::Php => resolve_php(raw_import, importing_file, file_index),
         Language::Scala => resolve_scala(raw_import, &file_index.stem),
         Language::R => resolve_r(raw_import, importing_file, &file_index.stem),
         Language::Shell => resolve_shell(raw_import, importing_file, &file_index.stem),
@@ -107,10 +366,229 @@ pub fn resolve_import(
         .collect()
 }
 
-/// Returns true if a path is under a vendored/generated directory.
-fn is_vendored(path: &str) -> bool {
-    path.split(['/', '\\'])
-        .any(|component| VENDORED_DIRS.contains(&component))
+/// How one raw import was resolved, for debugging wrong or missing edges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportTrace {
+    /// The import as written (`./auth`, `app.models`, `Admin::User`).
+    pub raw: String,
+    /// Resolver that handled it, e.g. `python-absolute` or `js-relative`.
+    pub resolver: &'static str,
+    /// Where that resolver looked, in order.
+    pub rule: String,
+    /// Files the import became edges to.
+    pub chosen: Vec<String>,
+    /// Other files named like the import, with why each got no edge.
+    pub rejected: Vec<(String, String)>,
+}
+
+/// Resolve each of a file's raw imports the way [`build_import_graph_with`]
+/// does, recording which resolver ran and which same-named files it passed
+/// over.
+pub fn explain_imports(
+    importing_file: &str,
+    language: Language,
+    raw_imports: &[String],
+    all_paths: &[&str],
+    options: &ResolveOptions,
+) -> Vec<ImportTrace> {
+    let non_vendored: Vec<&str> = all_paths
+        .iter()
+        .copied()
+        .filter(|p| !is_vendored(p))
+        .collect();
+    let index = build_file_index(&non_vendored, options);
+
+    raw_imports
+        .iter()
+        .map(|raw| {
+            let (resolver, rule) = resolver_rule(raw, importing_file, language, &index);
+            let chosen = resolve_import(raw, importing_file, language, &index);
+            let name = import_name(raw, language);
+            let rejected = all_paths
+                .iter()
+                .filter(|p| named_like(p, &name, language) && !chosen.iter().any(|c| c == *p))
+                .map(|p| {
+                    let reason = if *p == importing_file {
+                        "the importing file itself".to_string()
+                    } else if is_vendored(p) {
+                        "under a vendored directory, excluded from the graph".to_string()
+                    } else if chosen.is_empty() {
+                        format!("not at a path {resolver} matches")
+                    } else {
+                        format!("{resolver} matched a more specific path")
+                    };
+                    (p.to_string(), reason)
+                })
+                .collect();
+            ImportTrace {
+                raw: raw.clone(),
+                resolver,
+                rule,
+                chosen,
+                rejected,
+            }
+        })
+        .collect()
+}
+
+/// The lowercase file stem (or Go package directory) an import names.
+fn import_name(raw: &str, language: Language) -> String {
+    let last = |s: &'static str| raw.rsplit(s).next().unwrap_or(raw).to_string();
+    let name = match language {
+        Language::Python | Language::Scala => last("."),
+        Language::Java | Language::Kotlin => {
+            let path = raw.trim_end_matches(".*");
+            path.rsplit('.').next().unwrap_or(path).to_string()
+        }
+        Language::Elixir => underscore(raw.trim_end_matches('.').rsplit('.').next().unwrap_or(raw)),
+        Language::Ruby if raw.starts_with(|c: char| c.is_ascii_uppercase()) => {
+            underscore(&last("::"))
+        }
+        Language::Php if raw.contains('\\') => last("\\"),
+        Language::Rust | Language::Swift | Language::Go => last("/"),
+        _ => {
+            let segment = last("/");
+            Path::new(&segment)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(&segment)
+                .to_string()
+        }
+    };
+    name.to_lowercase()
+}
+
+/// Whether `path` has the stem `name`, or is a module/package directory so named.
+fn named_like(path: &str, name: &str, language: Language) -> bool {
+    let p = Path::new(path);
+    let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let parent = p
+        .parent()
+        .and_then(|d| d.file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or("");
+    stem.eq_ignore_ascii_case(name)
+        || (parent.eq_ignore_ascii_case(name)
+            && (language == Language::Go || matches!(stem, "mod" | "index" | "__init__")))
+}
+
+/// The resolver [`resolve_import`] picks for an import, and where it looks.
+fn resolver_rule(
+    raw: &str,
+    importing_file: &str,
+    language: Language,
+    index: &RepoIndex,
+) -> (&'static str, String) {
+    let base = Path::new(importing_file)
+        .parent()
+        .and_then(|b| b.to_str())
+        .unwrap_or("");
+    let list = |dirs: &[String]| {
+        if dirs.is_empty() {
+            "(none)".to_string()
+        } else {
+            dirs.join(", ")
+        }
+    };
+    let name = import_name(raw, language);
+    match language {
+        Language::Rust => (
+            "rust-module",
+            format!("files named `{name}.rs` or `{name}/mod.rs`"),
+        ),
+        Language::JavaScript | Language::TypeScript if raw.starts_with('.') => {
+            let target = normalize_path(&Path::new(base).join(raw).to_string_lossy());
+            (
+                "js-relative",
+                format!(
+                    "`{target}`, `{target}.<ext>`, `{target}/index.<ext>`, then files named `{name}` near it"
+                ),
+            )
+        }
+        Language::JavaScript | Language::TypeScript => ("js-bare", format!("files named `{name}`")),
+        Language::Python if raw.starts_with('.') => (
+            "python-relative",
+            format!(
+                "the module under the package of `{importing_file}`, then files named `{name}`"
+            ),
+        ),
+        Language::Python if index.python_roots.is_empty() => (
+            "python-absolute",
+            "the repository root, then files named by the last or first segment".to_string(),
+        ),
+        Language::Python => (
+            "python-absolute",
+            format!(
+                "the longest module prefix under package roots {} and the repository root",
+                list(&index.python_roots)
+            ),
+        ),
+        Language::Go => (
+            "go-package",
+            format!(
+                "files in a directory named `{name}`, narrowed by its parent, then files named `{name}`"
+            ),
+        ),
+        Language::Java | Language::Kotlin => ("jvm-class", format!("files named `{name}`")),
+        Language::C | Language::Cpp => (
+            "c-include",
+            format!(
+                "`{raw}` relative to `{base}`, include roots {}, the repository root, then files named `{name}`",
+                list(&index.include_roots)
+            ),
+        ),
+        Language::Ruby if raw.starts_with(|c: char| c.is_ascii_uppercase()) => (
+            "ruby-constant",
+            format!(
+                "the longest constant prefix as a `.rb` file under autoload roots {}",
+                list(&index.ruby_roots)
+            ),
+        ),
+        Language::Ruby => (
+            "ruby-require",
+            format!("`{raw}.rb` relative to `{base}`, then files named `{name}`"),
+        ),
+        Language::Swift if index.swift_modules.contains_key(raw) => (
+            "swift-target",
+            format!("every file of package target `{raw}`"),
+        ),
+        Language::Swift => ("swift-module", format!("files named `{name}`")),
+        Language::Elixir if !index.mix_roots.is_empty() => (
+            "elixir-mix",
+            format!(
+                "the longest module prefix as a `.ex`/`.exs` file under {}",
+                list(&index.mix_roots)
+            ),
+        ),
+        Language::Elixir => (
+            "elixir-module",
+            "files named by the last module segment, then earlier ones".to_string(),
+        ),
+        Language::Php if raw.contains('\\') => {
+            let prefixes: Vec<String> = index
+                .psr4
+                .iter()
+                .map(|(prefix, dir)| format!("{prefix} => {dir}"))
+                .collect();
+            (
+                "php-namespace",
+                format!(
+                    "PSR-4 prefixes {}, then files named `{name}`",
+                    list(&prefixes)
+                ),
+            )
+        }
+        Language::Php | Language::Shell => (
+            "relative-path",
+            format!("`{raw}` relative to `{base}`, then files named `{name}`"),
+        ),
+        Language::R if raw.contains(['/', '.']) => (
+            "relative-path",
+            format!("`{raw}` relative to `{base}`, then files named `{name}`"),
+        ),
+        Language::Scala | Language::R => ("name", format!("files named `{name}`")),
+        _ => ("none", "no resolver for this language".to_string()),
+    }
 }
 
 /// Build an ImportGraph from files with their content.
@@ -122,6 +600,24 @@ fn is_vendored(path: &str) -> bool {
 pub fn build_import_graph(
     file_imports: &[(String, Language, Vec<String>)],
     all_paths: &[&str],
+) -> ImportGraph {
+    let weighted: Vec<(String, Language, WeightedImports)> = file_imports
+        .iter()
+        .map(|(path, language, raw)| {
+            let raw = raw.iter().map(|r| (r.clone(), 1.0)).collect();
+            (path.clone(), *language, raw)
+        })
+        .collect();
+    build_import_graph_with(&weighted, all_paths, &ResolveOptions::default())
+}
+
+/// Like [`build_import_graph`], with repository-specific resolution settings
+/// and weighted imports (see [`crate::extract_weighted_imports`]). Each edge
+/// carries the weight of the import it came from.
+pub fn build_import_graph_with(
+    file_imports: &[(String, Language, WeightedImports)],
+    all_paths: &[&str],
+    options: &ResolveOptions,
 ) -> ImportGraph {
     // Filter out vendored paths before building the file index and graph
     let non_vendored: Vec<&str> = all_paths
@@ -130,7 +626,7 @@ pub fn build_import_graph(
         .filter(|p| !is_vendored(p))
         .collect();
 
-    let file_index = build_file_index(&non_vendored);
+    let file_index = build_file_index(&non_vendored, options);
     let mut graph = ImportGraph::new();
 
     // Add only non-vendored files as nodes
@@ -139,21 +635,202 @@ pub fn build_import_graph(
     }
 
     // Resolve imports and add edges (only from non-vendored files)
+    let (mut total, mut unresolved) = (0usize, 0usize);
     for (path, language, raw_imports) in file_imports {
         if is_vendored(path) {
             continue;
         }
-        for raw in raw_imports {
+        for (raw, weight) in raw_imports {
             let resolved = resolve_import(raw, path, *language, &file_index);
+            total += 1;
+            if resolved.is_empty() {
+                unresolved += 1;
+                log::trace!("{path}: unresolved import {raw:?}");
+            }
             for target in resolved {
-                graph.add_edge(path, &target);
+                graph.add_weighted_edge(path, &target, *weight);
             }
         }
     }
+    log::debug!(
+        "Resolved {} of {total} imports ({} vendored files skipped)",
+        total - unresolved,
+        all_paths.len() - non_vendored.len()
+    );
 
     graph
 }
 
+/// An import specifier that resolved to no repo file, with every file using it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnresolvedImport {
+    pub specifier: String,
+    pub language: Language,
+    pub origin: ImportOrigin,
+    /// Importing files, sorted.
+    pub files: Vec<String>,
+}
+
+/// What an unresolved import most likely refers to, judged against the
+/// dependency manifests in the repository.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOrigin {
+    /// A third-party package declared in a manifest, by its declared name.
+    Dependency(String),
+    /// Relative, a path alias, or under the project's own package name: it
+    /// should have resolved, so the miss is probably a resolution gap.
+    Internal,
+    /// Neither: the standard library, or a package used without being declared.
+    Undeclared,
+}
+
+impl ImportOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dependency(_) => "dependency",
+            Self::Internal => "internal",
+            Self::Undeclared => "undeclared",
+        }
+    }
+}
+
+/// Classify an unresolved import against the declared packages.
+fn import_origin(
+    specifier: &str,
+    language: Language,
+    options: &ResolveOptions,
+    index: &RepoIndex,
+) -> ImportOrigin {
+    let Some(ecosystem) = Ecosystem::of(language) else {
+        return ImportOrigin::Undeclared;
+    };
+    // Rust imports are only extracted from `crate::` paths and `mod` items
+    if ecosystem == Ecosystem::Cargo || specifier.starts_with('.') {
+        return ImportOrigin::Internal;
+    }
+    let declared = options
+        .packages
+        .iter()
+        .filter(|(e, _)| *e == ecosystem)
+        .map(|(_, packages)| packages);
+
+    // The name a dependency would be declared under
+    let package = match ecosystem {
+        Ecosystem::Npm if specifier.starts_with("@/") || specifier.starts_with("~/") => {
+            return ImportOrigin::Internal;
+        }
+        Ecosystem::Npm => {
+            let segments = if specifier.starts_with('@') { 2 } else { 1 };
+            specifier
+                .splitn(segments + 1, '/')
+                .take(segments)
+                .collect::<Vec<_>>()
+                .join("/")
+        }
+        Ecosystem::Python => specifier
+            .split('.')
+            .next()
+            .unwrap_or(specifier)
+            .to_lowercase(),
+        _ => specifier.to_string(),
+    };
+    let matches = |name: &String| match ecosystem {
+        // Go imports name packages inside a required module
+        Ecosystem::Go => package == *name || package.starts_with(&format!("{name}/")),
+        _ => package == *name,
+    };
+
+    let mut own = declared.clone().flat_map(|p| &p.own);
+    if own.any(matches) {
+        return ImportOrigin::Internal;
+    }
+    if let Some(name) = declared
+        .flat_map(|p| &p.dependencies)
+        .filter(|name| matches(name))
+        .max_by_key(|name| name.len())
+    {
+        return ImportOrigin::Dependency(name.clone());
+    }
+    // A top-level Python package named like a repo directory
+    if ecosystem == Ecosystem::Python && index.dir.contains_key(&package) {
+        return ImportOrigin::Internal;
+    }
+    ImportOrigin::Undeclared
+}
+
+/// Files using each declared third-party dependency, most used first, from
+/// an [`unresolved_imports`] report.
+pub fn dependency_usage(unresolved: &[UnresolvedImport]) -> Vec<(String, usize)> {
+    let mut files: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for u in unresolved {
+        if let ImportOrigin::Dependency(name) = &u.origin {
+            files
+                .entry(name)
+                .or_default()
+                .extend(u.files.iter().map(String::as_str));
+        }
+    }
+    let mut usage: Vec<(String, usize)> = files
+        .into_iter()
+        .map(|(name, files)| (name.to_string(), files.len()))
+        .collect();
+    usage.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    usage
+}
+
+/// Imports that resolve to no repo file, grouped by specifier and language,
+/// most widely used first.
+///
+/// Most are external dependencies (`react`, `os`); a specifier that looks
+/// internal (`./utils`, `app.models`) usually means a resolution gap worth a
+/// config entry. Each is classified by [`ImportOrigin`] against the manifests
+/// added by [`ResolveOptions::with_manifests`]. Vendored files are skipped,
+/// as in the graph.
+pub fn unresolved_imports(
+    file_imports: &[(String, Language, WeightedImports)],
+    all_paths: &[&str],
+    options: &ResolveOptions,
+) -> Vec<UnresolvedImport> {
+    let non_vendored: Vec<&str> = all_paths
+        .iter()
+        .copied()
+        .filter(|p| !is_vendored(p))
+        .collect();
+    let file_index = build_file_index(&non_vendored, options);
+
+    let mut groups: HashMap<(&str, Language), BTreeSet<&str>> = HashMap::new();
+    for (path, language, raw_imports) in file_imports {
+        if is_vendored(path) {
+            continue;
+        }
+        for (raw, _) in raw_imports {
+            if resolve_import(raw, path, *language, &file_index).is_empty() {
+                groups
+                    .entry((raw.as_str(), *language))
+                    .or_default()
+                    .insert(path.as_str());
+            }
+        }
+    }
+
+    let mut unresolved: Vec<UnresolvedImport> = groups
+        .into_iter()
+        .map(|((specifier, language), files)| UnresolvedImport {
+            specifier: specifier.to_string(),
+            language,
+            origin: import_origin(specifier, language, options, &file_index),
+            files: files.into_iter().map(str::to_string).collect(),
+        })
+        .collect();
+    unresolved.sort_by(|a, b| {
+        b.files
+            .len()
+            .cmp(&a.files.len())
+            .then_with(|| a.specifier.cmp(&b.specifier))
+    });
+    unresolved
+}
+
 /// Rust: match module name against file stems.
 /// e.g., `"auth"` matches `src/auth.rs` or `src/auth/mod.rs`.
 fn resolve_rust(module: &str, file_index: &HashMap<String, Vec<String>>) -> Vec<String> {
@@ -163,15 +840,24 @@ fn resolve_rust(module: &str, file_index: &HashMap<String, Vec<String>>) -> Vec<
         .unwrap_or_default()
 }
 
+/// Extensions tried, in order, for an extensionless JS/TS import.
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts"];
+
 /// JS/TS: relative paths resolve relative to importing file; bare specifiers match stems.
-fn resolve_js(
-    import_path: &str,
-    importing_file: &str,
-    file_index: &HashMap<String, Vec<String>>,
-) -> Vec<String> {
+///
+/// Relative imports are first resolved like a bundler would: the exact path,
+/// the path with each of [`JS_EXTENSIONS`], then `<dir>/index.*`. A `.js`
+/// specifier also matches its `.ts`/`.tsx` source, since TypeScript emitting
+/// ES modules imports compiled names. Stem matching is the fallback.
+fn resolve_js(import_path: &str, importing_file: &str, index: &RepoIndex) -> Vec<String> {
+    let file_index = &index.stem;
     if import_path.starts_with('.') {
         // Relative import: resolve relative to importing file's directory
         let base = Path::new(importing_file).parent().unwrap_or(Path::new(""));
+        let target = normalize_path(&base.join(import_path).to_string_lossy());
+        if let Some(found) = js_module_file(&target, index) {
+            return vec![found];
+        }
         let resolved = base.join(import_path);
 
         // Extract the stem from the resolved path
@@ -210,15 +896,86 @@ fn resolve_js(
     }
 }
 
-/// Python: relative imports resolve relative to importing file; absolute match stems.
-fn resolve_python(
-    import_path: &str,
-    importing_file: &str,
-    file_index: &HashMap<String, Vec<String>>,
-) -> Vec<String> {
+/// The file a relative JS/TS specifier (already joined and normalized) names.
+fn js_module_file(target: &str, index: &RepoIndex) -> Option<String> {
+    let exists = |p: &String| index.paths.contains(p);
+
+    let exact = target.to_string();
+    if exists(&exact) {
+        return Some(exact);
+    }
+
+    // `./foo.js` written against `foo.ts`
+    let stem = [".js", ".jsx", ".mjs", ".cjs"]
+        .iter()
+        .find_map(|ext| target.strip_suffix(ext));
+    if let Some(stem) = stem {
+        let source = ["ts", "tsx", "mts", "cts"]
+            .iter()
+            .map(|ext| format!("{stem}.{ext}"))
+            .find(exists);
+        if source.is_some() {
+            return source;
+        }
+    }
+
+    let with_ext = JS_EXTENSIONS.iter().map(|ext| format!("{target}.{ext}"));
+    let index_file = JS_EXTENSIONS.iter().map(|ext| {
+        if target.is_empty() {
+            format!("index.{ext}")
+        } else {
+            format!("{target}/index.{ext}")
+        }
+    });
+    with_ext.chain(index_file).find(exists)
+}
+
+/// Collapse `.` and `..` components of a relative `/`-separated path.
+///
+/// `..` above the start is dropped, matching how a path outside the
+/// repository can never resolve to a repo file anyway.
+fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Python: resolve dotted module paths to files.
+///
+/// Relative imports resolve against the importing file's package; absolute
+/// imports against each package root, then the repository root. The longest
+/// module prefix that exists wins, so `app.services.auth.verify` (a function)
+/// still resolves to `app/services/auth.py`.
+///
+/// When nothing matches exactly, the last or first segment is matched against
+/// file stems — but only for relative imports, or for absolute imports in repos
+/// without detectable package roots. With roots known, an unmatched absolute
+/// import is external (`os.path`, `requests.auth`).
+fn resolve_python(import_path: &str, importing_file: &str, index: &RepoIndex) -> Vec<String> {
+    let file_index = &index.stem;
     if import_path.starts_with('.') {
-        // Relative import
+        // Relative import: one dot is the current package, each extra dot goes up
         let module = import_path.trim_start_matches('.');
+        let levels = import_path.len() - module.len();
+        let mut base = Path::new(importing_file).parent();
+        for _ in 1..levels {
+            base = base.and_then(Path::parent);
+        }
+        if let Some(base) = base.and_then(|b| b.to_str()) {
+            let found = python_module_files(module, 0, &[base], index);
+            if !found.is_empty() {
+                return found;
+            }
+        }
+
         if module.is_empty() {
             // `from . import X` — try the parent package's __init__.py
             let parent = Path::new(importing_file)
@@ -239,7 +996,18 @@ fn resolve_python(
             .cloned()
             .unwrap_or_default()
     } else {
-        // Absolute import: match first/last segment against stems
+        let roots: Vec<&str> = index
+            .python_roots
+            .iter()
+            .map(String::as_str)
+            .chain([""])
+            .collect();
+        let found = python_module_files(import_path, 1, &roots, index);
+        if !found.is_empty() || !index.python_roots.is_empty() {
+            return found;
+        }
+
+        // Unknown layout: match first/last segment against stems
         let parts: Vec<&str> = import_path.split('.').collect();
         // Try last segment first (more specific), then first
         for segment in [parts.last().copied(), parts.first().copied()]
@@ -258,6 +1026,50 @@ fn resolve_python(
     }
 }
 
+/// Files for the longest prefix of a dotted module path found under any root,
+/// as `<root>/a/b.py` or `<root>/a/b/__init__.py`.
+///
+/// Prefixes shorter than `min_len` segments are not tried; with `min_len = 0`
+/// the root's own `__init__.py` matches (relative imports from a package).
+fn python_module_files(
+    module: &str,
+    min_len: usize,
+    roots: &[&str],
+    index: &RepoIndex,
+) -> Vec<String> {
+    let parts: Vec<&str> = if module.is_empty() {
+        Vec::new()
+    } else {
+        module.split('.').collect()
+    };
+    for len in (min_len..=parts.len()).rev() {
+        let rel = parts[..len].join("/");
+        let mut found = Vec::new();
+        for root in roots {
+            let dir = match (root.is_empty(), rel.is_empty()) {
+                (true, _) => rel.clone(),
+                (false, true) => root.to_string(),
+                (false, false) => format!("{root}/{rel}"),
+            };
+            let init = if dir.is_empty() {
+                "__init__.py".to_string()
+            } else {
+                format!("{dir}/__init__.py")
+            };
+            let candidates = [(!rel.is_empty()).then(|| format!("{dir}.py")), Some(init)];
+            for candidate in candidates.into_iter().flatten() {
+                if index.paths.contains(&candidate) && !found.contains(&candidate) {
+                    found.push(candidate);
+                }
+            }
+        }
+        if !found.is_empty() {
+            return found;
+        }
+    }
+    Vec::new()
+}
+
 /// Go: resolve by matching import path segments against directory structure.
 ///
 /// Go imports reference packages (directories), not files. `"k8s.io/api/core/v1"`
@@ -318,23 +1130,22 @@ fn resolve_java(import_path: &str, file_index: &HashMap<String, Vec<String>>) ->
 /// C/C++: resolve `#include "header.h"` paths.
 ///
 /// Quoted includes are project-local. Resolve relative to the importing file's
-/// directory, then fall back to stem matching.
-fn resolve_c_include(
-    include_path: &str,
-    importing_file: &str,
-    file_index: &HashMap<String, Vec<String>>,
-) -> Vec<String> {
-    // Try resolving relative to the importing file's directory
-    let base = Path::new(importing_file).parent().unwrap_or(Path::new(""));
-    let resolved = base.join(include_path);
-    let resolved_str = resolved.to_string_lossy();
-
-    // Check if the resolved path matches any known file exactly
-    for files in file_index.values() {
-        for f in files {
-            if f == resolved_str.as_ref() {
-                return vec![f.clone()];
-            }
+/// directory, then each include root, then the repository root, as a compiler
+/// searches `-I` directories. Otherwise fall back to stem matching, narrowed to
+/// files whose path ends with the include path when it names directories
+/// (`topo/parser.h` prefers `.../topo/parser.h` over other `parser.h` files).
+fn resolve_c_include(include_path: &str, importing_file: &str, index: &RepoIndex) -> Vec<String> {
+    let base = Path::new(importing_file)
+        .parent()
+        .and_then(|b| b.to_str())
+        .unwrap_or("");
+    let search = std::iter::once(base)
+        .chain(index.include_roots.iter().map(String::as_str))
+        .chain([""]);
+    for dir in search {
+        let candidate = normalize_path(&format!("{dir}/{include_path}"));
+        if index.paths.contains(&candidate) {
+            return vec![candidate];
         }
     }
 
@@ -343,10 +1154,25 @@ fn resolve_c_include(
         .file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or("");
-    file_index
+    let candidates = index
+        .stem
         .get(&stem.to_lowercase())
         .cloned()
-        .unwrap_or_default()
+        .unwrap_or_default();
+    if !include_path.contains('/') {
+        return candidates;
+    }
+    let suffix = format!("/{}", normalize_path(include_path));
+    let narrowed: Vec<String> = candidates
+        .iter()
+        .filter(|c| c.ends_with(&suffix))
+        .cloned()
+        .collect();
+    if narrowed.is_empty() {
+        candidates
+    } else {
+        narrowed
+    }
 }
 
 /// Ruby: resolve `require` and `require_relative`.
@@ -390,21 +1216,95 @@ fn resolve_ruby(
     file_index.get(&stem_lower).cloned().unwrap_or_default()
 }
 
-/// Swift: match module name against file stems.
-fn resolve_swift(module: &str, file_index: &HashMap<String, Vec<String>>) -> Vec<String> {
-    file_index
+/// Ruby constants: map `Admin::UsersController` to `admin/users_controller.rb`
+/// under each autoload root, the way Zeitwerk does.
+///
+/// The longest constant prefix with a file wins, so `User::ROLES` resolves to
+/// the `User` model. Constants without a file (core classes, gems) resolve to
+/// nothing — there is no stem fallback, since most constants are external.
+fn resolve_ruby_constant(constant: &str, index: &RepoIndex) -> Vec<String> {
+    let segments: Vec<String> = constant.split("::").map(underscore).collect();
+    for len in (1..=segments.len()).rev() {
+        let rel = segments[..len].join("/");
+        let found: Vec<String> = index
+            .ruby_roots
+            .iter()
+            .map(|root| format!("{root}/{rel}.rb"))
+            .filter(|candidate| index.paths.contains(candidate))
+            .collect();
+        if !found.is_empty() {
+            return found;
+        }
+    }
+    Vec::new()
+}
+
+/// ActiveSupport's (and Elixir's `Macro.underscore`) conversion of one
+/// module segment:
+/// `UsersController` → `users_controller`, `HTMLParser` → `html_parser`.
+fn underscore(segment: &str) -> String {
+    let chars: Vec<char> = segment.chars().collect();
+    let mut out = String::with_capacity(segment.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// Swift: a module defined by a package target resolves to all of the
+/// target's files; otherwise match the module name against file stems.
+fn resolve_swift(module: &str, index: &RepoIndex) -> Vec<String> {
+    if let Some(files) = index.swift_modules.get(module) {
+        return files.clone();
+    }
+    index
+        .stem
         .get(&module.to_lowercase())
         .cloned()
         .unwrap_or_default()
 }
 
-/// Elixir: match last module segment against file stems.
+/// Elixir: map module paths to files by mix conventions, else match stems.
 ///
-/// `MyApp.Auth.Handler` → try "Handler", then "Auth".
-fn resolve_elixir(module_path: &str, file_index: &HashMap<String, Vec<String>>) -> Vec<String> {
+/// In a mix project `MyApp.Accounts.User` lives at
+/// `lib/my_app/accounts/user.ex`; the longest module prefix with a file wins.
+/// Without any `mix.exs`, the last module segment (then earlier ones) is
+/// matched against file stems: `MyApp.Auth.Handler` → try "Handler", then
+/// "Auth". With mix projects present, unmatched modules (`Enum`, `Ecto.Schema`)
+/// are external.
+fn resolve_elixir(module_path: &str, index: &RepoIndex) -> Vec<String> {
+    let module_path = module_path.trim_end_matches('.');
+    if !index.mix_roots.is_empty() {
+        let segments: Vec<String> = module_path.split('.').map(underscore).collect();
+        for len in (1..=segments.len()).rev() {
+            let rel = segments[..len].join("/");
+            let found: Vec<String> = index
+                .mix_roots
+                .iter()
+                .flat_map(|root| [format!("{root}/{rel}.ex"), format!("{root}/{rel}.exs")])
+                .filter(|candidate| index.paths.contains(candidate))
+                .collect();
+            if !found.is_empty() {
+                return found;
+            }
+        }
+        return Vec::new();
+    }
+
     // Elixir modules are like MyApp.Auth.Handler — try last segment first
     for segment in module_path.rsplit('.') {
-        let candidates = file_index
+        let candidates = index
+            .stem
             .get(&segment.to_lowercase())
             .cloned()
             .unwrap_or_default();
@@ -416,12 +1316,28 @@ fn resolve_elixir(module_path: &str, file_index: &HashMap<String, Vec<String>>)
 }
 
 /// PHP: resolve `use` namespaces and `require`/`include` paths.
-fn resolve_php(
-    import_path: &str,
-    importing_file: &str,
-    file_index: &HashMap<String, Vec<String>>,
-) -> Vec<String> {
+///
+/// Namespaces go through the PSR-4 prefixes first (`App\Auth\Handler` with
+/// `App\ → app/` is `app/Auth/Handler.php`), then last-segment stem matching.
+fn resolve_php(import_path: &str, importing_file: &str, index: &RepoIndex) -> Vec<String> {
+    let file_index = &index.stem;
     if import_path.contains('\\') {
+        let class = import_path.trim_start_matches('\\');
+        for (prefix, dir) in &index.psr4 {
+            let Some(rest) = class.strip_prefix(prefix.as_str()) else {
+                continue;
+            };
+            let rel = format!("{}.php", rest.replace('\\', "/"));
+            let candidate = if dir.is_empty() {
+                rel
+            } else {
+                format!("{dir}/{rel}")
+            };
+            if index.paths.contains(&candidate) {
+                return vec![candidate];
+            }
+        }
+
         // Namespace import: App\Auth\Handler → match last segment "Handler"
         let segment = import_path.rsplit('\\').next().unwrap_or(import_path);
         file_index
@@ -543,7 +1459,7 @@ mod tests {
             "src/handler.rs",
             "src/lib.rs",
         ];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         assert!(idx.stem["auth"].contains(&"src/auth.rs".to_string()));
         assert!(idx.stem["auth"].contains(&"src/auth/mod.rs".to_string()));
@@ -554,7 +1470,7 @@ mod tests {
     #[test]
     fn build_file_index_mod_indexes_parent() {
         let paths = vec!["src/auth/mod.rs"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         // "mod" stem entry
         assert!(idx.stem["mod"].contains(&"src/auth/mod.rs".to_string()));
@@ -565,7 +1481,7 @@ mod tests {
     #[test]
     fn build_file_index_js_index() {
         let paths = vec!["src/components/index.ts"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         assert!(idx.stem["index"].contains(&"src/components/index.ts".to_string()));
         assert!(idx.stem["components"].contains(&"src/components/index.ts".to_string()));
@@ -574,7 +1490,7 @@ mod tests {
     #[test]
     fn resolve_rust_module() {
         let paths = vec!["src/auth.rs", "src/db.rs"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("auth", "src/main.rs", Language::Rust, &idx);
         assert_eq!(result, vec!["src/auth.rs".to_string()]);
@@ -583,16 +1499,71 @@ mod tests {
     #[test]
     fn resolve_js_relative() {
         let paths = vec!["src/utils.ts", "src/handler.ts"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("./utils", "src/handler.ts", Language::TypeScript, &idx);
         assert!(result.contains(&"src/utils.ts".to_string()));
     }
 
+    #[test]
+    fn resolve_js_directory_index() {
+        let paths = vec![
+            "src/App.tsx",
+            "src/index.ts",
+            "src/components/index.ts",
+            "src/components/Button.tsx",
+            "src/utils/index.js",
+            "lib/utils.ts",
+        ];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+
+        let result = resolve_import("./components", "src/App.tsx", Language::TypeScript, &idx);
+        assert_eq!(result, vec!["src/components/index.ts"]);
+
+        let result = resolve_import("./utils", "src/App.tsx", Language::TypeScript, &idx);
+        assert_eq!(result, vec!["src/utils/index.js"]);
+
+        let result = resolve_import(
+            "../../lib/utils",
+            "src/components/Button.tsx",
+            Language::TypeScript,
+            &idx,
+        );
+        assert_eq!(result, vec!["lib/utils.ts"]);
+
+        let result = resolve_import(
+            "..",
+            "src/components/Button.tsx",
+            Language::TypeScript,
+            &idx,
+        );
+        assert_eq!(result, vec!["src/index.ts"]);
+    }
+
+    #[test]
+    fn resolve_js_extensionful_specifier() {
+        let paths = vec!["src/server.ts", "src/db.ts", "src/legacy.js"];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+
+        // TypeScript ESM imports the compiled `.js` name
+        let result = resolve_import("./db.js", "src/server.ts", Language::TypeScript, &idx);
+        assert_eq!(result, vec!["src/db.ts"]);
+
+        let result = resolve_import("./legacy.js", "src/server.ts", Language::TypeScript, &idx);
+        assert_eq!(result, vec!["src/legacy.js"]);
+    }
+
+    #[test]
+    fn normalize_path_collapses_dots() {
+        assert_eq!(normalize_path("src/./a/../b/c"), "src/b/c");
+        assert_eq!(normalize_path("../x"), "x");
+        assert_eq!(normalize_path("src/.."), "");
+    }
+
     #[test]
     fn resolve_js_bare_specifier_no_match() {
         let paths = vec!["src/handler.ts"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         // "react" has no matching file — external dependency
         let result = resolve_import("react", "src/handler.ts", Language::JavaScript, &idx);
@@ -602,12 +1573,87 @@ mod tests {
     #[test]
     fn resolve_python_relative() {
         let paths = vec!["src/utils.py", "src/main.py"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import(".utils", "src/main.py", Language::Python, &idx);
         assert!(result.contains(&"src/utils.py".to_string()));
     }
 
+    #[test]
+    fn resolve_python_absolute_uses_package_roots() {
+        let paths = vec![
+            "app/__init__.py",
+            "app/services/__init__.py",
+            "app/services/auth.py",
+            "app/api/auth.py",
+            "scripts/auth.py",
+        ];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+        assert_eq!(idx.python_roots, Vec::<String>::new());
+
+        let result = resolve_import("app.services.auth", "app/main.py", Language::Python, &idx);
+        assert_eq!(result, vec!["app/services/auth.py"]);
+
+        // Imported names fall back to the longest module prefix
+        let result = resolve_import(
+            "app.services.auth.verify",
+            "app/main.py",
+            Language::Python,
+            &idx,
+        );
+        assert_eq!(result, vec!["app/services/auth.py"]);
+
+        let result = resolve_import("app.services", "app/main.py", Language::Python, &idx);
+        assert_eq!(result, vec!["app/services/__init__.py"]);
+    }
+
+    #[test]
+    fn resolve_python_src_layout() {
+        let paths = vec![
+            "src/app/__init__.py",
+            "src/app/auth.py",
+            "tests/test_auth.py",
+        ];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+        assert_eq!(idx.python_roots, vec!["src"]);
+
+        let result = resolve_import("app.auth", "tests/test_auth.py", Language::Python, &idx);
+        assert_eq!(result, vec!["src/app/auth.py"]);
+
+        // With package roots known, unmatched absolute imports are external
+        let result = resolve_import("requests.auth", "src/app/auth.py", Language::Python, &idx);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn resolve_python_configured_pythonpath() {
+        let paths = vec!["services/api/handlers/users.py", "lib/handlers/users.py"];
+        let options = ResolveOptions::new().python_paths(&["./services/api/"]);
+        let idx = build_file_index(&paths, &options);
+        assert_eq!(idx.python_roots, vec!["services/api"]);
+
+        let result = resolve_import("handlers.users", "main.py", Language::Python, &idx);
+        assert_eq!(result, vec!["services/api/handlers/users.py"]);
+    }
+
+    #[test]
+    fn resolve_python_relative_parent_package() {
+        let paths = vec![
+            "app/__init__.py",
+            "app/models.py",
+            "app/api/__init__.py",
+            "app/api/routes.py",
+            "other/models.py",
+        ];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+
+        let result = resolve_import("..models", "app/api/routes.py", Language::Python, &idx);
+        assert_eq!(result, vec!["app/models.py"]);
+
+        let result = resolve_import(".", "app/api/routes.py", Language::Python, &idx);
+        assert_eq!(result, vec!["app/api/__init__.py"]);
+    }
+
     #[test]
     fn resolve_go_directory_based() {
         // Go imports reference packages (directories), not files
@@ -616,7 +1662,7 @@ mod tests {
             "pkg/http/server.go",
             "internal/auth/auth.go",
         ];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         // "myapp/pkg/http" → last segment "http" matches directory "pkg/http/"
         let result = resolve_import("myapp/pkg/http", "cmd/main.go", Language::Go, &idx);
@@ -638,7 +1684,7 @@ mod tests {
             "testdata/config/after/v1.yaml",
             "testdata/openapi/v3/api/v1.json",
         ];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import(
             "k8s.io/api/core/v1",
@@ -658,7 +1704,7 @@ mod tests {
     fn resolve_go_multi_segment_disambiguation() {
         // Two packages both named "v1" but in different parent dirs
         let paths = vec!["api/core/v1/types.go", "api/apps/v1/deployment.go"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         // "k8s.io/api/core/v1" → penultimate "core" narrows to core/v1/
         let result = resolve_import("k8s.io/api/core/v1", "cmd/main.go", Language::Go, &idx);
@@ -675,7 +1721,7 @@ mod tests {
     fn resolve_go_fallback_to_stem() {
         // When there's no directory match, fall back to stem matching
         let paths = vec!["pkg/handler.go"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("myapp/handler", "cmd/main.go", Language::Go, &idx);
         assert!(result.contains(&"pkg/handler.go".to_string()));
@@ -684,7 +1730,7 @@ mod tests {
     #[test]
     fn resolve_java_qualified() {
         let paths = vec!["src/main/java/AuthService.java"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import(
             "com.example.auth.AuthService",
@@ -698,7 +1744,7 @@ mod tests {
     #[test]
     fn resolve_java_wildcard() {
         let paths = vec!["src/main/java/Utils.java"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import(
             "com.example.utils.*",
@@ -712,7 +1758,7 @@ mod tests {
     #[test]
     fn resolve_c_include_relative() {
         let paths = vec!["src/auth.h", "src/auth.c", "src/utils/helpers.h"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         // #include "auth.h" from src/main.c → resolves to src/auth.h
         let result = resolve_import("auth.h", "src/main.c", Language::C, &idx);
@@ -722,7 +1768,7 @@ mod tests {
     #[test]
     fn resolve_c_include_subdirectory() {
         let paths = vec!["src/utils/helpers.h", "src/main.c"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         // #include "utils/helpers.h" from src/main.c
         let result = resolve_import("utils/helpers.h", "src/main.c", Language::C, &idx);
@@ -732,17 +1778,56 @@ mod tests {
     #[test]
     fn resolve_cpp_include_stem_fallback() {
         let paths = vec!["include/myclass.hpp", "src/main.cpp"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         // When relative path doesn't match, fall back to stem
         let result = resolve_import("myclass.hpp", "src/main.cpp", Language::Cpp, &idx);
         assert!(result.contains(&"include/myclass.hpp".to_string()));
     }
 
+    #[test]
+    fn resolve_c_include_roots() {
+        let paths = vec![
+            "include/topo/parser.h",
+            "src/parser/parser.h",
+            "tools/gen/parser.h",
+            "src/main.c",
+        ];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+        assert_eq!(idx.include_roots, vec!["include"]);
+
+        let result = resolve_import("topo/parser.h", "src/main.c", Language::C, &idx);
+        assert_eq!(result, vec!["include/topo/parser.h"]);
+    }
+
+    #[test]
+    fn resolve_c_include_configured_dirs() {
+        let paths = vec!["src/net/socket.h", "lib/net/socket.h", "app/main.cpp"];
+        let options = ResolveOptions::new().include_dirs(&["src/"]);
+        let idx = build_file_index(&paths, &options);
+
+        let result = resolve_import("net/socket.h", "app/main.cpp", Language::Cpp, &idx);
+        assert_eq!(result, vec!["src/net/socket.h"]);
+
+        // Without the root, both headers match by stem and path suffix
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+        let result = resolve_import("net/socket.h", "app/main.cpp", Language::Cpp, &idx);
+        assert_eq!(result.len(), 2);
+    }
+
+    #[test]
+    fn resolve_c_include_narrows_stem_fallback() {
+        let paths = vec!["vendor_src/a/util.h", "modules/b/util.h", "main.c"];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+
+        let result = resolve_import("b/util.h", "main.c", Language::C, &idx);
+        assert_eq!(result, vec!["modules/b/util.h"]);
+    }
+
     #[test]
     fn resolve_ruby_require() {
         let paths = vec!["lib/auth.rb", "lib/handler.rb"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("auth", "lib/handler.rb", Language::Ruby, &idx);
         assert!(result.contains(&"lib/auth.rb".to_string()));
@@ -751,26 +1836,99 @@ mod tests {
     #[test]
     fn resolve_ruby_require_relative() {
         let paths = vec!["lib/utils.rb", "lib/main.rb"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("./utils", "lib/main.rb", Language::Ruby, &idx);
         assert!(result.contains(&"lib/utils.rb".to_string()));
     }
 
+    #[test]
+    fn resolve_ruby_autoloaded_constants() {
+        let paths = vec![
+            "app/controllers/admin/users_controller.rb",
+            "app/controllers/application_controller.rb",
+            "app/models/user.rb",
+            "app/models/concerns/searchable.rb",
+            "app/views/users/index.html.erb",
+            "lib/user.rb",
+        ];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+        assert_eq!(
+            idx.ruby_roots,
+            vec!["app/controllers", "app/models", "app/models/concerns"]
+        );
+
+        let from = "app/controllers/admin/users_controller.rb";
+        let result = resolve_import("ApplicationController", from, Language::Ruby, &idx);
+        assert_eq!(result, vec!["app/controllers/application_controller.rb"]);
+
+        let result = resolve_import("User::ROLES", from, Language::Ruby, &idx);
+        assert_eq!(result, vec!["app/models/user.rb"]);
+
+        let result = resolve_import("Searchable", "app/models/user.rb", Language::Ruby, &idx);
+        assert_eq!(result, vec!["app/models/concerns/searchable.rb"]);
+
+        let result = resolve_import(
+            "Admin::UsersController",
+            "app/models/user.rb",
+            Language::Ruby,
+            &idx,
+        );
+        assert_eq!(result, vec!["app/controllers/admin/users_controller.rb"]);
+
+        // Core classes have no file
+        assert!(resolve_import("Hash", from, Language::Ruby, &idx).is_empty());
+    }
+
+    #[test]
+    fn underscore_matches_active_support() {
+        assert_eq!(underscore("UsersController"), "users_controller");
+        assert_eq!(underscore("HTMLParser"), "html_parser");
+        assert_eq!(underscore("OAuth2Client"), "o_auth2_client");
+        assert_eq!(underscore("User"), "user");
+    }
+
     #[test]
     fn resolve_swift_module() {
         let paths = vec!["Sources/Auth/Auth.swift", "Sources/App/App.swift"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         // Swift imports are module names, matched against stems
         let result = resolve_import("Auth", "Sources/App/App.swift", Language::Swift, &idx);
         assert!(result.contains(&"Sources/Auth/Auth.swift".to_string()));
     }
 
+    #[test]
+    fn resolve_swift_package_target() {
+        let paths = vec![
+            "Sources/MyFeatureKit/Feature.swift",
+            "Sources/MyFeatureKit/Views/FeatureView.swift",
+            "Sources/App/MyFeatureKit.swift",
+            "Sources/App/main.swift",
+        ];
+        let options = ResolveOptions::new().swift_target("MyFeatureKit", "Sources/MyFeatureKit");
+        let idx = build_file_index(&paths, &options);
+
+        let mut result = resolve_import(
+            "MyFeatureKit",
+            "Sources/App/main.swift",
+            Language::Swift,
+            &idx,
+        );
+        result.sort();
+        assert_eq!(
+            result,
+            vec![
+                "Sources/MyFeatureKit/Feature.swift",
+                "Sources/MyFeatureKit/Views/FeatureView.swift",
+            ]
+        );
+    }
+
     #[test]
     fn resolve_kotlin_import() {
         let paths = vec!["src/main/kotlin/AuthService.kt"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import(
             "com.example.auth.AuthService",
@@ -784,25 +1942,105 @@ mod tests {
     #[test]
     fn resolve_elixir_module() {
         let paths = vec!["lib/auth/handler.ex", "lib/utils.ex"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("MyApp.Auth.Handler", "lib/app.ex", Language::Elixir, &idx);
         assert!(result.contains(&"lib/auth/handler.ex".to_string()));
     }
 
+    #[test]
+    fn resolve_elixir_mix_layout() {
+        let paths = vec![
+            "mix.exs",
+            "lib/my_app.ex",
+            "lib/my_app/accounts/user.ex",
+            "lib/my_app/billing/user.ex",
+            "apps/web/mix.exs",
+            "apps/web/lib/web/router.ex",
+            "test/support/fixtures.ex",
+        ];
+        let idx = build_file_index(&paths, &ResolveOptions::default());
+        assert_eq!(
+            idx.mix_roots,
+            vec![
+                "apps/web/lib",
+                "apps/web/test/support",
+                "lib",
+                "test/support"
+            ]
+        );
+
+        let from = "lib/my_app.ex";
+        let result = resolve_import("MyApp.Accounts.User", from, Language::Elixir, &idx);
+        assert_eq!(result, vec!["lib/my_app/accounts/user.ex"]);
+
+        let result = resolve_import("Web.Router", from, Language::Elixir, &idx);
+        assert_eq!(result, vec!["apps/web/lib/web/router.ex"]);
+
+        // Longest prefix: MyApp.Missing falls back to the MyApp module
+        let result = resolve_import("MyApp.Missing", "lib/x.ex", Language::Elixir, &idx);
+        assert_eq!(result, vec!["lib/my_app.ex"]);
+
+        // Modules outside the project are external
+        assert!(resolve_import("Ecto.Schema", from, Language::Elixir, &idx).is_empty());
+    }
+
     #[test]
     fn resolve_php_namespace() {
         let paths = vec!["src/Auth/Handler.php", "src/App.php"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import(r"App\Auth\Handler", "src/App.php", Language::Php, &idx);
         assert!(result.contains(&"src/Auth/Handler.php".to_string()));
     }
 
+    #[test]
+    fn resolve_php_psr4() {
+        let paths = vec![
+            "app/Http/Controllers/UserController.php",
+            "app/Models/User.php",
+            "database/factories/User.php",
+            "packages/billing/src/Invoice.php",
+        ];
+        let options = ResolveOptions::new()
+            .psr4(r"App\", "app/")
+            .psr4(r"Acme\Billing\", "packages/billing/src");
+        let idx = build_file_index(&paths, &options);
+
+        let result = resolve_import(
+            r"App\Models\User",
+            "app/Http/Controllers/UserController.php",
+            Language::Php,
+            &idx,
+        );
+        assert_eq!(result, vec!["app/Models/User.php"]);
+
+        let result = resolve_import(
+            r"\Acme\Billing\Invoice",
+            "app/Models/User.php",
+            Language::Php,
+            &idx,
+        );
+        assert_eq!(result, vec!["packages/billing/src/Invoice.php"]);
+    }
+
+    #[test]
+    fn manifests_add_composer_psr4() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("composer.json"),
+            r#"{"autoload": {"psr-4": {"App\\": "app/"}}}"#,
+        )
+        .unwrap();
+        let paths = vec!["composer.json", "vendor/foo/composer.json"];
+        let options = ResolveOptions::new().with_manifests(dir.path(), &paths);
+        assert_eq!(options, ResolveOptions::new().psr4(r"App\", "app"));
+    }
+
     #[test]
     fn resolve_php_require() {
         let paths = vec!["src/config.php", "src/main.php"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("config.php", "src/main.php", Language::Php, &idx);
         assert!(result.contains(&"src/config.php".to_string()));
@@ -811,7 +2049,7 @@ mod tests {
     #[test]
     fn resolve_scala_import() {
         let paths = vec!["src/main/scala/Handler.scala"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import(
             "com.example.auth.Handler",
@@ -825,7 +2063,7 @@ mod tests {
     #[test]
     fn resolve_r_source() {
         let paths = vec!["R/utils.R", "R/main.R"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("utils.R", "R/main.R", Language::R, &idx);
         assert!(result.contains(&"R/utils.R".to_string()));
@@ -834,7 +2072,7 @@ mod tests {
     #[test]
     fn resolve_shell_source() {
         let paths = vec!["lib/utils.sh", "bin/run.sh"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("../lib/utils.sh", "bin/run.sh", Language::Shell, &idx);
         assert!(result.contains(&"lib/utils.sh".to_string()));
@@ -843,12 +2081,155 @@ mod tests {
     #[test]
     fn resolve_filters_self_import() {
         let paths = vec!["src/auth.rs"];
-        let idx = build_file_index(&paths);
+        let idx = build_file_index(&paths, &ResolveOptions::default());
 
         let result = resolve_import("auth", "src/auth.rs", Language::Rust, &idx);
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn explain_imports_reports_rejected_candidates() {
+        let paths = [
+            "app/main.py",
+            "app/services/auth.py",
+            "app/__init__.py",
+            "legacy/auth.py",
+            "vendor/lib/auth.py",
+        ];
+        let traces = explain_imports(
+            "app/main.py",
+            Language::Python,
+            &["app.services.auth".to_string(), "requests".to_string()],
+            &paths,
+            &ResolveOptions::default(),
+        );
+
+        let auth = &traces[0];
+        assert_eq!(auth.resolver, "python-absolute");
+        assert_eq!(auth.chosen, vec!["app/services/auth.py"]);
+        let rejected: Vec<&str> = auth.rejected.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(rejected, vec!["legacy/auth.py", "vendor/lib/auth.py"]);
+        assert!(auth.rejected[1].1.contains("vendored"));
+
+        assert!(traces[1].chosen.is_empty());
+        assert!(traces[1].rejected.is_empty());
+    }
+
+    #[test]
+    fn explain_imports_names_resolver_per_form() {
+        let paths = ["src/app.ts", "src/auth/index.ts"];
+        let traces = explain_imports(
+            "src/app.ts",
+            Language::TypeScript,
+            &["./auth".to_string(), "react".to_string()],
+            &paths,
+            &ResolveOptions::default(),
+        );
+        assert_eq!(traces[0].resolver, "js-relative");
+        assert!(traces[0].rule.contains("`src/auth`"));
+        assert_eq!(traces[0].chosen, vec!["src/auth/index.ts"]);
+        assert_eq!(traces[1].resolver, "js-bare");
+    }
+
+    #[test]
+    fn unresolved_imports_grouped_by_specifier() {
+        let imports = vec![
+            (
+                "src/a.ts".to_string(),
+                Language::TypeScript,
+                vec![("react".to_string(), 1.0), ("./b".to_string(), 1.0)],
+            ),
+            (
+                "src/c.ts".to_string(),
+                Language::TypeScript,
+                vec![("react".to_string(), 1.0), ("./missing".to_string(), 1.0)],
+            ),
+            (
+                "vendor/x.ts".to_string(),
+                Language::TypeScript,
+                vec![("lodash".to_string(), 1.0)],
+            ),
+        ];
+        let paths = ["src/a.ts", "src/b.ts", "src/c.ts", "vendor/x.ts"];
+        let unresolved = unresolved_imports(&imports, &paths, &ResolveOptions::default());
+
+        assert_eq!(unresolved.len(), 2);
+        assert_eq!(unresolved[0].specifier, "react");
+        assert_eq!(unresolved[0].files, vec!["src/a.ts", "src/c.ts"]);
+        assert_eq!(unresolved[1].specifier, "./missing");
+        assert_eq!(unresolved[1].files, vec!["src/c.ts"]);
+    }
+
+    #[test]
+    fn unresolved_imports_classified_by_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"name": "@acme/web", "dependencies": {"react": "1", "@tanstack/query": "5"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("go.mod"),
+            "module github.com/acme/api\nrequire github.com/google/uuid v1.6.0\n",
+        )
+        .unwrap();
+        let paths = ["package.json", "go.mod", "src/app.ts", "cmd/main.go"];
+        let options = ResolveOptions::new().with_manifests(dir.path(), &paths);
+        let ts = |s: &str| (s.to_string(), 1.0);
+        let imports = vec![
+            (
+                "src/app.ts".to_string(),
+                Language::TypeScript,
+                vec![
+                    ts("react"),
+                    ts("@tanstack/query/devtools"),
+                    ts("@/components/Button"),
+                    ts("./missing"),
+                    ts("lodash"),
+                ],
+            ),
+            (
+                "cmd/main.go".to_string(),
+                Language::Go,
+                vec![
+                    ts("github.com/google/uuid"),
+                    ts("github.com/acme/api/internal/gone"),
+                    ts("fmt"),
+                ],
+            ),
+        ];
+
+        let unresolved = unresolved_imports(&imports, &paths, &options);
+        let origin = |spec: &str| {
+            unresolved
+                .iter()
+                .find(|u| u.specifier == spec)
+                .map(|u| u.origin.clone())
+                .unwrap()
+        };
+        assert_eq!(origin("react"), ImportOrigin::Dependency("react".into()));
+        assert_eq!(
+            origin("@tanstack/query/devtools"),
+            ImportOrigin::Dependency("@tanstack/query".into())
+        );
+        assert_eq!(origin("@/components/Button"), ImportOrigin::Internal);
+        assert_eq!(origin("./missing"), ImportOrigin::Internal);
+        assert_eq!(origin("lodash"), ImportOrigin::Undeclared);
+        assert_eq!(
+            origin("github.com/google/uuid"),
+            ImportOrigin::Dependency("github.com/google/uuid".into())
+        );
+        assert_eq!(
+            origin("github.com/acme/api/internal/gone"),
+            ImportOrigin::Internal
+        );
+        assert_eq!(origin("fmt"), ImportOrigin::Undeclared);
+
+        let usage = dependency_usage(&unresolved);
+        assert_eq!(usage.len(), 3);
+        assert!(usage.iter().all(|(_, files)| *files == 1));
+    }
+
     #[test]
     fn build_import_graph_basic() {
         let all_paths = vec!["src/main.rs", "src/auth.rs", "src/utils.rs"];
@@ -926,16 +2307,6 @@ mod tests {
         assert!(!scores.contains_key("vendor/github.com/lib/strings.go"));
     }
 
-    #[test]
-    fn is_vendored_detects_vendor_dirs() {
-        assert!(is_vendored("vendor/github.com/lib/foo.go"));
-        assert!(is_vendored("node_modules/react/index.js"));
-        assert!(is_vendored("third_party/proto/types.go"));
-        assert!(!is_vendored("src/vendor_utils.go"));
-        assert!(!is_vendored("pkg/handler.go"));
-        assert!(!is_vendored("cmd/main.go"));
-    }
-
     #[test]
     fn build_import_graph_diamond() {
         // main → auth, main → db, auth → utils, db → utils
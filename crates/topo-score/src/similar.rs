@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use topo_core::{DeepIndex, FileEntry};
+
+/// Hash functions in a MinHash signature. The Jaccard estimate's standard
+/// error is about `1 / sqrt(SIGNATURE_HASHES)`, here 0.125.
+pub const SIGNATURE_HASHES: usize = 64;
+
+/// Tokens per shingle: long enough that shared boilerplate (`} else {`)
+/// doesn't make unrelated files look alike.
+const SHINGLE_TOKENS: usize = 5;
+
+/// Estimated Jaccard overlap from which two files count as near-duplicates.
+pub const NEAR_DUPLICATE_OVERLAP: f64 = 0.7;
+
+/// MinHash signature of `content`: for each of [`SIGNATURE_HASHES`] hash
+/// functions, the smallest hash over the content's shingles of
+/// [`SHINGLE_TOKENS`] consecutive tokens. Empty when `content` has no
+/// tokens.
+pub fn minhash_signature(content: &str) -> Vec<u32> {
+    let tokens: Vec<&str> = content
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|t| !t.is_empty())
+        .collect();
+    if tokens.is_empty() {
+        return Vec::new();
+    }
+    let mut signature = vec![u32::MAX; SIGNATURE_HASHES];
+    for shingle in tokens.windows(SHINGLE_TOKENS.min(tokens.len())) {
+        let base = fnv1a(shingle);
+        for (i, min) in signature.iter_mut().enumerate() {
+            let hash = (mix(base ^ (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)) >> 32) as u32;
+            *min = (*min).min(hash);
+        }
+    }
+    signature
+}
+
+/// Share of matching slots in two MinHash signatures: an estimate of the
+/// Jaccard overlap of their shingle sets.
+pub fn estimated_jaccard(a: &[u32], b: &[u32]) -> f64 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let matching = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    matching as f64 / a.len() as f64
+}
+
+/// 64-bit FNV-1a over the tokens of a shingle, NUL-separated. Stable
+/// across builds, unlike `std`'s hasher, since signatures are stored.
+fn fnv1a(tokens: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for token in tokens {
+        for byte in token.bytes().chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// The SplitMix64 finalizer, deriving independent hash functions from one.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// A file similar to the target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Similar {
+    pub path: String,
+    /// Cosine similarity of the two files' TF-IDF term vectors.
+    pub similarity: f64,
+    /// Estimated Jaccard overlap of their token shingles.
+    pub overlap: f64,
+}
+
+impl Similar {
+    /// Whether the two files are largely the same text.
+    pub fn is_near_duplicate(&self) -> bool {
+        self.overlap >= NEAR_DUPLICATE_OVERLAP
+    }
+}
+
+/// Finds files in the deep index similar to a given one.
+///
+/// Two measures are reported: the cosine similarity of TF-IDF term vectors,
+/// which also catches parallel implementations that share vocabulary but
+/// not text, and the shingle overlap estimated from MinHash signatures,
+/// which singles out copy-paste.
+pub struct SimilarityScorer<'a> {
+    index: &'a DeepIndex,
+}
+
+impl<'a> SimilarityScorer<'a> {
+    pub fn new(index: &'a DeepIndex) -> Self {
+        Self { index }
+    }
+
+    /// Files of the index at least `min_similarity` similar to `target` by
+    /// either measure, most similar first. Empty when `target` isn't
+    /// indexed.
+    pub fn score(&self, target: &str, min_similarity: f64) -> Vec<Similar> {
+        let Some(entry) = self.index.files.get(target) else {
+            return Vec::new();
+        };
+        let target_vector = self.vector(entry);
+        let target_norm = norm(&target_vector);
+
+        let mut similar: Vec<Similar> = self
+            .index
+            .files
+            .iter()
+            .filter(|(path, _)| path.as_str() != target)
+            .filter_map(|(path, other)| {
+                let vector = self.vector(other);
+                let dot: f64 = vector
+                    .iter()
+                    .filter_map(|(term, w)| target_vector.get(term).map(|t| t * w))
+                    .sum();
+                let denominator = target_norm * norm(&vector);
+                let similarity = if denominator > 0.0 {
+                    dot / denominator
+                } else {
+                    0.0
+                };
+                let overlap = estimated_jaccard(&entry.signature, &other.signature);
+                (similarity.max(overlap) >= min_similarity).then(|| Similar {
+                    path: path.clone(),
+                    similarity,
+                    overlap,
+                })
+            })
+            .collect();
+        similar.sort_by(|a, b| {
+            b.similarity
+                .max(b.overlap)
+                .total_cmp(&a.similarity.max(a.overlap))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        similar
+    }
+
+    /// TF-IDF weights of an entry's content and symbol terms.
+    fn vector(&self, entry: &'a FileEntry) -> HashMap<&'a str, f64> {
+        let total_docs = self.index.files.len().max(1) as f64;
+        entry
+            .term_frequencies
+            .iter()
+            .filter(|(_, tf)| tf.body + tf.symbols > 0)
+            .map(|(term, tf)| {
+                let df = self.index.doc_frequencies.get(term).copied().unwrap_or(1) as f64;
+                let idf = (total_docs / df.max(1.0)).ln_1p();
+                (term.as_str(), (tf.body + tf.symbols) as f64 * idf)
+            })
+            .collect()
+    }
+}
+
+fn norm(vector: &HashMap<&str, f64>) -> f64 {
+    vector.values().map(|w| w * w).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::{FileSummary, TermFreqs};
+
+    const RETRY: &str = "
+        pub fn retry_request(client: &Client, request: Request, attempts: u32) -> Result<Response> {
+            let mut delay = Duration::from_millis(100);
+            for attempt in 0..attempts {
+                match client.send(request.clone()) {
+                    Ok(response) => return Ok(response),
+                    Err(err) if attempt + 1 < attempts => sleep(delay),
+                    Err(err) => return Err(err),
+                }
+                delay *= 2;
+            }
+        }";
+
+    fn entry(content: &str) -> FileEntry {
+        let mut term_frequencies: HashMap<String, TermFreqs> = HashMap::new();
+        for token in crate::Tokenizer::tokenize(content) {
+            term_frequencies.entry(token).or_default().body += 1;
+        }
+        FileEntry {
+            sha256: [0u8; 32],
+            chunks: Vec::new(),
+            term_frequencies,
+            positions: HashMap::new(),
+            doc_length: 0,
+            summary: FileSummary::default(),
+            signature: minhash_signature(content),
+            imports: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn signatures_estimate_overlap() {
+        let copy = RETRY.replace("100", "250");
+        let a = minhash_signature(RETRY);
+        assert_eq!(a.len(), SIGNATURE_HASHES);
+        assert_eq!(a, minhash_signature(RETRY));
+        assert_eq!(estimated_jaccard(&a, &a), 1.0);
+        assert!(estimated_jaccard(&a, &minhash_signature(&copy)) > 0.6);
+        let unrelated = minhash_signature("fn render(page: &Page) -> Html { page.body.to_html() }");
+        assert!(estimated_jaccard(&a, &unrelated) < 0.2);
+        assert!(minhash_signature("  ;; ").is_empty());
+    }
+
+    #[test]
+    fn finds_copies_and_parallel_implementations() {
+        let mut files = HashMap::new();
+        files.insert("src/http/retry.rs".to_string(), entry(RETRY));
+        files.insert(
+            "src/grpc/retry.rs".to_string(),
+            entry(&RETRY.replace("100", "250")),
+        );
+        files.insert(
+            "src/db/retry.rs".to_string(),
+            entry("pub fn retry_query(pool: &Pool, query: Query, attempts: u32) -> Result<Rows> { for attempt in 0..attempts { match pool.send(query.clone()) { Ok(rows) => return Ok(rows), Err(err) => sleep(delay) } } }"),
+        );
+        files.insert(
+            "src/render.rs".to_string(),
+            entry("fn render(page: &Page) -> Html { page.body.to_html() }"),
+        );
+        let mut doc_frequencies = HashMap::new();
+        for entry in files.values() {
+            for term in entry.term_frequencies.keys() {
+                *doc_frequencies.entry(term.clone()).or_default() += 1;
+            }
+        }
+        let index = DeepIndex {
+            version: topo_core::INDEX_VERSION,
+            total_docs: files.len() as u32,
+            files,
+            avg_doc_length: 0.0,
+            doc_frequencies,
+            pagerank_scores: HashMap::new(),
+            pagerank_settings: String::new(),
+            stop_words: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let similar = SimilarityScorer::new(&index).score("src/http/retry.rs", 0.2);
+        let paths: Vec<&str> = similar.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, ["src/grpc/retry.rs", "src/db/retry.rs"]);
+        assert!(similar[0].is_near_duplicate());
+        assert!(!similar[1].is_near_duplicate());
+        assert!(similar[1].similarity > 0.2);
+        assert!(
+            SimilarityScorer::new(&index)
+                .score("missing.rs", 0.0)
+                .is_empty()
+        );
+    }
+}
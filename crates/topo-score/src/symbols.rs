@@ -0,0 +1,201 @@
+use crate::tokenizer::{StopWords, Tokenizer};
+use topo_core::{ChunkKind, DeepIndex};
+
+/// Score of a match on the exact symbol name.
+const EXACT: f64 = 1.0;
+
+/// Score of a match on the same words in another case or separator style:
+/// `refresh_token` for `refreshToken`.
+const SAME_WORDS: f64 = 0.9;
+
+/// Score range of a partial match: each query word starting a word of the
+/// name, in order (`parseResp` for `parseHTTPResponse`), by the share of
+/// the name's words covered.
+const PARTIAL_BASE: f64 = 0.4;
+const PARTIAL_SPAN: f64 = 0.4;
+
+/// A function, type or impl block of the deep index matching a search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolMatch {
+    pub path: String,
+    pub name: String,
+    pub kind: ChunkKind,
+    /// First line of the definition, 1-based.
+    pub line: u32,
+    pub score: f64,
+}
+
+/// Searches the names of the chunks in a deep index.
+///
+/// Names are compared word by word, splitting camelCase, PascalCase and
+/// snake_case, so `refresh token`, `refreshToken` and `RefreshToken` all
+/// find `refresh_token`, and query words may abbreviate the name's
+/// (`parseResp` finds `parseHTTPResponse`).
+pub struct SymbolSearch<'a> {
+    index: &'a DeepIndex,
+    kind: Option<ChunkKind>,
+}
+
+impl<'a> SymbolSearch<'a> {
+    pub fn new(index: &'a DeepIndex) -> Self {
+        Self { index, kind: None }
+    }
+
+    /// Only match chunks of this kind.
+    pub fn kind(mut self, kind: ChunkKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Symbols matching `query`, best match first, then by path and line.
+    pub fn search(&self, query: &str) -> Vec<SymbolMatch> {
+        let query_words = words(query);
+        if query_words.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<SymbolMatch> = self
+            .index
+            .files
+            .iter()
+            .flat_map(|(path, entry)| entry.chunks.iter().map(move |c| (path, c)))
+            .filter(|(_, c)| match self.kind {
+                Some(kind) => c.kind == kind,
+                None => matches!(
+                    c.kind,
+                    ChunkKind::Function | ChunkKind::Type | ChunkKind::Impl
+                ),
+            })
+            .filter_map(|(path, chunk)| {
+                let score = name_score(query.trim(), &query_words, &chunk.name)?;
+                Some(SymbolMatch {
+                    path: path.clone(),
+                    name: chunk.name.clone(),
+                    kind: chunk.kind,
+                    line: chunk.start_line,
+                    score,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        matches
+    }
+}
+
+/// The lowercase words of an identifier or query, stop words included:
+/// in names they carry meaning (`is_empty`, `toString`).
+fn words(text: &str) -> Vec<String> {
+    Tokenizer::tokenize_with(text, &StopWords::none())
+}
+
+/// How well `name` matches the query, or `None` if it doesn't.
+fn name_score(query: &str, query_words: &[String], name: &str) -> Option<f64> {
+    if name == query {
+        return Some(EXACT);
+    }
+    let name_words = words(name);
+    if name_words == query_words {
+        return Some(SAME_WORDS);
+    }
+    let mut rest = name_words.iter();
+    let in_order = query_words
+        .iter()
+        .all(|q| rest.any(|w| w.starts_with(q.as_str())));
+    in_order
+        .then(|| PARTIAL_BASE + PARTIAL_SPAN * query_words.len() as f64 / name_words.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use topo_core::{Chunk, FileEntry, FileSummary};
+
+    fn entry(chunks: &[(ChunkKind, &str, u32)]) -> FileEntry {
+        FileEntry {
+            sha256: [0u8; 32],
+            chunks: chunks
+                .iter()
+                .map(|&(kind, name, line)| Chunk {
+                    kind,
+                    name: name.to_string(),
+                    start_line: line,
+                    end_line: line,
+                    content: String::new(),
+                })
+                .collect(),
+            term_frequencies: HashMap::new(),
+            positions: HashMap::new(),
+            doc_length: 0,
+            summary: FileSummary::default(),
+            signature: Vec::new(),
+            imports: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn matches_across_case_styles() {
+        assert_eq!(
+            name_score("refreshToken", &words("refreshToken"), "refreshToken"),
+            Some(EXACT)
+        );
+        assert_eq!(
+            name_score("refresh token", &words("refresh token"), "RefreshToken"),
+            Some(SAME_WORDS)
+        );
+        let partial = name_score("parseResp", &words("parseResp"), "parseHTTPResponse").unwrap();
+        assert!(partial > PARTIAL_BASE && partial < SAME_WORDS);
+        assert_eq!(
+            name_score("respParse", &words("respParse"), "parseHTTPResponse"),
+            None
+        );
+        assert_eq!(
+            name_score("is_empty", &words("is_empty"), "isEmpty"),
+            Some(SAME_WORDS)
+        );
+    }
+
+    #[test]
+    fn searches_the_index_by_kind() {
+        let mut files = HashMap::new();
+        files.insert(
+            "src/auth.rs".to_string(),
+            entry(&[
+                (ChunkKind::Type, "TokenStore", 3),
+                (ChunkKind::Function, "refresh_token", 12),
+                (ChunkKind::Import, "token", 1),
+            ]),
+        );
+        files.insert(
+            "src/client.ts".to_string(),
+            entry(&[(ChunkKind::Function, "refreshTokenLater", 40)]),
+        );
+        let index = DeepIndex {
+            version: topo_core::INDEX_VERSION,
+            total_docs: files.len() as u32,
+            files,
+            avg_doc_length: 0.0,
+            doc_frequencies: HashMap::new(),
+            pagerank_scores: HashMap::new(),
+            pagerank_settings: String::new(),
+            stop_words: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let found = SymbolSearch::new(&index).search("refreshToken");
+        let names: Vec<&str> = found.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["refresh_token", "refreshTokenLater"]);
+        assert_eq!(found[0].line, 12);
+
+        let types = SymbolSearch::new(&index)
+            .kind(ChunkKind::Type)
+            .search("token");
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].name, "TokenStore");
+        assert!(SymbolSearch::new(&index).search("  ").is_empty());
+    }
+}
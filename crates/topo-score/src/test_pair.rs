@@ -0,0 +1,252 @@
+use crate::pagerank::ImportGraph;
+use crate::tokenizer::Tokenizer;
+use std::path::Path;
+use topo_core::{FileInfo, FileRole, ScoredFile};
+
+/// Query terms that signal a change to implementation code.
+const IMPLEMENTATION_TERMS: &[&str] = &[
+    "add",
+    "broken",
+    "bug",
+    "bugfix",
+    "change",
+    "crash",
+    "crashes",
+    "error",
+    "errors",
+    "fix",
+    "fixes",
+    "handle",
+    "implement",
+    "refactor",
+    "regression",
+    "support",
+    "update",
+];
+
+/// Query terms that make the tests themselves the subject.
+const TEST_TERMS: &[&str] = &[
+    "coverage", "fixture", "fixtures", "mock", "mocks", "spec", "specs", "test", "testing", "tests",
+];
+
+/// File stems too generic to pair by name: `mod.rs` and `test_mod.rs`
+/// rarely belong together.
+const GENERIC_STEMS: &[&str] = &["index", "init", "lib", "main", "mod"];
+
+/// Evidence needed before two files count as a pair: an import or a
+/// matching name.
+const MIN_EVIDENCE: u32 = 2;
+
+/// Test-to-implementation pairing.
+///
+/// A task like "fix token refresh in the auth middleware" changes
+/// implementation code, and the change is not done until its tests follow.
+/// On such queries each selected implementation file is paired with its
+/// best-matching test file, and each selected test with the implementation
+/// it covers, so the two travel together. Queries about the tests
+/// themselves ("add coverage for...") leave pairing off.
+#[derive(Debug, Clone)]
+pub struct TestPairing {
+    active: bool,
+}
+
+impl TestPairing {
+    pub fn new(query: &str) -> Self {
+        let tokens = Tokenizer::tokenize(&Tokenizer::split_negated(query).0);
+        let has = |terms: &[&str]| {
+            tokens
+                .iter()
+                .any(|t| terms.binary_search(&t.as_str()).is_ok())
+        };
+        Self {
+            active: has(IMPLEMENTATION_TERMS) && !has(TEST_TERMS),
+        }
+    }
+
+    /// Whether the query was classified as targeting implementation.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Set `pair` on each of `selected` that has a partner among `files`.
+    pub fn pair(&self, selected: &mut [ScoredFile], files: &[FileInfo], graph: &ImportGraph) {
+        if !self.active {
+            return;
+        }
+        for file in selected.iter_mut() {
+            file.pair = match file.role {
+                FileRole::Implementation => best_test(&file.path, files, graph),
+                FileRole::Test => best_impl(&file.path, files, graph),
+                _ => None,
+            };
+        }
+    }
+}
+
+/// The test file that best covers the implementation file `path`: one
+/// that imports it, is named after it, or ideally both.
+pub fn best_test(path: &str, files: &[FileInfo], graph: &ImportGraph) -> Option<String> {
+    let stem = pairable_stem(path);
+    best_partner(path, files, FileRole::Test, |test| {
+        let imports = graph.imports_of(test).iter().any(|p| p == path);
+        let named = stem.is_some() && impl_stem(test) == stem;
+        imports as u32 * 2 + named as u32 * 2
+    })
+}
+
+/// The implementation file the test file `path` best covers: one it
+/// imports, one named after it, or ideally both.
+pub fn best_impl(path: &str, files: &[FileInfo], graph: &ImportGraph) -> Option<String> {
+    let stem = impl_stem(path).filter(|s| !GENERIC_STEMS.contains(&s.as_str()));
+    let imports = graph.imports_of(path);
+    best_partner(path, files, FileRole::Implementation, |candidate| {
+        let imported = imports.iter().any(|p| p == candidate);
+        let named = stem.is_some() && pairable_stem(candidate) == stem;
+        imported as u32 * 2 + named as u32 * 2
+    })
+}
+
+/// The file of `role` with the most evidence of pairing with `path`, ties
+/// going to the one sharing the longest directory prefix, then by path.
+fn best_partner(
+    path: &str,
+    files: &[FileInfo],
+    role: FileRole,
+    evidence: impl Fn(&str) -> u32,
+) -> Option<String> {
+    files
+        .iter()
+        .filter(|f| f.role == role && f.path != path)
+        .map(|f| (evidence(&f.path), shared_dirs(path, &f.path), &f.path))
+        .filter(|&(e, _, _)| e >= MIN_EVIDENCE)
+        .max_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)).then(b.2.cmp(a.2)))
+        .map(|(_, _, p)| p.clone())
+}
+
+/// Number of leading directories `a` and `b` have in common.
+fn shared_dirs(a: &str, b: &str) -> usize {
+    let dirs = |p: &str| {
+        Path::new(p)
+            .parent()
+            .map(|d| d.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let (a, b) = (dirs(a), dirs(b));
+    a.split('/')
+        .zip(b.split('/'))
+        .take_while(|(x, y)| !x.is_empty() && x == y)
+        .count()
+}
+
+/// The lowercase file stem of `path`, unless too generic to pair by name.
+fn pairable_stem(path: &str) -> Option<String> {
+    let file_name = Path::new(path).file_name()?.to_str()?.to_lowercase();
+    let stem = file_name.split('.').next()?;
+    (!stem.is_empty() && !GENERIC_STEMS.contains(&stem)).then(|| stem.to_string())
+}
+
+/// The stem of the file a test file is named after, with common test
+/// affixes stripped: `auth_test.go` → `auth`, `test_db.py` → `db`,
+/// `session.spec.ts` → `session`.
+pub fn impl_stem(test_path: &str) -> Option<String> {
+    let file_name = Path::new(test_path).file_name()?.to_str()?.to_lowercase();
+    let stem = file_name.split('.').next()?;
+    let stem = stem
+        .strip_prefix("test_")
+        .or_else(|| stem.strip_suffix("_test"))
+        .or_else(|| stem.strip_suffix("_spec"))
+        .or_else(|| stem.strip_suffix("test"))
+        .unwrap_or(stem);
+    (!stem.is_empty()).then(|| stem.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::Language;
+
+    fn file(path: &str) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size: 100,
+            language: Language::Python,
+            role: FileRole::from_path(Path::new(path)),
+            sha256: [0u8; 32],
+            lines: 0,
+            executable: false,
+            shebang: None,
+            entry_point: false,
+            license: None,
+        }
+    }
+
+    #[test]
+    fn terms_sorted() {
+        for terms in [IMPLEMENTATION_TERMS, TEST_TERMS] {
+            let mut sorted = terms.to_vec();
+            sorted.sort();
+            assert_eq!(sorted, terms);
+        }
+    }
+
+    #[test]
+    fn implementation_queries_activate_pairing() {
+        assert!(TestPairing::new("fix token refresh in auth middleware").is_active());
+        assert!(!TestPairing::new("add tests for token refresh").is_active());
+        assert!(!TestPairing::new("how does token refresh work").is_active());
+    }
+
+    #[test]
+    fn pairs_by_import_and_name() {
+        let files = vec![
+            file("app/auth.py"),
+            file("app/session.py"),
+            file("app/db/__init__.py"),
+            file("tests/test_auth.py"),
+            file("tests/test_login.py"),
+            file("tests/db/test_init.py"),
+        ];
+        let graph = ImportGraph::from_imports(&[
+            (
+                "tests/test_login.py".to_string(),
+                vec!["app/session.py".to_string(), "app/auth.py".to_string()],
+            ),
+            (
+                "tests/test_auth.py".to_string(),
+                vec!["app/auth.py".to_string()],
+            ),
+        ]);
+
+        // Named after it and importing it beats importing it alone
+        assert_eq!(
+            best_test("app/auth.py", &files, &graph).as_deref(),
+            Some("tests/test_auth.py")
+        );
+        assert_eq!(
+            best_test("app/session.py", &files, &graph).as_deref(),
+            Some("tests/test_login.py")
+        );
+        assert_eq!(
+            best_impl("tests/test_auth.py", &files, &graph).as_deref(),
+            Some("app/auth.py")
+        );
+        // Generic names alone are no evidence
+        assert_eq!(best_impl("tests/db/test_init.py", &files, &graph), None);
+
+        let mut selected = vec![ScoredFile {
+            path: "app/auth.py".to_string(),
+            score: 1.0,
+            signals: Default::default(),
+            tokens: 0,
+            language: Language::Python,
+            role: FileRole::Implementation,
+            root: None,
+            cluster: None,
+            pair: None,
+            chunks: Vec::new(),
+            outline: Vec::new(),
+        }];
+        TestPairing::new("fix login").pair(&mut selected, &files, &graph);
+        assert_eq!(selected[0].pair.as_deref(), Some("tests/test_auth.py"));
+    }
+}
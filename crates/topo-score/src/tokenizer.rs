@@ -1,3 +1,7 @@
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+use topo_core::StopWordsConfig;
+
 /// Text tokenizer for scoring: splits on whitespace, camelCase, snake_case,
 /// removes stop words, and normalizes to lowercase.
 pub struct Tokenizer;
@@ -10,14 +14,127 @@ const STOP_WORDS: &[&str] = &[
     "what", "when", "which", "who", "will", "with", "would", "you", "your",
 ];
 
+static ENGLISH: LazyLock<StopWords> = LazyLock::new(StopWords::english);
+
+/// Terms dropped by the tokenizer.
+///
+/// Starts from a built-in English list; projects add domain noise and remove
+/// words that carry meaning in their API names (`is`, `do`). The same list
+/// must be used when indexing and querying so term statistics line up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StopWords {
+    words: BTreeSet<String>,
+}
+
+impl Default for StopWords {
+    fn default() -> Self {
+        ENGLISH.clone()
+    }
+}
+
+impl StopWords {
+    /// The built-in English list.
+    pub fn english() -> Self {
+        Self {
+            words: STOP_WORDS.iter().map(|w| w.to_string()).collect(),
+        }
+    }
+
+    /// An empty list, keeping every word.
+    pub fn none() -> Self {
+        Self {
+            words: BTreeSet::new(),
+        }
+    }
+
+    /// The English list adjusted by a `[stop_words]` config section.
+    pub fn from_config(config: &StopWordsConfig) -> Self {
+        Self::english()
+            .with_words(&config.add)
+            .without_words(&config.remove)
+    }
+
+    /// Also drop these words (matched case-insensitively).
+    pub fn with_words<S: AsRef<str>>(mut self, words: &[S]) -> Self {
+        self.words
+            .extend(words.iter().map(|w| w.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Keep these words even if they are on the list.
+    pub fn without_words<S: AsRef<str>>(mut self, words: &[S]) -> Self {
+        for w in words {
+            self.words.remove(&w.as_ref().to_lowercase());
+        }
+        self
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+
+    /// The words in sorted order.
+    pub fn to_vec(&self) -> Vec<String> {
+        self.words.iter().cloned().collect()
+    }
+}
+
 impl Tokenizer {
-    /// Tokenize a string into normalized terms.
+    /// Split a query into the text to rank by and its negated words, without
+    /// their `-`. A word is negated when it starts with `-` and a letter or
+    /// digit (`-vendor`, `-test`); `--flag` and `a-b` are left alone.
+    pub fn split_negated(query: &str) -> (String, Vec<String>) {
+        let mut text = Vec::new();
+        let mut negated = Vec::new();
+        for word in query.split_whitespace() {
+            match word.strip_prefix('-') {
+                Some(term) if term.starts_with(|c: char| c.is_alphanumeric()) => {
+                    negated.push(term.to_string());
+                }
+                _ => text.push(word),
+            }
+        }
+        (text.join(" "), negated)
+    }
+
+    /// Tokenize a query into the terms to rank by and the terms of its
+    /// negated words, so a `-term` never counts as a match.
+    pub fn tokenize_query_with(query: &str, stop_words: &StopWords) -> (Vec<String>, Vec<String>) {
+        let (text, negated) = Self::split_negated(query);
+        let negative = negated
+            .iter()
+            .flat_map(|word| Self::tokenize_with(word, stop_words))
+            .collect();
+        (Self::tokenize_with(&text, stop_words), negative)
+    }
+
+    /// Split a query into the text outside double quotes and the quoted
+    /// phrases, in order. An unclosed quote runs to the end of the query.
+    pub fn split_phrases(query: &str) -> (String, Vec<String>) {
+        let mut text = Vec::new();
+        let mut phrases = Vec::new();
+        for (i, part) in query.split('"').enumerate() {
+            if i % 2 == 0 {
+                text.extend(part.split_whitespace());
+            } else if !part.trim().is_empty() {
+                phrases.push(part.trim().to_string());
+            }
+        }
+        (text.join(" "), phrases)
+    }
+
+    /// Tokenize a string into normalized terms, dropping English stop words.
     pub fn tokenize(input: &str) -> Vec<String> {
+        Self::tokenize_with(input, &ENGLISH)
+    }
+
+    /// Tokenize a string into normalized terms, dropping `stop_words`.
+    pub fn tokenize_with(input: &str, stop_words: &StopWords) -> Vec<String> {
         let mut tokens = Vec::new();
 
         // Split on whitespace and common separators
-        for word in input
-            .split(|c: char| c.is_whitespace() || c == '/' || c == '\\' || c == '.' || c == '-')
+        for word in
+            input.split(|c: char| c.is_whitespace() || matches!(c, '/' | '\\' | '.' | '-' | '"'))
         {
             if word.is_empty() {
                 continue;
@@ -32,7 +149,7 @@ impl Tokenizer {
                 let sub_tokens = split_camel_case(part);
                 for token in sub_tokens {
                     let lower = token.to_lowercase();
-                    if lower.len() >= 2 && !is_stop_word(&lower) {
+                    if lower.len() >= 2 && !stop_words.contains(&lower) {
                         tokens.push(lower);
                     }
                 }
@@ -86,10 +203,6 @@ fn split_camel_case(s: &str) -> Vec<&str> {
     parts
 }
 
-fn is_stop_word(word: &str) -> bool {
-    STOP_WORDS.binary_search(&word).is_ok()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,6 +223,40 @@ mod tests {
         assert!(tokens.contains(&"middleware".to_string()));
     }
 
+    #[test]
+    fn custom_stop_words() {
+        let stop_words = StopWords::english()
+            .with_words(&["Acme"])
+            .without_words(&["is"]);
+        let tokens = Tokenizer::tokenize_with("AcmeClient is_valid the", &stop_words);
+        assert_eq!(tokens, vec!["client", "is", "valid"]);
+        assert!(!Tokenizer::tokenize("is_valid").contains(&"is".to_string()));
+    }
+
+    #[test]
+    fn split_negated_words() {
+        let (text, negated) =
+            Tokenizer::split_negated("rate limiter -vendor -testUtils --force a-b");
+        assert_eq!(text, "rate limiter --force a-b");
+        assert_eq!(negated, vec!["vendor", "testUtils"]);
+
+        let (positive, negative) =
+            Tokenizer::tokenize_query_with("auth -test_helpers", &StopWords::default());
+        assert_eq!(positive, vec!["auth"]);
+        assert_eq!(negative, vec!["test", "helpers"]);
+    }
+
+    #[test]
+    fn split_quoted_phrases() {
+        let (text, phrases) = Tokenizer::split_phrases(r#"limit "token bucket" requests "rate"#);
+        assert_eq!(text, "limit requests");
+        assert_eq!(phrases, vec!["token bucket", "rate"]);
+        assert_eq!(
+            Tokenizer::tokenize(r#""token bucket""#),
+            vec!["token", "bucket"]
+        );
+    }
+
     #[test]
     fn tokenize_windows_and_unix_paths_match() {
         let unix = Tokenizer::tokenize("src/auth/middleware.rs");
@@ -0,0 +1,107 @@
+use topo_core::{FileRole, VendoredConfig};
+
+/// Directories holding vendored code: external dependencies checked into
+/// the repository.
+pub const VENDORED_DIRS: &[&str] = &["vendor", "node_modules", "third_party"];
+
+/// Score multiplier for vendored and generated files when
+/// `[vendored] multiplier` is unset.
+pub const DEFAULT_VENDORED_MULTIPLIER: f64 = 0.1;
+
+/// Whether `path` is under a vendored directory.
+pub fn is_vendored(path: &str) -> bool {
+    path.split(['/', '\\'])
+        .any(|component| VENDORED_DIRS.contains(&component))
+}
+
+/// How scoring treats vendored and generated files.
+///
+/// Left in the corpus, thousands of dependency files skew IDF: a term the
+/// project uses once but `node_modules/` uses everywhere looks common. Under
+/// every mode but `Include` those files stay out of the corpus statistics;
+/// `Downweight` still ranks them at a fraction of their score, and
+/// `Exclude` drops them from the results.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VendoredPolicy {
+    Downweight(f64),
+    Exclude,
+    Include,
+}
+
+impl Default for VendoredPolicy {
+    fn default() -> Self {
+        Self::Downweight(DEFAULT_VENDORED_MULTIPLIER)
+    }
+}
+
+impl VendoredPolicy {
+    /// The policy `[vendored]` sets; validated when the config was loaded.
+    pub fn from_config(config: &VendoredConfig) -> Self {
+        match config.policy.as_deref() {
+            Some("exclude") => Self::Exclude,
+            Some("include") => Self::Include,
+            _ => Self::Downweight(config.multiplier.unwrap_or(DEFAULT_VENDORED_MULTIPLIER)),
+        }
+    }
+
+    /// Whether the policy covers a file: vendored by path or generated.
+    pub fn covers(path: &str, role: FileRole) -> bool {
+        role == FileRole::Generated || is_vendored(path)
+    }
+
+    /// Whether a file counts toward the corpus statistics.
+    pub fn in_corpus(&self, path: &str, role: FileRole) -> bool {
+        *self == Self::Include || !Self::covers(path, role)
+    }
+
+    /// Whether a file is scored at all.
+    pub fn retains(&self, path: &str, role: FileRole) -> bool {
+        *self != Self::Exclude || !Self::covers(path, role)
+    }
+
+    /// The multiplier for a file's score, if downweighted.
+    pub fn multiplier(&self, path: &str, role: FileRole) -> Option<f64> {
+        match self {
+            Self::Downweight(m) if Self::covers(path, role) => Some(*m),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_vendored_detects_vendor_dirs() {
+        assert!(is_vendored("vendor/github.com/lib/foo.go"));
+        assert!(is_vendored("node_modules/react/index.js"));
+        assert!(is_vendored("third_party/proto/types.go"));
+        assert!(!is_vendored("src/vendor_utils.go"));
+        assert!(!is_vendored("pkg/handler.go"));
+        assert!(!is_vendored("cmd/main.go"));
+    }
+
+    #[test]
+    fn policies() {
+        let vendored = ("node_modules/lodash/index.js", FileRole::Implementation);
+        let generated = ("src/schema.pb.go", FileRole::Generated);
+        let source = ("src/app.js", FileRole::Implementation);
+
+        let downweight = VendoredPolicy::from_config(&VendoredConfig::default());
+        assert!(!downweight.in_corpus(vendored.0, vendored.1));
+        assert!(downweight.retains(vendored.0, vendored.1));
+        assert_eq!(downweight.multiplier(generated.0, generated.1), Some(0.1));
+        assert_eq!(downweight.multiplier(source.0, source.1), None);
+
+        let exclude = VendoredPolicy::Exclude;
+        assert!(!exclude.retains(generated.0, generated.1));
+        assert!(exclude.retains(source.0, source.1));
+        assert_eq!(exclude.multiplier(vendored.0, vendored.1), None);
+
+        let include = VendoredPolicy::Include;
+        assert!(include.in_corpus(vendored.0, vendored.1));
+        assert!(include.retains(vendored.0, vendored.1));
+        assert_eq!(include.multiplier(vendored.0, vendored.1), None);
+    }
+}
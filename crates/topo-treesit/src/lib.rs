@@ -3,11 +3,15 @@
 //! Uses tree-sitter for precise AST chunking when a grammar is available,
 //! with regex-based fallback for unsupported languages.
 
+mod outline;
 mod queries;
 mod regex_chunker;
+mod summary;
 mod ts_chunker;
 
+pub use outline::{OutlineNode, nest, outline};
 pub use regex_chunker::RegexChunker;
+pub use summary::summarize;
 pub use ts_chunker::TreeSitterChunker;
 pub use ts_chunker::ts_language_for;
 
@@ -32,6 +36,34 @@ impl Chunker for CompositeChunker {
     }
 }
 
+/// Line prefixes only valid in C++, used to spot C++ code in `.h` headers.
+const CPP_ONLY_PREFIXES: &[&str] = &[
+    "class ",
+    "namespace ",
+    "template<",
+    "template <",
+    "using namespace ",
+    "public:",
+    "private:",
+    "protected:",
+];
+
+/// The language to chunk `content` as.
+///
+/// `.h` headers are detected as C by extension, but C++ projects use them
+/// too. C content containing C++-only constructs (`class`, `template`,
+/// `namespace`) is chunked with the C++ grammar instead.
+pub fn chunk_language(content: &str, language: Language) -> Language {
+    if language != Language::C {
+        return language;
+    }
+    let is_cpp = content.lines().any(|line| {
+        let line = line.trim_start();
+        CPP_ONLY_PREFIXES.iter().any(|p| line.starts_with(p))
+    });
+    if is_cpp { Language::Cpp } else { language }
+}
+
 /// Create the default chunker (regex-based, fast indexing).
 ///
 /// Tree-sitter chunkers (`TreeSitterChunker`, `CompositeChunker`) remain
@@ -77,6 +109,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn chunk_language_detects_cpp_headers() {
+        let cpp =
+            "#pragma once\nnamespace topo {\nclass Parser {\npublic:\n  void parse();\n};\n}\n";
+        assert_eq!(chunk_language(cpp, Language::C), Language::Cpp);
+
+        let c = "#pragma once\nstruct parser;\nint parse(struct parser *p);\n";
+        assert_eq!(chunk_language(c, Language::C), Language::C);
+        assert_eq!(chunk_language(cpp, Language::Rust), Language::Rust);
+    }
+
+    #[test]
+    fn cpp_header_chunks_classes() {
+        let src = "#pragma once\n\nnamespace topo {\n\nclass Parser {\npublic:\n    void parse();\n};\n\n}\n";
+        let chunks = TreeSitterChunker.chunk(src, Language::C);
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.name == "Parser" && c.kind == ChunkKind::Type)
+        );
+    }
+
     #[test]
     fn composite_falls_back_to_regex() {
         let chunker = CompositeChunker;
@@ -0,0 +1,128 @@
+use crate::{Chunker, CompositeChunker};
+use topo_core::{Chunk, Language};
+
+/// Longest label taken from a declaration's first line.
+const MAX_LABEL_CHARS: usize = 80;
+
+/// A chunk of a file outline with the chunks declared inside it: a type or
+/// impl block with its methods.
+#[derive(Debug, Clone)]
+pub struct OutlineNode {
+    pub chunk: Chunk,
+    pub children: Vec<OutlineNode>,
+}
+
+/// The outline of a file: its chunks from [`CompositeChunker`], nested.
+/// Chunks the grammar gives no name, such as imports and Rust impl
+/// blocks, are labelled with their first line (`use std::fmt;`,
+/// `impl Display for Session`).
+pub fn outline(content: &str, language: Language) -> Vec<OutlineNode> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut chunks = CompositeChunker.chunk(content, language);
+    for chunk in &mut chunks {
+        if chunk.name.is_empty()
+            && let Some(line) = lines.get(chunk.start_line as usize - 1)
+        {
+            chunk.name = line
+                .trim()
+                .trim_end_matches('{')
+                .trim_end()
+                .chars()
+                .take(MAX_LABEL_CHARS)
+                .collect();
+        }
+    }
+    nest(chunks)
+}
+
+/// Nest `chunks` by line range into a tree in source order. A chunk goes
+/// under the innermost earlier chunk whose range contains it; duplicate
+/// matches of the same declaration are dropped.
+pub fn nest(mut chunks: Vec<Chunk>) -> Vec<OutlineNode> {
+    chunks.sort_by(|a, b| {
+        a.start_line
+            .cmp(&b.start_line)
+            .then(b.end_line.cmp(&a.end_line))
+    });
+    chunks.dedup_by(|b, a| {
+        a.kind == b.kind
+            && a.name == b.name
+            && a.start_line == b.start_line
+            && a.end_line == b.end_line
+    });
+
+    let mut roots: Vec<OutlineNode> = Vec::new();
+    // Open ancestors of the next chunk, outermost first
+    let mut stack: Vec<OutlineNode> = Vec::new();
+    for chunk in chunks {
+        while let Some(open) = stack.pop_if(|open| chunk.end_line > open.chunk.end_line) {
+            close(open, &mut stack, &mut roots);
+        }
+        stack.push(OutlineNode {
+            chunk,
+            children: Vec::new(),
+        });
+    }
+    while let Some(open) = stack.pop() {
+        close(open, &mut stack, &mut roots);
+    }
+    roots
+}
+
+/// Add `node`, just popped off `stack`, to its parent, or the roots.
+fn close(node: OutlineNode, stack: &mut [OutlineNode], roots: &mut Vec<OutlineNode>) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topo_core::ChunkKind;
+
+    #[test]
+    fn nests_methods_under_their_impl() {
+        let source = "\
+use std::fmt;
+
+pub struct Session {
+    id: u64,
+}
+
+impl Session {
+    pub fn new(id: u64) -> Self {
+        Self { id }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+fn helper() {}
+";
+        let outline = outline(source, Language::Rust);
+        let top: Vec<(ChunkKind, &str)> = outline
+            .iter()
+            .map(|n| (n.chunk.kind, n.chunk.name.as_str()))
+            .collect();
+        assert_eq!(
+            top,
+            [
+                (ChunkKind::Import, "use std::fmt;"),
+                (ChunkKind::Type, "Session"),
+                (ChunkKind::Impl, "impl Session"),
+                (ChunkKind::Function, "helper"),
+            ]
+        );
+        let methods: Vec<&str> = outline[2]
+            .children
+            .iter()
+            .map(|n| n.chunk.name.as_str())
+            .collect();
+        assert_eq!(methods, ["new", "id"]);
+        assert_eq!(outline[2].children[0].chunk.start_line, 8);
+    }
+}
@@ -64,22 +64,59 @@ const PYTHON: &str = r#"
 (import_from_statement) @import
 "#;
 
-const JAVASCRIPT: &str = r#"
-(function_declaration name: (identifier) @name) @function
+/// Functions, plus React components: capitalized function declarations,
+/// `const Foo = () => ...` (optionally wrapped as in `memo(...)`), and
+/// anonymous `export default` functions, named `default`. Shared by the
+/// JavaScript, TypeScript and TSX queries.
+macro_rules! jsx_components {
+    () => {
+        r#"
+((function_declaration name: (identifier) @name) @function
+  (#match? @name "^[^A-Z]"))
+((function_declaration name: (identifier) @name) @type
+  (#match? @name "^[A-Z]"))
+((lexical_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [(arrow_function) (function_expression)])) @function
+  (#match? @name "^[^A-Z]"))
+((lexical_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [
+      (arrow_function)
+      (function_expression)
+      (call_expression arguments: (arguments [(arrow_function) (function_expression)]))
+    ])) @type
+  (#match? @name "^[A-Z]"))
+(export_statement
+  "default" @name
+  value: [(arrow_function) (function_expression)]) @type
+"#
+    };
+}
+
+const JAVASCRIPT: &str = concat!(
+    r#"
 (class_declaration name: (identifier) @name) @type
 (method_definition name: (property_identifier) @name) @function
 (import_statement) @import
-"#;
+"#,
+    jsx_components!()
+);
 
-const TYPESCRIPT: &str = r#"
-(function_declaration name: (identifier) @name) @function
+/// Also used with the TSX grammar.
+const TYPESCRIPT: &str = concat!(
+    r#"
 (class_declaration name: (type_identifier) @name) @type
 (method_definition name: (property_identifier) @name) @function
 (interface_declaration name: (type_identifier) @name) @type
 (type_alias_declaration name: (type_identifier) @name) @type
 (enum_declaration name: (identifier) @name) @type
 (import_statement) @import
-"#;
+"#,
+    jsx_components!()
+);
 
 const JAVA: &str = r#"
 (class_declaration name: (identifier) @name) @type
@@ -113,7 +150,7 @@ const CPP: &str = r#"
 (class_specifier name: (type_identifier) @name) @type
 (struct_specifier name: (type_identifier) @name) @type
 (enum_specifier name: (type_identifier) @name) @type
-(namespace_definition name: (identifier) @name) @type
+(namespace_definition name: (namespace_identifier) @name) @type
 (preproc_include) @import
 "#;
 
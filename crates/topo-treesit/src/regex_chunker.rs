@@ -135,10 +135,20 @@ fn extract_js_ts(line: &str) -> Option<(ChunkKind, String)> {
         .trim_start_matches("abstract ")
         .trim_start_matches("declare ");
 
+    // Anonymous default export: export default function () / export default () =>
+    if line.starts_with("export default ") {
+        let anonymous = stripped
+            .strip_prefix("function")
+            .map(|rest| rest.trim_start().starts_with('('))
+            .unwrap_or_else(|| stripped.starts_with('(') && stripped.contains("=>"));
+        if anonymous {
+            return Some((ChunkKind::Type, "default".to_string()));
+        }
+    }
     if let Some(rest) = stripped.strip_prefix("function ") {
         let name = ident(rest, &['(', '<', ' '])?;
         if name != "*" {
-            return Some((ChunkKind::Function, name));
+            return Some((function_or_component(&name), name));
         }
     }
     if let Some(rest) = stripped.strip_prefix("class ") {
@@ -159,7 +169,7 @@ fn extract_js_ts(line: &str) -> Option<(ChunkKind, String)> {
         .or_else(|| stripped.strip_prefix("let "))
         && (rest.contains("=>") || rest.contains("function"))
     {
-        return ident(rest, &[' ', '=', ':']).map(|n| (ChunkKind::Function, n));
+        return ident(rest, &[' ', '=', ':']).map(|n| (function_or_component(&n), n));
     }
     if line.starts_with("import ") {
         return Some((ChunkKind::Import, line.to_string()));
@@ -167,6 +177,16 @@ fn extract_js_ts(line: &str) -> Option<(ChunkKind, String)> {
     None
 }
 
+/// Capitalized functions are React components by convention; they describe
+/// UI the way a class would, so they are indexed as types.
+fn function_or_component(name: &str) -> ChunkKind {
+    if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        ChunkKind::Type
+    } else {
+        ChunkKind::Function
+    }
+}
+
 // ── Java ───────────────────────────────────────────────────────────
 
 fn extract_java(line: &str) -> Option<(ChunkKind, String)> {
@@ -590,6 +610,30 @@ export enum Status {
         );
     }
 
+    #[test]
+    fn jsx_components_are_types() {
+        let src = "\
+export const Header = ({ title }) => <h1>{title}</h1>;
+const Row = memo(function Row() {
+export function Sidebar() {
+const formatTitle = (title) => title.trim();
+export default function () {
+";
+        let chunks = RegexChunker.chunk(src, Language::JavaScript);
+        let kinds: Vec<(&str, ChunkKind)> =
+            chunks.iter().map(|c| (c.name.as_str(), c.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("Header", ChunkKind::Type),
+                ("Row", ChunkKind::Type),
+                ("Sidebar", ChunkKind::Type),
+                ("formatTitle", ChunkKind::Function),
+                ("default", ChunkKind::Type),
+            ]
+        );
+    }
+
     // ── Java ───────────────────────────────────────────────────────
 
     #[test]
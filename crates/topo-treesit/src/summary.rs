@@ -0,0 +1,670 @@
+//! Extractive file summaries.
+//!
+//! Keeps the lines of a file that describe its interface — the leading doc
+//! comment, exported signatures with the first line of their docs, and
+//! top-level constants — copied verbatim and in source order. Line-based
+//! like the regex chunker, so the result is deterministic and cheap enough
+//! to build for every file at index time.
+
+use topo_core::{ChunkKind, FileSummary, Language, SummaryKind, SummaryLine};
+
+use crate::{Chunker, RegexChunker};
+
+/// Lines kept per file; the rest of the file is left out.
+const MAX_LINES: usize = 80;
+/// Lines of the file's leading doc comment kept.
+const MAX_HEADER_LINES: usize = 6;
+/// Source lines a single signature may span (multi-line parameter lists).
+const MAX_SIGNATURE_LINES: usize = 6;
+
+/// Summarize `content` as `language`.
+pub fn summarize(content: &str, language: Language) -> FileSummary {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut kept: Vec<SummaryLine> = header_doc(&lines, language);
+    let mut state = State::default();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+        let indent = line.len() - line.trim_start().len();
+        if language == Language::Rust && trimmed == "#[cfg(test)]" && indent == 0 {
+            break;
+        }
+        state.observe(i, trimmed, indent, language);
+
+        let Some(kind) = classify(trimmed, indent, language, &state) else {
+            i += 1;
+            continue;
+        };
+
+        if kind == SummaryKind::Signature
+            && language == Language::Rust
+            && indent > 0
+            && let Some(header) = state.rust_impl.take()
+        {
+            kept.push(SummaryLine {
+                kind,
+                line: (header + 1) as u32,
+                text: strip_body(lines[header]),
+            });
+        }
+        if let Some(doc) = item_doc(&lines, i, language) {
+            kept.push(keep(SummaryKind::Doc, doc, lines[doc]));
+        }
+
+        let end = signature_end(&lines, i);
+        let text = if kind == SummaryKind::Signature {
+            strip_body(&join_lines(&lines[i..=end]))
+        } else {
+            join_lines(&lines[i..=end])
+        };
+        kept.push(SummaryLine {
+            kind,
+            line: (i + 1) as u32,
+            text,
+        });
+
+        if kind == SummaryKind::Signature
+            && language == Language::Python
+            && let Some(doc) = python_docstring(&lines, end + 1)
+        {
+            kept.push(keep(SummaryKind::Doc, doc, lines[doc]));
+        }
+        i = end + 1;
+    }
+
+    kept.sort_by_key(|l| l.line);
+    kept.dedup_by_key(|l| l.line);
+    kept.truncate(MAX_LINES);
+    FileSummary { lines: kept }
+}
+
+fn keep(kind: SummaryKind, index: usize, text: &str) -> SummaryLine {
+    SummaryLine {
+        kind,
+        line: (index + 1) as u32,
+        text: text.trim_end().to_string(),
+    }
+}
+
+/// Context carried from line to line.
+#[derive(Default)]
+struct State {
+    /// Inside a Go `const (` or `var (` block.
+    go_const_block: bool,
+    /// Inside a top-level public Python class.
+    python_class: bool,
+    /// The enclosing top-level Rust `impl` line, until one of its methods is kept.
+    rust_impl: Option<usize>,
+}
+
+impl State {
+    fn observe(&mut self, index: usize, trimmed: &str, indent: usize, language: Language) {
+        if trimmed.is_empty() || indent > 0 {
+            if language == Language::Go && trimmed == ")" {
+                self.go_const_block = false;
+            }
+            return;
+        }
+        match language {
+            Language::Go => {
+                self.go_const_block = trimmed == "const (" || trimmed == "var (";
+            }
+            Language::Python if !trimmed.starts_with('#') && !trimmed.starts_with('@') => {
+                self.python_class = trimmed
+                    .strip_prefix("class ")
+                    .is_some_and(|rest| !rest.starts_with('_'));
+            }
+            Language::Rust => {
+                if trimmed.starts_with("impl ") || trimmed.starts_with("impl<") {
+                    self.rust_impl = Some(index);
+                } else if !trimmed.starts_with('}') && !trimmed.starts_with("//") {
+                    self.rust_impl = None;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether a line is kept, and as what.
+fn classify(
+    trimmed: &str,
+    indent: usize,
+    language: Language,
+    state: &State,
+) -> Option<SummaryKind> {
+    use SummaryKind::{Constant, Signature};
+
+    match language {
+        Language::Rust => {
+            let rest = trimmed.strip_prefix("pub ")?;
+            if rest.starts_with("const fn ") || rest.starts_with("const unsafe fn ") {
+                Some(Signature)
+            } else if rest.starts_with("const ") || rest.starts_with("static ") {
+                Some(Constant)
+            } else if [
+                "fn ",
+                "async fn ",
+                "unsafe fn ",
+                "struct ",
+                "enum ",
+                "trait ",
+                "type ",
+                "union ",
+            ]
+            .iter()
+            .any(|p| rest.starts_with(p))
+            {
+                Some(Signature)
+            } else {
+                None
+            }
+        }
+        Language::Go => {
+            if indent > 0 {
+                return (state.go_const_block && starts_upper(trimmed)).then_some(Constant);
+            }
+            if let Some(rest) = trimmed.strip_prefix("func ") {
+                let name = if rest.starts_with('(') {
+                    rest.split_once(')')?.1.trim_start()
+                } else {
+                    rest
+                };
+                starts_upper(name).then_some(Signature)
+            } else if let Some(rest) = trimmed.strip_prefix("type ") {
+                starts_upper(rest).then_some(Signature)
+            } else if let Some(rest) = trimmed
+                .strip_prefix("const ")
+                .or_else(|| trimmed.strip_prefix("var "))
+            {
+                starts_upper(rest).then_some(Constant)
+            } else {
+                None
+            }
+        }
+        Language::Python => {
+            let def = trimmed.trim_start_matches("async ");
+            let name = def
+                .strip_prefix("def ")
+                .or_else(|| def.strip_prefix("class "));
+            if let Some(name) = name {
+                let public = !name.starts_with('_') || name.starts_with("__init__");
+                let top_level =
+                    indent == 0 && (def.starts_with("class ") || !name.starts_with('_'));
+                let method =
+                    indent > 0 && indent <= 4 && state.python_class && def.starts_with("def ");
+                return (public && (top_level || method)).then_some(Signature);
+            }
+            (indent == 0 && is_constant_assignment(trimmed)).then_some(Constant)
+        }
+        Language::JavaScript | Language::TypeScript => {
+            let rest = trimmed.strip_prefix("export ")?;
+            if indent > 0
+                || rest.starts_with('{')
+                || rest.starts_with('*')
+                || rest.starts_with("type {")
+            {
+                return None;
+            }
+            let declared = rest
+                .strip_prefix("const ")
+                .or_else(|| rest.strip_prefix("let "))
+                .or_else(|| rest.strip_prefix("var "));
+            match declared {
+                Some(decl) if is_upper_ident(decl.split([' ', ':', '=']).next().unwrap_or("")) => {
+                    Some(Constant)
+                }
+                _ => Some(Signature),
+            }
+        }
+        Language::Java | Language::Scala | Language::Swift => {
+            let rest = trimmed
+                .strip_prefix("public ")
+                .or_else(|| trimmed.strip_prefix("open "))?;
+            if rest.contains("static final ")
+                || rest.starts_with("let ")
+                || rest.starts_with("static let ")
+            {
+                Some(Constant)
+            } else {
+                Some(Signature)
+            }
+        }
+        Language::Kotlin => {
+            if ["private ", "internal ", "protected ", "override "]
+                .iter()
+                .any(|p| trimmed.starts_with(p))
+            {
+                return None;
+            }
+            let rest = trimmed.trim_start_matches("public ");
+            if rest.starts_with("const val ") {
+                return Some(Constant);
+            }
+            let rest = [
+                "data ",
+                "enum ",
+                "sealed ",
+                "abstract ",
+                "open ",
+                "suspend ",
+                "inline ",
+            ]
+            .iter()
+            .fold(rest, |r, p| r.trim_start_matches(p));
+            ["fun ", "class ", "interface ", "object "]
+                .iter()
+                .any(|p| rest.starts_with(p))
+                .then_some(Signature)
+        }
+        Language::Php => {
+            if trimmed.starts_with("const ") || trimmed.starts_with("public const ") {
+                Some(Constant)
+            } else if trimmed.starts_with("public function ")
+                || trimmed.starts_with("public static function ")
+                || (indent == 0
+                    && [
+                        "function ",
+                        "class ",
+                        "interface ",
+                        "trait ",
+                        "abstract class ",
+                        "final class ",
+                    ]
+                    .iter()
+                    .any(|p| trimmed.starts_with(p)))
+            {
+                Some(Signature)
+            } else {
+                None
+            }
+        }
+        Language::Ruby => {
+            if ["def ", "class ", "module "]
+                .iter()
+                .any(|p| trimmed.starts_with(p))
+            {
+                Some(Signature)
+            } else {
+                is_constant_assignment(trimmed).then_some(Constant)
+            }
+        }
+        Language::Elixir => [
+            "defmodule ",
+            "def ",
+            "defmacro ",
+            "defprotocol ",
+            "defstruct ",
+        ]
+        .iter()
+        .any(|p| trimmed.starts_with(p))
+        .then_some(Signature),
+        Language::C | Language::Cpp => {
+            if let Some(rest) = trimmed.strip_prefix("#define ") {
+                // Include guards define a name without a value
+                let mut parts = rest.split_whitespace();
+                let name = parts.next().unwrap_or("");
+                return (is_upper_ident(name) && parts.next().is_some()).then_some(Constant);
+            }
+            if indent > 0 || trimmed.starts_with("static ") || trimmed.starts_with('#') {
+                return None;
+            }
+            if (trimmed.starts_with("const ") || trimmed.starts_with("constexpr "))
+                && trimmed.contains('=')
+            {
+                return Some(Constant);
+            }
+            RegexChunker
+                .chunk(trimmed, language)
+                .first()
+                .is_some_and(|c| matches!(c.kind, ChunkKind::Function | ChunkKind::Type))
+                .then_some(Signature)
+        }
+        Language::Markdown => (trimmed.starts_with('#') && indent == 0).then_some(Signature),
+        _ => None,
+    }
+}
+
+/// The leading doc comment of the file, skipping shebangs and license
+/// headers. Only its first paragraph is kept.
+fn header_doc(lines: &[&str], language: Language) -> Vec<SummaryLine> {
+    let mut i = 0;
+    loop {
+        while i < lines.len()
+            && (lines[i].trim().is_empty() || (i == 0 && lines[i].starts_with("#!")))
+        {
+            i += 1;
+        }
+        let Some(end) = comment_block_end(lines, i, language) else {
+            return Vec::new();
+        };
+        // A comment directly above a declaration documents that item instead
+        let next = lines.get(end + 1).map_or("", |l| l.trim());
+        let item_doc = match language {
+            Language::Rust => !lines[i].trim_start().starts_with("//!"),
+            Language::Python | Language::Elixir => false,
+            _ => !next.is_empty() && !next.starts_with("package "),
+        };
+        if item_doc {
+            return Vec::new();
+        }
+        let block = &lines[i..=end];
+        let license = block.iter().any(|l| {
+            let l = l.to_lowercase();
+            l.contains("copyright")
+                || l.contains("spdx-license-identifier")
+                || l.contains("license")
+        });
+        if !license {
+            return block
+                .iter()
+                .enumerate()
+                .skip_while(|(_, l)| is_blank_comment(l))
+                .take_while(|(_, l)| !is_blank_comment(l))
+                .take(MAX_HEADER_LINES)
+                .map(|(j, l)| keep(SummaryKind::Header, i + j, l))
+                .collect();
+        }
+        i = end + 1;
+    }
+}
+
+/// Last line of the comment or docstring starting at `start`, if one does.
+fn comment_block_end(lines: &[&str], start: usize, language: Language) -> Option<usize> {
+    let first = lines.get(start)?.trim();
+
+    if language == Language::Python || language == Language::Elixir {
+        let body = first.strip_prefix("@moduledoc ").unwrap_or(first);
+        if let Some(quote) = ["\"\"\"", "'''"].into_iter().find(|q| body.starts_with(q)) {
+            if body.len() > 3 && body[3..].contains(quote) {
+                return Some(start);
+            }
+            return (start + 1..lines.len()).find(|&j| lines[j].contains(quote));
+        }
+    }
+    if first.starts_with("/*") {
+        return (start..lines.len()).find(|&j| lines[j].contains("*/"));
+    }
+    let prefix = line_comment(language)?;
+    if !first.starts_with(prefix) {
+        return None;
+    }
+    let mut end = start;
+    while end + 1 < lines.len() && lines[end + 1].trim().starts_with(prefix) {
+        end += 1;
+    }
+    Some(end)
+}
+
+/// The first line of the doc comment directly above line `i`, looking past
+/// attributes and decorators.
+fn item_doc(lines: &[&str], i: usize, language: Language) -> Option<usize> {
+    let mut j = i.checked_sub(1)?;
+    while ["#[", "@"].iter().any(|p| lines[j].trim().starts_with(p)) {
+        j = j.checked_sub(1)?;
+    }
+    let above = lines[j].trim();
+
+    if above.ends_with("*/") {
+        let start = (0..=j).rev().find(|&k| lines[k].trim().starts_with("/*"))?;
+        let opening = lines[start]
+            .trim()
+            .trim_start_matches('/')
+            .trim_start_matches('*');
+        if opening.trim().is_empty() && start < j {
+            return Some(start + 1);
+        }
+        return Some(start);
+    }
+
+    let prefix = line_comment(language)?;
+    if !above.starts_with(prefix) || (language == Language::Rust && !above.starts_with("///")) {
+        return None;
+    }
+    let mut start = j;
+    while start > 0 && lines[start - 1].trim().starts_with(prefix) {
+        start -= 1;
+    }
+    (!is_blank_comment(lines[start])).then_some(start)
+}
+
+/// The first text line of a Python docstring starting at line `i`.
+fn python_docstring(lines: &[&str], i: usize) -> Option<usize> {
+    let first = lines.get(i)?.trim();
+    let quote = ["\"\"\"", "'''"]
+        .into_iter()
+        .find(|q| first.starts_with(q))?;
+    if first == quote {
+        return (i + 1 < lines.len() && !lines[i + 1].trim().is_empty()).then_some(i + 1);
+    }
+    Some(i)
+}
+
+fn line_comment(language: Language) -> Option<&'static str> {
+    match language {
+        Language::Python
+        | Language::Ruby
+        | Language::Shell
+        | Language::R
+        | Language::Elixir
+        | Language::Yaml
+        | Language::Toml => Some("#"),
+        Language::Lua | Language::Haskell => Some("--"),
+        Language::Markdown | Language::Json | Language::Html | Language::Css | Language::Other => {
+            None
+        }
+        _ => Some("//"),
+    }
+}
+
+/// A comment line with no text, which ends a paragraph.
+fn is_blank_comment(line: &str) -> bool {
+    line.trim()
+        .trim_start_matches(['/', '!', '#', '*', '-', '"', '\''])
+        .trim()
+        .is_empty()
+}
+
+/// Last line of a declaration starting at `i`: open parentheses and
+/// brackets are followed onto later lines.
+fn signature_end(lines: &[&str], i: usize) -> usize {
+    let mut depth: i32 = 0;
+    for (j, line) in lines.iter().enumerate().skip(i).take(MAX_SIGNATURE_LINES) {
+        for c in line.chars() {
+            match c {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth <= 0 {
+            return j;
+        }
+    }
+    (i + MAX_SIGNATURE_LINES - 1).min(lines.len() - 1)
+}
+
+/// Join a declaration spread over several lines into one, keeping the
+/// indentation of the first.
+fn join_lines(lines: &[&str]) -> String {
+    let indent = &lines[0][..lines[0].len() - lines[0].trim_start().len()];
+    let mut out = indent.to_string();
+    for (k, line) in lines.iter().enumerate() {
+        let piece = line.trim();
+        if k > 0 && !out.ends_with(['(', '[']) && !piece.starts_with([')', ']']) {
+            out.push(' ');
+        }
+        out.push_str(piece);
+    }
+    out.replace(", )", ")").replace(",)", ")")
+}
+
+/// Cut a signature at the body that opens after its parameter list.
+fn strip_body(text: &str) -> String {
+    let mut depth: i32 = 0;
+    let mut previous = ' ';
+    for (at, c) in text.char_indices() {
+        match c {
+            '(' | '[' | '<' => depth += 1,
+            // `->` and `=>` are arrows, not closing generics
+            '>' if matches!(previous, '-' | '=') => {}
+            ')' | ']' | '>' if depth > 0 => depth -= 1,
+            '{' if depth == 0 => return text[..at].trim_end().to_string(),
+            _ => {}
+        }
+        previous = c;
+    }
+    text.trim_end().to_string()
+}
+
+fn starts_upper(s: &str) -> bool {
+    s.chars().next().is_some_and(|c| c.is_ascii_uppercase())
+}
+
+fn is_upper_ident(s: &str) -> bool {
+    s.chars().any(|c| c.is_ascii_uppercase())
+        && s.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// `NAME = value` or `NAME: type = value` with an upper-case name.
+fn is_constant_assignment(trimmed: &str) -> bool {
+    let Some((lhs, _)) = trimmed.split_once('=') else {
+        return false;
+    };
+    if trimmed[lhs.len()..].starts_with("==") {
+        return false;
+    }
+    let name = lhs.split(':').next().unwrap_or("").trim();
+    is_upper_ident(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(summary: &FileSummary) -> Vec<&str> {
+        summary.lines.iter().map(|l| l.text.as_str()).collect()
+    }
+
+    #[test]
+    fn rust_keeps_docs_pub_items_and_constants() {
+        let src = "\
+//! Session handling.
+//!
+//! Longer explanation that is dropped.
+
+use std::fmt;
+
+/// Maximum session age in seconds.
+pub const MAX_AGE: u64 = 3600;
+
+/// A logged-in user.
+/// Second doc line.
+#[derive(Debug)]
+pub struct Session {
+    pub user: String,
+}
+
+impl Session {
+    /// Start a session.
+    pub fn new(
+        user: &str,
+    ) -> Self {
+        Self { user: user.into() }
+    }
+
+    fn secret(&self) {}
+}
+
+fn private_helper() {}
+
+#[cfg(test)]
+mod tests {
+    pub fn fixture() {}
+}
+";
+        let summary = summarize(src, Language::Rust);
+        assert_eq!(
+            texts(&summary),
+            vec![
+                "//! Session handling.",
+                "/// Maximum session age in seconds.",
+                "pub const MAX_AGE: u64 = 3600;",
+                "/// A logged-in user.",
+                "pub struct Session",
+                "impl Session",
+                "    /// Start a session.",
+                "    pub fn new(user: &str) -> Self",
+            ]
+        );
+        assert_eq!(summary.lines[0].kind, SummaryKind::Header);
+        assert_eq!(summary.lines[2].kind, SummaryKind::Constant);
+        assert_eq!(summary.lines[7].line, 19);
+    }
+
+    #[test]
+    fn license_header_is_skipped() {
+        let src = "// Copyright 2024 Example\n// Licensed under MIT\n\n// Package auth checks tokens.\npackage auth\n\n// Verify checks a token.\nfunc Verify(t string) bool {\n\treturn true\n}\n\nfunc helper() {}\n";
+        let summary = summarize(src, Language::Go);
+        assert_eq!(
+            texts(&summary),
+            vec![
+                "// Package auth checks tokens.",
+                "// Verify checks a token.",
+                "func Verify(t string) bool",
+            ]
+        );
+    }
+
+    #[test]
+    fn python_docstrings_and_public_names() {
+        let src = "\"\"\"Billing helpers.\"\"\"\n\nTIMEOUT = 30\n_cache = {}\n\n\nclass Invoice:\n    \"\"\"An invoice.\"\"\"\n\n    def total(self):\n        \"\"\"\n        Sum of line items.\n        \"\"\"\n        return 0\n\n    def _round(self):\n        pass\n\n\ndef _private():\n    pass\n";
+        let summary = summarize(src, Language::Python);
+        assert_eq!(
+            texts(&summary),
+            vec![
+                "\"\"\"Billing helpers.\"\"\"",
+                "TIMEOUT = 30",
+                "class Invoice:",
+                "    \"\"\"An invoice.\"\"\"",
+                "    def total(self):",
+                "        Sum of line items.",
+            ]
+        );
+    }
+
+    #[test]
+    fn typescript_exports_with_jsdoc() {
+        let src = "import { x } from './x';\n\nexport const MAX_RETRIES = 3;\n\n/**\n * Fetch with retries.\n */\nexport async function fetchRetry(url: string): Promise<Response> {\n  return fetch(url);\n}\n\nfunction internal() {}\nexport { internal };\n";
+        let summary = summarize(src, Language::TypeScript);
+        assert_eq!(
+            texts(&summary),
+            vec![
+                "export const MAX_RETRIES = 3;",
+                " * Fetch with retries.",
+                "export async function fetchRetry(url: string): Promise<Response>",
+            ]
+        );
+    }
+
+    #[test]
+    fn render_marks_gaps() {
+        let summary = summarize(
+            "/// Doc.\npub fn a() {}\n\nfn b() {}\npub fn c() {}\n",
+            Language::Rust,
+        );
+        assert_eq!(summary.render(), "/// Doc.\npub fn a()\n...\npub fn c()\n");
+    }
+
+    #[test]
+    fn summary_is_capped_and_deterministic() {
+        let src: String = (0..200).map(|i| format!("pub fn f{i}() {{}}\n")).collect();
+        let a = summarize(&src, Language::Rust);
+        assert_eq!(a.lines.len(), MAX_LINES);
+        assert_eq!(a, summarize(&src, Language::Rust));
+        assert!(summarize("", Language::Rust).is_empty());
+    }
+}
@@ -24,13 +24,25 @@ struct GrammarEntry {
 
 static GRAMMARS: LazyLock<HashMap<Language, GrammarEntry>> = LazyLock::new(init_grammars);
 
+/// The TSX dialect shares `Language::TypeScript` but needs its own grammar:
+/// the plain TypeScript grammar fails to parse JSX elements.
+static TSX: LazyLock<Option<GrammarEntry>> = LazyLock::new(|| {
+    let query_src = queries::query_for(Language::TypeScript)?;
+    grammar_entry(tree_sitter_typescript::LANGUAGE_TSX.into(), query_src)
+});
+
 /// Tree-sitter based chunker. Zero-size struct — all state lives in `GRAMMARS`.
 pub struct TreeSitterChunker;
 
 impl Chunker for TreeSitterChunker {
     fn chunk(&self, content: &str, language: Language) -> Vec<Chunk> {
-        let grammars = &*GRAMMARS;
-        let entry = match grammars.get(&language) {
+        let language = crate::chunk_language(content, language);
+        let entry = if language == Language::TypeScript && looks_like_jsx(content) {
+            TSX.as_ref()
+        } else {
+            GRAMMARS.get(&language)
+        };
+        let entry = match entry {
             Some(e) => e,
             None => return vec![],
         };
@@ -137,35 +149,36 @@ fn init_grammars() -> HashMap<Language, GrammarEntry> {
             None => continue,
         };
 
-        let ts_lang = make_ts_lang();
-        let query = match Query::new(&ts_lang, query_src) {
-            Ok(q) => q,
-            Err(_) => continue,
-        };
-
-        let function_idx = capture_index(&query, "function");
-        let type_idx = capture_index(&query, "type");
-        let impl_idx = capture_index(&query, "impl");
-        let import_idx = capture_index(&query, "import");
-        let name_idx = capture_index(&query, "name");
-
-        map.insert(
-            lang,
-            GrammarEntry {
-                language: ts_lang,
-                query,
-                function_idx,
-                type_idx,
-                impl_idx,
-                import_idx,
-                name_idx,
-            },
-        );
+        if let Some(entry) = grammar_entry(make_ts_lang(), query_src) {
+            map.insert(lang, entry);
+        }
     }
 
     map
 }
 
+/// Compile a query for a grammar; `None` if the query does not compile.
+fn grammar_entry(ts_lang: tree_sitter::Language, query_src: &str) -> Option<GrammarEntry> {
+    let query = Query::new(&ts_lang, query_src).ok()?;
+    Some(GrammarEntry {
+        function_idx: capture_index(&query, "function"),
+        type_idx: capture_index(&query, "type"),
+        impl_idx: capture_index(&query, "impl"),
+        import_idx: capture_index(&query, "import"),
+        name_idx: capture_index(&query, "name"),
+        language: ts_lang,
+        query,
+    })
+}
+
+/// Whether TypeScript source contains JSX elements (`<Foo />`, `</div>`).
+///
+/// The TSX grammar rejects `<T>value` type assertions, so `.ts` files only
+/// switch to it when they contain closing or self-closing tags.
+fn looks_like_jsx(content: &str) -> bool {
+    content.contains("/>") || content.contains("</")
+}
+
 fn capture_index(query: &Query, name: &str) -> Option<u32> {
     let names = query.capture_names();
     names.iter().position(|n| *n == name).map(|i| i as u32)
@@ -333,6 +346,87 @@ import { useState } from 'react';
         assert!(chunks.iter().any(|c| c.kind == ChunkKind::Import));
     }
 
+    #[test]
+    fn jsx_queries_compile() {
+        assert!(GRAMMARS.contains_key(&Language::JavaScript));
+        assert!(GRAMMARS.contains_key(&Language::TypeScript));
+        assert!(TSX.is_some());
+    }
+
+    #[test]
+    fn tsx_components_are_types() {
+        let src = r#"
+import React, { memo } from 'react';
+
+interface Props { title: string }
+
+export const Header = ({ title }: Props) => <h1>{title}</h1>;
+
+const Row = memo(function Row() {
+    return <tr />;
+});
+
+function Sidebar(): JSX.Element {
+    return <aside />;
+}
+
+const formatTitle = (title: string) => title.trim();
+
+export default function () {
+    return <Header title="home" />;
+}
+"#;
+        let chunks = TreeSitterChunker.chunk(src, Language::TypeScript);
+        let types: Vec<&str> = chunks
+            .iter()
+            .filter(|c| c.kind == ChunkKind::Type)
+            .map(|c| c.name.as_str())
+            .collect();
+        for name in ["Props", "Header", "Row", "Sidebar", "default"] {
+            assert!(types.contains(&name), "missing component {name}: {types:?}");
+        }
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.name == "formatTitle" && c.kind == ChunkKind::Function)
+        );
+        let header = chunks.iter().find(|c| c.name == "Header").unwrap();
+        assert_eq!(header.start_line, 6);
+    }
+
+    #[test]
+    fn jsx_arrow_component_and_default_export() {
+        let src = r#"
+const Button = (props) => {
+    return <button {...props} />;
+};
+
+export default () => <Button />;
+"#;
+        let chunks = TreeSitterChunker.chunk(src, Language::JavaScript);
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.name == "Button" && c.kind == ChunkKind::Type)
+        );
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.name == "default" && c.kind == ChunkKind::Type)
+        );
+    }
+
+    #[test]
+    fn typescript_without_jsx_keeps_type_assertions() {
+        let src = "function parse(raw: unknown): Config {\n    return <Config>raw;\n}\n";
+        let chunks = TreeSitterChunker.chunk(src, Language::TypeScript);
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.name == "parse" && c.kind == ChunkKind::Function)
+        );
+    }
+
     #[test]
     fn unsupported_language_returns_empty() {
         let chunks = TreeSitterChunker.chunk("# heading\nsome text", Language::Markdown);